@@ -1,6 +1,7 @@
 //! This crate offers convenience macros for [gvdb](https://!github.com/felinira/gvdb-rs).
-//! The macros are [`include_gresource_from_xml!()`] and
-//! [`include_gresource_from_dir!()`]
+//! The macros are [`include_gresource_from_xml!()`],
+//! [`include_gresource_from_dir!()`], [`include_gresource_from_dir_compressed!()`],
+//! [`include_gvdb_from_json!()`] and [`gresource_paths_from_xml!()`]
 //!
 //! ## Examples
 //!
@@ -17,6 +18,13 @@
 //! use gvdb_macros::include_gresource_from_dir;
 //! static GRESOURCE_BYTES: &[u8] = include_gresource_from_dir!("/gvdb/rs/test", "test-data/gresource");
 //! ```
+//!
+//! Compile a JSON file to a GVDB file and include the bytes in the file.
+//!
+//! ```
+//! use gvdb_macros::include_gvdb_from_json;
+//! static GVDB_BYTES: &[u8] = include_gvdb_from_json!("test-data/test2.json");
+//! ```
 
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
@@ -24,15 +32,79 @@
 extern crate proc_macro;
 
 use litrs::{Literal, StringLit};
-use proc_macro2::TokenTree;
-use quote::quote;
-use std::path::PathBuf;
+use proc_macro2::{Span, TokenTree};
+use quote::{quote, quote_spanned};
+use std::path::{Path, PathBuf};
+
+/// An error that is reported with `compile_error!` at `span`
+struct Error {
+    span: Span,
+    message: String,
+}
 
-fn quote_bytes(bytes: &[u8]) -> proc_macro2::TokenStream {
+impl Error {
+    fn new(span: Span, message: impl ToString) -> Self {
+        Self {
+            span,
+            message: message.to_string(),
+        }
+    }
+
+    fn to_compile_error(&self) -> proc_macro2::TokenStream {
+        let message = &self.message;
+        quote_spanned! {self.span=>
+            compile_error!(#message)
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Resolve `path` relative to the directory of the manifest of the crate that uses the macro,
+/// like `include_bytes!` does relative to the source file
+fn resolve_path(path: &str) -> PathBuf {
+    let path = PathBuf::from(path);
+    match std::env::var_os("CARGO_MANIFEST_DIR") {
+        Some(manifest_dir) if path.is_relative() => PathBuf::from(manifest_dir).join(path),
+        _ => path,
+    }
+}
+
+/// All files in `directory` and its subdirectories
+fn files_in_directory(directory: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![directory.to_path_buf()];
+    while let Some(directory) = pending.pop() {
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Include `bytes` aligned to 16 bytes
+///
+/// The files in `dependencies` are included with `include_bytes!` as well, so the compiler
+/// rebuilds the crate when one of them changes.
+fn quote_bytes(bytes: &[u8], dependencies: &[PathBuf]) -> proc_macro2::TokenStream {
     let bytes_lit = proc_macro2::Literal::byte_string(bytes);
+    let dependencies = dependencies
+        .iter()
+        .map(|path| path.to_string_lossy().to_string());
 
     quote! {
         {{
+            #(
+                const _: &[u8] = include_bytes!(#dependencies);
+            )*
+
             #[repr(align(16))]
             #[doc(hidden)]
             struct __GvdbAligned<T: ?Sized>(T);
@@ -44,37 +116,71 @@ fn quote_bytes(bytes: &[u8]) -> proc_macro2::TokenStream {
     }
 }
 
-fn include_gresource_from_xml_with_filename(filename: &str) -> proc_macro2::TokenStream {
-    let path = PathBuf::from(filename);
-    let xml = gvdb::gresource::XmlManifest::from_file(&path).unwrap();
-    let builder = gvdb::gresource::BundleBuilder::from_xml(xml).unwrap();
-    let data = builder.build().unwrap();
+/// Include `bytes` compressed, to be decompressed with `gvdb::gresource::CompressedBytes` on first
+/// access
+///
+/// The files in `dependencies` are handled like in [`quote_bytes`].
+fn quote_compressed_bytes(bytes: &[u8], dependencies: &[PathBuf]) -> proc_macro2::TokenStream {
+    let compressed = gvdb::gresource::CompressedBytes::compress(bytes);
+    let bytes_lit = proc_macro2::Literal::byte_string(&compressed);
+    let dependencies = dependencies
+        .iter()
+        .map(|path| path.to_string_lossy().to_string());
 
-    quote_bytes(&data)
-}
+    quote! {
+        {{
+            #(
+                const _: &[u8] = include_bytes!(#dependencies);
+            )*
 
-fn include_gresource_from_xml_inner(input: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
-    let mut iter = input.into_iter();
+            #[doc(hidden)]
+            static __GVDB_COMPRESSED: ::gvdb::gresource::CompressedBytes =
+                ::gvdb::gresource::CompressedBytes::new(#bytes_lit);
 
-    let first = iter
-        .next()
-        .expect("Expected exactly one string literal argument (gresource file location)");
-    let second = iter.next();
-    if let Some(second) = second {
-        panic!("Unexpected token '{}', expected exactly one string literal argument (gresource file location)", second)
+            __GVDB_COMPRESSED.get()
+        }}
     }
+}
 
-    match Literal::try_from(first) {
-        Err(e) => proc_macro2::TokenStream::from(e.to_compile_error()),
-        Ok(Literal::String(str)) => {
-            include_gresource_from_xml_with_filename(str.value())
-        }
-        Ok(other) => panic!("Unexpected token '{:?}', expected exactly one string literal argument (gresource file location)", other)
-    }
+fn include_gresource_from_xml_with_filename(
+    filename: &str,
+    span: Span,
+) -> Result<proc_macro2::TokenStream> {
+    let path = resolve_path(filename);
+    let xml =
+        gvdb::gresource::XmlManifest::from_file(&path).map_err(|err| Error::new(span, err))?;
+
+    let mut dependencies = vec![path];
+    dependencies.extend(xml.file_paths());
+
+    let data = gvdb::gresource::BundleBuilder::from_xml(xml)
+        .and_then(|builder| builder.build())
+        .map_err(|err| Error::new(span, err))?;
+
+    Ok(quote_bytes(&data, &dependencies))
+}
+
+fn include_gresource_from_xml_inner(input: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let err_msg = "expected exactly one string literal argument (gresource file location)";
+    let result = match &*input.into_iter().collect::<Vec<_>>() {
+        [token] => match Literal::try_from(token) {
+            Ok(Literal::String(str)) => {
+                include_gresource_from_xml_with_filename(str.value(), token.span())
+            }
+            _ => Err(Error::new(token.span(), err_msg)),
+        },
+        [] => Err(Error::new(Span::call_site(), err_msg)),
+        [_, token, ..] => Err(Error::new(token.span(), err_msg)),
+    };
+
+    result.unwrap_or_else(|err| err.to_compile_error())
 }
 
 /// Compile a GResource XML file to its binary representation and include it in the source file.
 ///
+/// Relative paths are resolved against the directory of the `Cargo.toml` of the crate that uses
+/// the macro. The crate is rebuilt when the XML file or one of the files it references changes.
+///
 /// ```
 /// use gvdb_macros::include_gresource_from_xml;
 /// static GRESOURCE_BYTES: &[u8] = include_gresource_from_xml!("test-data/gresource/test3.gresource.xml");
@@ -86,32 +192,56 @@ pub fn include_gresource_from_xml(input: proc_macro::TokenStream) -> proc_macro:
     proc_macro::TokenStream::from(output)
 }
 
-fn include_gresource_from_dir_str(prefix: &str, directory: &str) -> proc_macro2::TokenStream {
-    let path = PathBuf::from(directory);
-    let builder =
-        gvdb::gresource::BundleBuilder::from_directory(prefix, &path, true, true).unwrap();
-    let data = builder.build().unwrap();
+fn include_gresource_from_dir_str(
+    prefix: &str,
+    directory: &str,
+    compressed: bool,
+    span: Span,
+) -> Result<proc_macro2::TokenStream> {
+    let path = resolve_path(directory);
+    let dependencies = files_in_directory(&path).map_err(|err| {
+        Error::new(
+            span,
+            format!("Error reading directory '{}': {}", path.display(), err),
+        )
+    })?;
+
+    let data = gvdb::gresource::BundleBuilder::from_directory(prefix, &path, true, true)
+        .and_then(|builder| builder.build())
+        .map_err(|err| Error::new(span, err))?;
 
-    quote_bytes(&data)
+    if compressed {
+        Ok(quote_compressed_bytes(&data, &dependencies))
+    } else {
+        Ok(quote_bytes(&data, &dependencies))
+    }
 }
 
-fn include_gresource_from_dir_inner(input: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+fn include_gresource_from_dir_inner(
+    input: proc_macro2::TokenStream,
+    compressed: bool,
+) -> proc_macro2::TokenStream {
     let err_msg = "expected exactly two string literal arguments (prefix, gresource directory)";
-    let (prefix, directory) = match &*input.into_iter().collect::<Vec<_>>() {
-        [TokenTree::Literal(str1), TokenTree::Punct(comma), TokenTree::Literal(str2)] => {
-            if comma.as_char() != ',' {
-                panic!("{}", err_msg);
+    let tokens = input.into_iter().collect::<Vec<_>>();
+    let result = match &*tokens {
+        [TokenTree::Literal(str1), TokenTree::Punct(comma), TokenTree::Literal(str2)]
+            if comma.as_char() == ',' =>
+        {
+            match (StringLit::try_from(str1), StringLit::try_from(str2)) {
+                (Ok(prefix), Ok(directory)) => include_gresource_from_dir_str(
+                    prefix.value(),
+                    directory.value(),
+                    compressed,
+                    str2.span(),
+                ),
+                _ => Err(Error::new(str1.span(), err_msg)),
             }
-
-            (
-                StringLit::try_from(str1).expect(err_msg),
-                StringLit::try_from(str2).expect(err_msg),
-            )
         }
-        _ => panic!("{}", err_msg),
+        [first, ..] => Err(Error::new(first.span(), err_msg)),
+        [] => Err(Error::new(Span::call_site(), err_msg)),
     };
 
-    include_gresource_from_dir_str(prefix.value(), directory.value())
+    result.unwrap_or_else(|err| err.to_compile_error())
 }
 
 /// Scan a directory and create a GResource file with all the contents of the directory.
@@ -130,7 +260,11 @@ fn include_gresource_from_dir_inner(input: proc_macro2::TokenStream) -> proc_mac
 /// ```
 ///
 /// The first argument to this macro is the prefix for the GResource file. The second argument is
-/// the path to the folder containing the files to include in the file.
+/// the path to the folder containing the files to include in the file. Relative paths are resolved
+/// against the directory of the `Cargo.toml` of the crate that uses the macro.
+///
+/// The crate is rebuilt when one of the files in the directory changes. Files that are added to
+/// the directory are only picked up with the next rebuild.
 ///
 /// This acts as if every xml file uses the option `xml-stripblanks` in the GResource XML and every
 /// JSON file uses `json-stripblanks`.
@@ -146,7 +280,253 @@ fn include_gresource_from_dir_inner(input: proc_macro2::TokenStream) -> proc_mac
 #[proc_macro]
 pub fn include_gresource_from_dir(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = proc_macro2::TokenStream::from(input);
-    let output = include_gresource_from_dir_inner(input);
+    let output = include_gresource_from_dir_inner(input, false);
+    proc_macro::TokenStream::from(output)
+}
+
+/// Scan a directory and create a compressed GResource file with all the contents of the directory.
+///
+/// This works like [`include_gresource_from_dir!()`], but the bytes of the GResource file are
+/// compressed with zlib before they are included, which keeps large bundles small in the binary.
+/// The macro evaluates to a `Cow<'static, [u8]>`. The data is decompressed on the first
+/// evaluation and kept in memory for the rest of the program, later evaluations borrow the same
+/// data.
+///
+/// The generated code uses `gvdb::gresource::CompressedBytes`, so the crate that uses the macro
+/// needs to depend on `gvdb` with the `gresource` feature.
+///
+/// ```
+/// use std::borrow::Cow;
+/// use gvdb_macros::include_gresource_from_dir_compressed;
+///
+/// fn gresource_bytes() -> Cow<'static, [u8]> {
+///     include_gresource_from_dir_compressed!("/gvdb/rs/tests/data", "test-data/gresource")
+/// }
+/// ```
+#[proc_macro]
+pub fn include_gresource_from_dir_compressed(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let input = proc_macro2::TokenStream::from(input);
+    let output = include_gresource_from_dir_inner(input, true);
+    proc_macro::TokenStream::from(output)
+}
+
+/// Keywords that can't be used as module names
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Convert the file or directory `name` to a valid identifier
+///
+/// All characters that aren't valid in identifiers are replaced with `_`. Names that start with a
+/// digit and keywords get an additional `_`.
+fn identifier(name: &str, uppercase: bool) -> proc_macro2::Ident {
+    let mut ident: String = name
+        .trim_end_matches('/')
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' if uppercase => c.to_ascii_uppercase(),
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' => c.to_ascii_lowercase(),
+            _ => '_',
+        })
+        .collect();
+
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if ident == "_" || KEYWORDS.contains(&ident.as_str()) {
+        ident.push('_');
+    }
+
+    proc_macro2::Ident::new(&ident, Span::call_site())
+}
+
+/// A module with one constant per file in `node` and one nested module per directory
+fn quote_path_node(
+    node: &gvdb::gresource::PathNode,
+    span: Span,
+) -> Result<proc_macro2::TokenStream> {
+    let mut names = std::collections::HashMap::new();
+    let mut items = Vec::new();
+    for child in node.children() {
+        let ident = identifier(child.name(), child.is_file());
+        if let Some(other) = names.insert((ident.to_string(), child.is_file()), child.path()) {
+            return Err(Error::new(
+                span,
+                format!(
+                    "The paths '{}' and '{}' both map to the name '{}'",
+                    other,
+                    child.path(),
+                    ident
+                ),
+            ));
+        }
+
+        let path = child.path();
+        if child.is_file() {
+            let doc = format!("`{}`", path);
+            items.push(quote! {
+                #[doc = #doc]
+                pub const #ident: &str = #path;
+            });
+        } else {
+            let doc = format!("The files in `{}`", path);
+            let content = quote_path_node(child, span)?;
+            items.push(quote! {
+                #[doc = #doc]
+                pub mod #ident {
+                    #content
+                }
+            });
+        }
+    }
+
+    Ok(quote! { #(#items)* })
+}
+
+fn gresource_paths_from_xml_with_filename(
+    filename: &str,
+    span: Span,
+) -> Result<proc_macro2::TokenStream> {
+    let path = resolve_path(filename);
+    let xml =
+        gvdb::gresource::XmlManifest::from_file(&path).map_err(|err| Error::new(span, err))?;
+    let data = gvdb::gresource::BundleBuilder::from_xml(xml)
+        .and_then(|builder| builder.build())
+        .map_err(|err| Error::new(span, err))?;
+
+    let tree = gvdb::read::File::from_bytes(std::borrow::Cow::Owned(data))
+        .map_err(gvdb::gresource::ExtractorError::from)
+        .and_then(|file| gvdb::gresource::Bundle::new(&file)?.path_tree())
+        .map_err(|err| Error::new(span, err))?;
+
+    let content = quote_path_node(tree.root(), span)?;
+    let path = path.to_string_lossy().to_string();
+    Ok(quote! {
+        const _: &[u8] = include_bytes!(#path);
+        #content
+    })
+}
+
+fn gresource_paths_from_xml_inner(input: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let err_msg = "expected exactly one string literal argument (gresource file location)";
+    let result = match &*input.into_iter().collect::<Vec<_>>() {
+        [token] => match Literal::try_from(token) {
+            Ok(Literal::String(str)) => {
+                gresource_paths_from_xml_with_filename(str.value(), token.span())
+            }
+            _ => Err(Error::new(token.span(), err_msg)),
+        },
+        [] => Err(Error::new(Span::call_site(), err_msg)),
+        [_, token, ..] => Err(Error::new(token.span(), err_msg)),
+    };
+
+    result.unwrap_or_else(|err| err.to_compile_error())
+}
+
+/// Generate constants for the paths of all files in a GResource XML file.
+///
+/// The macro expands to one module per directory and one `&str` constant per file, so paths are
+/// checked by the compiler instead of being passed as string literals. The names of modules are
+/// the lowercase directory names and the names of constants are the uppercase file names. All
+/// characters that aren't valid in identifiers are replaced with `_`, names that start with a digit
+/// or that are keywords get an additional `_`. Paths that result in the same name are reported as
+/// an error.
+///
+/// The macro is used in item position, usually inside of a dedicated module. Relative paths are
+/// resolved against the directory of the `Cargo.toml` of the crate that uses the macro. The crate
+/// is rebuilt when the XML file changes.
+///
+/// ```
+/// mod resources {
+///     gvdb_macros::gresource_paths_from_xml!("test-data/gresource/test3.gresource.xml");
+/// }
+///
+/// assert_eq!(resources::gvdb::rs::test::TEST_CSS, "/gvdb/rs/test/test.css");
+/// assert_eq!(
+///     resources::gvdb::rs::test::json::TEST_JSON,
+///     "/gvdb/rs/test/json/test.json"
+/// );
+/// ```
+#[proc_macro]
+pub fn gresource_paths_from_xml(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = proc_macro2::TokenStream::from(input);
+    let output = gresource_paths_from_xml_inner(input);
+    proc_macro::TokenStream::from(output)
+}
+
+fn include_gvdb_from_json_with_filename(
+    filename: &str,
+    span: Span,
+) -> Result<proc_macro2::TokenStream> {
+    let path = resolve_path(filename);
+    let json = std::fs::read(&path).map_err(|err| {
+        Error::new(
+            span,
+            format!("Error reading file '{}': {}", path.display(), err),
+        )
+    })?;
+    let json: serde_json::Value = serde_json::from_slice(&json).map_err(|err| {
+        Error::new(
+            span,
+            format!("Error parsing JSON file '{}': {}", path.display(), err),
+        )
+    })?;
+
+    let data = gvdb::write::HashTableBuilder::from_json(&json)
+        .and_then(|table_builder| {
+            gvdb::write::FileWriter::new().write_to_vec_with_table(table_builder)
+        })
+        .map_err(|err| Error::new(span, err))?;
+
+    Ok(quote_bytes(&data, &[path]))
+}
+
+fn include_gvdb_from_json_inner(input: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let err_msg = "expected exactly one string literal argument (JSON file location)";
+    let result = match &*input.into_iter().collect::<Vec<_>>() {
+        [token] => match Literal::try_from(token) {
+            Ok(Literal::String(str)) => {
+                include_gvdb_from_json_with_filename(str.value(), token.span())
+            }
+            _ => Err(Error::new(token.span(), err_msg)),
+        },
+        [] => Err(Error::new(Span::call_site(), err_msg)),
+        [_, token, ..] => Err(Error::new(token.span(), err_msg)),
+    };
+
+    result.unwrap_or_else(|err| err.to_compile_error())
+}
+
+/// Compile a JSON file to a GVDB file and include it in the source file.
+///
+/// The JSON file uses the format of `gvdb::read::File::to_json` and the `dump-json` command of
+/// `gvdb-tool`: Every item is an object with the GVariant `type` and the `value`. Nested hash
+/// tables have the type `H` and an object of items as value.
+///
+/// ```json
+/// {
+///   "string": { "type": "s", "value": "test string" },
+///   "table": { "type": "H", "value": { "int": { "type": "u", "value": 42 } } }
+/// }
+/// ```
+///
+/// Relative paths are resolved against the directory of the `Cargo.toml` of the crate that uses
+/// the macro. The crate is rebuilt when the JSON file changes.
+///
+/// ```
+/// use gvdb_macros::include_gvdb_from_json;
+/// static GVDB_BYTES: &[u8] = include_gvdb_from_json!("test-data/test2.json");
+/// ```
+#[proc_macro]
+pub fn include_gvdb_from_json(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = proc_macro2::TokenStream::from(input);
+    let output = include_gvdb_from_json_inner(input);
     proc_macro::TokenStream::from(output)
 }
 
@@ -155,65 +535,184 @@ mod tests {
     use super::*;
     use quote::quote;
 
+    fn is_compile_error(tokens: proc_macro2::TokenStream) -> bool {
+        tokens.to_string().starts_with("compile_error")
+    }
+
     #[test]
     fn include_gresource_from_xml() {
         let tokens =
             include_gresource_from_xml_inner(quote! {"test-data/gresource/test3.gresource.xml"});
-        assert!(tokens.to_string().contains(r#"b"GVariant"#));
+        let tokens = tokens.to_string();
+        assert!(tokens.contains(r#"b"GVariant"#));
+        assert!(tokens.contains("include_bytes"));
+        assert!(tokens.contains("test3.gresource.xml"));
+        assert!(tokens.contains("test.css"));
     }
 
     #[test]
-    #[should_panic]
-    fn include_gresource_from_xml_panic() {
-        include_gresource_from_xml_inner(quote! {4});
+    fn include_gresource_from_xml_error() {
+        assert!(is_compile_error(include_gresource_from_xml_inner(
+            quote! {4}
+        )));
     }
 
     #[test]
-    #[should_panic]
-    fn include_gresource_from_xml_panic2() {
-        include_gresource_from_xml_inner(quote! { "test", 4 });
+    fn include_gresource_from_xml_error2() {
+        assert!(is_compile_error(include_gresource_from_xml_inner(
+            quote! { "test", 4 }
+        )));
     }
 
     #[test]
-    #[should_panic]
-    fn include_gresource_from_xml_panic3() {
-        include_gresource_from_xml_inner(quote! { test });
+    fn include_gresource_from_xml_error3() {
+        assert!(is_compile_error(include_gresource_from_xml_inner(
+            quote! { test }
+        )));
+    }
+
+    #[test]
+    fn include_gresource_from_xml_error4() {
+        let tokens = include_gresource_from_xml_inner(quote! { "INVALID_FILE.xml" });
+        assert!(tokens.to_string().contains("INVALID_FILE.xml"));
+        assert!(is_compile_error(tokens));
+        assert!(is_compile_error(include_gresource_from_xml_inner(
+            quote! {}
+        )));
     }
 
     #[test]
     fn include_gresource_from_dir() {
+        let tokens = include_gresource_from_dir_inner(
+            quote! {"/gvdb/rs/test", "test-data/gresource"},
+            false,
+        );
+        let tokens = tokens.to_string();
+        assert!(tokens.contains(r#"b"GVariant"#));
+        assert!(tokens.contains("send-symbolic.svg"));
+    }
+
+    #[test]
+    fn include_gresource_from_dir_compressed() {
+        let tokens =
+            include_gresource_from_dir_inner(quote! {"/gvdb/rs/test", "test-data/gresource"}, true);
+        let tokens = tokens.to_string();
+        assert!(!tokens.contains(r#"b"GVariant"#));
+        assert!(tokens.contains("CompressedBytes"));
+        assert!(tokens.contains("send-symbolic.svg"));
+        assert!(is_compile_error(include_gresource_from_dir_inner(
+            quote! {"/gvdb/rs/test", "INVALID_DIRECTORY"},
+            true
+        )));
+    }
+
+    #[test]
+    fn include_gvdb_from_json() {
+        let tokens = include_gvdb_from_json_inner(quote! {"test-data/test2.json"});
+        let tokens = tokens.to_string();
+        assert!(tokens.contains(r#"b"GVariant"#));
+        assert!(tokens.contains("test2.json"));
+    }
+
+    #[test]
+    fn include_gvdb_from_json_error() {
+        assert!(is_compile_error(include_gvdb_from_json_inner(quote! {})));
+        assert!(is_compile_error(include_gvdb_from_json_inner(
+            quote! { "a", "b" }
+        )));
+        assert!(is_compile_error(include_gvdb_from_json_inner(
+            quote! { "INVALID_FILE.json" }
+        )));
+        assert!(is_compile_error(include_gvdb_from_json_inner(
+            quote! { "test-data/test2.gvdb" }
+        )));
+        assert!(is_compile_error(include_gvdb_from_json_inner(
+            quote! { "test-data/gresource/json/test.json" }
+        )));
+    }
+
+    #[test]
+    fn gresource_paths_from_xml() {
         let tokens =
-            include_gresource_from_dir_inner(quote! {"/gvdb/rs/test", "test-data/gresource"});
-        assert!(tokens.to_string().contains(r#"b"GVariant"#));
+            gresource_paths_from_xml_inner(quote! {"test-data/gresource/test3.gresource.xml"});
+        let tokens = tokens.to_string();
+        assert!(tokens.contains("include_bytes"));
+        assert!(tokens.contains("pub mod json"));
+        assert!(tokens.contains(r#"pub const TEST_CSS : & str = "/gvdb/rs/test/test.css""#));
+        assert!(tokens.contains("ONLINE_SYMBOLIC_SVG"));
+
+        assert!(is_compile_error(gresource_paths_from_xml_inner(quote! {})));
+        assert!(is_compile_error(gresource_paths_from_xml_inner(
+            quote! { "INVALID_FILE.xml" }
+        )));
+    }
+
+    #[test]
+    fn identifier() {
+        assert_eq!(super::identifier("test.css", true), "TEST_CSS");
+        assert_eq!(super::identifier("icons/", false), "icons");
+        assert_eq!(super::identifier("Dark-Mode/", false), "dark_mode");
+        assert_eq!(super::identifier("1.svg", true), "_1_SVG");
+        assert_eq!(super::identifier("type/", false), "type_");
+        assert_eq!(super::identifier("self/", false), "self_");
+        assert_eq!(super::identifier("_/", false), "__");
+    }
+
+    #[test]
+    fn quote_path_node() {
+        let tree = gvdb::gresource::PathTree::from_keys(["/app/a-b.css", "/app/a_b.css"]);
+        let err = super::quote_path_node(tree.root(), Span::call_site()).unwrap_err();
+        assert!(err.message.contains("A_B_CSS"));
+
+        let tree = gvdb::gresource::PathTree::from_keys(["/app/json", "/app/json/"]);
+        assert!(super::quote_path_node(tree.root(), Span::call_site()).is_ok());
+    }
+
+    #[test]
+    fn resolve_path() {
+        let path = super::resolve_path("test-data/gresource");
+        assert!(path.is_absolute());
+        assert!(path.is_dir());
+        assert_eq!(super::resolve_path("/absolute"), PathBuf::from("/absolute"));
     }
 
     #[test]
-    #[should_panic]
-    fn include_gresource_from_dir_panic1() {
-        include_gresource_from_dir_inner(quote! {"/gvdb/rs/test",});
+    fn include_gresource_from_dir_error1() {
+        assert!(is_compile_error(include_gresource_from_dir_inner(
+            quote! {"/gvdb/rs/test",},
+            false
+        )));
     }
 
     #[test]
-    #[should_panic]
-    fn include_gresource_from_dir_panic2() {
-        include_gresource_from_dir_inner(quote! {"/gvdb/rs/test"});
+    fn include_gresource_from_dir_error2() {
+        assert!(is_compile_error(include_gresource_from_dir_inner(
+            quote! {"/gvdb/rs/test"},
+            false
+        )));
     }
 
     #[test]
-    #[should_panic]
-    fn include_gresource_from_dir_panic3() {
-        include_gresource_from_dir_inner(quote! {"/gvdb/rs/test","bla","bla"});
+    fn include_gresource_from_dir_error3() {
+        assert!(is_compile_error(include_gresource_from_dir_inner(
+            quote! {"/gvdb/rs/test","bla","bla"},
+            false
+        )));
     }
 
     #[test]
-    #[should_panic]
-    fn include_gresource_from_dir_panic4() {
-        include_gresource_from_dir_inner(quote! {"/gvdb/rs/test","INVALID_DIRECTORY"});
+    fn include_gresource_from_dir_error4() {
+        assert!(is_compile_error(include_gresource_from_dir_inner(
+            quote! {"/gvdb/rs/test","INVALID_DIRECTORY"},
+            false
+        )));
     }
 
     #[test]
-    #[should_panic]
-    fn include_gresource_from_dir_panic5() {
-        include_gresource_from_dir_inner(quote! {"/gvdb/rs/test"."test-data/gresource"});
+    fn include_gresource_from_dir_error5() {
+        assert!(is_compile_error(include_gresource_from_dir_inner(
+            quote! {"/gvdb/rs/test"."test-data/gresource"},
+            false
+        )));
     }
 }
@@ -1,4 +1,7 @@
-use gvdb_macros::{include_gresource_from_dir, include_gresource_from_xml};
+use gvdb_macros::{
+    include_gresource_from_dir, include_gresource_from_dir_compressed, include_gresource_from_xml,
+    include_gvdb_from_json,
+};
 
 #[test]
 fn macros() {
@@ -6,6 +9,22 @@ fn macros() {
     let _data2 = include_gresource_from_xml!("test-data/gresource/test3.gresource.xml");
 }
 
+#[test]
+fn json() {
+    let data = include_gvdb_from_json!("test-data/test2.json");
+    let file = gvdb::read::File::from_bytes(std::borrow::Cow::Borrowed(data)).unwrap();
+    let table = file.hash_table().unwrap();
+    assert_eq!(table.get::<String>("string").unwrap(), "test string");
+    assert_eq!(
+        table
+            .get_hash_table("table")
+            .unwrap()
+            .get::<u32>("int")
+            .unwrap(),
+        42
+    );
+}
+
 #[test]
 fn align() {
     for _ in 0..100 {
@@ -15,3 +34,35 @@ fn align() {
         assert_eq!(0, ptr_addr % 16);
     }
 }
+
+#[test]
+fn compressed() {
+    let data = include_gresource_from_dir!("/test", "test-data/gresource");
+    let compressed = || include_gresource_from_dir_compressed!("/test", "test-data/gresource");
+    assert_eq!(&*compressed(), data);
+    assert_eq!(compressed().as_ptr(), compressed().as_ptr());
+
+    let file = gvdb::read::File::from_bytes(compressed()).unwrap();
+    let bundle = gvdb::gresource::Bundle::new(&file).unwrap();
+    assert!(bundle.lookup("/test/json/test.json").is_ok());
+}
+
+mod resources {
+    gvdb_macros::gresource_paths_from_xml!("test-data/gresource/test3.gresource.xml");
+}
+
+#[test]
+fn paths() {
+    let data = include_gresource_from_xml!("test-data/gresource/test3.gresource.xml");
+    let file = gvdb::read::File::from_bytes(std::borrow::Cow::Borrowed(data)).unwrap();
+    let bundle = gvdb::gresource::Bundle::new(&file).unwrap();
+
+    for path in [
+        resources::gvdb::rs::test::TEST_CSS,
+        resources::gvdb::rs::test::ONLINE_SYMBOLIC_SVG,
+        resources::gvdb::rs::test::icons::scalable::actions::SEND_SYMBOLIC_SVG,
+        resources::gvdb::rs::test::json::TEST_JSON,
+    ] {
+        assert!(bundle.lookup(path).is_ok());
+    }
+}
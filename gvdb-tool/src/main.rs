@@ -0,0 +1,283 @@
+//! Command line tool to inspect and create gvdb and GResource files
+
+use clap::{Parser, Subcommand};
+use gvdb::gresource::{BundleBuilder, BundleExtractor, XmlManifest};
+use gvdb::read::{Entry, File, HashTable};
+use gvdb::write::{FileWriter, HashTableBuilder};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List all entries of a file, including nested hash tables
+    List {
+        /// The gvdb file
+        file: PathBuf,
+    },
+
+    /// Print a single value in GVariant text format
+    Get {
+        /// The gvdb file
+        file: PathBuf,
+
+        /// The key of the value
+        key: String,
+
+        /// Look the key up in a nested hash table. Can be given multiple times.
+        #[arg(short, long)]
+        table: Vec<String>,
+
+        /// Write the raw contents of a string or byte array value to stdout
+        #[arg(short, long)]
+        raw: bool,
+    },
+
+    /// Check a file for structural problems and print all of them
+    Validate {
+        /// The gvdb file
+        file: PathBuf,
+    },
+
+    /// Print size and layout statistics of a file
+    Stats {
+        /// The gvdb file
+        file: PathBuf,
+    },
+
+    /// Print the contents of a file as JSON
+    DumpJson {
+        /// The gvdb file
+        file: PathBuf,
+    },
+
+    /// Create a gvdb file from JSON as printed by `dump-json`
+    CreateFromJson {
+        /// The JSON input file
+        json: PathBuf,
+
+        /// The gvdb output file
+        output: PathBuf,
+
+        /// Write the file in big endian byte order
+        #[arg(long)]
+        big_endian: bool,
+    },
+
+    /// Work with GResource bundles
+    #[command(subcommand)]
+    Gresource(GresourceCommand),
+}
+
+#[derive(Debug, Subcommand)]
+enum GresourceCommand {
+    /// Compile a GResource XML manifest to a GResource bundle
+    Compile {
+        /// The GResource XML manifest
+        manifest: PathBuf,
+
+        /// The GResource output file
+        output: PathBuf,
+    },
+
+    /// Extract all files of a GResource bundle into a directory, together with a manifest
+    Extract {
+        /// The GResource bundle
+        file: PathBuf,
+
+        /// The output directory
+        directory: PathBuf,
+    },
+}
+
+fn lookup_table<'a, 'file>(
+    table: HashTable<'a, 'file>,
+    path: &[String],
+) -> Result<HashTable<'a, 'file>> {
+    let mut table = table;
+    for key in path {
+        table = table.get_hash_table(key)?;
+    }
+
+    Ok(table)
+}
+
+fn list(file: PathBuf) -> Result<()> {
+    let file = File::from_file(&file)?;
+    let table = file.hash_table()?;
+    let mut stdout = std::io::stdout().lock();
+    for entry in table.walk() {
+        let (path, entry) = entry?;
+        let indent = "  ".repeat(path.len() - 1);
+        let key = path.last().map(String::as_str).unwrap_or_default();
+        match entry {
+            Entry::Value(value) => {
+                writeln!(stdout, "{}{}\t{}", indent, key, value.value_signature())?
+            }
+            Entry::Table(_) => writeln!(stdout, "{}{}\t(hash table)", indent, key)?,
+            Entry::Container => writeln!(stdout, "{}{}", indent, key)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn get(file: PathBuf, key: String, table: Vec<String>, raw: bool) -> Result<()> {
+    let file = File::from_file(&file)?;
+    let table = lookup_table(file.hash_table()?, &table)?;
+    let mut stdout = std::io::stdout().lock();
+
+    if raw {
+        let value = table.get_value(&key)?;
+        match value.value_signature().as_str() {
+            "s" | "o" | "g" => stdout.write_all(table.get_str(&key)?.as_bytes())?,
+            "ay" => stdout.write_all(table.get_byte_slice(&key)?)?,
+            signature => {
+                return Err(format!(
+                    "Value '{}' of type '{}' has no raw representation",
+                    key, signature
+                )
+                .into())
+            }
+        }
+    } else {
+        writeln!(stdout, "{}", table.get_value(&key)?)?;
+    }
+
+    Ok(())
+}
+
+fn stats(file: PathBuf) -> Result<()> {
+    let file = File::from_file(&file)?;
+    println!("{}", file.stats()?);
+    Ok(())
+}
+
+fn validate(file: PathBuf) -> Result<()> {
+    let file = File::from_file(&file)?;
+    let issues = file.validate();
+    let mut stdout = std::io::stdout().lock();
+    for issue in &issues {
+        writeln!(stdout, "{}", issue)?;
+    }
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Found {} issues", issues.len()).into())
+    }
+}
+
+fn dump_json(file: PathBuf) -> Result<()> {
+    let file = File::from_file(&file)?;
+    let json = file.to_json()?;
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, &json)?;
+    writeln!(stdout)?;
+    Ok(())
+}
+
+fn create_from_json(input: PathBuf, output: PathBuf, big_endian: bool) -> Result<()> {
+    let data = std::fs::read(&input)?;
+    let json = serde_json::from_slice(&data)?;
+    let table = HashTableBuilder::from_json(&json)?;
+
+    let writer = if big_endian {
+        FileWriter::for_big_endian()
+    } else {
+        FileWriter::new()
+    };
+
+    let data = writer.write_to_vec_with_table(table)?;
+    std::fs::write(output, data)?;
+    Ok(())
+}
+
+fn gresource(command: GresourceCommand) -> Result<()> {
+    match command {
+        GresourceCommand::Compile { manifest, output } => {
+            let manifest = XmlManifest::from_file(&manifest)?;
+            let data = BundleBuilder::from_xml(manifest)?.build()?;
+            std::fs::write(output, data)?;
+        }
+        GresourceCommand::Extract { file, directory } => {
+            let file = File::from_file(&file)?;
+            BundleExtractor::new(&file)?.extract_to_directory(&directory)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn run(cli: Cli) -> Result<()> {
+    match cli.command {
+        Command::List { file } => list(file),
+        Command::Get {
+            file,
+            key,
+            table,
+            raw,
+        } => get(file, key, table, raw),
+        Command::Validate { file } => validate(file),
+        Command::Stats { file } => stats(file),
+        Command::DumpJson { file } => dump_json(file),
+        Command::CreateFromJson {
+            json,
+            output,
+            big_endian,
+        } => create_from_json(json, output, big_endian),
+        Command::Gresource(command) => gresource(command),
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        // The output was closed early, e.g. when piping into `head`
+        Err(err)
+            if err
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|err| err.kind() == std::io::ErrorKind::BrokenPipe) =>
+        {
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("gvdb-tool: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli() {
+        Cli::command().debug_assert();
+
+        let cli = Cli::parse_from(["gvdb-tool", "get", "file.gvdb", "int", "-t", "table"]);
+        assert!(matches!(
+            cli.command,
+            Command::Get { key, table, raw: false, .. } if key == "int" && table == ["table"]
+        ));
+    }
+
+    #[test]
+    fn lookup() {
+        let file = File::from_file(&PathBuf::from("../test-data/test2.gvdb")).unwrap();
+        let table = lookup_table(file.hash_table().unwrap(), &["table".to_string()]).unwrap();
+        assert_eq!(table.get::<u32>("int").unwrap(), 42);
+        assert!(lookup_table(file.hash_table().unwrap(), &["string".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,33 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use gvdb::gresource::{BundleBuilder, Compression, XmlManifest};
+use std::path::PathBuf;
+
+fn manifest() -> XmlManifest {
+    let path =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test-data/gresource/test3.gresource.xml");
+    XmlManifest::from_file(&path).unwrap()
+}
+
+fn compile(c: &mut Criterion) {
+    let mut group = c.benchmark_group("gresource");
+    group.bench_function("compile", |b| {
+        b.iter(|| {
+            BundleBuilder::from_xml(manifest())
+                .unwrap()
+                .build()
+                .unwrap()
+        })
+    });
+
+    group.bench_function("compile_uncompressed", |b| {
+        b.iter(|| {
+            let mut builder = BundleBuilder::from_xml(manifest()).unwrap();
+            builder.set_compression(Compression::none());
+            builder.build().unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, compile);
+criterion_main!(benches);
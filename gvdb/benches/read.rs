@@ -0,0 +1,86 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use gvdb::read::File;
+use gvdb::write::{FileWriter, HashTableBuilder};
+use std::borrow::Cow;
+
+const SIZES: [usize; 4] = [10, 100, 1_000, 10_000];
+
+fn keys(n: usize) -> Vec<String> {
+    (0..n).map(|i| format!("/org/gtk/test/key{}", i)).collect()
+}
+
+fn file_data(n: usize) -> Vec<u8> {
+    let mut table_builder = HashTableBuilder::new();
+    for (i, key) in keys(n).iter().enumerate() {
+        table_builder.insert(key, i as u32).unwrap();
+    }
+
+    FileWriter::new()
+        .write_to_vec_with_table(table_builder)
+        .unwrap()
+}
+
+fn lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookup");
+    for n in SIZES {
+        let file = File::from_bytes(Cow::Owned(file_data(n))).unwrap();
+        let table = file.hash_table().unwrap();
+        let keys = keys(n);
+        let hashes: Vec<u32> = keys.iter().map(|key| gvdb::djb_hash(key)).collect();
+
+        group.bench_with_input(BenchmarkId::new("get", n), &keys, |b, keys| {
+            b.iter(|| {
+                for key in keys {
+                    black_box(table.get::<u32>(key).unwrap());
+                }
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("get_with_hash", n), &keys, |b, keys| {
+            b.iter(|| {
+                for (key, hash) in keys.iter().zip(&hashes) {
+                    black_box(table.get_with_hash::<u32>(key, *hash).unwrap());
+                }
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("missing", n), &keys, |b, keys| {
+            b.iter(|| {
+                for key in keys {
+                    black_box(table.get::<u32>(&key[1..]).unwrap_err());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn iterate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iterate");
+    for n in SIZES {
+        let file = File::from_bytes(Cow::Owned(file_data(n))).unwrap();
+        let table = file.hash_table().unwrap();
+
+        group.bench_function(BenchmarkId::new("iter", n), |b| {
+            b.iter(|| table.iter().count())
+        });
+
+        group.bench_function(BenchmarkId::new("keys", n), |b| {
+            b.iter(|| table.keys().unwrap().len())
+        });
+    }
+    group.finish();
+}
+
+fn open(c: &mut Criterion) {
+    let data = file_data(1_000);
+    c.bench_function("open", |b| {
+        b.iter(|| {
+            let file = File::from_bytes(Cow::Borrowed(&data)).unwrap();
+            black_box(file.hash_table().unwrap());
+        })
+    });
+}
+
+criterion_group!(benches, lookup, iterate, open);
+criterion_main!(benches);
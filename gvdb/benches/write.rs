@@ -0,0 +1,43 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use gvdb::write::{FileWriter, HashTableBuilder};
+
+fn table_builder(n: usize, value_len: usize) -> HashTableBuilder<'static> {
+    let mut table_builder = HashTableBuilder::new();
+    for i in 0..n {
+        table_builder
+            .insert(&format!("/org/gtk/test/key{}", i), vec![i as u8; value_len])
+            .unwrap();
+    }
+
+    table_builder
+}
+
+fn write(c: &mut Criterion) {
+    let mut group = c.benchmark_group("write");
+    for (n, value_len) in [(100, 16), (1_000, 16), (10_000, 16), (100, 16_384)] {
+        let size = FileWriter::new()
+            .write_to_vec_with_table(table_builder(n, value_len))
+            .unwrap()
+            .len();
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_function(
+            BenchmarkId::new("write_to_vec", format!("{}x{}", n, value_len)),
+            |b| {
+                b.iter_batched(
+                    || table_builder(n, value_len),
+                    |table_builder| {
+                        FileWriter::new()
+                            .write_to_vec_with_table(table_builder)
+                            .unwrap()
+                    },
+                    criterion::BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, write);
+criterion_main!(benches);
@@ -0,0 +1,54 @@
+mod error;
+mod reader;
+mod writer;
+
+pub use error::{DconfError, DconfResult};
+pub use reader::DconfReader;
+pub use writer::DconfWriter;
+
+/// The key of the nested hash table that contains the locked keys and directories
+const LOCKS_TABLE: &str = ".locks";
+
+/// Checks that `path` starts with a `/` and doesn't contain empty path segments
+fn check_path(path: &str) -> bool {
+    path.starts_with('/') && !path.contains("//")
+}
+
+/// Checks that `key` is a valid dconf key, e.g. `/org/gnome/key`
+fn check_key(key: &str) -> DconfResult<()> {
+    if check_path(key) && !key.ends_with('/') {
+        Ok(())
+    } else {
+        Err(DconfError::InvalidPath(key.to_string()))
+    }
+}
+
+/// Checks that `dir` is a valid dconf directory, e.g. `/org/gnome/`
+fn check_dir(dir: &str) -> DconfResult<()> {
+    if check_path(dir) && dir.ends_with('/') {
+        Ok(())
+    } else {
+        Err(DconfError::InvalidPath(dir.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_dir, check_key};
+
+    #[test]
+    fn paths() {
+        assert!(check_key("/org/gnome/key").is_ok());
+        assert!(check_key("/key").is_ok());
+        assert!(check_key("/org/").is_err());
+        assert!(check_key("org/key").is_err());
+        assert!(check_key("/org//key").is_err());
+        assert!(check_key("").is_err());
+
+        assert!(check_dir("/").is_ok());
+        assert!(check_dir("/org/gnome/").is_ok());
+        assert!(check_dir("/org").is_err());
+        assert!(check_dir("org/").is_err());
+        assert!(check_dir("//").is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::path::PathBuf;
+
+/// Error type for reading and writing dconf databases
+#[non_exhaustive]
+pub enum DconfError {
+    /// An error occurred while reading the GVDB file
+    Gvdb(crate::read::Error),
+
+    /// An error occurred while writing the GVDB file
+    Writer(crate::write::Error),
+
+    /// I/O error
+    Io(std::io::Error, Option<PathBuf>),
+
+    /// The path is not a valid dconf key or directory
+    InvalidPath(String),
+
+    /// The keyfile input could not be parsed. Contains the line number and a description.
+    Keyfile(usize, String),
+}
+
+impl DconfError {
+    pub(crate) fn from_io_with_filename<P>(
+        filename: Option<P>,
+    ) -> impl FnOnce(std::io::Error) -> DconfError
+    where
+        P: Into<PathBuf>,
+    {
+        let path = filename.map(|p| p.into());
+        move |err| DconfError::Io(err, path)
+    }
+}
+
+impl std::error::Error for DconfError {}
+
+impl From<crate::read::Error> for DconfError {
+    fn from(err: crate::read::Error) -> Self {
+        Self::Gvdb(err)
+    }
+}
+
+impl From<crate::write::Error> for DconfError {
+    fn from(err: crate::write::Error) -> Self {
+        Self::Writer(err)
+    }
+}
+
+impl Display for DconfError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DconfError::Gvdb(err) => {
+                write!(f, "Error while reading GVDB file: {}", err)
+            }
+            DconfError::Writer(err) => {
+                write!(f, "Error while writing GVDB file: {}", err)
+            }
+            DconfError::Io(err, path) => {
+                if let Some(path) = path {
+                    write!(f, "I/O error for file '{}': {}", path.display(), err)
+                } else {
+                    write!(f, "I/O error: {}", err)
+                }
+            }
+            DconfError::InvalidPath(path) => {
+                write!(f, "Invalid dconf path: '{}'", path)
+            }
+            DconfError::Keyfile(line, msg) => {
+                write!(f, "Error parsing keyfile at line {}: {}", line, msg)
+            }
+        }
+    }
+}
+
+impl Debug for DconfError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+/// Result type for [`DconfError`]
+pub type DconfResult<T> = std::result::Result<T, DconfError>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from() {
+        let io_res = std::fs::File::open("test/invalid_file_name");
+        let err = DconfError::from_io_with_filename(Some("test"))(io_res.unwrap_err());
+        assert!(format!("{}", err).contains("test"));
+
+        let err = DconfError::from(crate::read::Error::KeyNotFound("/org/key".to_string()));
+        assert!(format!("{}", err).contains("/org/key"));
+
+        let err = DconfError::from(crate::write::Error::Consistency("test".to_string()));
+        assert!(format!("{}", err).contains("test"));
+
+        let err = DconfError::InvalidPath("org/key".to_string());
+        assert!(format!("{:?}", err).contains("org/key"));
+
+        let err = DconfError::Keyfile(3, "Missing '='".to_string());
+        assert_eq!(
+            format!("{}", err),
+            "Error parsing keyfile at line 3: Missing '='"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use crate::dconf::error::{DconfError, DconfResult};
+use crate::dconf::{check_dir, check_key, LOCKS_TABLE};
+use crate::read::{File, HashTable};
+use std::collections::BTreeSet;
+
+/// Read settings from a compiled dconf database
+///
+/// dconf databases are GVDB files that store every key with its full path, e.g.
+/// `/org/gnome/desktop/interface/clock-format`. Keys that are locked by the system
+/// administrator are listed in the nested `.locks` hash table.
+///
+/// ```
+/// use gvdb::dconf::{DconfReader, DconfWriter};
+/// use gvdb::read::File;
+/// use std::borrow::Cow;
+///
+/// let mut writer = DconfWriter::new();
+/// writer.load_keyfile("[org/gnome/desktop/interface]\nclock-format='24h'\n").unwrap();
+/// let data = writer.write_to_vec().unwrap();
+///
+/// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+/// let reader = DconfReader::new(&file).unwrap();
+/// let value = reader.read_key("/org/gnome/desktop/interface/clock-format").unwrap();
+/// assert_eq!(value, zvariant::Value::from("24h"));
+/// assert_eq!(reader.list_dir("/org/gnome/").unwrap(), ["desktop/"]);
+/// ```
+#[derive(Debug)]
+pub struct DconfReader<'a, 'file> {
+    table: HashTable<'a, 'file>,
+}
+
+impl<'a, 'file> DconfReader<'a, 'file> {
+    /// Create a new reader for the dconf database `file`
+    pub fn new(file: &'a File<'file>) -> DconfResult<Self> {
+        Ok(Self {
+            table: file.hash_table()?,
+        })
+    }
+
+    /// Read the value of the key at `key`, e.g. `/org/gnome/desktop/interface/clock-format`
+    pub fn read_key(&self, key: &str) -> DconfResult<zvariant::Value<'_>> {
+        check_key(key)?;
+        Ok(self.table.get_value(key)?)
+    }
+
+    /// Whether the database contains a value for `key`
+    pub fn has_key(&self, key: &str) -> DconfResult<bool> {
+        match self.read_key(key) {
+            Ok(_) => Ok(true),
+            Err(DconfError::Gvdb(err)) if err.is_key_not_found() => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// List the contents of the directory `dir`, e.g. `/org/gnome/`
+    ///
+    /// Keys are returned by their name, subdirectories by their name with a trailing `/`.
+    /// The result is sorted.
+    pub fn list_dir(&self, dir: &str) -> DconfResult<Vec<String>> {
+        check_dir(dir)?;
+
+        let mut names = BTreeSet::new();
+        for key in self.table.keys()? {
+            if let Some(rest) = key.strip_prefix(dir) {
+                let name = match rest.find('/') {
+                    Some(index) => &rest[..=index],
+                    None => rest,
+                };
+
+                if !name.is_empty() {
+                    names.insert(name.to_string());
+                }
+            }
+        }
+
+        Ok(names.into_iter().collect())
+    }
+
+    /// All keys of the database that have a value, sorted
+    pub fn keys(&self) -> DconfResult<Vec<String>> {
+        let mut keys: Vec<String> = self
+            .table
+            .iter()
+            .map(|res| res.map(|(key, _)| key))
+            .collect::<Result<_, _>>()?;
+        keys.retain(|key| check_key(key).is_ok());
+        keys.sort();
+        Ok(keys)
+    }
+
+    /// All locked keys and directories, sorted
+    pub fn locks(&self) -> DconfResult<Vec<String>> {
+        match self.table.get_hash_table(LOCKS_TABLE) {
+            Ok(locks) => {
+                let mut keys = locks.keys()?;
+                keys.sort();
+                Ok(keys)
+            }
+            Err(err) if err.is_key_not_found() => Ok(Vec::new()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Whether `key` is locked, either directly or by locking one of its parent directories
+    pub fn is_locked(&self, key: &str) -> DconfResult<bool> {
+        check_key(key)?;
+        Ok(self
+            .locks()?
+            .iter()
+            .any(|lock| lock == key || (lock.ends_with('/') && key.starts_with(lock.as_str()))))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dconf::{DconfError, DconfReader, DconfWriter};
+    use crate::read::File;
+    use crate::write::{FileWriter, HashTableBuilder};
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+
+    fn database() -> File<'static> {
+        let mut writer = DconfWriter::new();
+        writer
+            .load_keyfile(
+                "[org/gnome/desktop/interface]\n\
+                 clock-format='24h'\n\
+                 text-scaling-factor=1.25\n\
+                 [org/gnome/shell]\n\
+                 favorite-apps=['org.gnome.Nautilus.desktop']\n",
+            )
+            .unwrap();
+        writer.lock("/org/gnome/shell/").unwrap();
+        writer
+            .lock("/org/gnome/desktop/interface/clock-format")
+            .unwrap();
+
+        File::from_bytes(Cow::Owned(writer.write_to_vec().unwrap())).unwrap()
+    }
+
+    #[test]
+    fn read() {
+        let file = database();
+        let reader = DconfReader::new(&file).unwrap();
+        assert_eq!(
+            reader
+                .read_key("/org/gnome/desktop/interface/text-scaling-factor")
+                .unwrap(),
+            zvariant::Value::F64(1.25)
+        );
+        assert!(reader.has_key("/org/gnome/shell/favorite-apps").unwrap());
+        assert!(!reader.has_key("/org/gnome/shell/missing").unwrap());
+        assert_matches!(
+            reader.read_key("/org/gnome/shell/"),
+            Err(DconfError::InvalidPath(_))
+        );
+
+        assert_eq!(
+            reader.keys().unwrap(),
+            [
+                "/org/gnome/desktop/interface/clock-format",
+                "/org/gnome/desktop/interface/text-scaling-factor",
+                "/org/gnome/shell/favorite-apps"
+            ]
+        );
+    }
+
+    #[test]
+    fn list_dir() {
+        let file = database();
+        let reader = DconfReader::new(&file).unwrap();
+        assert_eq!(reader.list_dir("/").unwrap(), ["org/"]);
+        assert_eq!(
+            reader.list_dir("/org/gnome/").unwrap(),
+            ["desktop/", "shell/"]
+        );
+        assert_eq!(
+            reader.list_dir("/org/gnome/desktop/interface/").unwrap(),
+            ["clock-format", "text-scaling-factor"]
+        );
+        assert!(reader.list_dir("/com/").unwrap().is_empty());
+        assert_matches!(reader.list_dir("/org"), Err(DconfError::InvalidPath(_)));
+    }
+
+    #[test]
+    fn locks() {
+        let file = database();
+        let reader = DconfReader::new(&file).unwrap();
+        assert_eq!(
+            reader.locks().unwrap(),
+            [
+                "/org/gnome/desktop/interface/clock-format",
+                "/org/gnome/shell/"
+            ]
+        );
+        assert!(reader
+            .is_locked("/org/gnome/desktop/interface/clock-format")
+            .unwrap());
+        assert!(reader.is_locked("/org/gnome/shell/favorite-apps").unwrap());
+        assert!(!reader
+            .is_locked("/org/gnome/desktop/interface/text-scaling-factor")
+            .unwrap());
+
+        // Databases without locks
+        let mut table = HashTableBuilder::new();
+        table.insert("/org/key", 1u32).unwrap();
+        let data = FileWriter::new().write_to_vec_with_table(table).unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let reader = DconfReader::new(&file).unwrap();
+        assert!(reader.locks().unwrap().is_empty());
+        assert!(!reader.is_locked("/org/key").unwrap());
+    }
+}
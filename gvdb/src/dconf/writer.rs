@@ -0,0 +1,353 @@
+use crate::dconf::error::{DconfError, DconfResult};
+use crate::dconf::{check_dir, check_key, LOCKS_TABLE};
+use crate::write::{FileWriter, HashTableBuilder};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Create a dconf database
+///
+/// Values can be added individually with [`write_key`](DconfWriter::write_key), or from the
+/// keyfile format that is used for the files in `/etc/dconf/db/*.d/`:
+///
+/// ```text
+/// # Comments start with '#' or ';'
+/// [org/gnome/desktop/interface]
+/// clock-format='24h'
+/// text-scaling-factor=1.25
+/// ```
+///
+/// The values are written in the GVariant text format, see [`crate::variant::parse`].
+///
+/// ```
+/// use gvdb::dconf::DconfWriter;
+///
+/// let mut writer = DconfWriter::new();
+/// writer.load_keyfile("[org/gnome/desktop/interface]\nclock-format='24h'\n").unwrap();
+/// writer.write_key("/org/gnome/desktop/interface/text-scaling-factor", 1.25.into()).unwrap();
+/// writer.lock("/org/gnome/desktop/interface/clock-format").unwrap();
+/// let data = writer.write_to_vec().unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct DconfWriter {
+    values: BTreeMap<String, zvariant::Value<'static>>,
+    locks: BTreeSet<String>,
+}
+
+impl DconfWriter {
+    /// Create a new empty dconf database
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compile a directory in the format of `/etc/dconf/db/*.d/`
+    ///
+    /// All files in `directory` are loaded as keyfiles in alphabetical order, so later files
+    /// override the values of earlier ones. All files in the subdirectory `locks` are loaded as
+    /// lock files. Hidden files are ignored.
+    pub fn from_directory(directory: &Path) -> DconfResult<Self> {
+        let mut this = Self::new();
+        for path in Self::files_in_directory(directory)? {
+            this.load_keyfile(&Self::read_file(&path)?)
+                .map_err(Self::with_filename(&path))?;
+        }
+
+        let locks_dir = directory.join("locks");
+        if locks_dir.is_dir() {
+            for path in Self::files_in_directory(&locks_dir)? {
+                this.load_locks(&Self::read_file(&path)?)
+                    .map_err(Self::with_filename(&path))?;
+            }
+        }
+
+        Ok(this)
+    }
+
+    fn files_in_directory(directory: &Path) -> DconfResult<Vec<std::path::PathBuf>> {
+        let mut files = Vec::new();
+        let entries = std::fs::read_dir(directory)
+            .map_err(DconfError::from_io_with_filename(Some(directory)))?;
+
+        for entry in entries {
+            let path = entry
+                .map_err(DconfError::from_io_with_filename(Some(directory)))?
+                .path();
+            let hidden = path
+                .file_name()
+                .map_or(true, |name| name.to_string_lossy().starts_with('.'));
+
+            if path.is_file() && !hidden {
+                files.push(path);
+            }
+        }
+
+        files.sort();
+        Ok(files)
+    }
+
+    fn read_file(path: &Path) -> DconfResult<String> {
+        std::fs::read_to_string(path).map_err(DconfError::from_io_with_filename(Some(path)))
+    }
+
+    fn with_filename(path: &Path) -> impl FnOnce(DconfError) -> DconfError + '_ {
+        move |err| match err {
+            DconfError::Keyfile(line, msg) => {
+                DconfError::Keyfile(line, format!("{}: {}", path.display(), msg))
+            }
+            err => err,
+        }
+    }
+
+    /// Set `key` to `value`, e.g. `/org/gnome/desktop/interface/clock-format`
+    pub fn write_key(&mut self, key: &str, value: zvariant::Value<'static>) -> DconfResult<()> {
+        check_key(key)?;
+        self.values.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    /// Remove the value of `key`. Returns whether the database contained a value for `key`.
+    pub fn reset_key(&mut self, key: &str) -> DconfResult<bool> {
+        check_key(key)?;
+        Ok(self.values.remove(key).is_some())
+    }
+
+    /// Lock the key or directory at `path`, so users can't change it
+    pub fn lock(&mut self, path: &str) -> DconfResult<()> {
+        if check_key(path).is_err() {
+            check_dir(path)?;
+        }
+
+        self.locks.insert(path.to_string());
+        Ok(())
+    }
+
+    /// Load all values from `keyfile`
+    ///
+    /// Existing values for the same keys are replaced.
+    pub fn load_keyfile(&mut self, keyfile: &str) -> DconfResult<()> {
+        let mut dir: Option<String> = None;
+
+        for (index, line) in keyfile.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(group) = line.strip_prefix('[') {
+                let group = group.strip_suffix(']').ok_or_else(|| {
+                    DconfError::Keyfile(line_number, "Missing ']' after group name".to_string())
+                })?;
+
+                let group = group.trim_matches('/');
+                let path = if group.is_empty() {
+                    "/".to_string()
+                } else {
+                    format!("/{}/", group)
+                };
+
+                check_dir(&path)
+                    .map_err(|err| DconfError::Keyfile(line_number, err.to_string()))?;
+                dir = Some(path);
+                continue;
+            }
+
+            let Some((name, value)) = line.split_once('=') else {
+                return Err(DconfError::Keyfile(
+                    line_number,
+                    "Expected group or key=value pair".to_string(),
+                ));
+            };
+
+            let dir = dir.as_ref().ok_or_else(|| {
+                DconfError::Keyfile(line_number, "Key outside of a group".to_string())
+            })?;
+
+            let name = name.trim();
+            let key = format!("{}{}", dir, name);
+            if name.contains('/') {
+                return Err(DconfError::Keyfile(
+                    line_number,
+                    format!("Invalid key name '{}'", name),
+                ));
+            }
+
+            check_key(&key).map_err(|err| DconfError::Keyfile(line_number, err.to_string()))?;
+            let value = crate::variant::parse_inferred(value.trim())
+                .map_err(|err| DconfError::Keyfile(line_number, err.to_string()))?;
+            self.values.insert(key, value);
+        }
+
+        Ok(())
+    }
+
+    /// Load all locks from `locks`, which contains one key or directory path per line
+    pub fn load_locks(&mut self, locks: &str) -> DconfResult<()> {
+        for (index, line) in locks.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            self.lock(line)
+                .map_err(|err| DconfError::Keyfile(index + 1, err.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Create the hash table of the database
+    pub fn into_table_builder(self) -> DconfResult<HashTableBuilder<'static>> {
+        let mut table = HashTableBuilder::new();
+        for (key, value) in self.values {
+            table.insert_value(&key, value)?;
+        }
+
+        if !self.locks.is_empty() {
+            let mut locks = HashTableBuilder::with_path_separator(None);
+            for lock in &self.locks {
+                locks.insert_string(lock, "")?;
+            }
+
+            table.insert_table(LOCKS_TABLE, locks)?;
+        }
+
+        Ok(table)
+    }
+
+    /// Write the database to `writer`
+    pub fn write(self, writer: &mut dyn std::io::Write) -> DconfResult<usize> {
+        let table = self.into_table_builder()?;
+        Ok(FileWriter::new().write_with_table(table, writer)?)
+    }
+
+    /// Write the database to a `Vec<u8>`
+    pub fn write_to_vec(self) -> DconfResult<Vec<u8>> {
+        let table = self.into_table_builder()?;
+        Ok(FileWriter::new().write_to_vec_with_table(table)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dconf::{DconfError, DconfReader, DconfWriter};
+    use crate::read::File;
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+
+    #[test]
+    fn keyfile() {
+        let mut writer = DconfWriter::new();
+        writer
+            .load_keyfile(
+                "# Comment\n\
+                 [/]\n\
+                 root=true\n\
+                 \n\
+                 [org/gnome/desktop/interface/]\n\
+                 ; Another comment\n\
+                 clock-format = '24h'\n\
+                 enable-animations=false\n",
+            )
+            .unwrap();
+        writer
+            .load_keyfile("[org/gnome/desktop/interface]\nclock-format='12h'\n")
+            .unwrap();
+        assert!(writer
+            .reset_key("/org/gnome/desktop/interface/enable-animations")
+            .unwrap());
+        assert!(!writer.reset_key("/org/missing").unwrap());
+
+        let file = File::from_bytes(Cow::Owned(writer.write_to_vec().unwrap())).unwrap();
+        let table = file.hash_table().unwrap();
+        let mut keys = table.keys().unwrap();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "/",
+                "/org/",
+                "/org/gnome/",
+                "/org/gnome/desktop/",
+                "/org/gnome/desktop/interface/",
+                "/org/gnome/desktop/interface/clock-format",
+                "/root",
+            ]
+        );
+
+        let reader = DconfReader::new(&file).unwrap();
+        assert_eq!(
+            reader
+                .read_key("/org/gnome/desktop/interface/clock-format")
+                .unwrap(),
+            zvariant::Value::from("12h")
+        );
+        assert_eq!(
+            reader.read_key("/root").unwrap(),
+            zvariant::Value::Bool(true)
+        );
+    }
+
+    #[test]
+    fn keyfile_errors() {
+        let error = |keyfile: &str| match DconfWriter::new().load_keyfile(keyfile) {
+            Err(DconfError::Keyfile(line, _)) => line,
+            res => panic!("Expected keyfile error, got {:?}", res),
+        };
+
+        assert_eq!(error("key=1"), 1);
+        assert_eq!(error("[org\n"), 1);
+        assert_eq!(error("[org]\nkey"), 2);
+        assert_eq!(error("[org]\n\nkey='unterminated"), 3);
+        assert_eq!(error("[org]\nsub/key=1"), 2);
+        assert_eq!(error("[org//gnome]"), 1);
+        assert_eq!(error("[org]\n=1"), 2);
+
+        let mut writer = DconfWriter::new();
+        assert_matches!(
+            writer.load_locks("/org/\norg"),
+            Err(DconfError::Keyfile(2, _))
+        );
+        assert_matches!(
+            writer.write_key("/org/", true.into()),
+            Err(DconfError::InvalidPath(_))
+        );
+        assert_matches!(writer.lock("org"), Err(DconfError::InvalidPath(_)));
+    }
+
+    #[test]
+    fn from_directory() {
+        let dir = std::env::temp_dir().join("gvdb-dconf-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("locks")).unwrap();
+        std::fs::write(dir.join("00-defaults"), "[org/test]\na=1\nb=2\n").unwrap();
+        std::fs::write(dir.join("10-override"), "[org/test]\nb=3\n").unwrap();
+        std::fs::write(dir.join(".hidden"), "invalid").unwrap();
+        std::fs::write(dir.join("locks/test"), "# Locks\n/org/test/a\n").unwrap();
+
+        let writer = DconfWriter::from_directory(&dir).unwrap();
+        let file = File::from_bytes(Cow::Owned(writer.write_to_vec().unwrap())).unwrap();
+        let reader = DconfReader::new(&file).unwrap();
+        assert_eq!(
+            reader.read_key("/org/test/a").unwrap(),
+            zvariant::Value::I32(1)
+        );
+        assert_eq!(
+            reader.read_key("/org/test/b").unwrap(),
+            zvariant::Value::I32(3)
+        );
+        assert_eq!(reader.locks().unwrap(), ["/org/test/a"]);
+
+        std::fs::write(dir.join("20-invalid"), "[org/test]\nc=\n").unwrap();
+        let err = DconfWriter::from_directory(&dir).unwrap_err();
+        assert_matches!(err, DconfError::Keyfile(2, ref msg) if msg.contains("20-invalid"));
+
+        assert_matches!(
+            DconfWriter::from_directory(&dir.join("missing")),
+            Err(DconfError::Io(_, Some(_)))
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,200 @@
+use crate::read::{Entry, File, Result};
+use std::collections::BTreeMap;
+
+/// A change of a single value, found by [`diff`]
+#[derive(Debug, PartialEq)]
+pub enum Change<'a> {
+    /// The value only exists in the new file
+    Added(zvariant::Value<'a>),
+
+    /// The value only exists in the old file
+    Removed(zvariant::Value<'a>),
+
+    /// The value exists in both files, but differs
+    Modified {
+        /// The value in the old file
+        old: zvariant::Value<'a>,
+        /// The value in the new file
+        new: zvariant::Value<'a>,
+    },
+}
+
+/// The changes between two GVDB files, created by [`diff`]
+///
+/// Changes are keyed by their path, the keys leading to the value starting at the root hash
+/// table. Every element of a path except the last one is the key of a nested hash table.
+///
+/// This is unrelated to [`write::ChangeSet`](crate::write::ChangeSet), which describes changes
+/// to apply to a file.
+#[derive(Debug, Default, PartialEq)]
+pub struct ChangeSet<'a> {
+    changes: BTreeMap<Vec<String>, Change<'a>>,
+}
+
+impl<'a> ChangeSet<'a> {
+    /// The change of the value at `path`, if it changed
+    pub fn get(&self, path: &[&str]) -> Option<&Change<'a>> {
+        let path: Vec<String> = path.iter().map(|key| key.to_string()).collect();
+        self.changes.get(&path)
+    }
+
+    /// Iterate over all changes, sorted by path
+    pub fn iter(&self) -> impl Iterator<Item = (&[String], &Change<'a>)> {
+        self.changes
+            .iter()
+            .map(|(path, change)| (path.as_slice(), change))
+    }
+
+    /// The number of changed values
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Whether the files contain the same values
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for ChangeSet<'a> {
+    type Item = (Vec<String>, Change<'a>);
+    type IntoIter = std::collections::btree_map::IntoIter<Vec<String>, Change<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.into_iter()
+    }
+}
+
+/// Collect the values of all hash tables in `file` by their path
+fn values<'a>(file: &'a File) -> Result<BTreeMap<Vec<String>, zvariant::Value<'a>>> {
+    let mut values = BTreeMap::new();
+    for (path, entry) in file.entries()? {
+        if let Entry::Value(value) = entry {
+            values.insert(path, value);
+        }
+    }
+
+    Ok(values)
+}
+
+/// List the values that were added, removed or modified from `old` to `new`
+///
+/// The values of nested hash tables are compared recursively. Containers and hash tables are not
+/// listed themselves, but all of their values are, e.g. when a whole nested hash table was added.
+/// Values are compared after decoding them, so files with different byte orders or layouts can be
+/// compared.
+///
+/// ```
+/// # use std::borrow::Cow;
+/// use gvdb::read::File;
+/// use gvdb::write::{FileWriter, HashTableBuilder};
+/// use gvdb::Change;
+///
+/// let mut table_builder = HashTableBuilder::new();
+/// table_builder.insert("int", 42u32).unwrap();
+/// let old = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+/// let old = File::from_bytes(Cow::Owned(old)).unwrap();
+///
+/// let mut table_builder = HashTableBuilder::new();
+/// table_builder.insert("int", 43u32).unwrap();
+/// table_builder.insert_string("string", "test string").unwrap();
+/// let new = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+/// let new = File::from_bytes(Cow::Owned(new)).unwrap();
+///
+/// let changes = gvdb::diff(&old, &new).unwrap();
+/// assert_eq!(changes.len(), 2);
+/// assert!(matches!(changes.get(&["string"]), Some(Change::Added(_))));
+/// assert_eq!(
+///     changes.get(&["int"]),
+///     Some(&Change::Modified {
+///         old: 42u32.into(),
+///         new: 43u32.into()
+///     })
+/// );
+/// ```
+pub fn diff<'a>(old: &'a File, new: &'a File) -> Result<ChangeSet<'a>> {
+    let old = values(old)?;
+    let mut new = values(new)?;
+    let mut changes = BTreeMap::new();
+
+    for (path, old_value) in old {
+        match new.remove(&path) {
+            Some(new_value) if new_value == old_value => {}
+            Some(new_value) => {
+                changes.insert(
+                    path,
+                    Change::Modified {
+                        old: old_value,
+                        new: new_value,
+                    },
+                );
+            }
+            None => {
+                changes.insert(path, Change::Removed(old_value));
+            }
+        }
+    }
+
+    for (path, new_value) in new {
+        changes.insert(path, Change::Added(new_value));
+    }
+
+    Ok(ChangeSet { changes })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::write::{FileWriter, HashTableBuilder};
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+
+    #[test]
+    fn unchanged() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let changes = diff(&file, &file).unwrap();
+        assert!(changes.is_empty());
+        assert_eq!(changes.iter().count(), 0);
+    }
+
+    #[test]
+    fn nested() {
+        let old = File::from_file(&TEST_FILE_2).unwrap();
+
+        let mut nested = HashTableBuilder::new();
+        nested.insert("int", 43u32).unwrap();
+        nested.insert_string("dir/added", "new").unwrap();
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert_table("table", nested).unwrap();
+        let data = FileWriter::for_big_endian()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let new = File::from_bytes(Cow::Owned(data)).unwrap();
+
+        let changes = diff(&old, &new).unwrap();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(
+            changes.get(&["string"]),
+            Some(&Change::Removed(zvariant::Value::from("test string")))
+        );
+        assert_eq!(
+            changes.get(&["table", "int"]),
+            Some(&Change::Modified {
+                old: 42u32.into(),
+                new: 43u32.into()
+            })
+        );
+        assert_eq!(changes.get(&["table", "dir/"]), None);
+
+        let changes = changes.into_iter().collect::<Vec<_>>();
+        assert_eq!(
+            changes[1],
+            (
+                vec!["table".to_string(), "dir/added".to_string()],
+                Change::Added(zvariant::Value::from("new"))
+            )
+        );
+    }
+}
@@ -0,0 +1,64 @@
+/// The byte order of a GVDB file
+///
+/// GVDB files are usually written in little endian byte order. Big endian files are supported
+/// for compatibility with files created on big endian machines.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Endian {
+    /// Little endian byte order (the preferred byte order of GVDB files)
+    #[default]
+    Little,
+    /// Big endian byte order
+    Big,
+}
+
+impl Endian {
+    /// The byte order of the machine this code is running on
+    pub fn native() -> Self {
+        if cfg!(target_endian = "little") {
+            Self::Little
+        } else {
+            Self::Big
+        }
+    }
+
+    /// Whether data in this byte order has to be byteswapped on this machine
+    pub(crate) fn is_byteswapped(self) -> bool {
+        self != Self::native()
+    }
+
+    /// The byte order of data that is byteswapped on this machine if `byteswapped` is set
+    pub(crate) fn from_byteswapped(byteswapped: bool) -> Self {
+        match (Self::native(), byteswapped) {
+            (native, false) => native,
+            (Self::Little, true) => Self::Big,
+            (Self::Big, true) => Self::Little,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Endian> for zvariant::Endian {
+    fn from(endian: Endian) -> Self {
+        match endian {
+            Endian::Little => zvariant::LE,
+            Endian::Big => zvariant::BE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    #[test]
+    fn byteswapped() {
+        assert!(!Endian::native().is_byteswapped());
+        assert_eq!(Endian::from_byteswapped(false), Endian::native());
+        assert_ne!(Endian::from_byteswapped(true), Endian::native());
+        assert!(Endian::from_byteswapped(true).is_byteswapped());
+        assert_eq!(zvariant::Endian::from(Endian::Little), zvariant::LE);
+        assert_eq!(zvariant::Endian::from(Endian::Big), zvariant::BE);
+    }
+}
@@ -1,8 +1,33 @@
+mod build_script;
 mod bundle;
+mod bundle_set;
+mod compressed;
+mod extractor;
+mod metadata;
+mod reader;
+#[cfg(feature = "glib")]
+mod register;
+mod stream;
+mod tree;
 mod xml;
 
-pub use bundle::{BuilderError, BuilderResult, BundleBuilder, FileData};
-pub use xml::{PreprocessOptions, XmlManifest, XmlManifestError, XmlManifestResult};
+pub use build_script::compile_for_build_script;
+pub use bundle::{
+    BuilderError, BuilderResult, BundleBuilder, Compression, CompressionAlgorithm, ConflictPolicy,
+    FileData, Preprocessor, FLAG_COMPRESSED, FLAG_COMPRESSED_ZSTD,
+};
+#[cfg(feature = "fs")]
+pub use bundle::{DirectoryOptions, InvalidFileNamePolicy, SymlinkPolicy};
+pub use bundle_set::BundleSet;
+pub use compressed::CompressedBytes;
+pub use extractor::{BundleExtractor, ExtractedFile, ExtractorError, ExtractorResult};
+pub use metadata::{BundleMetadata, METADATA_PATH};
+pub use reader::{Bundle, ResourceData, ResourceInfo};
+#[cfg(feature = "glib")]
+pub use register::{register_bytes, Resource};
+pub use stream::ResourceStream;
+pub use tree::{PathNode, PathNodeKind, PathTree};
+pub use xml::{PreprocessOptions, XmlLocation, XmlManifest, XmlManifestError, XmlManifestResult};
 
 /// Deprecated type aliases
 mod deprecated {
@@ -0,0 +1,81 @@
+use super::bundle::{BuilderError, BuilderResult, BundleBuilder};
+use super::xml::XmlManifest;
+use std::path::{Path, PathBuf};
+
+/// Compile the GResource XML file at `xml_path` and write the bundle to `out_path`
+///
+/// Returns the paths of all files that were read: the XML file itself, followed by every file it
+/// references. Build scripts can print these as `cargo:rerun-if-changed` lines to only recompile
+/// the bundle when one of its inputs changes.
+///
+/// ```no_run
+/// use std::path::{Path, PathBuf};
+///
+/// let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+/// let dependencies = gvdb::gresource::compile_for_build_script(
+///     Path::new("data/resources.gresource.xml"),
+///     &out_dir.join("resources.gresource"),
+/// )
+/// .unwrap();
+///
+/// for path in dependencies {
+///     println!("cargo:rerun-if-changed={}", path.display());
+/// }
+/// ```
+pub fn compile_for_build_script(xml_path: &Path, out_path: &Path) -> BuilderResult<Vec<PathBuf>> {
+    let xml = XmlManifest::from_file(xml_path)?;
+
+    let mut dependencies = vec![xml_path.to_path_buf()];
+    dependencies.extend(xml.file_paths());
+
+    let data = BundleBuilder::from_xml(xml)?.build()?;
+    std::fs::write(out_path, data).map_err(BuilderError::from_io_with_filename(Some(out_path)))?;
+
+    Ok(dependencies)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::File;
+    use crate::test::*;
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    #[test]
+    fn compile() {
+        let xml_path = GRESOURCE_XML.clone();
+        let out_path = std::env::temp_dir().join("gvdb-rs-build-script-test.gresource");
+
+        let dependencies = compile_for_build_script(&xml_path, &out_path).unwrap();
+        assert_eq!(dependencies[0], xml_path);
+        assert!(dependencies.contains(&GRESOURCE_DIR.join("test.css")));
+        assert!(dependencies.iter().all(|path| path.is_file()));
+
+        let file = File::from_file(&out_path).unwrap();
+        let _ = std::fs::remove_file(&out_path);
+        assert_eq!(
+            file.hash_table().unwrap().n_hash_items(),
+            File::from_file(&TEST_FILE_3)
+                .unwrap()
+                .hash_table()
+                .unwrap()
+                .n_hash_items()
+        );
+    }
+
+    #[test]
+    fn error() {
+        let out_path = std::env::temp_dir().join("gvdb-rs-build-script-error.gresource");
+        let res = compile_for_build_script(&GRESOURCE_DIR.join("missing.xml"), &out_path);
+        assert_matches!(res, Err(BuilderError::Manifest(_)));
+        assert!(!out_path.exists());
+
+        let res = compile_for_build_script(
+            &GRESOURCE_XML,
+            &GRESOURCE_DIR.join("missing_dir").join("out.gresource"),
+        );
+        assert_matches!(res, Err(BuilderError::Io(_, Some(_))));
+    }
+}
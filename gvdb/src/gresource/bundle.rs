@@ -2,21 +2,52 @@ mod error;
 
 pub use error::*;
 
-use crate::gresource::xml::PreprocessOptions;
-use crate::write::{FileWriter, HashTableBuilder};
+use crate::gresource::metadata::{BundleMetadata, METADATA_PATH};
+use crate::gresource::xml::{PreprocessOptions, XmlLocation};
+use crate::util::trace_event;
+use crate::write::{glib_order, FileWriter, HashTableBuilder};
 use flate2::write::ZlibEncoder;
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
+#[cfg(feature = "fs")]
 use walkdir::WalkDir;
 
-const FLAG_COMPRESSED: u32 = 1 << 0;
+/// The flag of files in a GResource bundle that are stored zlib compressed
+pub const FLAG_COMPRESSED: u32 = 1 << 0;
 
+/// The flag of files in a GResource bundle that are stored zstd compressed
+///
+/// This is an extension of gvdb-rs, see [`CompressionAlgorithm::Zstd`]. GLib doesn't know this
+/// flag and returns the compressed data for such files.
+pub const FLAG_COMPRESSED_ZSTD: u32 = 1 << 16;
+
+#[cfg(feature = "fs")]
 static SKIPPED_FILE_EXTENSIONS_DEFAULT: &[&str] =
     &["meson.build", "gresource.xml", ".gitignore", ".license"];
+#[cfg(feature = "fs")]
 static COMPRESS_EXTENSIONS_DEFAULT: &[&str] = &[".ui", ".css"];
 
+/// Whether `prefix` is a valid prefix of a `<gresource>` element
+///
+/// The prefix is used as is for the file keys, so it needs to be an absolute path.
+fn is_valid_prefix(prefix: &str) -> bool {
+    if prefix.is_empty() || prefix == "/" {
+        return true;
+    }
+
+    let Some(path) = prefix.strip_prefix('/') else {
+        return false;
+    };
+
+    path.strip_suffix('/')
+        .unwrap_or(path)
+        .split('/')
+        .all(|component| !matches!(component, "" | "." | ".."))
+}
+
 /// A container for a GResource data object
 ///
 /// Allows to read a file from the filesystem. The file is then preprocessed and compressed.
@@ -30,7 +61,7 @@ static COMPRESS_EXTENSIONS_DEFAULT: &[&str] = &[".ui", ".css"];
 ///
 /// let preprocess_options = PreprocessOptions::empty();
 /// let file_data =
-///     FileData::from_file(key, &filename, true, &preprocess_options).unwrap();
+///     FileData::from_file(key, &filename, true, &preprocess_options, None).unwrap();
 /// ```
 #[derive(Debug)]
 pub struct FileData<'a> {
@@ -41,8 +72,30 @@ pub struct FileData<'a> {
     /// uncompressed data is zero-terminated
     /// compressed data is not
     size: u32,
+
+    /// Custom preprocessing and compression that is applied by the `BundleBuilder`
+    deferred: Option<Deferred>,
+}
+
+/// Custom preprocessors and compression that still need to be applied to a [`FileData`]
+#[derive(Debug)]
+struct Deferred {
+    preprocessors: Vec<String>,
+    compressed: bool,
+    compression: Option<Compression>,
+    path: Option<PathBuf>,
 }
 
+/// A custom preprocessor for GResource files
+///
+/// Registered with [`BundleBuilder::add_preprocessor`]. Receives the file data and returns the
+/// preprocessed data.
+pub type Preprocessor = Box<
+    dyn Fn(Cow<'_, [u8]>) -> Result<Cow<'_, [u8]>, Box<dyn std::error::Error + Send + Sync>>
+        + Send
+        + Sync,
+>;
+
 impl<'a> FileData<'a> {
     /// Create a new `GResourceFileData` from raw bytes
     ///
@@ -50,51 +103,129 @@ impl<'a> FileData<'a> {
     /// if possible or `None` if not applicable.
     ///
     /// Preprocessing will be applied based on the `preprocess` parameter.
-    /// Will compress the data if `compressed` is set.
+    /// Will compress the data if `compressed` is set. The data is compressed according to
+    /// `compression`, or the settings of the [`BundleBuilder`] if `None`.
+    ///
+    /// Custom preprocessors in [`PreprocessOptions::custom`] and the compression are applied by
+    /// [`BundleBuilder::build`], which looks up the preprocessors by name.
     ///
     /// ```
     /// # use std::borrow::Cow;
     /// use std::path::PathBuf;
-    /// use gvdb::gresource::{FileData, PreprocessOptions};
+    /// use gvdb::gresource::{Compression, FileData, PreprocessOptions};
     ///
     /// let mut key = "/my/app/id/style.css".to_string();
     /// let mut filename = PathBuf::from("path/to/style.css");
     ///
     /// let preprocess_options = PreprocessOptions::empty();
     /// let data: Vec<u8> = vec![1, 2, 3, 4];
-    /// let file_data =
-    ///     FileData::new(key, Cow::Owned(data), None, true, &preprocess_options).unwrap();
+    /// let file_data = FileData::new(
+    ///     key,
+    ///     Cow::Owned(data),
+    ///     None,
+    ///     true,
+    ///     &preprocess_options,
+    ///     Some(&Compression::fast()),
+    /// )
+    /// .unwrap();
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(key = %key, len = data.len()))
+    )]
     pub fn new(
         key: String,
         data: Cow<'a, [u8]>,
         path: Option<PathBuf>,
         compressed: bool,
         preprocess: &PreprocessOptions,
+        compression: Option<&Compression>,
     ) -> BuilderResult<Self> {
-        let mut flags = 0;
-        let mut data = Self::preprocess(data, preprocess, path.clone())?;
-        let size = data.len() as u32;
-
-        if compressed {
-            data = Self::compress(data, path)?;
-            flags |= FLAG_COMPRESSED;
-        } else {
-            data.to_mut().push(0);
-        }
-
+        let data = Self::preprocess(data, preprocess, path.clone())?;
         Ok(Self {
             key,
             data,
-            flags,
-            size,
+            flags: 0,
+            size: 0,
+            deferred: Some(Deferred {
+                preprocessors: preprocess.custom.clone(),
+                compressed,
+                compression: compression.cloned(),
+                path,
+            }),
         })
     }
 
+    /// Apply the custom preprocessors from `preprocessors` and compress the data
+    ///
+    /// `compression` is used unless the file has its own compression settings.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(key = %self.key))
+    )]
+    fn finish(
+        mut self,
+        preprocessors: &HashMap<String, Preprocessor>,
+        compression: &Compression,
+    ) -> BuilderResult<Self> {
+        let Some(deferred) = self.deferred.take() else {
+            return Ok(self);
+        };
+        let compression = deferred.compression.as_ref().unwrap_or(compression);
+
+        let mut data = self.data;
+        for name in &deferred.preprocessors {
+            let preprocessor = preprocessors.get(name).ok_or_else(|| {
+                BuilderError::UnknownPreprocessor(name.clone(), deferred.path.clone())
+            })?;
+            data = preprocessor(data).map_err(|err| {
+                BuilderError::Preprocess(name.clone(), err, deferred.path.clone())
+            })?;
+            trace_event!(debug, preprocessor = %name, len = data.len(), "Applied preprocessor");
+        }
+
+        self.size = data.len() as u32;
+        let algorithm = if deferred.compressed {
+            compression.algorithm_for(&self.key)
+        } else {
+            CompressionAlgorithm::None
+        };
+
+        match algorithm {
+            CompressionAlgorithm::Zlib => {
+                data = Self::compress(data, compression.level, deferred.path)?;
+                self.flags |= FLAG_COMPRESSED;
+            }
+            #[cfg(feature = "zstd")]
+            CompressionAlgorithm::Zstd => {
+                data = Cow::Owned(
+                    zstd::encode_all(&*data, compression.level as i32)
+                        .map_err(BuilderError::from_io_with_filename(deferred.path))?,
+                );
+                self.flags |= FLAG_COMPRESSED_ZSTD;
+            }
+            CompressionAlgorithm::None => data.to_mut().push(0),
+        }
+
+        if algorithm != CompressionAlgorithm::None {
+            trace_event!(
+                debug,
+                size = self.size,
+                compressed = data.len(),
+                ?algorithm,
+                "Compressed file"
+            );
+        }
+
+        self.data = data;
+        Ok(self)
+    }
+
     /// Read the data from a file
     ///
     /// Preprocessing will be applied based on the `preprocess` parameter.
-    /// Will compress the data if `compressed` is set.
+    /// Will compress the data if `compressed` is set. The data is compressed according to
+    /// `compression`, or the settings of the [`BundleBuilder`] if `None`.
     ///
     /// ```
     /// # use std::path::PathBuf;
@@ -105,13 +236,18 @@ impl<'a> FileData<'a> {
     ///
     /// let preprocess_options = PreprocessOptions::empty();
     /// let file_data =
-    ///     FileData::from_file(key, &filename, true, &preprocess_options).unwrap();
+    ///     FileData::from_file(key, &filename, true, &preprocess_options, None).unwrap();
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %file_path.display()))
+    )]
     pub fn from_file(
         key: String,
         file_path: &Path,
         compressed: bool,
         preprocess: &PreprocessOptions,
+        compression: Option<&Compression>,
     ) -> BuilderResult<Self> {
         let mut open_file = std::fs::File::open(file_path)
             .map_err(BuilderError::from_io_with_filename(Some(file_path)))?;
@@ -125,6 +261,7 @@ impl<'a> FileData<'a> {
             Some(file_path.to_path_buf()),
             compressed,
             preprocess,
+            compression,
         )
     }
 
@@ -192,8 +329,12 @@ impl<'a> FileData<'a> {
         Ok(data)
     }
 
-    fn compress(data: Cow<'a, [u8]>, path: Option<PathBuf>) -> BuilderResult<Cow<'a, [u8]>> {
-        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::best());
+    fn compress(
+        data: Cow<'a, [u8]>,
+        level: u32,
+        path: Option<PathBuf>,
+    ) -> BuilderResult<Cow<'a, [u8]>> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::new(level));
         encoder
             .write_all(&data)
             .map_err(BuilderError::from_io_with_filename(path.clone()))?;
@@ -223,6 +364,360 @@ pub struct Data {
     data: Vec<u8>,
 }
 
+/// How to handle multiple files with the same key when merging GResource XML files
+///
+/// Used by [`BundleBuilder::from_xml_documents_with_policy`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Fail with [`BuilderError::DuplicateKey`]
+    #[default]
+    Error,
+
+    /// Keep the file that is listed first
+    FirstWins,
+
+    /// Keep the file that is listed last
+    LastWins,
+}
+
+/// How [`BundleBuilder::from_directory_with_options`] handles symbolic links
+#[cfg(feature = "fs")]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Skip all symbolic links
+    Skip,
+
+    /// Include symbolic links to files, but don't descend into symbolic links to directories
+    #[default]
+    Files,
+
+    /// Follow all symbolic links. A link to one of its own parent directories fails with
+    /// [`BuilderError::Io`]
+    Follow,
+}
+
+/// How [`BundleBuilder::from_directory_with_options`] handles paths that are not valid UTF-8
+///
+/// Keys of a GResource bundle must be valid UTF-8.
+#[cfg(feature = "fs")]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum InvalidFileNamePolicy {
+    /// Fail with [`BuilderError::Utf8`]
+    #[default]
+    Error,
+
+    /// Skip the file. The skipped files are listed by [`BundleBuilder::skipped_paths`].
+    Skip,
+
+    /// Replace each byte that is not valid UTF-8 with `%` and its value as two hexadecimal
+    /// digits, like `%FF`. A `%` in the same path component is replaced with `%25`.
+    ///
+    /// Path components that are valid UTF-8 are not changed.
+    PercentEncode,
+}
+
+/// Percent-encode the bytes of `name` that are not valid UTF-8, see
+/// [`InvalidFileNamePolicy::PercentEncode`]
+#[cfg(feature = "fs")]
+fn percent_encode(name: &std::ffi::OsStr) -> String {
+    let mut bytes = name.as_encoded_bytes();
+    let mut encoded = String::with_capacity(bytes.len() * 3);
+    while !bytes.is_empty() {
+        let (valid, invalid) = match std::str::from_utf8(bytes) {
+            Ok(valid) => (valid, 0),
+            Err(err) => {
+                let (valid, rest) = bytes.split_at(err.valid_up_to());
+                (
+                    std::str::from_utf8(valid).unwrap_or_default(),
+                    err.error_len().unwrap_or(rest.len()),
+                )
+            }
+        };
+
+        encoded.push_str(&valid.replace('%', "%25"));
+        for byte in &bytes[valid.len()..valid.len() + invalid] {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+        bytes = &bytes[valid.len() + invalid..];
+    }
+
+    encoded
+}
+
+/// Options for scanning a directory with [`BundleBuilder::from_directory_with_options`]
+///
+/// By default, blanks are not stripped, no files are compressed, the files skipped by
+/// [`BundleBuilder::from_directory`] are skipped, hidden files are included, symbolic links to
+/// files are included and all subdirectories are scanned.
+///
+/// ```
+/// use gvdb::gresource::{DirectoryOptions, SymlinkPolicy};
+///
+/// let options = DirectoryOptions::new()
+///     .with_strip_blanks(true)
+///     .with_compress_extensions(&[".ui", ".css"])
+///     .with_symlinks(SymlinkPolicy::Skip)
+///     .with_hidden_files(false)
+///     .with_max_depth(2);
+/// ```
+///
+/// Files can also be selected with glob patterns. A pattern that contains a `/` is matched
+/// against the path relative to the scanned directory, any other pattern against the file name.
+/// `*` and `?` never match a `/`, use `**` to match any number of directories.
+///
+/// ```
+/// use gvdb::gresource::DirectoryOptions;
+///
+/// let options = DirectoryOptions::new()
+///     .with_compress_pattern("*.svg")
+///     .with_skip_pattern("icons/**/*-symbolic.svg")
+///     .with_strip_blanks_pattern("ui/*.ui");
+/// ```
+#[cfg(feature = "fs")]
+#[derive(Clone, Debug)]
+pub struct DirectoryOptions {
+    strip_blanks: bool,
+    compress_extensions: Vec<String>,
+    skipped_file_extensions: Vec<String>,
+    compress_patterns: Vec<String>,
+    skip_patterns: Vec<String>,
+    strip_blanks_patterns: Vec<String>,
+    symlinks: SymlinkPolicy,
+    hidden_files: bool,
+    max_depth: Option<usize>,
+    invalid_file_names: InvalidFileNamePolicy,
+}
+
+#[cfg(feature = "fs")]
+impl DirectoryOptions {
+    /// Create the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Act as if every XML file uses `xml-stripblanks` and every JSON file `json-stripblanks`
+    ///
+    /// See [`BundleBuilder::from_directory`] for the file extensions.
+    pub fn with_strip_blanks(mut self, strip_blanks: bool) -> Self {
+        self.strip_blanks = strip_blanks;
+        self
+    }
+
+    /// Compress all files that end with one of `extensions`
+    pub fn with_compress_extensions(mut self, extensions: &[&str]) -> Self {
+        self.compress_extensions = extensions.iter().map(|ext| ext.to_string()).collect();
+        self
+    }
+
+    /// Skip all files that end with one of `extensions`
+    pub fn with_skipped_file_extensions(mut self, extensions: &[&str]) -> Self {
+        self.skipped_file_extensions = extensions.iter().map(|ext| ext.to_string()).collect();
+        self
+    }
+
+    /// Compress all files that match the glob `pattern`, in addition to the compressed extensions
+    pub fn with_compress_pattern(mut self, pattern: &str) -> Self {
+        self.compress_patterns.push(pattern.to_string());
+        self
+    }
+
+    /// Skip all files that match the glob `pattern`, in addition to the skipped extensions
+    pub fn with_skip_pattern(mut self, pattern: &str) -> Self {
+        self.skip_patterns.push(pattern.to_string());
+        self
+    }
+
+    /// Strip the blanks of all files that match the glob `pattern`
+    ///
+    /// Files that end with `.json` use `json-stripblanks`, all other files `xml-stripblanks`.
+    pub fn with_strip_blanks_pattern(mut self, pattern: &str) -> Self {
+        self.strip_blanks_patterns.push(pattern.to_string());
+        self
+    }
+
+    /// Choose how symbolic links are handled
+    pub fn with_symlinks(mut self, symlinks: SymlinkPolicy) -> Self {
+        self.symlinks = symlinks;
+        self
+    }
+
+    /// Whether to include hidden files and directories, whose names start with a `.`
+    pub fn with_hidden_files(mut self, hidden_files: bool) -> Self {
+        self.hidden_files = hidden_files;
+        self
+    }
+
+    /// Only include files that are at most `max_depth` directories below the scanned directory
+    ///
+    /// A depth of 0 only includes the files that are directly in the scanned directory.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Choose how files are handled whose path is not valid UTF-8
+    pub fn with_invalid_file_names(mut self, policy: InvalidFileNamePolicy) -> Self {
+        self.invalid_file_names = policy;
+        self
+    }
+}
+
+#[cfg(feature = "fs")]
+impl Default for DirectoryOptions {
+    fn default() -> Self {
+        Self {
+            strip_blanks: false,
+            compress_extensions: Vec::new(),
+            skipped_file_extensions: SKIPPED_FILE_EXTENSIONS_DEFAULT
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+            compress_patterns: Vec::new(),
+            skip_patterns: Vec::new(),
+            strip_blanks_patterns: Vec::new(),
+            symlinks: SymlinkPolicy::default(),
+            hidden_files: true,
+            max_depth: None,
+            invalid_file_names: InvalidFileNamePolicy::default(),
+        }
+    }
+}
+
+/// A list of compiled glob patterns of [`DirectoryOptions`]
+#[cfg(feature = "fs")]
+struct GlobSet(Vec<(glob::Pattern, bool)>);
+
+#[cfg(feature = "fs")]
+impl GlobSet {
+    fn new(patterns: &[String]) -> BuilderResult<Self> {
+        patterns
+            .iter()
+            .map(|pattern| match glob::Pattern::new(pattern) {
+                Ok(glob) => Ok((glob, pattern.contains('/'))),
+                Err(err) => Err(BuilderError::InvalidPattern(pattern.clone(), err)),
+            })
+            .collect::<BuilderResult<_>>()
+            .map(Self)
+    }
+
+    /// Whether one of the patterns matches the relative path or the file name
+    fn matches(&self, path: &str, filename: &str) -> bool {
+        let options = glob::MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+
+        self.0.iter().any(|(glob, match_path)| {
+            glob.matches_with(if *match_path { path } else { filename }, options)
+        })
+    }
+}
+
+/// The compression algorithm for files in a GResource bundle
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CompressionAlgorithm {
+    /// Compress the data with zlib
+    #[default]
+    Zlib,
+
+    /// Compress the data with zstd
+    ///
+    /// **This is not compatible with GLib**, which only supports zlib. The files are marked with
+    /// [`FLAG_COMPRESSED_ZSTD`] and can only be read with this crate, e.g. with
+    /// [`ResourceData::content`](crate::gresource::ResourceData::content) when the `zstd` feature
+    /// is enabled. Use this only for bundles that are never loaded by GLib.
+    #[cfg(feature = "zstd")]
+    Zstd,
+
+    /// Store the data uncompressed
+    None,
+}
+
+/// Compression settings for the files in a GResource bundle
+///
+/// Only files that are marked as compressed are affected, e.g. with the `compressed` attribute in
+/// a GResource XML file. The default is zlib with the best compression level.
+///
+/// ```
+/// use gvdb::gresource::{Compression, CompressionAlgorithm};
+///
+/// // Fast compression for development builds, but don't compress icons at all
+/// let compression =
+///     Compression::fast().with_pattern(".svg", CompressionAlgorithm::None);
+/// assert_eq!(compression.level(), 1);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Compression {
+    algorithm: CompressionAlgorithm,
+    level: u32,
+    patterns: Vec<(String, CompressionAlgorithm)>,
+}
+
+impl Compression {
+    /// Compress with `algorithm` at `level`
+    ///
+    /// The level ranges from 0 (no compression) to 9 (best compression). Higher values are
+    /// clamped to 9. For zstd, the level is passed as it is and 0 selects the default level of
+    /// zstd.
+    pub fn new(algorithm: CompressionAlgorithm, level: u32) -> Self {
+        Self {
+            algorithm,
+            level: level.min(9),
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Zlib with the best compression level
+    pub fn best() -> Self {
+        Self::new(CompressionAlgorithm::Zlib, 9)
+    }
+
+    /// Zlib with the fastest compression level
+    pub fn fast() -> Self {
+        Self::new(CompressionAlgorithm::Zlib, 1)
+    }
+
+    /// Don't compress any files
+    pub fn none() -> Self {
+        Self::new(CompressionAlgorithm::None, 0)
+    }
+
+    /// Use `algorithm` for all files with a key that ends with `suffix`
+    ///
+    /// If multiple patterns match a key, the pattern that was added last is used.
+    pub fn with_pattern(mut self, suffix: &str, algorithm: CompressionAlgorithm) -> Self {
+        self.patterns.push((suffix.to_string(), algorithm));
+        self
+    }
+
+    /// The default compression algorithm
+    pub fn algorithm(&self) -> CompressionAlgorithm {
+        self.algorithm
+    }
+
+    /// The compression level
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// The compression algorithm for the file with the key `key`
+    pub fn algorithm_for(&self, key: &str) -> CompressionAlgorithm {
+        self.patterns
+            .iter()
+            .rev()
+            .find(|(suffix, _)| key.ends_with(suffix.as_str()))
+            .map_or(self.algorithm, |(_, algorithm)| *algorithm)
+    }
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self::best()
+    }
+}
+
 /// Create a GResource binary file
 ///
 /// # Example
@@ -245,39 +740,130 @@ pub struct Data {
 ///     let root = File::from_bytes(Cow::Owned(data)).unwrap();
 /// }
 /// ```
-#[derive(Debug)]
 pub struct BundleBuilder<'a> {
     files: Vec<FileData<'a>>,
+    preprocessors: HashMap<String, Preprocessor>,
+    compression: Compression,
+    deduplicate: bool,
+    glib_compat: bool,
+    metadata: Option<BundleMetadata>,
+    #[cfg(feature = "fs")]
+    skipped_paths: Vec<PathBuf>,
 }
 
 impl<'a> BundleBuilder<'a> {
     /// Create this builder from a GResource XML file
+    ///
+    /// Errors that are caused by an element of the document, like a missing file, an invalid
+    /// prefix or a file key that is used more than once, are returned as
+    /// [`BuilderError::Element`] with the location of the element.
     pub fn from_xml(xml: super::xml::XmlManifest) -> BuilderResult<Self> {
-        let mut files = Vec::new();
+        Self::from_xml_documents_with_policy(vec![xml], ConflictPolicy::LastWins)
+    }
 
-        for gresource in &xml.gresources {
-            for file in &gresource.files {
-                let mut key = gresource.prefix.clone();
-                if !key.ends_with('/') {
-                    key.push('/');
-                }
+    /// Create this builder from multiple GResource XML files, to compile them into one bundle
+    ///
+    /// Returns [`BuilderError::DuplicateKey`] wrapped in [`BuilderError::Element`] if a file key
+    /// is used more than once. Use
+    /// [`from_xml_documents_with_policy`](Self::from_xml_documents_with_policy) to resolve
+    /// duplicate keys instead.
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// use gvdb::gresource::{BundleBuilder, XmlManifest};
+    ///
+    /// let dir = PathBuf::from("test-data/gresource");
+    /// let css = XmlManifest::from_string(
+    ///     &dir,
+    ///     r#"<gresources><gresource prefix="/app"><file>test.css</file></gresource></gresources>"#,
+    /// ).unwrap();
+    /// let json = XmlManifest::from_string(
+    ///     &dir,
+    ///     r#"<gresources><gresource prefix="/app"><file>json/test.json</file></gresource></gresources>"#,
+    /// ).unwrap();
+    ///
+    /// let data = BundleBuilder::from_xml_documents(vec![css, json]).unwrap().build().unwrap();
+    /// ```
+    pub fn from_xml_documents(xmls: Vec<super::xml::XmlManifest>) -> BuilderResult<Self> {
+        Self::from_xml_documents_with_policy(xmls, ConflictPolicy::Error)
+    }
 
-                if let Some(alias) = &file.alias {
-                    key.push_str(alias);
-                } else {
-                    key.push_str(&file.filename);
+    /// Create this builder from multiple GResource XML files, resolving files with the same key
+    /// according to `policy`
+    ///
+    /// The policy only applies to keys in different documents. A key that is used more than once
+    /// within the same document is always an error, like in `glib-compile-resources`.
+    ///
+    /// Only the files that end up in the bundle are read from the filesystem.
+    pub fn from_xml_documents_with_policy(
+        xmls: Vec<super::xml::XmlManifest>,
+        policy: ConflictPolicy,
+    ) -> BuilderResult<Self> {
+        let mut entries: Vec<(String, PathBuf, &super::xml::File)> = Vec::new();
+        let mut indices = HashMap::new();
+
+        for xml in &xmls {
+            // Keys must be unique within a single document, regardless of the policy
+            let mut document_keys = HashSet::new();
+
+            for gresource in &xml.gresources {
+                if !is_valid_prefix(&gresource.prefix) {
+                    return Err(BuilderError::InvalidPrefix(gresource.prefix.clone())
+                        .with_location(gresource.location.as_ref()));
                 }
 
-                let mut filename = xml.dir.clone();
-                filename.push(PathBuf::from(&file.filename));
+                for file in &gresource.files {
+                    let mut key = gresource.prefix.clone();
+                    if !key.ends_with('/') {
+                        key.push('/');
+                    }
 
-                let file_data =
-                    FileData::from_file(key, &filename, file.compressed, &file.preprocess)?;
-                files.push(file_data);
+                    if let Some(alias) = &file.alias {
+                        key.push_str(alias);
+                    } else {
+                        key.push_str(&file.filename);
+                    }
+
+                    let mut filename = xml.dir.clone();
+                    filename.push(PathBuf::from(&file.filename));
+
+                    let duplicate_key = || {
+                        BuilderError::DuplicateKey(key.clone())
+                            .with_location(file.location.as_ref())
+                    };
+
+                    if !document_keys.insert(key.clone()) {
+                        return Err(duplicate_key());
+                    }
+
+                    if let Some(&index) = indices.get(&key) {
+                        match policy {
+                            ConflictPolicy::Error => return Err(duplicate_key()),
+                            ConflictPolicy::FirstWins => {}
+                            ConflictPolicy::LastWins => entries[index] = (key, filename, file),
+                        }
+                    } else {
+                        indices.insert(key.clone(), entries.len());
+                        entries.push((key, filename, file));
+                    }
+                }
             }
         }
 
-        Ok(Self { files })
+        let entries = entries
+            .into_iter()
+            .map(|(key, filename, file)| {
+                (
+                    key,
+                    filename,
+                    file.compressed,
+                    &file.preprocess,
+                    file.location.as_ref(),
+                )
+            })
+            .collect();
+
+        Ok(Self::from_file_data(Self::read_files(entries)?))
     }
 
     /// Scan a directory and create a GResource file with all the contents of the directory.
@@ -315,6 +901,7 @@ impl<'a> BundleBuilder<'a> {
     ///
     /// Compresses all files that end with the preconfigured patterns.
     /// Compressed files are currently: ".ui", ".css"
+    #[cfg(feature = "fs")]
     pub fn from_directory(
         prefix: &str,
         directory: &Path,
@@ -345,21 +932,74 @@ impl<'a> BundleBuilder<'a> {
     /// ## `skipped_file_extensions`
     ///
     /// Skip all files that end with this string
+    #[cfg(feature = "fs")]
     pub fn from_directory_with_extensions(
         prefix: &str,
         directory: &Path,
         strip_blanks: bool,
         compress_extensions: &[&str],
         skipped_file_extensions: &[&str],
+    ) -> BuilderResult<Self> {
+        Self::from_directory_with_options(
+            prefix,
+            directory,
+            &DirectoryOptions::new()
+                .with_strip_blanks(strip_blanks)
+                .with_compress_extensions(compress_extensions)
+                .with_skipped_file_extensions(skipped_file_extensions),
+        )
+    }
+
+    /// Like `from_directory` but with full control over which files are included
+    ///
+    /// The directory is scanned in the order of the file names, independent of the platform and
+    /// file system. Only regular files are included, special files like sockets or device nodes
+    /// are skipped. The keys always use `/` as path separator.
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// use gvdb::gresource::{BundleBuilder, DirectoryOptions};
+    ///
+    /// let builder = BundleBuilder::from_directory_with_options(
+    ///     "/gvdb/rs/test",
+    ///     &PathBuf::from("test-data/gresource"),
+    ///     &DirectoryOptions::new().with_max_depth(0),
+    /// )
+    /// .unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn from_directory_with_options(
+        prefix: &str,
+        directory: &Path,
+        options: &DirectoryOptions,
     ) -> BuilderResult<Self> {
         let mut prefix = prefix.to_string();
         if !prefix.ends_with('/') {
             prefix.push('/');
         }
 
-        let mut files = Vec::new();
+        let mut walk_dir = WalkDir::new(directory)
+            .follow_links(options.symlinks == SymlinkPolicy::Follow)
+            .sort_by_file_name();
+        if let Some(max_depth) = options.max_depth {
+            walk_dir = walk_dir.max_depth(max_depth.saturating_add(1));
+        }
+
+        let is_hidden = |entry: &walkdir::DirEntry| {
+            entry.depth() > 0 && entry.file_name().as_encoded_bytes().starts_with(b".")
+        };
+
+        let compress_patterns = GlobSet::new(&options.compress_patterns)?;
+        let skip_patterns = GlobSet::new(&options.skip_patterns)?;
+        let strip_blanks_patterns = GlobSet::new(&options.strip_blanks_patterns)?;
 
-        'outer: for res in WalkDir::new(directory).into_iter() {
+        let mut entries = Vec::new();
+        let mut skipped_paths = Vec::new();
+
+        'outer: for res in walk_dir
+            .into_iter()
+            .filter_entry(|entry| options.hidden_files || !is_hidden(entry))
+        {
             let entry = match res {
                 Ok(entry) => entry,
                 Err(err) => {
@@ -368,27 +1008,13 @@ impl<'a> BundleBuilder<'a> {
                 }
             };
 
-            if entry.path().is_file() {
-                let filename: &str = match entry.file_name().try_into() {
-                    Ok(name) => name,
-                    Err(err) => return Err(BuilderError::Utf8(err, Some(entry.path().to_owned()))),
-                };
-
-                for name in skipped_file_extensions {
-                    if filename.ends_with(name) {
-                        continue 'outer;
-                    }
-                }
-
-                let mut compress_this = false;
-
-                for name in compress_extensions {
-                    if filename.ends_with(name) {
-                        compress_this = true;
-                        break;
-                    }
-                }
+            let is_file = match options.symlinks {
+                SymlinkPolicy::Skip => !entry.path_is_symlink() && entry.file_type().is_file(),
+                SymlinkPolicy::Files => entry.path().is_file(),
+                SymlinkPolicy::Follow => entry.file_type().is_file(),
+            };
 
+            if is_file {
                 let file_abs_path = entry.path();
                 let file_path_relative = match file_abs_path.strip_prefix(directory) {
                     Ok(path) => path,
@@ -397,14 +1023,58 @@ impl<'a> BundleBuilder<'a> {
                     }
                 };
 
-                let file_path_str_relative: &str = match file_path_relative.as_os_str().try_into() {
-                    Ok(name) => name,
-                    Err(err) => {
-                        return Err(BuilderError::Utf8(err, Some(file_path_relative.to_owned())))
+                // Keys always use `/` as separator, independent of the platform
+                let mut components = Vec::new();
+                for component in file_path_relative.iter() {
+                    match component.to_str() {
+                        Some(component) => components.push(Cow::Borrowed(component)),
+                        None => match options.invalid_file_names {
+                            InvalidFileNamePolicy::Error => {
+                                let err = std::str::from_utf8(component.as_encoded_bytes())
+                                    .expect_err("Component is not valid UTF-8");
+                                return Err(BuilderError::Utf8(
+                                    err,
+                                    Some(file_path_relative.to_owned()),
+                                ));
+                            }
+                            InvalidFileNamePolicy::Skip => {
+                                skipped_paths.push(file_abs_path.to_owned());
+                                continue 'outer;
+                            }
+                            InvalidFileNamePolicy::PercentEncode => {
+                                components.push(Cow::Owned(percent_encode(component)))
+                            }
+                        },
                     }
-                };
+                }
+                let file_path_str_relative = components.join("/");
+                let filename = components.last().map(|name| &**name).unwrap_or_default();
 
-                let options = if strip_blanks && file_path_str_relative.ends_with(".json") {
+                for name in &options.skipped_file_extensions {
+                    if filename.ends_with(name.as_str()) {
+                        continue 'outer;
+                    }
+                }
+
+                if skip_patterns.matches(&file_path_str_relative, filename) {
+                    continue;
+                }
+
+                let compress_this = options
+                    .compress_extensions
+                    .iter()
+                    .any(|name| filename.ends_with(name.as_str()))
+                    || compress_patterns.matches(&file_path_str_relative, filename);
+
+                let strip_blanks = options.strip_blanks;
+                let preprocess = if strip_blanks_patterns.matches(&file_path_str_relative, filename)
+                {
+                    if filename.ends_with(".json") {
+                        PreprocessOptions::json_stripblanks()
+                    } else {
+                        PreprocessOptions::xml_stripblanks()
+                    }
+                } else if strip_blanks && file_path_str_relative.ends_with(".json") {
                     PreprocessOptions::json_stripblanks()
                 } else if strip_blanks && file_path_str_relative.ends_with(".xml")
                     || file_path_str_relative.ends_with(".ui")
@@ -416,27 +1086,240 @@ impl<'a> BundleBuilder<'a> {
                 };
 
                 let key = format!("{}{}", prefix, file_path_str_relative);
-                let file_data = FileData::from_file(key, file_abs_path, compress_this, &options)?;
-                files.push(file_data);
+                entries.push((key, file_abs_path.to_owned(), compress_this, preprocess));
             }
         }
 
-        Ok(Self { files })
+        let entries = entries
+            .iter()
+            .map(|(key, path, compressed, options)| {
+                (key.clone(), path.clone(), *compressed, options, None)
+            })
+            .collect();
+
+        let mut builder = Self::from_file_data(Self::read_files(entries)?);
+        builder.skipped_paths = skipped_paths;
+        Ok(builder)
+    }
+
+    /// The files that were skipped by [`BundleBuilder::from_directory_with_options`] because their
+    /// path is not valid UTF-8
+    ///
+    /// See [`InvalidFileNamePolicy::Skip`].
+    #[cfg(feature = "fs")]
+    pub fn skipped_paths(&self) -> &[PathBuf] {
+        &self.skipped_paths
+    }
+
+    /// Read, preprocess and compress the files in `entries`
+    ///
+    /// With the `rayon` feature the files are processed in parallel. The order of the returned
+    /// files always matches the order of `entries`. Errors are annotated with the location of the
+    /// XML element of the file, if there is one.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(files = entries.len()))
+    )]
+    fn read_files(
+        entries: Vec<(
+            String,
+            PathBuf,
+            bool,
+            &PreprocessOptions,
+            Option<&XmlLocation>,
+        )>,
+    ) -> BuilderResult<Vec<FileData<'a>>> {
+        #[cfg(feature = "rayon")]
+        use rayon::prelude::*;
+
+        #[cfg(feature = "rayon")]
+        let entries = entries.into_par_iter();
+        #[cfg(not(feature = "rayon"))]
+        let entries = entries.into_iter();
+
+        entries
+            .map(|(key, path, compressed, options, location)| {
+                FileData::from_file(key, &path, compressed, options, None)
+                    .map_err(|err| err.with_location(location))
+            })
+            .collect()
     }
 
     /// Create a new Builder from a `Vec<FileData>`.
     ///
     /// This is the most flexible way to create a GResource file, but also the most hands-on.
     pub fn from_file_data(files: Vec<FileData<'a>>) -> Self {
-        Self { files }
+        Self {
+            files,
+            preprocessors: HashMap::new(),
+            compression: Compression::default(),
+            deduplicate: false,
+            glib_compat: false,
+            metadata: None,
+            #[cfg(feature = "fs")]
+            skipped_paths: Vec::new(),
+        }
+    }
+
+    /// Register a custom preprocessor with the name `name`
+    ///
+    /// The preprocessor is applied to all files that list `name` in their `preprocess` attribute
+    /// in the GResource XML, after the built-in preprocessing options. Building the bundle fails
+    /// with [`BuilderError::UnknownPreprocessor`] if a file uses a name that is not registered.
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// use std::borrow::Cow;
+    /// use gvdb::gresource::{BundleBuilder, XmlManifest};
+    ///
+    /// let xml = XmlManifest::from_string(
+    ///     &PathBuf::from("test-data/gresource"),
+    ///     r#"<gresources><gresource prefix="/app"><file preprocess="uppercase">test.css</file></gresource></gresources>"#,
+    /// ).unwrap();
+    ///
+    /// let mut builder = BundleBuilder::from_xml(xml).unwrap();
+    /// builder.add_preprocessor(
+    ///     "uppercase",
+    ///     Box::new(|data| Ok(Cow::Owned(data.to_ascii_uppercase()))),
+    /// );
+    /// let data = builder.build().unwrap();
+    /// ```
+    pub fn add_preprocessor(&mut self, name: &str, preprocessor: Preprocessor) {
+        self.preprocessors.insert(name.to_string(), preprocessor);
+    }
+
+    /// Set the compression settings for all files that don't have their own
+    ///
+    /// Defaults to [`Compression::best`].
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// use gvdb::gresource::{BundleBuilder, Compression, XmlManifest};
+    ///
+    /// let xml = XmlManifest::from_file(&PathBuf::from("test-data/gresource/test3.gresource.xml"))
+    ///     .unwrap();
+    /// let mut builder = BundleBuilder::from_xml(xml).unwrap();
+    /// builder.set_compression(Compression::fast());
+    /// let data = builder.build().unwrap();
+    /// ```
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.compression = compression;
+    }
+
+    /// Share the data of files with identical contents and of identical path components
+    ///
+    /// See [`FileWriter::with_deduplication`]. Defaults to `false`, which is required for files that
+    /// are byte-identical to the ones created by `glib-compile-resources`.
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// use gvdb::gresource::{BundleBuilder, XmlManifest};
+    ///
+    /// let xml = XmlManifest::from_file(&PathBuf::from("test-data/gresource/test3.gresource.xml"))
+    ///     .unwrap();
+    /// let mut builder = BundleBuilder::from_xml(xml).unwrap();
+    /// builder.set_deduplication(true);
+    /// let data = builder.build().unwrap();
+    /// ```
+    pub fn set_deduplication(&mut self, deduplicate: bool) {
+        self.deduplicate = deduplicate;
+    }
+
+    /// Write files that are byte-identical to the ones created by `glib-compile-resources`
+    ///
+    /// See [`FileWriter::with_glib_compat_layout`]. The files are laid out as if they were added in
+    /// the order of this builder, which is the document order for
+    /// [`from_xml`](Self::from_xml). Defaults to `false`.
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// use gvdb::gresource::{BundleBuilder, XmlManifest};
+    ///
+    /// let xml = XmlManifest::from_file(&PathBuf::from("test-data/gresource/test3.gresource.xml"))
+    ///     .unwrap();
+    /// let mut builder = BundleBuilder::from_xml(xml).unwrap();
+    /// builder.set_glib_compat_layout(true);
+    /// let data = builder.build().unwrap();
+    ///
+    /// assert_eq!(data, std::fs::read("test-data/test3.gresource").unwrap());
+    /// ```
+    pub fn set_glib_compat_layout(&mut self, glib_compat: bool) {
+        self.glib_compat = glib_compat;
+    }
+
+    /// Store `metadata` in the bundle at [`METADATA_PATH`]
+    ///
+    /// No metadata is stored by default, so the output stays byte-compatible with
+    /// `glib-compile-resources`. Building fails with [`BuilderError::DuplicateKey`] if one of the
+    /// files already uses [`METADATA_PATH`].
+    ///
+    /// See [`BundleMetadata`] for an example.
+    pub fn set_metadata(&mut self, metadata: Option<BundleMetadata>) {
+        self.metadata = metadata;
     }
 
     /// Build the binary GResource data
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(files = self.files.len()))
+    )]
     pub fn build(self) -> BuilderResult<Vec<u8>> {
-        let builder = FileWriter::new();
+        let builder = FileWriter::new()
+            .with_deduplication(self.deduplicate)
+            .with_glib_compat_layout(self.glib_compat);
         let mut table_builder = HashTableBuilder::new();
 
-        for file_data in self.files.into_iter() {
+        #[cfg(feature = "rayon")]
+        let mut files = {
+            use rayon::prelude::*;
+
+            let preprocessors = &self.preprocessors;
+            let compression = &self.compression;
+            self.files
+                .into_par_iter()
+                .map(|file_data| file_data.finish(preprocessors, compression))
+                .collect::<BuilderResult<Vec<_>>>()?
+        };
+        #[cfg(not(feature = "rayon"))]
+        let mut files = self
+            .files
+            .into_iter()
+            .map(|file_data| file_data.finish(&self.preprocessors, &self.compression))
+            .collect::<BuilderResult<Vec<_>>>()?;
+
+        if let Some(metadata) = &self.metadata {
+            if files
+                .iter()
+                .any(|file_data| file_data.key() == METADATA_PATH)
+            {
+                return Err(BuilderError::DuplicateKey(METADATA_PATH.to_string()));
+            }
+
+            let mut data = metadata.to_bytes();
+            let size = data.len() as u32;
+            data.push(0);
+            files.push(FileData {
+                key: METADATA_PATH.to_string(),
+                data: Cow::Owned(data),
+                flags: 0,
+                size,
+                deferred: None,
+            });
+        }
+
+        if self.glib_compat {
+            // glib-compile-resources adds the files to the GVDB hash table in the order of its own
+            // hash table of files
+            let order: HashMap<String, usize> =
+                glib_order::iteration_order(files.iter().map(|file_data| file_data.key()))
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, key)| (key.to_string(), index))
+                    .collect();
+            files.sort_by_key(|file_data| order.get(file_data.key()).copied());
+        }
+
+        for file_data in files {
             let data = Data {
                 size: file_data.size,
                 flags: file_data.flags,
@@ -446,7 +1329,24 @@ impl<'a> BundleBuilder<'a> {
             table_builder.insert_value(file_data.key(), zvariant::Value::from(data))?;
         }
 
-        Ok(builder.write_to_vec_with_table(table_builder)?)
+        let data = builder.write_to_vec_with_table(table_builder)?;
+        trace_event!(debug, len = data.len(), "Built GResource bundle");
+        Ok(data)
+    }
+}
+
+impl std::fmt::Debug for BundleBuilder<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut preprocessors: Vec<&String> = self.preprocessors.keys().collect();
+        preprocessors.sort();
+
+        f.debug_struct("BundleBuilder")
+            .field("files", &self.files)
+            .field("preprocessors", &preprocessors)
+            .field("compression", &self.compression)
+            .field("deduplicate", &self.deduplicate)
+            .field("glib_compat", &self.glib_compat)
+            .finish()
     }
 }
 
@@ -455,8 +1355,12 @@ mod test {
     use super::*;
     use crate::gresource::xml::XmlManifest;
     use crate::read::File;
-    use crate::test::{assert_is_file_3, byte_compare_file_3, GRESOURCE_DIR, GRESOURCE_XML};
+    use crate::test::{
+        assert_bytes_eq, assert_is_file_3, byte_compare_file_3, GRESOURCE_DIR, GRESOURCE_XML,
+        TEST_FILE_3,
+    };
     use matches::assert_matches;
+    #[cfg(feature = "fs")]
     use std::ffi::OsStr;
     use zvariant::Type;
 
@@ -469,13 +1373,13 @@ mod test {
             assert!(file.key().starts_with("/gvdb/rs/test"));
 
             assert!(
-                vec![
+                [
                     "/gvdb/rs/test/online-symbolic.svg",
                     "/gvdb/rs/test/icons/scalable/actions/send-symbolic.svg",
                     "/gvdb/rs/test/json/test.json",
                     "/gvdb/rs/test/test.css",
                 ]
-                .contains(&&*file.key()),
+                .contains(&file.key()),
                 "Unknown file with key: {}",
                 file.key()
             )
@@ -483,6 +1387,7 @@ mod test {
     }
 
     #[test]
+    #[cfg(feature = "fs")]
     fn from_dir_file_data() {
         for preprocess in [true, false] {
             let builder = BundleBuilder::from_directory(
@@ -497,14 +1402,14 @@ mod test {
                 assert!(file.key().starts_with("/gvdb/rs/test"));
 
                 assert!(
-                    vec![
+                    [
                         "/gvdb/rs/test/icons/scalable/actions/online-symbolic.svg",
                         "/gvdb/rs/test/icons/scalable/actions/send-symbolic.svg",
                         "/gvdb/rs/test/json/test.json",
                         "/gvdb/rs/test/test.css",
                         "/gvdb/rs/test/test3.gresource.xml",
                     ]
-                    .contains(&&*file.key()),
+                    .contains(&file.key()),
                     "Unknown file with key: {}",
                     file.key()
                 );
@@ -513,6 +1418,20 @@ mod test {
     }
 
     #[test]
+    #[cfg(feature = "fs")]
+    fn from_dir_reproducible() {
+        let build = || {
+            BundleBuilder::from_directory("/gvdb/rs/test", &GRESOURCE_DIR, true, true)
+                .unwrap()
+                .build()
+                .unwrap()
+        };
+
+        assert_eq!(build(), build());
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
     fn from_dir_invalid() {
         let res = BundleBuilder::from_directory(
             "/gvdb/rs/test",
@@ -526,6 +1445,182 @@ mod test {
         assert_matches!(err, BuilderError::Io(..));
     }
 
+    #[test]
+    #[cfg(feature = "fs")]
+    fn from_dir_options() {
+        let keys = |options: &DirectoryOptions| {
+            BundleBuilder::from_directory_with_options("/gvdb/rs/test", &GRESOURCE_DIR, options)
+                .unwrap()
+                .files
+                .iter()
+                .map(|file| file.key().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // Files are always sorted by their path
+        assert_eq!(
+            keys(&DirectoryOptions::new()),
+            [
+                "/gvdb/rs/test/icons/scalable/actions/online-symbolic.svg",
+                "/gvdb/rs/test/icons/scalable/actions/send-symbolic.svg",
+                "/gvdb/rs/test/json/test.json",
+                "/gvdb/rs/test/test.css",
+            ]
+        );
+        assert_eq!(
+            keys(&DirectoryOptions::new().with_max_depth(0)),
+            ["/gvdb/rs/test/test.css"]
+        );
+        assert_eq!(
+            keys(&DirectoryOptions::new().with_max_depth(1)),
+            ["/gvdb/rs/test/json/test.json", "/gvdb/rs/test/test.css"]
+        );
+        assert_eq!(
+            keys(&DirectoryOptions::new().with_skipped_file_extensions(&[".svg", ".xml"])),
+            ["/gvdb/rs/test/json/test.json", "/gvdb/rs/test/test.css"]
+        );
+
+        let builder = BundleBuilder::from_directory_with_options(
+            "/gvdb/rs/test",
+            &GRESOURCE_DIR,
+            &DirectoryOptions::new().with_compress_extensions(&[".css"]),
+        )
+        .unwrap();
+        for file in builder.files {
+            assert_eq!(
+                file.flags & FLAG_COMPRESSED != 0
+                    || file.deferred.as_ref().is_some_and(|d| d.compressed),
+                file.key().ends_with(".css"),
+                "{}",
+                file.key()
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn from_dir_patterns() {
+        let build = |options: &DirectoryOptions| {
+            BundleBuilder::from_directory_with_options("/gvdb/rs/test", &GRESOURCE_DIR, options)
+        };
+        let is_compressed = |file: &FileData| {
+            file.flags & FLAG_COMPRESSED != 0
+                || file.deferred.as_ref().is_some_and(|d| d.compressed)
+        };
+
+        let builder = build(
+            &DirectoryOptions::new()
+                .with_compress_pattern("*.svg")
+                .with_skip_pattern("icons/**/send-*.svg")
+                .with_skip_pattern("test.css"),
+        )
+        .unwrap();
+        let files = builder
+            .files
+            .iter()
+            .map(|file| (file.key(), is_compressed(file)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files,
+            [
+                (
+                    "/gvdb/rs/test/icons/scalable/actions/online-symbolic.svg",
+                    true
+                ),
+                ("/gvdb/rs/test/json/test.json", false),
+            ]
+        );
+
+        // A pattern with a separator only matches the relative path
+        let builder = build(&DirectoryOptions::new().with_skip_pattern("*/test.json")).unwrap();
+        assert_eq!(builder.files.len(), 3);
+        let builder = build(&DirectoryOptions::new().with_skip_pattern("*.json")).unwrap();
+        assert_eq!(builder.files.len(), 3);
+        let builder = build(&DirectoryOptions::new().with_skip_pattern("/json/*")).unwrap();
+        assert_eq!(builder.files.len(), 4);
+
+        let json_size = |options: &DirectoryOptions| {
+            build(options)
+                .unwrap()
+                .files
+                .into_iter()
+                .find(|file| file.key().ends_with(".json"))
+                .unwrap()
+                .data
+                .len()
+        };
+        assert!(
+            json_size(&DirectoryOptions::new().with_strip_blanks_pattern("json/*"))
+                < json_size(&DirectoryOptions::new())
+        );
+
+        let err = build(&DirectoryOptions::new().with_compress_pattern("[")).unwrap_err();
+        assert_matches!(err, BuilderError::InvalidPattern(ref pattern, _) if pattern == "[");
+    }
+
+    #[test]
+    #[cfg(all(unix, feature = "fs"))]
+    fn from_dir_symlinks_hidden() {
+        let dir: PathBuf = ["test-data", "temp_options"].iter().collect();
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("sub").join(".hidden_dir")).unwrap();
+        std::fs::write(dir.join("file.txt"), "file").unwrap();
+        std::fs::write(dir.join(".hidden"), "hidden").unwrap();
+        std::fs::write(dir.join("sub").join(".hidden_dir").join("a.txt"), "a").unwrap();
+        std::os::unix::fs::symlink("file.txt", dir.join("link.txt")).unwrap();
+        std::os::unix::fs::symlink("sub", dir.join("link_dir")).unwrap();
+
+        let keys = |options: DirectoryOptions| {
+            BundleBuilder::from_directory_with_options("/test", &dir, &options).map(|builder| {
+                builder
+                    .files
+                    .iter()
+                    .map(|file| file.key().to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        let default = keys(DirectoryOptions::new());
+        let no_hidden = keys(DirectoryOptions::new().with_hidden_files(false));
+        let skip = keys(DirectoryOptions::new().with_symlinks(SymlinkPolicy::Skip));
+        let follow = keys(DirectoryOptions::new().with_symlinks(SymlinkPolicy::Follow));
+
+        // A link to a parent directory is a loop
+        std::os::unix::fs::symlink("..", dir.join("sub").join("loop")).unwrap();
+        let looped = keys(DirectoryOptions::new().with_symlinks(SymlinkPolicy::Follow));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            default.unwrap(),
+            [
+                "/test/.hidden",
+                "/test/file.txt",
+                "/test/link.txt",
+                "/test/sub/.hidden_dir/a.txt",
+            ]
+        );
+        assert_eq!(no_hidden.unwrap(), ["/test/file.txt", "/test/link.txt"]);
+        assert_eq!(
+            skip.unwrap(),
+            [
+                "/test/.hidden",
+                "/test/file.txt",
+                "/test/sub/.hidden_dir/a.txt",
+            ]
+        );
+        assert_eq!(
+            follow.unwrap(),
+            [
+                "/test/.hidden",
+                "/test/file.txt",
+                "/test/link.txt",
+                "/test/link_dir/.hidden_dir/a.txt",
+                "/test/sub/.hidden_dir/a.txt",
+            ]
+        );
+        assert_matches!(looped, Err(BuilderError::Io(..)));
+    }
+
     #[test]
     fn test_file_3() {
         let doc = XmlManifest::from_file(&GRESOURCE_XML).unwrap();
@@ -538,6 +1633,296 @@ mod test {
     }
 
     #[test]
+    fn xml_documents() {
+        let manifest = |files: &str| {
+            XmlManifest::from_string(
+                &GRESOURCE_DIR,
+                format!(
+                    r#"<gresources><gresource prefix="/app">{}</gresource></gresources>"#,
+                    files
+                ),
+            )
+            .unwrap()
+        };
+
+        let build = |builder: BundleBuilder| {
+            let data = builder.build().unwrap();
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            let mut keys = file.hash_table().unwrap().keys().unwrap();
+            keys.sort();
+            let (_, _, data) = file
+                .hash_table()
+                .unwrap()
+                .get::<(u32, u32, Vec<u8>)>("/app/style.css")
+                .unwrap();
+            (keys, data)
+        };
+
+        let css = r#"<file alias="style.css">test.css</file>"#;
+        let json = r#"<file alias="style.css">json/test.json</file>"#;
+        let other = r#"<file>json/test.json</file>"#;
+
+        let builder =
+            BundleBuilder::from_xml_documents(vec![manifest(css), manifest(other)]).unwrap();
+        let (keys, _) = build(builder);
+        assert_eq!(
+            keys,
+            [
+                "/",
+                "/app/",
+                "/app/json/",
+                "/app/json/test.json",
+                "/app/style.css"
+            ]
+        );
+
+        let err = BundleBuilder::from_xml_documents(vec![manifest(css), manifest(json)]);
+        assert_matches!(
+            err,
+            Err(BuilderError::Element(err, _))
+                if matches!(&*err, BuilderError::DuplicateKey(key) if key == "/app/style.css")
+        );
+
+        let css_data = std::fs::read(GRESOURCE_DIR.join("test.css")).unwrap();
+        let builder = BundleBuilder::from_xml_documents_with_policy(
+            vec![manifest(css), manifest(json)],
+            ConflictPolicy::FirstWins,
+        )
+        .unwrap();
+        assert_eq!(build(builder).1[..css_data.len()], css_data);
+
+        let builder = BundleBuilder::from_xml_documents_with_policy(
+            vec![manifest(css), manifest(json)],
+            ConflictPolicy::LastWins,
+        )
+        .unwrap();
+        assert_ne!(build(builder).1[..css_data.len()], css_data);
+    }
+
+    #[test]
+    fn glib_compat_layout() {
+        let build = |glib_compat: bool| {
+            let doc = XmlManifest::from_file(&GRESOURCE_XML).unwrap();
+            let mut builder = BundleBuilder::from_xml(doc).unwrap();
+            builder.set_glib_compat_layout(glib_compat);
+            builder.build().unwrap()
+        };
+
+        let reference = std::fs::read(&*TEST_FILE_3).unwrap();
+        assert_bytes_eq(&reference, &build(true), "GLib compatible layout");
+        assert_ne!(build(false), reference);
+    }
+
+    #[test]
+    fn xml_diagnostics() {
+        let error = |xml: &str| {
+            let manifest = XmlManifest::from_string(&GRESOURCE_DIR, xml).unwrap();
+            match BundleBuilder::from_xml(manifest) {
+                Err(BuilderError::Element(err, location)) => (err, location),
+                other => panic!("Expected an element error, got {:?}", other.map(|_| ())),
+            }
+        };
+
+        let (err, location) = error(
+            "<gresources>\n  <gresource prefix=\"/app\">\n    <file>test.css</file>\n    <file>missing.css</file>\n  </gresource>\n</gresources>",
+        );
+        assert_matches!(*err, BuilderError::Io(_, Some(_)));
+        assert_eq!((location.line(), location.column()), (4, 5));
+        assert_eq!(location.filename(), None);
+        assert!(
+            format!("{}", BuilderError::Element(err, location)).starts_with("line 4, column 5: ")
+        );
+
+        let (err, location) = error(
+            "<gresources>\n<gresource prefix=\"/app\"/>\n<gresource prefix=\"app/../x\"/>\n</gresources>",
+        );
+        assert_matches!(*err, BuilderError::InvalidPrefix(prefix) if prefix == "app/../x");
+        assert_eq!((location.line(), location.column()), (3, 1));
+
+        let (err, location) = error(
+            r#"<gresources><gresource prefix="/app"><file alias="a.css">test.css</file><file alias="a.css">test.css</file></gresource></gresources>"#,
+        );
+        assert_matches!(*err, BuilderError::DuplicateKey(key) if key == "/app/a.css");
+        assert_eq!((location.line(), location.column()), (1, 73));
+
+        for prefix in ["", "/", "/app", "/app/", "/org/example/"] {
+            assert!(is_valid_prefix(prefix), "{}", prefix);
+        }
+        for prefix in ["app", "/app//x", "/./app", "/app/..", "//"] {
+            assert!(!is_valid_prefix(prefix), "{}", prefix);
+        }
+    }
+
+    #[test]
+    fn custom_preprocessor() {
+        let xml = |compressed: bool| {
+            XmlManifest::from_string(
+                &GRESOURCE_DIR,
+                format!(
+                    r#"<gresources><gresource prefix="/app"><file compressed="{}" preprocess="upper,suffix">test.css</file></gresource></gresources>"#,
+                    compressed
+                ),
+            )
+            .unwrap()
+        };
+
+        let css = std::fs::read(GRESOURCE_DIR.join("test.css")).unwrap();
+        let mut expected = css.to_ascii_uppercase();
+        expected.extend_from_slice(b"/* end */");
+
+        for compressed in [false, true] {
+            let mut builder = BundleBuilder::from_xml(xml(compressed)).unwrap();
+            builder.add_preprocessor(
+                "upper",
+                Box::new(|data| Ok(Cow::Owned(data.to_ascii_uppercase()))),
+            );
+            builder.add_preprocessor(
+                "suffix",
+                Box::new(|mut data| {
+                    data.to_mut().extend_from_slice(b"/* end */");
+                    Ok(data)
+                }),
+            );
+            assert!(format!("{:?}", builder).contains("[\"suffix\", \"upper\"]"));
+
+            let data = builder.build().unwrap();
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            let extractor = crate::gresource::BundleExtractor::new(&file).unwrap();
+            let files = extractor.files().unwrap();
+            assert_eq!(files[0].key(), "/app/test.css");
+            assert_eq!(files[0].data(), &expected[..]);
+        }
+
+        let err = BundleBuilder::from_xml(xml(false)).unwrap().build();
+        assert_matches!(err, Err(BuilderError::UnknownPreprocessor(name, Some(_))) if name == "upper");
+
+        let mut builder = BundleBuilder::from_xml(xml(false)).unwrap();
+        builder.add_preprocessor("upper", Box::new(|_| Err("Invalid data".into())));
+        let err = builder.build();
+        assert_matches!(err, Err(BuilderError::Preprocess(name, _, _)) if name == "upper");
+    }
+
+    #[test]
+    fn compression() {
+        let compression = Compression::new(CompressionAlgorithm::Zlib, 100)
+            .with_pattern(".css", CompressionAlgorithm::None)
+            .with_pattern("test.css", CompressionAlgorithm::Zlib);
+        assert_eq!(compression.level(), 9);
+        assert_eq!(compression.algorithm(), CompressionAlgorithm::Zlib);
+        assert_eq!(
+            compression.algorithm_for("/a/test.css"),
+            CompressionAlgorithm::Zlib
+        );
+        assert_eq!(
+            compression.algorithm_for("/a/other.css"),
+            CompressionAlgorithm::None
+        );
+        assert_eq!(
+            compression.algorithm_for("/a/test.ui"),
+            CompressionAlgorithm::Zlib
+        );
+        assert_eq!(Compression::default(), Compression::best());
+
+        let build = |compression: Compression, file_compression: Option<Compression>| {
+            let file_data = FileData::from_file(
+                "/app/test.css".to_string(),
+                &GRESOURCE_DIR.join("test.css"),
+                true,
+                &PreprocessOptions::empty(),
+                file_compression.as_ref(),
+            )
+            .unwrap();
+            let mut builder = BundleBuilder::from_file_data(vec![file_data]);
+            builder.set_compression(compression);
+            let data = builder.build().unwrap();
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            let extractor = crate::gresource::BundleExtractor::new(&file).unwrap();
+            let files = extractor.files().unwrap();
+            assert_eq!(
+                files[0].data(),
+                &std::fs::read(GRESOURCE_DIR.join("test.css")).unwrap()[..]
+            );
+            files[0].is_compressed()
+        };
+
+        assert!(build(Compression::best(), None));
+        assert!(build(Compression::fast(), None));
+        assert!(!build(Compression::none(), None));
+        assert!(build(Compression::none(), Some(Compression::fast())));
+        assert!(!build(
+            Compression::fast().with_pattern(".css", CompressionAlgorithm::None),
+            None
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn compression_zstd() {
+        let css = std::fs::read(GRESOURCE_DIR.join("test.css")).unwrap();
+        let file_data = FileData::from_file(
+            "/app/test.css".to_string(),
+            &GRESOURCE_DIR.join("test.css"),
+            true,
+            &PreprocessOptions::empty(),
+            None,
+        )
+        .unwrap();
+        let mut builder = BundleBuilder::from_file_data(vec![file_data]);
+        builder.set_compression(Compression::new(CompressionAlgorithm::Zstd, 3));
+        let data = builder.build().unwrap();
+
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let bundle = crate::gresource::Bundle::new(&file).unwrap();
+        let info = bundle.info("/app/test.css").unwrap();
+        assert_eq!(info.flags(), FLAG_COMPRESSED_ZSTD);
+        assert!(info.is_compressed());
+        assert_eq!(info.size() as usize, css.len());
+        assert_eq!(bundle.lookup("/app/test.css").unwrap(), css);
+
+        let table = file.hash_table().unwrap();
+        let stored = crate::gresource::ResourceData::lookup(&table, "/app/test.css").unwrap();
+        assert!(stored.data().starts_with(&[0x28, 0xb5, 0x2f, 0xfd]));
+    }
+
+    #[test]
+    fn deduplication() {
+        let build = |deduplicate: bool| {
+            let files = ["a", "b"]
+                .iter()
+                .map(|dir| {
+                    FileData::from_file(
+                        format!("/app/{}/test.css", dir),
+                        &GRESOURCE_DIR.join("test.css"),
+                        true,
+                        &PreprocessOptions::empty(),
+                        None,
+                    )
+                    .unwrap()
+                })
+                .collect();
+            let mut builder = BundleBuilder::from_file_data(files);
+            builder.set_deduplication(deduplicate);
+            builder.build().unwrap()
+        };
+
+        let data = build(false);
+        let deduplicated = build(true);
+        assert!(deduplicated.len() < data.len());
+
+        let file = File::from_bytes(Cow::Owned(deduplicated)).unwrap();
+        let extractor = crate::gresource::BundleExtractor::new(&file).unwrap();
+        let files = extractor.files().unwrap();
+        assert_eq!(files.len(), 2);
+        for file in files {
+            assert_eq!(
+                file.data(),
+                &std::fs::read(GRESOURCE_DIR.join("test.css")).unwrap()[..]
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
     fn test_file_from_dir() {
         let builder =
             BundleBuilder::from_directory("/gvdb/rs/test", &GRESOURCE_DIR, true, true).unwrap();
@@ -583,7 +1968,7 @@ mod test {
     }
 
     #[test]
-    #[cfg(unix)]
+    #[cfg(all(unix, feature = "fs"))]
     fn test_from_dir_invalid() {
         use std::os::unix::ffi::OsStrExt;
         let invalid_utf8 = OsStr::from_bytes(&[0xC3, 0x28]);
@@ -591,7 +1976,7 @@ mod test {
         dir.push(invalid_utf8);
         std::fs::create_dir_all(&dir).unwrap();
         std::fs::File::create(dir.join("test.xml")).unwrap();
-        let res = BundleBuilder::from_directory("test", &dir.parent().unwrap(), false, false);
+        let res = BundleBuilder::from_directory("test", dir.parent().unwrap(), false, false);
         let _ = std::fs::remove_file(dir.join("test.xml"));
         let _ = std::fs::remove_dir(&dir);
         std::fs::remove_dir(dir.parent().unwrap()).unwrap();
@@ -603,6 +1988,68 @@ mod test {
     }
 
     #[test]
+    #[cfg(all(unix, feature = "fs"))]
+    fn from_dir_invalid_file_names() {
+        use std::os::unix::ffi::OsStrExt;
+        let dir: PathBuf = ["test-data", "temp_invalid_names"].iter().collect();
+        let _ = std::fs::remove_dir_all(&dir);
+        let invalid_dir = dir.join(OsStr::from_bytes(b"dir\xC3("));
+        let invalid_file = dir.join(OsStr::from_bytes(b"100%\xFF.txt"));
+        std::fs::create_dir_all(&invalid_dir).unwrap();
+        std::fs::write(invalid_dir.join("a.txt"), "a").unwrap();
+        std::fs::write(&invalid_file, "b").unwrap();
+        std::fs::write(dir.join("100%.txt"), "c").unwrap();
+
+        let build = |policy: InvalidFileNamePolicy| {
+            BundleBuilder::from_directory_with_options(
+                "/test",
+                &dir,
+                &DirectoryOptions::new().with_invalid_file_names(policy),
+            )
+        };
+        let error = build(InvalidFileNamePolicy::Error);
+        let skip = build(InvalidFileNamePolicy::Skip);
+        let encode = build(InvalidFileNamePolicy::PercentEncode);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_matches!(error, Err(BuilderError::Utf8(..)));
+
+        let skip = skip.unwrap();
+        assert_eq!(skip.files.len(), 1);
+        assert_eq!(skip.files[0].key(), "/test/100%.txt");
+        assert_eq!(
+            skip.skipped_paths(),
+            [invalid_file.clone(), invalid_dir.join("a.txt")]
+        );
+
+        let encode = encode.unwrap();
+        let keys = encode
+            .files
+            .iter()
+            .map(|file| file.key())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                "/test/100%.txt",
+                "/test/100%25%FF.txt",
+                "/test/dir%C3(/a.txt"
+            ]
+        );
+        assert!(encode.skipped_paths().is_empty());
+
+        assert_eq!(
+            percent_encode(OsStr::from_bytes(b"\xF0\x9F\x92")),
+            "%F0%9F%92"
+        );
+        assert_eq!(
+            percent_encode(OsStr::from_bytes(b"\xE2\x82\xAC\xFF")),
+            "\u{20ac}%FF"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
     fn test_invalid_utf8_json() {
         use std::os::unix::ffi::OsStrExt;
         let invalid_utf8 = OsStr::from_bytes(&[0xC3, 0x28]);
@@ -629,6 +2076,7 @@ mod test {
             &path,
             false,
             &PreprocessOptions::empty(),
+            None,
         )
         .unwrap();
         println!("{:?}", file_data);
@@ -643,7 +2091,8 @@ mod test {
         let path = GRESOURCE_DIR.join("json").join("test.json");
         let mut options = PreprocessOptions::empty();
         options.to_pixdata = true;
-        let err = FileData::from_file("test.json".to_string(), &path, false, &options).unwrap_err();
+        let err =
+            FileData::from_file("test.json".to_string(), &path, false, &options, None).unwrap_err();
         assert_matches!(err, BuilderError::Unimplemented(_));
         assert!(format!("{}", err).contains("to-pixdata is deprecated"));
     }
@@ -658,6 +2107,7 @@ mod test {
                 path,
                 false,
                 &PreprocessOptions::xml_stripblanks(),
+                None,
             )
             .unwrap_err();
 
@@ -677,6 +2127,7 @@ mod test {
                 path.clone(),
                 false,
                 &PreprocessOptions::json_stripblanks(),
+                None,
             )
             .unwrap_err();
 
@@ -690,6 +2141,7 @@ mod test {
                 path,
                 false,
                 &PreprocessOptions::json_stripblanks(),
+                None,
             )
             .unwrap_err();
 
@@ -704,7 +2156,10 @@ mod test {
             None,
             false,
             &PreprocessOptions::json_stripblanks(),
+            None,
         )
+        .unwrap()
+        .finish(&HashMap::new(), &Compression::default())
         .unwrap();
 
         let json = std::str::from_utf8(&data.data).unwrap();
@@ -728,7 +2183,7 @@ mod test {
     }
 
     #[test]
-    #[cfg(unix)]
+    #[cfg(all(unix, feature = "fs"))]
     fn invalid_utf8_filename() {
         use std::os::unix::ffi::OsStrExt;
         let temp_path: PathBuf = ["test-data", "temp"].iter().collect();
@@ -738,8 +2193,7 @@ mod test {
         std::fs::create_dir_all(PathBuf::from(&temp_path)).unwrap();
         let _ = std::fs::File::create(&invalid_path).unwrap();
 
-        let res =
-            BundleBuilder::from_directory("test", &PathBuf::from(temp_path.clone()), false, false);
+        let res = BundleBuilder::from_directory("test", &temp_path, false, false);
 
         let _ = std::fs::remove_file(invalid_path);
         std::fs::remove_dir(temp_path).unwrap();
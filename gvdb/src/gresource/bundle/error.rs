@@ -7,6 +7,9 @@ pub enum BuilderError {
     /// An internal error occurred during creation of the GVDB file
     Gvdb(crate::write::Error),
 
+    /// The GResource XML file could not be loaded
+    Manifest(crate::gresource::XmlManifestError),
+
     /// I/O error
     Io(std::io::Error, Option<PathBuf>),
 
@@ -24,6 +27,35 @@ pub enum BuilderError {
 
     /// This feature is not implemented in gvdb-rs
     Unimplemented(String),
+
+    /// The same file key is used more than once when merging GResource XML files
+    DuplicateKey(String),
+
+    /// The prefix of a `<gresource>` element is not an absolute resource path
+    ///
+    /// A prefix must be empty or start with `/` and must not contain empty, `.` or `..`
+    /// components.
+    InvalidPrefix(String),
+
+    /// An error caused by an element of a GResource XML file
+    ///
+    /// The location points to the `<gresource>` or `<file>` element that caused the error.
+    Element(Box<BuilderError>, crate::gresource::XmlLocation),
+
+    /// A file uses a custom preprocessor that was not registered with
+    /// [`BundleBuilder::add_preprocessor`](crate::gresource::BundleBuilder::add_preprocessor)
+    UnknownPreprocessor(String, Option<PathBuf>),
+
+    /// A glob pattern of [`DirectoryOptions`](crate::gresource::DirectoryOptions) is invalid
+    #[cfg(feature = "fs")]
+    InvalidPattern(String, glob::PatternError),
+
+    /// A custom preprocessor returned an error
+    Preprocess(
+        String,
+        Box<dyn std::error::Error + Send + Sync>,
+        Option<PathBuf>,
+    ),
 }
 
 impl BuilderError {
@@ -36,6 +68,14 @@ impl BuilderError {
         let path = filename.map(|p| p.into());
         move |err| BuilderError::Io(err, path)
     }
+
+    /// Attach the location of the XML element that caused this error, if there is one
+    pub(crate) fn with_location(self, location: Option<&crate::gresource::XmlLocation>) -> Self {
+        match location {
+            Some(location) => BuilderError::Element(Box::new(self), location.clone()),
+            None => self,
+        }
+    }
 }
 
 impl std::error::Error for BuilderError {}
@@ -46,9 +86,16 @@ impl From<crate::write::Error> for BuilderError {
     }
 }
 
+impl From<crate::gresource::XmlManifestError> for BuilderError {
+    fn from(err: crate::gresource::XmlManifestError) -> Self {
+        Self::Manifest(err)
+    }
+}
+
 impl Display for BuilderError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
+            BuilderError::Manifest(err) => write!(f, "{}", err),
             BuilderError::Xml(err, path) => {
                 if let Some(path) = path {
                     write!(
@@ -95,6 +142,46 @@ impl Display for BuilderError {
             BuilderError::Unimplemented(err) => {
                 write!(f, "{}", err)
             }
+            BuilderError::UnknownPreprocessor(name, path) => {
+                if let Some(path) = path {
+                    write!(
+                        f,
+                        "Unknown preprocessor '{}' for file '{}'",
+                        name,
+                        path.display()
+                    )
+                } else {
+                    write!(f, "Unknown preprocessor '{}'", name)
+                }
+            }
+            BuilderError::Preprocess(name, err, path) => {
+                if let Some(path) = path {
+                    write!(
+                        f,
+                        "Error in preprocessor '{}' for file '{}': {}",
+                        name,
+                        path.display(),
+                        err
+                    )
+                } else {
+                    write!(f, "Error in preprocessor '{}': {}", name, err)
+                }
+            }
+            BuilderError::DuplicateKey(key) => {
+                write!(f, "Duplicate file key '{}' in GResource XML files", key)
+            }
+            BuilderError::InvalidPrefix(prefix) => {
+                write!(
+                    f,
+                    "Invalid prefix '{}', expected an absolute path like '/org/example'",
+                    prefix
+                )
+            }
+            #[cfg(feature = "fs")]
+            BuilderError::InvalidPattern(pattern, err) => {
+                write!(f, "Invalid glob pattern '{}': {}", pattern, err)
+            }
+            BuilderError::Element(err, location) => write!(f, "{}: {}", location, err),
             BuilderError::Gvdb(err) => {
                 write!(f, "Error while creating GVDB file: {:?}", err)
             }
@@ -143,5 +230,33 @@ mod test {
         assert!(format!("{}", err).contains("test_file"));
         let err = BuilderError::Xml(quick_xml::Error::TextNotFound, None);
         assert!(format!("{}", err).contains("XML"));
+
+        let err = BuilderError::from(crate::gresource::XmlManifestError::Io(
+            std::io::Error::from(std::io::ErrorKind::NotFound),
+            Some(PathBuf::from("test.gresource.xml")),
+        ));
+        assert!(format!("{}", err).contains("test.gresource.xml"));
+
+        let err = BuilderError::DuplicateKey("/app/test.css".to_string());
+        assert!(format!("{}", err).contains("/app/test.css"));
+
+        let err = BuilderError::InvalidPrefix("app".to_string());
+        assert!(format!("{}", err).contains("'app'"));
+
+        let err = BuilderError::UnknownPreprocessor(
+            "scss".to_string(),
+            Some(PathBuf::from("style.scss")),
+        );
+        assert!(format!("{}", err).contains("'scss' for file 'style.scss'"));
+
+        #[cfg(feature = "fs")]
+        {
+            let err =
+                BuilderError::InvalidPattern("[".to_string(), glob::Pattern::new("[").unwrap_err());
+            assert!(format!("{}", err).contains("pattern '['"));
+        }
+
+        let err = BuilderError::Preprocess("scss".to_string(), "Invalid syntax".into(), None);
+        assert!(format!("{}", err).contains("Invalid syntax"));
     }
 }
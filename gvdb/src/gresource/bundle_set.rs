@@ -0,0 +1,384 @@
+use crate::gresource::extractor::{ExtractorError, ExtractorResult};
+use crate::gresource::reader::{Bundle, ResourceInfo};
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+
+/// A directory that replaces the files below a resource path
+#[derive(Debug)]
+struct Overlay {
+    prefix: String,
+    directory: PathBuf,
+}
+
+impl Overlay {
+    /// The file in the overlay directory that replaces the resource at `path`
+    fn file_for_path(&self, path: &str) -> Option<PathBuf> {
+        let rest = path.strip_prefix(self.prefix.trim_end_matches('/'))?;
+        let rest = rest.strip_prefix('/')?;
+
+        // Only plain file names can be looked up, so the overlay can't be escaped with `..`
+        let relative = Path::new(rest);
+        if rest.is_empty()
+            || !relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return None;
+        }
+
+        Some(self.directory.join(relative))
+    }
+}
+
+/// Look up files in multiple GResource bundles and overlay directories
+///
+/// Like the resources registered with `g_resources_register`, the bundle that was added last
+/// takes precedence. Overlay directories are consulted before all bundles, in the order in which
+/// they were added, like the `G_RESOURCE_OVERLAYS` environment variable of GLib. This allows to
+/// replace the embedded resources e.g. for theming or in tests without rebuilding the bundles.
+///
+/// # Example
+///
+/// ```
+/// use std::path::PathBuf;
+/// use gvdb::gresource::{Bundle, BundleSet};
+/// use gvdb::read::File;
+///
+/// let file = File::from_file(&PathBuf::from("test-data/test3.gresource")).unwrap();
+///
+/// let mut set = BundleSet::new();
+/// set.add_bundle(Bundle::new(&file).unwrap());
+/// set.add_overlays("/gvdb/rs/test=/usr/share/my-theme").unwrap();
+///
+/// // Read from /usr/share/my-theme/test.css if it exists, otherwise from the bundle
+/// let css = set.lookup("/gvdb/rs/test/test.css").unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct BundleSet<'a, 'file> {
+    // Sorted by precedence, the bundle that was added last is consulted first
+    bundles: Vec<Bundle<'a, 'file>>,
+    overlays: Vec<Overlay>,
+}
+
+impl<'a, 'file> BundleSet<'a, 'file> {
+    /// Create a new set without any bundles or overlays
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a bundle that takes precedence over all bundles that were added before
+    pub fn add_bundle(&mut self, bundle: Bundle<'a, 'file>) {
+        self.bundles.insert(0, bundle);
+    }
+
+    /// Replace the files below the resource path `prefix` with the files in `directory`
+    ///
+    /// The resource `prefix/name` is read from `directory/name` if that file exists. Overlays are
+    /// consulted before all bundles, in the order in which they were added.
+    ///
+    /// Returns [`ExtractorError::Data`] if `prefix` does not start with `/`.
+    pub fn add_overlay(
+        &mut self,
+        prefix: &str,
+        directory: impl Into<PathBuf>,
+    ) -> ExtractorResult<()> {
+        if !prefix.starts_with('/') {
+            return Err(ExtractorError::Data(format!(
+                "Overlay prefix '{}' is not an absolute resource path",
+                prefix
+            )));
+        }
+
+        self.overlays.push(Overlay {
+            prefix: prefix.to_string(),
+            directory: directory.into(),
+        });
+
+        Ok(())
+    }
+
+    /// Add the overlays of a `G_RESOURCE_OVERLAYS` value
+    ///
+    /// The value is a list of `prefix=directory` entries, separated by the platform's search path
+    /// separator, like `/org/example/app=/home/user/overlay`. The directories must be absolute
+    /// paths.
+    ///
+    /// ```
+    /// # use gvdb::gresource::BundleSet;
+    /// let mut set = BundleSet::new();
+    /// if let Ok(overlays) = std::env::var("G_RESOURCE_OVERLAYS") {
+    ///     set.add_overlays(&overlays).unwrap();
+    /// }
+    /// ```
+    pub fn add_overlays(&mut self, overlays: &str) -> ExtractorResult<()> {
+        for entry in std::env::split_paths(overlays) {
+            let entry = entry.to_string_lossy();
+            if entry.is_empty() {
+                continue;
+            }
+
+            let Some((prefix, directory)) = entry.split_once('=') else {
+                return Err(ExtractorError::Data(format!(
+                    "Overlay '{}' is not in the format 'prefix=directory'",
+                    entry
+                )));
+            };
+
+            if !Path::new(directory).is_absolute() {
+                return Err(ExtractorError::Data(format!(
+                    "Overlay directory '{}' is not an absolute path",
+                    directory
+                )));
+            }
+
+            self.add_overlay(prefix, directory)?;
+        }
+
+        Ok(())
+    }
+
+    /// The file in an overlay directory that replaces the resource at `path`, if any
+    fn overlay_file(&self, path: &str) -> Option<PathBuf> {
+        self.overlays
+            .iter()
+            .filter_map(|overlay| overlay.file_for_path(path))
+            .find(|file| file.is_file())
+    }
+
+    /// Find the first bundle that contains `path` and apply `f` to it
+    fn find_in_bundles<T>(
+        &self,
+        path: &str,
+        f: impl Fn(&Bundle<'a, 'file>) -> ExtractorResult<T>,
+    ) -> ExtractorResult<T> {
+        for bundle in &self.bundles {
+            match f(bundle) {
+                Err(ExtractorError::Gvdb(err)) if err.is_key_not_found() => continue,
+                result => return result,
+            }
+        }
+
+        Err(ExtractorError::Gvdb(crate::read::Error::KeyNotFound(
+            path.to_string(),
+        )))
+    }
+
+    /// The size and flags of the file at `path`
+    ///
+    /// Files in overlay directories are never compressed and have no flags.
+    pub fn info(&self, path: &str) -> ExtractorResult<ResourceInfo> {
+        if let Some(file) = self.overlay_file(path) {
+            let metadata = std::fs::metadata(&file)
+                .map_err(ExtractorError::from_io_with_filename(Some(&file)))?;
+            let size = metadata.len().try_into().map_err(|_| {
+                ExtractorError::Data(format!(
+                    "Overlay file '{}' is larger than 4 GiB",
+                    file.display()
+                ))
+            })?;
+
+            return Ok(ResourceInfo::new(size, 0));
+        }
+
+        self.find_in_bundles(path, |bundle| bundle.info(path))
+    }
+
+    /// The data of the file at `path`
+    ///
+    /// The file is read from the first overlay directory that contains it, otherwise from the
+    /// bundle with the highest precedence that contains it. Returns
+    /// [`read::Error::KeyNotFound`](crate::read::Error::KeyNotFound) if no overlay or bundle
+    /// contains the file.
+    pub fn lookup(&self, path: &str) -> ExtractorResult<Cow<'a, [u8]>> {
+        if let Some(file) = self.overlay_file(path) {
+            let data =
+                std::fs::read(&file).map_err(ExtractorError::from_io_with_filename(Some(&file)))?;
+            return Ok(Cow::Owned(data));
+        }
+
+        self.find_in_bundles(path, |bundle| bundle.lookup(path))
+    }
+
+    /// The names of the immediate children of the directory at `path` in all bundles, sorted by
+    /// name
+    ///
+    /// Like `g_resources_enumerate_children`, overlay directories are not taken into account.
+    pub fn enumerate_children(&self, path: &str) -> ExtractorResult<Vec<String>> {
+        let mut children = BTreeSet::new();
+        let mut found = false;
+
+        for bundle in &self.bundles {
+            match bundle.enumerate_children(path) {
+                Ok(names) => {
+                    found = true;
+                    children.extend(names);
+                }
+                Err(ExtractorError::Gvdb(err)) if err.is_key_not_found() => continue,
+                Err(err) => return Err(err),
+            }
+        }
+
+        if !found {
+            return Err(ExtractorError::Gvdb(crate::read::Error::KeyNotFound(
+                path.to_string(),
+            )));
+        }
+
+        Ok(children.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gresource::{BundleBuilder, FileData, PreprocessOptions};
+    use crate::read::File;
+    use crate::test::TEST_FILE_3;
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    fn new_bundle_data(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let files = files
+            .iter()
+            .map(|(key, data)| {
+                FileData::new(
+                    key.to_string(),
+                    Cow::Owned(data.to_vec()),
+                    None,
+                    false,
+                    &PreprocessOptions::empty(),
+                    None,
+                )
+                .unwrap()
+            })
+            .collect();
+
+        BundleBuilder::from_file_data(files).build().unwrap()
+    }
+
+    #[test]
+    fn bundles() {
+        let base = File::from_file(&TEST_FILE_3).unwrap();
+        let override_data = new_bundle_data(&[
+            ("/gvdb/rs/test/test.css", b"override"),
+            ("/gvdb/rs/test/new.txt", b"new"),
+        ]);
+        let overrides = File::from_bytes(Cow::Owned(override_data)).unwrap();
+
+        let mut set = BundleSet::new();
+        assert_matches!(
+            set.lookup("/gvdb/rs/test/test.css"),
+            Err(ExtractorError::Gvdb(crate::read::Error::KeyNotFound(_)))
+        );
+
+        set.add_bundle(Bundle::new(&base).unwrap());
+        set.add_bundle(Bundle::new(&overrides).unwrap());
+
+        assert_eq!(
+            set.lookup("/gvdb/rs/test/test.css").unwrap(),
+            &b"override"[..]
+        );
+        assert_eq!(set.info("/gvdb/rs/test/test.css").unwrap().size(), 8);
+        assert_eq!(set.lookup("/gvdb/rs/test/new.txt").unwrap(), &b"new"[..]);
+        assert_eq!(
+            set.lookup("/gvdb/rs/test/json/test.json").unwrap(),
+            Bundle::new(&base)
+                .unwrap()
+                .lookup("/gvdb/rs/test/json/test.json")
+                .unwrap()
+        );
+        assert_matches!(
+            set.lookup("/gvdb/rs/test/missing"),
+            Err(ExtractorError::Gvdb(crate::read::Error::KeyNotFound(_)))
+        );
+        assert_matches!(
+            set.info("/gvdb/rs/test/"),
+            Err(ExtractorError::Gvdb(
+                crate::read::Error::WrongItemType { .. }
+            ))
+        );
+
+        assert_eq!(
+            set.enumerate_children("/gvdb/rs/test").unwrap(),
+            vec![
+                "icons/",
+                "json/",
+                "new.txt",
+                "online-symbolic.svg",
+                "test.css"
+            ]
+        );
+        assert_matches!(
+            set.enumerate_children("/missing"),
+            Err(ExtractorError::Gvdb(crate::read::Error::KeyNotFound(_)))
+        );
+    }
+
+    #[test]
+    fn overlays() {
+        let base = File::from_file(&TEST_FILE_3).unwrap();
+        let mut set = BundleSet::new();
+        set.add_bundle(Bundle::new(&base).unwrap());
+
+        let overlay = std::env::temp_dir().join(format!("gvdb-overlay-{}", std::process::id()));
+        std::fs::create_dir_all(overlay.join("json")).unwrap();
+        std::fs::write(overlay.join("test.css"), "overlay").unwrap();
+        std::fs::write(overlay.join("json").join("test.json"), "{}").unwrap();
+
+        let overlays =
+            std::env::join_paths([format!("/gvdb/rs/test/={}", overlay.display())]).unwrap();
+        set.add_overlays(overlays.to_str().unwrap()).unwrap();
+
+        assert_eq!(
+            set.lookup("/gvdb/rs/test/test.css").unwrap(),
+            &b"overlay"[..]
+        );
+        let info = set.info("/gvdb/rs/test/test.css").unwrap();
+        assert_eq!(info.size(), 7);
+        assert!(!info.is_compressed());
+        assert_eq!(
+            set.lookup("/gvdb/rs/test/json/test.json").unwrap(),
+            &b"{}"[..]
+        );
+
+        // Files that are not in the overlay directory are read from the bundle
+        assert!(
+            set.info("/gvdb/rs/test/online-symbolic.svg")
+                .unwrap()
+                .size()
+                > 0
+        );
+        // Overlay paths can't escape the directory
+        assert_eq!(
+            Overlay {
+                prefix: "/gvdb/rs/test".to_string(),
+                directory: overlay.clone(),
+            }
+            .file_for_path("/gvdb/rs/test/../test.css"),
+            None
+        );
+        // The prefix only matches complete path segments
+        assert_eq!(
+            Overlay {
+                prefix: "/gvdb/rs/test".to_string(),
+                directory: overlay.clone(),
+            }
+            .file_for_path("/gvdb/rs/tests/test.css"),
+            None
+        );
+
+        assert_matches!(
+            set.add_overlay("relative", &overlay),
+            Err(ExtractorError::Data(_))
+        );
+        assert_matches!(set.add_overlays("/prefix"), Err(ExtractorError::Data(_)));
+        assert_matches!(
+            set.add_overlays("/prefix=relative"),
+            Err(ExtractorError::Data(_))
+        );
+
+        std::fs::remove_dir_all(&overlay).unwrap();
+    }
+}
@@ -0,0 +1,102 @@
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use std::borrow::Cow;
+use std::io::{Read, Write};
+use std::sync::OnceLock;
+
+/// Compressed GVDB data that is decompressed once on first access
+///
+/// This is used by the `include_gresource_from_dir_compressed!` macro of
+/// [gvdb-macros](https://crates.io/crates/gvdb-macros) to keep large bundles small in the binary.
+/// The data is compressed with zlib, see [`CompressedBytes::compress`]. The decompressed data is
+/// kept in memory for the rest of the program.
+///
+/// ```
+/// # use std::borrow::Cow;
+/// use gvdb::gresource::CompressedBytes;
+///
+/// let compressed = CompressedBytes::compress(b"GVDB data").leak();
+/// let bytes: &'static CompressedBytes = Box::leak(Box::new(CompressedBytes::new(compressed)));
+///
+/// assert_eq!(&*bytes.get(), b"GVDB data");
+/// assert!(matches!(bytes.get(), Cow::Borrowed(_)));
+/// ```
+pub struct CompressedBytes {
+    compressed: &'static [u8],
+    data: OnceLock<Vec<u8>>,
+}
+
+impl CompressedBytes {
+    /// Wrap the zlib `compressed` data
+    pub const fn new(compressed: &'static [u8]) -> Self {
+        Self {
+            compressed,
+            data: OnceLock::new(),
+        }
+    }
+
+    /// Compress `data` in the format expected by [`CompressedBytes::new`]
+    pub fn compress(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder
+            .write_all(data)
+            .and_then(|_| encoder.finish())
+            .expect("Writing to a Vec never fails")
+    }
+
+    /// The compressed data
+    pub fn compressed(&self) -> &'static [u8] {
+        self.compressed
+    }
+
+    /// The decompressed data
+    ///
+    /// The data is decompressed on the first call, later calls borrow the same data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the compressed data is corrupted. The data is created by a macro at compile
+    /// time, so this can only happen if the binary itself is damaged.
+    pub fn get(&'static self) -> Cow<'static, [u8]> {
+        Cow::Borrowed(self.data.get_or_init(|| {
+            let mut data = Vec::new();
+            ZlibDecoder::new(self.compressed)
+                .read_to_end(&mut data)
+                .expect("Embedded GVDB data is not valid zlib data");
+            data
+        }))
+    }
+}
+
+impl std::fmt::Debug for CompressedBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompressedBytes")
+            .field("compressed", &self.compressed.len())
+            .field("decompressed", &self.data.get().map(|data| data.len()))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::File;
+    use crate::test::{assert_is_file_1, TEST_FILE_1};
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    #[test]
+    fn roundtrip() {
+        let data = std::fs::read(&*TEST_FILE_1).unwrap();
+        let compressed = CompressedBytes::compress(&data);
+        assert!(compressed.len() < data.len());
+
+        let bytes: &'static CompressedBytes =
+            Box::leak(Box::new(CompressedBytes::new(compressed.leak())));
+        assert_eq!(bytes.get(), data);
+        assert!(format!("{:?}", bytes).contains(&format!("Some({})", data.len())));
+
+        let file = File::from_bytes(bytes.get()).unwrap();
+        assert_is_file_1(&file);
+    }
+}
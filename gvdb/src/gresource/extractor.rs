@@ -0,0 +1,353 @@
+mod error;
+
+pub use error::*;
+
+use crate::gresource::Bundle;
+use crate::read::{File, HashItemType};
+use std::path::{Component, Path, PathBuf};
+
+/// The file name of the manifest written by [`BundleExtractor::extract_to_directory`]
+const MANIFEST_FILENAME: &str = "resources.gresource.xml";
+
+/// A file contained in a GResource bundle
+///
+/// The data is already decompressed and doesn't contain the zero-termination that is added to
+/// uncompressed files in the GResource format.
+#[derive(Debug)]
+pub struct ExtractedFile {
+    key: String,
+    data: Vec<u8>,
+    compressed: bool,
+}
+
+impl ExtractedFile {
+    /// The key of this file in the GResource bundle
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The uncompressed file data
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consume the file and return the uncompressed file data
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Whether the file is stored compressed in the GResource bundle
+    pub fn is_compressed(&self) -> bool {
+        self.compressed
+    }
+}
+
+/// Extract the files of a GResource bundle
+///
+/// This is the inverse of [`BundleBuilder`](crate::gresource::BundleBuilder). The files can be
+/// written to a directory together with a GResource XML manifest that can be used to build the
+/// bundle again.
+///
+/// Preprocessing options and aliases can't be reconstructed. The manifest uses the prefix `/` and
+/// refers to every file by its key.
+///
+/// # Example
+///
+/// ```
+/// use std::path::PathBuf;
+/// use gvdb::gresource::BundleExtractor;
+/// use gvdb::read::File;
+///
+/// let file = File::from_file(&PathBuf::from("test-data/test3.gresource")).unwrap();
+/// let extractor = BundleExtractor::new(&file).unwrap();
+/// for file in extractor.files().unwrap() {
+///     println!("{}: {} bytes", file.key(), file.data().len());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct BundleExtractor<'a, 'file> {
+    bundle: Bundle<'a, 'file>,
+}
+
+impl<'a, 'file> BundleExtractor<'a, 'file> {
+    /// Create a new extractor for the GResource bundle `file`
+    pub fn new(file: &'a File<'file>) -> ExtractorResult<Self> {
+        Ok(Self {
+            bundle: Bundle::new(file)?,
+        })
+    }
+
+    /// All files contained in the bundle, sorted by key
+    pub fn files(&self) -> ExtractorResult<Vec<ExtractedFile>> {
+        let mut files = Vec::new();
+
+        for (key, item) in self.bundle.table.keys_and_items()? {
+            // Containers only represent the directory structure
+            if item.typ()? == HashItemType::Value {
+                files.push(self.file(key)?);
+            }
+        }
+
+        files.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(files)
+    }
+
+    fn file(&self, key: String) -> ExtractorResult<ExtractedFile> {
+        let (info, data) = self.bundle.lookup_with_info(&key)?;
+
+        Ok(ExtractedFile {
+            key,
+            data: data.into_owned(),
+            compressed: info.is_compressed(),
+        })
+    }
+
+    /// Reconstruct a GResource XML manifest for the bundle
+    ///
+    /// The file names in the manifest are relative to the directory the files are extracted to.
+    pub fn manifest(&self) -> ExtractorResult<String> {
+        Self::manifest_for_files(&self.files()?)
+    }
+
+    fn manifest_for_files(files: &[ExtractedFile]) -> ExtractorResult<String> {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<gresources>\n");
+        xml.push_str("  <gresource prefix=\"/\">\n");
+
+        for file in files {
+            let filename = relative_path(&file.key)?.to_string_lossy();
+            let compressed = if file.compressed {
+                " compressed=\"true\""
+            } else {
+                ""
+            };
+
+            xml.push_str(&format!(
+                "    <file{}>{}</file>\n",
+                compressed,
+                quick_xml::escape::escape(&filename)
+            ));
+        }
+
+        xml.push_str("  </gresource>\n</gresources>\n");
+        Ok(xml)
+    }
+
+    /// Write all files of the bundle to `directory`, followed by a GResource XML manifest
+    ///
+    /// The manifest is called `resources.gresource.xml`, its path is returned.
+    pub fn extract_to_directory(&self, directory: &Path) -> ExtractorResult<PathBuf> {
+        let files = self.files()?;
+        let manifest = Self::manifest_for_files(&files)?;
+
+        let manifest_path = directory.join(MANIFEST_FILENAME);
+        if files.iter().any(|file| {
+            relative_path(&file.key).is_ok_and(|path| path == Path::new(MANIFEST_FILENAME))
+        }) {
+            return Err(ExtractorError::Data(format!(
+                "The bundle contains a file that conflicts with the manifest '{}'",
+                MANIFEST_FILENAME
+            )));
+        }
+
+        std::fs::create_dir_all(directory)
+            .map_err(ExtractorError::from_io_with_filename(Some(directory)))?;
+
+        for file in &files {
+            let path = directory.join(relative_path(&file.key)?);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(ExtractorError::from_io_with_filename(Some(parent)))?;
+            }
+
+            std::fs::write(&path, &file.data)
+                .map_err(ExtractorError::from_io_with_filename(Some(&path)))?;
+        }
+
+        std::fs::write(&manifest_path, manifest)
+            .map_err(ExtractorError::from_io_with_filename(Some(&manifest_path)))?;
+
+        Ok(manifest_path)
+    }
+}
+
+/// Convert the GResource `key` into a relative file path
+///
+/// Keys that could escape the target directory are rejected.
+fn relative_path(key: &str) -> ExtractorResult<&Path> {
+    let path =
+        Path::new(key.strip_prefix('/').ok_or_else(|| {
+            ExtractorError::Data(format!("Key '{}' does not start with '/'", key))
+        })?);
+
+    if path.as_os_str().is_empty()
+        || !path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(ExtractorError::Data(format!(
+            "Key '{}' can't be used as a file path",
+            key
+        )));
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gresource::bundle::FLAG_COMPRESSED;
+    use crate::gresource::{BundleBuilder, FileData, PreprocessOptions, XmlManifest};
+    use crate::test::{assert_is_file_3, GRESOURCE_DIR, TEST_FILE_3};
+    use crate::write::{FileWriter, HashTableBuilder};
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gvdb-rs-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn files() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let extractor = BundleExtractor::new(&file).unwrap();
+        println!("{:?}", extractor);
+
+        let files = extractor.files().unwrap();
+        let keys: Vec<&str> = files.iter().map(|file| file.key()).collect();
+        assert_eq!(
+            keys,
+            [
+                "/gvdb/rs/test/icons/scalable/actions/send-symbolic.svg",
+                "/gvdb/rs/test/json/test.json",
+                "/gvdb/rs/test/online-symbolic.svg",
+                "/gvdb/rs/test/test.css",
+            ]
+        );
+
+        let send_symbolic = &files[0];
+        assert!(send_symbolic.is_compressed());
+        let reference =
+            std::fs::read(GRESOURCE_DIR.join("icons/scalable/actions/send-symbolic.svg")).unwrap();
+        assert_eq!(send_symbolic.data(), reference);
+
+        let online_symbolic = &files[2];
+        assert!(!online_symbolic.is_compressed());
+        let reference =
+            std::fs::read(GRESOURCE_DIR.join("icons/scalable/actions/online-symbolic.svg"))
+                .unwrap();
+        assert_eq!(online_symbolic.data(), reference);
+
+        let json = files.into_iter().nth(1).unwrap().into_data();
+        assert_eq!(json, b"[\"test_string\",42,{\"bool\":true}]\n");
+    }
+
+    #[test]
+    fn manifest() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let extractor = BundleExtractor::new(&file).unwrap();
+        let manifest = extractor.manifest().unwrap();
+        assert!(manifest.contains("<file compressed=\"true\">gvdb/rs/test/test.css</file>"));
+        assert!(manifest.contains("<file>gvdb/rs/test/online-symbolic.svg</file>"));
+
+        let doc = XmlManifest::from_string(&PathBuf::from("/"), manifest).unwrap();
+        assert_eq!(doc.gresources.len(), 1);
+        assert_eq!(doc.gresources[0].files.len(), 4);
+    }
+
+    #[test]
+    fn extract_to_directory() {
+        let dir = temp_dir("extract");
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let extractor = BundleExtractor::new(&file).unwrap();
+        let manifest_path = extractor.extract_to_directory(&dir).unwrap();
+        assert_eq!(manifest_path, dir.join("resources.gresource.xml"));
+
+        let css = std::fs::read(dir.join("gvdb/rs/test/test.css")).unwrap();
+        assert_eq!(css, std::fs::read(GRESOURCE_DIR.join("test.css")).unwrap());
+
+        // Build the bundle again from the extracted files
+        let doc = XmlManifest::from_file(&manifest_path).unwrap();
+        let data = BundleBuilder::from_xml(doc).unwrap().build().unwrap();
+        let root = File::from_bytes(Cow::Owned(data)).unwrap();
+        assert_is_file_3(&root);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_key() {
+        for key in ["/../evil", "relative"] {
+            let file_data = FileData::new(
+                key.to_string(),
+                Cow::Borrowed(b"test"),
+                None,
+                false,
+                &PreprocessOptions::empty(),
+                None,
+            )
+            .unwrap();
+            let data = BundleBuilder::from_file_data(vec![file_data])
+                .build()
+                .unwrap();
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            let extractor = BundleExtractor::new(&file).unwrap();
+
+            let dir = temp_dir("invalid-key");
+            let err = extractor.extract_to_directory(&dir).unwrap_err();
+            assert_matches!(err, ExtractorError::Data(_));
+            assert!(!dir.exists());
+        }
+    }
+
+    #[test]
+    fn manifest_conflict() {
+        let file_data = FileData::new(
+            "/resources.gresource.xml".to_string(),
+            Cow::Borrowed(b"test"),
+            None,
+            false,
+            &PreprocessOptions::empty(),
+            None,
+        )
+        .unwrap();
+        let data = BundleBuilder::from_file_data(vec![file_data])
+            .build()
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let extractor = BundleExtractor::new(&file).unwrap();
+
+        let dir = temp_dir("manifest-conflict");
+        let err = extractor.extract_to_directory(&dir).unwrap_err();
+        assert_matches!(err, ExtractorError::Data(_));
+    }
+
+    #[test]
+    fn invalid_data() {
+        let mut table_builder = HashTableBuilder::new();
+        table_builder
+            .insert("/size", (5u32, 0u32, vec![1u8, 2, 0]))
+            .unwrap();
+        table_builder
+            .insert("/compressed", (3u32, FLAG_COMPRESSED, vec![1u8, 2, 3]))
+            .unwrap();
+        table_builder.insert("/string", "test").unwrap();
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let extractor = BundleExtractor::new(&file).unwrap();
+
+        let err = extractor.file("/size".to_string()).unwrap_err();
+        assert_matches!(err, ExtractorError::Data(_));
+        let err = extractor.file("/compressed".to_string()).unwrap_err();
+        assert_matches!(err, ExtractorError::Io(_, None));
+        let err = extractor.file("/string".to_string()).unwrap_err();
+        assert_matches!(err, ExtractorError::Gvdb(_));
+        assert_matches!(extractor.files(), Err(_));
+    }
+}
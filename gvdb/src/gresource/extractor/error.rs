@@ -0,0 +1,87 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::path::PathBuf;
+
+/// Error type for extracting a GResource bundle
+#[non_exhaustive]
+pub enum ExtractorError {
+    /// An error occurred while reading the GVDB file
+    Gvdb(crate::read::Error),
+
+    /// I/O error
+    Io(std::io::Error, Option<PathBuf>),
+
+    /// The GResource data is not in the expected format
+    Data(String),
+}
+
+impl ExtractorError {
+    pub(crate) fn from_io_with_filename<P>(
+        filename: Option<P>,
+    ) -> impl FnOnce(std::io::Error) -> ExtractorError
+    where
+        P: Into<PathBuf>,
+    {
+        let path = filename.map(|p| p.into());
+        move |err| ExtractorError::Io(err, path)
+    }
+}
+
+impl std::error::Error for ExtractorError {}
+
+impl From<crate::read::Error> for ExtractorError {
+    fn from(err: crate::read::Error) -> Self {
+        Self::Gvdb(err)
+    }
+}
+
+impl Display for ExtractorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtractorError::Gvdb(err) => {
+                write!(f, "Error while reading GVDB file: {}", err)
+            }
+            ExtractorError::Io(err, path) => {
+                if let Some(path) = path {
+                    write!(f, "I/O error for file '{}': {}", path.display(), err)
+                } else {
+                    write!(f, "I/O error: {}", err)
+                }
+            }
+            ExtractorError::Data(msg) => {
+                write!(f, "Invalid GResource data: {}", msg)
+            }
+        }
+    }
+}
+
+impl Debug for ExtractorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+/// Result type for [`ExtractorError`]
+pub type ExtractorResult<T> = std::result::Result<T, ExtractorError>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from() {
+        let io_res = std::fs::File::open("test/invalid_file_name");
+        let err = ExtractorError::Io(io_res.unwrap_err(), None);
+        assert!(format!("{}", err).contains("I/O"));
+
+        let io_res = std::fs::File::open("test/invalid_file_name");
+        let err = ExtractorError::from_io_with_filename(Some("test"))(io_res.unwrap_err());
+        assert!(format!("{}", err).contains("test"));
+
+        let reader_error = crate::read::Error::Data("test".to_string());
+        let err = ExtractorError::from(reader_error);
+        assert!(format!("{}", err).contains("test"));
+
+        let err = ExtractorError::Data("size mismatch".to_string());
+        assert!(format!("{:?}", err).contains("size mismatch"));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::gresource::extractor::{ExtractorError, ExtractorResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The path of the metadata entry in a GResource bundle
+///
+/// See [`BundleMetadata`].
+pub const METADATA_PATH: &str = "/.gresource/meta";
+
+/// Provenance information that is stored in a GResource bundle
+///
+/// The metadata is stored as JSON file at [`METADATA_PATH`] when it is set with
+/// [`BundleBuilder::set_metadata`](crate::gresource::BundleBuilder::set_metadata), and can be read
+/// back with [`Bundle::metadata`](crate::gresource::Bundle::metadata). It records the version of
+/// gvdb-rs that created the bundle, an optional build timestamp and arbitrary entries.
+///
+/// The build timestamp is not set by default, to keep builds reproducible.
+///
+/// ```
+/// # use std::borrow::Cow;
+/// use gvdb::gresource::{Bundle, BundleBuilder, BundleMetadata};
+/// use gvdb::read::File;
+///
+/// let mut builder = BundleBuilder::from_file_data(Vec::new());
+/// builder.set_metadata(Some(
+///     BundleMetadata::new()
+///         .with_timestamp(1_700_000_000)
+///         .with_entry("license", "MIT"),
+/// ));
+/// let data = builder.build().unwrap();
+///
+/// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+/// let metadata = Bundle::new(&file).unwrap().metadata().unwrap().unwrap();
+/// assert!(metadata.creator().starts_with("gvdb-rs "));
+/// assert_eq!(metadata.timestamp(), Some(1_700_000_000));
+/// assert_eq!(metadata.entry("license"), Some("MIT"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleMetadata {
+    creator: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timestamp: Option<u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    entries: BTreeMap<String, String>,
+}
+
+impl BundleMetadata {
+    /// Create metadata that only records the version of gvdb-rs
+    pub fn new() -> Self {
+        Self {
+            creator: concat!("gvdb-rs ", env!("CARGO_PKG_VERSION")).to_string(),
+            timestamp: None,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Record the build time in seconds since the UNIX epoch
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Record the current time as build time
+    ///
+    /// If the `SOURCE_DATE_EPOCH` environment variable is set, its value is used instead, as
+    /// recommended by <https://reproducible-builds.org/specs/source-date-epoch/>.
+    pub fn with_current_time(self) -> Self {
+        let timestamp = std::env::var("SOURCE_DATE_EPOCH")
+            .ok()
+            .and_then(|epoch| epoch.parse().ok())
+            .unwrap_or_else(|| {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or(0)
+            });
+        self.with_timestamp(timestamp)
+    }
+
+    /// Add the entry `key` with `value`, replacing a previous entry with the same key
+    pub fn with_entry(mut self, key: &str, value: &str) -> Self {
+        self.entries.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// The name and version of the library that created the bundle
+    pub fn creator(&self) -> &str {
+        &self.creator
+    }
+
+    /// The build time in seconds since the UNIX epoch, if it was recorded
+    pub fn timestamp(&self) -> Option<u64> {
+        self.timestamp
+    }
+
+    /// The value of the entry `key`
+    pub fn entry(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(|value| value.as_str())
+    }
+
+    /// All entries, sorted by key
+    pub fn entries(&self) -> &BTreeMap<String, String> {
+        &self.entries
+    }
+
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut data = serde_json::to_vec(self).expect("Metadata can always be serialized");
+        data.push(b'\n');
+        data
+    }
+
+    pub(crate) fn from_bytes(data: &[u8]) -> ExtractorResult<Self> {
+        serde_json::from_slice(data).map_err(|err| {
+            ExtractorError::Data(format!("Invalid metadata at '{}': {}", METADATA_PATH, err))
+        })
+    }
+}
+
+impl Default for BundleMetadata {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    #[test]
+    fn roundtrip() {
+        let metadata = BundleMetadata::new();
+        assert_eq!(metadata.timestamp(), None);
+        assert!(metadata.entries().is_empty());
+        assert_eq!(
+            String::from_utf8(metadata.to_bytes()).unwrap(),
+            format!(
+                "{{\"creator\":\"gvdb-rs {}\"}}\n",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+
+        let metadata = BundleMetadata::new()
+            .with_timestamp(42)
+            .with_entry("z", "last")
+            .with_entry("a", "first")
+            .with_entry("z", "replaced");
+        assert_eq!(
+            metadata.entries().keys().collect::<Vec<_>>(),
+            vec!["a", "z"]
+        );
+        assert_eq!(metadata.entry("z"), Some("replaced"));
+        assert_eq!(metadata.entry("missing"), None);
+        assert_eq!(
+            BundleMetadata::from_bytes(&metadata.to_bytes()).unwrap(),
+            metadata
+        );
+
+        assert!(BundleMetadata::new()
+            .with_current_time()
+            .timestamp()
+            .is_some());
+        assert_matches!(
+            BundleMetadata::from_bytes(b"{}"),
+            Err(ExtractorError::Data(_))
+        );
+    }
+}
@@ -0,0 +1,455 @@
+use crate::gresource::bundle::{FLAG_COMPRESSED, FLAG_COMPRESSED_ZSTD};
+use crate::gresource::extractor::{ExtractorError, ExtractorResult};
+use crate::gresource::metadata::{BundleMetadata, METADATA_PATH};
+use crate::read::{File, HashItemType, HashTable};
+use flate2::read::ZlibDecoder;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::io::Read;
+use std::mem::size_of;
+use std::path::PathBuf;
+
+/// Metadata of a file in a GResource bundle
+///
+/// Returned by [`Bundle::info`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ResourceInfo {
+    size: u32,
+    flags: u32,
+}
+
+impl ResourceInfo {
+    pub(super) fn new(size: u32, flags: u32) -> Self {
+        Self { size, flags }
+    }
+
+    /// The uncompressed size of the file in bytes
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// The flags of the file
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Whether the file is stored compressed, with zlib or zstd
+    pub fn is_compressed(&self) -> bool {
+        self.flags & (FLAG_COMPRESSED | FLAG_COMPRESSED_ZSTD) != 0
+    }
+}
+
+/// The value stored for a file in a GResource bundle
+///
+/// This is the `(uuay)` tuple of the uncompressed size, the flags and the data of the file. The
+/// data is borrowed from the GVDB file. Uncompressed data is zero-terminated, compressed data is
+/// stored as zlib stream, or as zstd frame with [`FLAG_COMPRESSED_ZSTD`].
+///
+/// ```
+/// use std::path::PathBuf;
+/// use gvdb::gresource::ResourceData;
+/// use gvdb::read::File;
+///
+/// let file = File::from_file(&PathBuf::from("test-data/test3.gresource")).unwrap();
+/// let table = file.hash_table().unwrap();
+///
+/// let svg = ResourceData::lookup(&table, "/gvdb/rs/test/online-symbolic.svg").unwrap();
+/// let content = svg.content().unwrap();
+/// println!("{}", std::str::from_utf8(&content).unwrap());
+///
+/// let css = ResourceData::lookup_decompressed(&table, "/gvdb/rs/test/test.css").unwrap();
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, zvariant::Type)]
+pub struct ResourceData<'a> {
+    size: u32,
+    flags: u32,
+    data: &'a [u8],
+}
+
+impl<'a> ResourceData<'a> {
+    /// Create a new value from the uncompressed `size`, the `flags` and the stored `data`
+    pub fn new(size: u32, flags: u32, data: &'a [u8]) -> Self {
+        Self { size, flags, data }
+    }
+
+    /// Read the value of the file at `path` from the root hash table of a GResource bundle
+    pub fn lookup(table: &HashTable<'a, '_>, path: &str) -> ExtractorResult<Self> {
+        Ok(table.get::<Self>(path)?)
+    }
+
+    /// Read the decompressed content of the file at `path` from the root hash table of a
+    /// GResource bundle
+    ///
+    /// See [`ResourceData::content`].
+    pub fn lookup_decompressed(
+        table: &HashTable<'a, '_>,
+        path: &str,
+    ) -> ExtractorResult<Cow<'a, [u8]>> {
+        Self::lookup(table, path)?.content()
+    }
+
+    /// The uncompressed size of the file in bytes
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// The flags of the file
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Whether the file is stored compressed, with zlib or zstd
+    pub fn is_compressed(&self) -> bool {
+        self.flags & (FLAG_COMPRESSED | FLAG_COMPRESSED_ZSTD) != 0
+    }
+
+    /// The data as stored in the bundle, possibly compressed and zero-terminated
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The size and flags of the file
+    pub fn info(&self) -> ResourceInfo {
+        ResourceInfo {
+            size: self.size,
+            flags: self.flags,
+        }
+    }
+
+    /// The content of the file
+    ///
+    /// Compressed data is decompressed. Uncompressed data is borrowed from the file, without the
+    /// zero-termination. Returns [`ExtractorError::Data`] if the size of the content does not
+    /// match [`ResourceData::size`], or if the data is zstd compressed and the `zstd` feature is
+    /// not enabled.
+    pub fn content(&self) -> ExtractorResult<Cow<'a, [u8]>> {
+        let content = if self.flags & FLAG_COMPRESSED_ZSTD != 0 {
+            Cow::Owned(self.decompress_zstd()?)
+        } else if self.is_compressed() {
+            let mut decompressed = Vec::with_capacity(self.size as usize);
+            ZlibDecoder::new(self.data)
+                .read_to_end(&mut decompressed)
+                .map_err(ExtractorError::from_io_with_filename(None::<PathBuf>))?;
+            Cow::Owned(decompressed)
+        } else {
+            // Uncompressed data is zero-terminated
+            match self.data.split_last() {
+                Some((0, rest)) => Cow::Borrowed(rest),
+                _ => Cow::Borrowed(self.data),
+            }
+        };
+
+        if content.len() != self.size as usize {
+            return Err(ExtractorError::Data(format!(
+                "File has a size of {} bytes, but {} bytes were expected",
+                content.len(),
+                self.size
+            )));
+        }
+
+        Ok(content)
+    }
+
+    #[cfg(feature = "zstd")]
+    fn decompress_zstd(&self) -> ExtractorResult<Vec<u8>> {
+        zstd::decode_all(self.data).map_err(ExtractorError::from_io_with_filename(None::<PathBuf>))
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    fn decompress_zstd(&self) -> ExtractorResult<Vec<u8>> {
+        Err(ExtractorError::Data(
+            "File is zstd compressed, which requires the 'zstd' feature".to_string(),
+        ))
+    }
+}
+
+/// Read files from a GResource bundle
+///
+/// Compressed files are decompressed automatically, like with `g_resource_lookup_data`.
+///
+/// # Example
+///
+/// ```
+/// use std::path::PathBuf;
+/// use gvdb::gresource::Bundle;
+/// use gvdb::read::File;
+///
+/// let file = File::from_file(&PathBuf::from("test-data/test3.gresource")).unwrap();
+/// let bundle = Bundle::new(&file).unwrap();
+///
+/// let info = bundle.info("/gvdb/rs/test/test.css").unwrap();
+/// assert!(info.is_compressed());
+///
+/// let css = bundle.lookup("/gvdb/rs/test/test.css").unwrap();
+/// assert_eq!(css.len(), info.size() as usize);
+/// ```
+#[derive(Debug)]
+pub struct Bundle<'a, 'file> {
+    pub(super) table: HashTable<'a, 'file>,
+}
+
+impl<'a, 'file> Bundle<'a, 'file> {
+    /// Create a new reader for the GResource bundle `file`
+    pub fn new(file: &'a File<'file>) -> ExtractorResult<Self> {
+        Ok(Self {
+            table: file.hash_table()?,
+        })
+    }
+
+    /// The size and flags of the file at `path`
+    pub fn info(&self, path: &str) -> ExtractorResult<ResourceInfo> {
+        Ok(ResourceData::lookup(&self.table, path)?.info())
+    }
+
+    /// The data of the file at `path`
+    ///
+    /// Compressed data is decompressed. Uncompressed data is borrowed from the file, without the
+    /// zero-termination that is added to uncompressed files in the GResource format.
+    pub fn lookup(&self, path: &str) -> ExtractorResult<Cow<'a, [u8]>> {
+        Ok(self.lookup_with_info(path)?.1)
+    }
+
+    /// The names of the immediate children of the directory at `path`, sorted by name
+    ///
+    /// Like `g_resources_enumerate_children`, a `/` is appended to `path` if it is missing. The
+    /// names of subdirectories end with `/`.
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use gvdb::gresource::Bundle;
+    /// use gvdb::read::File;
+    ///
+    /// let file = File::from_file(&PathBuf::from("test-data/test3.gresource")).unwrap();
+    /// let bundle = Bundle::new(&file).unwrap();
+    ///
+    /// let children = bundle.enumerate_children("/gvdb/rs/test/json").unwrap();
+    /// assert_eq!(children, vec!["test.json"]);
+    /// ```
+    pub fn enumerate_children(&self, path: &str) -> ExtractorResult<Vec<String>> {
+        let mut path = path.to_string();
+        if !path.ends_with('/') {
+            path.push('/');
+        }
+
+        let (typ, data) = self.table.get_raw(&path)?;
+        if typ != HashItemType::Container {
+            return Err(ExtractorError::Data(format!(
+                "'{}' is not a directory",
+                path
+            )));
+        }
+
+        let mut children = Vec::with_capacity(data.len() / size_of::<u32>());
+        for chunk in data.chunks_exact(size_of::<u32>()) {
+            let index = u32::from_le_bytes(chunk.try_into().unwrap());
+            let item = self.table.get_hash_item_for_index(index as usize)?;
+            children.push(self.table.key_for_item(&item)?.to_string());
+        }
+
+        children.sort();
+        Ok(children)
+    }
+
+    /// The provenance metadata of the bundle, if it has any
+    ///
+    /// See [`BundleMetadata`].
+    pub fn metadata(&self) -> ExtractorResult<Option<BundleMetadata>> {
+        match self.lookup(METADATA_PATH) {
+            Ok(data) => BundleMetadata::from_bytes(&data).map(Some),
+            Err(ExtractorError::Gvdb(err)) if err.is_key_not_found() => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// The metadata and data of the file at `path`
+    pub(super) fn lookup_with_info(
+        &self,
+        path: &str,
+    ) -> ExtractorResult<(ResourceInfo, Cow<'a, [u8]>)> {
+        let resource = ResourceData::lookup(&self.table, path)?;
+        let data = resource.content().map_err(|err| match err {
+            ExtractorError::Data(message) => {
+                ExtractorError::Data(format!("'{}': {}", path, message))
+            }
+            err => err,
+        })?;
+
+        Ok((resource.info(), data))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gresource::{BundleBuilder, FileData, PreprocessOptions};
+    use crate::test::{GRESOURCE_DIR, TEST_FILE_3};
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    #[test]
+    fn lookup() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let bundle = Bundle::new(&file).unwrap();
+
+        let css = std::fs::read(GRESOURCE_DIR.join("test.css")).unwrap();
+        let info = bundle.info("/gvdb/rs/test/test.css").unwrap();
+        assert!(info.is_compressed());
+        assert_eq!(info.flags(), FLAG_COMPRESSED);
+        assert_eq!(info.size() as usize, css.len());
+        assert_matches!(bundle.lookup("/gvdb/rs/test/test.css").unwrap(), Cow::Owned(data) if data == css);
+
+        let info = bundle.info("/gvdb/rs/test/json/test.json").unwrap();
+        assert!(!info.is_compressed());
+        let json = bundle.lookup("/gvdb/rs/test/json/test.json").unwrap();
+        assert_matches!(json, Cow::Borrowed(_));
+        assert_eq!(json.len(), info.size() as usize);
+        assert_eq!(json.last(), Some(&b'\n'));
+
+        assert_matches!(
+            bundle.lookup("/gvdb/rs/test/missing"),
+            Err(ExtractorError::Gvdb(crate::read::Error::KeyNotFound(_)))
+        );
+        assert_matches!(
+            bundle.info("/gvdb/rs/test/"),
+            Err(ExtractorError::Gvdb(
+                crate::read::Error::WrongItemType { .. }
+            ))
+        );
+    }
+
+    #[test]
+    fn resource_data() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let table = file.hash_table().unwrap();
+
+        let css = std::fs::read(GRESOURCE_DIR.join("test.css")).unwrap();
+        let data = ResourceData::lookup(&table, "/gvdb/rs/test/test.css").unwrap();
+        assert!(data.is_compressed());
+        assert_eq!(data.size() as usize, css.len());
+        assert_ne!(data.data(), &css[..]);
+        assert_eq!(data.content().unwrap(), &css[..]);
+        assert_eq!(
+            ResourceData::lookup_decompressed(&table, "/gvdb/rs/test/test.css").unwrap(),
+            &css[..]
+        );
+
+        let json = ResourceData::lookup(&table, "/gvdb/rs/test/json/test.json").unwrap();
+        assert_eq!(json.flags(), 0);
+        assert_eq!(json.data().last(), Some(&0));
+        assert_matches!(json.content().unwrap(), Cow::Borrowed(content) if content.len() == json.size() as usize);
+
+        let invalid = ResourceData::new(5, 0, b"abc\0");
+        assert_matches!(invalid.content(), Err(ExtractorError::Data(_)));
+
+        let zstd = ResourceData::new(3, FLAG_COMPRESSED_ZSTD, b"abc");
+        assert!(zstd.is_compressed());
+        assert!(zstd.info().is_compressed());
+        assert!(zstd.content().is_err());
+
+        // Roundtrip through the hash table builder
+        let resource = ResourceData::new(3, 0, b"abc\0");
+        let mut table_builder = crate::write::HashTableBuilder::new();
+        table_builder.insert_serialized("/data", &resource).unwrap();
+        let data = crate::write::FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+        assert_eq!(
+            ResourceData::lookup_decompressed(&table, "/data").unwrap(),
+            &b"abc"[..]
+        );
+    }
+
+    #[test]
+    fn enumerate_children() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let bundle = Bundle::new(&file).unwrap();
+
+        assert_eq!(bundle.enumerate_children("/").unwrap(), vec!["gvdb/"]);
+        assert_eq!(
+            bundle.enumerate_children("/gvdb/rs/test").unwrap(),
+            bundle.enumerate_children("/gvdb/rs/test/").unwrap()
+        );
+        assert_eq!(
+            bundle.enumerate_children("/gvdb/rs/test/").unwrap(),
+            vec!["icons/", "json/", "online-symbolic.svg", "test.css"]
+        );
+        assert_eq!(
+            bundle
+                .enumerate_children("/gvdb/rs/test/icons/scalable/actions")
+                .unwrap(),
+            vec!["send-symbolic.svg"]
+        );
+
+        assert_matches!(
+            bundle.enumerate_children("/gvdb/rs/missing"),
+            Err(ExtractorError::Gvdb(crate::read::Error::KeyNotFound(_)))
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let file_data = FileData::new(
+            "/empty".to_string(),
+            Cow::Borrowed(b""),
+            None,
+            false,
+            &PreprocessOptions::empty(),
+            None,
+        )
+        .unwrap();
+        let data = BundleBuilder::from_file_data(vec![file_data])
+            .build()
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let bundle = Bundle::new(&file).unwrap();
+        assert_eq!(bundle.lookup("/empty").unwrap(), &b""[..]);
+        assert_eq!(bundle.info("/empty").unwrap().size(), 0);
+    }
+
+    #[test]
+    fn metadata() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        assert_eq!(Bundle::new(&file).unwrap().metadata().unwrap(), None);
+
+        let metadata = BundleMetadata::new().with_entry("license", "MIT");
+        let mut builder = BundleBuilder::from_file_data(Vec::new());
+        builder.set_metadata(Some(metadata.clone()));
+        builder.set_glib_compat_layout(true);
+        let data = builder.build().unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let bundle = Bundle::new(&file).unwrap();
+        assert_eq!(bundle.metadata().unwrap(), Some(metadata));
+        assert_eq!(
+            bundle.enumerate_children("/.gresource").unwrap(),
+            vec!["meta"]
+        );
+
+        // Metadata is not stored by default
+        let build = |metadata: Option<BundleMetadata>| {
+            let file_data = FileData::new(
+                METADATA_PATH.to_string(),
+                Cow::Borrowed(b"invalid"),
+                None,
+                false,
+                &PreprocessOptions::empty(),
+                None,
+            )
+            .unwrap();
+            let mut builder = BundleBuilder::from_file_data(vec![file_data]);
+            builder.set_metadata(metadata);
+            builder.build()
+        };
+        let file = File::from_bytes(Cow::Owned(build(None).unwrap())).unwrap();
+        assert_matches!(
+            Bundle::new(&file).unwrap().metadata(),
+            Err(ExtractorError::Data(_))
+        );
+        assert_matches!(
+            build(Some(BundleMetadata::new())),
+            Err(crate::gresource::BuilderError::DuplicateKey(key)) if key == METADATA_PATH
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use crate::gresource::extractor::{ExtractorError, ExtractorResult};
+use crate::gresource::reader::{Bundle, ResourceData};
+use crate::read::File;
+use glib::translate::{from_glib_full, ToGlibPtr};
+use std::borrow::Cow;
+
+glib::wrapper! {
+    /// A GResource bundle that is loaded by GLib
+    ///
+    /// This is the same boxed type as `gio::Resource`, which can be obtained from the pointer
+    /// with `from_glib_none(resource.to_glib_none().0)` when the `gio` crate is used.
+    #[doc(alias = "GResource")]
+    pub struct Resource(Shared<gio_sys::GResource>);
+
+    match fn {
+        ref => |ptr| gio_sys::g_resource_ref(ptr),
+        unref => |ptr| gio_sys::g_resource_unref(ptr),
+        type_ => || gio_sys::g_resource_get_type(),
+    }
+}
+
+impl Resource {
+    /// Remove the bundle from the global list of resources again
+    #[doc(alias = "g_resources_unregister")]
+    pub fn unregister(&self) {
+        unsafe { gio_sys::g_resources_unregister(self.to_glib_none().0) }
+    }
+}
+
+impl std::fmt::Debug for Resource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ptr: *mut gio_sys::GResource = self.to_glib_none().0;
+        f.debug_tuple("Resource").field(&ptr).finish()
+    }
+}
+
+/// Check that `data` is a GResource bundle that GLib can load
+///
+/// GLib only reports a generic error for invalid data and ignores inconsistent files until they
+/// are looked up, so every file entry is read once.
+fn validate(data: &[u8]) -> ExtractorResult<()> {
+    let file = File::from_bytes(Cow::Borrowed(data))?;
+    let bundle = Bundle::new(&file)?;
+    for path in bundle.table.keys()? {
+        if !path.ends_with('/') {
+            ResourceData::lookup(&bundle.table, &path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Load the GResource bundle `data` and register it in the global list of resources of GLib
+///
+/// This is the equivalent of `g_resource_new_from_data` followed by `g_resources_register`,
+/// which is commonly needed for bundles that are embedded with the macros of
+/// [gvdb-macros](https://crates.io/crates/gvdb-macros). The data is validated with this crate
+/// first, so invalid bundles result in a descriptive [`ExtractorError`] instead of the generic
+/// error of GLib.
+///
+/// ```
+/// use gvdb::gresource::{register_bytes, BundleBuilder};
+///
+/// let builder = BundleBuilder::from_file_data(Vec::new());
+/// let data: &'static [u8] = builder.build().unwrap().leak();
+/// let resource = register_bytes(data).unwrap();
+/// resource.unregister();
+///
+/// assert!(register_bytes(b"invalid").is_err());
+/// ```
+#[doc(alias = "g_resources_register")]
+pub fn register_bytes(data: &'static [u8]) -> ExtractorResult<Resource> {
+    validate(data)?;
+
+    let bytes = glib::Bytes::from_static(data);
+    let resource: Resource = unsafe {
+        let mut error = std::ptr::null_mut();
+        let resource = gio_sys::g_resource_new_from_data(bytes.to_glib_none().0, &mut error);
+        if !error.is_null() {
+            let error: glib::Error = from_glib_full(error);
+            return Err(ExtractorError::Data(format!(
+                "GLib rejected the GResource bundle: {}",
+                error
+            )));
+        }
+
+        from_glib_full(resource)
+    };
+
+    unsafe { gio_sys::g_resources_register(resource.to_glib_none().0) };
+    Ok(resource)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::TEST_FILE_3;
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    /// The size of the file at `path` in the global list of resources of GLib
+    fn registered_size(path: &str) -> Option<usize> {
+        let path = std::ffi::CString::new(path).unwrap();
+        let mut size = 0;
+        let mut flags = 0;
+        let mut error = std::ptr::null_mut();
+        let found = unsafe {
+            gio_sys::g_resources_get_info(path.as_ptr(), 0, &mut size, &mut flags, &mut error)
+        };
+
+        if found == glib::ffi::GFALSE {
+            let _error: glib::Error = unsafe { from_glib_full(error) };
+            None
+        } else {
+            Some(size)
+        }
+    }
+
+    #[test]
+    fn register() {
+        let data: &'static [u8] = std::fs::read(&*TEST_FILE_3).unwrap().leak();
+        let file = File::from_bytes(Cow::Borrowed(data)).unwrap();
+        let size = Bundle::new(&file)
+            .unwrap()
+            .info("/gvdb/rs/test/json/test.json")
+            .unwrap()
+            .size();
+
+        let resource = register_bytes(data).unwrap();
+        assert_eq!(
+            registered_size("/gvdb/rs/test/json/test.json"),
+            Some(size as usize)
+        );
+
+        resource.unregister();
+        assert_eq!(registered_size("/gvdb/rs/test/json/test.json"), None);
+
+        let err = register_bytes(b"invalid").unwrap_err();
+        assert_matches!(err, ExtractorError::Gvdb(_));
+    }
+}
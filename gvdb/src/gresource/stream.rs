@@ -0,0 +1,212 @@
+use crate::gresource::bundle::{FLAG_COMPRESSED, FLAG_COMPRESSED_ZSTD};
+use crate::gresource::extractor::{ExtractorError, ExtractorResult};
+use crate::gresource::reader::{Bundle, ResourceData};
+use flate2::read::ZlibDecoder;
+use std::io::Read;
+
+/// The source of the content of a [`ResourceStream`]
+enum Source<'a> {
+    Uncompressed(&'a [u8]),
+    Zlib(ZlibDecoder<&'a [u8]>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::read::Decoder<'static, &'a [u8]>),
+}
+
+/// A reader for the content of a file in a GResource bundle
+///
+/// Compressed data is decompressed while it is read, so large files can be parsed without
+/// keeping the whole content in memory. Uncompressed data is read directly from the bundle.
+/// Created by [`Bundle::open_stream`] and [`ResourceData::stream`].
+///
+/// When the end of the data is reached, the number of bytes that were read is checked against
+/// the size of the file. A mismatch is returned as [`std::io::ErrorKind::InvalidData`].
+///
+/// ```
+/// use std::io::Read;
+/// use std::path::PathBuf;
+/// use gvdb::gresource::Bundle;
+/// use gvdb::read::File;
+///
+/// let file = File::from_file(&PathBuf::from("test-data/test3.gresource")).unwrap();
+/// let bundle = Bundle::new(&file).unwrap();
+///
+/// let mut stream = bundle.open_stream("/gvdb/rs/test/test.css").unwrap();
+/// let mut css = String::new();
+/// stream.read_to_string(&mut css).unwrap();
+/// assert_eq!(css.len(), stream.size() as usize);
+/// ```
+pub struct ResourceStream<'a> {
+    source: Source<'a>,
+    size: u32,
+    position: u64,
+}
+
+impl ResourceStream<'_> {
+    /// The uncompressed size of the file in bytes
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+}
+
+impl Read for ResourceStream<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = match &mut self.source {
+            Source::Uncompressed(data) => data.read(buf)?,
+            Source::Zlib(decoder) => decoder.read(buf)?,
+            #[cfg(feature = "zstd")]
+            Source::Zstd(decoder) => decoder.read(buf)?,
+        };
+
+        self.position += n as u64;
+        if (n == 0 && !buf.is_empty() && self.position != self.size as u64)
+            || self.position > self.size as u64
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "File has a size of at least {} bytes, but {} bytes were expected",
+                    self.position, self.size
+                ),
+            ));
+        }
+
+        Ok(n)
+    }
+}
+
+impl std::fmt::Debug for ResourceStream<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let source = match self.source {
+            Source::Uncompressed(_) => "Uncompressed",
+            Source::Zlib(_) => "Zlib",
+            #[cfg(feature = "zstd")]
+            Source::Zstd(_) => "Zstd",
+        };
+
+        f.debug_struct("ResourceStream")
+            .field("source", &source)
+            .field("size", &self.size)
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl<'a> ResourceData<'a> {
+    /// A reader for the content of the file that decompresses the data on the fly
+    ///
+    /// See [`ResourceStream`]. Returns [`ExtractorError::Data`] if the data is zstd compressed
+    /// and the `zstd` feature is not enabled.
+    pub fn stream(&self) -> ExtractorResult<ResourceStream<'a>> {
+        let source = if self.flags() & FLAG_COMPRESSED_ZSTD != 0 {
+            #[cfg(feature = "zstd")]
+            {
+                Source::Zstd(
+                    zstd::stream::read::Decoder::with_buffer(self.data()).map_err(
+                        ExtractorError::from_io_with_filename(None::<std::path::PathBuf>),
+                    )?,
+                )
+            }
+            #[cfg(not(feature = "zstd"))]
+            return Err(ExtractorError::Data(
+                "File is zstd compressed, which requires the 'zstd' feature".to_string(),
+            ));
+        } else if self.flags() & FLAG_COMPRESSED != 0 {
+            Source::Zlib(ZlibDecoder::new(self.data()))
+        } else {
+            // Uncompressed data is zero-terminated
+            match self.data().split_last() {
+                Some((0, rest)) => Source::Uncompressed(rest),
+                _ => Source::Uncompressed(self.data()),
+            }
+        };
+
+        Ok(ResourceStream {
+            source,
+            size: self.size(),
+            position: 0,
+        })
+    }
+}
+
+impl<'a> Bundle<'a, '_> {
+    /// A reader for the data of the file at `path`
+    ///
+    /// In contrast to [`Bundle::lookup`], compressed data is decompressed while it is read
+    /// instead of all at once. See [`ResourceStream`].
+    pub fn open_stream(&self, path: &str) -> ExtractorResult<ResourceStream<'a>> {
+        ResourceData::lookup(&self.table, path)?.stream()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::File;
+    use crate::test::{GRESOURCE_DIR, TEST_FILE_3};
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    fn read_all(stream: &mut ResourceStream) -> std::io::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    #[test]
+    fn open_stream() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let bundle = Bundle::new(&file).unwrap();
+
+        for path in [
+            "/gvdb/rs/test/test.css",
+            "/gvdb/rs/test/json/test.json",
+            "/gvdb/rs/test/icons/scalable/actions/send-symbolic.svg",
+        ] {
+            let mut stream = bundle.open_stream(path).unwrap();
+            assert_eq!(bundle.lookup(path).unwrap(), read_all(&mut stream).unwrap());
+        }
+
+        // Read in small chunks
+        let css = std::fs::read(GRESOURCE_DIR.join("test.css")).unwrap();
+        let mut stream = bundle.open_stream("/gvdb/rs/test/test.css").unwrap();
+        assert!(format!("{:?}", stream).contains("Zlib"));
+        let mut data = Vec::new();
+        let mut buf = [0; 7];
+        loop {
+            let n = stream.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            data.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(data, css);
+
+        assert!(bundle.open_stream("/gvdb/rs/test/").is_err());
+        assert!(bundle.open_stream("/gvdb/rs/test/missing").is_err());
+    }
+
+    #[test]
+    fn stream_size_mismatch() {
+        let data = ResourceData::new(5, 0, b"abc\0");
+        let err = read_all(&mut data.stream().unwrap()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        let data = ResourceData::new(2, 0, b"abc\0");
+        let err = read_all(&mut data.stream().unwrap()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        let data = ResourceData::new(3, 0, b"abc\0");
+        assert_eq!(read_all(&mut data.stream().unwrap()).unwrap(), &b"abc"[..]);
+
+        let data = ResourceData::new(3, FLAG_COMPRESSED, b"abc");
+        assert!(read_all(&mut data.stream().unwrap()).is_err());
+
+        let data = ResourceData::new(3, FLAG_COMPRESSED_ZSTD, b"abc");
+        if cfg!(feature = "zstd") {
+            assert!(read_all(&mut data.stream().unwrap()).is_err());
+        } else {
+            assert_matches!(data.stream(), Err(ExtractorError::Data(_)));
+        }
+    }
+}
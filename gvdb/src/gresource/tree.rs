@@ -0,0 +1,265 @@
+use crate::gresource::extractor::ExtractorResult;
+use crate::gresource::reader::Bundle;
+
+/// The type of a [`PathNode`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PathNodeKind {
+    /// A directory, whose key ends with `/`
+    Directory,
+
+    /// A file
+    File,
+}
+
+/// A directory or file in a [`PathTree`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathNode {
+    name: String,
+    path: String,
+    kind: PathNodeKind,
+    children: Vec<PathNode>,
+}
+
+impl PathNode {
+    fn new(parent: &str, name: String, kind: PathNodeKind) -> Self {
+        Self {
+            path: format!("{}{}", parent, name),
+            name,
+            kind,
+            children: Vec::new(),
+        }
+    }
+
+    /// The name of the node inside its directory
+    ///
+    /// Like in [`Bundle::enumerate_children`], the names of directories end with `/`. The name of
+    /// the root directory is empty.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The full path of the node, which is its key in the bundle
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Whether the node is a directory or a file
+    pub fn kind(&self) -> PathNodeKind {
+        self.kind
+    }
+
+    /// Whether the node is a directory
+    pub fn is_directory(&self) -> bool {
+        self.kind == PathNodeKind::Directory
+    }
+
+    /// Whether the node is a file
+    pub fn is_file(&self) -> bool {
+        self.kind == PathNodeKind::File
+    }
+
+    /// The children of a directory, sorted by name. Files have no children.
+    pub fn children(&self) -> &[PathNode] {
+        &self.children
+    }
+
+    /// The child with the name `name`
+    pub fn child(&self, name: &str) -> Option<&PathNode> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// The full paths of the children
+    ///
+    /// This is the list of keys that is stored in the container item of a directory.
+    pub fn child_keys(&self) -> Vec<String> {
+        self.children
+            .iter()
+            .map(|child| child.path.clone())
+            .collect()
+    }
+
+    /// Insert the descendant with the path `segments` below this directory
+    fn insert(&mut self, segments: &[&str], kind: PathNodeKind) {
+        let Some((segment, rest)) = segments.split_first() else {
+            return;
+        };
+
+        let (name, child_kind) = if rest.is_empty() && kind == PathNodeKind::File {
+            (segment.to_string(), PathNodeKind::File)
+        } else {
+            (format!("{}/", segment), PathNodeKind::Directory)
+        };
+
+        let position = match self
+            .children
+            .binary_search_by(|child| child.name.as_str().cmp(&name))
+        {
+            Ok(position) => position,
+            Err(position) => {
+                let child = PathNode::new(&self.path, name, child_kind);
+                self.children.insert(position, child);
+                position
+            }
+        };
+
+        self.children[position].insert(rest, kind);
+    }
+
+    /// Append the paths of this node and all of its descendants to `keys`
+    fn collect_keys(&self, keys: &mut Vec<String>) {
+        keys.push(self.path.clone());
+        for child in &self.children {
+            child.collect_keys(keys);
+        }
+    }
+}
+
+/// The directory hierarchy of the files in a GResource bundle
+///
+/// GResource bundles store every directory as a container item whose key ends with `/`. The tree
+/// makes this hierarchy explicit, so it doesn't have to be derived from the keys. It can be built
+/// from any list of keys, missing parent directories are added.
+///
+/// ```
+/// use std::path::PathBuf;
+/// use gvdb::gresource::{Bundle, PathNodeKind};
+/// use gvdb::read::File;
+///
+/// let file = File::from_file(&PathBuf::from("test-data/test3.gresource")).unwrap();
+/// let tree = Bundle::new(&file).unwrap().path_tree().unwrap();
+///
+/// let json = tree.find("/gvdb/rs/test/json/").unwrap();
+/// assert_eq!(json.kind(), PathNodeKind::Directory);
+/// assert_eq!(json.child_keys(), vec!["/gvdb/rs/test/json/test.json"]);
+/// assert!(json.child("test.json").unwrap().is_file());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathTree {
+    root: PathNode,
+}
+
+impl PathTree {
+    /// Build the tree of the bundle keys `keys`
+    ///
+    /// Keys that end with `/` are directories, all other keys are files.
+    pub fn from_keys<I>(keys: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut root = PathNode::new("/", String::new(), PathNodeKind::Directory);
+        for key in keys {
+            let key = key.as_ref();
+            let kind = if key.ends_with('/') {
+                PathNodeKind::Directory
+            } else {
+                PathNodeKind::File
+            };
+
+            let segments: Vec<&str> = key.split('/').filter(|s| !s.is_empty()).collect();
+            root.insert(&segments, kind);
+        }
+
+        Self { root }
+    }
+
+    /// The root directory `/`
+    pub fn root(&self) -> &PathNode {
+        &self.root
+    }
+
+    /// The node with the full path `path`
+    ///
+    /// The path of a directory has to end with `/`.
+    pub fn find(&self, path: &str) -> Option<&PathNode> {
+        let mut node = &self.root;
+        let mut rest = path.strip_prefix('/')?;
+        while !rest.is_empty() {
+            let end = rest.find('/').map_or(rest.len(), |separator| separator + 1);
+            node = node.child(&rest[..end])?;
+            rest = &rest[end..];
+        }
+
+        Some(node)
+    }
+
+    /// The paths of all directories and files, in depth-first order
+    ///
+    /// This is the list of keys of a bundle with these files, including the container items of
+    /// all directories.
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys = Vec::new();
+        self.root.collect_keys(&mut keys);
+        keys
+    }
+}
+
+impl Bundle<'_, '_> {
+    /// The directory hierarchy of the files in the bundle
+    ///
+    /// See [`PathTree`].
+    pub fn path_tree(&self) -> ExtractorResult<PathTree> {
+        Ok(PathTree::from_keys(self.table.keys()?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::File;
+    use crate::test::TEST_FILE_3;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    #[test]
+    fn path_tree() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let bundle = Bundle::new(&file).unwrap();
+        let tree = bundle.path_tree().unwrap();
+
+        let mut keys = tree.keys();
+        keys.sort();
+        assert_eq!(keys, file.hash_table().unwrap().keys_sorted().unwrap());
+
+        let root = tree.root();
+        assert_eq!(root.name(), "");
+        assert_eq!(root.path(), "/");
+        assert_eq!(root.child_keys(), ["/gvdb/"]);
+        assert_eq!(tree.find("/"), Some(root));
+
+        let test = tree.find("/gvdb/rs/test/").unwrap();
+        for child in test.children() {
+            assert_eq!(child.path(), format!("{}{}", test.path(), child.name()));
+            assert_eq!(child.is_directory(), child.name().ends_with('/'));
+        }
+        assert_eq!(
+            test.children()
+                .iter()
+                .map(|child| child.name())
+                .collect::<Vec<_>>(),
+            bundle.enumerate_children("/gvdb/rs/test/").unwrap()
+        );
+
+        let css = tree.find("/gvdb/rs/test/test.css").unwrap();
+        assert_eq!(css.kind(), PathNodeKind::File);
+        assert!(css.children().is_empty());
+        assert!(tree.find("/gvdb/rs/test/test.css/").is_none());
+        assert!(tree.find("/gvdb/rs/test").is_none());
+        assert!(tree.find("gvdb/").is_none());
+    }
+
+    #[test]
+    fn from_keys() {
+        let tree = PathTree::from_keys(["/a/b/file", "/a/dir/", "/file"]);
+        assert_eq!(
+            tree.keys(),
+            ["/", "/a/", "/a/b/", "/a/b/file", "/a/dir/", "/file"]
+        );
+        assert!(tree.find("/a/dir/").unwrap().is_directory());
+        assert!(tree.find("/a/dir/").unwrap().children().is_empty());
+        assert!(tree.find("/file").unwrap().is_file());
+
+        let tree = PathTree::from_keys(Vec::<String>::new());
+        assert_eq!(tree.keys(), ["/"]);
+    }
+}
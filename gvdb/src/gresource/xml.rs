@@ -1,7 +1,9 @@
 mod error;
+mod glob;
 
 pub use error::*;
 
+use quick_xml::events::Event;
 use serde::de::Error;
 use serde::Deserialize;
 use std::borrow::Cow;
@@ -9,6 +11,23 @@ use std::io::Read;
 use std::path::{Path, PathBuf};
 
 /// A GResource XML document
+///
+/// The file name of a `<file>` element can be a glob pattern relative to the directory of the
+/// document. `*` matches any part of a file name, `?` a single character and `**` any number of
+/// directories. The pattern is replaced by one element for every matching file, sorted by path.
+/// Patterns that don't match any file are an error.
+///
+/// The alias of a pattern is a template with the placeholders `{path}` (the path of the file
+/// relative to the document directory), `{name}` (the file name) and `{stem}` (the file name
+/// without its extension):
+///
+/// ```xml
+/// <gresources>
+///   <gresource prefix="/org/example/icons">
+///     <file alias="scalable/actions/{name}" preprocess="xml-stripblanks">icons/**/*.svg</file>
+///   </gresource>
+/// </gresources>
+/// ```
 #[derive(Debug, Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
 #[non_exhaustive]
@@ -22,6 +41,55 @@ pub struct XmlManifest {
     pub dir: PathBuf,
 }
 
+/// The position of an element in a GResource XML document
+///
+/// Used by [`BuilderError::Element`](crate::gresource::BuilderError::Element) to point to the
+/// element that caused an error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XmlLocation {
+    filename: Option<PathBuf>,
+    line: usize,
+    column: usize,
+}
+
+impl XmlLocation {
+    /// Calculate the line and column of the byte `offset` in `data`
+    fn new(filename: Option<PathBuf>, data: &str, offset: usize) -> Self {
+        let before = &data[..offset.min(data.len())];
+
+        Self {
+            filename,
+            line: before.matches('\n').count() + 1,
+            column: before.chars().rev().take_while(|c| *c != '\n').count() + 1,
+        }
+    }
+
+    /// The file name of the XML document, if it was loaded from a file
+    pub fn filename(&self) -> Option<&Path> {
+        self.filename.as_deref()
+    }
+
+    /// The line of the element, starting at 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column of the element in characters, starting at 1
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl std::fmt::Display for XmlLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(filename) = &self.filename {
+            write!(f, "{}:{}:{}", filename.display(), self.line, self.column)
+        } else {
+            write!(f, "line {}, column {}", self.line, self.column)
+        }
+    }
+}
+
 /// A GResource section inside a GResource XML document
 #[derive(Debug, Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
@@ -34,10 +102,14 @@ pub struct GResource {
     /// An optional prefix to prepend to the containing file keys
     #[serde(default, rename = "@prefix")]
     pub prefix: String,
+
+    /// The position of the `<gresource>` element in the XML document
+    #[serde(skip)]
+    pub location: Option<XmlLocation>,
 }
 
 /// A file within a GResource section
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
 #[non_exhaustive]
 pub struct File {
@@ -60,10 +132,16 @@ pub struct File {
         rename = "@preprocess"
     )]
     pub preprocess: PreprocessOptions,
+
+    /// The position of the `<file>` element in the XML document
+    ///
+    /// All files that are matched by a glob pattern share the position of the pattern.
+    #[serde(skip)]
+    pub location: Option<XmlLocation>,
 }
 
 /// Preprocessing options for files that will be put in a GResource
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct PreprocessOptions {
     /// Strip whitespace from XML file
@@ -74,6 +152,13 @@ pub struct PreprocessOptions {
 
     /// Strip whitespace from JSON file
     pub json_stripblanks: bool,
+
+    /// Names of custom preprocessors, in the order they are applied
+    ///
+    /// Custom preprocessors are registered with
+    /// [`BundleBuilder::add_preprocessor`](crate::gresource::BundleBuilder::add_preprocessor) and
+    /// applied after the built-in preprocessing options.
+    pub custom: Vec<String>,
 }
 
 impl PreprocessOptions {
@@ -85,6 +170,7 @@ impl PreprocessOptions {
             xml_stripblanks: false,
             to_pixdata: false,
             json_stripblanks: false,
+            custom: Vec::new(),
         }
     }
 
@@ -94,6 +180,7 @@ impl PreprocessOptions {
             xml_stripblanks: true,
             to_pixdata: false,
             json_stripblanks: false,
+            custom: Vec::new(),
         }
     }
 
@@ -103,6 +190,7 @@ impl PreprocessOptions {
             xml_stripblanks: false,
             to_pixdata: false,
             json_stripblanks: true,
+            custom: Vec::new(),
         }
     }
 }
@@ -129,12 +217,12 @@ where
             "json-stripblanks" => this.json_stripblanks = true,
             "xml-stripblanks" => this.xml_stripblanks = true,
             "to-pixdata" => this.to_pixdata = true,
-            other => {
-                return Err(D::Error::custom(format!(
-                    "got '{}' but expected any of 'json-stripblanks', 'xml-stripblanks'",
-                    other
-                )))
+            "" => {
+                return Err(D::Error::custom(
+                    "got an empty preprocessor name but expected any of 'json-stripblanks', 'xml-stripblanks' or a custom preprocessor",
+                ))
             }
+            other => this.custom.push(other.to_string()),
         }
     }
 
@@ -165,16 +253,112 @@ impl XmlManifest {
         filename: Option<PathBuf>,
         data: Cow<'_, [u8]>,
     ) -> error::XmlManifestResult<Self> {
-        let mut this: Self = quick_xml::de::from_str(
-            std::str::from_utf8(&data)
-                .map_err(|err| error::XmlManifestError::Utf8(err, filename.clone()))?,
-        )
-        .map_err(|err| error::XmlManifestError::Serde(err, filename))?;
+        let data = std::str::from_utf8(&data)
+            .map_err(|err| error::XmlManifestError::Utf8(err, filename.clone()))?;
+        let mut this: Self = quick_xml::de::from_str(data)
+            .map_err(|err| error::XmlManifestError::Serde(err, filename.clone()))?;
 
         this.dir = dir.to_path_buf();
+        this.set_locations(data, &filename);
+        this.expand_patterns(filename)?;
         Ok(this)
     }
 
+    /// Store the position of every `<gresource>` and `<file>` element
+    ///
+    /// `data` was already deserialized successfully, so the elements appear in the same order as
+    /// in the deserialized document.
+    fn set_locations(&mut self, data: &str, filename: &Option<PathBuf>) {
+        let mut reader = quick_xml::Reader::from_str(data);
+        let mut gresources = self.gresources.iter_mut();
+        let mut files = None;
+
+        loop {
+            let offset = reader.buffer_position();
+            let element = match reader.read_event() {
+                Ok(Event::Start(element)) | Ok(Event::Empty(element)) => element,
+                Ok(Event::Eof) | Err(_) => break,
+                Ok(_) => continue,
+            };
+
+            let location = Some(XmlLocation::new(filename.clone(), data, offset));
+            match element.name().as_ref() {
+                b"gresource" => {
+                    if let Some(gresource) = gresources.next() {
+                        gresource.location = location;
+                        files = Some(gresource.files.iter_mut());
+                    }
+                }
+                b"file" => {
+                    if let Some(file) = files.as_mut().and_then(Iterator::next) {
+                        file.location = location;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Replace all `<file>` elements with glob patterns by the files they match
+    fn expand_patterns(&mut self, filename: Option<PathBuf>) -> error::XmlManifestResult<()> {
+        let glob_error = |err: String| error::XmlManifestError::Glob(err, filename.clone());
+
+        for gresource in &mut self.gresources {
+            if !gresource
+                .files
+                .iter()
+                .any(|file| glob::is_pattern(&file.filename))
+            {
+                continue;
+            }
+
+            let mut files = Vec::with_capacity(gresource.files.len());
+            for file in std::mem::take(&mut gresource.files) {
+                if !glob::is_pattern(&file.filename) {
+                    files.push(file);
+                    continue;
+                }
+
+                if let Some(alias) = &file.alias {
+                    if !glob::has_placeholder(alias) {
+                        return Err(glob_error(format!(
+                            "Alias '{}' for pattern '{}' must contain '{{path}}', '{{name}}' or '{{stem}}'",
+                            alias, file.filename
+                        )));
+                    }
+                }
+
+                let paths = glob::expand(&self.dir, &file.filename).map_err(|err| match err {
+                    error::XmlManifestError::Glob(err, None) => glob_error(err),
+                    err => err,
+                })?;
+                if paths.is_empty() {
+                    return Err(glob_error(format!(
+                        "Pattern '{}' does not match any files",
+                        file.filename
+                    )));
+                }
+
+                for path in paths {
+                    files.push(File {
+                        alias: file
+                            .alias
+                            .as_ref()
+                            .map(|alias| glob::expand_alias(alias, &path)),
+                        filename: path,
+                        compressed: file.compressed,
+                        preprocess: file.preprocess.clone(),
+                        location: file.location.clone(),
+                    });
+                }
+            }
+
+            gresource.files = files;
+        }
+
+        Ok(())
+    }
+
     /// Load a GResource XML file from the provided `Cow<[u8]>` bytes
     pub fn from_bytes(dir: &Path, data: Cow<'_, [u8]>) -> error::XmlManifestResult<Self> {
         Self::from_bytes_with_filename(dir, None, data)
@@ -184,6 +368,18 @@ impl XmlManifest {
     pub fn from_string(dir: &Path, str: impl ToString) -> error::XmlManifestResult<Self> {
         Self::from_bytes(dir, Cow::Borrowed(str.to_string().as_bytes()))
     }
+
+    /// The on-disk paths of all files referenced by this document, in document order
+    ///
+    /// The paths are relative to [`dir`](Self::dir), like the files that are read when compiling
+    /// the document.
+    pub fn file_paths(&self) -> Vec<PathBuf> {
+        self.gresources
+            .iter()
+            .flat_map(|gresource| &gresource.files)
+            .map(|file| self.dir.join(&file.filename))
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -228,6 +424,37 @@ mod test {
         assert_eq!(doc.gresources[0].prefix, "/bla/blub")
     }
 
+    #[test]
+    fn locations() {
+        let doc = XmlManifest::from_file(&crate::test::GRESOURCE_XML).unwrap();
+        let location = doc.gresources[2].files[0].location.as_ref().unwrap();
+        assert_eq!(location.line(), 11);
+        assert_eq!(location.column(), 5);
+        assert_eq!(location.filename(), Some(&**crate::test::GRESOURCE_XML));
+        assert!(format!("{}", location).ends_with("test3.gresource.xml:11:5"));
+
+        let data = "<gresources>\r\n\t<gresource prefix=\"/ä\"><file>a</file>\n<!-- <file>b</file> --><file>c</file></gresource></gresources>";
+        let doc = XmlManifest::from_string(&PathBuf::from("/TEST"), data).unwrap();
+        let location = doc.gresources[0].location.as_ref().unwrap();
+        assert_eq!((location.line(), location.column()), (2, 2));
+        let location = doc.gresources[0].files[0].location.as_ref().unwrap();
+        assert_eq!((location.line(), location.column()), (2, 25));
+        let location = doc.gresources[0].files[1].location.as_ref().unwrap();
+        assert_eq!((location.line(), location.column()), (3, 24));
+        assert_eq!(format!("{}", location), "line 3, column 24");
+    }
+
+    #[test]
+    fn deserialize_custom_preprocessor() {
+        let test_path = PathBuf::from("/TEST");
+
+        let data = r#"<gresources><gresource><file preprocess="xml-stripblanks,scss,minify">style.scss</file></gresource></gresources>"#;
+        let doc = XmlManifest::from_string(&test_path, data).unwrap();
+        let preprocess = &doc.gresources[0].files[0].preprocess;
+        assert_eq!(preprocess.xml_stripblanks, true);
+        assert_eq!(preprocess.custom, ["scss", "minify"]);
+    }
+
     #[test]
     fn deserialize_fail() {
         let test_path = PathBuf::from("/TEST");
@@ -272,8 +499,8 @@ mod test {
         );
 
         assert_matches!(
-            XmlManifest::from_string(&test_path, r#"<gresources><gresource><file preprocess="fail">filename</file></gresource></gresources>"#),
-            Err(error::XmlManifestError::Serde(quick_xml::de::DeError::Custom(field), _)) if field.starts_with("got 'fail' but expected any of")
+            XmlManifest::from_string(&test_path, r#"<gresources><gresource><file preprocess="xml-stripblanks,">filename</file></gresource></gresources>"#),
+            Err(error::XmlManifestError::Serde(quick_xml::de::DeError::Custom(field), _)) if field.starts_with("got an empty preprocessor name but expected any of")
         );
 
         let res = XmlManifest::from_bytes(&test_path, Cow::Borrowed(&[0x80, 0x81])).unwrap_err();
@@ -282,6 +509,69 @@ mod test {
         assert_matches!(res, error::XmlManifestError::Utf8(..));
     }
 
+    #[test]
+    #[cfg(feature = "fs")]
+    fn deserialize_glob() {
+        let data = r#"<gresources><gresource prefix="/icons"><file alias="actions/{name}" preprocess="xml-stripblanks">icons/**/*.svg</file><file>test.css</file></gresource><gresource><file compressed="true">*/*.json</file></gresource></gresources>"#;
+        let doc = XmlManifest::from_string(&crate::test::GRESOURCE_DIR, data).unwrap();
+
+        let files = &doc.gresources[0].files;
+        assert_eq!(files.len(), 3);
+        assert_eq!(
+            files[0].filename,
+            "icons/scalable/actions/online-symbolic.svg"
+        );
+        assert_eq!(
+            files[0].alias.as_deref(),
+            Some("actions/online-symbolic.svg")
+        );
+        assert_eq!(files[1].alias.as_deref(), Some("actions/send-symbolic.svg"));
+        assert_eq!(files[1].preprocess.xml_stripblanks, true);
+        assert_eq!(files[2].filename, "test.css");
+
+        let files = &doc.gresources[1].files;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].filename, "json/test.json");
+        assert_eq!(files[0].alias, None);
+        assert_eq!(files[0].compressed, true);
+
+        assert_matches!(
+            XmlManifest::from_string(&crate::test::GRESOURCE_DIR, r#"<gresources><gresource><file>*.png</file></gresource></gresources>"#),
+            Err(error::XmlManifestError::Glob(err, _)) if err.contains("does not match")
+        );
+        assert_matches!(
+            XmlManifest::from_string(&crate::test::GRESOURCE_DIR, r#"<gresources><gresource><file alias="icon.svg">icons/**/*.svg</file></gresource></gresources>"#),
+            Err(error::XmlManifestError::Glob(err, _)) if err.contains("must contain")
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "fs"))]
+    fn deserialize_glob_without_fs() {
+        let data = r#"<gresources><gresource><file>*.css</file></gresource></gresources>"#;
+        assert_matches!(
+            XmlManifest::from_bytes_with_filename(
+                &crate::test::GRESOURCE_DIR,
+                Some(PathBuf::from("test.gresource.xml")),
+                Cow::Borrowed(data.as_bytes())
+            ),
+            Err(error::XmlManifestError::Glob(err, Some(_))) if err.contains("`fs` feature")
+        );
+    }
+
+    #[test]
+    fn file_paths() {
+        let data = r#"<gresources><gresource prefix="/a"><file>test.css</file></gresource><gresource><file alias="b.json">json/test.json</file></gresource></gresources>"#;
+        let doc = XmlManifest::from_string(Path::new("/TEST"), data).unwrap();
+        assert_eq!(
+            doc.file_paths(),
+            vec![
+                PathBuf::from("/TEST/test.css"),
+                PathBuf::from("/TEST/json/test.json")
+            ]
+        );
+    }
+
     #[test]
     fn io_error() {
         let test_path = PathBuf::from("invalid_file_name.xml");
@@ -9,6 +9,9 @@ pub enum XmlManifestError {
 
     /// A file needs to be interpreted as UTF-8 (for stripping whitespace etc.) but it is invalid
     Utf8(std::str::Utf8Error, Option<std::path::PathBuf>),
+
+    /// A glob pattern in a `<file>` element is invalid or doesn't match any files
+    Glob(String, Option<std::path::PathBuf>),
 }
 
 impl XmlManifestError {
@@ -51,6 +54,18 @@ impl std::fmt::Display for XmlManifestError {
                     write!(f, "Error converting data to UTF-8: {}", err)
                 }
             }
+            XmlManifestError::Glob(err, path) => {
+                if let Some(path) = path {
+                    write!(
+                        f,
+                        "Error expanding file pattern in XML file '{}': {}",
+                        path.display(),
+                        err
+                    )
+                } else {
+                    write!(f, "Error expanding file pattern: {}", err)
+                }
+            }
         }
     }
 }
@@ -73,5 +88,11 @@ mod test {
         let io_res = std::fs::File::open("test/invalid_file_name");
         let err = XmlManifestError::Io(io_res.unwrap_err(), None);
         assert!(format!("{}", err).contains("I/O"));
+
+        let err = XmlManifestError::Glob(
+            "Pattern '*.svg' does not match any files".to_string(),
+            Some("test.gresource.xml".into()),
+        );
+        assert!(format!("{}", err).contains("test.gresource.xml"));
     }
 }
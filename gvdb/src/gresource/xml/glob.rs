@@ -0,0 +1,228 @@
+use super::error::{XmlManifestError, XmlManifestResult};
+use std::path::Path;
+#[cfg(feature = "fs")]
+use walkdir::WalkDir;
+
+/// Whether the file name of a `<file>` element is a glob pattern
+pub(crate) fn is_pattern(filename: &str) -> bool {
+    filename.contains(['*', '?'])
+}
+
+/// Match a single path component against a pattern component
+///
+/// `*` matches any number of characters and `?` matches exactly one character. Wildcards don't
+/// match a leading `.`, so hidden files are only matched explicitly.
+#[cfg(feature = "fs")]
+fn match_component(pattern: &str, name: &str) -> bool {
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                n += 1;
+            }
+            Some(c) if *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Match a relative path against a pattern, both split into components
+///
+/// A `**` component matches any number of directories.
+#[cfg(feature = "fs")]
+fn match_path(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.first(), path.first()) {
+        (None, None) => true,
+        (Some(&"**"), _) => {
+            match_path(&pattern[1..], path) || (!path.is_empty() && match_path(pattern, &path[1..]))
+        }
+        (Some(component), Some(name)) => {
+            match_component(component, name) && match_path(&pattern[1..], &path[1..])
+        }
+        _ => false,
+    }
+}
+
+/// Expand a glob pattern to the paths of all matching files relative to `dir`, sorted by name
+///
+/// Directories are not matched. Components of the result are separated by `/`.
+#[cfg(feature = "fs")]
+pub(crate) fn expand(dir: &Path, pattern: &str) -> XmlManifestResult<Vec<String>> {
+    let pattern: Vec<&str> = pattern.split('/').filter(|c| !c.is_empty()).collect();
+    let n_literal = pattern
+        .iter()
+        .take_while(|component| !is_pattern(component))
+        .count();
+
+    let base = pattern[..n_literal]
+        .iter()
+        .fold(dir.to_path_buf(), |path, component| path.join(component));
+    if !base.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut walk = WalkDir::new(&base).min_depth(1).sort_by_file_name();
+    if !pattern.contains(&"**") {
+        walk = walk.max_depth(pattern.len() - n_literal);
+    }
+
+    let mut paths = Vec::new();
+    for res in walk {
+        let entry = match res {
+            Ok(entry) => entry,
+            Err(err) => {
+                let path = err.path().map(|p| p.to_path_buf());
+                return Err(XmlManifestError::Io(err.into(), path));
+            }
+        };
+
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        // Files that are not valid UTF-8 can't be referenced from the XML file
+        let Some(components) = entry
+            .path()
+            .strip_prefix(dir)
+            .ok()
+            .and_then(|path| path.iter().map(|c| c.to_str()).collect::<Option<Vec<_>>>())
+        else {
+            continue;
+        };
+
+        if match_path(&pattern, &components) {
+            paths.push(components.join("/"));
+        }
+    }
+
+    Ok(paths)
+}
+
+/// Glob patterns can't be expanded without access to the file system
+#[cfg(not(feature = "fs"))]
+pub(crate) fn expand(_dir: &Path, pattern: &str) -> XmlManifestResult<Vec<String>> {
+    Err(XmlManifestError::Glob(
+        format!("Pattern '{}' requires the `fs` feature", pattern),
+        None,
+    ))
+}
+
+/// Whether an alias contains a placeholder that is replaced by [`expand_alias`]
+pub(crate) fn has_placeholder(alias: &str) -> bool {
+    ["{path}", "{name}", "{stem}"]
+        .iter()
+        .any(|placeholder| alias.contains(placeholder))
+}
+
+/// Replace the placeholders of an alias template for the matched file at `path`
+///
+/// `{path}` is the path relative to the manifest directory, `{name}` the file name and `{stem}`
+/// the file name without its extension.
+pub(crate) fn expand_alias(alias: &str, path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let stem = match name.rfind('.') {
+        Some(index) if index > 0 => &name[..index],
+        _ => name,
+    };
+
+    alias
+        .replace("{path}", path)
+        .replace("{name}", name)
+        .replace("{stem}", stem)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(feature = "fs")]
+    use crate::test::*;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn component() {
+        assert!(match_component("*.svg", "send-symbolic.svg"));
+        assert!(match_component("send-*.svg", "send-symbolic.svg"));
+        assert!(match_component("*", "test.css"));
+        assert!(match_component("test.?ss", "test.css"));
+        assert!(match_component("*a*b*", "xaxxbx"));
+        assert!(!match_component("*.svg", "test.css"));
+        assert!(!match_component("?", "ab"));
+        assert!(!match_component("*", ".hidden"));
+        assert!(match_component(".*", ".hidden"));
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn path() {
+        assert!(match_path(
+            &["icons", "*", "*.svg"],
+            &["icons", "a", "b.svg"]
+        ));
+        assert!(!match_path(&["icons", "*.svg"], &["icons", "a", "b.svg"]));
+        assert!(match_path(&["**", "*.svg"], &["b.svg"]));
+        assert!(match_path(&["**", "*.svg"], &["icons", "a", "b.svg"]));
+        assert!(match_path(&["icons", "**"], &["icons", "a", "b.svg"]));
+        assert!(!match_path(&["**", "*.css"], &["icons", "a", "b.svg"]));
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn expand_files() {
+        assert_eq!(
+            expand(&GRESOURCE_DIR, "icons/**/*.svg").unwrap(),
+            vec![
+                "icons/scalable/actions/online-symbolic.svg",
+                "icons/scalable/actions/send-symbolic.svg"
+            ]
+        );
+        assert_eq!(expand(&GRESOURCE_DIR, "*.css").unwrap(), vec!["test.css"]);
+        assert_eq!(
+            expand(&GRESOURCE_DIR, "*/test.*").unwrap(),
+            vec!["json/test.json"]
+        );
+        assert!(expand(&GRESOURCE_DIR, "icons/*").unwrap().is_empty());
+        assert!(expand(&GRESOURCE_DIR, "missing/*").unwrap().is_empty());
+    }
+
+    #[test]
+    fn alias() {
+        let path = "icons/scalable/actions/send-symbolic.svg";
+        assert!(has_placeholder("actions/{name}"));
+        assert!(!has_placeholder("actions/icon.svg"));
+        assert_eq!(expand_alias("{path}", path), path);
+        assert_eq!(
+            expand_alias("actions/{name}", path),
+            "actions/send-symbolic.svg"
+        );
+        assert_eq!(expand_alias("{stem}.png", path), "send-symbolic.png");
+        assert_eq!(expand_alias("{stem}", ".hidden"), ".hidden");
+    }
+}
@@ -32,6 +32,9 @@
 //! }
 //! ```
 //!
+//! With the `gresource` feature, `gvdb::gresource::ResourceData` and `gvdb::gresource::Bundle`
+//! can be used instead to read and decompress the files of a GResource bundle.
+//!
 //! Create a simple GVDB file with [`FileWriter`](crate::write::FileWriter)
 //!
 //! ```
@@ -58,7 +61,15 @@
 //!
 //! ## Features
 //!
-//! By default, no features are enabled.
+//! By default, only the `std` feature is enabled.
+//!
+//! ### `std`
+//!
+//! Everything except the low-level reader needs the standard library: file I/O, writing files,
+//! the zvariant based accessors of `read::HashTable` and the `variant` module.
+//! All other features imply `std`. Without it, the crate is `no_std` and only needs `core` and
+//! `alloc`. [`SliceFile`](crate::read::SliceFile) can then be used to look up the raw data of the
+//! items of a GVDB file in a byte slice, e.g. a GResource bundle embedded in a firmware image.
 //!
 //! ### `mmap`
 //!
@@ -74,14 +85,67 @@
 //!
 //! To be able to compile GResource files, the `gresource` feature must be enabled.
 //!
+//! ### `fs`
+//!
+//! Scan directories for GResource files with
+//! [`BundleBuilder::from_directory`](crate::gresource::BundleBuilder::from_directory) and expand
+//! glob patterns in GResource XML files. Implies the `gresource` feature. Without it, the
+//! `gresource` module only needs file system access to read the files listed in a manifest, and
+//! bundles can be built from in-memory data on targets like wasm.
+//!
+//! ### `async`
+//!
+//! Load and write GVDB files asynchronously using [tokio](https://crates.io/crates/tokio). Only the
+//! I/O is done asynchronously, parsing and serializing the data is still synchronous.
+//!
+//! ### `json`
+//!
+//! Convert whole GVDB files to JSON with [`File::to_json`](crate::read::File::to_json) and back
+//! with [`HashTableBuilder::from_json`](crate::write::HashTableBuilder::from_json).
+//!
+//! ### `dconf`
+//!
+//! Read and write dconf databases with a settings-oriented API in the `dconf` module.
+//!
+//! ### `rayon`
+//!
+//! Read, preprocess and compress the files of a GResource bundle in parallel with
+//! [rayon](https://crates.io/crates/rayon). Implies the `gresource` feature. The output is
+//! identical to a sequential build.
+//!
+//! Also enables [`HashTable::par_get_many`](crate::read::HashTable::par_get_many) to look up many
+//! keys in parallel.
+//!
+//! ### `tracing`
+//!
+//! Emit [tracing](https://crates.io/crates/tracing) spans and events for opening files, reading
+//! headers and hash tables, hash table lookups (hits, misses and bloom filter rejections), chunk
+//! allocation in [`FileWriter`](crate::write::FileWriter) and the steps of building a GResource
+//! bundle. Lookups and allocations are logged at the `trace` level, everything else at `debug`.
+//!
+//! ### `zstd`
+//!
+//! Compress files in GResource bundles with
+//! [`CompressionAlgorithm::Zstd`](crate::gresource::CompressionAlgorithm::Zstd) and read them
+//! back. Implies the `gresource` feature. Bundles with zstd compressed files are **not compatible
+//! with GLib**, which only supports zlib, so this is only useful for bundles that are read with
+//! this crate.
+//!
+//! ### `testutil`
+//!
+//! Generate synthetic GVDB files of a configurable size and shape for benchmarks and integration
+//! tests with [`SyntheticFile`](crate::testutil::SyntheticFile).
+//!
 //! ## Macros
 //!
 //! The [gvdb-macros](https://crates.io/crates/gvdb-macros) crate provides useful macros for
 //! GResource file creation.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+extern crate alloc;
 extern crate core;
 
 /// Read GResource XML files and compile a GResource file
@@ -100,9 +164,40 @@ pub mod read;
 /// Create GVDB files
 ///
 /// See the documentation of [`FileWriter`](crate::write::FileWriter) to get started
+#[cfg(feature = "std")]
 pub mod write;
 
+/// Read and write dconf settings databases
+///
+/// Use [`DconfReader`](crate::dconf::DconfReader) to look up settings and
+/// [`DconfWriter`](crate::dconf::DconfWriter) to compile keyfiles to a database
+#[cfg(feature = "dconf")]
+pub mod dconf;
+
+/// Parse and print GVariant values in the GVariant text format
+///
+/// Use [`parse`](crate::variant::parse) to create a [`zvariant::Value`] from text, e.g. to insert
+/// it into a [`HashTableBuilder`](crate::write::HashTableBuilder), and
+/// [`print`](crate::variant::print) to convert a value back to text. [`Value`](crate::variant::Value)
+/// reads serialized GVariant data into a plain enum that can be inspected with pattern matching.
+#[cfg(feature = "std")]
+pub mod variant;
+
+/// Generate synthetic GVDB files for benchmarks and tests
+///
+/// See the documentation of [`SyntheticFile`](crate::testutil::SyntheticFile) to get started
+#[cfg(feature = "testutil")]
+pub mod testutil;
+
 #[cfg(test)]
 pub(crate) mod test;
 
+#[cfg(feature = "std")]
+mod diff;
+mod endian;
 mod util;
+
+#[cfg(feature = "std")]
+pub use diff::{diff, Change, ChangeSet};
+pub use endian::Endian;
+pub use util::djb_hash;
@@ -1,20 +1,89 @@
+#[cfg(feature = "std")]
+mod cache;
+#[cfg(feature = "std")]
+mod de;
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "std")]
+mod dump;
 mod error;
+#[cfg(feature = "std")]
 mod file;
+#[cfg(feature = "std")]
+mod file_with_table;
+#[cfg(feature = "std")]
 mod hash;
+mod hash_header;
 mod hash_item;
 mod header;
+#[cfg(feature = "std")]
+mod item;
+#[cfg(feature = "std")]
+mod iter;
+#[cfg(feature = "json")]
+pub(crate) mod json;
+#[cfg(feature = "std")]
+mod lazy;
+#[cfg(feature = "std")]
+mod limits;
+#[cfg(feature = "std")]
+mod lossy;
+#[cfg(feature = "mmap")]
+mod mmap;
 mod pointer;
+#[cfg(feature = "std")]
+pub mod raw;
+mod slice;
+#[cfg(feature = "std")]
+mod sorted;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(feature = "std")]
+mod validate;
+#[cfg(feature = "std")]
+mod walk;
 
+#[cfg(feature = "std")]
+pub use cache::CachedTable;
+#[cfg(feature = "std")]
+pub use diff::{Difference, DifferenceKind};
+#[cfg(feature = "std")]
+pub use dump::DumpFormat;
 pub use error::{Error, Result};
+#[cfg(feature = "std")]
 pub use file::File;
+#[cfg(feature = "std")]
+pub use file_with_table::FileWithTable;
+#[cfg(feature = "std")]
 pub use hash::HashTable;
+pub use hash_item::{HashItemInfo, HashItemType};
+#[cfg(feature = "std")]
+pub use item::Item;
+#[cfg(feature = "std")]
+pub use iter::{Iter, IterRaw, Keys, ValuesOwned};
+#[cfg(feature = "std")]
+pub use limits::Limits;
+#[cfg(feature = "std")]
+pub use lossy::{LossyFile, SkippedItem};
+#[cfg(feature = "mmap")]
+pub use mmap::{MmapAdvice, MmapOptions};
+pub use slice::{SliceFile, SliceHashTable};
+#[cfg(feature = "std")]
+pub use sorted::SortedKeys;
+#[cfg(feature = "std")]
+pub use stats::FileStats;
+#[cfg(feature = "std")]
+pub use validate::{ValidationIssue, ValidationIssueKind};
+#[cfg(feature = "std")]
+pub use walk::{Entry, Walk};
 
-pub(crate) use hash::HashHeader;
-pub(crate) use hash_item::{HashItem, HashItemType};
+pub(crate) use hash_header::HashHeader;
+pub(crate) use hash_item::HashItem;
 pub(crate) use header::Header;
 pub(crate) use pointer::Pointer;
 
 /// Deprecated type aliases
+#[cfg(feature = "std")]
 mod deprecated {
     use super::*;
 
@@ -35,4 +104,5 @@ mod deprecated {
     pub type GvdbReaderResult<T> = Result<T>;
 }
 
+#[cfg(feature = "std")]
 pub use deprecated::*;
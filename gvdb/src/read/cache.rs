@@ -0,0 +1,305 @@
+use crate::read::error::Result;
+use crate::read::{HashTable, SortedKeys};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// The least recently used values of a [`CachedTable`]
+#[derive(Debug, Default)]
+struct Lru {
+    capacity: usize,
+    // Incremented on every access, to find the least recently used value
+    tick: u64,
+    values: HashMap<usize, (Arc<zvariant::OwnedValue>, u64)>,
+    // The item indices of the cached values, by their last access
+    order: BTreeMap<u64, usize>,
+    hits: u64,
+    misses: u64,
+}
+
+impl Lru {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn get(&mut self, index: usize) -> Option<Arc<zvariant::OwnedValue>> {
+        let tick = self.next_tick();
+        let Some((value, last)) = self.values.get_mut(&index) else {
+            self.misses += 1;
+            return None;
+        };
+
+        self.order.remove(last);
+        self.order.insert(tick, index);
+        *last = tick;
+        self.hits += 1;
+        Some(value.clone())
+    }
+
+    fn insert(&mut self, index: usize, value: Arc<zvariant::OwnedValue>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        while self.values.len() >= self.capacity {
+            let Some((_, evicted)) = self.order.pop_first() else {
+                break;
+            };
+            self.values.remove(&evicted);
+        }
+
+        let tick = self.next_tick();
+        if let Some((_, last)) = self.values.insert(index, (value, tick)) {
+            self.order.remove(&last);
+        }
+        self.order.insert(tick, index);
+    }
+}
+
+/// A [`HashTable`] that caches decoded values
+///
+/// Decoding a value allocates and copies its data every time it is looked up. For long-running
+/// processes that look up the same keys again and again, this table keeps the `capacity` most
+/// recently used values in memory. The values are stored by the index of their hash item, which
+/// is unique within a hash table. The cache can be used from multiple threads.
+///
+/// Create a cached table with [`HashTable::with_cache`].
+///
+/// ```
+/// # use std::borrow::Cow;
+/// use gvdb::read::File;
+/// use gvdb::write::{FileWriter, HashTableBuilder};
+///
+/// let mut table_builder = HashTableBuilder::new();
+/// table_builder.insert("int", 42u32).unwrap();
+/// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+///
+/// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+/// let table = file.hash_table().unwrap().with_cache(64);
+///
+/// for _ in 0..10 {
+///     let value = table.get_owned_value("int").unwrap();
+///     assert_eq!(u32::try_from(&*value).unwrap(), 42);
+/// }
+/// assert_eq!(table.hits(), 9);
+/// ```
+#[derive(Debug)]
+pub struct CachedTable<'a, 'file> {
+    table: HashTable<'a, 'file>,
+    cache: Mutex<Lru>,
+    sorted_keys: OnceLock<SortedKeys>,
+}
+
+impl<'a, 'file> CachedTable<'a, 'file> {
+    /// Cache up to `capacity` decoded values of `table`
+    ///
+    /// A capacity of 0 disables the cache.
+    pub fn new(table: HashTable<'a, 'file>, capacity: usize) -> Self {
+        Self {
+            table,
+            cache: Mutex::new(Lru {
+                capacity,
+                ..Default::default()
+            }),
+            sorted_keys: OnceLock::new(),
+        }
+    }
+
+    /// The underlying hash table
+    pub fn table(&self) -> &HashTable<'a, 'file> {
+        &self.table
+    }
+
+    /// Return the underlying hash table and drop the cache
+    pub fn into_table(self) -> HashTable<'a, 'file> {
+        self.table
+    }
+
+    /// The maximum number of cached values
+    pub fn capacity(&self) -> usize {
+        self.lock().capacity
+    }
+
+    /// The number of values that are currently cached
+    pub fn len(&self) -> usize {
+        self.lock().values.len()
+    }
+
+    /// Whether no values are cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of lookups that were answered from the cache
+    pub fn hits(&self) -> u64 {
+        self.lock().hits
+    }
+
+    /// The number of lookups that had to decode the value
+    pub fn misses(&self) -> u64 {
+        self.lock().misses
+    }
+
+    /// Remove all cached values
+    pub fn clear(&self) {
+        let mut cache = self.lock();
+        cache.values.clear();
+        cache.order.clear();
+    }
+
+    /// The sorted keys of the table, see [`HashTable::sorted_keys`]
+    ///
+    /// The keys are read on the first call and kept for the lifetime of the cache, they are not
+    /// affected by [`CachedTable::clear`].
+    pub fn sorted_keys(&self) -> Result<&SortedKeys> {
+        if let Some(sorted_keys) = self.sorted_keys.get() {
+            return Ok(sorted_keys);
+        }
+
+        let sorted_keys = self.table.sorted_keys()?;
+        Ok(self.sorted_keys.get_or_init(|| sorted_keys))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Lru> {
+        // The cache is always in a consistent state, even if another thread panicked
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Returns the data for `key` as a [`zvariant::OwnedValue`], from the cache if possible
+    ///
+    /// The value is shared with the cache, so repeated lookups don't copy it.
+    pub fn get_owned_value(&self, key: &str) -> Result<Arc<zvariant::OwnedValue>> {
+        self.table.with_path(key, || {
+            let (index, item) = self.table.find_hash_item(key)?;
+            if let Some(value) = self.lock().get(index) {
+                return Ok(value);
+            }
+
+            // Decode without holding the lock, so other threads are not blocked
+            let data = self.table.value_bytes_for_item(&item, key)?;
+            let value = Arc::new(self.table.value_for_bytes(data)?.try_to_owned()?);
+            self.lock().insert(index, value.clone());
+            Ok(value)
+        })
+    }
+}
+
+impl<'a, 'file> HashTable<'a, 'file> {
+    /// Cache up to `capacity` decoded values of this hash table
+    ///
+    /// See [`CachedTable`].
+    pub fn with_cache(self, capacity: usize) -> CachedTable<'a, 'file> {
+        CachedTable::new(self, capacity)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::{Error, File};
+    use crate::test::*;
+    use crate::write::{FileWriter, HashTableBuilder};
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+
+    #[test]
+    fn lru() {
+        let mut table_builder = HashTableBuilder::new();
+        for i in 0..4u32 {
+            table_builder.insert(&format!("key{}", i), i).unwrap();
+        }
+        table_builder.insert_string("dir/item", "test").unwrap();
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap().with_cache(2);
+        assert_eq!(table.capacity(), 2);
+        assert!(table.is_empty());
+
+        let get = |key: &str| u32::try_from(&*table.get_owned_value(key).unwrap()).unwrap();
+        assert_eq!(get("key0"), 0);
+        assert_eq!(get("key1"), 1);
+        assert_eq!(get("key0"), 0);
+        assert_eq!((table.hits(), table.misses()), (1, 2));
+        assert_eq!(table.len(), 2);
+
+        // key1 is the least recently used value and is evicted
+        assert_eq!(get("key2"), 2);
+        assert_eq!(table.len(), 2);
+        assert_eq!(get("key0"), 0);
+        assert_eq!((table.hits(), table.misses()), (2, 3));
+        assert_eq!(get("key1"), 1);
+        assert_eq!((table.hits(), table.misses()), (2, 4));
+
+        // Shared values are not copied
+        let value = table.get_owned_value("key1").unwrap();
+        assert!(Arc::ptr_eq(&value, &table.get_owned_value("key1").unwrap()));
+
+        assert_matches!(table.get_owned_value("missing"), Err(Error::KeyNotFound(_)));
+        assert_matches!(
+            table.get_owned_value("dir/"),
+            Err(Error::WrongItemType { .. })
+        );
+        assert_eq!(table.len(), 2);
+
+        table.clear();
+        assert!(table.is_empty());
+        assert_eq!(get("key3"), 3);
+
+        let sorted_keys = table.sorted_keys().unwrap();
+        assert_eq!(sorted_keys.with_prefix("key").len(), 4);
+        assert!(std::ptr::eq(sorted_keys, table.sorted_keys().unwrap()));
+        assert_eq!(
+            table.into_table().get::<String>("dir/item").unwrap(),
+            "test"
+        );
+    }
+
+    #[test]
+    fn disabled() {
+        let file = File::from_file(&TEST_FILE_2).unwrap();
+        let table = file.hash_table().unwrap().with_cache(0);
+        for _ in 0..3 {
+            assert_eq!(
+                String::try_from(
+                    table
+                        .get_owned_value("string")
+                        .unwrap()
+                        .try_clone()
+                        .unwrap()
+                )
+                .unwrap(),
+                "test string"
+            );
+        }
+        assert!(table.is_empty());
+        assert_eq!(table.hits(), 0);
+        assert_eq!(table.misses(), 3);
+        assert_eq!(
+            table.table().get::<String>("string").unwrap(),
+            "test string"
+        );
+    }
+
+    #[test]
+    fn threads() {
+        let file = new_simple_file(false);
+        let table = file.hash_table().unwrap().with_cache(8);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..10 {
+                        assert!(table.get_owned_value("test").is_ok());
+                    }
+                });
+            }
+        });
+        assert_eq!(table.hits() + table.misses(), 40);
+        assert_eq!(table.len(), 1);
+    }
+}
@@ -0,0 +1,375 @@
+use crate::read::error::{Error, Result};
+use crate::read::{HashItemType, HashTable, IterRaw};
+use serde::de::value::{
+    BorrowedStrDeserializer, MapDeserializer, SeqDeserializer, StrDeserializer,
+};
+use serde::de::{DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::Deserialize;
+use zvariant::Value;
+
+/// Deserialize a whole hash table as a map
+///
+/// Every value item becomes a map entry with its key. Nested hash tables are deserialized as
+/// nested maps, so they can be used for nested structs as well. Container items are skipped.
+impl<'de> serde::Deserializer<'de> for &HashTable<'de, '_> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(TableMapAccess {
+            table: self,
+            items: self.iter_raw(),
+            pending: None,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// The entries of a hash table
+struct TableMapAccess<'t, 'a, 'file> {
+    table: &'t HashTable<'a, 'file>,
+    items: IterRaw<'t, 'a, 'file>,
+
+    // The item of the last key that was returned
+    pending: Option<(String, HashItemType, &'a [u8])>,
+}
+
+impl<'a> MapAccess<'a> for TableMapAccess<'_, 'a, '_> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'a>,
+    {
+        for item in self.items.by_ref() {
+            let (key, typ, data) = item?;
+            if typ == HashItemType::Container {
+                continue;
+            }
+
+            let key_de: StrDeserializer<Error> = key.as_str().into_deserializer();
+            let key_value = seed.deserialize(key_de)?;
+            self.pending = Some((key, typ, data));
+            return Ok(Some(key_value));
+        }
+
+        Ok(None)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'a>,
+    {
+        let (key, typ, data) = self
+            .pending
+            .take()
+            .ok_or_else(|| Error::Data("Map value requested before its key".to_string()))?;
+
+        if typ == HashItemType::HashTable {
+            return seed.deserialize(&self.table.get_hash_table(&key)?);
+        }
+
+        let value = self.table.value_for_bytes(data)?;
+        let res = if let Value::Str(_) = value {
+            // Strings are borrowed from the file, so they can be deserialized as `&str`
+            let string = self.table.get_str(&key)?;
+            seed.deserialize(BorrowedStrDeserializer::new(string))
+        } else {
+            seed.deserialize(ValueDeserializer(&value))
+        };
+
+        res.map_err(|err| match err {
+            Error::Data(msg) => Error::Data(format!(
+                "Error deserializing value for key \"{}\": {}",
+                key, msg
+            )),
+            err => err,
+        })
+    }
+}
+
+/// Deserialize a [`enum@zvariant::Value`] according to its own type
+///
+/// In contrast to the zvariant deserializer, this doesn't need the type signature of the target
+/// type.
+struct ValueDeserializer<'v, 'a>(&'v Value<'a>);
+
+impl<'de> IntoDeserializer<'de, Error> for ValueDeserializer<'_, '_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> serde::Deserializer<'de> for ValueDeserializer<'_, '_> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Value::U8(value) => visitor.visit_u8(*value),
+            Value::Bool(value) => visitor.visit_bool(*value),
+            Value::I16(value) => visitor.visit_i16(*value),
+            Value::U16(value) => visitor.visit_u16(*value),
+            Value::I32(value) => visitor.visit_i32(*value),
+            Value::U32(value) => visitor.visit_u32(*value),
+            Value::I64(value) => visitor.visit_i64(*value),
+            Value::U64(value) => visitor.visit_u64(*value),
+            Value::F64(value) => visitor.visit_f64(*value),
+            Value::Str(value) => visitor.visit_str(value.as_str()),
+            Value::Signature(value) => visitor.visit_str(value.as_str()),
+            Value::ObjectPath(value) => visitor.visit_str(value.as_str()),
+            Value::Value(value) => ValueDeserializer(value).deserialize_any(visitor),
+            Value::Array(array) => {
+                let mut seq = SeqDeserializer::new(array.inner().iter().map(ValueDeserializer));
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Value::Dict(dict) => {
+                let mut map = MapDeserializer::new(
+                    dict.iter()
+                        .map(|(key, value)| (ValueDeserializer(key), ValueDeserializer(value))),
+                );
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+            Value::Structure(structure) => {
+                let mut seq =
+                    SeqDeserializer::new(structure.fields().iter().map(ValueDeserializer));
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Value::Maybe(maybe) => match maybe.inner() {
+                Some(value) => visitor.visit_some(ValueDeserializer(value)),
+                None => visitor.visit_none(),
+            },
+            #[cfg(unix)]
+            Value::Fd(_) => Err(Error::Data(
+                "File descriptors can't be deserialized".to_string(),
+            )),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Value::Maybe(_) => self.deserialize_any(visitor),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        // Unit variants are stored as their name
+        match self.0 {
+            Value::Str(value) => {
+                let de: StrDeserializer<Error> = value.as_str().into_deserializer();
+                visitor.visit_enum(de)
+            }
+            _ => Err(Error::Data(format!(
+                "Expected a string for an enum, got type \"{}\"",
+                self.0.value_signature()
+            ))),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'a> HashTable<'a, '_> {
+    /// Deserialize the whole hash table as `T`
+    ///
+    /// The keys of the hash table are the field names of a struct or the keys of a map. Nested
+    /// hash tables are deserialized as nested structs or maps. Container items are skipped.
+    ///
+    /// The values are deserialized according to their GVariant type, so the target type doesn't
+    /// need to implement [`zvariant::Type`]. Values of type `s` can be borrowed from the file as
+    /// `&str`, strings inside of containers have to be deserialized as `String`.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Config<'a> {
+    ///     name: &'a str,
+    ///     window: Window,
+    /// }
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Window {
+    ///     width: u32,
+    ///     height: u32,
+    /// }
+    ///
+    /// let mut window = HashTableBuilder::new();
+    /// window.insert("width", 800u32).unwrap();
+    /// window.insert("height", 600u32).unwrap();
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert_string("name", "app").unwrap();
+    /// table_builder.insert_table("window", window).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let config: Config = file.hash_table().unwrap().deserialize().unwrap();
+    /// assert_eq!(config.name, "app");
+    /// assert_eq!(config.window.width, 800);
+    /// ```
+    pub fn deserialize<T>(&self) -> Result<T>
+    where
+        T: Deserialize<'a>,
+    {
+        T::deserialize(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::{Error, File};
+    use crate::test::*;
+    use crate::write::{FileWriter, HashTableBuilder};
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Nested {
+        int: u32,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct TestFile2<'a> {
+        string: &'a str,
+        table: Nested,
+    }
+
+    #[test]
+    fn deserialize_struct() {
+        let file = File::from_file(&TEST_FILE_2).unwrap();
+        let table = file.hash_table().unwrap();
+        let value: TestFile2 = table.deserialize().unwrap();
+        assert_eq!(
+            value,
+            TestFile2 {
+                string: "test string",
+                table: Nested { int: 42 }
+            }
+        );
+
+        let map: HashMap<String, u32> = table
+            .get_hash_table("table")
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        assert_eq!(map, HashMap::from([("int".to_string(), 42)]));
+
+        #[derive(Debug, serde::Deserialize)]
+        #[allow(dead_code)]
+        struct WrongType {
+            string: u32,
+        }
+        let err = table.deserialize::<WrongType>().unwrap_err();
+        assert_matches!(err, Error::Data(_));
+        assert!(format!("{}", err).contains("\"string\""));
+
+        #[derive(Debug, serde::Deserialize)]
+        #[allow(dead_code)]
+        struct Missing {
+            missing: u32,
+        }
+        assert_matches!(table.deserialize::<Missing>(), Err(Error::Data(_)));
+    }
+
+    #[test]
+    fn deserialize_values() {
+        let mut table_builder = HashTableBuilder::with_path_separator(Some("/"));
+        table_builder.insert("/a/int", 1u32).unwrap();
+        table_builder.insert("/a/tuple", (2u8, "test")).unwrap();
+        table_builder.insert("/b", vec![1i64, 2, 3]).unwrap();
+        table_builder.insert_string("/c", "dark").unwrap();
+        table_builder.insert("/d", 4u32).unwrap();
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        #[serde(rename_all = "lowercase")]
+        enum Mode {
+            Light,
+            Dark,
+        }
+
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Values {
+            #[serde(rename = "/a/int")]
+            int: u32,
+            #[serde(rename = "/a/tuple")]
+            tuple: (u8, String),
+            #[serde(rename = "/b")]
+            list: Vec<i64>,
+            #[serde(rename = "/c")]
+            mode: Mode,
+            #[serde(rename = "/d")]
+            optional: Option<u32>,
+            missing: Option<u32>,
+        }
+
+        let values: Values = file.hash_table().unwrap().deserialize().unwrap();
+        assert_eq!(
+            values,
+            Values {
+                int: 1,
+                tuple: (2, "test".to_string()),
+                list: vec![1, 2, 3],
+                mode: Mode::Dark,
+                optional: Some(4),
+                missing: None,
+            }
+        );
+
+        // Containers are skipped
+        let keys: BTreeMap<String, serde::de::IgnoredAny> =
+            file.hash_table().unwrap().deserialize().unwrap();
+        assert_eq!(
+            keys.keys().collect::<Vec<_>>(),
+            ["/a/int", "/a/tuple", "/b", "/c", "/d"]
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use crate::read::error::Result;
+use crate::read::{Entry, File};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// The category of a [`Difference`]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum DifferenceKind {
+    /// The item only exists in the other file
+    Added,
+
+    /// The item only exists in this file
+    Removed,
+
+    /// The item exists in both files, but its value or type differs
+    Changed,
+}
+
+impl Display for DifferenceKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            DifferenceKind::Added => "Added",
+            DifferenceKind::Removed => "Removed",
+            DifferenceKind::Changed => "Changed",
+        };
+
+        write!(f, "{}", text)
+    }
+}
+
+/// A difference between two files found by [`File::diff`]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Difference {
+    path: Vec<String>,
+    kind: DifferenceKind,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+impl Difference {
+    /// The keys leading to the item, starting at the root hash table. Every element except the
+    /// last one is the key of a nested hash table.
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// The category of this difference
+    pub fn kind(&self) -> DifferenceKind {
+        self.kind
+    }
+
+    /// A summary of the item in this file, if it exists there
+    ///
+    /// Values are printed in the GVariant text format.
+    pub fn before(&self) -> Option<&str> {
+        self.before.as_deref()
+    }
+
+    /// A summary of the item in the other file, if it exists there
+    ///
+    /// Values are printed in the GVariant text format.
+    pub fn after(&self) -> Option<&str> {
+        self.after.as_deref()
+    }
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} '{}'", self.kind, self.path.join("' > '"))?;
+        match (&self.before, &self.after) {
+            (Some(before), Some(after)) => write!(f, ": {} -> {}", before, after),
+            (Some(summary), None) | (None, Some(summary)) => write!(f, ": {}", summary),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// A short description of an entry, used for comparison and in [`Difference`]
+fn summary(entry: &Entry) -> String {
+    match entry {
+        Entry::Value(value) => crate::variant::print(value, true),
+        Entry::Table(_) => "hash table".to_string(),
+        Entry::Container => "container".to_string(),
+    }
+}
+
+/// Whether two entries are equal. The contents of tables and containers are compared by their
+/// own entries.
+fn entry_eq(a: &Entry, b: &Entry) -> bool {
+    match (a, b) {
+        (Entry::Value(a), Entry::Value(b)) => a == b,
+        (Entry::Table(_), Entry::Table(_)) => true,
+        (Entry::Container, Entry::Container) => true,
+        _ => false,
+    }
+}
+
+impl<'a> File<'a> {
+    /// Collect all entries of the file by their path
+    pub(crate) fn entries(&self) -> Result<BTreeMap<Vec<String>, Entry<'_, 'a>>> {
+        self.hash_table()?.walk().collect()
+    }
+
+    /// Compare the contents of this file with `other`
+    ///
+    /// In contrast to comparing the bytes of two files, this compares the keys and values of all
+    /// hash tables recursively. Files that only differ in their byte order, the order of their
+    /// chunks, the number of hash buckets or their bloom filters are equal.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert("int", 42u32).unwrap();
+    /// let little = FileWriter::new()
+    ///     .write_to_vec_with_table(table_builder)
+    ///     .unwrap();
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert("int", 42u32).unwrap();
+    /// let big = FileWriter::for_big_endian()
+    ///     .write_to_vec_with_table(table_builder)
+    ///     .unwrap();
+    ///
+    /// let little = File::from_bytes(Cow::Owned(little)).unwrap();
+    /// let big = File::from_bytes(Cow::Owned(big)).unwrap();
+    /// assert!(little.semantic_eq(&big).unwrap());
+    /// ```
+    pub fn semantic_eq(&self, other: &File) -> Result<bool> {
+        Ok(self.diff(other)?.is_empty())
+    }
+
+    /// List the differences between the contents of this file and `other`, sorted by path
+    ///
+    /// See [`File::semantic_eq`] for what is compared. Items that only exist in `other` are
+    /// [`DifferenceKind::Added`], items that only exist in this file are
+    /// [`DifferenceKind::Removed`].
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// use gvdb::read::{DifferenceKind, File};
+    ///
+    /// let file1 = File::from_file(&PathBuf::from("test-data/test1.gvdb")).unwrap();
+    /// let file2 = File::from_file(&PathBuf::from("test-data/test2.gvdb")).unwrap();
+    /// for difference in file1.diff(&file2).unwrap() {
+    ///     println!("{}", difference);
+    /// }
+    /// ```
+    pub fn diff(&self, other: &File) -> Result<Vec<Difference>> {
+        let old = self.entries()?;
+        let mut new = other.entries()?;
+        let mut differences = Vec::new();
+
+        for (path, old_entry) in old {
+            match new.remove(&path) {
+                Some(new_entry) if entry_eq(&old_entry, &new_entry) => {}
+                Some(new_entry) => differences.push(Difference {
+                    path,
+                    kind: DifferenceKind::Changed,
+                    before: Some(summary(&old_entry)),
+                    after: Some(summary(&new_entry)),
+                }),
+                None => differences.push(Difference {
+                    path,
+                    kind: DifferenceKind::Removed,
+                    before: Some(summary(&old_entry)),
+                    after: None,
+                }),
+            }
+        }
+
+        for (path, new_entry) in new {
+            differences.push(Difference {
+                path,
+                kind: DifferenceKind::Added,
+                before: None,
+                after: Some(summary(&new_entry)),
+            });
+        }
+
+        differences.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(differences)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::{DifferenceKind, File};
+    use crate::test::*;
+    use crate::write::{FileWriter, HashTableBuilder};
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+
+    fn file(writer: FileWriter, table_builder: HashTableBuilder) -> File<'static> {
+        let data = writer.write_to_vec_with_table(table_builder).unwrap();
+        File::from_bytes(Cow::Owned(data)).unwrap()
+    }
+
+    #[test]
+    fn equal() {
+        let file1 = File::from_file(&TEST_FILE_2).unwrap();
+        assert!(file1.semantic_eq(&file1).unwrap());
+
+        // Different bucket count, insertion order and byte order
+        let mut nested = HashTableBuilder::new();
+        nested.insert("int", 42u32).unwrap();
+        let mut table_builder = HashTableBuilder::new().with_bucket_count(7);
+        table_builder.insert_table("table", nested).unwrap();
+        table_builder
+            .insert_string("string", "test string")
+            .unwrap();
+        let file2 = file(FileWriter::for_big_endian(), table_builder);
+
+        assert!(file1.semantic_eq(&file2).unwrap());
+        assert!(file2.semantic_eq(&file1).unwrap());
+    }
+
+    #[test]
+    fn differences() {
+        let mut nested = HashTableBuilder::new();
+        nested.insert("int", 42u32).unwrap();
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert_table("table", nested).unwrap();
+        table_builder.insert_string("removed", "gone").unwrap();
+        table_builder.insert("typ", 1u32).unwrap();
+        let old = file(FileWriter::new(), table_builder);
+
+        let mut nested = HashTableBuilder::new();
+        nested.insert("int", 43u32).unwrap();
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert_table("table", nested).unwrap();
+        table_builder.insert_string("dir/added", "new").unwrap();
+        table_builder.insert("typ", 1u64).unwrap();
+        let new = file(FileWriter::new(), table_builder);
+
+        assert!(!old.semantic_eq(&new).unwrap());
+        let differences = old.diff(&new).unwrap();
+        let summary = differences
+            .iter()
+            .map(|d| (d.path().join(">"), d.kind(), d.before(), d.after()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "dir/".to_string(),
+                    DifferenceKind::Added,
+                    None,
+                    Some("container")
+                ),
+                (
+                    "dir/added".to_string(),
+                    DifferenceKind::Added,
+                    None,
+                    Some("'new'")
+                ),
+                (
+                    "removed".to_string(),
+                    DifferenceKind::Removed,
+                    Some("'gone'"),
+                    None
+                ),
+                (
+                    "table>int".to_string(),
+                    DifferenceKind::Changed,
+                    Some("uint32 42"),
+                    Some("uint32 43")
+                ),
+                (
+                    "typ".to_string(),
+                    DifferenceKind::Changed,
+                    Some("uint32 1"),
+                    Some("uint64 1")
+                ),
+            ]
+        );
+
+        assert_eq!(
+            differences[3].to_string(),
+            "Changed 'table' > 'int': uint32 42 -> uint32 43"
+        );
+        assert_eq!(differences[2].to_string(), "Removed 'removed': 'gone'");
+    }
+}
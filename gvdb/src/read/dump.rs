@@ -0,0 +1,258 @@
+use crate::read::error::{Error, Result};
+use crate::read::{File, HashItemType, HashTable};
+use crate::variant::print;
+use std::fmt::Write;
+
+/// The output format of [`File::dump`]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DumpFormat {
+    /// One line per item
+    ///
+    /// Values are written as `key = value`, with the value in the GVariant text format with type
+    /// annotations. Containers are written as `key -> child, child`. Nested hash tables are
+    /// written as `key:`, followed by their items indented by two spaces.
+    #[default]
+    KeyValue,
+
+    /// A dictionary of type `a{sv}` in the GVariant text format, with one item per line
+    ///
+    /// Nested hash tables are nested dictionaries and containers are arrays with the keys of
+    /// their children. The text can be read with [`parse`](crate::variant::parse).
+    GVariant,
+}
+
+impl<'a> File<'a> {
+    /// Write a human readable representation of the contents of the file to `writer`
+    ///
+    /// The items of every hash table are sorted by key, so the output only depends on the
+    /// contents of the file and not on the order of the items in the file. This makes it suitable
+    /// for golden file tests of generated files.
+    ///
+    /// ```
+    /// let path = std::path::PathBuf::from("test-data/test2.gvdb");
+    /// let file = gvdb::read::File::from_file(&path).unwrap();
+    ///
+    /// let mut text = Vec::new();
+    /// file.dump(&mut text, gvdb::read::DumpFormat::KeyValue).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(text).unwrap(),
+    ///     "string = 'test string'\ntable:\n  int = uint32 42\n"
+    /// );
+    /// ```
+    pub fn dump(&self, writer: &mut impl std::io::Write, format: DumpFormat) -> Result<()> {
+        let table = self.hash_table()?;
+        let mut text = String::new();
+        match format {
+            DumpFormat::KeyValue => dump_key_value(&mut text, &table, 0)?,
+            DumpFormat::GVariant => {
+                dump_gvariant(&mut text, &table, 0)?;
+                text.push('\n');
+            }
+        }
+
+        writer
+            .write_all(text.as_bytes())
+            .map_err(|err| Error::Io(err, None))
+    }
+}
+
+/// The keys, types and data of all items of `table`, sorted by key, and the keys of the items in
+/// the order of their index
+#[allow(clippy::type_complexity)]
+fn sorted_items<'a>(
+    table: &HashTable<'a, '_>,
+) -> Result<(Vec<(String, HashItemType, &'a [u8])>, Vec<String>)> {
+    let items = table.iter_raw().collect::<Result<Vec<_>>>()?;
+    let keys = items.iter().map(|(key, _, _)| key.clone()).collect();
+    let mut sorted = items;
+    sorted.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+    Ok((sorted, keys))
+}
+
+/// The keys of the children of a container with the data `data`
+fn children<'k>(data: &[u8], keys: &'k [String]) -> Result<Vec<&'k str>> {
+    data.chunks_exact(4)
+        .map(|child| {
+            let child = u32::from_le_bytes(child.try_into().unwrap()) as usize;
+            keys.get(child).map(String::as_str).ok_or_else(|| {
+                Error::Data(format!("Child with invalid index encountered: {}", child))
+            })
+        })
+        .collect()
+}
+
+fn quote(string: &str) -> String {
+    print(&zvariant::Value::from(string), false)
+}
+
+fn dump_key_value(text: &mut String, table: &HashTable, depth: usize) -> Result<()> {
+    let (items, keys) = sorted_items(table)?;
+    let indent = "  ".repeat(depth);
+    for (key, typ, data) in items {
+        match typ {
+            HashItemType::Value => {
+                let value = table.value_for_bytes(data)?;
+                let _ = writeln!(text, "{}{} = {}", indent, key, print(&value, true));
+            }
+            HashItemType::Container => {
+                let children = children(data, &keys)?;
+                let _ = writeln!(text, "{}{} -> {}", indent, key, children.join(", "));
+            }
+            HashItemType::HashTable => {
+                let _ = writeln!(text, "{}{}:", indent, key);
+                dump_key_value(text, &table.get_hash_table(&key)?, depth + 1)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn dump_gvariant(text: &mut String, table: &HashTable, depth: usize) -> Result<()> {
+    let (items, keys) = sorted_items(table)?;
+    if items.is_empty() {
+        text.push_str("@a{sv} {}");
+        return Ok(());
+    }
+
+    let indent = "  ".repeat(depth + 1);
+    let _ = writeln!(text, "{{");
+    for (index, (key, typ, data)) in items.iter().enumerate() {
+        let _ = write!(text, "{}{}: <", indent, quote(key));
+        match typ {
+            HashItemType::Value => {
+                let value = table.value_for_bytes(data)?;
+                let _ = write!(text, "{}", print(&value, true));
+            }
+            HashItemType::Container => {
+                let children = children(data, &keys)?;
+                if children.is_empty() {
+                    text.push_str("@as []");
+                } else {
+                    let children: Vec<String> = children.into_iter().map(quote).collect();
+                    let _ = write!(text, "[{}]", children.join(", "));
+                }
+            }
+            HashItemType::HashTable => {
+                dump_gvariant(text, &table.get_hash_table(key)?, depth + 1)?;
+            }
+        }
+
+        let separator = if index + 1 < items.len() { "," } else { "" };
+        let _ = writeln!(text, ">{}", separator);
+    }
+
+    let _ = write!(text, "{}}}", "  ".repeat(depth));
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::write::{FileWriter, HashTableBuilder};
+    use crate::Endian;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+
+    fn dump(file: &File, format: DumpFormat) -> String {
+        let mut text = Vec::new();
+        file.dump(&mut text, format).unwrap();
+        String::from_utf8(text).unwrap()
+    }
+
+    fn new_file(endian: Endian) -> File<'static> {
+        let mut nested = HashTableBuilder::new();
+        nested.insert("b", vec!["x", "y"]).unwrap();
+        nested.insert("a", 1.5f64).unwrap();
+
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert("value", 5u8).unwrap();
+        table_builder.insert("dir/b", "it's").unwrap();
+        table_builder.insert("dir/a", (1i32, true)).unwrap();
+        table_builder.insert_table("table", nested).unwrap();
+        table_builder
+            .insert_table("empty", HashTableBuilder::new())
+            .unwrap();
+
+        let data = FileWriter::endian(endian)
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        File::from_bytes(Cow::Owned(data)).unwrap()
+    }
+
+    #[test]
+    fn dump_key_value() {
+        let file = new_file(Endian::Little);
+        assert_str_eq!(
+            dump(&file, DumpFormat::KeyValue),
+            "\
+dir/ -> dir/b, dir/a
+dir/a = (1, true)
+dir/b = \"it's\"
+empty:
+table:
+  a = 1.5
+  b = ['x', 'y']
+value = byte 0x05
+"
+        );
+
+        assert_eq!(
+            dump(&file, DumpFormat::KeyValue),
+            dump(&new_file(Endian::Big), DumpFormat::KeyValue)
+        );
+    }
+
+    #[test]
+    fn dump_gvariant() {
+        let file = new_file(Endian::Little);
+        let text = dump(&file, DumpFormat::GVariant);
+        assert_str_eq!(
+            text,
+            "\
+{
+  'dir/': <['dir/b', 'dir/a']>,
+  'dir/a': <(1, true)>,
+  'dir/b': <\"it's\">,
+  'empty': <@a{sv} {}>,
+  'table': <{
+    'a': <1.5>,
+    'b': <['x', 'y']>
+  }>,
+  'value': <byte 0x05>
+}
+"
+        );
+
+        assert_eq!(text, dump(&new_file(Endian::Big), DumpFormat::GVariant));
+
+        let value = crate::variant::parse("a{sv}", &text).unwrap();
+        let zvariant::Value::Dict(dict) = value else {
+            panic!("Expected a dictionary");
+        };
+        assert_eq!(dict.iter().count(), 6);
+
+        let file = new_empty_file();
+        assert_eq!(dump(&file, DumpFormat::GVariant), "@a{sv} {}\n");
+        assert_eq!(dump(&file, DumpFormat::KeyValue), "");
+    }
+
+    #[test]
+    fn dump_test_files() {
+        for path in [&*TEST_FILE_1, &*TEST_FILE_2, &*TEST_FILE_3] {
+            let file = File::from_file(path).unwrap();
+            for format in [DumpFormat::KeyValue, DumpFormat::GVariant] {
+                assert_eq!(dump(&file, format), dump(&file, format));
+            }
+        }
+
+        let file = File::from_file(&TEST_FILE_1).unwrap();
+        assert_eq!(
+            dump(&file, DumpFormat::KeyValue),
+            "root_key = (uint32 1234, uint32 98765, 'TEST_STRING_VALUE')\n"
+        );
+    }
+}
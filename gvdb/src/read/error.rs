@@ -1,8 +1,13 @@
-use std::fmt::{Display, Formatter};
-use std::num::TryFromIntError;
+use crate::read::HashItemType;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{FromUtf8Error, String};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::num::TryFromIntError;
+use core::str::Utf8Error;
+#[cfg(feature = "std")]
 use std::path::{Path, PathBuf};
-use std::str::Utf8Error;
-use std::string::FromUtf8Error;
 
 /// An error that can occur during GVDB file reading
 #[non_exhaustive]
@@ -12,32 +17,165 @@ pub enum Error {
     Utf8(Utf8Error),
 
     /// Generic I/O error. Path contains an optional filename if applicable
+    #[cfg(feature = "std")]
     Io(std::io::Error, Option<PathBuf>),
 
     /// An error occured when deserializing variant data with zvariant
+    #[cfg(feature = "std")]
     ZVariant(zvariant::Error),
 
     /// Tried to access an invalid data offset
     DataOffset,
 
-    /// Tried to read unaligned data
-    DataAlignment,
+    /// A pointer points outside of the file data, or its end is before its start
+    PointerOutOfBounds {
+        /// The start offset of the pointer
+        start: u32,
+        /// The end offset of the pointer
+        end: u32,
+        /// The size of the file data
+        len: usize,
+    },
 
-    /// Like InvalidData but with context information in the provided string
+    /// A pointer is not aligned correctly for the data it points to
+    UnalignedPointer {
+        /// The start offset of the pointer
+        start: u32,
+        /// The required alignment
+        alignment: u32,
+    },
+
+    /// The file does not start with a GVDB signature in either byte order
+    InvalidHeader {
+        /// The signature found at the start of the file
+        signature: [u32; 2],
+    },
+
+    /// The file has a format version other than 0
+    UnsupportedVersion {
+        /// The version stored in the file header
+        found: u32,
+    },
+
+    /// A hash item has a type character other than `v`, `H` or `L`
+    InvalidItemType {
+        /// The type character stored in the hash item
+        found: u8,
+    },
+
+    /// An item has a different type than the operation requires, e.g. a hash table was requested
+    /// for a key that contains a value
+    WrongItemType {
+        /// The type required by the operation
+        expected: HashItemType,
+        /// The type of the item
+        found: HashItemType,
+        /// The key of the item
+        key: String,
+    },
+
+    /// The checksum stored in the file header does not match the file data, see
+    /// [`File::verify_checksum`](crate::read::File::verify_checksum)
+    ChecksumMismatch {
+        /// The checksum stored in the file header
+        expected: u32,
+        /// The checksum of the file data
+        found: u32,
+    },
+
+    /// The file data is inconsistent. The string contains context information
     Data(String),
 
     /// The item with the specified key does not exist in the hash table
     KeyNotFound(String),
+
+    /// The file exceeds one of the [`Limits`](crate::read::Limits) it is read with
+    LimitExceeded(String),
+
+    /// A value has a different GVariant type than the typed getter requires, e.g.
+    /// [`HashTable::get_u32`](crate::read::HashTable::get_u32) for a string value
+    TypeMismatch {
+        /// The key of the value
+        key: String,
+        /// A description of the types accepted by the getter, e.g. `type 'b'`
+        expected: String,
+        /// The type signature of the value
+        found: String,
+    },
+
+    /// An error occurred while reading an item of a nested hash table
+    ///
+    /// The error message starts with the path, e.g. `error at '/table/int'`. The keys are separated
+    /// by `/`, `/` and `\` in the keys are escaped with `\`.
+    Context {
+        /// The keys of the enclosing hash tables, followed by the key of the item
+        path: Vec<String>,
+        /// The error that occurred
+        source: Box<Error>,
+    },
 }
 
 impl Error {
+    #[cfg(feature = "std")]
     pub(crate) fn from_io_with_filename(filename: &Path) -> impl FnOnce(std::io::Error) -> Error {
         let path = filename.to_path_buf();
         move |err| Error::Io(err, Some(path))
     }
+
+    /// The path of the item at which the error occurred, if it is inside a nested hash table
+    ///
+    /// The path consists of the keys of the enclosing hash tables, followed by the key of the
+    /// item, see [`Error::Context`].
+    pub fn path(&self) -> Option<&[String]> {
+        match self {
+            Error::Context { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The error without the context of [`Error::Context`]
+    pub fn inner(&self) -> &Error {
+        match self {
+            Error::Context { source, .. } => source.inner(),
+            err => err,
+        }
+    }
+
+    /// Whether the error is [`Error::KeyNotFound`], also if it is wrapped in [`Error::Context`]
+    ///
+    /// Errors of nested hash tables are wrapped in [`Error::Context`], so matching
+    /// `Err(Error::KeyNotFound(_))` only works for the root hash table.
+    ///
+    /// ```
+    /// let path = std::path::PathBuf::from("test-data/test2.gvdb");
+    /// let file = gvdb::read::File::from_file(&path).unwrap();
+    /// let table = file.hash_table().unwrap().get_hash_table("table").unwrap();
+    ///
+    /// let err = table.get_u32("missing").unwrap_err();
+    /// assert!(!matches!(err, gvdb::read::Error::KeyNotFound(_)));
+    /// assert!(err.is_key_not_found());
+    /// ```
+    pub fn is_key_not_found(&self) -> bool {
+        matches!(self.inner(), Error::KeyNotFound(_))
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Context { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
 }
 
-impl std::error::Error for Error {}
+#[cfg(feature = "std")]
+impl serde::de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Data(msg.to_string())
+    }
+}
 
 impl From<FromUtf8Error> for Error {
     fn from(err: FromUtf8Error) -> Self {
@@ -51,6 +189,7 @@ impl From<Utf8Error> for Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<zvariant::Error> for Error {
     fn from(err: zvariant::Error) -> Self {
         Self::ZVariant(err)
@@ -63,38 +202,36 @@ impl From<TryFromIntError> for Error {
     }
 }
 
-impl<S, T> From<safe_transmute::Error<'_, S, T>> for Error {
-    fn from(err: safe_transmute::Error<S, T>) -> Self {
-        let name = std::any::type_name::<T>();
-
-        match err {
-            safe_transmute::Error::Guard(guard_err) => {
-                if guard_err.actual > guard_err.required {
-                    Self::Data(format!(
-                        "Found {} unexpected trailing bytes at the end while reading {}",
-                        guard_err.actual - guard_err.required,
-                        name
-                    ))
-                } else {
-                    Self::Data(format!(
-                        "Missing {} bytes to read {}",
-                        guard_err.required - guard_err.actual,
-                        name
-                    ))
-                }
-            }
-            safe_transmute::Error::Unaligned(..) => {
-                Self::Data(format!("Unaligned data read for {}", name))
-            }
-            _ => Self::Data(format!("Error transmuting data as {}", name)),
+impl<T> From<zerocopy::SizeError<&[u8], T>> for Error {
+    fn from(err: zerocopy::SizeError<&[u8], T>) -> Self {
+        let name = core::any::type_name::<T>();
+        let actual = err.into_src().len();
+        let required = core::mem::size_of::<T>();
+
+        if actual > required {
+            Self::Data(format!(
+                "Found {} unexpected trailing bytes at the end while reading {}",
+                actual - required,
+                name
+            ))
+        } else {
+            Self::Data(format!(
+                "Missing {} bytes to read {}",
+                required - actual,
+                name
+            ))
         }
     }
 }
 
+/// Prefix of the messages of all errors caused by inconsistent file data
+const DATA_ERROR_PREFIX: &str = "A data inconsistency error occured while reading gvdb file";
+
 impl Display for Error {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             Error::Utf8(err) => write!(f, "Error converting string to UTF-8: {}", err),
+            #[cfg(feature = "std")]
             Error::Io(err, path) => {
                 if let Some(path) = path {
                     write!(
@@ -107,39 +244,112 @@ impl Display for Error {
                     write!(f, "I/O error: {}", err)
                 }
             }
+            #[cfg(feature = "std")]
             Error::ZVariant(err) => write!(f, "Error parsing ZVariant data: {}", err),
-            Error::DataOffset => {
+            Error::DataOffset | Error::PointerOutOfBounds { .. } => {
                 write!(f, "Tried to access an invalid data offset. Most likely reason is a corrupted GVDB file")
             }
-            Error::DataAlignment => {
+            Error::UnalignedPointer { .. } => {
                 write!(
                     f,
                     "Tried to read unaligned data. Most likely reason is a corrupted GVDB file"
                 )
             }
-            Error::Data(msg) => {
+            Error::InvalidHeader { signature } => {
                 write!(
                     f,
-                    "A data inconsistency error occured while reading gvdb file: {}",
-                    msg
+                    "{}: Invalid GVDB header signature: {:?}. Is this a GVariant database file?",
+                    DATA_ERROR_PREFIX, signature
+                )
+            }
+            Error::UnsupportedVersion { found } => {
+                write!(
+                    f,
+                    "{}: Unknown GVDB file format version: {}",
+                    DATA_ERROR_PREFIX, found
                 )
             }
+            Error::InvalidItemType { found } => {
+                write!(
+                    f,
+                    "{}: Invalid HashItemType: '{}'",
+                    DATA_ERROR_PREFIX, *found as char
+                )
+            }
+            Error::WrongItemType {
+                expected,
+                found,
+                key,
+            } => {
+                write!(
+                    f,
+                    "{}: Unable to parse item for key '{}' as ",
+                    DATA_ERROR_PREFIX, key
+                )?;
+                match expected {
+                    HashItemType::Value => {
+                        write!(f, "GVariant: Expected type 'v', got type {}", found)
+                    }
+                    HashItemType::HashTable => {
+                        write!(f, "hash table: Expected type 'H', got type '{}'", found)
+                    }
+                    HashItemType::Container => {
+                        write!(f, "container: Expected type 'L', got type '{}'", found)
+                    }
+                }
+            }
+            Error::ChecksumMismatch { expected, found } => {
+                write!(
+                    f,
+                    "Checksum mismatch: Expected {:#010x}, found {:#010x}. The file is truncated or corrupted",
+                    expected, found
+                )
+            }
+            Error::Data(msg) => {
+                write!(f, "{}: {}", DATA_ERROR_PREFIX, msg)
+            }
             Error::KeyNotFound(key) => {
                 write!(f, "The item with the key '{}' does not exist", key)
             }
+            Error::LimitExceeded(msg) => {
+                write!(
+                    f,
+                    "Resource limit exceeded while reading gvdb file: {}",
+                    msg
+                )
+            }
+            Error::TypeMismatch {
+                key,
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "Value for key '{}' has type '{}', expected {}",
+                    key, found, expected
+                )
+            }
+            Error::Context { path, source } => {
+                // Keys can contain '/' themselves, so it is escaped to keep the path unambiguous
+                write!(f, "error at '")?;
+                for key in path {
+                    write!(f, "/{}", key.replace('\\', "\\\\").replace('/', "\\/"))?;
+                }
+                write!(f, "': {}", source)
+            }
         }
     }
 }
 
 /// The Result type for [`Error`]
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 #[cfg(test)]
 mod test {
-    use crate::read::{Error, Header, Pointer};
+    use crate::read::{Error, HashItemType, Header, Pointer};
     use matches::assert_matches;
-    use safe_transmute::{transmute_one_pedantic, transmute_one_to_bytes, transmute_vec};
     use std::num::TryFromIntError;
+    use zerocopy::{FromBytes, IntoBytes};
 
     #[test]
     fn from() {
@@ -159,40 +369,77 @@ mod test {
         let err = Error::Data("my data error".to_string());
         assert!(format!("{}", err).contains("my data error"));
 
+        let err = Error::PointerOutOfBounds {
+            start: 8,
+            end: 4,
+            len: 16,
+        };
+        assert!(format!("{}", err).contains("data offset"));
+
+        let err = Error::UnalignedPointer {
+            start: 3,
+            alignment: 4,
+        };
+        assert!(format!("{}", err).contains("unaligned"));
+
+        let err = Error::InvalidHeader { signature: [1, 2] };
+        assert!(format!("{}", err).contains("[1, 2]"));
+
+        let err = Error::UnsupportedVersion { found: 2 };
+        assert!(format!("{}", err).contains("version: 2"));
+
+        let err = Error::InvalidItemType { found: b'x' };
+        assert!(format!("{}", err).contains("'x'"));
+
+        let err = Error::WrongItemType {
+            expected: HashItemType::HashTable,
+            found: HashItemType::Value,
+            key: "test".to_string(),
+        };
+        assert_eq!(
+            format!("{}", err),
+            "A data inconsistency error occured while reading gvdb file: Unable to parse item for key 'test' as hash table: Expected type 'H', got type 'Value'"
+        );
+
+        let err = Error::ChecksumMismatch {
+            expected: 1,
+            found: 2,
+        };
+        assert!(format!("{}", err).contains("0x00000001"));
+
         let err = Error::KeyNotFound("test".to_string());
         assert!(format!("{}", err).contains("test"));
 
+        let err = Error::LimitExceeded("too many items".to_string());
+        assert!(format!("{}", err).contains("Resource limit exceeded"));
+
+        let err = Error::TypeMismatch {
+            key: "test".to_string(),
+            expected: "type 'u'".to_string(),
+            found: "s".to_string(),
+        };
+        assert_eq!(
+            format!("{}", err),
+            "Value for key 'test' has type 's', expected type 'u'"
+        );
+
         let err = Error::from(zvariant::Error::Message("test".to_string()));
         assert!(format!("{}", err).contains("test"));
 
-        let to_transmute = Header::new(false, 0, Pointer::NULL);
-        let mut bytes = transmute_one_to_bytes(&to_transmute).to_vec();
+        let header = Header::new(false, 0, Pointer::NULL);
+        let mut bytes = header.as_bytes().to_vec();
         bytes.extend_from_slice(b"fail");
-        let res = transmute_one_pedantic::<Header>(&bytes);
+        let res = Header::read_from_bytes(&bytes);
         let err = Error::from(res.unwrap_err());
         assert_matches!(err, Error::Data(_));
         assert!(format!("{}", err).contains("unexpected trailing bytes"));
 
-        let to_transmute = Header::new(false, 0, Pointer::NULL);
-        let mut bytes = transmute_one_to_bytes(&to_transmute).to_vec();
+        let mut bytes = header.as_bytes().to_vec();
         bytes.remove(bytes.len() - 1);
-        let res = transmute_one_pedantic::<Header>(&bytes);
+        let res = Header::read_from_bytes(&bytes);
         let err = Error::from(res.unwrap_err());
         assert_matches!(err, Error::Data(_));
         assert!(format!("{}", err).contains("Missing 1 bytes"));
-
-        let to_transmute = Header::new(false, 0, Pointer::NULL);
-        let mut bytes = b"unalign".to_vec();
-        bytes.extend_from_slice(transmute_one_to_bytes(&to_transmute));
-        let res = transmute_one_pedantic::<Header>(&bytes[7..]);
-        let err = Error::from(res.unwrap_err());
-        assert_matches!(err, Error::Data(_));
-        assert!(format!("{}", err).contains("Unaligned"));
-
-        let bytes = vec![0u8; 5];
-        let res = transmute_vec::<u8, Header>(bytes);
-        let err = Error::from(res.unwrap_err());
-        assert_matches!(err, Error::Data(_));
-        assert!(format!("{}", err).contains("transmuting data as gvdb::read::header::Header"));
+        assert!(format!("{}", err).contains("gvdb::read::header::Header"));
     }
 }
@@ -1,28 +1,52 @@
 use crate::read::error::{Error, Result};
 use crate::read::header::Header;
+use crate::read::lazy::LazyData;
 use crate::read::pointer::Pointer;
-use crate::read::HashTable;
-use safe_transmute::transmute_one_pedantic;
+use crate::read::{HashItemType, HashTable, Limits};
+use crate::util::trace_event;
+use crate::Endian;
 use std::borrow::Cow;
-use std::io::Read;
+use std::io::{Read, Seek};
 use std::mem::size_of;
+use std::ops::Range;
 use std::path::Path;
+use zerocopy::FromBytes;
 
 #[derive(Debug)]
 pub(crate) enum Data<'a> {
     Cow(Cow<'a, [u8]>),
     #[cfg(feature = "mmap")]
     Mmap(memmap2::Mmap),
+    Lazy(LazyData),
 }
 
-impl AsRef<[u8]> for Data<'_> {
-    fn as_ref(&self) -> &[u8] {
+impl Data<'_> {
+    /// The size of the data
+    pub(crate) fn len(&self) -> usize {
         match self {
-            Data::Cow(cow) => cow.as_ref(),
+            Data::Cow(cow) => cow.len(),
             #[cfg(feature = "mmap")]
-            Data::Mmap(mmap) => mmap.as_ref(),
+            Data::Mmap(mmap) => mmap.len(),
+            Data::Lazy(lazy) => lazy.len(),
         }
     }
+
+    /// The bytes in `range`, or `None` if `range` is out of bounds
+    ///
+    /// Only data that is read lazily can fail with [`Error::Io`].
+    pub(crate) fn get(&self, range: Range<usize>) -> Result<Option<&[u8]>> {
+        match self {
+            Data::Cow(cow) => Ok(cow.get(range)),
+            #[cfg(feature = "mmap")]
+            Data::Mmap(mmap) => Ok(mmap.get(range)),
+            Data::Lazy(lazy) => lazy.get(range),
+        }
+    }
+
+    /// All bytes of the data
+    pub(crate) fn all(&self) -> Result<&[u8]> {
+        Ok(self.get(0..self.len())?.unwrap_or_default())
+    }
 }
 
 /// The root of a GVDB file
@@ -82,6 +106,8 @@ impl AsRef<[u8]> for Data<'_> {
 pub struct File<'a> {
     pub(crate) data: Data<'a>,
     pub(crate) byteswapped: bool,
+    pub(crate) limits: Limits,
+    pub(crate) lookup_fallback: bool,
 }
 
 impl<'a> File<'a> {
@@ -89,17 +115,56 @@ impl<'a> File<'a> {
     pub(crate) fn get_header(&self) -> Result<Header> {
         let header_data = self
             .data
-            .as_ref()
-            .get(0..size_of::<Header>())
+            .get(0..size_of::<Header>())?
             .ok_or(Error::DataOffset)?;
-        Ok(transmute_one_pedantic(header_data)?)
+        Ok(Header::read_from_bytes(header_data)?)
     }
 
     /// Returns the root hash table of the file
-    pub fn hash_table(&self) -> Result<HashTable> {
+    pub fn hash_table(&self) -> Result<HashTable<'_, 'a>> {
         let header = self.get_header()?;
         let root_ptr = header.root();
-        HashTable::for_bytes(*root_ptr, self)
+        HashTable::for_bytes(*root_ptr, self, 0)
+    }
+
+    /// Returns the nested hash table `name` of the root hash table
+    ///
+    /// This is the counterpart to
+    /// [`FileWriter::write_with_tables`](crate::write::FileWriter::write_with_tables), which stores
+    /// independent namespaces as nested hash tables.
+    ///
+    /// ```
+    /// # use gvdb::read::File;
+    /// # use std::path::PathBuf;
+    /// let file = File::from_file(&PathBuf::from("test-data/test2.gvdb")).unwrap();
+    /// let table = file.table("table").unwrap();
+    /// assert_eq!(table.get::<u32>("int").unwrap(), 42);
+    /// ```
+    pub fn table(&self, name: &str) -> Result<HashTable<'_, 'a>> {
+        self.hash_table()?.get_hash_table(name)
+    }
+
+    /// Returns the names of all nested hash tables of the root hash table, sorted by name
+    ///
+    /// Returns [`Error::WrongItemType`] if the root hash table contains items that are not hash
+    /// tables.
+    pub fn table_names(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for (key, item) in self.hash_table()?.keys_and_items()? {
+            let typ = item.typ()?;
+            if typ != HashItemType::HashTable {
+                return Err(Error::WrongItemType {
+                    expected: HashItemType::HashTable,
+                    found: typ,
+                    key,
+                });
+            }
+
+            names.push(key);
+        }
+
+        names.sort();
+        Ok(names)
     }
 
     /// Dereference a pointer
@@ -108,40 +173,73 @@ impl<'a> File<'a> {
         let end: usize = pointer.end() as usize;
         let alignment: usize = alignment as usize;
 
+        let out_of_bounds = || Error::PointerOutOfBounds {
+            start: pointer.start(),
+            end: pointer.end(),
+            len: self.data.len(),
+        };
+
         if start > end {
-            Err(Error::DataOffset)
+            Err(out_of_bounds())
         } else if start & (alignment - 1) != 0 {
-            Err(Error::DataAlignment)
+            Err(Error::UnalignedPointer {
+                start: pointer.start(),
+                alignment: alignment as u32,
+            })
         } else {
-            self.data.as_ref().get(start..end).ok_or(Error::DataOffset)
+            self.data.get(start..end)?.ok_or_else(out_of_bounds)
         }
     }
 
     fn read_header(&mut self) -> Result<()> {
         let header = self.get_header()?;
-        if !header.header_valid() {
-            return Err(Error::Data(
-                "Invalid GVDB header. Is this a GVDB file?".to_string(),
-            ));
-        }
-
         self.byteswapped = header.is_byteswap()?;
 
         if header.version() != 0 {
-            return Err(Error::Data(format!(
-                "Unknown GVDB file format version: {}",
-                header.version()
-            )));
+            return Err(Error::UnsupportedVersion {
+                found: header.version(),
+            });
         }
 
+        trace_event!(
+            debug,
+            byteswapped = self.byteswapped,
+            root = ?header.root(),
+            "Read GVDB header"
+        );
         Ok(())
     }
 
     /// Interpret a slice of bytes as a GVDB file
     pub fn from_bytes(bytes: Cow<'a, [u8]>) -> Result<Self> {
+        Self::from_bytes_with_limits(bytes, Limits::default())
+    }
+
+    /// Interpret a slice of bytes as a GVDB file, and restrict the resources used to read it
+    ///
+    /// Use this for data from untrusted sources. Reading data that exceeds `limits` returns
+    /// [`Error::LimitExceeded`].
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::{Error, File, Limits};
+    ///
+    /// let data = std::fs::read("test-data/test2.gvdb").unwrap();
+    /// let file = File::from_bytes_with_limits(Cow::Owned(data), Limits::new().with_max_depth(0))
+    ///     .unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// assert!(matches!(table.get_hash_table("table"), Err(Error::LimitExceeded(_))));
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(len = bytes.len()))
+    )]
+    pub fn from_bytes_with_limits(bytes: Cow<'a, [u8]>, limits: Limits) -> Result<Self> {
         let mut this = Self {
             data: Data::Cow(bytes),
             byteswapped: false,
+            limits,
+            lookup_fallback: false,
         };
 
         this.read_header()?;
@@ -154,6 +252,10 @@ impl<'a> File<'a> {
     /// let path = std::path::PathBuf::from("test-data/test3.gresource");
     /// let file = gvdb::read::File::from_file(&path).unwrap();
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", fields(filename = %filename.display()))
+    )]
     pub fn from_file(filename: &Path) -> Result<Self> {
         let mut file =
             std::fs::File::open(filename).map_err(Error::from_io_with_filename(filename))?;
@@ -167,21 +269,163 @@ impl<'a> File<'a> {
         Self::from_bytes(Cow::Owned(data))
     }
 
+    /// Interpret the data of `reader` as GVDB, and read it on demand
+    ///
+    /// The GVDB data starts at the current position of `reader` and extends to its end. This
+    /// allows reading GVDB data that is embedded in a larger file without reading the whole file
+    /// into memory. Only the header is read immediately, hash tables and values are read when they
+    /// are accessed. Data that has been read is kept in memory until the `File` is dropped.
+    ///
+    /// Reading from `reader` can fail with [`Error::Io`] whenever data is accessed.
+    ///
+    /// ```
+    /// use std::io::{Cursor, Seek, SeekFrom, Write};
+    /// use gvdb::read::File;
+    ///
+    /// // A container file with GVDB data after a 16 byte prefix
+    /// let mut container = Cursor::new(vec![0u8; 16]);
+    /// container.seek(SeekFrom::End(0)).unwrap();
+    /// container.write_all(&std::fs::read("test-data/test2.gvdb").unwrap()).unwrap();
+    ///
+    /// container.seek(SeekFrom::Start(16)).unwrap();
+    /// let file = File::from_reader(container).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// assert_eq!(table.get::<String>("string").unwrap(), "test string");
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn from_reader(reader: impl Read + Seek + Send + 'static) -> Result<Self> {
+        let mut this = Self {
+            data: Data::Lazy(LazyData::new(reader, None)?),
+            byteswapped: false,
+            limits: Limits::default(),
+            lookup_fallback: false,
+        };
+
+        this.read_header()?;
+
+        Ok(this)
+    }
+
+    /// Open a file and interpret the data as GVDB, and read it on demand
+    ///
+    /// This is a safe alternative to [`File::from_file_mmap`] for large files of which only a few
+    /// values are needed. Like with a memory mapped file, only the header is read when the file is
+    /// opened, but all data is copied into memory when it is accessed. If the file is modified
+    /// while it is open, lookups return wrong data or errors, for example [`Error::Io`] if the
+    /// file was truncated, but never cause undefined behavior.
+    ///
+    /// See [`File::from_reader`] for details.
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use gvdb::read::File;
+    ///
+    /// let file = File::from_file_lazy(&PathBuf::from("test-data/test2.gvdb")).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// assert_eq!(table.get::<String>("string").unwrap(), "test string");
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", fields(filename = %filename.display()))
+    )]
+    pub fn from_file_lazy(filename: &Path) -> Result<Self> {
+        let file = std::fs::File::open(filename).map_err(Error::from_io_with_filename(filename))?;
+        let mut this = Self {
+            data: Data::Lazy(LazyData::new(file, Some(filename.to_path_buf()))?),
+            byteswapped: false,
+            limits: Limits::default(),
+            lookup_fallback: false,
+        };
+
+        this.read_header()?;
+
+        Ok(this)
+    }
+
+    /// Asynchronously open a file and interpret the data as GVDB
+    /// ```
+    /// # tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap().block_on(async {
+    /// let path = std::path::PathBuf::from("test-data/test3.gresource");
+    /// let file = gvdb::read::File::from_file_async(&path).await.unwrap();
+    /// # });
+    /// ```
+    #[cfg(feature = "async")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", fields(filename = %filename.display()))
+    )]
+    pub async fn from_file_async(filename: &Path) -> Result<Self> {
+        let data = tokio::fs::read(filename)
+            .await
+            .map_err(Error::from_io_with_filename(filename))?;
+        Self::from_bytes(Cow::Owned(data))
+    }
+
     /// Open a file and `mmap` it into memory.
     ///
     /// # Safety
     ///
     /// This is marked unsafe as the file could be modified on-disk while the mmap is active.
     /// This will cause undefined behavior. You must make sure to employ your own locking and to
-    /// reload the file yourself when any modification occurs.
+    /// reload the file yourself when any modification occurs. Use [`File::from_file_lazy`] to
+    /// read large files on demand without this restriction.
     #[cfg(feature = "mmap")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", fields(filename = %filename.display()))
+    )]
     pub unsafe fn from_file_mmap(filename: &Path) -> Result<Self> {
+        Self::from_file_mmap_with_options(filename, &crate::read::MmapOptions::default())
+    }
+
+    /// Open a file and `mmap` it into memory, with `options` to control how it is loaded
+    ///
+    /// Advising the kernel how the file will be accessed can reduce the latency of the first
+    /// lookups considerably.
+    ///
+    /// # Safety
+    ///
+    /// See [`File::from_file_mmap`].
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use gvdb::read::{File, MmapAdvice, MmapOptions};
+    ///
+    /// let options = MmapOptions::new().with_advice(MmapAdvice::WillNeed);
+    /// let file = unsafe {
+    ///     File::from_file_mmap_with_options(&PathBuf::from("test-data/test3.gresource"), &options)
+    /// }
+    /// .unwrap();
+    /// ```
+    #[cfg(feature = "mmap")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", fields(filename = %filename.display()))
+    )]
+    pub unsafe fn from_file_mmap_with_options(
+        filename: &Path,
+        options: &crate::read::MmapOptions,
+    ) -> Result<Self> {
         let file = std::fs::File::open(filename).map_err(Error::from_io_with_filename(filename))?;
-        let mmap = memmap2::Mmap::map(&file).map_err(Error::from_io_with_filename(filename))?;
+        let mut mmap_options = memmap2::MmapOptions::new();
+        if options.populate() {
+            mmap_options.populate();
+        }
+        let mmap = mmap_options
+            .map(&file)
+            .map_err(Error::from_io_with_filename(filename))?;
+
+        #[cfg(unix)]
+        if options.advice() != crate::read::MmapAdvice::Normal {
+            mmap.advise(options.advice().into())
+                .map_err(Error::from_io_with_filename(filename))?;
+        }
 
         let mut this = Self {
             data: Data::Mmap(mmap),
             byteswapped: false,
+            limits: Limits::default(),
+            lookup_fallback: false,
         };
 
         this.read_header()?;
@@ -189,16 +433,105 @@ impl<'a> File<'a> {
         Ok(this)
     }
 
-    /// Determine the endianess to use for zvariant
-    pub(crate) fn zvariant_endianess(&self) -> zvariant::Endian {
-        if cfg!(target_endian = "little") && !self.byteswapped
-            || cfg!(target_endian = "big") && self.byteswapped
-        {
-            zvariant::LE
+    /// Scan all items of a hash table when a key is not found in its hash bucket
+    ///
+    /// GVDB files store the items of each hash bucket in a contiguous range, sorted by bucket.
+    /// Some third-party writers don't follow this order, so lookups miss items that exist. With
+    /// the fallback enabled, every lookup that misses scans all items of the hash table, which
+    /// makes such files readable at the cost of slow lookups for keys that don't exist.
+    ///
+    /// Items that are only found by the fallback are reported as warnings with the `tracing`
+    /// feature. Use [`File::validate`] to find all items that are stored in the wrong bucket.
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// use gvdb::read::File;
+    ///
+    /// let file = File::from_file(&PathBuf::from("test-data/test1.gvdb"))
+    ///     .unwrap()
+    ///     .with_lookup_fallback(true);
+    /// assert!(file.lookup_fallback());
+    /// ```
+    pub fn with_lookup_fallback(mut self, fallback: bool) -> Self {
+        self.lookup_fallback = fallback;
+        self
+    }
+
+    /// Whether lookups scan all items when a key is not found in its hash bucket
+    pub fn lookup_fallback(&self) -> bool {
+        self.lookup_fallback
+    }
+
+    /// The byte order of the file
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// use gvdb::Endian;
+    /// use gvdb::read::File;
+    ///
+    /// let file = File::from_file(&PathBuf::from("test-data/test1.gvdb")).unwrap();
+    /// assert_eq!(file.endianness(), Endian::Little);
+    /// ```
+    pub fn endianness(&self) -> Endian {
+        Endian::from_byteswapped(self.byteswapped)
+    }
+
+    /// Whether the byte order of the file differs from the byte order of this machine
+    pub fn is_byteswapped(&self) -> bool {
+        self.byteswapped
+    }
+
+    /// The version of the GVDB file format. Only version 0 is supported.
+    pub fn version(&self) -> u32 {
+        // The header has already been validated when the file was opened
+        self.get_header()
+            .map(|header| header.version())
+            .unwrap_or_default()
+    }
+
+    /// Check the data of the file against the checksum stored in its header
+    ///
+    /// Files written with [`FileWriter::with_checksum`](crate::write::FileWriter::with_checksum)
+    /// store a CRC32 checksum of all data after the header. This detects truncated or otherwise
+    /// damaged files without reading every hash table.
+    ///
+    /// Returns `Ok(true)` if the checksum matches and `Ok(false)` if the file has no checksum.
+    /// Returns [`Error::ChecksumMismatch`] if the data doesn't match the checksum.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert("int", 42u32).unwrap();
+    /// let data = FileWriter::new()
+    ///     .with_checksum(true)
+    ///     .write_to_vec_with_table(table_builder)
+    ///     .unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// assert!(file.verify_checksum().unwrap());
+    /// ```
+    pub fn verify_checksum(&self) -> Result<bool> {
+        let expected = self.get_header()?.checksum();
+        // A checksum of zero can't be told apart from a file without a checksum
+        if expected == 0 {
+            return Ok(false);
+        }
+
+        let found = crc32fast::hash(&self.data.all()?[size_of::<Header>()..]);
+        if found == expected {
+            Ok(true)
         } else {
-            zvariant::BE
+            Err(Error::ChecksumMismatch { expected, found })
         }
     }
+
+    /// Determine the endianess to use for zvariant
+    pub(crate) fn zvariant_endianess(&self) -> zvariant::Endian {
+        self.endianness().into()
+    }
 }
 
 impl std::fmt::Debug for File<'_> {
@@ -225,13 +558,13 @@ mod test {
     use std::mem::size_of;
     use std::path::PathBuf;
 
-    use crate::read::{Error, HashItem, Header, Pointer};
+    use crate::read::{Error, HashItem, HashItemType, Header, Limits, Pointer};
     use crate::test::*;
     use crate::write::{FileWriter, HashTableBuilder};
     use matches::assert_matches;
     #[allow(unused_imports)]
     use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
-    use safe_transmute::transmute_one_to_bytes;
+    use zerocopy::IntoBytes;
 
     #[test]
     fn test_file_1() {
@@ -246,6 +579,61 @@ mod test {
         assert_is_file_1(&file);
     }
 
+    #[test]
+    fn from_file_lazy() {
+        let file = File::from_file_lazy(&TEST_FILE_1).unwrap();
+        assert_is_file_1(&file);
+
+        assert_matches!(
+            File::from_file_lazy(&PathBuf::from("this_file_does_not_exist")),
+            Err(Error::Io(_, Some(_)))
+        );
+
+        // Truncating the file while it is open results in an error
+        let path: PathBuf = ["test-data", "temp_lazy.gvdb"].iter().collect();
+        std::fs::copy(&*TEST_FILE_2, &path).unwrap();
+        let file = File::from_file_lazy(&path).unwrap();
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(size_of::<Header>() as u64)
+            .unwrap();
+        let res = file.hash_table().map(|_| ());
+        std::fs::remove_file(&path).unwrap();
+        assert_matches!(res, Err(Error::Io(_, Some(p))) if p == path);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap_options() {
+        use crate::read::{MmapAdvice, MmapOptions};
+
+        for advice in [
+            MmapAdvice::Normal,
+            MmapAdvice::Random,
+            MmapAdvice::Sequential,
+            MmapAdvice::WillNeed,
+        ] {
+            for populate in [false, true] {
+                let options = MmapOptions::new()
+                    .with_advice(advice)
+                    .with_populate(populate);
+                assert_eq!(options.populate(), populate);
+                let file = unsafe { File::from_file_mmap_with_options(&TEST_FILE_3, &options) };
+                assert_is_file_3(&file.unwrap());
+            }
+        }
+
+        let res = unsafe {
+            File::from_file_mmap_with_options(
+                &PathBuf::from("this_file_does_not_exist"),
+                &MmapOptions::new().with_advice(MmapAdvice::Random),
+            )
+        };
+        assert_matches!(res, Err(Error::Io(_, Some(_))));
+    }
+
     #[test]
     fn test_file_2() {
         let file = File::from_file(&TEST_FILE_2).unwrap();
@@ -261,18 +649,24 @@ mod test {
     #[test]
     fn invalid_header() {
         let header = Header::new_be(0, Pointer::new(0, 0));
-        let mut data = transmute_one_to_bytes(&header).to_vec();
+        let mut data = header.as_bytes().to_vec();
 
         data[0] = 0;
-        assert_matches!(File::from_bytes(Cow::Owned(data)), Err(Error::Data(_)));
+        assert_matches!(
+            File::from_bytes(Cow::Owned(data)),
+            Err(Error::InvalidHeader { .. })
+        );
     }
 
     #[test]
     fn invalid_version() {
         let header = Header::new_le(1, Pointer::new(0, 0));
-        let data = transmute_one_to_bytes(&header).to_vec();
+        let data = header.as_bytes().to_vec();
 
-        assert_matches!(File::from_bytes(Cow::Owned(data)), Err(Error::Data(_)));
+        assert_matches!(
+            File::from_bytes(Cow::Owned(data)),
+            Err(Error::UnsupportedVersion { found: 1 })
+        );
     }
 
     #[test]
@@ -282,6 +676,16 @@ mod test {
         println!("{}", res.unwrap_err());
     }
 
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_file_3_async() {
+        let file = File::from_file_async(&TEST_FILE_3).await.unwrap();
+        assert_is_file_3(&file);
+
+        let res = File::from_file_async(&PathBuf::from("this_file_does_not_exist")).await;
+        assert_matches!(res, Err(Error::Io(_, Some(_))));
+    }
+
     #[cfg(feature = "mmap")]
     #[test]
     fn file_error_mmap() {
@@ -293,9 +697,56 @@ mod test {
         }
     }
 
+    #[test]
+    fn from_reader() {
+        use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+        let file = File::from_reader(std::fs::File::open(&*TEST_FILE_3).unwrap()).unwrap();
+        assert_is_file_3(&file);
+
+        // GVDB data embedded after a prefix
+        let mut container = Cursor::new(vec![0xffu8; 13]);
+        container.seek(SeekFrom::End(0)).unwrap();
+        container
+            .write_all(&std::fs::read(&*TEST_FILE_2).unwrap())
+            .unwrap();
+        container.seek(SeekFrom::Start(13)).unwrap();
+        let file = File::from_reader(container).unwrap();
+        assert_is_file_2(&file);
+        assert!(!file.verify_checksum().unwrap());
+
+        // A source that fails after the header has been read
+        struct Failing(Cursor<Vec<u8>>, usize);
+
+        impl Read for Failing {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.1 == 0 {
+                    return Err(std::io::Error::other("Read failed"));
+                }
+                self.1 -= 1;
+                self.0.read(buf)
+            }
+        }
+
+        impl Seek for Failing {
+            fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+                self.0.seek(pos)
+            }
+        }
+
+        let data = std::fs::read(&*TEST_FILE_2).unwrap();
+        let file = File::from_reader(Failing(Cursor::new(data), 1)).unwrap();
+        assert_matches!(file.hash_table(), Err(Error::Io(_, None)));
+
+        assert_matches!(
+            File::from_reader(Cursor::new(vec![0u8; 4])),
+            Err(Error::DataOffset)
+        );
+    }
+
     fn create_minimal_file() -> File<'static> {
         let header = Header::new_le(0, Pointer::new(0, 0));
-        let data = transmute_one_to_bytes(&header).to_vec();
+        let data = header.as_bytes().to_vec();
         assert_bytes_eq(
             &data,
             &[
@@ -310,7 +761,7 @@ mod test {
     #[test]
     fn test_minimal_file() {
         let file = create_minimal_file();
-        format!("{file:?}");
+        let _ = format!("{file:?}");
     }
 
     #[test]
@@ -328,7 +779,7 @@ mod test {
         // The root pointer end is always at position sizeof(u32 * 5).
         // As this is little endian, we can just modify the first byte.
         let root_ptr_end = size_of::<u32>() * 5;
-        data[root_ptr_end] = data[root_ptr_end] - 25;
+        data[root_ptr_end] -= 25;
 
         let file = File::from_bytes(Cow::Owned(data)).unwrap();
         let err = file.hash_table().unwrap_err();
@@ -347,7 +798,7 @@ mod test {
         // The root pointer end is always at position sizeof(u32 * 5).
         // As this is little endian, we can just modify the first byte.
         let root_ptr_end = size_of::<u32>() * 5;
-        data[root_ptr_end] = data[root_ptr_end] - 23;
+        data[root_ptr_end] -= 23;
 
         let file = File::from_bytes(Cow::Owned(data)).unwrap();
         let err = file.hash_table().unwrap_err();
@@ -371,8 +822,7 @@ mod test {
         let start = file.hash_table().unwrap().hash_items_offset() + hash_item_size * 2;
 
         let parent_field = start + 4;
-        data[parent_field..parent_field + size_of::<u32>()]
-            .copy_from_slice(safe_transmute::transmute_one_to_bytes(&10u32.to_le()));
+        data[parent_field..parent_field + size_of::<u32>()].copy_from_slice(&10u32.to_le_bytes());
 
         println!("{:?}", File::from_bytes(Cow::Owned(data.clone())).unwrap());
 
@@ -399,8 +849,7 @@ mod test {
         let start = file.hash_table().unwrap().hash_items_offset() + hash_item_size * 2;
 
         let parent_field = start + 4;
-        data[parent_field..parent_field + size_of::<u32>()]
-            .copy_from_slice(safe_transmute::transmute_one_to_bytes(&1u32.to_le()));
+        data[parent_field..parent_field + size_of::<u32>()].copy_from_slice(&1u32.to_le_bytes());
 
         println!("{:?}", File::from_bytes(Cow::Owned(data.clone())).unwrap());
 
@@ -410,13 +859,80 @@ mod test {
         assert!(format!("{}", err).contains("loop"));
     }
 
+    #[test]
+    fn parent_loop_empty_key() {
+        let writer = FileWriter::new();
+        let mut table = HashTableBuilder::new();
+        table.insert_string("parent/test", "test").unwrap();
+        let mut data = writer.write_to_vec_with_table(table).unwrap();
+
+        let file = File::from_bytes(Cow::Owned(data.clone())).unwrap();
+        let table = file.hash_table().unwrap();
+        let (index, _) = table.find_hash_item("parent/test").unwrap();
+
+        // An item with an empty key that is its own parent matches any key with its hash value
+        let start = table.pointer.start() as usize
+            + table.hash_items_offset()
+            + size_of::<HashItem>() * index;
+        data[start + 4..start + 8].copy_from_slice(&(index as u32).to_le_bytes());
+        data[start + 12..start + 14].copy_from_slice(&0u16.to_le_bytes());
+
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+        assert_matches!(
+            table.get::<String>("parent/test"),
+            Err(Error::KeyNotFound(_))
+        );
+        assert_matches!(table.keys(), Err(Error::Data(_)));
+    }
+
+    #[test]
+    fn nested_table_loop() {
+        let mut table = HashTableBuilder::new();
+        let mut nested = HashTableBuilder::new();
+        nested.insert("int", 42u32).unwrap();
+        table.insert_table("table", nested).unwrap();
+        let mut data = FileWriter::new().write_to_vec_with_table(table).unwrap();
+
+        let file = File::from_bytes(Cow::Owned(data.clone())).unwrap();
+        let table = file.hash_table().unwrap();
+        let (index, _) = table.find_hash_item("table").unwrap();
+
+        // Let the nested hash table point to the root hash table
+        let start = table.pointer.start() as usize
+            + table.hash_items_offset()
+            + size_of::<HashItem>() * index;
+        data[start + 16..start + 24].copy_from_slice(table.pointer.as_bytes());
+
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let mut table = file.hash_table().unwrap();
+        for _ in 0..64 {
+            table = table.get_hash_table("table").unwrap();
+        }
+        let err = table.get_hash_table("table").unwrap_err();
+        assert_matches!(err.inner(), Error::Data(_));
+        assert_eq!(err.path().unwrap().len(), 65);
+        assert!(format!("{}", err).contains("loop"));
+
+        let debug = format!("{:?}", file);
+        assert!(debug.contains("Nested hash table contains itself"));
+        assert!(file.hash_table().unwrap().walk().any(|res| res.is_err()));
+    }
+
     #[test]
     fn test_dereference_offset1() {
         // Pointer start > EOF
         let file = create_minimal_file();
         let res = file.dereference(&Pointer::new(40, 42), 2);
 
-        assert_matches!(res, Err(Error::DataOffset));
+        assert_matches!(
+            res,
+            Err(Error::PointerOutOfBounds {
+                start: 40,
+                end: 42,
+                ..
+            })
+        );
         println!("{}", res.unwrap_err());
     }
 
@@ -426,7 +942,7 @@ mod test {
         let file = create_minimal_file();
         let res = file.dereference(&Pointer::new(10, 0), 2);
 
-        assert_matches!(res, Err(Error::DataOffset));
+        assert_matches!(res, Err(Error::PointerOutOfBounds { .. }));
         println!("{}", res.unwrap_err());
     }
 
@@ -436,7 +952,7 @@ mod test {
         let file = create_minimal_file();
         let res = file.dereference(&Pointer::new(10, 0), 2);
 
-        assert_matches!(res, Err(Error::DataOffset));
+        assert_matches!(res, Err(Error::PointerOutOfBounds { .. }));
         println!("{}", res.unwrap_err());
     }
 
@@ -446,7 +962,13 @@ mod test {
         let file = create_minimal_file();
         let res = file.dereference(&Pointer::new(1, 2), 2);
 
-        assert_matches!(res, Err(Error::DataAlignment));
+        assert_matches!(
+            res,
+            Err(Error::UnalignedPointer {
+                start: 1,
+                alignment: 2
+            })
+        );
         println!("{}", res.unwrap_err());
     }
 
@@ -458,7 +980,14 @@ mod test {
 
         // A table isn't a value
         let table_res = table.get_value("table");
-        assert_matches!(table_res, Err(Error::Data(_)));
+        assert_matches!(
+            table_res,
+            Err(Error::WrongItemType {
+                expected: HashItemType::Value,
+                found: HashItemType::HashTable,
+                ..
+            })
+        );
     }
 
     #[test]
@@ -466,7 +995,7 @@ mod test {
         let file = File::from_file(&TEST_FILE_2).unwrap();
         let table = file.hash_table().unwrap();
         let res = table.get_hash_table("string");
-        assert_matches!(res, Err(Error::Data(_)));
+        assert_matches!(res, Err(Error::WrongItemType { ref key, .. }) if key == "string");
     }
 
     #[test]
@@ -482,4 +1011,34 @@ mod test {
         table2.keys().unwrap();
         table.keys().unwrap();
     }
+
+    #[test]
+    fn limits() {
+        let data = std::fs::read(&*TEST_FILE_3).unwrap();
+        let limits = Limits::new().with_max_items(5);
+        let file = File::from_bytes_with_limits(Cow::Borrowed(&data), limits).unwrap();
+        let err = file.hash_table().unwrap_err();
+        assert_matches!(err, Error::LimitExceeded(_));
+        assert!(format!("{}", err).contains("the maximum is 5"));
+
+        let data = std::fs::read(&*TEST_FILE_2).unwrap();
+        let limits = Limits::new().with_max_depth(0).with_max_value_size(8);
+        assert_eq!(limits.max_items(), None);
+        assert_eq!(limits.max_depth(), Some(0));
+        assert_eq!(limits.max_value_size(), Some(8));
+
+        let file = File::from_bytes_with_limits(Cow::Borrowed(&data), limits).unwrap();
+        let table = file.hash_table().unwrap();
+        assert_matches!(table.get_hash_table("table"), Err(Error::LimitExceeded(_)));
+        assert_matches!(table.get::<String>("string"), Err(Error::LimitExceeded(_)));
+        assert_matches!(table.get_raw("string"), Err(Error::LimitExceeded(_)));
+        assert!(table
+            .walk()
+            .any(|res| matches!(res, Err(Error::LimitExceeded(_)))));
+        let _ = format!("{:?}", table);
+
+        let limits = Limits::new().with_max_depth(1).with_max_value_size(64);
+        let file = File::from_bytes_with_limits(Cow::Borrowed(&data), limits).unwrap();
+        assert_is_file_2(&file);
+    }
 }
@@ -0,0 +1,134 @@
+use crate::read::error::Result;
+use crate::read::{File, HashHeader, HashTable, Pointer};
+use serde::Deserialize;
+
+/// A [`File`] together with its root hash table
+///
+/// A [`HashTable`] borrows the [`File`] it was read from, so both can't be stored in the same
+/// struct. This type owns the file and creates the hash table on demand, which is cheap as the
+/// table was already checked when it was created with [`File::into_table`].
+///
+/// ```
+/// # use gvdb::read::File;
+/// # use std::path::PathBuf;
+/// use gvdb::read::FileWithTable;
+///
+/// struct Settings {
+///     table: FileWithTable<'static>,
+/// }
+///
+/// let file = File::from_file(&PathBuf::from("test-data/test2.gvdb")).unwrap();
+/// let settings = Settings {
+///     table: file.into_table().unwrap(),
+/// };
+/// assert_eq!(settings.table.get::<String>("string").unwrap(), "test string");
+/// ```
+#[derive(Debug)]
+pub struct FileWithTable<'file> {
+    file: File<'file>,
+    pointer: Pointer,
+    header: HashHeader,
+}
+
+impl<'file> FileWithTable<'file> {
+    /// The root hash table of the file
+    pub fn table(&self) -> HashTable<'_, 'file> {
+        HashTable {
+            file: &self.file,
+            pointer: self.pointer,
+            header: self.header,
+            depth: 0,
+            path: Vec::new(),
+        }
+    }
+
+    /// The file
+    pub fn file(&self) -> &File<'file> {
+        &self.file
+    }
+
+    /// Return the file and drop the hash table
+    pub fn into_file(self) -> File<'file> {
+        self.file
+    }
+
+    /// Returns the data for `key` deserialized as `T`, see [`HashTable::get`]
+    pub fn get<'a, T>(&'a self, key: &str) -> Result<T>
+    where
+        T: zvariant::Type + Deserialize<'a> + 'a,
+    {
+        self.table().get(key)
+    }
+
+    /// Returns the data for `key` as a [`enum@zvariant::Value`], see [`HashTable::get_value`]
+    pub fn get_value(&self, key: &str) -> Result<zvariant::Value<'_>> {
+        let table = self.table();
+        table.value_for_bytes(table.get_bytes(key)?)
+    }
+
+    /// Returns a list of all keys in the hash table, see [`HashTable::keys`]
+    pub fn keys(&self) -> Result<Vec<String>> {
+        self.table().keys()
+    }
+}
+
+impl<'file> File<'file> {
+    /// Convert this file into a [`FileWithTable`] that owns the file and gives access to its root
+    /// hash table
+    pub fn into_table(self) -> Result<FileWithTable<'file>> {
+        let table = self.hash_table()?;
+        let (pointer, header) = (table.pointer, table.header);
+
+        Ok(FileWithTable {
+            file: self,
+            pointer,
+            header,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::{Error, File, FileWithTable};
+    use crate::test::*;
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    #[test]
+    fn file_with_table() {
+        let table: FileWithTable<'static> =
+            File::from_file(&TEST_FILE_2).unwrap().into_table().unwrap();
+        println!("{:?}", table);
+
+        assert_eq!(table.get::<&str>("string").unwrap(), "test string");
+        assert_eq!(
+            table.get_value("string").unwrap(),
+            zvariant::Value::new("test string")
+        );
+        assert_matches!(table.get::<u32>("missing"), Err(Error::KeyNotFound(_)));
+
+        let mut keys = table.keys().unwrap();
+        keys.sort();
+        assert_eq!(keys, ["string", "table"]);
+        assert_eq!(
+            table
+                .table()
+                .get_hash_table("table")
+                .unwrap()
+                .get::<u32>("int")
+                .unwrap(),
+            42
+        );
+
+        let file = table.into_file();
+        assert!(file.hash_table().is_ok());
+
+        assert!(new_empty_file()
+            .into_table()
+            .unwrap()
+            .keys()
+            .unwrap()
+            .is_empty());
+    }
+}
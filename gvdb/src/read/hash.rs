@@ -1,17 +1,21 @@
 use crate::read::error::{Error, Result};
 use crate::read::file::File;
-use crate::read::hash_item::HashItem;
-use crate::util::djb_hash;
-use safe_transmute::{
-    transmute_many_pedantic, transmute_one, transmute_one_pedantic, TriviallyTransmutable,
-};
+use crate::read::hash_item::{HashItem, HashItemInfo};
+use crate::read::iter::{Iter, IterRaw, Keys, ValuesOwned};
+use crate::read::limits::MAX_DEPTH;
+use crate::read::walk::Walk;
+use crate::util::{djb_hash, trace_event};
 use serde::Deserialize;
+use std::borrow::Cow;
 use std::cmp::{max, min};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Debug, Formatter};
 use std::mem::size_of;
+use zerocopy::little_endian::U32;
+use zerocopy::FromBytes;
 use zvariant::Type;
 
-use super::{HashItemType, Pointer};
+use super::{HashHeader, HashItemType, Pointer};
 
 #[cfg(unix)]
 type GVariantDeserializer<'de, 'sig, 'f> =
@@ -19,58 +23,23 @@ type GVariantDeserializer<'de, 'sig, 'f> =
 #[cfg(not(unix))]
 type GVariantDeserializer<'de, 'sig, 'f> = zvariant::gvariant::Deserializer<'de, 'sig, 'f, ()>;
 
-/// The header of a GVDB hash table
-#[repr(C)]
-#[derive(Copy, Clone, PartialEq, Eq)]
-pub struct HashHeader {
-    n_bloom_words: u32,
-    n_buckets: u32,
-}
-
-unsafe impl TriviallyTransmutable for HashHeader {}
-
-impl HashHeader {
-    /// Create a new [`HashHeader`]` using the provided `bloom_shift`, `n_bloom_words` and
-    /// `n_buckets`
-    pub fn new(bloom_shift: u32, n_bloom_words: u32, n_buckets: u32) -> Self {
-        assert!(n_bloom_words < (1 << 27));
-        let n_bloom_words = bloom_shift << 27 | n_bloom_words;
-
-        Self {
-            n_bloom_words: n_bloom_words.to_le(),
-            n_buckets: n_buckets.to_le(),
-        }
-    }
-
-    /// Number of bloom words in the hash table header
-    pub fn n_bloom_words(&self) -> u32 {
-        u32::from_le(self.n_bloom_words) & ((1 << 27) - 1)
-    }
-
-    /// Size of the bloom words section in the header
-    pub fn bloom_words_len(&self) -> usize {
-        self.n_bloom_words() as usize * size_of::<u32>()
-    }
-
-    /// Number of hash buckets in the hash table header
-    pub fn n_buckets(&self) -> u32 {
-        u32::from_le(self.n_buckets)
-    }
-
-    /// Length of the hash buckets section in the header
-    pub fn buckets_len(&self) -> usize {
-        self.n_buckets() as usize * size_of::<u32>()
+/// Split the serialized data of a `v` typed GVariant into the data of the contained value and its
+/// type signature
+fn split_variant(data: &[u8]) -> Result<(&[u8], &str)> {
+    // The type signature is appended after a zero byte and never contains a zero byte itself
+    let separator = data
+        .iter()
+        .rposition(|byte| *byte == 0)
+        .ok_or_else(|| Error::Data("Variant data is missing its type signature".to_string()))?;
+
+    let signature = std::str::from_utf8(&data[separator + 1..])?;
+    if signature.is_empty() {
+        return Err(Error::Data(
+            "Variant data is missing its type signature".to_string(),
+        ));
     }
-}
 
-impl Debug for HashHeader {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("HashHeader")
-            .field("n_bloom_words", &self.n_bloom_words())
-            .field("n_buckets", &self.n_buckets())
-            .field("data", &safe_transmute::transmute_one_to_bytes(self))
-            .finish()
-    }
+    Ok((&data[..separator], signature))
 }
 
 /// A hash table inside a GVDB file
@@ -79,14 +48,34 @@ impl Debug for HashHeader {
 #[derive(Clone)]
 pub struct HashTable<'a, 'file> {
     pub(crate) file: &'a File<'file>,
-    pointer: Pointer,
+    pub(crate) pointer: Pointer,
     pub(crate) header: HashHeader,
+    /// The nesting depth below the root hash table
+    pub(crate) depth: usize,
+    /// The keys of the enclosing hash tables, added to the errors of lookups
+    pub(crate) path: Vec<String>,
 }
 
 impl<'a, 'file> HashTable<'a, 'file> {
     /// Interpret a chunk of bytes as a HashTable. The table_ptr should point to the hash table.
     /// Data has to be the complete GVDB file, as hash table items are stored somewhere else.
-    pub(crate) fn for_bytes(pointer: Pointer, root: &'a File<'file>) -> Result<Self> {
+    pub(crate) fn for_bytes(pointer: Pointer, root: &'a File<'file>, depth: usize) -> Result<Self> {
+        if let Some(max_depth) = root.limits.max_depth() {
+            if depth > max_depth {
+                return Err(Error::LimitExceeded(format!(
+                    "Hash table nesting depth {} exceeds the maximum of {}",
+                    depth, max_depth
+                )));
+            }
+        }
+
+        if depth > MAX_DEPTH {
+            return Err(Error::Data(format!(
+                "Hash table nesting depth {} exceeds the maximum of {}. The file appears to have a loop",
+                depth, MAX_DEPTH
+            )));
+        }
+
         let data = root.dereference(&pointer, 4)?;
         let header = Self::hash_header(data)?;
 
@@ -94,6 +83,8 @@ impl<'a, 'file> HashTable<'a, 'file> {
             file: root,
             pointer,
             header,
+            depth,
+            path: Vec::new(),
         };
 
         let header_len = size_of::<HashHeader>();
@@ -119,17 +110,80 @@ impl<'a, 'file> HashTable<'a, 'file> {
                 data.len()
             )))
         } else {
+            this.check_max_items()?;
+            trace_event!(
+                trace,
+                depth,
+                n_buckets = this.header.n_buckets(),
+                n_items = this.n_hash_items(),
+                "Read hash table"
+            );
             Ok(this)
         }
     }
 
+    fn check_max_items(&self) -> Result<()> {
+        match self.file.limits.max_items() {
+            Some(max_items) if self.n_hash_items() > max_items => {
+                Err(Error::LimitExceeded(format!(
+                    "Hash table contains {} items, the maximum is {}",
+                    self.n_hash_items(),
+                    max_items
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Interpret the data at `pointer` as a hash table nested in this hash table
+    pub(crate) fn nested(&self, pointer: Pointer) -> Result<HashTable<'a, 'file>> {
+        HashTable::for_bytes(pointer, self.file, self.depth + 1)
+    }
+
+    /// Interpret the data at `pointer` as the hash table at `key` nested in this hash table
+    pub(crate) fn nested_at(&self, pointer: Pointer, key: &str) -> Result<HashTable<'a, 'file>> {
+        let mut table = self.nested(pointer)?;
+        table.path = self.path.clone();
+        table.path.push(key.to_string());
+        Ok(table)
+    }
+
+    /// Add the path of the item at `key` to the error of `f`, if this is a nested hash table
+    ///
+    /// Errors that already have a path are returned unchanged.
+    pub(crate) fn with_path<T>(&self, key: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        f().map_err(|err| match err {
+            Error::Context { .. } => err,
+            err if self.path.is_empty() => err,
+            err => Error::Context {
+                path: self.path.iter().cloned().chain([key.to_string()]).collect(),
+                source: Box::new(err),
+            },
+        })
+    }
+
+    /// Dereference the value of `item` with the given alignment
+    fn dereference_value(&self, item: &HashItem, alignment: u32) -> Result<&'a [u8]> {
+        if let Some(max_value_size) = self.file.limits.max_value_size() {
+            if item.value_ptr().size() > max_value_size {
+                return Err(Error::LimitExceeded(format!(
+                    "Value of {} bytes exceeds the maximum of {} bytes",
+                    item.value_ptr().size(),
+                    max_value_size
+                )));
+            }
+        }
+
+        self.file.dereference(item.value_ptr(), alignment)
+    }
+
     /// Read the hash table header
     fn hash_header(data: &'a [u8]) -> Result<HashHeader> {
         let bytes: &[u8] = data
             .get(0..size_of::<HashHeader>())
             .ok_or(Error::DataOffset)?;
 
-        Ok(transmute_one(bytes)?)
+        Ok(HashHeader::read_from_bytes(bytes)?)
     }
 
     /// A reference to the data section of this [`HashTable`]
@@ -138,7 +192,7 @@ impl<'a, 'file> HashTable<'a, 'file> {
     }
 
     /// Retrieve a single [`u32`] at `offset`
-    fn get_u32(&self, offset: usize) -> Result<u32> {
+    fn read_u32(&self, offset: usize) -> Result<u32> {
         let bytes = self
             .data()?
             .get(offset..offset + size_of::<u32>())
@@ -156,10 +210,14 @@ impl<'a, 'file> HashTable<'a, 'file> {
 
     /// Returns the bloom words for this hash table
     #[allow(dead_code)]
-    fn bloom_words(&self) -> Result<Option<&[u32]>> {
+    fn bloom_words(&self) -> Result<Option<&[U32]>> {
+        if self.header.n_bloom_words() == 0 {
+            return Ok(None);
+        }
+
         // This indexing operation is safe as data is guaranteed to be larger than
-        // bloom_words_offset and this will just return an empty slice if end == offset
-        Ok(transmute_many_pedantic(
+        // bloom_words_end
+        Ok(<[U32]>::ref_from_bytes(
             &self.data()?[self.bloom_words_offset()..self.bloom_words_end()],
         )
         .ok())
@@ -171,16 +229,15 @@ impl<'a, 'file> HashTable<'a, 'file> {
         }
 
         let start = self.bloom_words_offset() + index * size_of::<u32>();
-        self.get_u32(start)
+        self.read_u32(start)
     }
 
-    // TODO: Calculate proper bloom shift
     fn bloom_shift(&self) -> usize {
-        0
+        self.header.bloom_shift() as usize
     }
 
     /// Check whether the hash value corresponds to the bloom filter
-    fn bloom_filter(&self, hash_value: u32) -> bool {
+    pub(crate) fn bloom_filter(&self, hash_value: u32) -> bool {
         if self.header.n_bloom_words() == 0 {
             return true;
         }
@@ -195,7 +252,7 @@ impl<'a, 'file> HashTable<'a, 'file> {
     }
 
     /// The offset of the hash buckets section
-    fn hash_buckets_offset(&self) -> usize {
+    pub(crate) fn hash_buckets_offset(&self) -> usize {
         self.bloom_words_end()
     }
 
@@ -205,9 +262,9 @@ impl<'a, 'file> HashTable<'a, 'file> {
     }
 
     /// Return the hash value at `index`
-    fn get_hash(&self, index: usize) -> Result<u32> {
+    pub(crate) fn get_hash(&self, index: usize) -> Result<u32> {
         let start = self.hash_buckets_offset() + index * size_of::<u32>();
-        self.get_u32(start)
+        self.read_u32(start)
     }
 
     /// The offset of the hash item section
@@ -216,7 +273,7 @@ impl<'a, 'file> HashTable<'a, 'file> {
     }
 
     /// The number of hash items
-    fn n_hash_items(&self) -> usize {
+    pub(crate) fn n_hash_items(&self) -> usize {
         let len = self.hash_items_end() - self.hash_items_offset();
         len / size_of::<HashItem>()
     }
@@ -227,107 +284,322 @@ impl<'a, 'file> HashTable<'a, 'file> {
     }
 
     /// Get the hash item at hash item index
-    fn get_hash_item_for_index(&self, index: usize) -> Result<HashItem> {
+    pub(crate) fn get_hash_item_for_index(&self, index: usize) -> Result<HashItem> {
         let size = size_of::<HashItem>();
         let start = self.hash_items_offset() + size * index;
         let end = start + size;
 
         let data = self.data()?.get(start..end).ok_or(Error::DataOffset)?;
-        Ok(transmute_one_pedantic(data)?)
+        Ok(HashItem::read_from_bytes(data)?)
+    }
+
+    /// List the keys that are used by more than one item, sorted
+    ///
+    /// Files written by GVDB writers never contain duplicate keys, but corrupt or adversarial files
+    /// can. Lookups always return the first item with a key, the other items can only be found by
+    /// iterating the hash table. [`File::validate`] reports every duplicate item as well.
+    ///
+    /// ```
+    /// # use gvdb::read::File;
+    /// # use std::path::PathBuf;
+    /// let file = File::from_file(&PathBuf::from("test-data/test2.gvdb")).unwrap();
+    /// assert!(file.hash_table().unwrap().duplicate_keys().unwrap().is_empty());
+    /// ```
+    pub fn duplicate_keys(&self) -> Result<Vec<String>> {
+        let mut counts = BTreeMap::new();
+        for key in self.keys()? {
+            *counts.entry(key).or_insert(0usize) += 1;
+        }
+
+        Ok(counts
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(key, _)| key)
+            .collect())
+    }
+
+    /// The index of the hash bucket that `key` belongs to
+    ///
+    /// The bucket is the [`djb_hash`](crate::djb_hash) of the key modulo the number of buckets of
+    /// this hash table. Returns `None` if the hash table has no buckets. The key doesn't need to
+    /// exist in the hash table.
+    ///
+    /// ```
+    /// # use gvdb::read::File;
+    /// # use std::path::PathBuf;
+    /// let file = File::from_file(&PathBuf::from("test-data/test2.gvdb")).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// assert!(table.bucket_for_key("string").is_some());
+    /// ```
+    pub fn bucket_for_key(&self, key: &str) -> Option<usize> {
+        let n_buckets = self.header.n_buckets();
+        if n_buckets == 0 {
+            None
+        } else {
+            Some((djb_hash(key) % n_buckets) as usize)
+        }
+    }
+
+    /// The number of items in the hash table
+    ///
+    /// This includes nested hash tables and the containers of keys with path separators. It is
+    /// computed from the size of the hash table without reading any items.
+    ///
+    /// ```
+    /// # use gvdb::read::File;
+    /// # use std::path::PathBuf;
+    /// let file = File::from_file(&PathBuf::from("test-data/test2.gvdb")).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// assert_eq!(table.len(), 2);
+    /// assert!(!table.is_empty());
+    /// ```
+    pub fn len(&self) -> usize {
+        self.n_hash_items()
+    }
+
+    /// Whether the hash table contains no items
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of values, nested hash tables and containers in the hash table
+    ///
+    /// The counts add up to [`HashTable::len`]. Returns an error if an item has an invalid type.
+    ///
+    /// ```
+    /// # use gvdb::read::File;
+    /// # use std::path::PathBuf;
+    /// let file = File::from_file(&PathBuf::from("test-data/test2.gvdb")).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// assert_eq!(table.counts().unwrap(), (1, 1, 0));
+    /// ```
+    pub fn counts(&self) -> Result<(usize, usize, usize)> {
+        let mut counts = (0, 0, 0);
+        for index in 0..self.n_hash_items() {
+            match self.get_hash_item_for_index(index)?.typ()? {
+                HashItemType::Value => counts.0 += 1,
+                HashItemType::HashTable => counts.1 += 1,
+                HashItemType::Container => counts.2 += 1,
+            }
+        }
+
+        Ok(counts)
     }
 
     /// Gets a list of keys contained in the hash table.
+    ///
+    /// The keys are ordered by item index. Use [`HashTable::iter_keys`] to avoid allocating a
+    /// [`String`] for every key.
     pub fn keys(&self) -> Result<Vec<String>> {
-        let count = self.n_hash_items();
-        let mut names: Vec<Option<String>> = vec![None; count];
+        self.iter_keys()
+            .map(|key| key.map(Cow::into_owned))
+            .collect()
+    }
 
-        let mut inserted = 0;
-        while inserted < count {
-            let last_inserted = inserted;
-            for index in 0..count {
-                let item = self.get_hash_item_for_index(index)?;
-                let parent: usize = item.parent().try_into()?;
-
-                if names[index].is_none() {
-                    // Only process items not already processed
-                    if parent == 0xffffffff {
-                        // root item
-                        let name = self.key_for_item(&item)?;
-                        let _ = std::mem::replace(&mut names[index], Some(name.to_string()));
-                        inserted += 1;
-                    } else if parent < count && names[parent].is_some() {
-                        // We already came across this item
-                        let name = self.key_for_item(&item)?;
-                        let parent_name = names.get(parent).unwrap().as_ref().unwrap();
-                        let full_name = parent_name.to_string() + name;
-                        let _ = std::mem::replace(&mut names[index], Some(full_name));
-                        inserted += 1;
-                    } else if parent > count {
+    /// Returns an iterator over the full keys of all items, ordered by item index
+    ///
+    /// Unlike [`HashTable::keys`], keys of items without a parent are borrowed from the file
+    /// data, and the keys of containers are only resolved once for all of their children.
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// use gvdb::read::File;
+    ///
+    /// let file = File::from_file(&PathBuf::from("test-data/test3.gresource")).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// let keys = table.iter_keys().collect::<Result<Vec<_>, _>>().unwrap();
+    /// assert!(keys.iter().any(|key| key == "/gvdb/rs/test/json/test.json"));
+    /// ```
+    pub fn iter_keys(&self) -> Keys<'_, 'a, 'file> {
+        Keys::new(self)
+    }
+
+    /// Gets the full keys of all items that start with `prefix`
+    ///
+    /// Instead of reading every item, this looks up the container of the last directory in
+    /// `prefix` (the part up to and including the last `/`) and only visits its children
+    /// recursively. Tables without containers, e.g. created with
+    /// [`HashTableBuilder::with_path_separator(None)`](crate::write::HashTableBuilder::with_path_separator),
+    /// are searched item by item. The order of the keys is unspecified.
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// use gvdb::read::File;
+    ///
+    /// let file = File::from_file(&PathBuf::from("test-data/test3.gresource")).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// let mut keys = table.keys_with_prefix("/gvdb/rs/test/json/").unwrap();
+    /// keys.sort();
+    /// assert_eq!(keys, vec!["/gvdb/rs/test/json/", "/gvdb/rs/test/json/test.json"]);
+    /// ```
+    pub fn keys_with_prefix(&self, prefix: &str) -> Result<Vec<String>> {
+        let Some(separator) = prefix.rfind('/') else {
+            return self.keys_with_prefix_scan(prefix);
+        };
+
+        let directory = &prefix[..=separator];
+        let data = match self.get_raw(directory) {
+            Ok((HashItemType::Container, data)) => data,
+            Ok(_) => return self.keys_with_prefix_scan(prefix),
+            Err(err) if err.is_key_not_found() => return self.keys_with_prefix_scan(prefix),
+            Err(err) => return Err(err),
+        };
+
+        let mut keys = Vec::new();
+        if directory.starts_with(prefix) {
+            keys.push(directory.to_string());
+        }
+
+        let mut visited = vec![false; self.n_hash_items()];
+        let mut pending = vec![(data, directory.to_string())];
+        while let Some((data, parent_key)) = pending.pop() {
+            for child in data.chunks_exact(size_of::<u32>()) {
+                let index = u32::from_le_bytes(child.try_into().unwrap()) as usize;
+                match visited.get_mut(index) {
+                    Some(visited) if !*visited => *visited = true,
+                    Some(_) => {
+                        return Err(Error::Data(format!(
+                            "Item {} is referenced multiple times. The file appears to have a loop",
+                            index
+                        )))
+                    }
+                    None => {
                         return Err(Error::Data(format!(
-                            "Parent with invalid offset encountered: {}",
-                            parent
-                        )));
+                            "Child with invalid index encountered: {}",
+                            index
+                        )))
                     }
                 }
-            }
 
-            if last_inserted == inserted {
-                // No insertion took place this round, there must be a parent loop
-                // We fail instead of infinitely looping
-                return Err(Error::Data(
-                    "Error finding all parent items. The file appears to have a loop".to_string(),
-                ));
+                let item = self.get_hash_item_for_index(index)?;
+                let key = parent_key.clone() + self.key_for_item(&item)?;
+                if !key.starts_with(prefix) {
+                    continue;
+                }
+
+                if item.typ()? == HashItemType::Container {
+                    pending.push((self.raw_item(&item)?.1, key.clone()));
+                }
+
+                keys.push(key);
             }
         }
 
-        let names = names.into_iter().map(|s| s.unwrap()).collect();
-        Ok(names)
+        Ok(keys)
+    }
+
+    /// Gets the full keys of all items that start with `prefix` by reading every item
+    fn keys_with_prefix_scan(&self, prefix: &str) -> Result<Vec<String>> {
+        Ok(self
+            .keys()?
+            .into_iter()
+            .filter(|key| key.starts_with(prefix))
+            .collect())
+    }
+
+    /// Gets the full keys together with their [`HashItem`], ordered by item index.
+    pub(crate) fn keys_and_items(&self) -> Result<Vec<(String, HashItem)>> {
+        self.keys()?
+            .into_iter()
+            .enumerate()
+            .map(|(index, key)| Ok((key, self.get_hash_item_for_index(index)?)))
+            .collect()
     }
 
-    /// Recurses through parents and check whether `item` has the specified full path name
+    /// Walks through the parents and checks whether `item` has the specified full path name
+    ///
+    /// A valid chain of parents visits every item at most once, so items with a parent loop are
+    /// rejected after visiting all items instead of looping forever.
     fn check_key(&self, item: &HashItem, key: &str) -> bool {
-        let this_key = match self.key_for_item(item) {
-            Ok(this_key) => this_key,
-            Err(_) => return false,
-        };
+        let mut item = *item;
+        let mut key = key;
+        for _ in 0..=self.n_hash_items() {
+            let this_key = match self.key_for_item(&item) {
+                Ok(this_key) => this_key,
+                Err(_) => return false,
+            };
 
-        if !key.ends_with(&this_key) {
-            return false;
-        }
+            if !key.ends_with(this_key) {
+                return false;
+            }
 
-        let parent = item.parent();
-        if key.len() == this_key.len() && parent == 0xffffffff {
-            return true;
-        }
+            let parent = item.parent();
+            if key.len() == this_key.len() && parent == 0xffffffff {
+                return true;
+            }
 
-        if parent < self.n_hash_items() as u32 && !key.is_empty() {
-            let parent_item = match self.get_hash_item_for_index(parent as usize) {
+            if parent >= self.n_hash_items() as u32 || key.is_empty() {
+                return false;
+            }
+
+            item = match self.get_hash_item_for_index(parent as usize) {
                 Ok(p) => p,
                 Err(_) => return false,
             };
-
-            let parent_key_len = key.len() - this_key.len();
-            return self.check_key(&parent_item, &key[0..parent_key_len]);
+            key = &key[..key.len() - this_key.len()];
         }
 
         false
     }
 
     /// Return the string that corresponds to the key part of the [`HashItem`].
-    fn key_for_item(&self, item: &HashItem) -> Result<&str> {
+    pub(crate) fn key_for_item(&self, item: &HashItem) -> Result<&'a str> {
         let data = self.file.dereference(&item.key_ptr(), 1)?;
         Ok(std::str::from_utf8(data)?)
     }
 
     /// Gets the item at key `key`.
     pub(crate) fn get_hash_item(&self, key: &str) -> Result<HashItem> {
+        Ok(self.find_hash_item(key)?.1)
+    }
+
+    /// Gets the index and the item at key `key`.
+    pub(crate) fn find_hash_item(&self, key: &str) -> Result<(usize, HashItem)> {
+        self.find_hash_item_with_hash(key, djb_hash(key))
+    }
+
+    /// Gets the index and the item at key `key`, whose hash value is `hash_value`.
+    ///
+    /// If the item is not found in its bucket and
+    /// [`File::with_lookup_fallback`](crate::read::File::with_lookup_fallback) is enabled, all
+    /// items are scanned.
+    fn find_hash_item_with_hash(&self, key: &str, hash_value: u32) -> Result<(usize, HashItem)> {
+        if let Some(found) = self.find_hash_item_in_bucket(key, hash_value)? {
+            return Ok(found);
+        }
+
+        if self.file.lookup_fallback {
+            for itemno in 0..self.n_hash_items() {
+                let item = self.get_hash_item_for_index(itemno)?;
+                if hash_value == item.hash_value() && self.check_key(&item, key) {
+                    trace_event!(
+                        warn,
+                        key,
+                        itemno,
+                        "Lookup hit outside of the hash bucket of the key, the hash table is not conforming"
+                    );
+                    return Ok((itemno, item));
+                }
+            }
+        }
+
+        Err(Error::KeyNotFound(key.to_string()))
+    }
+
+    /// Gets the index and the item at key `key` from the hash bucket of `hash_value`
+    fn find_hash_item_in_bucket(
+        &self,
+        key: &str,
+        hash_value: u32,
+    ) -> Result<Option<(usize, HashItem)>> {
         if self.header.n_buckets() == 0 || self.n_hash_items() == 0 {
-            return Err(Error::KeyNotFound(key.to_string()));
+            trace_event!(trace, key, "Lookup in empty hash table");
+            return Ok(None);
         }
 
-        let hash_value = djb_hash(key);
         if !self.bloom_filter(hash_value) {
-            return Err(Error::KeyNotFound(key.to_string()));
+            trace_event!(trace, key, "Lookup rejected by bloom filter");
+            return Ok(None);
         }
 
         let bucket = hash_value % self.header.n_buckets();
@@ -342,51 +614,116 @@ impl<'a, 'file> HashTable<'a, 'file> {
             ) as usize
         };
 
+        #[cfg(feature = "tracing")]
+        let firstno = itemno;
         while itemno < lastno {
             let item = self.get_hash_item_for_index(itemno)?;
             if hash_value == item.hash_value() && self.check_key(&item, key) {
-                return Ok(item);
+                trace_event!(
+                    trace,
+                    key,
+                    bucket,
+                    probes = itemno - firstno + 1,
+                    "Lookup hit"
+                );
+                return Ok(Some((itemno, item)));
             }
 
             itemno += 1;
         }
 
-        Err(Error::KeyNotFound(key.to_string()))
+        trace_event!(
+            trace,
+            key,
+            bucket,
+            probes = lastno.saturating_sub(firstno),
+            "Lookup miss"
+        );
+        Ok(None)
+    }
+
+    /// Get the type and the raw bytes of the [`HashItem`] at `index`.
+    pub(crate) fn get_raw_item_for_index(&self, index: usize) -> Result<(HashItemType, &'a [u8])> {
+        let item = self.get_hash_item_for_index(index)?;
+        self.raw_item(&item)
+    }
+
+    /// Get the type and the raw bytes of `item`.
+    pub(crate) fn raw_item(&self, item: &HashItem) -> Result<(HashItemType, &'a [u8])> {
+        let typ = item.typ()?;
+        let alignment = match typ {
+            HashItemType::Value => 8,
+            HashItemType::HashTable | HashItemType::Container => 4,
+        };
+
+        let data = if typ == HashItemType::Value {
+            self.dereference_value(item, alignment)?
+        } else {
+            self.file.dereference(item.value_ptr(), alignment)?
+        };
+
+        Ok((typ, data))
+    }
+
+    /// Returns the type and the raw bytes of the item at `key`, without decoding them.
+    ///
+    /// For [`HashItemType::Value`] this is the serialized GVariant of type `v` in the byte order
+    /// of the file. For [`HashItemType::HashTable`] it is the nested hash table and for
+    /// [`HashItemType::Container`] the list of `u32` indices of the child items.
+    pub fn get_raw(&self, key: &str) -> Result<(HashItemType, &'a [u8])> {
+        self.with_path(key, || self.raw_item(&self.get_hash_item(key)?))
+    }
+
+    /// Returns the metadata of the item at `key`, like its hash value and data offsets.
+    pub fn get_item_info(&self, key: &str) -> Result<HashItemInfo> {
+        self.with_path(key, || HashItemInfo::new(&self.get_hash_item(key)?))
     }
 
     /// Get the bytes for the [`HashItem`] at `key`.
-    fn get_bytes(&self, key: &str) -> Result<&[u8]> {
+    pub(crate) fn get_bytes(&self, key: &str) -> Result<&'a [u8]> {
         let item = self.get_hash_item(key)?;
+        self.value_bytes_for_item(&item, key)
+    }
+
+    /// Returns the serialized value data of `item`, which was found at `key`.
+    pub(crate) fn value_bytes_for_item(&self, item: &HashItem, key: &str) -> Result<&'a [u8]> {
         let typ = item.typ()?;
         if typ == HashItemType::Value {
-            Ok(self.file.dereference(item.value_ptr(), 8)?)
+            self.dereference_value(item, 8)
         } else {
-            Err(Error::Data(format!(
-                "Unable to parse item for key '{}' as GVariant: Expected type 'v', got type {}",
-                self.key_for_item(&item)?,
-                typ
-            )))
+            Err(Error::WrongItemType {
+                expected: HashItemType::Value,
+                found: typ,
+                key: key.to_string(),
+            })
         }
     }
 
     /// Returns the nested [`HashTable`] at `key`, if one is found.
-    pub fn get_hash_table(&self, key: &str) -> Result<HashTable> {
-        let item = self.get_hash_item(key)?;
-        let typ = item.typ()?;
-        if typ == HashItemType::HashTable {
-            HashTable::for_bytes(*item.value_ptr(), self.file)
-        } else {
-            Err(Error::Data(format!(
-                "Unable to parse item for key '{}' as hash table: Expected type 'H', got type '{}'",
-                self.key_for_item(&item)?,
-                typ
-            )))
-        }
+    ///
+    /// Errors of lookups in the nested hash table are returned as [`Error::Context`] with the path
+    /// of the item.
+    pub fn get_hash_table(&self, key: &str) -> Result<HashTable<'a, 'file>> {
+        self.with_path(key, || {
+            let item = self.get_hash_item(key)?;
+            let typ = item.typ()?;
+            if typ == HashItemType::HashTable {
+                self.nested_at(*item.value_ptr(), key)
+            } else {
+                Err(Error::WrongItemType {
+                    expected: HashItemType::HashTable,
+                    found: typ,
+                    key: key.to_string(),
+                })
+            }
+        })
     }
 
-    fn deserializer_for_key(&self, key: &str) -> Result<GVariantDeserializer> {
-        let data = self.get_bytes(key)?;
+    fn deserializer_for_key(&self, key: &str) -> Result<GVariantDeserializer<'a, '_, '_>> {
+        self.deserializer_for_bytes(self.get_bytes(key)?)
+    }
 
+    fn deserializer_for_bytes(&self, data: &'a [u8]) -> Result<GVariantDeserializer<'a, '_, '_>> {
         // Create a new zvariant context based our endianess and the byteswapped property
         let context =
             zvariant::serialized::Context::new_gvariant(self.file.zvariant_endianess(), 0);
@@ -406,19 +743,187 @@ impl<'a, 'file> HashTable<'a, 'file> {
     /// Returns the data for `key` as a [`enum@zvariant::Value`].
     ///
     /// Unless you need to inspect the value at runtime, it is recommended to use [`HashTable::get`].
-    pub fn get_value(&self, key: &str) -> Result<zvariant::Value> {
-        let mut de = self.deserializer_for_key(key)?;
+    pub fn get_value(&self, key: &str) -> Result<zvariant::Value<'_>> {
+        self.with_path(key, || {
+            let mut de = self.deserializer_for_key(key)?;
+            Ok(zvariant::Value::deserialize(&mut de)?)
+        })
+    }
+
+    /// Returns the data for `key` as a [`zvariant::OwnedValue`].
+    ///
+    /// In contrast to [`HashTable::get_value`] the value doesn't borrow from the file, so it can be
+    /// kept after the [`File`] was dropped, e.g. after closing a memory-mapped file.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert_string("string", "test string").unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let value = file.hash_table().unwrap().get_owned_value("string").unwrap();
+    /// drop(file);
+    /// assert_eq!(<&str>::try_from(&value).unwrap(), "test string");
+    /// ```
+    pub fn get_owned_value(&self, key: &str) -> Result<zvariant::OwnedValue> {
+        self.with_path(key, || Ok(self.get_value(key)?.try_to_owned()?))
+    }
+
+    /// Deserialize the `v` typed GVariant `data` as a [`enum@zvariant::Value`].
+    pub(crate) fn value_for_bytes(&self, data: &'a [u8]) -> Result<zvariant::Value<'a>> {
+        let mut de = self.deserializer_for_bytes(data)?;
         Ok(zvariant::Value::deserialize(&mut de)?)
     }
 
+    /// Returns an iterator over all values in the hash table and their keys.
+    ///
+    /// The items are visited in the order in which they are stored in the file. Nested hash
+    /// tables and containers are skipped, use [`HashTable::iter_raw`] to visit all items.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert("int", 42u32).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// for res in file.hash_table().unwrap().iter() {
+    ///     let (key, value) = res.unwrap();
+    ///     println!("{}: {}", key, value);
+    /// }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, 'a, 'file> {
+        Iter::new(self.iter_raw())
+    }
+
+    /// Returns an iterator over all values in the hash table and their keys as
+    /// [`zvariant::OwnedValue`].
+    ///
+    /// Like [`HashTable::iter`], but the values don't borrow from the file, see
+    /// [`HashTable::get_owned_value`].
+    pub fn values_owned(&self) -> ValuesOwned<'_, 'a, 'file> {
+        ValuesOwned::new(self.iter())
+    }
+
+    /// Returns an iterator over all items in the hash table, yielding the key, the type of the
+    /// item and the raw bytes of the item.
+    ///
+    /// For [`HashItemType::Value`] the bytes are the serialized `v` typed GVariant.
+    pub fn iter_raw(&self) -> IterRaw<'_, 'a, 'file> {
+        IterRaw::new(self)
+    }
+
+    /// Returns an iterator that recursively visits all entries of the hash table.
+    ///
+    /// Every entry is yielded together with its path: the keys of all enclosing nested hash
+    /// tables, followed by the full key of the entry itself. The children of containers and the
+    /// entries of nested hash tables are visited right after their parent.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::{Entry, File};
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut nested = HashTableBuilder::new();
+    /// nested.insert("int", 42u32).unwrap();
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert_table("dir/table", nested).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// for res in file.hash_table().unwrap().walk() {
+    ///     match res.unwrap() {
+    ///         (path, Entry::Value(value)) => println!("{:?}: {}", path, value),
+    ///         (path, Entry::Table(_)) => println!("{:?}: hash table", path),
+    ///         (path, Entry::Container) => println!("{:?}: container", path),
+    ///     }
+    /// }
+    /// ```
+    pub fn walk(&self) -> Walk<'a, 'file> {
+        Walk::new(self.clone())
+    }
+
     /// Returns the data for `key` and try to deserialize a [`enum@zvariant::Value`].
     ///
     /// Then try to extract an underlying `T`.
-    pub fn get<'d, T>(&'d self, key: &str) -> Result<T>
+    ///
+    /// Types containing references like `&str` or `&[u8]` are deserialized without copying the
+    /// data. They borrow from the file data directly, so they can outlive the [`HashTable`].
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// #[derive(serde::Deserialize, zvariant::Type)]
+    /// struct Entry<'a> {
+    ///     name: &'a str,
+    ///     data: &'a [u8],
+    /// }
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert("entry", ("name", vec![1u8, 2, 3])).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let entry: Entry = file.hash_table().unwrap().get("entry").unwrap();
+    /// assert_eq!(entry.name, "name");
+    /// assert_eq!(entry.data, [1, 2, 3]);
+    /// ```
+    pub fn get<T>(&self, key: &str) -> Result<T>
+    where
+        T: zvariant::Type + serde::Deserialize<'a> + 'a,
+    {
+        self.with_path(key, || self.deserialize_bytes(self.get_bytes(key)?, key))
+    }
+
+    /// Returns the data for `key` like [`HashTable::get`], with the precomputed `hash_value` of the
+    /// key
+    ///
+    /// Hashing the key is a significant part of a lookup. When the same keys are looked up again
+    /// and again, e.g. in a tight loop, their hash values can be computed once with
+    /// [`djb_hash`](crate::djb_hash). If `hash_value` is not the hash value of `key`, the key is
+    /// not found.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert("int", 42u32).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// let hash_value = gvdb::djb_hash("int");
+    /// for _ in 0..10 {
+    ///     assert_eq!(table.get_with_hash::<u32>("int", hash_value).unwrap(), 42);
+    /// }
+    /// ```
+    pub fn get_with_hash<T>(&self, key: &str, hash_value: u32) -> Result<T>
+    where
+        T: zvariant::Type + serde::Deserialize<'a> + 'a,
+    {
+        self.with_path(key, || {
+            let (_, item) = self.find_hash_item_with_hash(key, hash_value)?;
+            self.deserialize_bytes(self.value_bytes_for_item(&item, key)?, key)
+        })
+    }
+
+    /// Deserialize the `v` typed GVariant `data` of `key` as `T`
+    fn deserialize_bytes<T>(&self, data: &'a [u8], key: &str) -> Result<T>
     where
-        T: zvariant::Type + serde::Deserialize<'d> + 'd,
+        T: zvariant::Type + serde::Deserialize<'a> + 'a,
     {
-        let mut de = self.deserializer_for_key(key)?;
+        let mut de = self.deserializer_for_bytes(data)?;
         let value = zvariant::DeserializeValue::deserialize(&mut de).map_err(|err| {
             Error::Data(format!(
                 "Error deserializing value for key \"{}\" as gvariant type \"{}\": {}",
@@ -431,71 +936,558 @@ impl<'a, 'file> HashTable<'a, 'file> {
         Ok(value.0)
     }
 
-    #[cfg(feature = "glib")]
-    /// Returns the data for `key` as a [`struct@glib::Variant`].
-    pub fn get_gvariant(&self, key: &str) -> Result<glib::Variant> {
-        let data = self.get_bytes(key)?;
-        let variant = glib::Variant::from_data_with_type(data, glib::VariantTy::VARIANT);
+    /// Checks that the item for `key` contains the GVariant unit value `()`
+    ///
+    /// Returns [`Error::Data`] if the value has a different type.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert_unit("enabled").unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// assert!(file.hash_table().unwrap().get_unit("enabled").is_ok());
+    /// ```
+    pub fn get_unit(&self, key: &str) -> Result<()> {
+        self.with_path(key, || {
+            let (_, signature) = split_variant(self.get_bytes(key)?)?;
+            if signature != "()" {
+                return Err(Error::Data(format!(
+                    "Value for key '{}' has type '{}', expected type '()'",
+                    key, signature
+                )));
+            }
 
-        if self.file.byteswapped {
-            Ok(variant.byteswap())
-        } else {
-            Ok(variant)
-        }
+            Ok(())
+        })
     }
-}
-
-impl std::fmt::Debug for HashTable<'_, '_> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("HashTable")
-            .field("header", &self.header)
-            .field(
-                "map",
-                &self.keys().map(|res| {
-                    res.iter()
-                        .map(|name| {
-                            let item = self.get_hash_item(name);
-                            match item {
-                                Ok(item) => {
-                                    let value = match item.typ() {
-                                        Ok(super::HashItemType::Container) => {
-                                            Ok(Box::new(item) as Box<dyn std::fmt::Debug>)
-                                        }
-                                        Ok(super::HashItemType::HashTable) => {
-                                            self.get_hash_table(name).map(|table| {
-                                                Box::new(table) as Box<dyn std::fmt::Debug>
-                                            })
-                                        }
-                                        Ok(super::HashItemType::Value) => {
-                                            self.get_value(name).map(|value| {
-                                                Box::new(value) as Box<dyn std::fmt::Debug>
-                                            })
-                                        }
-                                        Err(err) => Err(err),
-                                    };
 
-                                    (name.to_string(), Ok((item, value)))
-                                }
-                                Err(err) => (name.to_string(), Err(err)),
-                            }
-                        })
-                        .collect::<std::collections::HashMap<_, _>>()
-                }),
-            )
-            .finish()
+    /// Returns the data of the `m` typed GVariant for `key` deserialized as `Option<T>`
+    ///
+    /// This is equivalent to `get::<Option<T>>(key)`. A missing key is still an error, `None`
+    /// means that the item contains the value `nothing`.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert_serialized("just", &Some(42u32)).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let value = file.hash_table().unwrap().get_optional::<u32>("just").unwrap();
+    /// assert_eq!(value, Some(42));
+    /// ```
+    pub fn get_optional<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: zvariant::Type + serde::Deserialize<'a> + 'a,
+    {
+        self.get::<Option<T>>(key)
     }
-}
 
-#[cfg(test)]
-pub(crate) mod test {
-    use crate::read::{Error, File, HashHeader, HashItem, Pointer};
-    use crate::test::*;
+    /// Returns the data of the `a{sv}` typed GVariant for `key` as a map
+    ///
+    /// Dictionaries of this type are commonly used for settings and metadata. The values don't
+    /// borrow from the file, see [`HashTable::get_owned_value`].
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use std::collections::HashMap;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut dict = HashMap::new();
+    /// dict.insert("width", zvariant::Value::new(800u32));
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert("window", dict).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let dict = file.hash_table().unwrap().get_dict("window").unwrap();
+    /// assert_eq!(u32::try_from(&dict["width"]).unwrap(), 800);
+    /// ```
+    pub fn get_dict(&self, key: &str) -> Result<HashMap<String, zvariant::OwnedValue>> {
+        self.get::<HashMap<String, zvariant::OwnedValue>>(key)
+    }
+
+    /// The indices of `keys` sorted by their hash bucket
+    ///
+    /// Looking up keys in this order reads the buckets and hash items front to back, instead of
+    /// jumping around in the file for every key.
+    fn lookup_order(&self, keys: &[&str]) -> Vec<usize> {
+        let n_buckets = max(self.header.n_buckets(), 1);
+        let mut order: Vec<usize> = (0..keys.len()).collect();
+        order.sort_by_cached_key(|index| djb_hash(keys[*index]) % n_buckets);
+        order
+    }
+
+    /// Look up the values of all `keys` and deserialize them as `T`
+    ///
+    /// The result for each key is equivalent to [`HashTable::get`], in the order of `keys`. The
+    /// keys are looked up sorted by their hash bucket, which is faster than separate lookups for
+    /// a large number of keys. With the `rayon` feature, [`HashTable::par_get_many`] looks up the
+    /// keys in parallel.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert("a", 1u32).unwrap();
+    /// table_builder.insert("b", 2u32).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let values = file.hash_table().unwrap().get_many::<u32>(&["b", "c", "a"]);
+    /// assert_eq!(values[0].as_ref().unwrap(), &2);
+    /// assert!(values[1].is_err());
+    /// assert_eq!(values[2].as_ref().unwrap(), &1);
+    /// ```
+    pub fn get_many<T>(&self, keys: &[&str]) -> Vec<Result<T>>
+    where
+        T: zvariant::Type + serde::Deserialize<'a> + 'a,
+    {
+        let mut results: Vec<Option<Result<T>>> = keys.iter().map(|_| None).collect();
+        for index in self.lookup_order(keys) {
+            results[index] = Some(self.get(keys[index]));
+        }
+
+        results.into_iter().flatten().collect()
+    }
+
+    /// Look up the values of all `keys` in parallel and deserialize them as `T`
+    ///
+    /// Identical to [`HashTable::get_many`], but the lookups are distributed over the
+    /// [rayon](https://crates.io/crates/rayon) thread pool.
+    #[cfg(feature = "rayon")]
+    pub fn par_get_many<T>(&self, keys: &[&str]) -> Vec<Result<T>>
+    where
+        T: zvariant::Type + serde::Deserialize<'a> + Send + 'a,
+    {
+        use rayon::prelude::*;
+
+        let mut results: Vec<(usize, Result<T>)> = self
+            .lookup_order(keys)
+            .into_par_iter()
+            .map(|index| (index, self.get(keys[index])))
+            .collect();
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    /// Returns a reader over the serialized data of the value at `key`.
+    ///
+    /// The reader borrows the value bytes directly from the file data, no copy is made. This is
+    /// useful for streaming large values, e.g. into a decompressor. The data is in GVariant
+    /// serialization format for the type of the stored value. For an `ay` value these are the
+    /// raw bytes.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use std::io::Read;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert_bytes("blob", &[1, 2, 3, 4]).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// let mut reader = table.get_reader("blob").unwrap();
+    /// let mut buf = Vec::new();
+    /// reader.read_to_end(&mut buf).unwrap();
+    /// assert_eq!(buf, &[1, 2, 3, 4]);
+    /// ```
+    pub fn get_reader(&self, key: &str) -> Result<std::io::Cursor<&'a [u8]>> {
+        self.with_path(key, || {
+            let (data, _signature) = split_variant(self.get_bytes(key)?)?;
+            Ok(std::io::Cursor::new(data))
+        })
+    }
+
+    /// Returns the string value at `key`, borrowed directly from the file data.
+    ///
+    /// This works for values of type `s`, `o` and `g`. Other types result in an error, use
+    /// [`HashTable::get`] to decode them.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert_string("string", "test string").unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// assert_eq!(table.get_str("string").unwrap(), "test string");
+    /// ```
+    pub fn get_str(&self, key: &str) -> Result<&'a str> {
+        self.with_path(key, || {
+            let (data, signature) = split_variant(self.get_bytes(key)?)?;
+            if !matches!(signature, "s" | "o" | "g") {
+                return Err(Error::Data(format!(
+                    "Unable to borrow value for key '{}' as string: Expected type 's', 'o' or 'g', got type '{}'",
+                    key, signature
+                )));
+            }
+
+            // Strings are zero-terminated
+            let data = data.strip_suffix(&[0]).ok_or_else(|| {
+                Error::Data(format!(
+                    "String value for key '{}' is not zero-terminated",
+                    key
+                ))
+            })?;
+
+            Ok(std::str::from_utf8(data)?)
+        })
+    }
+
+    /// Returns the byte array value at `key`, borrowed directly from the file data.
+    ///
+    /// This only works for values of type `ay`. Other types result in an error, use
+    /// [`HashTable::get`] to decode them.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert_bytes("bytes", &[1, 2, 3]).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// assert_eq!(table.get_byte_slice("bytes").unwrap(), &[1, 2, 3]);
+    /// ```
+    pub fn get_byte_slice(&self, key: &str) -> Result<&'a [u8]> {
+        self.with_path(key, || {
+            let (data, signature) = split_variant(self.get_bytes(key)?)?;
+            if signature != "ay" {
+                return Err(Error::Data(format!(
+                    "Unable to borrow value for key '{}' as byte slice: Expected type 'ay', got type '{}'",
+                    key, signature
+                )));
+            }
+
+            Ok(data)
+        })
+    }
+
+    /// Returns the `v` typed GVariant data of `key` if its value has one of the `accepted` type
+    /// signatures, or [`Error::TypeMismatch`] with the `expected` description
+    fn typed_bytes(
+        &self,
+        key: &str,
+        accepted: &[&str],
+        expected: &str,
+    ) -> Result<(&'a [u8], &'a str)> {
+        let data = self.get_bytes(key)?;
+        let (_, signature) = split_variant(data)?;
+        if !accepted.contains(&signature) {
+            return Err(Error::TypeMismatch {
+                key: key.to_string(),
+                expected: expected.to_string(),
+                found: signature.to_string(),
+            });
+        }
+
+        Ok((data, signature))
+    }
+
+    /// Decodes the integer value of `key` of any GVariant integer type
+    fn get_integer(&self, key: &str) -> Result<i128> {
+        let (data, signature) =
+            self.typed_bytes(key, &["y", "n", "q", "i", "u", "x", "t"], "an integer type")?;
+
+        Ok(match signature {
+            "y" => self.deserialize_bytes::<u8>(data, key)?.into(),
+            "n" => self.deserialize_bytes::<i16>(data, key)?.into(),
+            "q" => self.deserialize_bytes::<u16>(data, key)?.into(),
+            "i" => self.deserialize_bytes::<i32>(data, key)?.into(),
+            "u" => self.deserialize_bytes::<u32>(data, key)?.into(),
+            "x" => self.deserialize_bytes::<i64>(data, key)?.into(),
+            _ => self.deserialize_bytes::<u64>(data, key)?.into(),
+        })
+    }
+
+    /// Converts the integer `value` of `key` to `T`, or returns [`Error::Data`] if it is out of
+    /// range
+    fn integer_in_range<T: TryFrom<i128>>(key: &str, value: i128, typ: &str) -> Result<T> {
+        T::try_from(value).map_err(|_| {
+            Error::Data(format!(
+                "Value {} for key '{}' is out of range for type '{}'",
+                value, key, typ
+            ))
+        })
+    }
+
+    /// Returns the integer value at `key` as `u32`
+    ///
+    /// Values of all GVariant integer types are accepted. Values of other types result in
+    /// [`Error::TypeMismatch`], values that don't fit into a `u32` in [`Error::Data`].
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::{Error, File};
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert("int", 42u16).unwrap();
+    /// table_builder.insert("negative", -1i32).unwrap();
+    /// table_builder.insert_string("string", "test string").unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// assert_eq!(table.get_u32("int").unwrap(), 42);
+    /// assert!(matches!(table.get_u32("negative"), Err(Error::Data(_))));
+    /// assert!(matches!(table.get_u32("string"), Err(Error::TypeMismatch { .. })));
+    /// ```
+    pub fn get_u32(&self, key: &str) -> Result<u32> {
+        self.with_path(key, || {
+            Self::integer_in_range(key, self.get_integer(key)?, "u32")
+        })
+    }
+
+    /// Returns the integer value at `key` as `i64`
+    ///
+    /// Values of all GVariant integer types are accepted. Values of other types result in
+    /// [`Error::TypeMismatch`], `t` values that don't fit into an `i64` in [`Error::Data`].
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert("int", -42i32).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// assert_eq!(file.hash_table().unwrap().get_i64("int").unwrap(), -42);
+    /// ```
+    pub fn get_i64(&self, key: &str) -> Result<i64> {
+        self.with_path(key, || {
+            Self::integer_in_range(key, self.get_integer(key)?, "i64")
+        })
+    }
+
+    /// Returns the `d` typed value at `key`
+    ///
+    /// Values of other types result in [`Error::TypeMismatch`].
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert("scale", 1.5f64).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// assert_eq!(file.hash_table().unwrap().get_f64("scale").unwrap(), 1.5);
+    /// ```
+    pub fn get_f64(&self, key: &str) -> Result<f64> {
+        self.with_path(key, || {
+            let (data, _) = self.typed_bytes(key, &["d"], "type 'd'")?;
+            self.deserialize_bytes(data, key)
+        })
+    }
+
+    /// Returns the `b` typed value at `key`
+    ///
+    /// Values of other types result in [`Error::TypeMismatch`].
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert("enabled", true).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// assert!(file.hash_table().unwrap().get_bool("enabled").unwrap());
+    /// ```
+    pub fn get_bool(&self, key: &str) -> Result<bool> {
+        self.with_path(key, || {
+            let (data, _) = self.typed_bytes(key, &["b"], "type 'b'")?;
+            self.deserialize_bytes(data, key)
+        })
+    }
+
+    /// Returns the string value at `key` as owned [`String`]
+    ///
+    /// Like [`HashTable::get_str`], this works for values of type `s`, `o` and `g`. Values of
+    /// other types result in [`Error::TypeMismatch`].
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert_string("string", "test string").unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let string = file.hash_table().unwrap().get_string("string").unwrap();
+    /// assert_eq!(string, "test string");
+    /// ```
+    pub fn get_string(&self, key: &str) -> Result<String> {
+        self.with_path(key, || {
+            self.typed_bytes(key, &["s", "o", "g"], "type 's', 'o' or 'g'")?;
+            Ok(self.get_str(key)?.to_string())
+        })
+    }
+
+    /// Returns the GVariant type signature of the value at `key` without decoding the value.
+    ///
+    /// The signature is read from the end of the serialized variant data, which makes this a
+    /// cheap way to find out how to decode or display a value.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert_string("string", "test string").unwrap();
+    /// table_builder.insert("ints", vec![1u32, 2]).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// assert_eq!(table.signature_of("string").unwrap(), "s");
+    /// assert_eq!(table.signature_of("ints").unwrap(), "au");
+    /// ```
+    pub fn signature_of(&self, key: &str) -> Result<&'a str> {
+        self.with_path(key, || {
+            let (_, signature) = split_variant(self.get_bytes(key)?)?;
+            zvariant::Signature::try_from(signature)?;
+            Ok(signature)
+        })
+    }
+
+    #[cfg(feature = "glib")]
+    /// Returns the data for `key` as a [`struct@glib::Variant`].
+    pub fn get_gvariant(&self, key: &str) -> Result<glib::Variant> {
+        let data = self.with_path(key, || self.get_bytes(key))?;
+        let variant = glib::Variant::from_data_with_type(data, glib::VariantTy::VARIANT);
+
+        if self.file.byteswapped {
+            Ok(variant.byteswap())
+        } else {
+            Ok(variant)
+        }
+    }
+}
+
+impl std::fmt::Debug for HashTable<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.fmt_nested(f, &[])
+    }
+}
+
+/// A nested hash table in the [`Debug`] output of its enclosing hash tables
+struct NestedTable<'a, 'file> {
+    table: HashTable<'a, 'file>,
+    // The pointers of the enclosing hash tables, to detect loops
+    ancestors: Vec<Pointer>,
+}
+
+impl Debug for NestedTable<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.table.fmt_nested(f, &self.ancestors)
+    }
+}
+
+impl<'a, 'file> HashTable<'a, 'file> {
+    /// Format the hash table, which is nested in the hash tables at `ancestors`
+    fn fmt_nested(&self, f: &mut Formatter<'_>, ancestors: &[Pointer]) -> std::fmt::Result {
+        let mut ancestors = ancestors.to_vec();
+        ancestors.push(self.pointer);
+
+        f.debug_struct("HashTable")
+            .field("header", &self.header)
+            .field(
+                "map",
+                &self.keys().map(|res| {
+                    res.iter()
+                        .map(|name| {
+                            let item = self.get_hash_item(name);
+                            match item {
+                                Ok(item) => {
+                                    let value = match item.typ() {
+                                        Ok(super::HashItemType::Container) => {
+                                            Ok(Box::new(item) as Box<dyn std::fmt::Debug>)
+                                        }
+                                        Ok(super::HashItemType::HashTable) => self
+                                            .get_hash_table(name)
+                                            .and_then(|table| {
+                                                if ancestors.contains(&table.pointer) {
+                                                    return Err(Error::Data(
+                                                        "Nested hash table contains itself. The file appears to have a loop"
+                                                            .to_string(),
+                                                    ));
+                                                }
+
+                                                Ok(Box::new(NestedTable {
+                                                    table,
+                                                    ancestors: ancestors.clone(),
+                                                })
+                                                    as Box<dyn std::fmt::Debug>)
+                                            }),
+                                        Ok(super::HashItemType::Value) => {
+                                            self.get_value(name).map(|value| {
+                                                Box::new(value) as Box<dyn std::fmt::Debug>
+                                            })
+                                        }
+                                        Err(err) => Err(err),
+                                    };
+
+                                    (name.to_string(), Ok((item, value)))
+                                }
+                                Err(err) => (name.to_string(), Err(err)),
+                            }
+                        })
+                        .collect::<std::collections::HashMap<_, _>>()
+                }),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use crate::read::{Error, File, HashHeader, HashItem, HashItemType, Pointer};
+    use crate::test::*;
     use crate::test::{assert_eq, assert_matches, assert_ne};
+    use crate::util::djb_hash;
+    use crate::write::{FileWriter, HashTableBuilder};
+    use std::borrow::Cow;
+    use std::collections::HashMap;
+    use std::io::{Read, Seek, SeekFrom};
+    use std::mem::size_of;
 
     #[test]
     fn debug() {
         let header = HashHeader::new(0, 0, 0);
-        let header2 = header.clone();
+        let header2 = header;
         println!("{:?}", header2);
 
         let file = new_empty_file();
@@ -556,7 +1548,7 @@ pub(crate) mod test {
         for endianess in [true, false] {
             let file = new_simple_file(endianess);
             let table = file.hash_table().unwrap();
-            let res: String = table.get::<String>("test").unwrap().into();
+            let res: String = table.get::<String>("test").unwrap();
             assert_eq!(&res, "test");
 
             let res = table.get::<i32>("test");
@@ -564,6 +1556,367 @@ pub(crate) mod test {
         }
     }
 
+    #[test]
+    fn get_optional_and_dict() {
+        let new_table = || {
+            let mut dict = std::collections::HashMap::new();
+            dict.insert("int", zvariant::Value::new(1u32));
+            dict.insert("string", zvariant::Value::new("test"));
+
+            let mut table_builder = HashTableBuilder::new();
+            table_builder
+                .insert_serialized("just", &Some("test"))
+                .unwrap();
+            table_builder
+                .insert_serialized("nothing", &None::<u32>)
+                .unwrap();
+            table_builder.insert("dict", dict).unwrap();
+            table_builder.insert("int", 1u32).unwrap();
+            table_builder
+        };
+
+        for writer in [FileWriter::new(), FileWriter::for_big_endian()] {
+            let data = writer.write_to_vec_with_table(new_table()).unwrap();
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            let table = file.hash_table().unwrap();
+
+            assert_eq!(
+                table.get_optional::<String>("just").unwrap(),
+                Some("test".to_string())
+            );
+            assert_eq!(table.get_optional::<u32>("nothing").unwrap(), None);
+            assert_matches!(table.get_optional::<u32>("int"), Err(Error::Data(_)));
+            assert_matches!(
+                table.get_optional::<u32>("missing"),
+                Err(Error::KeyNotFound(_))
+            );
+
+            let dict = table.get_dict("dict").unwrap();
+            assert_eq!(dict.len(), 2);
+            assert_eq!(u32::try_from(&dict["int"]).unwrap(), 1);
+            assert_eq!(<&str>::try_from(&dict["string"]).unwrap(), "test");
+            assert_matches!(table.get_dict("int"), Err(Error::Data(_)));
+        }
+    }
+
+    #[test]
+    fn optional_and_unit() {
+        let new_table = || {
+            let mut table_builder = HashTableBuilder::new();
+            table_builder.insert_optional("just", Some(42u32)).unwrap();
+            table_builder
+                .insert_optional("nothing", None::<String>)
+                .unwrap();
+            table_builder
+                .insert_optional("nested", Some(None::<u32>))
+                .unwrap();
+            table_builder.insert_unit("unit").unwrap();
+            table_builder.insert("int", 1u32).unwrap();
+            table_builder
+        };
+
+        for writer in [FileWriter::new(), FileWriter::for_big_endian()] {
+            let data = writer.write_to_vec_with_table(new_table()).unwrap();
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            let table = file.hash_table().unwrap();
+
+            assert_eq!(table.get_optional::<u32>("just").unwrap(), Some(42));
+            assert_eq!(table.get_optional::<String>("nothing").unwrap(), None);
+            assert_eq!(
+                table.get_optional::<Option<u32>>("nested").unwrap(),
+                Some(None)
+            );
+            assert_eq!(table.signature_of("nested").unwrap(), "mmu");
+
+            table.get_unit("unit").unwrap();
+            assert_eq!(table.get_raw("unit").unwrap().1, b"\0\0()");
+            assert_matches!(table.get_unit("int"), Err(Error::Data(_)));
+            assert_matches!(table.get_unit("missing"), Err(Error::KeyNotFound(_)));
+
+            #[cfg(feature = "glib")]
+            {
+                use glib::prelude::*;
+                let unit = table.get_gvariant("unit").unwrap().as_variant().unwrap();
+                assert_eq!(unit, ().to_variant());
+                assert!(unit.is_normal_form());
+                let just = table.get_gvariant("just").unwrap().as_variant().unwrap();
+                assert_eq!(just, Some(42u32).to_variant());
+            }
+        }
+    }
+
+    #[test]
+    fn typed_getters() {
+        let new_table = || {
+            let mut table_builder = HashTableBuilder::new();
+            table_builder.insert("byte", 200u8).unwrap();
+            table_builder.insert("i16", -300i16).unwrap();
+            table_builder.insert("u32", 70000u32).unwrap();
+            table_builder.insert("i64", -5_000_000_000i64).unwrap();
+            table_builder.insert("u64", u64::MAX).unwrap();
+            table_builder.insert("double", 0.25f64).unwrap();
+            table_builder.insert("bool", true).unwrap();
+            table_builder
+                .insert_string("string", "test string")
+                .unwrap();
+            table_builder
+        };
+
+        for writer in [FileWriter::new(), FileWriter::for_big_endian()] {
+            let data = writer.write_to_vec_with_table(new_table()).unwrap();
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            let table = file.hash_table().unwrap();
+
+            assert_eq!(table.get_u32("byte").unwrap(), 200);
+            assert_eq!(table.get_u32("u32").unwrap(), 70000);
+            assert_eq!(table.get_i64("i16").unwrap(), -300);
+            assert_eq!(table.get_i64("i64").unwrap(), -5_000_000_000);
+            assert_eq!(table.get_f64("double").unwrap(), 0.25);
+            assert!(table.get_bool("bool").unwrap());
+            assert_eq!(table.get_string("string").unwrap(), "test string");
+
+            let err = table.get_u32("i16").unwrap_err();
+            assert_matches!(err, Error::Data(_));
+            assert!(format!("{}", err).contains("out of range for type 'u32'"));
+            assert_matches!(table.get_u32("i64"), Err(Error::Data(_)));
+            assert_matches!(table.get_i64("u64"), Err(Error::Data(_)));
+
+            assert_matches!(
+                table.get_u32("string"),
+                Err(Error::TypeMismatch { expected, found, .. })
+                    if expected == "an integer type" && found == "s"
+            );
+            assert_matches!(
+                table.get_f64("u32"),
+                Err(Error::TypeMismatch { found, .. }) if found == "u"
+            );
+            assert_matches!(table.get_bool("byte"), Err(Error::TypeMismatch { .. }));
+            assert_matches!(table.get_string("bool"), Err(Error::TypeMismatch { .. }));
+            assert_matches!(table.get_i64("missing"), Err(Error::KeyNotFound(_)));
+        }
+    }
+
+    #[test]
+    fn get_many() {
+        let mut table_builder = HashTableBuilder::new();
+        for num in 0..100u32 {
+            table_builder.insert(&num.to_string(), num).unwrap();
+        }
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+
+        let keys: Vec<String> = (0..110u32).rev().map(|num| num.to_string()).collect();
+        let keys: Vec<&str> = keys.iter().map(|key| key.as_str()).collect();
+        let values = table.get_many::<u32>(&keys);
+        assert_eq!(values.len(), keys.len());
+        for (key, value) in keys.iter().zip(&values) {
+            match key.parse::<u32>().unwrap() {
+                num @ 0..=99 => assert_eq!(value.as_ref().unwrap(), &num),
+                _ => assert_matches!(value, Err(Error::KeyNotFound(_))),
+            }
+        }
+
+        assert_matches!(table.get_many::<String>(&["1"])[..], [Err(Error::Data(_))]);
+        assert!(table.get_many::<u32>(&[]).is_empty());
+        assert!(new_empty_file()
+            .hash_table()
+            .unwrap()
+            .get_many::<u32>(&["1"])[0]
+            .is_err());
+
+        #[cfg(feature = "rayon")]
+        {
+            let par_values = table.par_get_many::<u32>(&keys);
+            assert_eq!(format!("{:?}", par_values), format!("{:?}", values));
+        }
+    }
+
+    #[test]
+    fn get_borrowed() {
+        #[derive(Debug, PartialEq, serde::Deserialize, zvariant::Type)]
+        struct Entry<'a> {
+            name: &'a str,
+            data: &'a [u8],
+        }
+
+        for endianess in [true, false] {
+            let writer = if endianess {
+                FileWriter::for_big_endian()
+            } else {
+                FileWriter::new()
+            };
+
+            let mut table_builder = HashTableBuilder::new();
+            table_builder
+                .insert("entry", ("name", vec![1u8, 2, 3]))
+                .unwrap();
+            let data = writer.write_to_vec_with_table(table_builder).unwrap();
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+
+            // The entry borrows from the file data, not from the hash table
+            let entry: Entry = {
+                let table = file.hash_table().unwrap();
+                table.get("entry").unwrap()
+            };
+            assert_eq!(
+                entry,
+                Entry {
+                    name: "name",
+                    data: &[1, 2, 3]
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let file = new_duplicate_key_file();
+        let table = file.hash_table().unwrap();
+        assert_eq!(table.keys().unwrap(), vec!["a1", "a1"]);
+        assert_eq!(table.duplicate_keys().unwrap(), vec!["a1"]);
+        // Lookups return the item with the lowest index, which is the renamed item here
+        assert_eq!(table.get::<u32>("a1").unwrap(), 2);
+
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        assert!(file
+            .hash_table()
+            .unwrap()
+            .duplicate_keys()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn len_and_counts() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let table = file.hash_table().unwrap();
+        assert_eq!(table.len(), table.keys().unwrap().len());
+        assert_eq!(table.counts().unwrap(), (4, 0, 8));
+
+        let file = File::from_file(&TEST_FILE_2).unwrap();
+        let table = file.hash_table().unwrap();
+        assert_eq!(table.counts().unwrap(), (1, 1, 0));
+        assert_eq!(table.get_hash_table("table").unwrap().len(), 1);
+
+        let file = new_empty_file();
+        let table = file.hash_table().unwrap();
+        assert!(table.is_empty());
+        assert_eq!(table.counts().unwrap(), (0, 0, 0));
+    }
+
+    #[test]
+    fn bucket_for_key() {
+        for path in [&*TEST_FILE_1, &*TEST_FILE_2, &*TEST_FILE_3] {
+            let file = File::from_file(path).unwrap();
+            let table = file.hash_table().unwrap();
+            let n_buckets = table.header.n_buckets();
+
+            // The hash items are sorted by bucket
+            let mut last = 0;
+            for (index, key) in table.keys().unwrap().iter().enumerate() {
+                let item = table.get_hash_item_for_index(index).unwrap();
+                let bucket = table.bucket_for_key(key).unwrap();
+                assert_eq!(bucket as u32, item.hash_value() % n_buckets);
+                assert!(bucket >= last);
+                last = bucket;
+            }
+        }
+
+        let file = new_empty_file();
+        let table = file.hash_table().unwrap();
+        assert_eq!(table.header.n_buckets(), 0);
+        assert_eq!(table.bucket_for_key("test"), None);
+
+        let mut table_builder = HashTableBuilder::new().with_bucket_count(4);
+        table_builder.insert_string("test", "test").unwrap();
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+        assert_eq!(
+            table.bucket_for_key("test"),
+            Some((crate::djb_hash("test") % 4) as usize)
+        );
+        assert_eq!(
+            table.bucket_for_key("missing"),
+            Some((crate::djb_hash("missing") % 4) as usize)
+        );
+    }
+
+    #[test]
+    fn keys_with_prefix() {
+        fn sorted(mut keys: Vec<String>) -> Vec<String> {
+            keys.sort();
+            keys
+        }
+
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let table = file.hash_table().unwrap();
+        for prefix in [
+            "",
+            "/",
+            "/gvdb/rs/test/",
+            "/gvdb/rs/test/icons",
+            "/gvdb/rs/test/online",
+            "/gvdb/rs/te",
+            "/missing/",
+            "gvdb",
+        ] {
+            assert_eq!(
+                sorted(table.keys_with_prefix(prefix).unwrap()),
+                sorted(table.keys_with_prefix_scan(prefix).unwrap()),
+                "prefix '{}'",
+                prefix
+            );
+        }
+
+        assert_eq!(
+            sorted(table.keys_with_prefix("/gvdb/rs/test/icons/").unwrap()),
+            vec![
+                "/gvdb/rs/test/icons/",
+                "/gvdb/rs/test/icons/scalable/",
+                "/gvdb/rs/test/icons/scalable/actions/",
+                "/gvdb/rs/test/icons/scalable/actions/send-symbolic.svg",
+            ]
+        );
+
+        // Without containers
+        let mut table_builder = HashTableBuilder::with_path_separator(None);
+        table_builder.insert("a/b", 1u32).unwrap();
+        table_builder.insert("a/c", 2u32).unwrap();
+        table_builder.insert("b/a", 3u32).unwrap();
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+        assert_eq!(
+            sorted(table.keys_with_prefix("a/").unwrap()),
+            vec!["a/b", "a/c"]
+        );
+
+        // Nested table without containers
+        let mut nested = HashTableBuilder::with_path_separator(None);
+        nested.insert("dir/a", 1u32).unwrap();
+        nested.insert("dir/b", 2u32).unwrap();
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert_table("table", nested).unwrap();
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap().get_hash_table("table").unwrap();
+        assert_eq!(
+            sorted(table.keys_with_prefix("dir/").unwrap()),
+            vec!["dir/a", "dir/b"]
+        );
+        assert!(table.lookup("dir/").unwrap().is_none());
+    }
+
     #[test]
     fn get_bloom_word() {
         for endianess in [true, false] {
@@ -580,10 +1933,35 @@ pub(crate) mod test {
             let file = new_simple_file(endianess);
             let table = file.hash_table().unwrap();
             let res = table.bloom_shift();
-            assert_eq!(res, 0);
+            assert_eq!(res, 5);
         }
     }
 
+    #[test]
+    fn bloom_filter() {
+        let mut table_builder = HashTableBuilder::new();
+        for i in 0..10 {
+            table_builder.insert(&format!("key{}", i), i).unwrap();
+        }
+        let data = FileWriter::new()
+            .with_bloom_words(16)
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+        assert!(table.bloom_words().unwrap().is_some());
+
+        for i in 0..10 {
+            assert!(table.bloom_filter(djb_hash(&format!("key{}", i))));
+        }
+
+        // With 16 words and 10 keys most missing keys are rejected by the bloom filter
+        let rejected = (0..100)
+            .filter(|i| !table.bloom_filter(djb_hash(&format!("missing{}", i))))
+            .count();
+        assert!(rejected > 50);
+    }
+
     #[test]
     fn get_value() {
         for endianess in [true, false] {
@@ -597,13 +1975,387 @@ pub(crate) mod test {
         }
     }
 
+    #[test]
+    fn get_with_hash() {
+        let file = File::from_file(&TEST_FILE_2).unwrap();
+        let table = file.hash_table().unwrap();
+        for key in table.keys().unwrap() {
+            if let Ok(value) = table.get::<zvariant::OwnedValue>(&key) {
+                assert_eq!(
+                    table
+                        .get_with_hash::<zvariant::OwnedValue>(&key, djb_hash(&key))
+                        .unwrap(),
+                    value
+                );
+            }
+        }
+
+        assert_eq!(
+            table
+                .get_with_hash::<String>("string", djb_hash("string"))
+                .unwrap(),
+            "test string"
+        );
+        assert_matches!(
+            table.get_with_hash::<String>("string", djb_hash("string") + 1),
+            Err(Error::KeyNotFound(_))
+        );
+        assert_matches!(
+            table.get_with_hash::<String>("table", djb_hash("table")),
+            Err(Error::WrongItemType { .. })
+        );
+    }
+
+    #[test]
+    fn byteswapped_values() {
+        type Tuple = (u16, i64, f64, Vec<u64>, HashMap<String, i32>);
+        let tuple: Tuple = (
+            0x1234,
+            -0x123456789a,
+            1.5,
+            vec![1, 0x0102030405060708],
+            HashMap::from([("key".to_string(), -2)]),
+        );
+
+        // Values are stored in the byte order of the file and decoded without GLib
+        let mut files = Vec::new();
+        for writer in [FileWriter::new(), FileWriter::for_big_endian()] {
+            let mut table_builder = HashTableBuilder::new();
+            table_builder.insert("int", 0x01020304u32).unwrap();
+            table_builder.insert("tuple", tuple.clone()).unwrap();
+            table_builder
+                .insert_string("nested/string", "test")
+                .unwrap();
+            let data = writer.write_to_vec_with_table(table_builder).unwrap();
+            files.push(File::from_bytes(Cow::Owned(data)).unwrap());
+        }
+
+        assert_eq!(files[0].endianness(), crate::Endian::Little);
+        assert_eq!(files[1].endianness(), crate::Endian::Big);
+        assert_ne!(
+            files[0].hash_table().unwrap().get_bytes("int").unwrap(),
+            files[1].hash_table().unwrap().get_bytes("int").unwrap()
+        );
+
+        for file in &files {
+            let table = file.hash_table().unwrap();
+            assert_eq!(table.get::<u32>("int").unwrap(), 0x01020304);
+            assert_eq!(table.get::<Tuple>("tuple").unwrap(), tuple);
+            assert_eq!(
+                table.get_value("int").unwrap(),
+                zvariant::Value::from(0x01020304u32)
+            );
+            assert_eq!(
+                Tuple::try_from(table.get_owned_value("tuple").unwrap()).unwrap(),
+                tuple
+            );
+            assert_eq!(
+                table.get_many::<u32>(&["int"])[0].as_ref().unwrap(),
+                &0x01020304
+            );
+            assert_eq!(table.get_str("nested/string").unwrap(), "test");
+
+            let cached = table.clone().with_cache(1);
+            assert_eq!(
+                u32::try_from(&*cached.get_owned_value("int").unwrap()).unwrap(),
+                0x01020304
+            );
+
+            let values: HashMap<String, zvariant::OwnedValue> = table
+                .iter()
+                .map(|res| {
+                    let (key, value) = res.unwrap();
+                    (key, value.try_to_owned().unwrap())
+                })
+                .collect();
+            assert_eq!(u32::try_from(&values["int"]).unwrap(), 0x01020304);
+        }
+    }
+
+    #[test]
+    fn get_reader() {
+        let blob: Vec<u8> = (0..=255).cycle().take(100_000).collect();
+
+        for big_endian in [true, false] {
+            let writer = if big_endian {
+                FileWriter::for_big_endian()
+            } else {
+                FileWriter::new()
+            };
+            let mut table_builder = HashTableBuilder::new();
+            table_builder.insert_bytes("blob", &blob).unwrap();
+            table_builder.insert_string("string", "test").unwrap();
+            table_builder
+                .insert_table("table", HashTableBuilder::new())
+                .unwrap();
+            let data = writer.write_to_vec_with_table(table_builder).unwrap();
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            let table = file.hash_table().unwrap();
+
+            let mut reader = table.get_reader("blob").unwrap();
+            let mut buf = Vec::new();
+            reader.read_to_end(&mut buf).unwrap();
+            assert_eq!(buf, blob);
+
+            reader.seek(SeekFrom::Start(256)).unwrap();
+            let mut byte = [0u8; 2];
+            reader.read_exact(&mut byte).unwrap();
+            assert_eq!(byte, [0, 1]);
+
+            // Strings are zero terminated in GVariant serialization
+            let mut reader = table.get_reader("string").unwrap();
+            let mut buf = Vec::new();
+            reader.read_to_end(&mut buf).unwrap();
+            assert_eq!(buf, b"test\0");
+
+            assert_matches!(table.get_reader("table"), Err(Error::WrongItemType { .. }));
+            assert_matches!(table.get_reader("fail"), Err(Error::KeyNotFound(_)));
+        }
+    }
+
+    #[test]
+    fn get_str() {
+        for byteswap in [true, false] {
+            let mut table_builder = HashTableBuilder::new();
+            table_builder
+                .insert_string("string", "test string")
+                .unwrap();
+            table_builder.insert_string("empty", "").unwrap();
+            table_builder
+                .insert("path", zvariant::ObjectPath::try_from("/org/test").unwrap())
+                .unwrap();
+            table_builder.insert("int", 1u32).unwrap();
+            table_builder.insert_bytes("bytes", b"test\0").unwrap();
+            let writer = if byteswap {
+                FileWriter::for_big_endian()
+            } else {
+                FileWriter::new()
+            };
+            let data = writer.write_to_vec_with_table(table_builder).unwrap();
+
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            let table = file.hash_table().unwrap();
+            assert_eq!(table.get_str("string").unwrap(), "test string");
+            assert_eq!(table.get_str("empty").unwrap(), "");
+            assert_eq!(table.get_str("path").unwrap(), "/org/test");
+            assert_matches!(table.get_str("int"), Err(Error::Data(_)));
+            assert!(format!("{}", table.get_str("int").unwrap_err())
+                .contains("Expected type 's', 'o' or 'g', got type 'u'"));
+            assert_matches!(table.get_str("bytes"), Err(Error::Data(_)));
+            assert_matches!(table.get_str("missing"), Err(Error::KeyNotFound(_)));
+        }
+    }
+
+    #[test]
+    fn get_byte_slice() {
+        for byteswap in [true, false] {
+            let mut table_builder = HashTableBuilder::new();
+            table_builder.insert_bytes("bytes", &[1, 2, 3, 0]).unwrap();
+            table_builder.insert_bytes("empty", &[]).unwrap();
+            table_builder.insert("ints", vec![1u32, 2]).unwrap();
+            table_builder.insert_string("string", "test").unwrap();
+            let writer = if byteswap {
+                FileWriter::for_big_endian()
+            } else {
+                FileWriter::new()
+            };
+            let data = writer.write_to_vec_with_table(table_builder).unwrap();
+
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            let table = file.hash_table().unwrap();
+            assert_eq!(table.get_byte_slice("bytes").unwrap(), &[1, 2, 3, 0]);
+            assert_eq!(table.get_byte_slice("empty").unwrap(), &[] as &[u8]);
+            assert_matches!(table.get_byte_slice("ints"), Err(Error::Data(_)));
+            assert_matches!(table.get_byte_slice("string"), Err(Error::Data(_)));
+        }
+    }
+
+    #[test]
+    fn signature_of() {
+        for byteswap in [true, false] {
+            let mut table_builder = HashTableBuilder::new();
+            table_builder.insert_string("string", "test").unwrap();
+            table_builder.insert("int", 1u32).unwrap();
+            table_builder
+                .insert("tuple", (1u8, "test".to_string(), vec![true]))
+                .unwrap();
+            table_builder.insert_bytes("bytes", &[]).unwrap();
+            table_builder.insert_string("table/item", "test").unwrap();
+            let writer = if byteswap {
+                FileWriter::for_big_endian()
+            } else {
+                FileWriter::new()
+            };
+            let data = writer.write_to_vec_with_table(table_builder).unwrap();
+
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            let table = file.hash_table().unwrap();
+            assert_eq!(table.signature_of("string").unwrap(), "s");
+            assert_eq!(table.signature_of("int").unwrap(), "u");
+            assert_eq!(table.signature_of("tuple").unwrap(), "(ysab)");
+            assert_eq!(table.signature_of("bytes").unwrap(), "ay");
+            assert_eq!(table.signature_of("table/item").unwrap(), "s");
+            assert_matches!(
+                table.signature_of("table/"),
+                Err(Error::WrongItemType { .. })
+            );
+            assert_matches!(table.signature_of("missing"), Err(Error::KeyNotFound(_)));
+        }
+    }
+
+    #[test]
+    fn split_variant() {
+        assert_eq!(
+            super::split_variant(b"test\0\0s").unwrap(),
+            (&b"test\0"[..], "s")
+        );
+        assert_matches!(super::split_variant(b"test"), Err(Error::Data(_)));
+        assert_matches!(super::split_variant(b"test\0"), Err(Error::Data(_)));
+    }
+
     #[test]
     fn get_hash_table() {
         let file = File::from_file(&TEST_FILE_2).unwrap();
         let table = file.hash_table().unwrap();
         let table = table.get_hash_table("table").unwrap();
         let fail = table.get_hash_table("fail").unwrap_err();
-        assert_matches!(fail, Error::KeyNotFound(_));
+        assert_matches!(fail.inner(), Error::KeyNotFound(_));
+        assert_eq!(fail.path().unwrap(), ["table", "fail"]);
+    }
+
+    #[test]
+    fn error_path() {
+        let mut inner = HashTableBuilder::new();
+        inner.insert_string("int", "string").unwrap();
+        let mut nested = HashTableBuilder::new();
+        nested.insert_table("inner", inner).unwrap();
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert_table("table", nested).unwrap();
+        table_builder.insert_string("string", "test").unwrap();
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+
+        // Errors in the root hash table have no path
+        let root = file.hash_table().unwrap();
+        let err = root.get_u32("string").unwrap_err();
+        assert_matches!(err, Error::TypeMismatch { .. });
+        assert!(err.path().is_none());
+        assert!(std::error::Error::source(&err).is_none());
+
+        let inner = root
+            .get_hash_table("table")
+            .unwrap()
+            .get_hash_table("inner")
+            .unwrap();
+        let err = inner.get_u32("int").unwrap_err();
+        assert_eq!(err.path().unwrap(), ["table", "inner", "int"]);
+        assert_matches!(err.inner(), Error::TypeMismatch { found, .. } if found == "s");
+        assert_eq!(
+            format!("{}", err),
+            "error at '/table/inner/int': Value for key 'int' has type 's', expected an integer type"
+        );
+        assert!(std::error::Error::source(&err).is_some());
+
+        // Errors of getters that call other getters have a single path
+        for err in [
+            inner.get::<u32>("int").unwrap_err(),
+            inner.get_string("missing").unwrap_err(),
+            inner.get_owned_value("missing").unwrap_err(),
+            inner
+                .lookup("int")
+                .unwrap()
+                .unwrap()
+                .as_table()
+                .unwrap_err(),
+        ] {
+            let path = err.path().unwrap();
+            assert_eq!(path[..2], ["table", "inner"]);
+            assert_eq!(path.len(), 3);
+            assert_matches!(err, Error::Context { source, .. } if source.path().is_none());
+        }
+
+        assert!(inner.get_str("int").is_ok());
+        assert!(file.table("table").unwrap().get_raw("missing").is_err());
+
+        // Keys that contain the separator are escaped
+        let mut inner = HashTableBuilder::with_path_separator(None);
+        inner.insert_string("a/b\\", "string").unwrap();
+        let mut table_builder = HashTableBuilder::with_path_separator(None);
+        table_builder.insert_table("/org/", inner).unwrap();
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let inner = file.hash_table().unwrap().get_hash_table("/org/").unwrap();
+        let err = inner.get_u32("a/b\\").unwrap_err();
+        assert_eq!(err.path().unwrap(), ["/org/", "a/b\\"]);
+        assert!(format!("{}", err).starts_with("error at '/\\/org\\//a\\/b\\\\': "));
+    }
+
+    /// A file with nested containers of fixed and variable size types
+    pub(crate) fn new_nested_file(big_endian: bool) -> File<'static> {
+        let writer = if big_endian {
+            FileWriter::for_big_endian()
+        } else {
+            FileWriter::new()
+        };
+
+        let mut dict = std::collections::HashMap::new();
+        dict.insert("a".to_string(), zvariant::Value::new(vec![1i16, -2, 3]));
+        dict.insert("b".to_string(), zvariant::Value::new((4u64, 5.5f64)));
+        let value = zvariant::Value::new((
+            vec![(1u32, "one".to_string()), (2u32, "two".to_string())],
+            dict,
+            zvariant::Value::new(vec![0x1234u16, 0x5678]),
+            -7i64,
+        ));
+
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert_value("nested", value).unwrap();
+        let data = writer.write_to_vec_with_table(table_builder).unwrap();
+        File::from_bytes(Cow::Owned(data)).unwrap()
+    }
+
+    #[test]
+    fn big_endian_containers() {
+        let le = new_nested_file(false);
+        let be = new_nested_file(true);
+        assert!(be.byteswapped);
+
+        let le_table = le.hash_table().unwrap();
+        let be_table = be.hash_table().unwrap();
+        assert_eq!(
+            le_table.get_value("nested").unwrap(),
+            be_table.get_value("nested").unwrap()
+        );
+    }
+
+    #[test]
+    fn get_raw() {
+        let file = File::from_file(&TEST_FILE_2).unwrap();
+        let table = file.hash_table().unwrap();
+        let (typ, data) = table.get_raw("string").unwrap();
+        assert_eq!(typ, HashItemType::Value);
+        assert_eq!(data, b"test string\0\0s");
+
+        let (typ, data) = table.get_raw("table").unwrap();
+        assert_eq!(typ, HashItemType::HashTable);
+        assert_eq!(
+            table.get_hash_table("table").unwrap().pointer.size(),
+            data.len()
+        );
+
+        let info = table.get_item_info("table").unwrap();
+        assert_eq!(info.typ(), HashItemType::HashTable);
+        assert_eq!(info.parent(), None);
+        assert_eq!(info.key_size() as usize, "table".len());
+        assert_eq!(info.hash_value(), djb_hash("table"));
+        assert_eq!((info.value_end() - info.value_start()) as usize, data.len());
+
+        assert_matches!(table.get_raw("fail"), Err(Error::KeyNotFound(_)));
+        assert_matches!(table.get_item_info("fail"), Err(Error::KeyNotFound(_)));
     }
 
     #[test]
@@ -647,7 +2399,7 @@ pub(crate) mod test {
             item.parent(),
             key_ptr,
             item.typ().unwrap(),
-            item.value_ptr().clone(),
+            *item.value_ptr(),
         );
 
         assert_eq!(table.check_key(&broken_item, "table"), false);
@@ -667,7 +2419,7 @@ pub(crate) mod test {
             50,
             item.key_ptr(),
             item.typ().unwrap(),
-            item.value_ptr().clone(),
+            *item.value_ptr(),
         );
 
         assert_eq!(
@@ -675,10 +2427,49 @@ pub(crate) mod test {
             false
         );
     }
+
+    #[test]
+    fn lookup_fallback() {
+        let mut table_builder = HashTableBuilder::new().with_bucket_count(4);
+        for i in 0..20 {
+            table_builder
+                .insert(&format!("key{}", i), i as u32)
+                .unwrap();
+        }
+        let mut data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+
+        // Let every bucket start at the first item, so all items are in the range of the last one
+        let file = File::from_bytes(Cow::Owned(data.clone())).unwrap();
+        let table = file.hash_table().unwrap();
+        let buckets = table.pointer.start() as usize + table.hash_buckets_offset();
+        for bucket in 0..4 {
+            let offset = buckets + bucket * size_of::<u32>();
+            data[offset..offset + size_of::<u32>()].copy_from_slice(&0u32.to_le_bytes());
+        }
+
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        assert!(!file.validate().is_empty());
+        let table = file.hash_table().unwrap();
+        let missing = (0..20)
+            .filter(|i| table.get_u32(&format!("key{}", i)).is_err())
+            .count();
+        assert!(missing > 0);
+
+        let file = file.with_lookup_fallback(true);
+        assert!(file.lookup_fallback());
+        let table = file.hash_table().unwrap();
+        for i in 0..20 {
+            assert_eq!(table.get_u32(&format!("key{}", i)).unwrap(), i);
+        }
+        assert_matches!(table.get_u32("key20"), Err(Error::KeyNotFound(_)));
+    }
 }
 
 #[cfg(all(feature = "glib", test))]
 mod test_glib {
+    use super::test::new_nested_file;
     use crate::read::Error;
     use crate::test::new_simple_file;
     use glib::prelude::*;
@@ -696,4 +2487,113 @@ mod test_glib {
             assert_matches!(fail, Error::KeyNotFound(_));
         }
     }
+    #[test]
+    fn get_gvariant_big_endian() {
+        // Compare the byteswapped glib variant with the zvariant value of the same data
+        let file = new_nested_file(true);
+        let table = file.hash_table().unwrap();
+        let variant = table.get_gvariant("nested").unwrap();
+        let value = table.get_value("nested").unwrap();
+
+        let endian = if cfg!(target_endian = "little") {
+            zvariant::LE
+        } else {
+            zvariant::BE
+        };
+        let context = zvariant::serialized::Context::new_gvariant(endian, 0);
+        let data = zvariant::to_bytes(context, &value).unwrap();
+        assert_eq!(variant.data(), &*data);
+
+        let inner: glib::Variant = variant.get().unwrap();
+        assert_eq!(inner.type_().as_str(), value.value_signature().as_str());
+    }
+}
+
+#[cfg(all(feature = "tracing", test))]
+mod test_tracing {
+    use crate::read::File;
+    use crate::util::djb_hash;
+    use crate::write::{FileWriter, HashTableBuilder};
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata};
+
+    /// Records the messages of all events
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    struct MessageVisitor<'a>(&'a mut Vec<String>);
+
+    impl Visit for MessageVisitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "message" {
+                self.0.push(format!("{:?}", value));
+            }
+        }
+    }
+
+    impl tracing::Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut MessageVisitor(&mut self.0.lock().unwrap()));
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn lookup_events() {
+        let mut table_builder = HashTableBuilder::new();
+        for i in 0..10 {
+            table_builder.insert(&format!("key{}", i), i).unwrap();
+        }
+        let data = FileWriter::new()
+            .with_bloom_words(16)
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            let table = file.hash_table().unwrap();
+            let missing = |accepted: bool| {
+                (0..)
+                    .map(|i| format!("missing{}", i))
+                    .find(|key| table.bloom_filter(djb_hash(key)) == accepted)
+                    .unwrap()
+            };
+
+            assert!(table.get_hash_item("key0").is_ok());
+            assert!(table.get_hash_item(&missing(true)).is_err());
+            assert!(table.get_hash_item(&missing(false)).is_err());
+        });
+
+        let messages = recorder.0.lock().unwrap();
+        for message in [
+            "Read GVDB header",
+            "Read hash table",
+            "Lookup hit",
+            "Lookup miss",
+            "Lookup rejected by bloom filter",
+        ] {
+            assert!(messages.iter().any(|m| m == message), "{}", message);
+        }
+    }
 }
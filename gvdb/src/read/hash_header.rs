@@ -0,0 +1,62 @@
+use core::fmt::{Debug, Formatter};
+use core::mem::size_of;
+use zerocopy::little_endian::U32;
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
+
+/// The header of a GVDB hash table
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq, FromBytes, IntoBytes, Immutable, KnownLayout, Unaligned)]
+pub struct HashHeader {
+    n_bloom_words: U32,
+    n_buckets: U32,
+}
+
+impl HashHeader {
+    /// Create a new [`HashHeader`]` using the provided `bloom_shift`, `n_bloom_words` and
+    /// `n_buckets`
+    #[cfg(feature = "std")]
+    pub fn new(bloom_shift: u32, n_bloom_words: u32, n_buckets: u32) -> Self {
+        assert!(n_bloom_words < (1 << 27));
+        let n_bloom_words = bloom_shift << 27 | n_bloom_words;
+
+        Self {
+            n_bloom_words: U32::new(n_bloom_words),
+            n_buckets: U32::new(n_buckets),
+        }
+    }
+
+    /// Number of bloom words in the hash table header
+    pub fn n_bloom_words(&self) -> u32 {
+        self.n_bloom_words.get() & ((1 << 27) - 1)
+    }
+
+    /// The bloom shift stored in the upper 5 bits of the bloom words field
+    pub fn bloom_shift(&self) -> u32 {
+        self.n_bloom_words.get() >> 27
+    }
+
+    /// Size of the bloom words section in the header
+    pub fn bloom_words_len(&self) -> usize {
+        self.n_bloom_words() as usize * size_of::<u32>()
+    }
+
+    /// Number of hash buckets in the hash table header
+    pub fn n_buckets(&self) -> u32 {
+        self.n_buckets.get()
+    }
+
+    /// Length of the hash buckets section in the header
+    pub fn buckets_len(&self) -> usize {
+        self.n_buckets() as usize * size_of::<u32>()
+    }
+}
+
+impl Debug for HashHeader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HashHeader")
+            .field("n_bloom_words", &self.n_bloom_words())
+            .field("n_buckets", &self.n_buckets())
+            .field("data", &self.as_bytes())
+            .finish()
+    }
+}
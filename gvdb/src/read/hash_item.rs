@@ -1,12 +1,19 @@
 use crate::read::error::{Error, Result};
 use crate::read::pointer::Pointer;
-use safe_transmute::TriviallyTransmutable;
-use std::fmt::{Display, Formatter};
+use core::fmt::{Display, Formatter};
+use zerocopy::little_endian::{U16, U32};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
 
-#[derive(PartialEq, Eq, Debug)]
+/// The type of an item in a GVDB hash table
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum HashItemType {
+    /// A GVariant value (`v`)
     Value,
+
+    /// A nested hash table (`H`)
     HashTable,
+
+    /// A container listing the indices of its child items (`L`)
     Container,
 }
 
@@ -32,13 +39,13 @@ impl TryFrom<u8> for HashItemType {
         } else if chr == 'L' {
             Ok(HashItemType::Container)
         } else {
-            Err(Error::Data(format!("Invalid HashItemType: '{}'", chr)))
+            Err(Error::InvalidItemType { found: value })
         }
     }
 }
 
 impl Display for HashItemType {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let text = match self {
             HashItemType::Value => "Value",
             HashItemType::HashTable => "HashTable",
@@ -50,13 +57,13 @@ impl Display for HashItemType {
 }
 
 #[repr(C)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, FromBytes, IntoBytes, Immutable, KnownLayout, Unaligned)]
 pub struct HashItem {
-    hash_value: u32,
-    parent: u32,
+    hash_value: U32,
+    parent: U32,
 
-    key_start: u32,
-    key_size: u16,
+    key_start: U32,
+    key_size: U16,
 
     typ: u8,
     unused: u8,
@@ -64,9 +71,8 @@ pub struct HashItem {
     value: Pointer,
 }
 
-unsafe impl TriviallyTransmutable for HashItem {}
-
 impl HashItem {
+    #[cfg(feature = "std")]
     pub fn new(
         hash_value: u32,
         parent: u32,
@@ -74,14 +80,11 @@ impl HashItem {
         typ: HashItemType,
         value: Pointer,
     ) -> Self {
-        let key_start = key_ptr.start().to_le();
-        let key_size = (key_ptr.size() as u16).to_le();
-
         Self {
-            hash_value: hash_value.to_le(),
-            parent: parent.to_le(),
-            key_start,
-            key_size,
+            hash_value: U32::new(hash_value),
+            parent: U32::new(parent),
+            key_start: U32::new(key_ptr.start()),
+            key_size: U16::new(key_ptr.size() as u16),
             typ: typ.into(),
             unused: 0,
             value,
@@ -89,19 +92,19 @@ impl HashItem {
     }
 
     pub fn hash_value(&self) -> u32 {
-        u32::from_le(self.hash_value)
+        self.hash_value.get()
     }
 
     pub fn parent(&self) -> u32 {
-        u32::from_le(self.parent)
+        self.parent.get()
     }
 
     pub fn key_start(&self) -> u32 {
-        u32::from_le(self.key_start)
+        self.key_start.get()
     }
 
     pub fn key_size(&self) -> u16 {
-        u16::from_le(self.key_size)
+        self.key_size.get()
     }
 
     pub fn key_ptr(&self) -> Pointer {
@@ -120,8 +123,74 @@ impl HashItem {
     }
 }
 
-impl std::fmt::Debug for HashItem {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+/// A read-only view of the metadata of an item in a GVDB hash table
+///
+/// Created by [`HashTable::get_item_info`](crate::read::HashTable::get_item_info). All offsets
+/// are byte offsets relative to the start of the file.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct HashItemInfo {
+    hash_value: u32,
+    parent: Option<u32>,
+    key_start: u32,
+    key_size: u16,
+    typ: HashItemType,
+    value_start: u32,
+    value_end: u32,
+}
+
+impl HashItemInfo {
+    #[cfg(feature = "std")]
+    pub(crate) fn new(item: &HashItem) -> Result<Self> {
+        let parent = item.parent();
+        Ok(Self {
+            hash_value: item.hash_value(),
+            parent: (parent != u32::MAX).then_some(parent),
+            key_start: item.key_start(),
+            key_size: item.key_size(),
+            typ: item.typ()?,
+            value_start: item.value_ptr().start(),
+            value_end: item.value_ptr().end(),
+        })
+    }
+
+    /// The hash value of the full key of this item
+    pub fn hash_value(&self) -> u32 {
+        self.hash_value
+    }
+
+    /// The index of the parent item in the same hash table, if the item has a parent
+    pub fn parent(&self) -> Option<u32> {
+        self.parent
+    }
+
+    /// The offset of the key part of this item that is not shared with the parent
+    pub fn key_start(&self) -> u32 {
+        self.key_start
+    }
+
+    /// The size of the key part of this item that is not shared with the parent
+    pub fn key_size(&self) -> u16 {
+        self.key_size
+    }
+
+    /// The type of this item
+    pub fn typ(&self) -> HashItemType {
+        self.typ
+    }
+
+    /// The offset of the start of the data of this item
+    pub fn value_start(&self) -> u32 {
+        self.value_start
+    }
+
+    /// The offset of the end of the data of this item
+    pub fn value_end(&self) -> u32 {
+        self.value_end
+    }
+}
+
+impl core::fmt::Debug for HashItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("HashItem")
             .field("hash_value", &self.hash_value())
             .field("parent", &self.parent())
@@ -136,7 +205,7 @@ impl std::fmt::Debug for HashItem {
 
 #[cfg(test)]
 mod test {
-    use crate::read::{Error, HashItem, HashItemType, Pointer};
+    use crate::read::{Error, HashItem, HashItemInfo, HashItemType, Pointer};
     use matches::assert_matches;
 
     #[test]
@@ -149,7 +218,7 @@ mod test {
         println!("{}, {:?}", typ, typ);
 
         let item = HashItem::new(0, 0, Pointer::NULL, HashItemType::Value, Pointer::NULL);
-        let item2 = item.clone();
+        let item2 = item;
         println!("{:?}", item2);
     }
 
@@ -158,8 +227,14 @@ mod test {
         assert_matches!(HashItemType::try_from(b'v'), Ok(HashItemType::Value));
         assert_matches!(HashItemType::try_from(b'H'), Ok(HashItemType::HashTable));
         assert_matches!(HashItemType::try_from(b'L'), Ok(HashItemType::Container));
-        assert_matches!(HashItemType::try_from(b'x'), Err(Error::Data(_)));
-        assert_matches!(HashItemType::try_from(b'?'), Err(Error::Data(_)));
+        assert_matches!(
+            HashItemType::try_from(b'x'),
+            Err(Error::InvalidItemType { found: b'x' })
+        );
+        assert_matches!(
+            HashItemType::try_from(b'?'),
+            Err(Error::InvalidItemType { found: b'?' })
+        );
     }
 
     #[test]
@@ -172,4 +247,27 @@ mod test {
         assert_matches!(item.typ(), Ok(HashItemType::Value));
         assert_eq!(item.value_ptr(), &Pointer::NULL);
     }
+
+    #[test]
+    fn info() {
+        let item = HashItem::new(
+            5,
+            u32::MAX,
+            Pointer::new(10, 14),
+            HashItemType::HashTable,
+            Pointer::new(16, 32),
+        );
+
+        let info = HashItemInfo::new(&item).unwrap();
+        assert_eq!(info.hash_value(), 5);
+        assert_eq!(info.parent(), None);
+        assert_eq!(info.key_start(), 10);
+        assert_eq!(info.key_size(), 4);
+        assert_eq!(info.typ(), HashItemType::HashTable);
+        assert_eq!(info.value_start(), 16);
+        assert_eq!(info.value_end(), 32);
+
+        let item = HashItem::new(0, 1, Pointer::NULL, HashItemType::Value, Pointer::NULL);
+        assert_eq!(HashItemInfo::new(&item).unwrap().parent(), Some(1));
+    }
 }
@@ -1,25 +1,28 @@
 use crate::read::error::{Error, Result};
 use crate::read::pointer::Pointer;
-use safe_transmute::TriviallyTransmutable;
+use crate::Endian;
+use zerocopy::little_endian::U32;
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
 
 // This is just a string, but it is stored in the byteorder of the file
-// Default byteorder is little endian, but the format supports big endian as well
+// Default byteorder is little endian, but the format supports big endian as well.
+// The constants are the values of the signature when it is read as little endian.
 // "GVar"
 const GVDB_SIGNATURE0: u32 = 1918981703;
 // "iant"
 const GVDB_SIGNATURE1: u32 = 1953390953;
 
 #[repr(C)]
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(
+    Copy, Clone, PartialEq, Eq, Debug, FromBytes, IntoBytes, Immutable, KnownLayout, Unaligned,
+)]
 pub struct Header {
-    signature: [u32; 2],
-    version: u32,
-    options: u32,
+    signature: [U32; 2],
+    version: U32,
+    options: U32,
     root: Pointer,
 }
 
-unsafe impl TriviallyTransmutable for Header {}
-
 impl Header {
     #[cfg(test)]
     pub fn new_le(version: u32, root: Pointer) -> Self {
@@ -41,73 +44,82 @@ impl Header {
         Self::new(byteswap, version, root)
     }
 
+    #[cfg(feature = "std")]
     pub fn new(byteswap: bool, version: u32, root: Pointer) -> Self {
-        let signature = if !byteswap {
-            [GVDB_SIGNATURE0, GVDB_SIGNATURE1]
-        } else {
-            [GVDB_SIGNATURE0.swap_bytes(), GVDB_SIGNATURE1.swap_bytes()]
+        let signature = match Endian::from_byteswapped(byteswap) {
+            Endian::Little => [GVDB_SIGNATURE0, GVDB_SIGNATURE1],
+            Endian::Big => [GVDB_SIGNATURE0.swap_bytes(), GVDB_SIGNATURE1.swap_bytes()],
         };
 
         Self {
-            signature,
-            version: version.to_le(),
-            options: 0,
+            signature: signature.map(U32::new),
+            version: U32::new(version),
+            options: U32::ZERO,
             root,
         }
     }
 
     pub fn is_byteswap(&self) -> Result<bool> {
-        if self.signature[0] == GVDB_SIGNATURE0 && self.signature[1] == GVDB_SIGNATURE1 {
-            Ok(false)
-        } else if self.signature[0] == GVDB_SIGNATURE0.swap_bytes()
-            && self.signature[1] == GVDB_SIGNATURE1.swap_bytes()
-        {
-            Ok(true)
+        let signature = self.signature();
+        if signature == [GVDB_SIGNATURE0, GVDB_SIGNATURE1] {
+            Ok(Endian::Little.is_byteswapped())
+        } else if signature == [GVDB_SIGNATURE0.swap_bytes(), GVDB_SIGNATURE1.swap_bytes()] {
+            Ok(Endian::Big.is_byteswapped())
         } else {
-            Err(Error::Data(format!(
-                "Invalid GVDB header signature: {:?}. Is this a GVariant database file?",
-                self.signature
-            )))
+            Err(Error::InvalidHeader { signature })
         }
     }
 
-    pub fn header_valid(&self) -> bool {
-        self.is_byteswap().is_ok()
+    /// The signature as it is read as little endian, see [`Error::InvalidHeader`]
+    pub fn signature(&self) -> [u32; 2] {
+        self.signature.map(U32::get)
     }
 
     pub fn version(&self) -> u32 {
-        self.version
+        self.version.get()
     }
 
     pub fn root(&self) -> &Pointer {
         &self.root
     }
+
+    /// The CRC32 checksum of the data after the header, stored in the `options` field that is
+    /// ignored by GLib. Zero if the file has no checksum.
+    #[cfg(feature = "std")]
+    pub fn checksum(&self) -> u32 {
+        self.options.get()
+    }
+
+    #[cfg(feature = "std")]
+    pub fn with_checksum(mut self, checksum: u32) -> Self {
+        self.options = U32::new(checksum);
+        self
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use safe_transmute::{transmute_one_pedantic, transmute_one_to_bytes};
 
     #[test]
     fn derives() {
         let header = Header::new(false, 0, Pointer::NULL);
-        let header2 = header.clone();
+        let header2 = header;
         println!("{:?}", header2);
     }
 
     #[test]
     fn header_serialize() {
         let header = Header::new(false, 123, Pointer::NULL);
-        assert_eq!(header.is_byteswap().unwrap(), false);
-        let data = transmute_one_to_bytes(&header);
-        let parsed_header: Header = transmute_one_pedantic(data.as_ref()).unwrap();
-        assert_eq!(parsed_header.is_byteswap().unwrap(), false);
+        assert!(!header.is_byteswap().unwrap());
+        let data = header.as_bytes();
+        let parsed_header = Header::read_from_bytes(data).unwrap();
+        assert!(!parsed_header.is_byteswap().unwrap());
 
         let header = Header::new(true, 0, Pointer::NULL);
-        assert_eq!(header.is_byteswap().unwrap(), true);
-        let data = transmute_one_to_bytes(&header);
-        let parsed_header: Header = transmute_one_pedantic(data.as_ref()).unwrap();
-        assert_eq!(parsed_header.is_byteswap().unwrap(), true);
+        assert!(header.is_byteswap().unwrap());
+        let data = header.as_bytes();
+        let parsed_header = Header::read_from_bytes(data).unwrap();
+        assert!(parsed_header.is_byteswap().unwrap());
     }
 }
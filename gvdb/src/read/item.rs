@@ -0,0 +1,151 @@
+use crate::read::error::{Error, Result};
+use crate::read::{HashItem, HashItemInfo, HashItemType, HashTable};
+
+/// An item of a [`HashTable`] that has already been looked up
+///
+/// Created by [`HashTable::lookup`]. The item is located once, the accessors only decode its
+/// data. This avoids hashing the key and searching the hash bucket again when the type of the
+/// item is not known in advance.
+#[derive(Clone, Debug)]
+pub struct Item<'a, 'file> {
+    table: HashTable<'a, 'file>,
+    key: String,
+    item: HashItem,
+}
+
+impl<'a, 'file> Item<'a, 'file> {
+    /// The full key of the item
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The type of the item
+    pub fn item_type(&self) -> Result<HashItemType> {
+        self.item.typ()
+    }
+
+    /// The metadata of the item, see [`HashTable::get_item_info`]
+    pub fn info(&self) -> Result<HashItemInfo> {
+        HashItemInfo::new(&self.item)
+    }
+
+    /// The raw bytes of the item, without decoding them, see [`HashTable::get_raw`]
+    pub fn as_bytes(&self) -> Result<&'a [u8]> {
+        Ok(self.table.raw_item(&self.item)?.1)
+    }
+
+    /// Decode the item as [`enum@zvariant::Value`]
+    ///
+    /// Returns [`Error::WrongItemType`] if the item is not a value.
+    pub fn as_value(&self) -> Result<zvariant::Value<'a>> {
+        self.table.with_path(&self.key, || {
+            let (typ, data) = self.table.raw_item(&self.item)?;
+            if typ != HashItemType::Value {
+                return Err(Error::WrongItemType {
+                    expected: HashItemType::Value,
+                    found: typ,
+                    key: self.key.clone(),
+                });
+            }
+
+            self.table.value_for_bytes(data)
+        })
+    }
+
+    /// The nested [`HashTable`] of the item
+    ///
+    /// Returns [`Error::WrongItemType`] if the item is not a hash table.
+    pub fn as_table(&self) -> Result<HashTable<'a, 'file>> {
+        self.table.with_path(&self.key, || {
+            let typ = self.item.typ()?;
+            if typ != HashItemType::HashTable {
+                return Err(Error::WrongItemType {
+                    expected: HashItemType::HashTable,
+                    found: typ,
+                    key: self.key.clone(),
+                });
+            }
+
+            self.table.nested_at(*self.item.value_ptr(), &self.key)
+        })
+    }
+}
+
+impl<'a, 'file> HashTable<'a, 'file> {
+    /// Look up the item at `key` once and return a handle to access its data
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// use gvdb::read::{File, HashItemType};
+    ///
+    /// let file = File::from_file(&PathBuf::from("test-data/test2.gvdb")).unwrap();
+    /// let table = file.hash_table().unwrap();
+    ///
+    /// let item = table.lookup("table").unwrap().unwrap();
+    /// if item.item_type().unwrap() == HashItemType::HashTable {
+    ///     let nested = item.as_table().unwrap();
+    ///     assert_eq!(nested.get::<u32>("int").unwrap(), 42);
+    /// }
+    ///
+    /// assert!(table.lookup("missing").unwrap().is_none());
+    /// ```
+    pub fn lookup(&self, key: &str) -> Result<Option<Item<'a, 'file>>> {
+        match self.get_hash_item(key) {
+            Ok(item) => Ok(Some(Item {
+                table: self.clone(),
+                key: key.to_string(),
+                item,
+            })),
+            Err(err) if err.is_key_not_found() => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::{Error, File, HashItemType};
+    use crate::test::*;
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    #[test]
+    fn lookup() {
+        let file = File::from_file(&TEST_FILE_2).unwrap();
+        let table = file.hash_table().unwrap();
+
+        let item = table.lookup("string").unwrap().unwrap();
+        assert_eq!(item.key(), "string");
+        assert_eq!(item.item_type().unwrap(), HashItemType::Value);
+        assert_eq!(
+            item.as_value().unwrap(),
+            zvariant::Value::from("test string")
+        );
+        assert_eq!(item.as_bytes().unwrap(), table.get_raw("string").unwrap().1);
+        assert_eq!(item.info().unwrap(), table.get_item_info("string").unwrap());
+        assert_matches!(
+            item.as_table(),
+            Err(Error::WrongItemType {
+                expected: HashItemType::HashTable,
+                found: HashItemType::Value,
+                ..
+            })
+        );
+
+        let item = table.lookup("table").unwrap().unwrap();
+        assert_eq!(item.item_type().unwrap(), HashItemType::HashTable);
+        assert_eq!(item.as_table().unwrap().get::<u32>("int").unwrap(), 42);
+        assert_matches!(item.as_value(), Err(Error::WrongItemType { .. }));
+
+        assert!(table.lookup("missing").unwrap().is_none());
+
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let table = file.hash_table().unwrap();
+        let item = table.lookup("/gvdb/rs/test/").unwrap().unwrap();
+        assert_eq!(item.item_type().unwrap(), HashItemType::Container);
+        assert_eq!(item.as_bytes().unwrap().len() % 4, 0);
+    }
+}
@@ -0,0 +1,352 @@
+use crate::read::error::{Error, Result};
+use crate::read::{HashItemType, HashTable};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::iter::Enumerate;
+
+/// Iterator over the full keys of a [`HashTable`], ordered by item index
+///
+/// Created by [`HashTable::iter_keys`]. Keys of items without a parent are borrowed from the file.
+/// The keys of items in a container are assembled from the full key of the container, which is
+/// resolved once and cached for all of its children.
+#[derive(Debug)]
+pub struct Keys<'t, 'a, 'file> {
+    table: &'t HashTable<'a, 'file>,
+    index: usize,
+    len: usize,
+    // The full keys of the containers that were encountered as parents, by item index
+    prefixes: HashMap<usize, String>,
+}
+
+impl<'t, 'a, 'file> Keys<'t, 'a, 'file> {
+    pub(crate) fn new(table: &'t HashTable<'a, 'file>) -> Self {
+        Self {
+            table,
+            index: 0,
+            len: table.n_hash_items(),
+            prefixes: HashMap::new(),
+        }
+    }
+
+    /// The full key of the item at `index`
+    fn key(&mut self, index: usize) -> Result<Cow<'a, str>> {
+        let item = self.table.get_hash_item_for_index(index)?;
+        let name = self.table.key_for_item(&item)?;
+        if item.parent() == u32::MAX {
+            Ok(Cow::Borrowed(name))
+        } else {
+            Ok(Cow::Owned(
+                self.prefix(item.parent() as usize)?.to_string() + name,
+            ))
+        }
+    }
+
+    /// The full key of the parent item at `index`, resolved with all of its uncached parents
+    fn prefix(&mut self, index: usize) -> Result<&str> {
+        // Walk up to the first cached parent or the root, a valid chain visits every item once
+        let mut chain = Vec::new();
+        let mut current = index;
+        while !self.prefixes.contains_key(&current) {
+            if current >= self.len {
+                return Err(Error::Data(format!(
+                    "Parent with invalid offset encountered: {}",
+                    current
+                )));
+            } else if chain.len() >= self.len {
+                return Err(Error::Data(
+                    "Error finding all parent items. The file appears to have a loop".to_string(),
+                ));
+            }
+
+            chain.push(current);
+            let parent = self.table.get_hash_item_for_index(current)?.parent();
+            if parent == u32::MAX {
+                break;
+            }
+            current = parent as usize;
+        }
+
+        for &index in chain.iter().rev() {
+            let key = self.key(index)?.into_owned();
+            self.prefixes.insert(index, key);
+        }
+
+        Ok(&self.prefixes[&index])
+    }
+}
+
+impl<'a> Iterator for Keys<'_, 'a, '_> {
+    type Item = Result<Cow<'a, str>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.len {
+            return None;
+        }
+
+        let result = self.key(self.index);
+        // The keys can't be resolved after an error, so the iteration ends
+        self.index = if result.is_ok() {
+            self.index + 1
+        } else {
+            self.len
+        };
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.len - self.index))
+    }
+}
+
+/// Iterator over the raw items of a [`HashTable`]
+///
+/// Created by [`HashTable::iter_raw`].
+#[derive(Debug)]
+pub struct IterRaw<'t, 'a, 'file> {
+    table: &'t HashTable<'a, 'file>,
+    keys: Enumerate<std::vec::IntoIter<String>>,
+
+    // An error that occurred when resolving the keys, returned on the first iteration
+    error: Option<Error>,
+}
+
+impl<'t, 'a, 'file> IterRaw<'t, 'a, 'file> {
+    pub(crate) fn new(table: &'t HashTable<'a, 'file>) -> Self {
+        let (keys, error) = match table.keys() {
+            Ok(keys) => (keys, None),
+            Err(err) => (Vec::new(), Some(err)),
+        };
+
+        Self {
+            table,
+            keys: keys.into_iter().enumerate(),
+            error,
+        }
+    }
+}
+
+impl<'t, 'a, 'file> Iterator for IterRaw<'t, 'a, 'file> {
+    type Item = Result<(String, HashItemType, &'a [u8])>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.error.take() {
+            return Some(Err(err));
+        }
+
+        let (index, key) = self.keys.next()?;
+        Some(
+            self.table
+                .get_raw_item_for_index(index)
+                .map(|(typ, data)| (key, typ, data)),
+        )
+    }
+}
+
+/// Iterator over the values of a [`HashTable`]
+///
+/// Created by [`HashTable::iter`].
+#[derive(Debug)]
+pub struct Iter<'t, 'a, 'file> {
+    raw: IterRaw<'t, 'a, 'file>,
+}
+
+impl<'t, 'a, 'file> Iter<'t, 'a, 'file> {
+    pub(crate) fn new(raw: IterRaw<'t, 'a, 'file>) -> Self {
+        Self { raw }
+    }
+}
+
+impl<'t, 'a, 'file> Iterator for Iter<'t, 'a, 'file> {
+    type Item = Result<(String, zvariant::Value<'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.raw.next()? {
+                Ok((key, HashItemType::Value, data)) => {
+                    return Some(
+                        self.raw
+                            .table
+                            .value_for_bytes(data)
+                            .map(|value| (key, value)),
+                    )
+                }
+                Ok(_) => continue,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// Iterator over the values of a [`HashTable`] that don't borrow from the file
+///
+/// Created by [`HashTable::values_owned`].
+#[derive(Debug)]
+pub struct ValuesOwned<'t, 'a, 'file> {
+    iter: Iter<'t, 'a, 'file>,
+}
+
+impl<'t, 'a, 'file> ValuesOwned<'t, 'a, 'file> {
+    pub(crate) fn new(iter: Iter<'t, 'a, 'file>) -> Self {
+        Self { iter }
+    }
+}
+
+impl Iterator for ValuesOwned<'_, '_, '_> {
+    type Item = Result<(String, zvariant::OwnedValue)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.iter.next()?.and_then(|(key, value)| {
+            let value = value.try_to_owned()?;
+            Ok((key, value))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::{Error, File, HashItem, HashItemType};
+    use crate::test::{new_simple_file, TEST_FILE_2, TEST_FILE_3};
+    use crate::write::{FileWriter, HashTableBuilder};
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+    use std::mem::size_of;
+
+    #[test]
+    fn iter() {
+        for endianess in [true, false] {
+            let file = new_simple_file(endianess);
+            let table = file.hash_table().unwrap();
+            let items: Vec<(String, zvariant::Value)> =
+                table.iter().collect::<Result<_, _>>().unwrap();
+            assert_eq!(items, [("test".to_string(), zvariant::Value::new("test"))]);
+        }
+
+        let file = File::from_file(&TEST_FILE_2).unwrap();
+        let table = file.hash_table().unwrap();
+        let mut keys: Vec<String> = table.iter().map(|res| res.unwrap().0).collect();
+        keys.sort();
+        assert_eq!(keys, ["string"]);
+    }
+
+    #[test]
+    fn values_owned() {
+        let values: Vec<(String, zvariant::OwnedValue)> = {
+            let file = File::from_file(&TEST_FILE_3).unwrap();
+            let table = file.hash_table().unwrap();
+            assert_eq!(
+                table.get_owned_value("/gvdb/rs/test/test.css").unwrap(),
+                table
+                    .get_value("/gvdb/rs/test/test.css")
+                    .unwrap()
+                    .try_to_owned()
+                    .unwrap()
+            );
+            assert_matches!(table.get_owned_value("missing"), Err(Error::KeyNotFound(_)));
+            table.values_owned().collect::<Result<_, _>>().unwrap()
+        };
+
+        // The values outlive the file
+        assert_eq!(values.len(), 4);
+        assert!(values
+            .iter()
+            .any(|(key, _)| key == "/gvdb/rs/test/json/test.json"));
+
+        let file = new_simple_file(false);
+        let table = file.hash_table().unwrap();
+        let values: Vec<(String, zvariant::OwnedValue)> =
+            table.values_owned().collect::<Result<_, _>>().unwrap();
+        assert_eq!(values[0].0, "test");
+        assert_eq!(<&str>::try_from(&values[0].1).unwrap(), "test");
+    }
+
+    #[test]
+    fn iter_raw() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let table = file.hash_table().unwrap();
+        let mut items: Vec<(String, HashItemType, &[u8])> =
+            table.iter_raw().collect::<Result<_, _>>().unwrap();
+        items.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(items.len(), 12);
+
+        assert_eq!(items[0].0, "/");
+        assert_eq!(items[0].1, HashItemType::Container);
+        // A single child index
+        assert_eq!(items[0].2.len(), 4);
+
+        let (key, typ, data) = &items[9];
+        assert_eq!(key, "/gvdb/rs/test/json/test.json");
+        assert_eq!(*typ, HashItemType::Value);
+        let value = table.get_value(key).unwrap();
+        assert_eq!(table.value_for_bytes(data).unwrap(), value);
+
+        let file = File::from_file(&TEST_FILE_2).unwrap();
+        let table = file.hash_table().unwrap();
+        let types: Vec<HashItemType> = table.iter_raw().map(|res| res.unwrap().1).collect();
+        assert!(types.contains(&HashItemType::HashTable));
+    }
+
+    #[test]
+    fn iter_error() {
+        let mut table = HashTableBuilder::new();
+        table.insert_string("parent/test", "test").unwrap();
+        let mut data = FileWriter::new().write_to_vec_with_table(table).unwrap();
+
+        // Let 'test' point to itself as its parent
+        let file = File::from_bytes(Cow::Owned(data.clone())).unwrap();
+        let table = file.hash_table().unwrap();
+        let index = (0..table.n_hash_items())
+            .find(|index| {
+                let item = table.get_hash_item_for_index(*index).unwrap();
+                table.key_for_item(&item).unwrap() == "test"
+            })
+            .unwrap();
+        let parent_field = table.pointer.start() as usize
+            + table.hash_items_offset()
+            + size_of::<HashItem>() * index
+            + 4;
+        data[parent_field..parent_field + size_of::<u32>()]
+            .copy_from_slice(&(index as u32).to_le_bytes());
+
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+        let mut iter = table.iter();
+        println!("{:?}", iter);
+        assert_matches!(iter.next(), Some(Err(Error::Data(_))));
+        assert_matches!(iter.next(), None);
+
+        let mut iter = table.iter_raw();
+        assert_matches!(iter.next(), Some(Err(Error::Data(_))));
+        assert_matches!(iter.next(), None);
+
+        let mut keys = table.iter_keys().skip_while(|key| key.is_ok());
+        let err = keys.next().unwrap().unwrap_err();
+        assert!(format!("{}", err).contains("appears to have a loop"));
+        assert_matches!(keys.next(), None);
+    }
+
+    #[test]
+    fn iter_keys() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let table = file.hash_table().unwrap();
+        let keys: Vec<Cow<str>> = table.iter_keys().collect::<Result<_, _>>().unwrap();
+        assert_eq!(keys, table.keys().unwrap());
+        assert_eq!(table.iter_keys().size_hint(), (0, Some(12)));
+
+        for (index, key) in keys.iter().enumerate() {
+            let item = table.get_hash_item_for_index(index).unwrap();
+            if item.parent() == u32::MAX {
+                assert_matches!(key, Cow::Borrowed(_));
+            } else {
+                assert_matches!(key, Cow::Owned(_));
+            }
+        }
+
+        let mut iter = table.iter_keys();
+        assert_eq!(iter.by_ref().count(), 12);
+        assert_matches!(iter.next(), None);
+        // Only the containers are cached
+        assert_eq!(iter.prefixes.len(), table.counts().unwrap().2);
+    }
+}
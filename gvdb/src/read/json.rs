@@ -0,0 +1,188 @@
+use crate::read::error::{Error, Result};
+use crate::read::{File, HashItemType, HashTable};
+use serde_json::{Map, Number, Value as JsonValue};
+
+/// The JSON type string of nested hash tables
+pub(crate) const TABLE_TYPE: &str = "H";
+
+/// The JSON type string of containers
+pub(crate) const CONTAINER_TYPE: &str = "L";
+
+/// Create a JSON object with the type string `typ` and the JSON representation `value`
+pub(crate) fn json_entry(typ: &str, value: JsonValue) -> JsonValue {
+    let mut map = Map::new();
+    map.insert("type".to_string(), JsonValue::String(typ.to_string()));
+    map.insert("value".to_string(), value);
+    JsonValue::Object(map)
+}
+
+impl<'a> File<'a> {
+    /// Convert the contents of the file to JSON
+    ///
+    /// Every item of a hash table is represented as an object with the GVariant type string in
+    /// `type` and the JSON representation of the value in `value`. Nested hash tables use the
+    /// type `H` and contain another object of items. Containers use the type `L` and contain the
+    /// keys of their children.
+    ///
+    /// Use [`HashTableBuilder::from_json`](crate::write::HashTableBuilder::from_json) to convert
+    /// the JSON back to a hash table.
+    ///
+    /// ```
+    /// let path = std::path::PathBuf::from("test-data/test2.gvdb");
+    /// let file = gvdb::read::File::from_file(&path).unwrap();
+    /// let json = file.to_json().unwrap();
+    /// assert_eq!(json["string"]["type"], "s");
+    /// assert_eq!(json["table"]["value"]["int"]["value"], 42);
+    /// ```
+    pub fn to_json(&self) -> Result<JsonValue> {
+        table_to_json(&self.hash_table()?)
+    }
+}
+
+fn table_to_json(table: &HashTable) -> Result<JsonValue> {
+    let items = table.iter_raw().collect::<Result<Vec<_>>>()?;
+
+    let mut map = Map::new();
+    for (key, typ, data) in &items {
+        let value = match typ {
+            HashItemType::Value => {
+                let value = table.value_for_bytes(data)?;
+                json_entry(value.value_signature().as_str(), value_to_json(&value)?)
+            }
+            HashItemType::HashTable => {
+                json_entry(TABLE_TYPE, table_to_json(&table.get_hash_table(key)?)?)
+            }
+            HashItemType::Container => {
+                let children = data
+                    .chunks_exact(4)
+                    .map(|child| {
+                        let child = u32::from_le_bytes(child.try_into().unwrap()) as usize;
+                        items
+                            .get(child)
+                            .map(|(key, _, _)| JsonValue::String(key.clone()))
+                            .ok_or_else(|| {
+                                Error::Data(format!(
+                                    "Child with invalid index encountered: {}",
+                                    child
+                                ))
+                            })
+                    })
+                    .collect::<Result<_>>()?;
+
+                json_entry(CONTAINER_TYPE, JsonValue::Array(children))
+            }
+        };
+
+        map.insert(key.clone(), value);
+    }
+
+    Ok(JsonValue::Object(map))
+}
+
+/// Convert a value to its JSON representation
+fn value_to_json(value: &zvariant::Value) -> Result<JsonValue> {
+    use zvariant::Value;
+
+    Ok(match value {
+        Value::U8(v) => JsonValue::from(*v),
+        Value::Bool(v) => JsonValue::from(*v),
+        Value::I16(v) => JsonValue::from(*v),
+        Value::U16(v) => JsonValue::from(*v),
+        Value::I32(v) => JsonValue::from(*v),
+        Value::U32(v) => JsonValue::from(*v),
+        Value::I64(v) => JsonValue::from(*v),
+        Value::U64(v) => JsonValue::from(*v),
+        Value::F64(v) => Number::from_f64(*v).map(JsonValue::Number).ok_or_else(|| {
+            Error::Data(format!(
+                "Floating point value {} can't be represented in JSON",
+                v
+            ))
+        })?,
+        Value::Str(v) => JsonValue::from(v.as_str()),
+        Value::Signature(v) => JsonValue::from(v.as_str()),
+        Value::ObjectPath(v) => JsonValue::from(v.as_str()),
+        Value::Value(v) => json_entry(v.value_signature().as_str(), value_to_json(v)?),
+        Value::Array(array) => JsonValue::Array(
+            array
+                .inner()
+                .iter()
+                .map(value_to_json)
+                .collect::<Result<_>>()?,
+        ),
+        Value::Dict(dict) => {
+            // Dictionaries with string keys are represented as objects, all others as a list
+            // of key value pairs
+            let key_type = dict.full_signature().as_str()[2..].chars().next();
+            if matches!(key_type, Some('s' | 'o' | 'g')) {
+                let mut map = Map::new();
+                for (key, value) in dict.iter() {
+                    if let JsonValue::String(key) = value_to_json(key)? {
+                        map.insert(key, value_to_json(value)?);
+                    }
+                }
+
+                JsonValue::Object(map)
+            } else {
+                JsonValue::Array(
+                    dict.iter()
+                        .map(|(key, value)| {
+                            Ok(JsonValue::Array(vec![
+                                value_to_json(key)?,
+                                value_to_json(value)?,
+                            ]))
+                        })
+                        .collect::<Result<_>>()?,
+                )
+            }
+        }
+        Value::Structure(structure) => JsonValue::Array(
+            structure
+                .fields()
+                .iter()
+                .map(value_to_json)
+                .collect::<Result<_>>()?,
+        ),
+        Value::Maybe(maybe) => match maybe.inner() {
+            Some(value) => value_to_json(value)?,
+            None => JsonValue::Null,
+        },
+        #[cfg(unix)]
+        Value::Fd(_) => {
+            return Err(Error::Data(
+                "File descriptors can't be represented in JSON".to_string(),
+            ))
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::File;
+    use crate::test::{TEST_FILE_2, TEST_FILE_3};
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use serde_json::json;
+
+    #[test]
+    fn test_file_2() {
+        let file = File::from_file(&TEST_FILE_2).unwrap();
+        assert_eq!(
+            file.to_json().unwrap(),
+            json!({
+                "string": { "type": "s", "value": "test string" },
+                "table": { "type": "H", "value": { "int": { "type": "u", "value": 42 } } },
+            })
+        );
+    }
+
+    #[test]
+    fn test_file_3() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let json = file.to_json().unwrap();
+        assert_eq!(json["/"], json!({ "type": "L", "value": ["/gvdb/"] }));
+
+        let entry = &json["/gvdb/rs/test/json/test.json"];
+        assert_eq!(entry["type"], "(uuay)");
+        assert_eq!(entry["value"][1], 0);
+    }
+}
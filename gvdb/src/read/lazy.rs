@@ -0,0 +1,97 @@
+use crate::read::error::{Error, Result};
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// A source of GVDB data for [`File::from_reader`](crate::read::File::from_reader)
+pub(crate) trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+/// GVDB data that is read on demand from a [`Read`] + [`Seek`] source
+///
+/// Every byte range is read once and kept in memory for as long as the file is open, so the data
+/// can be borrowed like the data of a file that was read completely.
+pub(crate) struct LazyData {
+    source: Mutex<Box<dyn ReadSeek + Send>>,
+    // The position of the GVDB data in the source
+    offset: u64,
+    len: usize,
+    // The file name for I/O errors
+    path: Option<PathBuf>,
+    chunks: elsa::sync::FrozenMap<(usize, usize), Box<[u8]>>,
+}
+
+impl LazyData {
+    /// Read the GVDB data from the current position of `source` to its end
+    ///
+    /// `path` is the file name of `source` that is reported in I/O errors.
+    pub(crate) fn new(
+        mut source: impl Read + Seek + Send + 'static,
+        path: Option<PathBuf>,
+    ) -> Result<Self> {
+        let offset = source
+            .stream_position()
+            .map_err(|err| Error::Io(err, path.clone()))?;
+        let end = source
+            .seek(SeekFrom::End(0))
+            .map_err(|err| Error::Io(err, path.clone()))?;
+        let len = usize::try_from(end.saturating_sub(offset)).map_err(|_| {
+            Error::Data(format!(
+                "GVDB data of {} bytes is too large for this platform",
+                end - offset
+            ))
+        })?;
+
+        Ok(Self {
+            source: Mutex::new(Box::new(source)),
+            offset,
+            len,
+            path,
+            chunks: elsa::sync::FrozenMap::new(),
+        })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The bytes in `range`, or `None` if `range` is out of bounds
+    pub(crate) fn get(&self, range: Range<usize>) -> Result<Option<&[u8]>> {
+        if range.start > range.end || range.end > self.len {
+            return Ok(None);
+        }
+
+        let key = (range.start, range.end);
+        if let Some(chunk) = self.chunks.get(&key) {
+            return Ok(Some(chunk));
+        }
+
+        let mut chunk = vec![0; range.len()].into_boxed_slice();
+        {
+            // A panic while reading leaves the position undefined, but every read seeks first
+            let mut source = self
+                .source
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            source
+                .seek(SeekFrom::Start(self.offset + range.start as u64))
+                .and_then(|_| source.read_exact(&mut chunk))
+                .map_err(|err| Error::Io(err, self.path.clone()))?;
+        }
+
+        Ok(Some(self.chunks.insert(key, chunk)))
+    }
+}
+
+impl std::fmt::Debug for LazyData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyData")
+            .field("offset", &self.offset)
+            .field("len", &self.len)
+            .field("path", &self.path)
+            .field("chunks", &self.chunks.len())
+            .finish()
+    }
+}
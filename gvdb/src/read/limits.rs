@@ -0,0 +1,67 @@
+/// The maximum nesting depth of hash tables, which is enforced even without [`Limits`]
+pub(crate) const MAX_DEPTH: usize = 64;
+
+/// Resource limits for reading untrusted GVDB files
+///
+/// Used with [`File::from_bytes_with_limits`](crate::read::File::from_bytes_with_limits). When a
+/// limit is exceeded, the reader returns [`Error::LimitExceeded`](crate::read::Error::LimitExceeded)
+/// instead of reading the data. By default, no limits are applied.
+///
+/// Independent of these limits, hash tables that are nested more than 64 levels deep are always
+/// rejected with [`Error::Data`](crate::read::Error::Data), so crafted files with nested hash
+/// tables that contain themselves can't exhaust the stack.
+///
+/// ```
+/// use gvdb::read::Limits;
+///
+/// let limits = Limits::new()
+///     .with_max_items(1024)
+///     .with_max_depth(4)
+///     .with_max_value_size(64 * 1024);
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    max_items: Option<usize>,
+    max_depth: Option<usize>,
+    max_value_size: Option<usize>,
+}
+
+impl Limits {
+    /// Create new limits that don't restrict anything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow at most `max_items` items in a single hash table
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    /// Allow hash tables to be nested at most `max_depth` levels below the root hash table
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Allow at most `max_value_size` bytes of serialized data for a single value
+    pub fn with_max_value_size(mut self, max_value_size: usize) -> Self {
+        self.max_value_size = Some(max_value_size);
+        self
+    }
+
+    /// The maximum number of items in a single hash table
+    pub fn max_items(&self) -> Option<usize> {
+        self.max_items
+    }
+
+    /// The maximum nesting depth of hash tables
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// The maximum size of a single value in bytes
+    pub fn max_value_size(&self) -> Option<usize> {
+        self.max_value_size
+    }
+}
@@ -0,0 +1,249 @@
+use crate::read::error::{Error, Result};
+use crate::read::{File, HashItemType, HashTable};
+use std::borrow::Cow;
+
+/// An item of the root hash table that was skipped by [`File::from_bytes_lossy`]
+#[derive(Debug)]
+pub struct SkippedItem {
+    index: usize,
+    key: Option<String>,
+    error: Error,
+}
+
+impl SkippedItem {
+    /// The index of the item in the root hash table
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The full key of the item, if it could be read
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    /// The error that occurred when reading the item
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+}
+
+/// A file that was read with [`File::from_bytes_lossy`]
+///
+/// Contains the keys of all items of the root hash table that can be read and the items that were
+/// skipped.
+#[derive(Debug)]
+pub struct LossyFile<'a> {
+    file: File<'a>,
+    keys: Vec<String>,
+    skipped: Vec<SkippedItem>,
+}
+
+impl<'a> LossyFile<'a> {
+    /// The file
+    ///
+    /// The readable items can be accessed with the usual methods of its hash table, while all
+    /// skipped items return an error.
+    pub fn file(&self) -> &File<'a> {
+        &self.file
+    }
+
+    /// Return the file and drop the list of keys and skipped items
+    pub fn into_file(self) -> File<'a> {
+        self.file
+    }
+
+    /// The keys of the items of the root hash table that can be read, in the order of the items
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// The items of the root hash table that can't be read
+    pub fn skipped(&self) -> &[SkippedItem] {
+        &self.skipped
+    }
+
+    /// Whether all items of the root hash table can be read
+    pub fn is_complete(&self) -> bool {
+        self.skipped.is_empty()
+    }
+}
+
+/// The full key of the item at `index`, following the parents of the item
+fn full_key(table: &HashTable, index: usize) -> Result<String> {
+    let count = table.n_hash_items();
+    let mut segments = Vec::new();
+    let mut index = index;
+
+    loop {
+        if segments.len() > count {
+            return Err(Error::Data(
+                "Error finding all parent items. The file appears to have a loop".to_string(),
+            ));
+        }
+
+        let item = table.get_hash_item_for_index(index)?;
+        segments.push(table.key_for_item(&item)?);
+
+        let parent: usize = item.parent().try_into()?;
+        if parent == 0xffffffff {
+            break;
+        } else if parent >= count {
+            return Err(Error::Data(format!(
+                "Parent with invalid offset encountered: {}",
+                parent
+            )));
+        }
+
+        index = parent;
+    }
+
+    Ok(segments.into_iter().rev().collect())
+}
+
+/// Check that the data of the item at `index` can be read
+fn check_item(table: &HashTable, index: usize) -> Result<()> {
+    let item = table.get_hash_item_for_index(index)?;
+    table.raw_item(&item)?;
+
+    if item.typ()? == HashItemType::HashTable {
+        table.nested(*item.value_ptr())?;
+    }
+
+    Ok(())
+}
+
+impl<'a> File<'a> {
+    /// Interpret a slice of bytes as a GVDB file and skip all items that can't be read
+    ///
+    /// This is meant for salvaging data from truncated or otherwise damaged files. Items of the
+    /// root hash table whose key or data points outside of the file are skipped instead of
+    /// failing the whole file. Nested hash tables are skipped if their header or items are
+    /// damaged, but the items inside of them are not checked.
+    ///
+    /// Returns an error if the header or the root hash table itself can't be read.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert_string("string", "test string").unwrap();
+    /// table_builder.insert("int", 42u32).unwrap();
+    /// let mut data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// // Cut off the value that is written last
+    /// data.truncate(data.len() - 1);
+    ///
+    /// let file = File::from_bytes_lossy(Cow::Owned(data)).unwrap();
+    /// assert_eq!(file.keys().len(), 1);
+    /// assert_eq!(file.skipped().len(), 1);
+    ///
+    /// let table = file.file().hash_table().unwrap();
+    /// assert!(table.get_value(&file.keys()[0]).is_ok());
+    /// ```
+    pub fn from_bytes_lossy(bytes: Cow<'a, [u8]>) -> Result<LossyFile<'a>> {
+        let file = Self::from_bytes(bytes)?;
+        let table = file.hash_table()?;
+
+        let mut keys = Vec::new();
+        let mut skipped = Vec::new();
+        for index in 0..table.n_hash_items() {
+            let key = full_key(&table, index);
+            match (key, check_item(&table, index)) {
+                (Ok(key), Ok(())) => keys.push(key),
+                (Ok(key), Err(error)) => skipped.push(SkippedItem {
+                    index,
+                    key: Some(key),
+                    error,
+                }),
+                (Err(error), _) => skipped.push(SkippedItem {
+                    index,
+                    key: None,
+                    error,
+                }),
+            }
+        }
+
+        Ok(LossyFile {
+            file,
+            keys,
+            skipped,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::{Error, File};
+    use crate::test::*;
+    use crate::write::{FileWriter, HashTableBuilder};
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+
+    #[test]
+    fn from_bytes_lossy() {
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert_string("dir/a", "value a").unwrap();
+        table_builder.insert_string("dir/b", "value b").unwrap();
+        table_builder.insert_string("c", "value c").unwrap();
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+
+        let file = File::from_bytes_lossy(Cow::Borrowed(&data)).unwrap();
+        assert!(file.is_complete());
+        let mut keys = file.keys().to_vec();
+        keys.sort();
+        assert_eq!(keys, ["c", "dir/", "dir/a", "dir/b"]);
+        println!("{:?}", file);
+
+        // The last value is cut off
+        let truncated = &data[..data.len() - 1];
+        assert!(File::from_bytes(Cow::Borrowed(truncated))
+            .unwrap()
+            .hash_table()
+            .unwrap()
+            .keys()
+            .is_ok());
+        let file = File::from_bytes_lossy(Cow::Borrowed(truncated)).unwrap();
+        assert_eq!(file.keys().len(), 3);
+        assert_eq!(file.skipped().len(), 1);
+        let skipped = &file.skipped()[0];
+        assert_matches!(skipped.error(), Error::PointerOutOfBounds { .. });
+        let key = skipped.key().unwrap();
+        assert!(!file.keys().contains(&key.to_string()));
+
+        // The readable values can be accessed as usual
+        let table = file.file().hash_table().unwrap();
+        for key in file.keys() {
+            assert!(table.get_raw(key).is_ok());
+        }
+        assert!(table.get::<String>(key).is_err());
+
+        // Everything after the hash table is cut off
+        let root = File::from_bytes(Cow::Borrowed(&data[..])).unwrap();
+        let end = root.get_header().unwrap().root().end() as usize;
+        let file = File::from_bytes_lossy(Cow::Borrowed(&data[..end])).unwrap();
+        assert!(file.keys().is_empty());
+        assert_eq!(file.skipped().len(), 4);
+        assert!(file.skipped().iter().all(|skipped| skipped.key().is_none()));
+        assert!(File::from_bytes(Cow::Borrowed(&data[..end]))
+            .unwrap()
+            .hash_table()
+            .unwrap()
+            .keys()
+            .is_err());
+
+        // The hash table itself is cut off
+        assert!(File::from_bytes_lossy(Cow::Borrowed(&data[..end - 1])).is_err());
+
+        let file =
+            File::from_bytes_lossy(Cow::Owned(new_empty_file().data.all().unwrap().to_vec()))
+                .unwrap();
+        assert!(file.keys().is_empty());
+        assert!(file.into_file().hash_table().is_ok());
+    }
+}
@@ -0,0 +1,80 @@
+/// How the data of a memory mapped file will be accessed
+///
+/// This is passed to `madvise` on Unix and ignored on other platforms. See [`MmapOptions`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MmapAdvice {
+    /// No special treatment
+    #[default]
+    Normal,
+
+    /// The data will be accessed in random order, for example for many lookups. The kernel reads
+    /// ahead less data.
+    Random,
+
+    /// The data will be accessed in sequential order, for example to dump all values. The kernel
+    /// reads ahead more aggressively.
+    Sequential,
+
+    /// The data will be accessed soon. The kernel starts reading it in the background.
+    WillNeed,
+}
+
+#[cfg(unix)]
+impl From<MmapAdvice> for memmap2::Advice {
+    fn from(advice: MmapAdvice) -> Self {
+        match advice {
+            MmapAdvice::Normal => memmap2::Advice::Normal,
+            MmapAdvice::Random => memmap2::Advice::Random,
+            MmapAdvice::Sequential => memmap2::Advice::Sequential,
+            MmapAdvice::WillNeed => memmap2::Advice::WillNeed,
+        }
+    }
+}
+
+/// Options for [`File::from_file_mmap_with_options`](crate::read::File::from_file_mmap_with_options)
+///
+/// ```
+/// use gvdb::read::{MmapAdvice, MmapOptions};
+///
+/// let options = MmapOptions::new()
+///     .with_advice(MmapAdvice::Random)
+///     .with_populate(true);
+/// assert_eq!(options.advice(), MmapAdvice::Random);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MmapOptions {
+    advice: MmapAdvice,
+    populate: bool,
+}
+
+impl MmapOptions {
+    /// Create options that map the file without any advice
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advise the kernel how the data will be accessed
+    pub fn with_advice(mut self, advice: MmapAdvice) -> Self {
+        self.advice = advice;
+        self
+    }
+
+    /// Read the whole file into memory when it is mapped
+    ///
+    /// This uses `MAP_POPULATE` on Linux and is ignored on other platforms.
+    pub fn with_populate(mut self, populate: bool) -> Self {
+        self.populate = populate;
+        self
+    }
+
+    /// How the data of the file will be accessed
+    pub fn advice(&self) -> MmapAdvice {
+        self.advice
+    }
+
+    /// Whether the whole file is read into memory when it is mapped
+    pub fn populate(&self) -> bool {
+        self.populate
+    }
+}
@@ -1,36 +1,43 @@
+use zerocopy::little_endian::U32;
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
+
 #[repr(C)]
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, FromBytes, IntoBytes, Immutable, KnownLayout, Unaligned)]
 pub struct Pointer {
-    start: u32,
-    end: u32,
+    start: U32,
+    end: U32,
 }
 
 impl Pointer {
     #[allow(unused)]
-    pub(crate) const NULL: Self = Self { start: 0, end: 0 };
+    pub(crate) const NULL: Self = Self {
+        start: U32::ZERO,
+        end: U32::ZERO,
+    };
 
     pub fn new(start: usize, end: usize) -> Self {
         Self {
-            start: (start as u32).to_le(),
-            end: (end as u32).to_le(),
+            start: U32::new(start as u32),
+            end: U32::new(end as u32),
         }
     }
 
     pub fn start(&self) -> u32 {
-        u32::from_le(self.start)
+        self.start.get()
     }
 
     pub fn end(&self) -> u32 {
-        u32::from_le(self.end)
+        self.end.get()
     }
 
+    #[cfg(feature = "std")]
     pub fn size(&self) -> usize {
         self.end().saturating_sub(self.start()) as usize
     }
 }
 
-impl std::fmt::Debug for Pointer {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for Pointer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Pointer")
             .field("start", &self.start())
             .field("end", &self.end())
@@ -45,7 +52,7 @@ mod test {
     #[test]
     fn derives() {
         let pointer = Pointer::new(0, 2);
-        let pointer2 = pointer.clone();
+        let pointer2 = pointer;
         println!("{:?}", pointer2);
     }
 
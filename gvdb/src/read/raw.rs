@@ -0,0 +1,397 @@
+//! Low-level access to the binary structure of GVDB files
+//!
+//! This module is meant for tools that inspect the file format itself, e.g. to annotate a hex dump.
+//! All ranges are byte offsets into the file data returned by [`data`].
+//!
+//! ```
+//! # use gvdb::read::File;
+//! # use std::path::PathBuf;
+//! use gvdb::read::raw;
+//!
+//! let file = File::from_file(&PathBuf::from("test-data/test2.gvdb")).unwrap();
+//! for chunk in raw::chunks(&file).unwrap() {
+//!     println!("{:?} {:?} {:?}", chunk.range(), chunk.kind(), chunk.key());
+//! }
+//! ```
+
+use crate::read::error::Result;
+use crate::read::{File, HashHeader, HashItem, HashItemType, HashTable, Header, Pointer};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::mem::size_of;
+use std::ops::Range;
+
+/// The byte range of `pointer`
+fn range(pointer: &Pointer) -> Range<usize> {
+    pointer.start() as usize..pointer.end() as usize
+}
+
+/// The complete data of `file`
+///
+/// Files opened with [`File::from_reader`] are read completely.
+pub fn data<'a>(file: &'a File) -> Result<&'a [u8]> {
+    file.data.all()
+}
+
+/// The fields of the file header
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawHeader {
+    signature: [u32; 2],
+    version: u32,
+    options: u32,
+    root: Range<usize>,
+}
+
+impl RawHeader {
+    /// The signature, read as little endian
+    ///
+    /// This is `"GVariant"` in the byte order of the file.
+    pub fn signature(&self) -> [u32; 2] {
+        self.signature
+    }
+
+    /// The version of the file format
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// The `options` field, which is unused by GLib and may contain a checksum
+    pub fn options(&self) -> u32 {
+        self.options
+    }
+
+    /// The range of the root hash table
+    pub fn root(&self) -> Range<usize> {
+        self.root.clone()
+    }
+}
+
+/// Read the header of `file`
+pub fn header(file: &File) -> Result<RawHeader> {
+    let header = file.get_header()?;
+
+    Ok(RawHeader {
+        signature: header.signature(),
+        version: header.version(),
+        options: header.checksum(),
+        root: range(header.root()),
+    })
+}
+
+/// The regions of a hash table
+///
+/// A hash table consists of a header, an optional bloom filter, the hash buckets and the hash
+/// items, stored directly after each other.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HashTableRegions {
+    header: Range<usize>,
+    bloom_words: Range<usize>,
+    buckets: Range<usize>,
+    items: Range<usize>,
+    bloom_shift: u32,
+    n_buckets: u32,
+}
+
+impl HashTableRegions {
+    /// Calculate the regions of `table`
+    pub fn new(table: &HashTable) -> Self {
+        let start = table.pointer.start() as usize;
+        let header_end = start + size_of::<HashHeader>();
+        let bloom_words_end = header_end + table.header.bloom_words_len();
+        let buckets_end = bloom_words_end + table.header.buckets_len();
+
+        Self {
+            header: start..header_end,
+            bloom_words: header_end..bloom_words_end,
+            buckets: bloom_words_end..buckets_end,
+            items: buckets_end..table.pointer.end() as usize,
+            bloom_shift: table.header.bloom_shift(),
+            n_buckets: table.header.n_buckets(),
+        }
+    }
+
+    /// The range of the hash table header
+    pub fn header(&self) -> Range<usize> {
+        self.header.clone()
+    }
+
+    /// The range of the bloom filter words, empty if the hash table has no bloom filter
+    pub fn bloom_words(&self) -> Range<usize> {
+        self.bloom_words.clone()
+    }
+
+    /// The range of the hash buckets
+    pub fn buckets(&self) -> Range<usize> {
+        self.buckets.clone()
+    }
+
+    /// The range of the hash items
+    pub fn items(&self) -> Range<usize> {
+        self.items.clone()
+    }
+
+    /// The bloom shift stored in the hash table header
+    pub fn bloom_shift(&self) -> u32 {
+        self.bloom_shift
+    }
+
+    /// The number of bloom filter words
+    pub fn n_bloom_words(&self) -> usize {
+        self.bloom_words.len() / size_of::<u32>()
+    }
+
+    /// The number of hash buckets
+    pub fn n_buckets(&self) -> u32 {
+        self.n_buckets
+    }
+
+    /// The number of hash items
+    pub fn n_items(&self) -> usize {
+        self.items.len() / size_of::<HashItem>()
+    }
+}
+
+/// The kind of a [`Chunk`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum ChunkKind {
+    /// The file header
+    Header,
+
+    /// The header of a hash table
+    HashTableHeader,
+
+    /// The bloom filter words of a hash table
+    BloomWords,
+
+    /// The hash buckets of a hash table
+    HashBuckets,
+
+    /// The hash items of a hash table
+    HashItems,
+
+    /// The part of a key that is not shared with the parent item
+    Key,
+
+    /// The serialized data of a value item
+    Value,
+
+    /// The list of child item indices of a container item
+    Container,
+}
+
+impl Display for ChunkKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            ChunkKind::Header => "header",
+            ChunkKind::HashTableHeader => "hash table header",
+            ChunkKind::BloomWords => "bloom words",
+            ChunkKind::HashBuckets => "hash buckets",
+            ChunkKind::HashItems => "hash items",
+            ChunkKind::Key => "key",
+            ChunkKind::Value => "value",
+            ChunkKind::Container => "container",
+        };
+
+        write!(f, "{}", text)
+    }
+}
+
+/// A region of a GVDB file that is reachable from the file header
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chunk {
+    kind: ChunkKind,
+    range: Range<usize>,
+    key: Option<String>,
+}
+
+impl Chunk {
+    /// The kind of data in this chunk
+    pub fn kind(&self) -> ChunkKind {
+        self.kind
+    }
+
+    /// The byte range of this chunk
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// The full key of the item this chunk belongs to, in the hash table that contains the item
+    ///
+    /// For the regions of a nested hash table this is the key of the hash table item in its parent
+    /// table. `None` for the file header and the regions of the root hash table.
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+}
+
+/// An iterator over all chunks of a file, see [`chunks`]
+#[derive(Debug)]
+pub struct Chunks {
+    inner: std::vec::IntoIter<Chunk>,
+}
+
+impl Iterator for Chunks {
+    type Item = Chunk;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Chunks {}
+
+/// Collect all chunks of `file` that are reachable from the file header, sorted by offset
+///
+/// The whole file is traversed, including nested hash tables. Empty regions like a missing bloom
+/// filter are skipped. Chunks that are referenced by more than one item, e.g. in files written
+/// with [`FileWriter::with_deduplication`](crate::write::FileWriter::with_deduplication), are
+/// returned once for the first item that references them. The space between chunks is padding.
+pub fn chunks(file: &File) -> Result<Chunks> {
+    let header = file.get_header()?;
+    let mut chunks = vec![Chunk {
+        kind: ChunkKind::Header,
+        range: 0..size_of::<Header>(),
+        key: None,
+    }];
+
+    let mut visited = HashSet::new();
+    let mut pending = vec![(*header.root(), 0, None)];
+    while let Some((pointer, depth, key)) = pending.pop() {
+        // Tables that are referenced more than once are only visited once
+        if !visited.insert(pointer.start()) {
+            continue;
+        }
+
+        let table = HashTable::for_bytes(pointer, file, depth)?;
+        let regions = HashTableRegions::new(&table);
+        for (kind, range) in [
+            (ChunkKind::HashTableHeader, regions.header()),
+            (ChunkKind::BloomWords, regions.bloom_words()),
+            (ChunkKind::HashBuckets, regions.buckets()),
+            (ChunkKind::HashItems, regions.items()),
+        ] {
+            chunks.push(Chunk {
+                kind,
+                range,
+                key: key.clone(),
+            });
+        }
+
+        let keys = table.keys()?;
+        for (index, key) in keys.into_iter().enumerate() {
+            let item = table.get_hash_item_for_index(index)?;
+            chunks.push(Chunk {
+                kind: ChunkKind::Key,
+                range: range(&item.key_ptr()),
+                key: Some(key.clone()),
+            });
+
+            let kind = match item.typ()? {
+                HashItemType::Value => ChunkKind::Value,
+                HashItemType::Container => ChunkKind::Container,
+                HashItemType::HashTable => {
+                    pending.push((*item.value_ptr(), depth + 1, Some(key)));
+                    continue;
+                }
+            };
+
+            chunks.push(Chunk {
+                kind,
+                range: range(item.value_ptr()),
+                key: Some(key),
+            });
+        }
+    }
+
+    chunks.retain(|chunk| !chunk.range.is_empty());
+    chunks.sort_by_key(|chunk| (chunk.range.start, chunk.range.end, chunk.kind));
+    chunks.dedup_by(|a, b| a.range == b.range && a.kind == b.kind);
+
+    Ok(Chunks {
+        inner: chunks.into_iter(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::write::{FileWriter, HashTableBuilder};
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_file_2() {
+        let file = File::from_file(&TEST_FILE_2).unwrap();
+        let header = header(&file).unwrap();
+        assert_eq!(header.version(), 0);
+        assert_eq!(header.options(), 0);
+        assert_eq!(header.signature(), file.get_header().unwrap().signature());
+
+        let regions = HashTableRegions::new(&file.hash_table().unwrap());
+        assert_eq!(regions.header().start, header.root().start);
+        assert_eq!(regions.items().end, header.root().end);
+        assert_eq!(regions.n_items(), 2);
+        assert_eq!(regions.n_buckets(), 2);
+        assert_eq!(regions.n_bloom_words(), 0);
+
+        let chunks: Vec<Chunk> = chunks(&file).unwrap().collect();
+        assert_eq!(chunks[0].kind(), ChunkKind::Header);
+        assert_eq!(chunks[0].range(), 0..24);
+
+        // Chunks are sorted and don't overlap
+        for pair in chunks.windows(2) {
+            assert!(pair[0].range().end <= pair[1].range().start);
+        }
+        assert!(chunks.last().unwrap().range().end <= data(&file).unwrap().len());
+
+        let keys: Vec<(&str, ChunkKind)> = chunks
+            .iter()
+            .filter(|chunk| matches!(chunk.kind(), ChunkKind::Key | ChunkKind::Value))
+            .map(|chunk| (chunk.key().unwrap(), chunk.kind()))
+            .collect();
+        assert_eq!(keys.len(), 5);
+        for key in ["string", "table", "int"] {
+            assert!(keys.contains(&(key, ChunkKind::Key)), "{}", key);
+        }
+        assert!(keys.contains(&("string", ChunkKind::Value)));
+        assert!(keys.contains(&("int", ChunkKind::Value)));
+
+        let nested = chunks
+            .iter()
+            .filter(|chunk| chunk.kind() == ChunkKind::HashItems)
+            .filter_map(|chunk| chunk.key())
+            .collect::<Vec<_>>();
+        assert_eq!(nested, ["table"]);
+    }
+
+    #[test]
+    fn shared_chunks() {
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert_string("a", "value").unwrap();
+        table_builder.insert_string("b", "value").unwrap();
+        let data = FileWriter::new()
+            .with_bloom_words(1)
+            .with_deduplication(true)
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+
+        let kinds: Vec<ChunkKind> = chunks(&file).unwrap().map(|chunk| chunk.kind()).collect();
+        assert_eq!(
+            kinds
+                .iter()
+                .filter(|kind| **kind == ChunkKind::Value)
+                .count(),
+            1
+        );
+        assert!(kinds.contains(&ChunkKind::BloomWords));
+        assert_eq!(format!("{}", ChunkKind::BloomWords), "bloom words");
+    }
+}
@@ -0,0 +1,510 @@
+use crate::read::error::{Error, Result};
+use crate::read::{HashHeader, HashItem, HashItemType, Header, Pointer};
+use crate::util::djb_hash;
+use crate::Endian;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::mem::size_of;
+use zerocopy::FromBytes;
+
+/// A GVDB file in a byte slice that is read with `core` and `alloc` only
+///
+/// This is the reader that is available without the `std` feature, e.g. to read a GResource
+/// bundle that is embedded in a firmware image. It looks up the raw data of items in hash tables
+/// and doesn't decode values, checksums or resource limits like the `File` type of the `std`
+/// feature.
+///
+/// ```
+/// use gvdb::read::{HashItemType, SliceFile};
+/// use gvdb::Endian;
+///
+/// let data = std::fs::read("test-data/test2.gvdb").unwrap();
+/// let file = SliceFile::from_bytes(&data).unwrap();
+/// assert_eq!(file.endianness(), Endian::Big);
+/// let table = file.hash_table().unwrap();
+///
+/// let (typ, _) = table.get_raw("string").unwrap();
+/// assert_eq!(typ, HashItemType::Value);
+/// let (data, signature) = table.get_value("string").unwrap();
+/// assert_eq!(signature, "s");
+/// assert_eq!(data, b"test string\0");
+///
+/// let nested = table.get_hash_table("table").unwrap();
+/// let (data, signature) = nested.get_value("int").unwrap();
+/// assert_eq!(signature, "u");
+/// assert_eq!(data, 42u32.to_be_bytes());
+/// ```
+#[derive(Copy, Clone)]
+pub struct SliceFile<'a> {
+    data: &'a [u8],
+    byteswapped: bool,
+}
+
+impl<'a> SliceFile<'a> {
+    /// Interpret `data` as a GVDB file
+    ///
+    /// Returns [`Error::InvalidHeader`] or [`Error::UnsupportedVersion`] if `data` doesn't start
+    /// with the header of a GVDB file.
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self> {
+        let header_data = data.get(0..size_of::<Header>()).ok_or(Error::DataOffset)?;
+        let header = Header::read_from_bytes(header_data)?;
+        let byteswapped = header.is_byteswap()?;
+
+        if header.version() != 0 {
+            return Err(Error::UnsupportedVersion {
+                found: header.version(),
+            });
+        }
+
+        Ok(Self { data, byteswapped })
+    }
+
+    /// The byte order of the values in the file
+    pub fn endianness(&self) -> Endian {
+        Endian::from_byteswapped(self.byteswapped)
+    }
+
+    /// Returns the root hash table of the file
+    pub fn hash_table(&self) -> Result<SliceHashTable<'a>> {
+        let header = Header::read_from_bytes(&self.data[0..size_of::<Header>()])?;
+        SliceHashTable::for_pointer(*self, header.root())
+    }
+
+    /// The data at `pointer`, which has to start at a multiple of `alignment`
+    fn dereference(&self, pointer: &Pointer, alignment: u32) -> Result<&'a [u8]> {
+        let start = pointer.start() as usize;
+        let end = pointer.end() as usize;
+
+        if start & (alignment as usize - 1) != 0 {
+            return Err(Error::UnalignedPointer {
+                start: pointer.start(),
+                alignment,
+            });
+        }
+
+        self.data.get(start..end).ok_or(Error::PointerOutOfBounds {
+            start: pointer.start(),
+            end: pointer.end(),
+            len: self.data.len(),
+        })
+    }
+}
+
+impl Debug for SliceFile<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SliceFile")
+            .field("len", &self.data.len())
+            .field("endianness", &self.endianness())
+            .finish()
+    }
+}
+
+/// A hash table inside a [`SliceFile`]
+///
+/// Keys are looked up in the hash buckets like in GLib, the data of the items is returned
+/// without decoding it.
+#[derive(Copy, Clone)]
+pub struct SliceHashTable<'a> {
+    file: SliceFile<'a>,
+    header: HashHeader,
+    data: &'a [u8],
+}
+
+impl<'a> SliceHashTable<'a> {
+    /// Interpret the data at `pointer` in `file` as a hash table
+    fn for_pointer(file: SliceFile<'a>, pointer: &Pointer) -> Result<Self> {
+        let data = file.dereference(pointer, 4)?;
+        let header_data = data
+            .get(0..size_of::<HashHeader>())
+            .ok_or(Error::DataOffset)?;
+        let header = HashHeader::read_from_bytes(header_data)?;
+
+        let items_offset =
+            size_of::<HashHeader>() + header.bloom_words_len() + header.buckets_len();
+        if items_offset > data.len() {
+            return Err(Error::Data(format!(
+                "Not enough bytes to fit hash table: Expected at least {} bytes, got {}",
+                items_offset,
+                data.len()
+            )));
+        } else if (data.len() - items_offset) % size_of::<HashItem>() != 0 {
+            return Err(Error::Data(format!(
+                "Remaining size invalid: Expected a multiple of {}, got {}",
+                size_of::<HashItem>(),
+                data.len() - items_offset
+            )));
+        }
+
+        Ok(Self { file, header, data })
+    }
+
+    /// Read the [`u32`] at `offset` in the hash table
+    fn read_u32(&self, offset: usize) -> Result<u32> {
+        let bytes = self
+            .data
+            .get(offset..offset + size_of::<u32>())
+            .ok_or(Error::DataOffset)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// The offset of the hash buckets
+    fn buckets_offset(&self) -> usize {
+        size_of::<HashHeader>() + self.header.bloom_words_len()
+    }
+
+    /// The offset of the hash items
+    fn items_offset(&self) -> usize {
+        self.buckets_offset() + self.header.buckets_len()
+    }
+
+    /// The hash item at `index`
+    fn item(&self, index: usize) -> Result<HashItem> {
+        let start = self.items_offset() + index * size_of::<HashItem>();
+        let data = self
+            .data
+            .get(start..start + size_of::<HashItem>())
+            .ok_or(Error::DataOffset)?;
+        Ok(HashItem::read_from_bytes(data)?)
+    }
+
+    /// The part of the key that is stored in `item`
+    fn key_for_item(&self, item: &HashItem) -> Result<&'a str> {
+        let data = self.file.dereference(&item.key_ptr(), 1)?;
+        Ok(core::str::from_utf8(data)?)
+    }
+
+    /// Check whether `hash_value` may be in the hash table according to the bloom filter
+    fn bloom_filter(&self, hash_value: u32) -> Result<bool> {
+        let n_bloom_words = self.header.n_bloom_words();
+        if n_bloom_words == 0 {
+            return Ok(true);
+        }
+
+        let word = (hash_value / 32) % n_bloom_words;
+        let mut mask = 1 << (hash_value & 31);
+        mask |= 1 << ((hash_value >> self.header.bloom_shift()) & 31);
+
+        let bloom_word =
+            self.read_u32(size_of::<HashHeader>() + word as usize * size_of::<u32>())?;
+        Ok(bloom_word & mask == mask)
+    }
+
+    /// Walk through the parents of `item` and check whether its full key is `key`
+    ///
+    /// A valid chain of parents visits every item at most once, so items with a parent loop are
+    /// rejected after visiting all items instead of looping forever.
+    fn check_key(&self, item: &HashItem, key: &str) -> Result<bool> {
+        let mut item = *item;
+        let mut key = key;
+        for _ in 0..=self.len() {
+            let this_key = self.key_for_item(&item)?;
+            if !key.ends_with(this_key) {
+                return Ok(false);
+            }
+
+            let parent = item.parent();
+            if key.len() == this_key.len() && parent == u32::MAX {
+                return Ok(true);
+            }
+
+            if parent >= self.len() as u32 || key.is_empty() {
+                return Ok(false);
+            }
+
+            item = self.item(parent as usize)?;
+            key = &key[..key.len() - this_key.len()];
+        }
+
+        Ok(false)
+    }
+
+    /// The item with the full key `key`
+    fn find_item(&self, key: &str) -> Result<HashItem> {
+        let hash_value = djb_hash(key);
+        let n_buckets = self.header.n_buckets();
+        if n_buckets == 0 || self.is_empty() || !self.bloom_filter(hash_value)? {
+            return Err(Error::KeyNotFound(key.to_string()));
+        }
+
+        let bucket = hash_value % n_buckets;
+        let first = self.read_u32(self.buckets_offset() + bucket as usize * size_of::<u32>())?;
+        let last = if bucket == n_buckets - 1 {
+            self.len()
+        } else {
+            let next =
+                self.read_u32(self.buckets_offset() + (bucket as usize + 1) * size_of::<u32>())?;
+            (next as usize).min(self.len())
+        };
+
+        for index in first as usize..last {
+            let item = self.item(index)?;
+            if item.hash_value() == hash_value && self.check_key(&item, key)? {
+                return Ok(item);
+            }
+        }
+
+        Err(Error::KeyNotFound(key.to_string()))
+    }
+
+    /// The number of items in the hash table
+    pub fn len(&self) -> usize {
+        (self.data.len() - self.items_offset()) / size_of::<HashItem>()
+    }
+
+    /// Whether the hash table contains no items
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the full keys of all items, ordered by item index
+    pub fn keys(&self) -> Result<Vec<String>> {
+        let n_items = self.len();
+        let mut keys: Vec<Option<String>> = vec![None; n_items];
+        for index in 0..n_items {
+            // Walk up to the first resolved parent or the root, a valid chain visits every item once
+            let mut chain = Vec::new();
+            let mut current = index;
+            while keys[current].is_none() {
+                if chain.len() >= n_items {
+                    return Err(Error::Data(
+                        "Error finding all parent items. The file appears to have a loop"
+                            .to_string(),
+                    ));
+                }
+
+                chain.push(current);
+                let parent = self.item(current)?.parent();
+                if parent == u32::MAX {
+                    break;
+                } else if parent as usize >= n_items {
+                    return Err(Error::Data(format!(
+                        "Parent with invalid offset encountered: {}",
+                        parent
+                    )));
+                }
+                current = parent as usize;
+            }
+
+            for &index in chain.iter().rev() {
+                let item = self.item(index)?;
+                let name = self.key_for_item(&item)?;
+                let key = match item.parent() {
+                    u32::MAX => name.to_string(),
+                    parent => format!("{}{}", keys[parent as usize].as_deref().unwrap_or(""), name),
+                };
+                keys[index] = Some(key);
+            }
+        }
+
+        Ok(keys.into_iter().map(Option::unwrap_or_default).collect())
+    }
+
+    /// Returns the type and the raw bytes of the item at `key`, without decoding them
+    ///
+    /// For [`HashItemType::Value`] this is the serialized GVariant of type `v` in the byte order
+    /// of the file. For [`HashItemType::HashTable`] it is the nested hash table and for
+    /// [`HashItemType::Container`] the list of `u32` indices of the child items.
+    pub fn get_raw(&self, key: &str) -> Result<(HashItemType, &'a [u8])> {
+        let item = self.find_item(key)?;
+        let typ = item.typ()?;
+        let alignment = match typ {
+            HashItemType::Value => 8,
+            HashItemType::HashTable | HashItemType::Container => 4,
+        };
+
+        Ok((typ, self.file.dereference(item.value_ptr(), alignment)?))
+    }
+
+    /// Returns the serialized data and the type signature of the value at `key`
+    ///
+    /// The data is in the byte order of the file, see [`SliceFile::endianness`].
+    pub fn get_value(&self, key: &str) -> Result<(&'a [u8], &'a str)> {
+        let item = self.find_item_of_type(key, HashItemType::Value)?;
+        let data = self.file.dereference(item.value_ptr(), 8)?;
+
+        // The type signature is appended after a zero byte and never contains a zero byte itself
+        let separator = data
+            .iter()
+            .rposition(|byte| *byte == 0)
+            .filter(|separator| separator + 1 < data.len())
+            .ok_or_else(|| Error::Data("Variant data is missing its type signature".to_string()))?;
+
+        Ok((
+            &data[..separator],
+            core::str::from_utf8(&data[separator + 1..])?,
+        ))
+    }
+
+    /// Returns the nested hash table at `key`
+    pub fn get_hash_table(&self, key: &str) -> Result<SliceHashTable<'a>> {
+        let item = self.find_item_of_type(key, HashItemType::HashTable)?;
+        Self::for_pointer(self.file, item.value_ptr())
+    }
+
+    /// The item with the full key `key`, which has to be of type `expected`
+    fn find_item_of_type(&self, key: &str, expected: HashItemType) -> Result<HashItem> {
+        let item = self.find_item(key)?;
+        let found = item.typ()?;
+        if found == expected {
+            Ok(item)
+        } else {
+            Err(Error::WrongItemType {
+                expected,
+                found,
+                key: key.to_string(),
+            })
+        }
+    }
+}
+
+impl Debug for SliceHashTable<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SliceHashTable")
+            .field("header", &self.header)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::{File, HashTable};
+    use crate::test::*;
+    use crate::write::{FileWriter, HashTableBuilder};
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+
+    /// Check that `slice` contains the same items as `table`
+    fn assert_same_items(slice: &SliceHashTable, table: &HashTable) {
+        assert_eq!(slice.len(), table.len());
+        let keys = slice.keys().unwrap();
+        assert_eq!(keys, table.keys().unwrap());
+        for key in keys {
+            let (typ, data) = slice.get_raw(&key).unwrap();
+            assert_eq!((typ, data), table.get_raw(&key).unwrap());
+            if typ == HashItemType::HashTable {
+                assert_same_items(
+                    &slice.get_hash_table(&key).unwrap(),
+                    &table.get_hash_table(&key).unwrap(),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_files() {
+        for path in [&*TEST_FILE_1, &*TEST_FILE_2, &*TEST_FILE_3] {
+            let data = std::fs::read(path).unwrap();
+            let slice = SliceFile::from_bytes(&data).unwrap();
+            let file = File::from_bytes(Cow::Borrowed(&data)).unwrap();
+            assert_eq!(slice.endianness(), file.endianness());
+            assert_same_items(&slice.hash_table().unwrap(), &file.hash_table().unwrap());
+        }
+    }
+
+    #[test]
+    fn values() {
+        for endian in [Endian::Little, Endian::Big] {
+            let mut table_builder = HashTableBuilder::new();
+            table_builder.insert("int", 5u32).unwrap();
+            table_builder.insert("/a/b", "abc").unwrap();
+            let data = FileWriter::endian(endian)
+                .with_bloom_words(4)
+                .write_to_vec_with_table(table_builder)
+                .unwrap();
+
+            let file = SliceFile::from_bytes(&data).unwrap();
+            assert_eq!(file.endianness(), endian);
+            let table = file.hash_table().unwrap();
+            assert_eq!(table.len(), 4);
+            assert!(!table.is_empty());
+
+            let int = match endian {
+                Endian::Little => 5u32.to_le_bytes(),
+                Endian::Big => 5u32.to_be_bytes(),
+            };
+            assert_eq!(table.get_value("int").unwrap(), (&int[..], "u"));
+            assert_eq!(table.get_value("/a/b").unwrap(), (&b"abc\0"[..], "s"));
+            assert_eq!(table.get_raw("/a/").unwrap().0, HashItemType::Container);
+
+            assert_matches!(table.get_value("missing"), Err(Error::KeyNotFound(key)) if key == "missing");
+            assert_matches!(table.get_raw("/a/c"), Err(Error::KeyNotFound(_)));
+            assert_matches!(
+                table.get_value("/a/"),
+                Err(Error::WrongItemType {
+                    expected: HashItemType::Value,
+                    found: HashItemType::Container,
+                    ..
+                })
+            );
+            assert_matches!(
+                table.get_hash_table("int"),
+                Err(Error::WrongItemType {
+                    expected: HashItemType::HashTable,
+                    ..
+                })
+            );
+        }
+
+        let data = FileWriter::new()
+            .write_to_vec_with_table(HashTableBuilder::new())
+            .unwrap();
+        let table = SliceFile::from_bytes(&data).unwrap().hash_table().unwrap();
+        assert!(table.is_empty());
+        assert_eq!(table.keys().unwrap(), Vec::<String>::new());
+        assert_matches!(table.get_raw("a"), Err(Error::KeyNotFound(_)));
+    }
+
+    #[test]
+    fn errors() {
+        assert_matches!(SliceFile::from_bytes(b""), Err(Error::DataOffset));
+        assert_matches!(
+            SliceFile::from_bytes(&[0; 24]),
+            Err(Error::InvalidHeader { .. })
+        );
+
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert("a/b", 1u32).unwrap();
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+
+        let mut version = data.clone();
+        version[8] = 1;
+        assert_matches!(
+            SliceFile::from_bytes(&version),
+            Err(Error::UnsupportedVersion { found: 1 })
+        );
+
+        let truncated = &data[..32];
+        let file = SliceFile::from_bytes(truncated).unwrap();
+        assert_matches!(file.hash_table(), Err(Error::PointerOutOfBounds { .. }));
+
+        // Let the container "a/" point to its child "a/b" as its parent
+        let file = File::from_bytes(Cow::Borrowed(&data)).unwrap();
+        let table = file.hash_table().unwrap();
+        let keys = table.keys().unwrap();
+        let item_offset = |key: &str| {
+            table.pointer.start() as usize
+                + table.hash_items_offset()
+                + size_of::<HashItem>() * keys.iter().position(|k| k == key).unwrap()
+        };
+        let parent = keys.iter().position(|key| key == "a/b").unwrap() as u32;
+        let mut looped = data.clone();
+        let offset = item_offset("a/") + 4;
+        looped[offset..offset + 4].copy_from_slice(&parent.to_le_bytes());
+
+        let table = SliceFile::from_bytes(&looped)
+            .unwrap()
+            .hash_table()
+            .unwrap();
+        assert_matches!(table.keys(), Err(Error::Data(_)));
+        assert_matches!(table.get_raw("a/b"), Err(Error::KeyNotFound(_)));
+    }
+}
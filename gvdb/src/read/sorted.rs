@@ -0,0 +1,120 @@
+use crate::read::error::Result;
+use crate::read::HashTable;
+
+/// The full keys of a [`HashTable`], sorted for binary search
+///
+/// Created by [`HashTable::sorted_keys`], or lazily by
+/// [`CachedTable::sorted_keys`](crate::read::CachedTable::sorted_keys) for repeated use. The keys
+/// are sorted by their bytes, so all keys with a common prefix are stored next to each other.
+///
+/// ```
+/// # use std::path::PathBuf;
+/// use gvdb::read::File;
+///
+/// let file = File::from_file(&PathBuf::from("test-data/test3.gresource")).unwrap();
+/// let sorted = file.hash_table().unwrap().sorted_keys().unwrap();
+/// assert!(sorted.contains("/gvdb/rs/test/test.css"));
+/// assert_eq!(
+///     sorted.with_prefix("/gvdb/rs/test/json/"),
+///     ["/gvdb/rs/test/json/", "/gvdb/rs/test/json/test.json"]
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SortedKeys {
+    keys: Vec<String>,
+}
+
+impl SortedKeys {
+    pub(crate) fn new(mut keys: Vec<String>) -> Self {
+        keys.sort_unstable();
+        Self { keys }
+    }
+
+    /// All keys in sorted order
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Return the sorted keys
+    pub fn into_keys(self) -> Vec<String> {
+        self.keys
+    }
+
+    /// The number of keys
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether there are no keys
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Whether `key` is one of the keys
+    pub fn contains(&self, key: &str) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// The position of `key` in [`SortedKeys::keys`]
+    pub fn position(&self, key: &str) -> Option<usize> {
+        self.keys.binary_search_by(|k| k.as_str().cmp(key)).ok()
+    }
+
+    /// All keys that start with `prefix`, in sorted order
+    pub fn with_prefix(&self, prefix: &str) -> &[String] {
+        let start = self.keys.partition_point(|key| key.as_str() < prefix);
+        let len = self.keys[start..].partition_point(|key| key.starts_with(prefix));
+        &self.keys[start..start + len]
+    }
+}
+
+impl<'a, 'file> HashTable<'a, 'file> {
+    /// Gets the full keys of all items, sorted
+    ///
+    /// Equivalent to sorting the result of [`HashTable::keys`]. See [`HashTable::sorted_keys`] to
+    /// search the keys.
+    pub fn keys_sorted(&self) -> Result<Vec<String>> {
+        Ok(self.sorted_keys()?.into_keys())
+    }
+
+    /// Gets the full keys of all items as [`SortedKeys`] for binary search
+    pub fn sorted_keys(&self) -> Result<SortedKeys> {
+        Ok(SortedKeys::new(self.keys()?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::File;
+    use crate::test::*;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    #[test]
+    fn sorted_keys() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let table = file.hash_table().unwrap();
+
+        let mut keys = table.keys().unwrap();
+        keys.sort();
+        assert_eq!(table.keys_sorted().unwrap(), keys);
+
+        let sorted = table.sorted_keys().unwrap();
+        assert_eq!(sorted.keys(), keys);
+        assert_eq!(sorted.len(), 12);
+        assert!(!sorted.is_empty());
+        assert_eq!(sorted.position("/"), Some(0));
+        assert!(sorted.contains("/gvdb/rs/test/json/test.json"));
+        assert!(!sorted.contains("/gvdb/rs/test/json"));
+        assert_eq!(sorted.with_prefix("/gvdb/rs/test/icons/").len(), 4);
+        assert_eq!(sorted.with_prefix("").len(), 12);
+        assert!(sorted.with_prefix("/missing").is_empty());
+        assert!(sorted.with_prefix("~").is_empty());
+
+        let file = File::from_file(&TEST_FILE_2).unwrap();
+        let sorted = file.hash_table().unwrap().sorted_keys().unwrap();
+        assert_eq!(sorted.into_keys(), ["string", "table"]);
+        assert!(SortedKeys::default().with_prefix("a").is_empty());
+    }
+}
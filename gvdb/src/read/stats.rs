@@ -0,0 +1,366 @@
+use crate::read::error::Result;
+use crate::read::{File, HashItemType, HashTable, Header};
+use std::cmp::min;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::mem::size_of;
+
+/// Size and layout statistics of a GVDB file
+///
+/// Returned by [`File::stats`]. All sizes are in bytes. Nested hash tables are included.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileStats {
+    file_size: usize,
+    n_hash_tables: usize,
+    n_values: usize,
+    n_containers: usize,
+    n_keys: usize,
+    key_bytes: usize,
+    value_bytes: usize,
+    container_bytes: usize,
+    hash_table_bytes: usize,
+    n_bloom_filters: usize,
+    bloom_filter_bytes: usize,
+    bucket_histogram: Vec<usize>,
+}
+
+impl FileStats {
+    /// The size of the file
+    pub fn file_size(&self) -> usize {
+        self.file_size
+    }
+
+    /// The number of chunks in the file: hash tables, values, containers and keys
+    pub fn n_chunks(&self) -> usize {
+        self.n_hash_tables + self.n_values + self.n_containers + self.n_keys
+    }
+
+    /// The number of hash tables, including the root hash table
+    pub fn n_hash_tables(&self) -> usize {
+        self.n_hash_tables
+    }
+
+    /// The number of value items
+    pub fn n_values(&self) -> usize {
+        self.n_values
+    }
+
+    /// The number of container items
+    pub fn n_containers(&self) -> usize {
+        self.n_containers
+    }
+
+    /// The number of non-empty keys
+    pub fn n_keys(&self) -> usize {
+        self.n_keys
+    }
+
+    /// The total size of all keys
+    pub fn key_bytes(&self) -> usize {
+        self.key_bytes
+    }
+
+    /// The total size of all serialized values
+    pub fn value_bytes(&self) -> usize {
+        self.value_bytes
+    }
+
+    /// The total size of all lists of container children
+    pub fn container_bytes(&self) -> usize {
+        self.container_bytes
+    }
+
+    /// The total size of all hash tables, including their headers, bloom filters, buckets and
+    /// items
+    pub fn hash_table_bytes(&self) -> usize {
+        self.hash_table_bytes
+    }
+
+    /// The number of hash tables with a bloom filter
+    pub fn n_bloom_filters(&self) -> usize {
+        self.n_bloom_filters
+    }
+
+    /// The total size of all bloom filters
+    pub fn bloom_filter_bytes(&self) -> usize {
+        self.bloom_filter_bytes
+    }
+
+    /// The bytes that are not part of any chunk or the file header, e.g. padding for alignment
+    pub fn padding_bytes(&self) -> usize {
+        self.file_size.saturating_sub(
+            size_of::<Header>()
+                + self.key_bytes
+                + self.value_bytes
+                + self.container_bytes
+                + self.hash_table_bytes,
+        )
+    }
+
+    /// The share of the padding bytes in the file size, from 0.0 to 1.0
+    pub fn padding_ratio(&self) -> f64 {
+        if self.file_size == 0 {
+            0.0
+        } else {
+            self.padding_bytes() as f64 / self.file_size as f64
+        }
+    }
+
+    /// The number of hash buckets by the number of items they contain
+    ///
+    /// `bucket_histogram()[n]` is the number of buckets that contain exactly `n` items.
+    pub fn bucket_histogram(&self) -> &[usize] {
+        &self.bucket_histogram
+    }
+
+    fn add_table(&mut self, table: &HashTable) -> Result<()> {
+        self.n_hash_tables += 1;
+        self.hash_table_bytes += table.pointer.size();
+
+        if table.header.n_bloom_words() > 0 {
+            self.n_bloom_filters += 1;
+            self.bloom_filter_bytes += table.header.bloom_words_len();
+        }
+
+        let n_items = table.n_hash_items();
+        let n_buckets = table.header.n_buckets() as usize;
+        for bucket in 0..n_buckets {
+            // Bucket offsets outside of the item section are clamped, like in lookups
+            let start = min(table.get_hash(bucket)? as usize, n_items);
+            let end = if bucket + 1 < n_buckets {
+                min(table.get_hash(bucket + 1)? as usize, n_items)
+            } else {
+                n_items
+            };
+
+            let count = end.saturating_sub(start);
+            if self.bucket_histogram.len() <= count {
+                self.bucket_histogram.resize(count + 1, 0);
+            }
+            self.bucket_histogram[count] += 1;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for FileStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "File size:       {}", self.file_size)?;
+        writeln!(f, "Chunks:          {}", self.n_chunks())?;
+        writeln!(
+            f,
+            "Hash tables:     {} ({} bytes, {} with bloom filter, {} bytes)",
+            self.n_hash_tables,
+            self.hash_table_bytes,
+            self.n_bloom_filters,
+            self.bloom_filter_bytes
+        )?;
+        writeln!(
+            f,
+            "Values:          {} ({} bytes)",
+            self.n_values, self.value_bytes
+        )?;
+        writeln!(
+            f,
+            "Containers:      {} ({} bytes)",
+            self.n_containers, self.container_bytes
+        )?;
+        writeln!(
+            f,
+            "Keys:            {} ({} bytes)",
+            self.n_keys, self.key_bytes
+        )?;
+        writeln!(
+            f,
+            "Padding:         {} ({:.1}%)",
+            self.padding_bytes(),
+            self.padding_ratio() * 100.0
+        )?;
+        write!(f, "Bucket sizes:   ")?;
+        for (n_items, n_buckets) in self.bucket_histogram.iter().enumerate() {
+            write!(f, " {}: {}", n_items, n_buckets)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl File<'_> {
+    /// Collect size and layout statistics of this file and all of its nested hash tables
+    ///
+    /// ```
+    /// # use gvdb::read::File;
+    /// # use std::path::PathBuf;
+    /// let file = File::from_file(&PathBuf::from("test-data/test2.gvdb")).unwrap();
+    /// let stats = file.stats().unwrap();
+    /// assert_eq!(stats.n_hash_tables(), 2);
+    /// println!("{}", stats);
+    /// ```
+    pub fn stats(&self) -> Result<FileStats> {
+        let mut stats = FileStats {
+            file_size: self.data.len(),
+            ..Default::default()
+        };
+
+        let mut visited = HashSet::new();
+        let mut pending = vec![(*self.get_header()?.root(), 0)];
+
+        while let Some((pointer, depth)) = pending.pop() {
+            // Tables that are referenced more than once are only counted once
+            if !visited.insert(pointer.start()) {
+                continue;
+            }
+
+            let table = HashTable::for_bytes(pointer, self, depth)?;
+            stats.add_table(&table)?;
+
+            for index in 0..table.n_hash_items() {
+                let item = table.get_hash_item_for_index(index)?;
+                if item.key_size() > 0 {
+                    stats.n_keys += 1;
+                    stats.key_bytes += item.key_size() as usize;
+                }
+
+                match item.typ()? {
+                    HashItemType::Value => {
+                        stats.n_values += 1;
+                        stats.value_bytes += item.value_ptr().size();
+                    }
+                    HashItemType::Container => {
+                        stats.n_containers += 1;
+                        stats.container_bytes += item.value_ptr().size();
+                    }
+                    HashItemType::HashTable => pending.push((*item.value_ptr(), depth + 1)),
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::{File, FileStats, Header};
+    use crate::test::*;
+    use crate::write::{FileWriter, HashTableBuilder};
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+    use std::mem::size_of;
+
+    #[test]
+    fn test_file_2() {
+        let file = File::from_file(&TEST_FILE_2).unwrap();
+        let stats = file.stats().unwrap();
+
+        assert_eq!(stats.file_size(), file.data.len());
+        assert_eq!(stats.n_hash_tables(), 2);
+        assert_eq!(stats.n_values(), 2);
+        assert_eq!(stats.n_containers(), 0);
+        assert_eq!(stats.n_keys(), 3);
+        assert_eq!(
+            stats.key_bytes(),
+            "string".len() + "table".len() + "int".len()
+        );
+        assert_eq!(stats.n_chunks(), 7);
+        // Every item is in exactly one bucket
+        assert_eq!(
+            stats
+                .bucket_histogram()
+                .iter()
+                .enumerate()
+                .map(|(n_items, n_buckets)| n_items * n_buckets)
+                .sum::<usize>(),
+            3
+        );
+        assert_eq!(
+            size_of::<Header>()
+                + stats.hash_table_bytes()
+                + stats.value_bytes()
+                + stats.key_bytes()
+                + stats.padding_bytes(),
+            stats.file_size()
+        );
+        assert!(format!("{}", stats).contains("Hash tables:     2"));
+    }
+
+    #[test]
+    fn invalid_buckets() {
+        let mut data = std::fs::read(&*TEST_FILE_2).unwrap();
+        let file = File::from_bytes(Cow::Borrowed(&data)).unwrap();
+        let table = file.hash_table().unwrap();
+        assert_eq!(table.header.n_buckets(), 2);
+        let offset =
+            table.pointer.start() as usize + table.hash_buckets_offset() + size_of::<u32>();
+
+        // The second bucket starts far after the end of the item section
+        data[offset..offset + 4].copy_from_slice(&0x40000000u32.to_le_bytes());
+        let file = File::from_bytes(Cow::Borrowed(&data)).unwrap();
+        let stats = file.stats().unwrap();
+        assert_eq!(stats.n_hash_tables(), 2);
+        assert_eq!(stats.bucket_histogram(), [1, 1, 1]);
+    }
+
+    #[test]
+    fn containers_and_buckets() {
+        let mut table_builder = HashTableBuilder::new().with_bucket_count(2);
+        for i in 0..10 {
+            table_builder
+                .insert(&format!("dir/{}", i), i as u32)
+                .unwrap();
+        }
+
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let stats = file.stats().unwrap();
+
+        assert_eq!(stats.n_hash_tables(), 1);
+        assert_eq!(stats.n_values(), 10);
+        assert_eq!(stats.n_containers(), 1);
+        assert_eq!(stats.container_bytes(), 10 * 4);
+        assert_eq!(stats.n_bloom_filters(), 0);
+        assert_eq!(stats.bucket_histogram().iter().sum::<usize>(), 2);
+        assert_eq!(
+            stats
+                .bucket_histogram()
+                .iter()
+                .enumerate()
+                .map(|(n_items, n_buckets)| n_items * n_buckets)
+                .sum::<usize>(),
+            11
+        );
+    }
+
+    #[test]
+    fn packed_layout() {
+        let table = || {
+            let mut table_builder = HashTableBuilder::new();
+            for i in 0..1000u32 {
+                table_builder.insert(&format!("key{}", i), i).unwrap();
+            }
+            table_builder
+        };
+
+        let data = FileWriter::new().write_to_vec_with_table(table()).unwrap();
+        let stats = File::from_bytes(Cow::Owned(data)).unwrap().stats().unwrap();
+        let packed = FileWriter::new()
+            .with_packed_layout(true)
+            .write_to_vec_with_table(table())
+            .unwrap();
+        let packed_stats = File::from_bytes(Cow::Owned(packed))
+            .unwrap()
+            .stats()
+            .unwrap();
+
+        assert_eq!(packed_stats.value_bytes(), stats.value_bytes());
+        assert_eq!(packed_stats.key_bytes(), stats.key_bytes());
+        assert!(packed_stats.padding_bytes() < stats.padding_bytes());
+        assert!(packed_stats.padding_ratio() < stats.padding_ratio());
+        assert!(format!("{}", packed_stats).contains("Padding:"));
+        assert_eq!(FileStats::default().padding_ratio(), 0.0);
+    }
+}
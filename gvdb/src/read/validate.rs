@@ -0,0 +1,597 @@
+use crate::read::{File, HashItem, HashItemType, HashTable, Pointer};
+use crate::util::djb_hash;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::mem::size_of;
+
+/// The category of a [`ValidationIssue`]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum ValidationIssueKind {
+    /// The file header is invalid
+    Header,
+
+    /// A pointer is out of bounds or not aligned correctly
+    Pointer,
+
+    /// The structure of a hash table is invalid, e.g. its size or the order of its buckets
+    HashTable,
+
+    /// A hash value does not match the key, or the key is missing from the bloom filter
+    Hash,
+
+    /// A key is not valid UTF-8 or is empty
+    Key,
+
+    /// A parent index is out of bounds, does not point to a container or forms a loop
+    Parent,
+
+    /// A value can't be parsed as GVariant
+    Value,
+
+    /// A container contains invalid child indices
+    Container,
+
+    /// More than one item of a hash table has the same key
+    DuplicateKey,
+}
+
+impl Display for ValidationIssueKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            ValidationIssueKind::Header => "Header",
+            ValidationIssueKind::Pointer => "Pointer",
+            ValidationIssueKind::HashTable => "HashTable",
+            ValidationIssueKind::Hash => "Hash",
+            ValidationIssueKind::Key => "Key",
+            ValidationIssueKind::Parent => "Parent",
+            ValidationIssueKind::Value => "Value",
+            ValidationIssueKind::Container => "Container",
+            ValidationIssueKind::DuplicateKey => "DuplicateKey",
+        };
+
+        write!(f, "{}", text)
+    }
+}
+
+/// A problem found by [`File::validate`]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ValidationIssue {
+    table: Vec<String>,
+    item: Option<usize>,
+    kind: ValidationIssueKind,
+    message: String,
+}
+
+impl ValidationIssue {
+    /// The keys of the nested hash tables leading to the hash table of this issue, starting at
+    /// the root hash table. Empty for the root hash table and the file header.
+    pub fn table(&self) -> &[String] {
+        &self.table
+    }
+
+    /// The index of the item in its hash table, if the issue concerns a single item
+    pub fn item(&self) -> Option<usize> {
+        self.item
+    }
+
+    /// The category of this issue
+    pub fn kind(&self) -> ValidationIssueKind {
+        self.kind
+    }
+
+    /// A description of this issue
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if !self.table.is_empty() {
+            write!(f, " in table '{}'", self.table.join("' > '"))?;
+        }
+        if let Some(item) = self.item {
+            write!(f, " at item {}", item)?;
+        }
+
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Collects the issues of a single hash table
+struct TableValidator<'t, 'a, 'file> {
+    table: &'t HashTable<'a, 'file>,
+    path: &'t [String],
+    issues: &'t mut Vec<ValidationIssue>,
+}
+
+impl<'t, 'a, 'file> TableValidator<'t, 'a, 'file> {
+    fn issue(&mut self, item: Option<usize>, kind: ValidationIssueKind, message: String) {
+        self.issues.push(ValidationIssue {
+            table: self.path.to_vec(),
+            item,
+            kind,
+            message,
+        });
+    }
+
+    fn validate_buckets(&mut self, n_items: usize) {
+        let n_buckets = self.table.header.n_buckets() as usize;
+        let mut previous = 0;
+
+        for bucket in 0..n_buckets {
+            let Ok(start) = self.table.get_hash(bucket) else {
+                self.issue(
+                    None,
+                    ValidationIssueKind::Pointer,
+                    format!("Hash bucket {} is out of bounds", bucket),
+                );
+                return;
+            };
+
+            let start = start as usize;
+            if start < previous || start > n_items {
+                self.issue(
+                    None,
+                    ValidationIssueKind::HashTable,
+                    format!(
+                        "Hash bucket {} starts at item {}, expected a value between {} and {}",
+                        bucket, start, previous, n_items
+                    ),
+                );
+                return;
+            }
+
+            previous = start;
+        }
+
+        // Every item has to be stored in the bucket that corresponds to its hash value
+        for bucket in 0..n_buckets {
+            let start = self.table.get_hash(bucket).unwrap_or(0) as usize;
+            let end = if bucket + 1 < n_buckets {
+                self.table.get_hash(bucket + 1).unwrap_or(0) as usize
+            } else {
+                n_items
+            };
+
+            for index in start..end {
+                let Ok(item) = self.table.get_hash_item_for_index(index) else {
+                    continue;
+                };
+
+                if item.hash_value() as usize % n_buckets != bucket {
+                    self.issue(
+                        Some(index),
+                        ValidationIssueKind::HashTable,
+                        format!(
+                            "Item with hash value {} is stored in bucket {}",
+                            item.hash_value(),
+                            bucket
+                        ),
+                    );
+                }
+            }
+        }
+
+        if n_buckets == 0 && n_items > 0 {
+            self.issue(
+                None,
+                ValidationIssueKind::HashTable,
+                format!("Hash table contains {} items but no buckets", n_items),
+            );
+        }
+    }
+
+    /// Validate the parent chain of the item at `index` and return its full key
+    fn full_key(
+        &mut self,
+        items: &[HashItem],
+        keys: &[Option<&str>],
+        index: usize,
+    ) -> Option<String> {
+        let mut parts = Vec::new();
+        let mut current = index;
+
+        loop {
+            parts.push(keys[current]?);
+
+            let parent = items[current].parent();
+            if parent == u32::MAX {
+                break;
+            }
+
+            let parent = parent as usize;
+            if parent >= items.len() {
+                self.issue(
+                    Some(index),
+                    ValidationIssueKind::Parent,
+                    format!("Parent index {} is out of bounds", parent),
+                );
+                return None;
+            }
+
+            if parts.len() > items.len() {
+                self.issue(
+                    Some(index),
+                    ValidationIssueKind::Parent,
+                    "The parent items form a loop".to_string(),
+                );
+                return None;
+            }
+
+            if current == index && items[parent].typ().ok() != Some(HashItemType::Container) {
+                self.issue(
+                    Some(index),
+                    ValidationIssueKind::Parent,
+                    format!("Parent item {} is not a container", parent),
+                );
+            }
+
+            current = parent;
+        }
+
+        parts.reverse();
+        Some(parts.concat())
+    }
+
+    fn validate_container(&mut self, items: &[HashItem], index: usize, data: &[u8]) {
+        if data.len() % size_of::<u32>() != 0 {
+            self.issue(
+                Some(index),
+                ValidationIssueKind::Container,
+                format!(
+                    "Container size {} is not a multiple of {}",
+                    data.len(),
+                    size_of::<u32>()
+                ),
+            );
+            return;
+        }
+
+        for chunk in data.chunks_exact(size_of::<u32>()) {
+            let child = u32::from_le_bytes(chunk.try_into().unwrap()) as usize;
+            match items.get(child) {
+                None => self.issue(
+                    Some(index),
+                    ValidationIssueKind::Container,
+                    format!("Child index {} is out of bounds", child),
+                ),
+                Some(item) if item.parent() as usize != index => self.issue(
+                    Some(index),
+                    ValidationIssueKind::Container,
+                    format!("Child item {} has a different parent", child),
+                ),
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Validate all items and return the pointers and keys of the nested hash tables
+    fn validate_items(&mut self) -> Vec<(Pointer, String)> {
+        let n_items = self.table.n_hash_items();
+        self.validate_buckets(n_items);
+
+        let mut items = Vec::with_capacity(n_items);
+        for index in 0..n_items {
+            match self.table.get_hash_item_for_index(index) {
+                Ok(item) => items.push(item),
+                Err(err) => {
+                    self.issue(Some(index), ValidationIssueKind::Pointer, err.to_string());
+                    return Vec::new();
+                }
+            }
+        }
+
+        let mut keys = Vec::with_capacity(n_items);
+        for (index, item) in items.iter().enumerate() {
+            let key = match self.table.file.dereference(&item.key_ptr(), 1) {
+                Ok(data) => match std::str::from_utf8(data) {
+                    Ok("") => {
+                        self.issue(
+                            Some(index),
+                            ValidationIssueKind::Key,
+                            "Empty key".to_string(),
+                        );
+                        None
+                    }
+                    Ok(key) => Some(key),
+                    Err(err) => {
+                        self.issue(Some(index), ValidationIssueKind::Key, err.to_string());
+                        None
+                    }
+                },
+                Err(err) => {
+                    self.issue(
+                        Some(index),
+                        ValidationIssueKind::Pointer,
+                        format!("Key: {}", err),
+                    );
+                    None
+                }
+            };
+
+            keys.push(key);
+        }
+
+        let mut tables = Vec::new();
+        let mut indices = HashMap::new();
+        for (index, item) in items.iter().enumerate() {
+            let key = self.full_key(&items, &keys, index);
+            if let Some(key) = &key {
+                // Lookups only ever find the first item with a key
+                if let Some(first) = indices.insert(key.clone(), index) {
+                    indices.insert(key.clone(), first);
+                    self.issue(
+                        Some(index),
+                        ValidationIssueKind::DuplicateKey,
+                        format!("Key '{}' is already used by item {}", key, first),
+                    );
+                }
+
+                if djb_hash(key) != item.hash_value() {
+                    self.issue(
+                        Some(index),
+                        ValidationIssueKind::Hash,
+                        format!("Hash value of key '{}' does not match", key),
+                    );
+                } else if !self.table.bloom_filter(item.hash_value()) {
+                    self.issue(
+                        Some(index),
+                        ValidationIssueKind::Hash,
+                        format!("Key '{}' is missing from the bloom filter", key),
+                    );
+                }
+            }
+
+            let typ = match item.typ() {
+                Ok(typ) => typ,
+                Err(err) => {
+                    self.issue(Some(index), ValidationIssueKind::HashTable, err.to_string());
+                    continue;
+                }
+            };
+
+            let alignment = match typ {
+                HashItemType::Value => 8,
+                HashItemType::HashTable | HashItemType::Container => 4,
+            };
+
+            let data = match self.table.file.dereference(item.value_ptr(), alignment) {
+                Ok(data) => data,
+                Err(err) => {
+                    self.issue(
+                        Some(index),
+                        ValidationIssueKind::Pointer,
+                        format!("Value: {}", err),
+                    );
+                    continue;
+                }
+            };
+
+            match typ {
+                HashItemType::Value => {
+                    if let Err(err) = self.table.value_for_bytes(data) {
+                        self.issue(Some(index), ValidationIssueKind::Value, err.to_string());
+                    }
+                }
+                HashItemType::HashTable => {
+                    tables.push((*item.value_ptr(), key.unwrap_or_else(|| index.to_string())))
+                }
+                HashItemType::Container => self.validate_container(&items, index, data),
+            }
+        }
+
+        tables
+    }
+}
+
+impl File<'_> {
+    /// Check the whole file for structural problems
+    ///
+    /// In contrast to the other methods, this does not stop at the first problem. All hash
+    /// tables, items, keys and values are checked and every issue that was found is returned.
+    /// An empty result means that the file is valid.
+    ///
+    /// ```
+    /// # use gvdb::read::File;
+    /// # use std::path::PathBuf;
+    /// let file = File::from_file(&PathBuf::from("test-data/test2.gvdb")).unwrap();
+    /// for issue in file.validate() {
+    ///     eprintln!("{}", issue);
+    /// }
+    /// ```
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let header = match self.get_header() {
+            Ok(header) => header,
+            Err(err) => {
+                issues.push(ValidationIssue {
+                    table: Vec::new(),
+                    item: None,
+                    kind: ValidationIssueKind::Header,
+                    message: err.to_string(),
+                });
+                return issues;
+            }
+        };
+
+        if header.version() != 0 {
+            issues.push(ValidationIssue {
+                table: Vec::new(),
+                item: None,
+                kind: ValidationIssueKind::Header,
+                message: format!("Unknown file format version {}", header.version()),
+            });
+        }
+
+        let mut visited = HashSet::new();
+        let mut pending = vec![(*header.root(), Vec::new())];
+
+        while let Some((pointer, path)) = pending.pop() {
+            // Nested tables pointing back to a parent table would otherwise never terminate
+            if !visited.insert(pointer.start()) {
+                issues.push(ValidationIssue {
+                    table: path,
+                    item: None,
+                    kind: ValidationIssueKind::HashTable,
+                    message: "Hash table is referenced more than once".to_string(),
+                });
+                continue;
+            }
+
+            let table = match HashTable::for_bytes(pointer, self, path.len()) {
+                Ok(table) => table,
+                Err(err) => {
+                    issues.push(ValidationIssue {
+                        table: path,
+                        item: None,
+                        kind: ValidationIssueKind::HashTable,
+                        message: err.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            let nested = TableValidator {
+                table: &table,
+                path: &path,
+                issues: &mut issues,
+            }
+            .validate_items();
+
+            for (pointer, key) in nested.into_iter().rev() {
+                let mut nested_path = path.clone();
+                nested_path.push(key);
+                pending.push((pointer, nested_path));
+            }
+        }
+
+        issues
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::{File, HashItem, ValidationIssueKind};
+    use crate::test::*;
+    use crate::write::{FileWriter, HashTableBuilder};
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+    use std::mem::size_of;
+
+    fn nested_file() -> Vec<u8> {
+        let mut table = HashTableBuilder::new();
+        table.insert_string("parent/test", "test").unwrap();
+        let mut nested = HashTableBuilder::new();
+        nested.insert("int", 42u32).unwrap();
+        table.insert_table("table", nested).unwrap();
+        FileWriter::new().write_to_vec_with_table(table).unwrap()
+    }
+
+    fn issue_kinds(data: Vec<u8>) -> Vec<ValidationIssueKind> {
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let issues = file.validate();
+        for issue in &issues {
+            println!("{}", issue);
+        }
+
+        issues.iter().map(|issue| issue.kind()).collect()
+    }
+
+    #[test]
+    fn valid() {
+        for path in [&*TEST_FILE_1, &*TEST_FILE_2, &*TEST_FILE_3] {
+            let file = File::from_file(path).unwrap();
+            assert_eq!(file.validate(), []);
+        }
+
+        assert_eq!(new_simple_file(true).validate(), []);
+        assert_eq!(new_empty_file().validate(), []);
+        assert_eq!(issue_kinds(nested_file()), []);
+    }
+
+    #[test]
+    fn broken_hash_table() {
+        let mut data = nested_file();
+        let root_ptr_end = size_of::<u32>() * 5;
+        data[root_ptr_end] -= 23;
+
+        assert_eq!(issue_kinds(data), [ValidationIssueKind::HashTable]);
+    }
+
+    #[test]
+    fn parent_loop() {
+        let data = nested_file();
+        let file = File::from_bytes(Cow::Owned(data.clone())).unwrap();
+        let table = file.hash_table().unwrap();
+        let index = table
+            .keys()
+            .unwrap()
+            .iter()
+            .position(|key| key == "parent/test")
+            .unwrap();
+        let parent_field = table.pointer.start() as usize
+            + table.hash_items_offset()
+            + size_of::<HashItem>() * index
+            + 4;
+
+        // Let the item point to itself
+        let mut looped = data.clone();
+        looped[parent_field..parent_field + size_of::<u32>()]
+            .copy_from_slice(&(index as u32).to_le_bytes());
+        assert!(issue_kinds(looped).contains(&ValidationIssueKind::Parent));
+
+        let mut out_of_bounds = data;
+        out_of_bounds[parent_field..parent_field + size_of::<u32>()]
+            .copy_from_slice(&10u32.to_le_bytes());
+        let kinds = issue_kinds(out_of_bounds);
+        assert!(kinds.contains(&ValidationIssueKind::Parent));
+        assert!(kinds.contains(&ValidationIssueKind::Container));
+    }
+
+    #[test]
+    fn hash_value() {
+        let mut data = nested_file();
+        let file = File::from_bytes(Cow::Owned(data.clone())).unwrap();
+        let table = file.hash_table().unwrap();
+        let hash_field = table.pointer.start() as usize + table.hash_items_offset();
+        data[hash_field] ^= 0xff;
+
+        assert!(issue_kinds(data).contains(&ValidationIssueKind::Hash));
+    }
+
+    #[test]
+    fn duplicate_key() {
+        let issues = new_duplicate_key_file().validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind(), ValidationIssueKind::DuplicateKey);
+        assert!(issues[0].message().contains("'a1'"));
+    }
+
+    #[test]
+    fn value() {
+        let mut data = nested_file();
+        let file = File::from_bytes(Cow::Owned(data.clone())).unwrap();
+        let info = file
+            .hash_table()
+            .unwrap()
+            .get_hash_table("table")
+            .unwrap()
+            .get_item_info("int")
+            .unwrap();
+
+        // Replace the type signature 'u' with an invalid one
+        data[info.value_end() as usize - 1] = b'z';
+
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let issues = file.validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind(), ValidationIssueKind::Value);
+        assert_eq!(issues[0].table(), ["table"]);
+        assert!(issues[0].item().is_some());
+        assert!(format!("{}", issues[0]).starts_with("Value in table 'table' at item"));
+    }
+}
@@ -0,0 +1,250 @@
+use crate::read::error::{Error, Result};
+use crate::read::{HashItemType, HashTable};
+
+/// An entry found by [`HashTable::walk`]
+#[derive(Debug)]
+pub enum Entry<'a, 'file> {
+    /// A value
+    Value(zvariant::Value<'a>),
+
+    /// A nested hash table. Its entries are visited right after this entry.
+    Table(HashTable<'a, 'file>),
+
+    /// A container. Its children are visited right after this entry.
+    Container,
+}
+
+/// A hash table that is currently being walked
+#[derive(Debug)]
+struct Frame<'a, 'file> {
+    table: HashTable<'a, 'file>,
+
+    // The keys of the enclosing hash tables
+    path: Vec<String>,
+
+    // Whether an item index has already been visited, to detect loops
+    visited: Vec<bool>,
+
+    // The item indices that still need to be visited, together with the full key of their parent
+    pending: Vec<(usize, String)>,
+}
+
+/// Recursive iterator over all entries of a [`HashTable`]
+///
+/// Created by [`HashTable::walk`].
+#[derive(Debug)]
+pub struct Walk<'a, 'file> {
+    stack: Vec<Frame<'a, 'file>>,
+
+    // An error that occurred when reading the root table, returned on the first iteration
+    error: Option<Error>,
+}
+
+impl<'a, 'file> Walk<'a, 'file> {
+    pub(crate) fn new(table: HashTable<'a, 'file>) -> Self {
+        let mut this = Self {
+            stack: Vec::new(),
+            error: None,
+        };
+
+        this.error = this.push_table(table, Vec::new()).err();
+        this
+    }
+
+    fn push_table(&mut self, table: HashTable<'a, 'file>, path: Vec<String>) -> Result<()> {
+        if self
+            .stack
+            .iter()
+            .any(|frame| frame.table.pointer == table.pointer)
+        {
+            return Err(Error::Data(
+                "Nested hash table contains itself. The file appears to have a loop".to_string(),
+            ));
+        }
+
+        let count = table.n_hash_items();
+        let mut pending = Vec::new();
+        for index in (0..count).rev() {
+            if table.get_hash_item_for_index(index)?.parent() == 0xffffffff {
+                pending.push((index, String::new()));
+            }
+        }
+
+        self.stack.push(Frame {
+            table,
+            path,
+            visited: vec![false; count],
+            pending,
+        });
+
+        Ok(())
+    }
+
+    fn visit(
+        &mut self,
+        index: usize,
+        parent_key: String,
+    ) -> Result<(Vec<String>, Entry<'a, 'file>)> {
+        // The caller ensures that there is a frame
+        let frame = self.stack.last_mut().unwrap();
+        match frame.visited.get_mut(index) {
+            Some(visited) if !*visited => *visited = true,
+            Some(_) => {
+                return Err(Error::Data(format!(
+                    "Item {} is referenced multiple times. The file appears to have a loop",
+                    index
+                )))
+            }
+            None => {
+                return Err(Error::Data(format!(
+                    "Child with invalid index encountered: {}",
+                    index
+                )))
+            }
+        }
+
+        let table = frame.table.clone();
+        let item = table.get_hash_item_for_index(index)?;
+        let key = parent_key + table.key_for_item(&item)?;
+        let mut path = frame.path.clone();
+        path.push(key.clone());
+
+        let (typ, data) = table.get_raw_item_for_index(index)?;
+        let entry = match typ {
+            HashItemType::Value => Entry::Value(table.value_for_bytes(data)?),
+            HashItemType::Container => {
+                if data.len() % 4 != 0 {
+                    return Err(Error::Data(format!(
+                        "Container '{}' has an invalid size of {} bytes",
+                        key,
+                        data.len()
+                    )));
+                }
+
+                for child in data.chunks_exact(4).rev() {
+                    let child = u32::from_le_bytes(child.try_into().unwrap());
+                    frame.pending.push((child as usize, key.clone()));
+                }
+
+                Entry::Container
+            }
+            HashItemType::HashTable => {
+                let nested = table.nested_at(*item.value_ptr(), &key)?;
+                self.push_table(nested.clone(), path.clone())?;
+                Entry::Table(nested)
+            }
+        };
+
+        Ok((path, entry))
+    }
+}
+
+impl<'a, 'file> Iterator for Walk<'a, 'file> {
+    type Item = Result<(Vec<String>, Entry<'a, 'file>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.error.take() {
+            return Some(Err(err));
+        }
+
+        loop {
+            let frame = self.stack.last_mut()?;
+            if let Some((index, parent_key)) = frame.pending.pop() {
+                return Some(self.visit(index, parent_key));
+            }
+
+            self.stack.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::{Entry, Error, File, HashItem};
+    use crate::test::{TEST_FILE_2, TEST_FILE_3};
+    use crate::write::{FileWriter, HashTableBuilder};
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+    use std::mem::size_of;
+
+    #[test]
+    fn walk_containers() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let table = file.hash_table().unwrap();
+        let entries: Vec<_> = table.walk().collect::<Result<_, _>>().unwrap();
+        assert_eq!(entries.len(), 12);
+
+        // Every entry is visited after its parent container
+        for (i, (path, entry)) in entries.iter().enumerate() {
+            assert_eq!(path.len(), 1);
+            let key = &path[0];
+            if key.ends_with('/') {
+                assert_matches!(entry, Entry::Container);
+            } else {
+                assert_matches!(entry, Entry::Value(zvariant::Value::Structure(_)));
+            }
+
+            let parent = &key[..key[..key.len() - 1].rfind('/').map_or(0, |i| i + 1)];
+            if !parent.is_empty() {
+                assert!(entries[..i].iter().any(|(path, _)| path[0] == parent));
+            }
+        }
+
+        assert_eq!(entries[0].0, ["/"]);
+    }
+
+    #[test]
+    fn walk_tables() {
+        let file = File::from_file(&TEST_FILE_2).unwrap();
+        let table = file.hash_table().unwrap();
+        let mut entries: Vec<_> = table.walk().collect::<Result<_, _>>().unwrap();
+        println!("{:?}", entries);
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let paths: Vec<Vec<String>> = entries.iter().map(|(path, _)| path.clone()).collect();
+        assert_eq!(
+            paths,
+            [
+                vec!["string".to_string()],
+                vec!["table".to_string()],
+                vec!["table".to_string(), "int".to_string()]
+            ]
+        );
+
+        assert_matches!(&entries[0].1, Entry::Value(value) if *value == zvariant::Value::new("test string"));
+        assert_matches!(&entries[1].1, Entry::Table(table) if table.keys().unwrap() == ["int"]);
+        assert_matches!(&entries[2].1, Entry::Value(value) if *value == zvariant::Value::new(42u32));
+    }
+
+    #[test]
+    fn walk_loop() {
+        let mut table = HashTableBuilder::new();
+        table.insert_string("parent/test", "test").unwrap();
+        let mut data = FileWriter::new().write_to_vec_with_table(table).unwrap();
+
+        // Make 'test' a root item as well, so it is referenced twice
+        let file = File::from_bytes(Cow::Owned(data.clone())).unwrap();
+        let table = file.hash_table().unwrap();
+        let index = (0..table.n_hash_items())
+            .find(|index| {
+                let item = table.get_hash_item_for_index(*index).unwrap();
+                table.key_for_item(&item).unwrap() == "test"
+            })
+            .unwrap();
+        let parent_field = table.pointer.start() as usize
+            + table.hash_items_offset()
+            + size_of::<HashItem>() * index
+            + 4;
+        data[parent_field..parent_field + size_of::<u32>()]
+            .copy_from_slice(&u32::MAX.to_le_bytes());
+
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+        let results: Vec<_> = table.walk().collect();
+        assert!(results
+            .iter()
+            .any(|res| matches!(res, Err(Error::Data(msg)) if msg.contains("loop"))));
+    }
+}
@@ -1,6 +1,6 @@
 #![allow(unused)]
 
-use crate::read::{File, HashItemType, HashTable};
+use crate::read::{File, HashItem, HashItemType, HashTable};
 use crate::write::{FileWriter, HashTableBuilder};
 use lazy_static::lazy_static;
 pub use matches::assert_matches;
@@ -108,7 +108,7 @@ pub fn assert_bytes_eq(a: &[u8], b: &[u8], context: &str) {
                 EXTRA_ROWS_TOP,
                 EXTRA_ROWS_BOTTOM,
                 WIDTH,
-                &a,
+                a,
             )
             .unwrap();
             let str_a = String::from_utf8(a_bytes_buf).unwrap();
@@ -120,7 +120,7 @@ pub fn assert_bytes_eq(a: &[u8], b: &[u8], context: &str) {
                 EXTRA_ROWS_TOP,
                 EXTRA_ROWS_BOTTOM,
                 WIDTH,
-                &b,
+                b,
             )
             .unwrap();
             let str_b = String::from_utf8(b_bytes_buf).unwrap();
@@ -146,7 +146,7 @@ fn byte_compare_file(file: &File, reference_path: &Path) {
 
     assert_bytes_eq(
         &reference_data,
-        file.data.as_ref(),
+        file.data.all().unwrap(),
         &format!("Byte comparing with file '{}'", reference_path.display()),
     );
 }
@@ -321,6 +321,39 @@ pub(crate) fn new_simple_file(big_endian: bool) -> File<'static> {
     File::from_bytes(Cow::Owned(cursor.into_inner())).unwrap()
 }
 
+/// A file with the keys "a1" and "a2" in a single bucket, where the key of "a2" has been replaced
+/// by "a1", so the key "a1" exists twice
+pub(crate) fn new_duplicate_key_file() -> File<'static> {
+    let mut table_builder = HashTableBuilder::new().with_bucket_count(1);
+    table_builder.insert("a1", 1u32).unwrap();
+    table_builder.insert("a2", 2u32).unwrap();
+    let mut data = FileWriter::new()
+        .write_to_vec_with_table(table_builder)
+        .unwrap();
+
+    let file = File::from_bytes(Cow::Borrowed(&data)).unwrap();
+    let table = file.hash_table().unwrap();
+    let index = table
+        .keys()
+        .unwrap()
+        .iter()
+        .position(|key| key == "a2")
+        .unwrap();
+    let key_start = table
+        .get_hash_item_for_index(index)
+        .unwrap()
+        .key_ptr()
+        .start() as usize;
+    let hash_start = table.pointer.start() as usize
+        + table.hash_items_offset()
+        + std::mem::size_of::<HashItem>() * index;
+
+    data[key_start..key_start + 2].copy_from_slice(b"a1");
+    data[hash_start..hash_start + 4].copy_from_slice(&crate::util::djb_hash("a1").to_le_bytes());
+
+    File::from_bytes(Cow::Owned(data)).unwrap()
+}
+
 pub(crate) fn byte_compare_gvdb_hash_table(a: &HashTable, b: &HashTable) {
     assert_eq!(a.header, b.header);
 
@@ -363,6 +396,7 @@ pub(crate) fn byte_compare_gvdb_hash_table(a: &HashTable, b: &HashTable) {
 }
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod test {
     #[test]
     fn assert_bytes_eq1() {
@@ -0,0 +1,241 @@
+use crate::write::{FileWriter, HashTableBuilder, Result};
+use crate::Endian;
+
+/// The distribution of the sizes of the values of a [`SyntheticFile`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ValueSize {
+    /// Every value has the same size in bytes
+    Fixed(usize),
+
+    /// The sizes are uniformly distributed between `min` and `max` bytes, both inclusive
+    Uniform {
+        /// The smallest size
+        min: usize,
+        /// The largest size
+        max: usize,
+    },
+}
+
+impl Default for ValueSize {
+    fn default() -> Self {
+        Self::Fixed(16)
+    }
+}
+
+/// A small deterministic random number generator (SplitMix64)
+///
+/// The generated data must be the same on every platform and with every version of the
+/// dependencies, so no external generator is used.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A random number between `min` and `max`, both inclusive
+    fn range(&mut self, min: usize, max: usize) -> usize {
+        if max <= min {
+            min
+        } else {
+            min + (self.next() % (max - min + 1) as u64) as usize
+        }
+    }
+}
+
+/// Generate synthetic GVDB files of a configurable size and shape
+///
+/// This is meant for benchmarks and integration tests that need files with many keys, deeply
+/// nested paths or large values. The same options always generate the same file.
+///
+/// Every item is a string value with random lowercase ASCII letters, whose length is chosen
+/// according to [`ValueSize`]. The keys are paths with [`SyntheticFile::with_depth`] directory
+/// levels, like `dir0/dir1/key5`, and the directories are stored as container items like in
+/// GResource bundles.
+///
+/// ```
+/// # use std::borrow::Cow;
+/// use gvdb::read::File;
+/// use gvdb::testutil::{SyntheticFile, ValueSize};
+///
+/// let synthetic = SyntheticFile::new(100)
+///     .with_depth(2)
+///     .with_fanout(4)
+///     .with_value_size(ValueSize::Uniform { min: 10, max: 100 });
+///
+/// let keys = synthetic.keys();
+/// assert_eq!(keys[5], "dir1/dir1/key5");
+///
+/// let file = File::from_bytes(Cow::Owned(synthetic.build().unwrap())).unwrap();
+/// let table = file.hash_table().unwrap();
+/// for key in keys {
+///     let value = table.get_string(&key).unwrap();
+///     assert!((10..=100).contains(&value.len()));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct SyntheticFile {
+    n_keys: usize,
+    depth: usize,
+    fanout: usize,
+    value_size: ValueSize,
+    seed: u64,
+    endian: Endian,
+}
+
+impl SyntheticFile {
+    /// Generate a file with `n_keys` values in a flat namespace
+    pub fn new(n_keys: usize) -> Self {
+        Self {
+            n_keys,
+            depth: 0,
+            fanout: 2,
+            value_size: ValueSize::default(),
+            seed: 0,
+            endian: Endian::default(),
+        }
+    }
+
+    /// The number of directory levels above each value. The default is 0.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// The number of subdirectories of each directory. The default is 2.
+    ///
+    /// The values are distributed evenly over the directories. The fanout is at least 1.
+    pub fn with_fanout(mut self, fanout: usize) -> Self {
+        self.fanout = fanout.max(1);
+        self
+    }
+
+    /// The distribution of the sizes of the values. The default is 16 bytes.
+    pub fn with_value_size(mut self, value_size: ValueSize) -> Self {
+        self.value_size = value_size;
+        self
+    }
+
+    /// The seed of the values. Different seeds generate different values for the same keys.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The byte order of the file. The default is little endian.
+    pub fn with_endian(mut self, endian: Endian) -> Self {
+        self.endian = endian;
+        self
+    }
+
+    /// The key of the value with the index `index`
+    fn key(&self, index: usize) -> String {
+        let mut key = String::new();
+        let mut rest = index;
+        for _ in 0..self.depth {
+            key.push_str(&format!("dir{}/", rest % self.fanout));
+            rest /= self.fanout;
+        }
+
+        key.push_str(&format!("key{}", index));
+        key
+    }
+
+    /// The keys of all values, without the keys of the directories
+    pub fn keys(&self) -> Vec<String> {
+        (0..self.n_keys).map(|index| self.key(index)).collect()
+    }
+
+    /// Create a [`HashTableBuilder`] with all items
+    pub fn table_builder(&self) -> Result<HashTableBuilder<'static>> {
+        let mut rng = Rng(self.seed);
+        let mut table_builder = HashTableBuilder::new();
+        for index in 0..self.n_keys {
+            let size = match self.value_size {
+                ValueSize::Fixed(size) => size,
+                ValueSize::Uniform { min, max } => rng.range(min, max),
+            };
+
+            let value: String = (0..size)
+                .map(|_| (b'a' + rng.range(0, 25) as u8) as char)
+                .collect();
+            table_builder.insert(&self.key(index), value)?;
+        }
+
+        Ok(table_builder)
+    }
+
+    /// Generate the file
+    pub fn build(&self) -> Result<Vec<u8>> {
+        FileWriter::endian(self.endian).write_to_vec_with_table(self.table_builder()?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::{File, HashItemType};
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+
+    #[test]
+    fn synthetic_file() {
+        let synthetic = SyntheticFile::new(50)
+            .with_depth(3)
+            .with_fanout(3)
+            .with_value_size(ValueSize::Uniform { min: 0, max: 40 })
+            .with_seed(7);
+
+        let data = synthetic.build().unwrap();
+        assert_eq!(data, synthetic.build().unwrap());
+        assert_ne!(data, synthetic.clone().with_seed(8).build().unwrap());
+
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+        let keys = synthetic.keys();
+        assert_eq!(keys[0], "dir0/dir0/dir0/key0");
+        assert_eq!(keys[49], "dir1/dir1/dir2/key49");
+
+        let mut sizes = Vec::new();
+        for key in &keys {
+            let value = table.get_string(key).unwrap();
+            assert!(value.len() <= 40);
+            assert!(value.bytes().all(|b| b.is_ascii_lowercase()));
+            sizes.push(value.len());
+        }
+        assert!(sizes.iter().any(|size| *size != sizes[0]));
+
+        // 3 + 9 + 27 directories
+        assert_eq!(table.keys().unwrap().len(), 50 + 39);
+        assert_eq!(table.get_raw("dir2/").unwrap().0, HashItemType::Container);
+    }
+
+    #[test]
+    fn synthetic_file_flat() {
+        let synthetic = SyntheticFile::new(3)
+            .with_value_size(ValueSize::Fixed(100))
+            .with_fanout(0)
+            .with_endian(Endian::Big);
+        assert_eq!(synthetic.keys(), ["key0", "key1", "key2"]);
+
+        let file = File::from_bytes(Cow::Owned(synthetic.build().unwrap())).unwrap();
+        assert_eq!(file.endianness(), Endian::Big);
+        let table = file.hash_table().unwrap();
+        assert_eq!(table.keys_sorted().unwrap(), synthetic.keys());
+        assert_eq!(table.get_string("key2").unwrap().len(), 100);
+
+        let empty = SyntheticFile::new(0).build().unwrap();
+        assert!(File::from_bytes(Cow::Owned(empty))
+            .unwrap()
+            .hash_table()
+            .unwrap()
+            .keys()
+            .unwrap()
+            .is_empty());
+    }
+}
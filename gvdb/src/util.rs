@@ -1,4 +1,13 @@
-/// Perform the djb2 hash function
+/// The djb2 hash function that is used for the keys of GVDB hash tables
+///
+/// The hash value of a key determines its hash bucket and is stored in its hash item. Use
+/// [`HashTable::bucket_for_key`](crate::read::HashTable::bucket_for_key) to find the bucket of a
+/// key in a specific hash table.
+///
+/// ```
+/// assert_eq!(gvdb::djb_hash(""), 5381);
+/// assert_eq!(gvdb::djb_hash("a"), 5381 * 33 + 97);
+/// ```
 pub fn djb_hash(key: &str) -> u32 {
     let mut hash_value: u32 = 5381;
     for char in key.bytes() {
@@ -8,16 +17,82 @@ pub fn djb_hash(key: &str) -> u32 {
     hash_value
 }
 
+/// Emit a `tracing` event if the `tracing` feature is enabled
+///
+/// Without the feature the event and its fields are not compiled at all.
+#[cfg(feature = "std")]
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)+);
+    };
+}
+#[cfg(feature = "std")]
+pub(crate) use trace_event;
+
 /// Align an arbitrary offset to a multiple of 2
 /// The result is undefined for alignments that are not a multiple of 2
+#[cfg(feature = "std")]
 pub fn align_offset(offset: usize, alignment: usize) -> usize {
     //(alignment - (offset % alignment)) % alignment
     (offset + alignment - 1) & !(alignment - 1)
 }
 
+/// Split the first complete type off the front of a GVariant type string
+///
+/// Returns `None` if the type string ends before the first type is complete.
+#[cfg(feature = "std")]
+pub fn split_type(typ: &str) -> Option<(&str, &str)> {
+    let mut depth = 0usize;
+    for (index, c) in typ.char_indices() {
+        match c {
+            'a' | 'm' => continue,
+            '(' | '{' => depth += 1,
+            ')' | '}' => depth = depth.checked_sub(1)?,
+            _ => {}
+        }
+
+        if depth == 0 {
+            return Some(typ.split_at(index + c.len_utf8()));
+        }
+    }
+
+    None
+}
+
+/// Split a tuple or dict entry type string into the types of its members
+#[cfg(feature = "std")]
+pub fn member_types(typ: &str) -> Option<Vec<&str>> {
+    let mut rest = typ.get(1..typ.len().checked_sub(1)?)?;
+    let mut types = Vec::new();
+    while !rest.is_empty() {
+        let (first, remaining) = split_type(rest)?;
+        types.push(first);
+        rest = remaining;
+    }
+
+    Some(types)
+}
+
 #[cfg(test)]
 mod test {
-    use super::align_offset;
+    use super::{align_offset, member_types, split_type};
+
+    #[test]
+    fn split() {
+        assert_eq!(split_type("s"), Some(("s", "")));
+        assert_eq!(split_type("aasu"), Some(("aas", "u")));
+        assert_eq!(split_type("ma{sv}(ii)"), Some(("ma{sv}", "(ii)")));
+        assert_eq!(split_type("a(s"), None);
+        assert_eq!(split_type(""), None);
+        assert_eq!(split_type("éu"), Some(("é", "u")));
+        assert_eq!(split_type("(é)"), Some(("(é)", "")));
+
+        assert_eq!(member_types("(sa{sv}mi)"), Some(vec!["s", "a{sv}", "mi"]));
+        assert_eq!(member_types("{sv}"), Some(vec!["s", "v"]));
+        assert_eq!(member_types("()"), Some(vec![]));
+        assert_eq!(member_types("(a)"), None);
+    }
 
     #[test]
     fn align() {
@@ -0,0 +1,11 @@
+mod error;
+mod normal;
+mod parse;
+mod print;
+mod value;
+
+pub use error::{Error, Result};
+pub use normal::{equal, is_normal_form, normal_form};
+pub use parse::{parse, parse_inferred};
+pub use print::print;
+pub use value::Value;
@@ -0,0 +1,81 @@
+use std::fmt::{Debug, Display, Formatter};
+
+/// Error type for parsing the GVariant text format
+#[non_exhaustive]
+pub enum Error {
+    /// The text is not valid GVariant text format or does not match the requested type
+    Parse {
+        /// The byte offset in the text at which the error occurred
+        position: usize,
+        /// A description of the error
+        message: String,
+    },
+
+    /// An error occurred when constructing the value with zvariant
+    ZVariant(zvariant::Error),
+
+    /// The type signature is not a single complete GVariant type
+    InvalidType(String),
+}
+
+impl std::error::Error for Error {}
+
+impl From<zvariant::Error> for Error {
+    fn from(err: zvariant::Error) -> Self {
+        Self::ZVariant(err)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parse { position, message } => {
+                write!(
+                    f,
+                    "Error parsing GVariant text at {}: {}",
+                    position, message
+                )
+            }
+            Error::ZVariant(err) => {
+                write!(f, "Error creating ZVariant data: {}", err)
+            }
+            Error::InvalidType(signature) => {
+                write!(f, "Invalid GVariant type '{}'", signature)
+            }
+        }
+    }
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+/// The Result type for [`Error`]
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod test {
+    use super::Error;
+    use matches::assert_matches;
+
+    #[test]
+    fn from() {
+        let err = Error::from(zvariant::Error::Message("Test".to_string()));
+        assert_matches!(err, Error::ZVariant(_));
+        assert!(format!("{}", err).contains("ZVariant"));
+
+        let err = Error::Parse {
+            position: 5,
+            message: "Expected ')'".to_string(),
+        };
+        assert_eq!(
+            format!("{:?}", err),
+            "Error parsing GVariant text at 5: Expected ')'"
+        );
+
+        let err = Error::InvalidType("z".to_string());
+        assert_eq!(format!("{}", err), "Invalid GVariant type 'z'");
+    }
+}
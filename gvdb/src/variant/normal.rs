@@ -0,0 +1,653 @@
+use super::{Error, Result};
+use crate::util::{align_offset, member_types, split_type};
+
+/// The maximum nesting depth of types and variants, like `G_VARIANT_MAX_RECURSION_DEPTH`
+pub(super) const MAX_DEPTH: usize = 128;
+
+/// The alignment and the size of a type, if it is fixed
+#[derive(Copy, Clone)]
+pub(super) struct TypeInfo {
+    pub(super) alignment: usize,
+    pub(super) fixed_size: Option<usize>,
+}
+
+impl TypeInfo {
+    fn fixed(alignment: usize, size: usize) -> Self {
+        Self {
+            alignment,
+            fixed_size: Some(size),
+        }
+    }
+
+    fn variable(alignment: usize) -> Self {
+        Self {
+            alignment,
+            fixed_size: None,
+        }
+    }
+}
+
+fn is_basic(typ: &str) -> bool {
+    matches!(
+        typ,
+        "b" | "y" | "n" | "q" | "i" | "u" | "x" | "t" | "h" | "d" | "s" | "o" | "g"
+    )
+}
+
+/// The type info of the single complete type `typ`, or `None` if it is not a valid type
+pub(super) fn type_info(typ: &str, depth: usize) -> Option<TypeInfo> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+
+    Some(match typ {
+        "b" | "y" => TypeInfo::fixed(1, 1),
+        "n" | "q" => TypeInfo::fixed(2, 2),
+        "i" | "u" | "h" => TypeInfo::fixed(4, 4),
+        "x" | "t" | "d" => TypeInfo::fixed(8, 8),
+        "s" | "o" | "g" => TypeInfo::variable(1),
+        "v" => TypeInfo::variable(8),
+        _ if typ.starts_with('a') || typ.starts_with('m') => {
+            TypeInfo::variable(type_info(&typ[1..], depth + 1)?.alignment)
+        }
+        _ if (typ.starts_with('(') && typ.ends_with(')'))
+            || (typ.starts_with('{') && typ.ends_with('}')) =>
+        {
+            let members = member_types(typ)?;
+            if typ.starts_with('{') && (members.len() != 2 || !is_basic(members[0])) {
+                return None;
+            }
+
+            let mut alignment = 1;
+            let mut offset = Some(0);
+            for member in members {
+                let info = type_info(member, depth + 1)?;
+                alignment = alignment.max(info.alignment);
+                offset = offset
+                    .zip(info.fixed_size)
+                    .map(|(offset, size)| align_offset(offset, info.alignment) + size);
+            }
+
+            match offset {
+                // The unit type has a size of 1
+                Some(0) => TypeInfo::fixed(1, 1),
+                Some(size) => TypeInfo::fixed(alignment, align_offset(size, alignment)),
+                None => TypeInfo::variable(alignment),
+            }
+        }
+        _ => return None,
+    })
+}
+
+/// Whether `typ` is a sequence of complete types
+fn is_signature(typ: &str) -> bool {
+    let mut rest = typ;
+    while !rest.is_empty() {
+        let Some((first, remaining)) = split_type(rest) else {
+            return false;
+        };
+
+        if type_info(first, 0).is_none() {
+            return false;
+        }
+
+        rest = remaining;
+    }
+
+    true
+}
+
+fn is_object_path(path: &str) -> bool {
+    path == "/"
+        || path.strip_prefix('/').is_some_and(|path| {
+            path.split('/').all(|segment| {
+                !segment.is_empty()
+                    && segment
+                        .bytes()
+                        .all(|c| c.is_ascii_alphanumeric() || c == b'_')
+            })
+        })
+}
+
+/// The size of the framing offsets in a container of `size` bytes
+pub(super) fn offset_size(size: usize) -> usize {
+    if size > u32::MAX as usize {
+        8
+    } else if size > u16::MAX as usize {
+        4
+    } else if size > u8::MAX as usize {
+        2
+    } else if size > 0 {
+        1
+    } else {
+        0
+    }
+}
+
+/// Read the little-endian framing offset at `position`
+pub(super) fn read_offset(data: &[u8], position: usize, size: usize) -> Option<usize> {
+    let bytes = data.get(position..position.checked_add(size)?)?;
+    let mut value = [0u8; 8];
+    value[..size].copy_from_slice(bytes);
+    usize::try_from(u64::from_le_bytes(value)).ok()
+}
+
+/// Append the framing `offsets` to `out`, with the smallest offset size for the resulting size
+fn write_offsets(out: &mut Vec<u8>, offsets: impl ExactSizeIterator<Item = usize> + Clone) {
+    let body = out.len();
+    let n_offsets = offsets.len();
+    let size = [1, 2, 4, 8]
+        .into_iter()
+        .find(|size| offset_size(body + n_offsets * size) == *size)
+        .unwrap_or(8);
+
+    for offset in offsets {
+        out.extend_from_slice(&offset.to_le_bytes()[..size]);
+    }
+}
+
+fn pad(out: &mut Vec<u8>, alignment: usize) {
+    out.resize(align_offset(out.len(), alignment), 0);
+}
+
+/// The normal form of `data` of the valid type `typ`
+///
+/// Invalid data is replaced with the default value of its type, like GLib does when reading it.
+fn normalize(typ: &str, info: TypeInfo, data: &[u8], depth: usize) -> Vec<u8> {
+    if let Some(size) = info.fixed_size {
+        if data.len() != size {
+            return default_value(typ, info, depth);
+        }
+    }
+
+    match typ {
+        "b" => vec![(data[0] != 0) as u8],
+        "y" | "n" | "q" | "i" | "u" | "h" | "x" | "t" | "d" => data.to_vec(),
+        "s" | "o" | "g" => {
+            let valid = data
+                .split_last()
+                .filter(|(last, rest)| **last == 0 && !rest.contains(&0))
+                .and_then(|(_, rest)| std::str::from_utf8(rest).ok())
+                .is_some_and(|string| match typ {
+                    "o" => is_object_path(string),
+                    "g" => is_signature(string),
+                    _ => true,
+                });
+
+            if valid {
+                data.to_vec()
+            } else {
+                default_value(typ, info, depth)
+            }
+        }
+        "v" => normalize_variant(data, depth),
+        _ if typ.starts_with('a') => normalize_array(&typ[1..], data, depth),
+        _ if typ.starts_with('m') => {
+            let element = &typ[1..];
+            let element_info = type_info(element, depth + 1).unwrap();
+            match element_info.fixed_size {
+                Some(size) if data.len() == size => normalize(element, element_info, data, depth),
+                None if !data.is_empty() => {
+                    let mut out = normalize(element, element_info, &data[..data.len() - 1], depth);
+                    out.push(0);
+                    out
+                }
+                _ => Vec::new(),
+            }
+        }
+        _ => normalize_tuple(typ, info, data, depth),
+    }
+}
+
+/// The normal form of the default value of `typ`, which is zero, empty or `Nothing`
+fn default_value(typ: &str, info: TypeInfo, depth: usize) -> Vec<u8> {
+    match typ {
+        "s" | "g" => vec![0],
+        "o" => b"/\0".to_vec(),
+        "v" => b"\0\0()".to_vec(),
+        _ if typ.starts_with('a') || typ.starts_with('m') => Vec::new(),
+        _ if typ.starts_with('(') || typ.starts_with('{') => normalize_tuple(typ, info, &[], depth),
+        _ => vec![0; info.fixed_size.unwrap_or(0)],
+    }
+}
+
+fn normalize_variant(data: &[u8], depth: usize) -> Vec<u8> {
+    let child = data
+        .iter()
+        .rposition(|byte| *byte == 0)
+        .and_then(|separator| {
+            let typ = std::str::from_utf8(&data[separator + 1..])
+                .ok()
+                .filter(|typ| typ.is_ascii())?;
+            let info = type_info(typ, depth + 1)?;
+            let child = &data[..separator];
+
+            // A fixed size child with the wrong size is replaced as a whole
+            if info.fixed_size.is_some_and(|size| size != child.len()) {
+                return None;
+            }
+
+            Some((typ, info, child))
+        });
+
+    let Some((typ, info, child)) = child else {
+        return b"\0\0()".to_vec();
+    };
+
+    let mut out = normalize(typ, info, child, depth + 1);
+    out.push(0);
+    out.extend_from_slice(typ.as_bytes());
+    out
+}
+
+fn normalize_array(element: &str, data: &[u8], depth: usize) -> Vec<u8> {
+    let info = type_info(element, depth + 1).unwrap();
+    let mut out = Vec::new();
+
+    if let Some(size) = info.fixed_size {
+        if data.len() % size == 0 {
+            for child in data.chunks_exact(size) {
+                out.extend(normalize(element, info, child, depth));
+            }
+        }
+
+        return out;
+    }
+
+    if data.is_empty() {
+        return out;
+    }
+
+    let size = offset_size(data.len());
+    let offsets_start = data
+        .len()
+        .checked_sub(size)
+        .and_then(|position| read_offset(data, position, size))
+        .filter(|last_end| *last_end <= data.len());
+    let Some(offsets_start) = offsets_start else {
+        return out;
+    };
+
+    let offsets_len = data.len() - offsets_start;
+    if offsets_len % size != 0 {
+        return out;
+    }
+
+    let mut ends = Vec::with_capacity(offsets_len / size);
+    let mut previous_end = 0;
+    let mut ordered = true;
+    for position in (offsets_start..data.len()).step_by(size) {
+        let end = read_offset(data, position, size).unwrap_or(0);
+        let start = align_offset(previous_end, info.alignment);
+
+        // Like GLib, all children after an offset that is out of order are empty
+        ordered &= end >= previous_end;
+        let child = data
+            .get(start..end)
+            .filter(|_| ordered && end <= offsets_start)
+            .unwrap_or(&[]);
+
+        pad(&mut out, info.alignment);
+        out.extend(normalize(element, info, child, depth));
+        ends.push(out.len());
+        previous_end = end;
+    }
+
+    write_offsets(&mut out, ends.into_iter());
+    out
+}
+
+fn normalize_tuple(typ: &str, info: TypeInfo, data: &[u8], depth: usize) -> Vec<u8> {
+    let members: Vec<(&str, TypeInfo)> = member_types(typ)
+        .unwrap()
+        .into_iter()
+        .map(|member| (member, type_info(member, depth + 1).unwrap()))
+        .collect();
+
+    // The framing offsets of all variable sized members except the last one
+    let n_offsets = members
+        .iter()
+        .take(members.len().saturating_sub(1))
+        .filter(|(_, info)| info.fixed_size.is_none())
+        .count();
+    let size = offset_size(data.len());
+    let offsets_start = data.len().checked_sub(n_offsets * size);
+
+    // The bounds of all members, computed like GLib from the end of the previous member. The
+    // start of a member whose preceding framing offset can't be read is 0.
+    let mut bounds = Vec::with_capacity(members.len());
+    let mut previous_end = 0;
+    let mut n_offset = 0;
+    for (index, (_, member_info)) in members.iter().enumerate() {
+        let start = align_offset(previous_end, member_info.alignment);
+        let end = match member_info.fixed_size {
+            Some(size) => start.checked_add(size),
+            None if index == members.len() - 1 => offsets_start,
+            None => {
+                n_offset += 1;
+                data.len()
+                    .checked_sub(n_offset * size)
+                    .and_then(|position| read_offset(data, position, size))
+            }
+        };
+        bounds.push((start, end));
+        previous_end = end.unwrap_or(0);
+    }
+
+    let valid =
+        |start: usize, end: Option<usize>| end.filter(|end| start <= *end && *end <= data.len());
+
+    // GLib treats all members after the first one that overlaps its predecessor or exceeds the
+    // data as empty. A failure of the first member disables this check.
+    let mut ordered = members.len();
+    if members.len() > 1 {
+        let mut previous_end = 0;
+        for (index, (start, end)) in bounds.iter().enumerate() {
+            match valid(*start, *end) {
+                Some(end) if *start >= previous_end => previous_end = end,
+                _ => {
+                    if index > 0 {
+                        ordered = index;
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    // The other members must not extend beyond the end of the last member, which is the start of
+    // the framing offsets if it is variable sized
+    let limit = bounds.last().and_then(|(_, end)| *end);
+
+    let mut out = Vec::new();
+    let mut ends = Vec::new();
+    for (index, ((member, member_info), (start, end))) in members.iter().zip(bounds).enumerate() {
+        let child = valid(start, end)
+            .filter(|end| index < ordered && limit.map_or(true, |limit| *end <= limit))
+            .and_then(|end| data.get(start..end))
+            .unwrap_or(&[]);
+
+        pad(&mut out, member_info.alignment);
+        out.extend(normalize(member, *member_info, child, depth));
+        if member_info.fixed_size.is_none() && index != members.len() - 1 {
+            ends.push(out.len());
+        }
+    }
+
+    match info.fixed_size {
+        Some(size) => out.resize(size, 0),
+        // GLib reads the framing offsets of empty data with a size of 0, so empty data is
+        // already in normal form if all members are empty
+        None if out.is_empty() && data.is_empty() => {}
+        None => write_offsets(&mut out, ends.into_iter().rev()),
+    }
+
+    out
+}
+
+/// Convert the serialized GVariant `data` of type `signature` to its normal form
+///
+/// The normal form is the unique serialization of a value: framing offsets have the smallest
+/// possible size, padding consists of zero bytes and all values are valid. Data that is not in
+/// normal form, e.g. because it was written by another serializer or is corrupt, is converted the
+/// same way as by `g_variant_get_normal_form`: invalid values are replaced with the default value
+/// of their type, which is zero, an empty string or container, or `Nothing`. The byte order of
+/// the data is preserved.
+///
+/// Values in GVDB files are stored as variants, so their data can be normalized with the type
+/// `v`. Returns [`Error::InvalidType`] if `signature` is not a single complete type.
+///
+/// ```
+/// # use std::borrow::Cow;
+/// use gvdb::read::File;
+/// use gvdb::variant::normal_form;
+/// use gvdb::write::{FileWriter, HashTableBuilder};
+///
+/// let mut table_builder = HashTableBuilder::new();
+/// table_builder.insert("strings", vec!["a", "b"]).unwrap();
+/// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+///
+/// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+/// let (_, value) = file.hash_table().unwrap().get_raw("strings").unwrap();
+/// assert_eq!(normal_form(value, "v").unwrap(), value);
+///
+/// // A boolean that is not 0 or 1 and a string without zero-termination
+/// assert_eq!(normal_form(b"\x02", "b").unwrap(), b"\x01");
+/// assert_eq!(normal_form(b"test", "s").unwrap(), b"\0");
+/// ```
+pub fn normal_form(data: &[u8], signature: &str) -> Result<Vec<u8>> {
+    let info = type_info(signature, 0)
+        .filter(|_| split_type(signature).is_some_and(|(_, rest)| rest.is_empty()))
+        .ok_or_else(|| Error::InvalidType(signature.to_string()))?;
+
+    Ok(normalize(signature, info, data, 0))
+}
+
+/// Whether the serialized GVariant `data` of type `signature` is in normal form
+///
+/// See [`normal_form`]. This is the equivalent of `g_variant_is_normal_form`.
+///
+/// ```
+/// use gvdb::variant::is_normal_form;
+///
+/// assert!(is_normal_form(b"test\0", "s").unwrap());
+/// assert!(!is_normal_form(b"test", "s").unwrap());
+/// assert!(!is_normal_form(&[2], "b").unwrap());
+/// ```
+pub fn is_normal_form(data: &[u8], signature: &str) -> Result<bool> {
+    Ok(normal_form(data, signature)? == data)
+}
+
+/// Whether the serialized GVariant values `a` and `b` of type `signature` are equal
+///
+/// The values are compared in normal form, so e.g. different sizes of framing offsets or
+/// non-zero padding don't make a difference. Both values have to be in the same byte order.
+///
+/// ```
+/// use gvdb::variant::equal;
+///
+/// // Non-zero padding between the members of a tuple
+/// assert!(equal(b"\x01\0\0\0\x02\0\0\0", b"\x01\xff\xff\xff\x02\0\0\0", "(yu)").unwrap());
+/// assert!(!equal(b"\x01\0\0\0\x02\0\0\0", b"\x01\0\0\0\x03\0\0\0", "(yu)").unwrap());
+/// ```
+pub fn equal(a: &[u8], b: &[u8], signature: &str) -> Result<bool> {
+    Ok(normal_form(a, signature)? == normal_form(b, signature)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    fn serialize<T: serde::Serialize + zvariant::Type>(value: &T) -> Vec<u8> {
+        let context = zvariant::serialized::Context::new_gvariant(zvariant::LE, 0);
+        zvariant::to_bytes(context, value).unwrap().to_vec()
+    }
+
+    #[test]
+    fn normal() {
+        // Data written by zvariant is in normal form
+        let values: Vec<(Vec<u8>, &str)> = vec![
+            (vec![1], "b"),
+            (serialize(&0x1234u16), "q"),
+            (serialize(&-5i64), "x"),
+            (serialize(&1.5f64), "d"),
+            (serialize(&"test"), "s"),
+            (serialize(&vec!["a", "bc", ""]), "as"),
+            (serialize(&vec![1u32, 2, 3]), "au"),
+            (serialize(&(1u8, "test", 2u64, vec![1u8])), "(ysxay)"),
+            (serialize(&(1u8, 2u32)), "(yu)"),
+            (
+                serialize(&std::collections::HashMap::from([("a", 1u32), ("b", 2)])),
+                "a{su}",
+            ),
+            (serialize(&Some("test")), "ms"),
+            (serialize(&Some(5u32)), "mu"),
+            (serialize(&None::<u32>), "mu"),
+            (serialize(&zvariant::Value::from(42u32)), "v"),
+            (serialize(&vec!["x".repeat(300); 2]), "as"),
+            (serialize(&vec![(1u16, "a".repeat(70000))]), "a(qs)"),
+        ];
+
+        for (data, signature) in values {
+            assert!(
+                is_normal_form(&data, signature).unwrap(),
+                "{} {:?}",
+                signature,
+                data
+            );
+            assert!(equal(&data, &data, signature).unwrap());
+        }
+
+        // The unit type
+        assert!(is_normal_form(b"\0", "()").unwrap());
+        assert!(is_normal_form(b"\0\0()", "v").unwrap());
+        assert!(is_normal_form(b"", "a()").unwrap());
+        assert!(is_normal_form(b"\0\0", "a()").unwrap());
+    }
+
+    #[test]
+    fn non_normal() {
+        // Fixed size values with the wrong size are zero
+        assert_eq!(normal_form(b"\x01\x02", "u").unwrap(), [0; 4]);
+        assert_eq!(normal_form(b"\x05", "b").unwrap(), [1]);
+
+        // Strings without termination, with invalid UTF-8 or embedded zero bytes are empty
+        assert_eq!(normal_form(b"test", "s").unwrap(), b"\0");
+        assert_eq!(normal_form(b"\xff\0", "s").unwrap(), b"\0");
+        assert_eq!(normal_form(b"a\0b\0", "s").unwrap(), b"\0");
+        assert_eq!(normal_form(b"a/b\0", "o").unwrap(), b"/\0");
+        assert_eq!(normal_form(b"/a/b_1\0", "o").unwrap(), b"/a/b_1\0");
+        assert_eq!(normal_form(b"a{\0", "g").unwrap(), b"\0");
+        assert_eq!(normal_form(b"a{sv}u\0", "g").unwrap(), b"a{sv}u\0");
+
+        // Fixed size arrays with a partial element are empty
+        assert_eq!(normal_form(b"\x01\0\0\0\x02", "au").unwrap(), b"");
+        assert_eq!(normal_form(b"\x00\x02", "ab").unwrap(), b"\x00\x01");
+
+        // Variable size arrays with invalid framing offsets
+        assert_eq!(normal_form(b"a\0\x09", "as").unwrap(), b"");
+        assert_eq!(
+            normal_form(b"a\0b\0\x05\x04", "as").unwrap(),
+            b"\0\0\x01\x02"
+        );
+
+        // Non-zero padding
+        assert_eq!(
+            normal_form(b"\x01\xff\xff\xff\x02\0\0\0", "(yu)").unwrap(),
+            b"\x01\0\0\0\x02\0\0\0"
+        );
+        assert_eq!(normal_form(b"\x01\x02", "(yu)").unwrap(), [0; 8]);
+
+        // Maybe
+        assert_eq!(normal_form(b"\x01\x02", "mu").unwrap(), b"");
+        assert_eq!(normal_form(b"a\0\x01", "ms").unwrap(), b"a\0\0");
+
+        // Variants with invalid types or a fixed size value of the wrong size contain the unit type
+        assert_eq!(normal_form(b"test", "v").unwrap(), b"\0\0()");
+        assert_eq!(normal_form(b"\0z", "v").unwrap(), b"\0\0()");
+        assert_eq!(normal_form(b"\x01\x02\0u", "v").unwrap(), b"\0\0()");
+        let data = [0, b'(', 0xc3, 0xa9, b')'];
+        assert_eq!(normal_form(&data, "v").unwrap(), b"\0\0()");
+        assert!(!is_normal_form(&data, "v").unwrap());
+        assert!(equal(&data, b"\0\0()", "v").unwrap());
+        assert_eq!(normal_form(b"\0\0\xc3\xa9", "v").unwrap(), b"\0\0()");
+
+        // Deeply nested variants are replaced
+        let mut data = b"\0\0()".to_vec();
+        for _ in 0..(MAX_DEPTH + 10) {
+            data.extend_from_slice(b"\0v");
+        }
+        let normal = normal_form(&data, "v").unwrap();
+        assert!(normal.len() < data.len());
+        assert!(is_normal_form(&normal, "v").unwrap());
+    }
+
+    #[test]
+    fn large_offsets() {
+        // 2 byte offsets in an array that only needs 1 byte offsets
+        let data = b"a\0b\0\x02\x00\x04\x00";
+        assert!(!is_normal_form(data, "as").unwrap());
+        assert!(is_normal_form(&normal_form(data, "as").unwrap(), "as").unwrap());
+
+        let data = serialize(&vec!["a".repeat(200), "b".repeat(200)]);
+        assert!(is_normal_form(&data, "as").unwrap());
+        assert_eq!(&data[data.len() - 4..], &[201, 0, 146, 1]);
+    }
+
+    #[test]
+    fn invalid_type() {
+        for signature in [
+            "", "z", "uu", "a", "(u", "{vu}", "{sus}", "(u}", "mm", "é", "(é)", "aé",
+        ] {
+            assert_matches!(normal_form(b"", signature), Err(Error::InvalidType(_)));
+        }
+
+        let deep = format!("{}u", "a".repeat(MAX_DEPTH + 1));
+        assert_matches!(normal_form(b"", &deep), Err(Error::InvalidType(_)));
+    }
+}
+
+#[cfg(all(feature = "glib", test))]
+mod test_glib {
+    use super::*;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    #[test]
+    fn glib_normal_form() {
+        let signatures = [
+            "b",
+            "s",
+            "o",
+            "g",
+            "v",
+            "as",
+            "au",
+            "aas",
+            "av",
+            "(yu)",
+            "(sus)",
+            "(ysy)",
+            "(uv)",
+            "(s())",
+            "(msmu)",
+            "(ss(ss)s)",
+            "a{sv}",
+            "a(ysy)",
+            "a{sa{sv}}",
+            "maay",
+        ];
+
+        // Compare the normal form of pseudo random data with GLib
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut random = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let alphabet = b"\0\x01\x02\x03\x04\x05ab/(){}suvy\xff";
+
+        for signature in signatures {
+            let typ = glib::VariantTy::new(signature).unwrap();
+            for _ in 0..2000 {
+                let len = random() as usize % 40;
+                let data: Vec<u8> = (0..len)
+                    .map(|_| alphabet[random() as usize % alphabet.len()])
+                    .collect();
+
+                let variant = glib::Variant::from_bytes_with_type(&glib::Bytes::from(&data), typ);
+                assert_eq!(
+                    normal_form(&data, signature).unwrap(),
+                    variant.normal_form().data(),
+                    "{} {:?}",
+                    signature,
+                    data
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,718 @@
+use crate::util::{member_types, split_type};
+use crate::variant::error::{Error, Result};
+use crate::variant::normal::MAX_DEPTH;
+use zvariant::{Array, Dict, Maybe, ObjectPath, Signature, StructureBuilder, Value};
+
+/// The type keywords that can be used to annotate the type of the following value
+const TYPE_KEYWORDS: &[(&str, &str)] = &[
+    ("boolean", "b"),
+    ("byte", "y"),
+    ("int16", "n"),
+    ("uint16", "q"),
+    ("int32", "i"),
+    ("uint32", "u"),
+    ("int64", "x"),
+    ("uint64", "t"),
+    ("double", "d"),
+    ("string", "s"),
+    ("objectpath", "o"),
+    ("signature", "g"),
+];
+
+/// Parse `text` in the GVariant text format as a value of the GVariant type `typ`
+///
+/// This is the equivalent of `g_variant_parse()`. The type annotations written by [`print`]
+/// (e.g. `uint32 42` or `@as []`) are understood, so the output of [`print`] can always be
+/// parsed again.
+///
+/// ```
+/// let value = gvdb::variant::parse("(sua{sv})", "('abc', 42, {'key': <true>})").unwrap();
+/// let mut table_builder = gvdb::write::HashTableBuilder::new();
+/// table_builder.insert_value("value", value).unwrap();
+/// ```
+///
+/// [`print`]: crate::variant::print
+pub fn parse(typ: &str, text: &str) -> Result<Value<'static>> {
+    check_type_string(typ)?;
+
+    let mut parser = Parser {
+        text,
+        position: 0,
+        depth: 0,
+    };
+    let value = parser.value(Some(typ))?;
+    parser.skip_whitespace();
+    if parser.position < text.len() {
+        return Err(parser.error("Unexpected trailing characters".to_string()));
+    }
+
+    Ok(value)
+}
+
+/// Parse `text` in the GVariant text format and infer the type from the text
+///
+/// This is the equivalent of `g_variant_parse()` without a type. Numbers without type
+/// annotation are parsed as `i` or `d`, like in GLib. Empty arrays and `nothing` need a type
+/// annotation.
+///
+/// ```
+/// let value = gvdb::variant::parse_inferred("[(1, 'a'), (2, 'b')]").unwrap();
+/// assert_eq!(value.value_signature(), "a(is)");
+/// ```
+pub fn parse_inferred(text: &str) -> Result<Value<'static>> {
+    let mut parser = Parser {
+        text,
+        position: 0,
+        depth: 0,
+    };
+    let value = parser.value(None)?;
+    parser.skip_whitespace();
+    if parser.position < text.len() {
+        return Err(parser.error("Unexpected trailing characters".to_string()));
+    }
+
+    Ok(value)
+}
+
+/// Check that `typ` is a single complete type that zvariant accepts
+fn check_type_string(typ: &str) -> Result<()> {
+    if !typ.is_ascii() || split_type(typ) != Some((typ, "")) || Signature::try_from(typ).is_err() {
+        return Err(Error::InvalidType(typ.to_string()));
+    }
+
+    Ok(())
+}
+
+struct Parser<'t> {
+    text: &'t str,
+    position: usize,
+    /// The number of values that are currently being parsed
+    depth: usize,
+}
+
+impl<'t> Parser<'t> {
+    fn error(&self, message: String) -> Error {
+        Error::Parse {
+            position: self.position,
+            message,
+        }
+    }
+
+    fn rest(&self) -> &'t str {
+        &self.text[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected '{}'", c)))
+        }
+    }
+
+    /// The alphanumeric word at the current position
+    fn peek_word(&self) -> &'t str {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        &rest[..len]
+    }
+
+    fn check_type(&self, expected: Option<&str>, found: &str) -> Result<()> {
+        match expected {
+            Some(expected) if expected != found => Err(self.error(format!(
+                "Expected value of type '{}', found value of type '{}'",
+                expected, found
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Parse a value of type `expected`, or infer the type from the text if it is `None`
+    fn value(&mut self, expected: Option<&str>) -> Result<Value<'static>> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error("Maximum nesting depth exceeded".to_string()));
+        }
+
+        self.depth += 1;
+        let value = self.value_inner(expected);
+        self.depth -= 1;
+        value
+    }
+
+    fn value_inner(&mut self, expected: Option<&str>) -> Result<Value<'static>> {
+        self.skip_whitespace();
+
+        if self.eat('@') {
+            let rest = self.rest();
+            let len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let typ = &rest[..len];
+            check_type_string(typ)?;
+
+            self.check_type(expected, typ)?;
+            self.position += len;
+            return self.value(Some(typ));
+        }
+
+        let word = self.peek_word();
+        if let Some((_, typ)) = TYPE_KEYWORDS.iter().find(|(keyword, _)| *keyword == word) {
+            if !expected.is_some_and(|expected| expected.starts_with('m')) {
+                self.check_type(expected, typ)?;
+                self.position += word.len();
+                return self.value(Some(typ));
+            }
+        }
+
+        if let Some(inner) = expected.and_then(|typ| typ.strip_prefix('m')) {
+            return self.maybe(inner);
+        }
+
+        match word {
+            "true" | "false" => {
+                self.check_type(expected, "b")?;
+                self.position += word.len();
+                return Ok(Value::Bool(word == "true"));
+            }
+            "nothing" => {
+                return Err(self.error("Unable to infer the type of 'nothing'".to_string()))
+            }
+            "just" => {
+                self.position += word.len();
+                return Ok(Value::Maybe(Maybe::just(self.value(None)?)));
+            }
+            _ => {}
+        }
+
+        match self.peek() {
+            Some('<') => {
+                self.check_type(expected, "v")?;
+                self.position += 1;
+                let value = self.value(None)?;
+                self.expect('>')?;
+                Ok(Value::Value(Box::new(value)))
+            }
+            Some('[') => self.array(expected),
+            Some('{') => self.dict(expected),
+            Some('(') => self.tuple(expected),
+            Some('\'' | '"') => self.string(expected),
+            Some('b') if matches!(self.rest()[1..].chars().next(), Some('\'' | '"')) => {
+                self.check_type(expected, "ay")?;
+                self.position += 1;
+                let string = self.quoted()?;
+                let mut array = Array::new(Signature::from_static_str_unchecked("y"));
+                for byte in string.bytes().chain(std::iter::once(0)) {
+                    array.append(Value::U8(byte))?;
+                }
+
+                Ok(Value::Array(array))
+            }
+            Some(c) if c.is_ascii_digit() || matches!(c, '-' | '+' | '.') => self.number(expected),
+            Some(c) => Err(self.error(format!("Unexpected character '{}'", c))),
+            None => Err(self.error("Unexpected end of text".to_string())),
+        }
+    }
+
+    fn maybe(&mut self, inner: &str) -> Result<Value<'static>> {
+        match self.peek_word() {
+            "nothing" => {
+                self.position += "nothing".len();
+                Ok(Value::Maybe(Maybe::nothing(
+                    Signature::try_from(inner)?.to_owned(),
+                )))
+            }
+            "just" => {
+                self.position += "just".len();
+                Ok(Value::Maybe(Maybe::just(self.value(Some(inner))?)))
+            }
+            _ => Ok(Value::Maybe(Maybe::just(self.value(Some(inner))?))),
+        }
+    }
+
+    fn array(&mut self, expected: Option<&str>) -> Result<Value<'static>> {
+        let element_type = match expected {
+            Some(typ) if typ.starts_with("a{") => {
+                return Err(self.error(format!("Expected dictionary of type '{}'", typ)))
+            }
+            Some(typ) if typ.starts_with('a') => Some(typ[1..].to_string()),
+            Some(typ) => return Err(self.error(format!("Expected value of type '{}'", typ))),
+            None => None,
+        };
+
+        self.expect('[')?;
+        let mut elements = Vec::new();
+        let mut element_type = element_type;
+        if !self.eat(']') {
+            loop {
+                let element = self.value(element_type.as_deref())?;
+                if element_type.is_none() {
+                    element_type = Some(element.value_signature().to_string());
+                }
+
+                elements.push(element);
+                if self.eat(']') {
+                    break;
+                }
+
+                self.expect(',')?;
+            }
+        }
+
+        let element_type = element_type
+            .ok_or_else(|| self.error("Unable to infer the type of an empty array".to_string()))?;
+        let mut array = Array::new(Signature::try_from(element_type)?);
+        for element in elements {
+            array.append(element)?;
+        }
+
+        Ok(Value::Array(array))
+    }
+
+    fn dict(&mut self, expected: Option<&str>) -> Result<Value<'static>> {
+        let (mut key_type, mut value_type) = match expected {
+            Some(typ) if typ.starts_with("a{") => {
+                // The type string has already been validated
+                let types = member_types(&typ[1..]).unwrap_or_default();
+                (Some(types[0].to_string()), Some(types[1].to_string()))
+            }
+            Some(typ) => return Err(self.error(format!("Expected value of type '{}'", typ))),
+            None => (None, None),
+        };
+
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        if !self.eat('}') {
+            loop {
+                let key = self.value(key_type.as_deref())?;
+                let signature = key.value_signature().to_string();
+                if !matches!(
+                    signature.as_str(),
+                    "y" | "b" | "n" | "q" | "i" | "u" | "x" | "t" | "d" | "s" | "o" | "g"
+                ) {
+                    return Err(self.error(format!(
+                        "Dictionary keys must be of a basic type, found '{}'",
+                        signature
+                    )));
+                }
+
+                key_type.get_or_insert(signature);
+                self.expect(':')?;
+                let value = self.value(value_type.as_deref())?;
+                value_type.get_or_insert_with(|| value.value_signature().to_string());
+                entries.push((key, value));
+
+                if self.eat('}') {
+                    break;
+                }
+
+                self.expect(',')?;
+            }
+        }
+
+        let (Some(key_type), Some(value_type)) = (key_type, value_type) else {
+            return Err(self.error("Unable to infer the type of an empty dictionary".to_string()));
+        };
+
+        let mut dict = Dict::new(
+            Signature::try_from(key_type)?,
+            Signature::try_from(value_type)?,
+        );
+        for (key, value) in entries {
+            dict.append(key, value)?;
+        }
+
+        Ok(Value::Dict(dict))
+    }
+
+    fn tuple(&mut self, expected: Option<&str>) -> Result<Value<'static>> {
+        let types = match expected {
+            // The type string has already been validated
+            Some(typ) if typ.starts_with('(') => Some(member_types(typ).unwrap_or_default()),
+            Some(typ) => return Err(self.error(format!("Expected value of type '{}'", typ))),
+            None => None,
+        };
+
+        self.expect('(')?;
+        let mut fields = Vec::new();
+        if !self.eat(')') {
+            loop {
+                let typ = match &types {
+                    Some(types) => Some(*types.get(fields.len()).ok_or_else(|| {
+                        self.error(format!("Too many fields for type '{}'", expected.unwrap()))
+                    })?),
+                    None => None,
+                };
+
+                fields.push(self.value(typ)?);
+                if self.eat(')') {
+                    break;
+                }
+
+                self.expect(',')?;
+                if self.eat(')') {
+                    break;
+                }
+            }
+        }
+
+        if types.is_some_and(|types| types.len() != fields.len()) {
+            return Err(self.error(format!("Too few fields for type '{}'", expected.unwrap())));
+        }
+
+        if fields.is_empty() {
+            return Err(self.error("The unit type '()' is not supported".to_string()));
+        }
+
+        let mut builder = StructureBuilder::new();
+        for field in fields {
+            builder = builder.append_field(field);
+        }
+
+        Ok(Value::Structure(builder.build()))
+    }
+
+    fn string(&mut self, expected: Option<&str>) -> Result<Value<'static>> {
+        let position = self.position;
+        let string = self.quoted()?;
+        let value = match expected {
+            None | Some("s") => Value::from(string),
+            Some("o") => Value::ObjectPath(ObjectPath::try_from(string)?),
+            Some("g") => Value::Signature(Signature::try_from(string)?),
+            Some(typ) => {
+                return Err(Error::Parse {
+                    position,
+                    message: format!("Expected value of type '{}', found string", typ),
+                })
+            }
+        };
+
+        Ok(value)
+    }
+
+    /// Parse a string in single or double quotes
+    fn quoted(&mut self) -> Result<String> {
+        let quote = self.peek().unwrap_or_default();
+        self.position += 1;
+
+        let mut string = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('v') => '\u{b}',
+                        Some('a') => '\u{7}',
+                        Some('0') => '\0',
+                        Some(c @ ('u' | 'U')) => {
+                            let digits: String =
+                                if chars.clone().next().map(|(_, c)| c) == Some('{') {
+                                    chars.next();
+                                    chars
+                                        .by_ref()
+                                        .map(|(_, c)| c)
+                                        .take_while(|c| *c != '}')
+                                        .collect()
+                                } else {
+                                    let len = if c == 'u' { 4 } else { 8 };
+                                    chars.by_ref().take(len).map(|(_, c)| c).collect()
+                                };
+
+                            u32::from_str_radix(&digits, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| {
+                                    self.error(format!("Invalid unicode escape '{}'", digits))
+                                })?
+                        }
+                        Some(c) => c,
+                        None => break,
+                    };
+
+                    string.push(escaped);
+                }
+                c if c == quote => {
+                    self.position += index + 1;
+                    return Ok(string);
+                }
+                c => string.push(c),
+            }
+        }
+
+        self.position = self.text.len();
+        Err(self.error("Unterminated string".to_string()))
+    }
+
+    fn number(&mut self, expected: Option<&str>) -> Result<Value<'static>> {
+        let rest = self.rest();
+        let mut len = 0;
+        for (index, c) in rest.char_indices() {
+            let exponent_sign = matches!(c, '-' | '+')
+                && index > 0
+                && matches!(rest.as_bytes()[index - 1], b'e' | b'E')
+                && !rest.starts_with("0x");
+            if c.is_ascii_alphanumeric() || c == '.' || exponent_sign || index == 0 {
+                len = index + c.len_utf8();
+            } else {
+                break;
+            }
+        }
+
+        let token = &rest[..len];
+        let is_hex = token.trim_start_matches(['-', '+']).starts_with("0x");
+        let is_float = !is_hex && token.contains(['.', 'e', 'E']);
+        let typ = expected.unwrap_or(if is_float { "d" } else { "i" });
+
+        let value = if typ == "d" {
+            token.parse().ok().map(Value::F64)
+        } else if is_float {
+            None
+        } else {
+            let (negative, digits) = match token.as_bytes().first() {
+                Some(b'-') => (true, &token[1..]),
+                Some(b'+') => (false, &token[1..]),
+                _ => (false, token),
+            };
+            let number = match digits.strip_prefix("0x") {
+                Some(hex) => i128::from_str_radix(hex, 16),
+                None => digits.parse::<i128>(),
+            }
+            .ok()
+            .map(|number| if negative { -number } else { number });
+
+            number.and_then(|number| match typ {
+                "y" => u8::try_from(number).ok().map(Value::U8),
+                "n" => i16::try_from(number).ok().map(Value::I16),
+                "q" => u16::try_from(number).ok().map(Value::U16),
+                "i" => i32::try_from(number).ok().map(Value::I32),
+                "u" => u32::try_from(number).ok().map(Value::U32),
+                "x" => i64::try_from(number).ok().map(Value::I64),
+                "t" => u64::try_from(number).ok().map(Value::U64),
+                _ => None,
+            })
+        };
+
+        let value = value.ok_or_else(|| {
+            self.error(format!(
+                "Unable to parse '{}' as value of type '{}'",
+                token, typ
+            ))
+        })?;
+
+        self.position += len;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse, parse_inferred, MAX_DEPTH};
+    use crate::variant::{print, Error};
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::collections::HashMap;
+    use zvariant::{Maybe, ObjectPath, Signature, Value};
+
+    #[test]
+    fn basic() {
+        assert_eq!(parse("b", "true").unwrap(), Value::Bool(true));
+        assert_eq!(parse("y", "0x2a").unwrap(), Value::U8(42));
+        assert_eq!(parse("n", "-42").unwrap(), Value::I16(-42));
+        assert_eq!(parse("q", "uint16 42").unwrap(), Value::U16(42));
+        assert_eq!(parse("i", " -0x10 ").unwrap(), Value::I32(-16));
+        assert_eq!(parse("u", "@u 42").unwrap(), Value::U32(42));
+        assert_eq!(parse("x", "-9000000000").unwrap(), Value::I64(-9000000000));
+        assert_eq!(
+            parse("t", "18446744073709551615").unwrap(),
+            Value::U64(u64::MAX)
+        );
+        assert_eq!(parse("d", "1").unwrap(), Value::F64(1.0));
+        assert_eq!(parse("d", "-1.5e-3").unwrap(), Value::F64(-1.5e-3));
+        assert_eq!(
+            parse("s", r#"'it\'s "text"'"#).unwrap(),
+            Value::from("it's \"text\"")
+        );
+        assert_eq!(
+            parse("s", r#""tab\tä\u{1F600}""#).unwrap(),
+            Value::from("tab\tä😀")
+        );
+        assert_eq!(
+            parse("o", "objectpath '/org/gtk'").unwrap(),
+            Value::ObjectPath(ObjectPath::try_from("/org/gtk").unwrap())
+        );
+        assert_eq!(
+            parse("g", "'a{sv}'").unwrap(),
+            Value::Signature(Signature::try_from("a{sv}").unwrap())
+        );
+    }
+
+    #[test]
+    fn containers() {
+        let value = parse("(sua{sv})", "('abc', 42, {'key': <true>})").unwrap();
+        let (string, int, dict): (String, u32, HashMap<String, Value>) = value.try_into().unwrap();
+        assert_eq!(string, "abc");
+        assert_eq!(int, 42);
+        assert_eq!(dict["key"], Value::Bool(true));
+
+        assert_eq!(
+            parse("ai", "[1, 2, 3]").unwrap(),
+            Value::new(vec![1i32, 2, 3])
+        );
+        assert_eq!(
+            parse("as", "@as []").unwrap(),
+            Value::new(Vec::<String>::new())
+        );
+        assert_eq!(
+            parse("ay", "b'abc'").unwrap(),
+            Value::new(b"abc\0".to_vec())
+        );
+        assert_eq!(parse("(s)", "('abc',)").unwrap(), Value::new(("abc",)));
+        assert_eq!(
+            parse("ms", "nothing").unwrap(),
+            Value::Maybe(Maybe::nothing(Signature::try_from("s").unwrap()))
+        );
+        assert_eq!(
+            parse("ms", "just 'abc'").unwrap(),
+            Value::Maybe(Maybe::just(Value::from("abc")))
+        );
+        assert_eq!(
+            parse("mu", "uint32 42").unwrap(),
+            Value::Maybe(Maybe::just(Value::U32(42)))
+        );
+
+        // Types are inferred inside of variants
+        let value = parse("v", "<[(1, 'a'), (2, 'b')]>").unwrap();
+        assert_eq!(value, Value::new(Value::new(vec![(1i32, "a"), (2, "b")])));
+        let value = parse("v", "<{uint32 1: [1.5]}>").unwrap();
+        assert_eq!(value.to_string(), "<{uint32 1: [1.5]}>");
+    }
+
+    #[test]
+    fn round_trip() {
+        let texts = [
+            (
+                "a{sv}",
+                "{'a': <int16 -1>, 'b': <@mas nothing>, 'c': <objectpath '/'>}",
+            ),
+            (
+                "(ybnqiuxtdsogv)",
+                "(0x01, true, 2, 3, 4, 5, 6, 7, 8.5, 'a', '/b', 'as', <'d'>)",
+            ),
+            ("aa{ias}", "[{1: ['a', 'b']}, @a{ias} {}]"),
+            ("mmu", "just nothing"),
+            ("a(ss)", "[('it\\'s', 'new\\nline')]"),
+        ];
+
+        for (typ, text) in texts {
+            let value = parse(typ, text).unwrap();
+            assert_eq!(value.value_signature().as_str(), typ);
+            for annotate in [true, false] {
+                let printed = print(&value, annotate);
+                assert_eq!(parse(typ, &printed).unwrap(), value, "{}", printed);
+            }
+        }
+    }
+
+    #[test]
+    fn inferred() {
+        assert_eq!(parse_inferred("42").unwrap(), Value::I32(42));
+        assert_eq!(parse_inferred("4.2").unwrap(), Value::F64(4.2));
+        assert_eq!(parse_inferred("uint64 1").unwrap(), Value::U64(1));
+        assert_eq!(
+            parse_inferred("['a', 'b']").unwrap(),
+            Value::new(vec!["a", "b"])
+        );
+        assert_eq!(
+            parse_inferred("just 'a'").unwrap(),
+            Value::Maybe(Maybe::just(Value::from("a")))
+        );
+        assert_matches!(parse_inferred("[]"), Err(Error::Parse { .. }));
+        assert_matches!(parse_inferred("1 2"), Err(Error::Parse { position: 2, .. }));
+    }
+
+    #[test]
+    fn errors() {
+        let error = |typ, text| match parse(typ, text) {
+            Err(Error::Parse { position, .. }) => position,
+            res => panic!("Expected parse error, got {:?}", res),
+        };
+
+        assert_eq!(error("u", "-1"), 0);
+        assert_eq!(error("y", "256"), 0);
+        assert_eq!(error("i", "1.5"), 0);
+        assert_eq!(error("s", "42"), 0);
+        assert_eq!(error("s", "'abc"), 4);
+        assert_eq!(error("(su)", "('a', 'b')"), 6);
+        assert_eq!(error("(su)", "('a', 1, 2)"), 9);
+        assert_eq!(error("(su)", "('a',)"), 6);
+        assert_eq!(error("ai", "[1 2]"), 3);
+        assert_eq!(error("u", "1 2"), 2);
+        assert_eq!(error("u", "uint16 1"), 0);
+        assert_eq!(error("v", "<[]>"), 3);
+        assert_eq!(error("v", "<nothing>"), 1);
+        assert_eq!(error("a{sv}", "['a']"), 0);
+        assert_eq!(error("v", "<{[1]: 2}>"), 5);
+        assert_eq!(error("u", ""), 0);
+        assert_eq!(error("u", "?"), 0);
+
+        assert_matches!(parse("uu", "1"), Err(Error::InvalidType(_)));
+        assert_matches!(parse("(u", "1"), Err(Error::InvalidType(_)));
+        assert_matches!(parse("é", "1"), Err(Error::InvalidType(_)));
+        assert_matches!(parse("(é)", "(1)"), Err(Error::InvalidType(_)));
+        assert_matches!(parse_inferred("@é 1"), Err(Error::InvalidType(_)));
+        assert_matches!(parse_inferred("@(u 1"), Err(Error::InvalidType(_)));
+        assert_matches!(parse("h", "1"), Err(_));
+        assert_matches!(parse("o", "'not a path'"), Err(Error::ZVariant(_)));
+    }
+
+    #[test]
+    fn depth() {
+        let nested = format!(
+            "{}1{}",
+            "[".repeat(MAX_DEPTH - 1),
+            "]".repeat(MAX_DEPTH - 1)
+        );
+        assert!(parse_inferred(&nested).is_ok());
+
+        let nested = format!("{}1{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert_matches!(parse_inferred(&nested), Err(Error::Parse { .. }));
+
+        assert_matches!(
+            parse_inferred(&"[".repeat(100000)),
+            Err(Error::Parse { .. })
+        );
+        assert_matches!(
+            parse_inferred(&"<".repeat(100000)),
+            Err(Error::Parse { .. })
+        );
+        assert_matches!(
+            parse_inferred(&"@u ".repeat(100000)),
+            Err(Error::Parse { .. })
+        );
+        assert_matches!(parse("v", &"<".repeat(100000)), Err(Error::Parse { .. }));
+    }
+}
@@ -0,0 +1,231 @@
+use super::Value;
+use std::fmt::Write;
+
+/// Serialize `value` to the GVariant text format
+///
+/// This is the equivalent of `g_variant_print()`. If `type_annotate` is `true`, type information
+/// is added wherever the type of a value can't be inferred from the text alone, so the result
+/// can be parsed again without knowing the type, e.g. inside of a variant. The
+/// [`Display`](std::fmt::Display) implementation of [`zvariant::Value`] produces a similar
+/// format with type annotations. [`Value`] is displayed exactly like `print(value, true)`.
+///
+/// ```
+/// let value = zvariant::Value::new(("abc", 42u32, vec![1i32, 2, 3]));
+/// assert_eq!(gvdb::variant::print(&value, true), "('abc', uint32 42, [1, 2, 3])");
+/// assert_eq!(gvdb::variant::print(&value, false), "('abc', 42, [1, 2, 3])");
+/// ```
+pub fn print(value: &zvariant::Value, type_annotate: bool) -> String {
+    let mut text = String::new();
+    print_value(&mut text, &Value::from(value), type_annotate);
+    text
+}
+
+fn print_string(text: &mut String, string: &str) {
+    // Prefer single quotes like GLib, unless the string contains single quotes only
+    let quote = if string.contains('\'') && !string.contains('"') {
+        '"'
+    } else {
+        '\''
+    };
+
+    text.push(quote);
+    for c in string.chars() {
+        match c {
+            '\\' => text.push_str("\\\\"),
+            '\n' => text.push_str("\\n"),
+            '\t' => text.push_str("\\t"),
+            '\r' => text.push_str("\\r"),
+            c if c == quote => {
+                text.push('\\');
+                text.push(c);
+            }
+            c if c.is_control() => {
+                let _ = write!(text, "\\u{:04x}", c as u32);
+            }
+            c => text.push(c),
+        }
+    }
+    text.push(quote);
+}
+
+/// Append the text format of `value` to `text`, see [`print`]
+pub(super) fn print_value(text: &mut String, value: &Value, type_annotate: bool) {
+    let annotation = match value {
+        Value::U8(_) => "byte ",
+        Value::I16(_) => "int16 ",
+        Value::U16(_) => "uint16 ",
+        Value::U32(_) => "uint32 ",
+        Value::I64(_) => "int64 ",
+        Value::U64(_) => "uint64 ",
+        Value::Handle(_) => "handle ",
+        Value::Signature(_) => "signature ",
+        Value::ObjectPath(_) => "objectpath ",
+        _ => "",
+    };
+
+    if type_annotate {
+        text.push_str(annotation);
+    }
+
+    match value {
+        Value::U8(v) => {
+            let _ = write!(text, "0x{:02x}", v);
+        }
+        Value::Bool(v) => {
+            let _ = write!(text, "{}", v);
+        }
+        Value::I16(v) => {
+            let _ = write!(text, "{}", v);
+        }
+        Value::U16(v) => {
+            let _ = write!(text, "{}", v);
+        }
+        Value::I32(v) | Value::Handle(v) => {
+            let _ = write!(text, "{}", v);
+        }
+        Value::U32(v) => {
+            let _ = write!(text, "{}", v);
+        }
+        Value::I64(v) => {
+            let _ = write!(text, "{}", v);
+        }
+        Value::U64(v) => {
+            let _ = write!(text, "{}", v);
+        }
+        Value::F64(v) => {
+            // The debug representation always contains a '.' or an exponent
+            let _ = write!(text, "{:?}", v);
+        }
+        Value::String(v) | Value::Signature(v) | Value::ObjectPath(v) => print_string(text, v),
+        Value::Variant(v) => {
+            text.push('<');
+            // The type of a nested variant can't be inferred from the outer type
+            print_value(text, v, true);
+            text.push('>');
+        }
+        Value::Array { values, .. } => {
+            if values.is_empty() {
+                if type_annotate {
+                    let _ = write!(text, "@{} ", value.signature());
+                }
+                text.push_str("[]");
+            } else {
+                text.push('[');
+                for (index, element) in values.iter().enumerate() {
+                    if index > 0 {
+                        text.push_str(", ");
+                    }
+
+                    // The type of the other elements follows from the first element
+                    print_value(text, element, type_annotate && index == 0);
+                }
+                text.push(']');
+            }
+        }
+        Value::Dict { entries, .. } => {
+            if entries.is_empty() {
+                if type_annotate {
+                    let _ = write!(text, "@{} ", value.signature());
+                }
+                text.push_str("{}");
+            } else {
+                text.push('{');
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 {
+                        text.push_str(", ");
+                    }
+
+                    print_value(text, key, type_annotate && index == 0);
+                    text.push_str(": ");
+                    print_value(text, value, type_annotate && index == 0);
+                }
+                text.push('}');
+            }
+        }
+        Value::DictEntry(key, value) => {
+            text.push('{');
+            print_value(text, key, type_annotate);
+            text.push_str(", ");
+            print_value(text, value, type_annotate);
+            text.push('}');
+        }
+        Value::Tuple(fields) => {
+            text.push('(');
+            for (index, field) in fields.iter().enumerate() {
+                if index > 0 {
+                    text.push_str(", ");
+                }
+
+                print_value(text, field, type_annotate);
+            }
+
+            if fields.len() == 1 {
+                text.push(',');
+            }
+            text.push(')');
+        }
+        Value::Maybe { value: inner, .. } => {
+            if type_annotate {
+                let _ = write!(text, "@{} ", value.signature());
+            }
+
+            // Nested maybes are only distinguishable if a 'just' prefix is written for each
+            // level that contains 'nothing'
+            let mut inner = inner;
+            let mut depth = 0;
+            while let Some(Value::Maybe { value, .. }) = inner.as_deref() {
+                inner = value;
+                depth += 1;
+            }
+
+            match inner {
+                Some(inner) => print_value(text, inner, false),
+                None => {
+                    text.push_str(&"just ".repeat(depth));
+                    text.push_str("nothing");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::print;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::collections::HashMap;
+    use zvariant::{Maybe, Signature, Value};
+
+    #[test]
+    fn print_values() {
+        let value = Value::new((1u8, true, -2i16, 3u16, 4i32, 5u32, -6i64, 7u64, 8.0f64));
+        assert_eq!(
+            print(&value, true),
+            "(byte 0x01, true, int16 -2, uint16 3, 4, uint32 5, int64 -6, uint64 7, 8.0)"
+        );
+        assert_eq!(
+            print(&value, false),
+            "(0x01, true, -2, 3, 4, 5, -6, 7, 8.0)"
+        );
+
+        assert_eq!(print(&Value::from("it's"), true), "\"it's\"");
+        assert_eq!(print(&Value::from("'\"\n"), true), "'\\'\"\\n'");
+        assert_eq!(print(&Value::new(("a",)), true), "('a',)");
+        assert_eq!(
+            print(&Value::new(Value::new(vec![1u32, 2])), false),
+            "<[uint32 1, 2]>"
+        );
+        assert_eq!(print(&Value::new(Vec::<String>::new()), true), "@as []");
+
+        let dict: HashMap<&str, u32> = HashMap::from([("a", 1)]);
+        assert_eq!(print(&Value::new(dict), true), "{'a': uint32 1}");
+
+        let maybe = Value::Maybe(Maybe::just(Value::Maybe(Maybe::nothing(
+            Signature::try_from("s").unwrap(),
+        ))));
+        assert_eq!(print(&maybe, true), "@mms just nothing");
+        let maybe = Value::Maybe(Maybe::just(Value::from("a")));
+        assert_eq!(print(&maybe, false), "'a'");
+    }
+}
@@ -0,0 +1,663 @@
+use super::normal::{offset_size, read_offset, type_info};
+use super::print::print_value;
+use super::{normal_form, Error, Result};
+use crate::util::{align_offset, member_types};
+use crate::Endian;
+use zvariant::{Array, Dict, Maybe, ObjectPath, Signature, StructureBuilder};
+
+/// A GVariant value that can be inspected with pattern matching
+///
+/// In contrast to [`zvariant::Value`], this is a plain enum that owns its data and can be
+/// created from serialized data with a type string that is only known at runtime, e.g. to show
+/// the contents of a file in a debugging tool. Containers store the types of their elements, so
+/// the type of empty arrays and `Nothing` values is not lost. Values can be converted from and
+/// to [`zvariant::Value`] and are displayed in the GVariant text format, like with
+/// [`print`](super::print).
+///
+/// ```
+/// # use std::borrow::Cow;
+/// use gvdb::read::File;
+/// use gvdb::variant::Value;
+/// use gvdb::write::{FileWriter, HashTableBuilder};
+///
+/// let mut table_builder = HashTableBuilder::new();
+/// table_builder.insert("pair", ("abc", 42u32)).unwrap();
+/// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+///
+/// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+/// let (_, data) = file.hash_table().unwrap().get_raw("pair").unwrap();
+///
+/// // Values in GVDB files are stored as variants
+/// let Value::Variant(value) = Value::from_data(data, "v", file.endianness()).unwrap() else {
+///     panic!("Expected a variant");
+/// };
+///
+/// assert_eq!(value.signature(), "(su)");
+/// assert_eq!(value.to_string(), "('abc', uint32 42)");
+/// match *value {
+///     Value::Tuple(members) => {
+///         assert_eq!(members[0], Value::String("abc".to_string()));
+///         assert_eq!(members[1], Value::U32(42));
+///     }
+///     _ => panic!("Expected a tuple"),
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// A boolean, `b`
+    Bool(bool),
+    /// A byte, `y`
+    U8(u8),
+    /// A signed 16 bit integer, `n`
+    I16(i16),
+    /// An unsigned 16 bit integer, `q`
+    U16(u16),
+    /// A signed 32 bit integer, `i`
+    I32(i32),
+    /// An unsigned 32 bit integer, `u`
+    U32(u32),
+    /// A signed 64 bit integer, `x`
+    I64(i64),
+    /// An unsigned 64 bit integer, `t`
+    U64(u64),
+    /// A file descriptor handle, `h`
+    Handle(i32),
+    /// A double precision floating point number, `d`
+    F64(f64),
+    /// A string, `s`
+    String(String),
+    /// A D-Bus object path, `o`
+    ObjectPath(String),
+    /// A type signature, `g`
+    Signature(String),
+
+    /// An array of values of the same type, `a*`
+    ///
+    /// Arrays of dictionary entries are stored as [`Value::Dict`].
+    Array {
+        /// The type of the elements
+        element_type: String,
+        /// The elements
+        values: Vec<Value>,
+    },
+
+    /// A tuple, `(...)`
+    Tuple(Vec<Value>),
+
+    /// A single dictionary entry outside of an array, `{**}`
+    DictEntry(Box<Value>, Box<Value>),
+
+    /// An array of dictionary entries, `a{**}`
+    Dict {
+        /// The type of the keys
+        key_type: String,
+        /// The type of the values
+        value_type: String,
+        /// The entries in the order in which they are stored
+        entries: Vec<(Value, Value)>,
+    },
+
+    /// An optional value, `m*`
+    Maybe {
+        /// The type of the value
+        element_type: String,
+        /// The value, or `None` for `Nothing`
+        value: Option<Box<Value>>,
+    },
+
+    /// A value of any type, `v`
+    Variant(Box<Value>),
+}
+
+macro_rules! read_number {
+    ($data:expr, $endian:expr, $typ:ty) => {{
+        let bytes = $data.try_into().unwrap();
+        match $endian {
+            Endian::Little => <$typ>::from_le_bytes(bytes),
+            Endian::Big => <$typ>::from_be_bytes(bytes),
+        }
+    }};
+}
+
+impl Value {
+    /// Read the serialized GVariant `data` of type `signature` in the byte order `endian`
+    ///
+    /// The data is converted to its normal form first, see [`normal_form`], so invalid values
+    /// are read as the default value of their type like in GLib. Returns
+    /// [`Error::InvalidType`](super::Error::InvalidType) if `signature` is not a single complete
+    /// type.
+    pub fn from_data(data: &[u8], signature: &str, endian: Endian) -> Result<Self> {
+        let data = normal_form(data, signature)?;
+        Ok(Self::decode(signature, &data, endian))
+    }
+
+    /// Decode `data` of the valid type `typ`, which has to be in normal form
+    fn decode(typ: &str, data: &[u8], endian: Endian) -> Self {
+        match typ {
+            "b" => Self::Bool(data[0] != 0),
+            "y" => Self::U8(data[0]),
+            "n" => Self::I16(read_number!(data, endian, i16)),
+            "q" => Self::U16(read_number!(data, endian, u16)),
+            "i" => Self::I32(read_number!(data, endian, i32)),
+            "u" => Self::U32(read_number!(data, endian, u32)),
+            "x" => Self::I64(read_number!(data, endian, i64)),
+            "t" => Self::U64(read_number!(data, endian, u64)),
+            "h" => Self::Handle(read_number!(data, endian, i32)),
+            "d" => Self::F64(read_number!(data, endian, f64)),
+            "s" => Self::String(decode_string(data)),
+            "o" => Self::ObjectPath(decode_string(data)),
+            "g" => Self::Signature(decode_string(data)),
+            "v" => {
+                let separator = data.iter().rposition(|byte| *byte == 0).unwrap();
+                let child_type = std::str::from_utf8(&data[separator + 1..]).unwrap();
+                Self::Variant(Box::new(Self::decode(
+                    child_type,
+                    &data[..separator],
+                    endian,
+                )))
+            }
+            _ if typ.starts_with("a{") => {
+                let members = member_types(&typ[1..]).unwrap();
+                let entries = decode_array(&typ[1..], data, endian)
+                    .into_iter()
+                    .map(|entry| match entry {
+                        Self::DictEntry(key, value) => (*key, *value),
+                        _ => unreachable!(),
+                    })
+                    .collect();
+
+                Self::Dict {
+                    key_type: members[0].to_string(),
+                    value_type: members[1].to_string(),
+                    entries,
+                }
+            }
+            _ if typ.starts_with('a') => Self::Array {
+                element_type: typ[1..].to_string(),
+                values: decode_array(&typ[1..], data, endian),
+            },
+            _ if typ.starts_with('m') => {
+                let element = &typ[1..];
+                let value = match type_info(element, 0).unwrap().fixed_size {
+                    _ if data.is_empty() => None,
+                    Some(_) => Some(Self::decode(element, data, endian)),
+                    None => Some(Self::decode(element, &data[..data.len() - 1], endian)),
+                };
+
+                Self::Maybe {
+                    element_type: element.to_string(),
+                    value: value.map(Box::new),
+                }
+            }
+            _ if typ.starts_with('{') => {
+                let mut members = decode_tuple(typ, data, endian).into_iter();
+                let key = members.next().unwrap();
+                let value = members.next().unwrap();
+                Self::DictEntry(Box::new(key), Box::new(value))
+            }
+            _ => Self::Tuple(decode_tuple(typ, data, endian)),
+        }
+    }
+
+    /// The GVariant type string of the value
+    ///
+    /// ```
+    /// use gvdb::variant::Value;
+    ///
+    /// let value = Value::Tuple(vec![Value::U8(1), Value::String("abc".to_string())]);
+    /// assert_eq!(value.signature(), "(ys)");
+    /// ```
+    pub fn signature(&self) -> String {
+        match self {
+            Self::Bool(_) => "b".to_string(),
+            Self::U8(_) => "y".to_string(),
+            Self::I16(_) => "n".to_string(),
+            Self::U16(_) => "q".to_string(),
+            Self::I32(_) => "i".to_string(),
+            Self::U32(_) => "u".to_string(),
+            Self::I64(_) => "x".to_string(),
+            Self::U64(_) => "t".to_string(),
+            Self::Handle(_) => "h".to_string(),
+            Self::F64(_) => "d".to_string(),
+            Self::String(_) => "s".to_string(),
+            Self::ObjectPath(_) => "o".to_string(),
+            Self::Signature(_) => "g".to_string(),
+            Self::Array { element_type, .. } => format!("a{}", element_type),
+            Self::Tuple(members) => {
+                let members: String = members.iter().map(|member| member.signature()).collect();
+                format!("({})", members)
+            }
+            Self::DictEntry(key, value) => format!("{{{}{}}}", key.signature(), value.signature()),
+            Self::Dict {
+                key_type,
+                value_type,
+                ..
+            } => format!("a{{{}{}}}", key_type, value_type),
+            Self::Maybe { element_type, .. } => format!("m{}", element_type),
+            Self::Variant(_) => "v".to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    /// Write the value in the GVariant text format with type annotations, see [`print`]
+    ///
+    /// [`print`]: super::print
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut text = String::new();
+        print_value(&mut text, self, true);
+        f.write_str(&text)
+    }
+}
+
+impl From<&zvariant::Value<'_>> for Value {
+    fn from(value: &zvariant::Value<'_>) -> Self {
+        match value {
+            zvariant::Value::Bool(v) => Self::Bool(*v),
+            zvariant::Value::U8(v) => Self::U8(*v),
+            zvariant::Value::I16(v) => Self::I16(*v),
+            zvariant::Value::U16(v) => Self::U16(*v),
+            zvariant::Value::I32(v) => Self::I32(*v),
+            zvariant::Value::U32(v) => Self::U32(*v),
+            zvariant::Value::I64(v) => Self::I64(*v),
+            zvariant::Value::U64(v) => Self::U64(*v),
+            zvariant::Value::F64(v) => Self::F64(*v),
+            zvariant::Value::Str(v) => Self::String(v.to_string()),
+            zvariant::Value::Signature(v) => Self::Signature(v.to_string()),
+            zvariant::Value::ObjectPath(v) => Self::ObjectPath(v.to_string()),
+            zvariant::Value::Value(v) => Self::Variant(Box::new(Self::from(&**v))),
+            zvariant::Value::Array(array) => Self::Array {
+                element_type: array.element_signature().to_string(),
+                values: array.iter().map(Self::from).collect(),
+            },
+            zvariant::Value::Dict(dict) => {
+                let signature = dict.full_signature();
+                let members = member_types(&signature[1..]).unwrap();
+                Self::Dict {
+                    key_type: members[0].to_string(),
+                    value_type: members[1].to_string(),
+                    entries: dict
+                        .iter()
+                        .map(|(key, value)| (Self::from(key), Self::from(value)))
+                        .collect(),
+                }
+            }
+            zvariant::Value::Structure(structure) => {
+                Self::Tuple(structure.fields().iter().map(Self::from).collect())
+            }
+            zvariant::Value::Maybe(maybe) => Self::Maybe {
+                element_type: maybe.value_signature().to_string(),
+                value: maybe.inner().as_ref().map(|v| Box::new(Self::from(v))),
+            },
+            #[cfg(unix)]
+            zvariant::Value::Fd(fd) => {
+                use std::os::fd::AsRawFd;
+                Self::Handle(fd.as_raw_fd())
+            }
+        }
+    }
+}
+
+impl TryFrom<&Value> for zvariant::Value<'static> {
+    type Error = Error;
+
+    /// Convert `value` to a [`zvariant::Value`]
+    ///
+    /// Handles and dictionary entries outside of a dictionary have no equivalent in zvariant and
+    /// return [`Error::InvalidType`].
+    fn try_from(value: &Value) -> Result<Self> {
+        let signature = |typ: &str| Ok::<_, Error>(Signature::try_from(typ)?.to_owned());
+
+        Ok(match value {
+            Value::Bool(v) => Self::Bool(*v),
+            Value::U8(v) => Self::U8(*v),
+            Value::I16(v) => Self::I16(*v),
+            Value::U16(v) => Self::U16(*v),
+            Value::I32(v) => Self::I32(*v),
+            Value::U32(v) => Self::U32(*v),
+            Value::I64(v) => Self::I64(*v),
+            Value::U64(v) => Self::U64(*v),
+            Value::F64(v) => Self::F64(*v),
+            Value::String(v) => Self::from(v.clone()),
+            Value::ObjectPath(v) => Self::ObjectPath(ObjectPath::try_from(v.clone())?),
+            Value::Signature(v) => Self::Signature(Signature::try_from(v.clone())?),
+            Value::Variant(v) => Self::Value(Box::new(Self::try_from(&**v)?)),
+            Value::Array {
+                element_type,
+                values,
+            } => {
+                let mut array = Array::new(signature(element_type)?);
+                for element in values {
+                    array.append(Self::try_from(element)?)?;
+                }
+                Self::Array(array)
+            }
+            Value::Dict {
+                key_type,
+                value_type,
+                entries,
+            } => {
+                let mut dict = Dict::new(signature(key_type)?, signature(value_type)?);
+                for (key, value) in entries {
+                    dict.append(Self::try_from(key)?, Self::try_from(value)?)?;
+                }
+                Self::Dict(dict)
+            }
+            Value::Tuple(fields) => {
+                let mut builder = StructureBuilder::new();
+                for field in fields {
+                    builder = builder.append_field(Self::try_from(field)?);
+                }
+                Self::Structure(builder.build())
+            }
+            Value::Maybe {
+                element_type,
+                value: Some(value),
+            } => {
+                let value = Self::try_from(&**value)?;
+                if value.value_signature() != element_type.as_str() {
+                    return Err(Error::InvalidType(value.value_signature().to_string()));
+                }
+                Self::Maybe(Maybe::just(value))
+            }
+            Value::Maybe {
+                element_type,
+                value: None,
+            } => Self::Maybe(Maybe::nothing(signature(element_type)?)),
+            Value::Handle(_) | Value::DictEntry(..) => {
+                return Err(Error::InvalidType(value.signature()))
+            }
+        })
+    }
+}
+
+/// The string in the zero-terminated and valid UTF-8 `data`
+fn decode_string(data: &[u8]) -> String {
+    String::from_utf8_lossy(&data[..data.len() - 1]).into_owned()
+}
+
+fn decode_array(element: &str, data: &[u8], endian: Endian) -> Vec<Value> {
+    let info = type_info(element, 0).unwrap();
+    if let Some(size) = info.fixed_size {
+        return data
+            .chunks_exact(size)
+            .map(|child| Value::decode(element, child, endian))
+            .collect();
+    }
+
+    if data.is_empty() {
+        return Vec::new();
+    }
+
+    let size = offset_size(data.len());
+    let offsets_start = read_offset(data, data.len() - size, size).unwrap();
+    let mut values = Vec::new();
+    let mut previous_end = 0;
+    for position in (offsets_start..data.len()).step_by(size) {
+        let end = read_offset(data, position, size).unwrap();
+        let start = align_offset(previous_end, info.alignment);
+        values.push(Value::decode(element, &data[start..end], endian));
+        previous_end = end;
+    }
+
+    values
+}
+
+fn decode_tuple(typ: &str, data: &[u8], endian: Endian) -> Vec<Value> {
+    let members = member_types(typ).unwrap();
+    let size = offset_size(data.len());
+    let mut offset_position = data.len();
+    let mut values = Vec::with_capacity(members.len());
+    let mut previous_end = 0;
+    for (index, member) in members.iter().enumerate() {
+        let info = type_info(member, 0).unwrap();
+        let start = align_offset(previous_end, info.alignment);
+        let end = match info.fixed_size {
+            Some(size) => start + size,
+            None if index == members.len() - 1 => offset_position,
+            None => {
+                offset_position -= size;
+                read_offset(data, offset_position, size).unwrap()
+            }
+        };
+
+        values.push(Value::decode(member, &data[start..end], endian));
+        previous_end = end;
+    }
+
+    values
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    fn serialize<T: serde::Serialize + zvariant::Type>(value: &T, endian: Endian) -> Vec<u8> {
+        let context = zvariant::serialized::Context::new_gvariant(endian.into(), 0);
+        zvariant::to_bytes(context, value).unwrap().to_vec()
+    }
+
+    fn string(value: &str) -> Value {
+        Value::String(value.to_string())
+    }
+
+    #[test]
+    fn basic() {
+        for endian in [Endian::Little, Endian::Big] {
+            let value = |data: Vec<u8>, signature| {
+                let value = Value::from_data(&data, signature, endian).unwrap();
+                assert_eq!(value.signature(), signature);
+                value
+            };
+
+            assert_eq!(value(vec![1], "b"), Value::Bool(true));
+            assert_eq!(value(serialize(&7u8, endian), "y"), Value::U8(7));
+            assert_eq!(value(serialize(&-300i16, endian), "n"), Value::I16(-300));
+            assert_eq!(value(serialize(&300u16, endian), "q"), Value::U16(300));
+            assert_eq!(
+                value(serialize(&-70000i32, endian), "i"),
+                Value::I32(-70000)
+            );
+            assert_eq!(value(serialize(&70000u32, endian), "u"), Value::U32(70000));
+            assert_eq!(
+                value(serialize(&(-1i64 << 40), endian), "x"),
+                Value::I64(-1 << 40)
+            );
+            assert_eq!(
+                value(serialize(&(1u64 << 40), endian), "t"),
+                Value::U64(1 << 40)
+            );
+            assert_eq!(value(serialize(&5i32, endian), "h"), Value::Handle(5));
+            assert_eq!(value(serialize(&1.5f64, endian), "d"), Value::F64(1.5));
+            assert_eq!(value(serialize(&"test", endian), "s"), string("test"));
+            assert_eq!(
+                value(b"/a/b\0".to_vec(), "o"),
+                Value::ObjectPath("/a/b".to_string())
+            );
+            assert_eq!(
+                value(b"a{sv}\0".to_vec(), "g"),
+                Value::Signature("a{sv}".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn containers() {
+        for endian in [Endian::Little, Endian::Big] {
+            let data = serialize(&vec![1u32, 2, 3], endian);
+            assert_eq!(
+                Value::from_data(&data, "au", endian).unwrap(),
+                Value::Array {
+                    element_type: "u".to_string(),
+                    values: vec![Value::U32(1), Value::U32(2), Value::U32(3)],
+                }
+            );
+
+            let data = serialize(&(1u8, "abc", 2u64, vec!["x", ""]), endian);
+            assert_eq!(
+                Value::from_data(&data, "(ysxas)", endian).unwrap(),
+                Value::Tuple(vec![
+                    Value::U8(1),
+                    string("abc"),
+                    Value::I64(2),
+                    Value::Array {
+                        element_type: "s".to_string(),
+                        values: vec![string("x"), string("")],
+                    },
+                ])
+            );
+
+            let data = serialize(&std::collections::BTreeMap::from([("a", 1u32)]), endian);
+            let value = Value::from_data(&data, "a{su}", endian).unwrap();
+            assert_eq!(value.signature(), "a{su}");
+            assert_eq!(
+                value,
+                Value::Dict {
+                    key_type: "s".to_string(),
+                    value_type: "u".to_string(),
+                    entries: vec![(string("a"), Value::U32(1))],
+                }
+            );
+
+            let data = serialize(&zvariant::Value::new(vec![1i16, 2]), endian);
+            let value = Value::from_data(&data, "v", endian).unwrap();
+            assert_eq!(value.signature(), "v");
+            assert_matches!(value, Value::Variant(inner) if inner.signature() == "an");
+        }
+
+        // Empty containers keep their type
+        let value = Value::from_data(b"", "aas", Endian::Little).unwrap();
+        assert_eq!(value.signature(), "aas");
+        assert_eq!(
+            Value::from_data(b"\0", "()", Endian::Little).unwrap(),
+            Value::Tuple(Vec::new())
+        );
+
+        let value = Value::from_data(b"a\0\x05\x02", "{sy}", Endian::Little).unwrap();
+        assert_eq!(value.signature(), "{sy}");
+        assert_eq!(
+            value,
+            Value::DictEntry(Box::new(string("a")), Box::new(Value::U8(5)))
+        );
+    }
+
+    #[test]
+    fn maybe() {
+        let endian = Endian::Little;
+        for (data, signature, value) in [
+            (serialize(&Some(5u32), endian), "mu", Some(Value::U32(5))),
+            (serialize(&None::<u32>, endian), "mu", None),
+            (serialize(&Some("abc"), endian), "ms", Some(string("abc"))),
+            (serialize(&None::<&str>, endian), "ms", None),
+        ] {
+            let element_type = signature[1..].to_string();
+            let value = value.map(Box::new);
+            assert_eq!(
+                Value::from_data(&data, signature, endian).unwrap(),
+                Value::Maybe {
+                    element_type,
+                    value
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        // Invalid data is read as the default value
+        assert_eq!(
+            Value::from_data(b"\x01\x02", "u", Endian::Little).unwrap(),
+            Value::U32(0)
+        );
+        assert_eq!(
+            Value::from_data(b"test", "s", Endian::Little).unwrap(),
+            string("")
+        );
+        assert_eq!(
+            Value::from_data(b"test", "v", Endian::Little).unwrap(),
+            Value::Variant(Box::new(Value::Tuple(Vec::new())))
+        );
+        assert_eq!(
+            Value::from_data(&[0, b'(', 0xc3, 0xa9, b')'], "v", Endian::Little).unwrap(),
+            Value::Variant(Box::new(Value::Tuple(Vec::new())))
+        );
+
+        assert_matches!(
+            Value::from_data(b"", "é", Endian::Little),
+            Err(Error::InvalidType(_))
+        );
+        assert_matches!(
+            Value::from_data(b"", "uu", Endian::Little),
+            Err(Error::InvalidType(_))
+        );
+    }
+
+    #[test]
+    fn zvariant() {
+        let values = [
+            zvariant::Value::new((1u8, true, -2i16, 3u16, 4i32, 5u32, -6i64, 7u64, 8.5f64)),
+            zvariant::Value::new(("abc", vec!["x", "y"], Vec::<u32>::new())),
+            zvariant::Value::new(std::collections::HashMap::from([("a", 1u32)])),
+            zvariant::Value::new(zvariant::Value::new(vec![1u32, 2])),
+            zvariant::Value::ObjectPath(zvariant::ObjectPath::try_from("/a/b").unwrap()),
+            zvariant::Value::Signature(Signature::try_from("a{sv}").unwrap()),
+            zvariant::Value::Maybe(Maybe::just(zvariant::Value::Maybe(Maybe::nothing(
+                Signature::try_from("s").unwrap(),
+            )))),
+            zvariant::Value::Maybe(Maybe::just(zvariant::Value::from("a"))),
+            zvariant::Value::Structure(StructureBuilder::new().build()),
+        ];
+
+        for value in values {
+            let converted = Value::from(&value);
+            assert_eq!(converted.signature(), value.value_signature().as_str());
+            assert_eq!(converted.to_string(), crate::variant::print(&value, true));
+            assert_eq!(zvariant::Value::try_from(&converted).unwrap(), value);
+        }
+
+        assert_eq!(
+            Value::from(&zvariant::Value::new(vec![1u32])),
+            Value::Array {
+                element_type: "u".to_string(),
+                values: vec![Value::U32(1)],
+            }
+        );
+        assert_eq!(
+            Value::from(&zvariant::Value::new(("a", 1u8))),
+            Value::Tuple(vec![string("a"), Value::U8(1)])
+        );
+
+        // Values without an equivalent in zvariant
+        assert_matches!(
+            zvariant::Value::try_from(&Value::Handle(1)),
+            Err(Error::InvalidType(typ)) if typ == "h"
+        );
+        let entry = Value::DictEntry(Box::new(string("a")), Box::new(Value::U8(5)));
+        assert_matches!(
+            zvariant::Value::try_from(&entry),
+            Err(Error::InvalidType(_))
+        );
+        assert_eq!(entry.to_string(), "{'a', byte 0x05}");
+        assert_eq!(Value::Handle(3).to_string(), "handle 3");
+
+        // Values that don't match their type
+        let array = Value::Array {
+            element_type: "u".to_string(),
+            values: vec![Value::U8(1)],
+        };
+        assert_matches!(zvariant::Value::try_from(&array), Err(Error::ZVariant(_)));
+        let maybe = Value::Maybe {
+            element_type: "u".to_string(),
+            value: Some(Box::new(Value::U8(1))),
+        };
+        assert_matches!(
+            zvariant::Value::try_from(&maybe),
+            Err(Error::InvalidType(_))
+        );
+        let path = Value::ObjectPath("not a path".to_string());
+        assert_matches!(zvariant::Value::try_from(&path), Err(Error::ZVariant(_)));
+    }
+}
@@ -1,10 +1,28 @@
+mod atomic;
+mod change_set;
+mod editor;
 mod error;
 mod file;
+pub(crate) mod glib_order;
 mod hash;
 mod item;
+#[cfg(feature = "json")]
+mod json;
+mod merge;
+mod plan;
+mod streaming;
+mod verify;
 
-pub use error::{Error, Result};
+pub use atomic::AtomicWriteStep;
+pub use change_set::ChangeSet;
+pub use editor::FileEditor;
+pub use error::{Error, InvalidKeyReason, Result};
 pub use file::{FileWriter, HashTableBuilder};
+pub use item::HashValue;
+pub use merge::{merge, MergePolicy};
+pub use plan::{HashTablePlan, PlannedItem};
+pub use streaming::StreamingFileWriter;
+pub use verify::VerifyMismatch;
 
 /// Deprecated type aliases
 mod deprecated {
@@ -0,0 +1,216 @@
+use crate::write::error::{Error, Result};
+use crate::write::{FileWriter, HashTableBuilder};
+use std::fmt::{Display, Formatter};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The step of [`FileWriter::write_to_path_atomic`] that failed, see [`Error::AtomicWrite`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AtomicWriteStep {
+    /// Creating the temporary file next to the target file
+    CreateTemp,
+
+    /// Writing the data to the temporary file
+    Write,
+
+    /// Flushing the temporary file to disk
+    Sync,
+
+    /// Renaming the temporary file over the target file
+    Rename,
+
+    /// Flushing the directory entry of the target file to disk
+    SyncDirectory,
+}
+
+impl Display for AtomicWriteStep {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            AtomicWriteStep::CreateTemp => "creating temporary file",
+            AtomicWriteStep::Write => "writing temporary file",
+            AtomicWriteStep::Sync => "syncing temporary file",
+            AtomicWriteStep::Rename => "renaming temporary file",
+            AtomicWriteStep::SyncDirectory => "syncing directory",
+        };
+
+        write!(f, "{}", text)
+    }
+}
+
+/// Counter to create unique temporary file names within this process
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A path for a temporary file in the same directory as `path`
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+
+    path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        name,
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Flush the directory entries of `dir` to disk
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    std::fs::File::open(dir)?.sync_all()
+}
+
+/// Directories can't be opened on this platform, renames are flushed with the file
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+impl FileWriter {
+    /// Write the GVDB file to `path` without ever leaving a partially written file behind
+    ///
+    /// The data is written to a temporary file in the same directory, flushed to disk and then
+    /// renamed over `path`. Readers either see the previous file or the new one. If `path`
+    /// already exists, the temporary file gets its permissions.
+    ///
+    /// The rename itself is only durable after the directory was flushed to disk as well. Set
+    /// `sync_directory` to do this, e.g. for settings databases that must survive a power loss.
+    ///
+    /// Returns [`Error::AtomicWrite`] with the step that failed if a file system operation fails.
+    /// The temporary file is removed in this case.
+    ///
+    /// ```
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let path = std::env::temp_dir().join("gvdb-doc-atomic.gvdb");
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert("int", 42u32).unwrap();
+    /// FileWriter::new()
+    ///     .write_to_path_atomic(&path, table_builder, true)
+    ///     .unwrap();
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn write_to_path_atomic(
+        self,
+        path: &Path,
+        table_builder: HashTableBuilder,
+        sync_directory: bool,
+    ) -> Result<usize> {
+        let data = self.write_to_vec_with_table(table_builder)?;
+        let error = |step| move |err| Error::AtomicWrite(step, err, path.to_path_buf());
+
+        let temp_path = temp_path(path);
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+            .map_err(error(AtomicWriteStep::CreateTemp))?;
+
+        let result = (|| {
+            if let Ok(metadata) = std::fs::metadata(path) {
+                file.set_permissions(metadata.permissions())
+                    .map_err(error(AtomicWriteStep::CreateTemp))?;
+            }
+
+            file.write_all(&data)
+                .map_err(error(AtomicWriteStep::Write))?;
+            file.sync_all().map_err(error(AtomicWriteStep::Sync))?;
+            drop(file);
+
+            std::fs::rename(&temp_path, path).map_err(error(AtomicWriteStep::Rename))
+        })();
+
+        if result.is_err() {
+            let _ = std::fs::remove_file(&temp_path);
+        }
+        result?;
+
+        if sync_directory {
+            let dir = match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            sync_dir(dir).map_err(error(AtomicWriteStep::SyncDirectory))?;
+        }
+
+        Ok(data.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::File;
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    fn table_builder(value: u32) -> HashTableBuilder<'static> {
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert("int", value).unwrap();
+        table_builder
+    }
+
+    #[test]
+    fn write_atomic() {
+        let dir = std::env::temp_dir().join("gvdb-rs-atomic-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir(&dir).unwrap();
+        let path = dir.join("test.gvdb");
+
+        let size = FileWriter::new()
+            .write_to_path_atomic(&path, table_builder(1), false)
+            .unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len() as usize, size);
+
+        // Replace the existing file
+        FileWriter::new()
+            .write_to_path_atomic(&path, table_builder(2), true)
+            .unwrap();
+        let file = File::from_file(&path).unwrap();
+        assert_eq!(file.hash_table().unwrap().get::<u32>("int").unwrap(), 2);
+
+        // No temporary files are left behind
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn write_atomic_error() {
+        let path = std::env::temp_dir()
+            .join("gvdb-rs-missing-dir")
+            .join("test.gvdb");
+        let err = FileWriter::new()
+            .write_to_path_atomic(&path, table_builder(1), false)
+            .unwrap_err();
+        assert_matches!(
+            err,
+            Error::AtomicWrite(AtomicWriteStep::CreateTemp, _, ref err_path) if *err_path == path
+        );
+        assert!(format!("{}", err).contains("creating temporary file"));
+
+        // Renaming a file over a directory fails
+        let path = std::env::temp_dir().join("gvdb-rs-atomic-dir");
+        let _ = std::fs::create_dir(&path);
+        let err = FileWriter::new()
+            .write_to_path_atomic(&path, table_builder(1), false)
+            .unwrap_err();
+        assert_matches!(err, Error::AtomicWrite(AtomicWriteStep::Rename, ..));
+        let temp_files = std::fs::read_dir(std::env::temp_dir())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(".gvdb-rs-atomic-dir.")
+            })
+            .count();
+        assert_eq!(temp_files, 0);
+        std::fs::remove_dir(&path).unwrap();
+    }
+}
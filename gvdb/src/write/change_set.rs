@@ -0,0 +1,176 @@
+use crate::write::error::Result;
+use crate::write::HashTableBuilder;
+use std::collections::BTreeMap;
+
+/// A set of changes to the root hash table of an existing GVDB file
+///
+/// Used with [`FileWriter::rewrite_with_changes`](crate::write::FileWriter::rewrite_with_changes).
+/// Keys are split into containers at the path separator `/`, like with
+/// [`HashTableBuilder::new`]. When the same key is changed multiple times, only the last change
+/// is applied.
+///
+/// ```
+/// use gvdb::write::ChangeSet;
+///
+/// let mut changes = ChangeSet::new();
+/// changes.insert("int", 42u32);
+/// changes.insert_string("string", "replaced string");
+/// changes.remove("table");
+/// assert_eq!(changes.len(), 3);
+/// ```
+#[derive(Debug, Default)]
+pub struct ChangeSet<'a> {
+    changes: BTreeMap<String, Option<zvariant::Value<'a>>>,
+}
+
+impl<'a> ChangeSet<'a> {
+    /// Create a new empty change set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert or replace the value at `key`
+    pub fn insert_value(&mut self, key: &(impl ToString + ?Sized), value: zvariant::Value<'a>) {
+        self.changes.insert(key.to_string(), Some(value));
+    }
+
+    /// Insert or replace the value at `key`, converting `value` to a [`zvariant::Value`]
+    pub fn insert<T>(&mut self, key: &(impl ToString + ?Sized), value: T)
+    where
+        T: Into<zvariant::Value<'a>>,
+    {
+        self.insert_value(key, value.into());
+    }
+
+    /// Insert or replace the string at `key`
+    pub fn insert_string(
+        &mut self,
+        key: &(impl ToString + ?Sized),
+        string: &(impl ToString + ?Sized),
+    ) {
+        self.insert_value(key, zvariant::Value::new(string.to_string()));
+    }
+
+    /// Remove the item at `key`, including its children if it is a container
+    ///
+    /// Removing a key that does not exist in the original file is not an error.
+    pub fn remove(&mut self, key: &(impl ToString + ?Sized)) {
+        self.changes.insert(key.to_string(), None);
+    }
+
+    /// The number of changed keys
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Whether the change set contains no changes
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Apply all changes to `table_builder`
+    pub(crate) fn apply(self, table_builder: &mut HashTableBuilder<'a>) -> Result<()> {
+        for (key, value) in self.changes {
+            match value {
+                Some(value) => {
+                    // Replacing a container with a value would leave its children behind
+                    table_builder.remove(&key);
+                    table_builder.insert_value(&key, value)?;
+                }
+                None => {
+                    table_builder.remove(&key);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::{File, HashItemType};
+    use crate::test::{assert_is_file_1, assert_is_file_2, assert_is_file_3, new_simple_file};
+    use crate::test::{TEST_FILE_1, TEST_FILE_2, TEST_FILE_3};
+    use crate::write::{ChangeSet, FileWriter};
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+
+    fn rewrite(file: &File, changes: ChangeSet) -> File<'static> {
+        let data = FileWriter::rewrite_with_changes(file, changes).unwrap();
+        File::from_bytes(Cow::Owned(data)).unwrap()
+    }
+
+    #[test]
+    fn unchanged() {
+        let file = File::from_file(&TEST_FILE_1).unwrap();
+        assert_is_file_1(&rewrite(&file, ChangeSet::new()));
+
+        let file = File::from_file(&TEST_FILE_2).unwrap();
+        assert_is_file_2(&rewrite(&file, ChangeSet::new()));
+
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let rewritten = rewrite(&file, ChangeSet::new());
+        assert_is_file_3(&rewritten);
+
+        // The value data is copied as is
+        let table = file.hash_table().unwrap();
+        let rewritten_table = rewritten.hash_table().unwrap();
+        for key in table.keys().unwrap() {
+            let (typ, data) = table.get_raw(&key).unwrap();
+            if typ == HashItemType::Value {
+                assert_eq!(rewritten_table.get_raw(&key).unwrap(), (typ, data));
+            }
+        }
+    }
+
+    #[test]
+    fn changes() {
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let mut changes = ChangeSet::new();
+        changes.insert("/gvdb/rs/test/new", 42u32);
+        changes.insert_string("/gvdb/rs/test/replaced", "first");
+        changes.insert_string("/gvdb/rs/test/replaced", "second");
+        changes.remove("/gvdb/rs/test/icons/");
+        changes.remove("/gvdb/rs/test/does-not-exist");
+        assert_eq!(changes.len(), 4);
+        assert!(!changes.is_empty());
+
+        let rewritten = rewrite(&file, changes);
+        let table = rewritten.hash_table().unwrap();
+        assert_eq!(table.get::<u32>("/gvdb/rs/test/new").unwrap(), 42);
+        assert_eq!(
+            table.get::<String>("/gvdb/rs/test/replaced").unwrap(),
+            "second"
+        );
+        assert_matches!(
+            table.get_raw("/gvdb/rs/test/icons/"),
+            Err(crate::read::Error::KeyNotFound(_))
+        );
+
+        let original = file.hash_table().unwrap();
+        assert_eq!(
+            table.get_raw("/gvdb/rs/test/json/test.json").unwrap(),
+            original.get_raw("/gvdb/rs/test/json/test.json").unwrap()
+        );
+        assert_eq!(
+            table.get_raw("/gvdb/rs/test/").unwrap().0,
+            HashItemType::Container
+        );
+    }
+
+    #[test]
+    fn big_endian() {
+        let file = new_simple_file(true);
+        let mut changes = ChangeSet::new();
+        changes.insert("int", 5u16);
+
+        let rewritten = rewrite(&file, changes);
+        assert!(rewritten.byteswapped);
+        let table = rewritten.hash_table().unwrap();
+        assert_eq!(table.get::<String>("test").unwrap(), "test");
+        assert_eq!(table.get::<u16>("int").unwrap(), 5);
+    }
+}
@@ -0,0 +1,252 @@
+use crate::read::File;
+use crate::write::error::Result;
+use crate::write::{FileWriter, HashTableBuilder};
+use std::borrow::Cow;
+use std::io::Write;
+use std::path::Path;
+
+/// Edit existing GVDB files
+///
+/// The whole file is loaded into a [`HashTableBuilder`], including nested hash tables. After
+/// modifying the table, the file can be serialized again. The endianness of the original file
+/// is preserved.
+///
+/// # Example
+///
+/// ```
+/// use gvdb::write::{FileEditor, HashTableBuilder};
+/// use std::path::PathBuf;
+///
+/// let path = PathBuf::from("test-data/test2.gvdb");
+/// let mut editor = FileEditor::from_file(&path).unwrap();
+/// editor.table_mut().insert_string("string", "replaced string").unwrap();
+/// editor.table_mut().remove("table");
+///
+/// let mut table_builder = HashTableBuilder::new();
+/// table_builder.insert("int", 42u32).unwrap();
+/// editor.table_mut().insert_table("new_table", table_builder).unwrap();
+///
+/// let file_data = editor.write_to_vec().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct FileEditor {
+    table: HashTableBuilder<'static>,
+    byteswap: bool,
+}
+
+impl FileEditor {
+    /// Load the GVDB file at `filename` for editing
+    ///
+    /// The file is read into memory completely, so the result may be written back to the same path.
+    pub fn from_file(filename: &Path) -> crate::read::Result<Self> {
+        Self::from_file_with_path_separator(filename, Some("/"))
+    }
+
+    /// Load the GVDB file at `filename`, which was written with the path separator `sep`, for
+    /// editing
+    ///
+    /// Keys that are inserted into the hash tables are split at `sep`, see
+    /// [`HashTableBuilder::with_path_separator`].
+    pub fn from_file_with_path_separator(
+        filename: &Path,
+        sep: Option<&str>,
+    ) -> crate::read::Result<Self> {
+        Self::from_read_file(&File::from_file(filename)?, sep)
+    }
+
+    /// Load the GVDB file contained in `bytes` for editing
+    pub fn from_bytes(bytes: Cow<'_, [u8]>) -> crate::read::Result<Self> {
+        Self::from_bytes_with_path_separator(bytes, Some("/"))
+    }
+
+    /// Load the GVDB file contained in `bytes`, which was written with the path separator `sep`,
+    /// for editing
+    pub fn from_bytes_with_path_separator(
+        bytes: Cow<'_, [u8]>,
+        sep: Option<&str>,
+    ) -> crate::read::Result<Self> {
+        Self::from_read_file(&File::from_bytes(bytes)?, sep)
+    }
+
+    fn from_read_file(file: &File, sep: Option<&str>) -> crate::read::Result<Self> {
+        Ok(Self {
+            table: HashTableBuilder::from_hash_table_with_path_separator(&file.hash_table()?, sep)?,
+            byteswap: file.byteswapped,
+        })
+    }
+
+    /// The root hash table of the file
+    pub fn table(&self) -> &HashTableBuilder<'static> {
+        &self.table
+    }
+
+    /// The root hash table of the file, to insert, replace or remove items
+    ///
+    /// Nested hash tables can be edited with [`HashTableBuilder::table_mut`].
+    pub fn table_mut(&mut self) -> &mut HashTableBuilder<'static> {
+        &mut self.table
+    }
+
+    /// Write the edited GVDB file into the provided [`std::io::Write`]
+    pub fn write(self, writer: &mut dyn Write) -> Result<usize> {
+        FileWriter::with_byteswap(self.byteswap).write_with_table(self.table, writer)
+    }
+
+    /// Create a [`Vec<u8>`] with the edited GVDB file data
+    pub fn write_to_vec(self) -> Result<Vec<u8>> {
+        FileWriter::with_byteswap(self.byteswap).write_to_vec_with_table(self.table)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::{Error, HashItemType};
+    use crate::test::{assert_is_file_1, assert_is_file_2, assert_is_file_3, TEST_FILE_1};
+    use crate::test::{TEST_FILE_2, TEST_FILE_3};
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::mem::size_of;
+
+    fn roundtrip(editor: FileEditor) -> File<'static> {
+        File::from_bytes(Cow::Owned(editor.write_to_vec().unwrap())).unwrap()
+    }
+
+    #[test]
+    fn unchanged() {
+        let editor = FileEditor::from_file(&TEST_FILE_1).unwrap();
+        println!("{:?}", editor);
+        assert_is_file_1(&roundtrip(editor));
+
+        let editor = FileEditor::from_file(&TEST_FILE_2).unwrap();
+        assert_is_file_2(&roundtrip(editor));
+
+        let editor = FileEditor::from_file(&TEST_FILE_3).unwrap();
+        assert_is_file_3(&roundtrip(editor));
+    }
+
+    #[test]
+    fn edit() {
+        let mut editor = FileEditor::from_file(&TEST_FILE_2).unwrap();
+        assert_eq!(editor.table().len(), 2);
+
+        editor
+            .table_mut()
+            .insert_string("string", "replaced")
+            .unwrap();
+        editor
+            .table_mut()
+            .table_mut("table")
+            .unwrap()
+            .insert("int2", 5u8)
+            .unwrap();
+        editor.table_mut().insert("new", 1u16).unwrap();
+
+        let file = roundtrip(editor);
+        let table = file.hash_table().unwrap();
+        let mut keys = table.keys().unwrap();
+        keys.sort();
+        assert_eq!(keys, ["new", "string", "table"]);
+        assert_eq!(table.get::<String>("string").unwrap(), "replaced");
+        assert_eq!(table.get::<u16>("new").unwrap(), 1);
+
+        let sub_table = table.get_hash_table("table").unwrap();
+        assert_eq!(sub_table.get::<u32>("int").unwrap(), 42);
+        assert_eq!(sub_table.get::<u8>("int2").unwrap(), 5);
+    }
+
+    #[test]
+    fn remove_nested() {
+        let mut editor = FileEditor::from_file(&TEST_FILE_3).unwrap();
+        assert!(editor
+            .table_mut()
+            .remove("/gvdb/rs/test/online-symbolic.svg"));
+        assert!(!editor.table_mut().remove("/gvdb/rs/test/does-not-exist"));
+        assert!(editor.table_mut().remove("/gvdb/rs/test/icons/"));
+
+        let file = roundtrip(editor);
+        let table = file.hash_table().unwrap();
+        let mut keys = table.keys().unwrap();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "/",
+                "/gvdb/",
+                "/gvdb/rs/",
+                "/gvdb/rs/test/",
+                "/gvdb/rs/test/json/",
+                "/gvdb/rs/test/json/test.json",
+                "/gvdb/rs/test/test.css",
+            ]
+        );
+    }
+
+    #[test]
+    fn path_separator() {
+        let mut nested = HashTableBuilder::with_path_separator(Some("::"));
+        nested.insert("x::y", 1u32).unwrap();
+        let mut table_builder = HashTableBuilder::with_path_separator(Some("::"));
+        table_builder.insert("a::b", 1u32).unwrap();
+        table_builder.insert_table("table", nested).unwrap();
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+
+        let mut editor =
+            FileEditor::from_bytes_with_path_separator(Cow::Owned(data), Some("::")).unwrap();
+        editor.table_mut().insert("a::c", 2u32).unwrap();
+        editor
+            .table_mut()
+            .table_mut("table")
+            .unwrap()
+            .insert("x::z", 3u32)
+            .unwrap();
+
+        let file = roundtrip(editor);
+        let table = file.hash_table().unwrap();
+        let mut keys = table.keys().unwrap();
+        keys.sort();
+        assert_eq!(keys, ["a::", "a::b", "a::c", "table"]);
+        assert_eq!(table.get::<u32>("a::c").unwrap(), 2);
+
+        // The new items are children of the existing containers
+        let (typ, children) = table.get_raw("a::").unwrap();
+        assert_eq!(typ, HashItemType::Container);
+        assert_eq!(children.len(), 2 * size_of::<u32>());
+
+        let nested = table.get_hash_table("table").unwrap();
+        let mut keys = nested.keys().unwrap();
+        keys.sort();
+        assert_eq!(keys, ["x::", "x::y", "x::z"]);
+        assert_eq!(nested.get::<u32>("x::z").unwrap(), 3);
+        assert_eq!(nested.get_raw("x::").unwrap().1.len(), 2 * size_of::<u32>());
+    }
+
+    #[test]
+    fn keep_endianness() {
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert_string("string", "test").unwrap();
+        let data = FileWriter::for_big_endian()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+
+        let mut editor = FileEditor::from_bytes(Cow::Owned(data)).unwrap();
+        editor.table_mut().insert("int", 42u32).unwrap();
+
+        let mut data = Vec::new();
+        editor.write(&mut data).unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        assert_eq!(file.byteswapped, cfg!(target_endian = "little"));
+        let table = file.hash_table().unwrap();
+        assert_eq!(table.get::<String>("string").unwrap(), "test");
+        assert_eq!(table.get::<u32>("int").unwrap(), 42);
+    }
+
+    #[test]
+    fn invalid_file() {
+        let err = FileEditor::from_bytes(Cow::Borrowed(&[0; 24])).unwrap_err();
+        assert_matches!(err, Error::InvalidHeader { .. });
+    }
+}
@@ -1,6 +1,42 @@
 use std::fmt::{Debug, Display, Formatter};
 use std::path::PathBuf;
 
+/// The reason why [`HashTableBuilder`](crate::write::HashTableBuilder) rejects a key
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InvalidKeyReason {
+    /// The key contains a NUL byte at `position`, which GLib can't look up
+    Nul {
+        /// The byte offset of the NUL byte in the key
+        position: usize,
+    },
+
+    /// A segment of the key between path separators is longer than the 65535 bytes supported by
+    /// the GVDB file format
+    SegmentTooLong {
+        /// The length of the segment in bytes, including the path separator
+        length: usize,
+    },
+}
+
+impl Display for InvalidKeyReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidKeyReason::Nul { position } => {
+                write!(f, "Key contains a NUL byte at position {}", position)
+            }
+            InvalidKeyReason::SegmentTooLong { length } => {
+                write!(
+                    f,
+                    "Key segment has a length of {} bytes, the maximum is {} bytes",
+                    length,
+                    u16::MAX
+                )
+            }
+        }
+    }
+}
+
 /// Error type for [`FileWriter`][crate::write::FileWriter]
 #[non_exhaustive]
 pub enum Error {
@@ -12,6 +48,54 @@ pub enum Error {
 
     /// An error occured when serializing variant data with zvariant
     ZVariant(zvariant::Error),
+
+    /// The JSON data passed to `HashTableBuilder::from_json` is not in the expected format
+    Json(String),
+
+    /// An error occured when reading GVDB data, e.g. the original file in
+    /// `FileWriter::rewrite_with_changes` or a table passed to `HashTableBuilder::insert_table_from`
+    Read(crate::read::Error),
+
+    /// The file would be larger than the 4 GiB that can be addressed by the GVDB file format
+    TooLarge {
+        /// The offset of the chunk that doesn't fit into the file
+        offset: usize,
+        /// The size of the chunk
+        size: usize,
+    },
+
+    /// A key is longer than the 65535 bytes supported by the GVDB file format
+    KeyTooLong {
+        /// The key
+        key: String,
+    },
+
+    /// A key was rejected by [`HashTableBuilder`](crate::write::HashTableBuilder) because GLib
+    /// can't read it. Use
+    /// [`HashTableBuilder::with_key_validation`](crate::write::HashTableBuilder::with_key_validation)
+    /// to insert such keys anyway
+    InvalidKey {
+        /// The key
+        key: String,
+        /// Why the key is invalid
+        reason: InvalidKeyReason,
+    },
+
+    /// A step of [`FileWriter::write_to_path_atomic`](crate::write::FileWriter::write_to_path_atomic)
+    /// failed. Path contains the target file
+    AtomicWrite(crate::write::AtomicWriteStep, std::io::Error, PathBuf),
+
+    /// An item exists in more than one file passed to [`merge`](crate::write::merge) and can't
+    /// be resolved with the [`MergePolicy`](crate::write::MergePolicy)
+    MergeConflict {
+        /// The keys of the enclosing nested hash tables, followed by the key of the item
+        path: Vec<String>,
+    },
+
+    /// The file written by
+    /// [`FileWriter::write_and_verify`](crate::write::FileWriter::write_and_verify) doesn't match
+    /// the hash table builder
+    Verification(Vec<crate::write::VerifyMismatch>),
 }
 
 impl std::error::Error for Error {}
@@ -28,6 +112,12 @@ impl From<zvariant::Error> for Error {
     }
 }
 
+impl From<crate::read::Error> for Error {
+    fn from(err: crate::read::Error) -> Self {
+        Self::Read(err)
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -44,6 +134,66 @@ impl Display for Error {
             Error::ZVariant(err) => {
                 write!(f, "Error writing ZVariant data: {}", err)
             }
+            Error::Json(context) => {
+                write!(f, "Invalid JSON data: {}", context)
+            }
+            Error::Read(err) => {
+                write!(f, "Error reading GVDB data: {}", err)
+            }
+            Error::TooLarge { offset, size } => {
+                write!(
+                    f,
+                    "Data too large for the GVDB file format: A chunk of {} bytes at offset {} exceeds the maximum file size of {} bytes",
+                    size,
+                    offset,
+                    u32::MAX
+                )
+            }
+            Error::KeyTooLong { key } => {
+                write!(
+                    f,
+                    "Data too large for the GVDB file format: Key '{}…' has a length of {} bytes, the maximum is {} bytes",
+                    key.chars().take(32).collect::<String>(),
+                    key.len(),
+                    u16::MAX
+                )
+            }
+            Error::InvalidKey { key, reason } => {
+                write!(
+                    f,
+                    "Invalid key '{}': {}",
+                    key.chars().take(32).collect::<String>().escape_debug(),
+                    reason
+                )
+            }
+            Error::AtomicWrite(step, err, path) => {
+                write!(
+                    f,
+                    "Error {} for atomic write of file '{}': {}",
+                    step,
+                    path.display(),
+                    err
+                )
+            }
+            Error::MergeConflict { path } => {
+                write!(
+                    f,
+                    "Conflicting items for key path {:?} while merging files",
+                    path
+                )
+            }
+            Error::Verification(mismatches) => {
+                write!(
+                    f,
+                    "Verification of the written file failed with {} mismatches",
+                    mismatches.len()
+                )?;
+                if let Some(mismatch) = mismatches.first() {
+                    write!(f, ", the first one is: {}", mismatch)?;
+                }
+
+                Ok(())
+            }
         }
     }
 }
@@ -59,7 +209,7 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 #[cfg(test)]
 mod test {
-    use super::Error;
+    use super::{Error, InvalidKeyReason};
     use matches::assert_matches;
     use std::path::PathBuf;
 
@@ -75,5 +225,48 @@ mod test {
         );
         assert_matches!(err, Error::Io(..));
         assert!(format!("{}", err).contains("test_path"));
+
+        let err = Error::Json("Expected JSON object".to_string());
+        assert!(format!("{}", err).contains("Expected JSON object"));
+
+        let err = Error::from(crate::read::Error::KeyNotFound("test".to_string()));
+        assert_matches!(err, Error::Read(_));
+        assert!(format!("{}", err).contains("test"));
+        assert!(format!("{}", err).starts_with("Error reading GVDB data: "));
+
+        let err = Error::TooLarge {
+            offset: u32::MAX as usize,
+            size: 16,
+        };
+        assert!(format!("{}", err).contains("too large"));
+        assert!(format!("{}", err).contains("16 bytes"));
+
+        let err = Error::KeyTooLong {
+            key: "a".repeat(u16::MAX as usize + 1),
+        };
+        assert!(format!("{}", err).contains("65536 bytes"));
+
+        let err = Error::InvalidKey {
+            key: "a\0b".to_string(),
+            reason: InvalidKeyReason::Nul { position: 1 },
+        };
+        assert_eq!(
+            format!("{}", err),
+            "Invalid key 'a\\0b': Key contains a NUL byte at position 1"
+        );
+
+        let err = Error::InvalidKey {
+            key: "a".repeat(70000),
+            reason: InvalidKeyReason::SegmentTooLong { length: 70000 },
+        };
+        assert!(format!("{}", err).contains("70000 bytes"));
+
+        let err = Error::MergeConflict {
+            path: vec!["table".to_string(), "int".to_string()],
+        };
+        assert_eq!(
+            format!("{}", err),
+            "Conflicting items for key path [\"table\", \"int\"] while merging files"
+        );
     }
 }
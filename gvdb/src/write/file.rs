@@ -1,15 +1,46 @@
+use crate::read::File;
 use crate::read::HashHeader;
 use crate::read::HashItem;
+use crate::read::HashItemType;
+use crate::read::HashTable;
 use crate::read::Header;
 use crate::read::Pointer;
-use crate::util::align_offset;
-use crate::write::error::{Error, Result};
+use crate::util::{align_offset, djb_hash, trace_event};
+use crate::write::change_set::ChangeSet;
+use crate::write::error::{Error, InvalidKeyReason, Result};
+use crate::write::glib_order;
 use crate::write::hash::SimpleHashTable;
-use crate::write::item::HashValue;
-use safe_transmute::transmute_one_to_bytes;
-use std::collections::{HashMap, VecDeque};
+use crate::write::item::{HashValue, SerializeFn};
+use crate::write::plan::{HashTablePlan, PlannedItem};
+use crate::Endian;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
 use std::io::Write;
 use std::mem::size_of;
+use zerocopy::IntoBytes;
+
+/// The bloom shift used for all hash tables, identical to the one used by GLib
+const BLOOM_SHIFT: u32 = 5;
+
+/// The number of bloom words is stored in the lower 27 bits of the hash table header
+const MAX_BLOOM_WORDS: u32 = 1 << 27;
+
+/// The alignment of values, identical to the one used by GLib
+const VALUE_ALIGNMENT: usize = 8;
+
+/// The order of the items in the hash buckets
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ItemOrder {
+    /// Descending order of the keys
+    Sorted,
+
+    /// Ascending order of `(hash, key)`
+    Canonical,
+
+    /// The order of GLib's GVDB writer
+    Glib,
+}
 
 /// Create hash tables for use in GVDB files
 ///
@@ -30,6 +61,13 @@ use std::mem::size_of;
 pub struct HashTableBuilder<'a> {
     items: HashMap<String, HashValue<'a>>,
     path_separator: Option<String>,
+    bucket_count: Option<usize>,
+    load_factor: f32,
+    validate_keys: bool,
+    escape: Option<char>,
+
+    // The position of every key in the order of insertion, used for the GLib compatible layout
+    insertion_order: HashMap<String, usize>,
 }
 
 impl<'a> HashTableBuilder<'a> {
@@ -53,59 +91,209 @@ impl<'a> HashTableBuilder<'a> {
         Self {
             items: Default::default(),
             path_separator: sep.map(|s| s.to_string()),
+            bucket_count: None,
+            load_factor: 1.0,
+            validate_keys: true,
+            escape: None,
+            insertion_order: Default::default(),
+        }
+    }
+
+    /// Use exactly `n_buckets` hash buckets for this hash table, independent of the number of
+    /// items
+    ///
+    /// Fewer buckets result in a smaller file, more buckets in faster lookups.
+    ///
+    /// ```
+    /// # use gvdb::write::HashTableBuilder;
+    /// let mut table_builder = HashTableBuilder::new().with_bucket_count(16);
+    /// ```
+    pub fn with_bucket_count(mut self, n_buckets: usize) -> Self {
+        self.bucket_count = Some(n_buckets);
+        self
+    }
+
+    /// Choose the number of hash buckets so that there are `load_factor` items per bucket on
+    /// average
+    ///
+    /// The default load factor is `1.0`, which results in one bucket per item. This setting has
+    /// no effect if [`HashTableBuilder::with_bucket_count`] is used.
+    ///
+    /// ```
+    /// # use gvdb::write::HashTableBuilder;
+    /// let mut table_builder = HashTableBuilder::new().with_load_factor(2.0);
+    /// ```
+    pub fn with_load_factor(mut self, load_factor: f32) -> Self {
+        self.load_factor = load_factor;
+        self
+    }
+
+    /// Whether to reject keys that GLib can't read
+    ///
+    /// By default, inserting a key that contains a NUL byte or a segment between path separators
+    /// that is longer than 65535 bytes fails with [`Error::InvalidKey`]. Disable the validation
+    /// to write such keys deliberately, e.g. to test other readers. Segments that are too long
+    /// for the file format still fail when the file is written.
+    ///
+    /// ```
+    /// use gvdb::write::{Error, HashTableBuilder, InvalidKeyReason};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// assert!(matches!(
+    ///     table_builder.insert("a\0b", 1u32),
+    ///     Err(Error::InvalidKey { reason: InvalidKeyReason::Nul { position: 1 }, .. })
+    /// ));
+    ///
+    /// let mut table_builder = HashTableBuilder::new().with_key_validation(false);
+    /// table_builder.insert("a\0b", 1u32).unwrap();
+    /// ```
+    pub fn with_key_validation(mut self, validate_keys: bool) -> Self {
+        self.validate_keys = validate_keys;
+        self
+    }
+
+    /// Use `escape` to insert keys that contain the path separator
+    ///
+    /// The escape character makes the character after it part of the key segment, so an escaped
+    /// path separator doesn't start a new container. The escape characters themselves are not
+    /// part of the key in the file. By default, keys are not unescaped.
+    ///
+    /// ```
+    /// use gvdb::write::HashTableBuilder;
+    ///
+    /// let mut table_builder = HashTableBuilder::new().with_escape_char('\\');
+    /// table_builder.insert("dir/a\\/b", 1u32).unwrap();
+    /// assert_eq!(table_builder.split_key("dir/a\\/b"), ["dir/", "dir/a/b"]);
+    /// assert!(table_builder.contains_key("dir/a/b"));
+    /// assert!(!table_builder.contains_key("dir/a/"));
+    /// ```
+    pub fn with_escape_char(mut self, escape: char) -> Self {
+        self.escape = Some(escape);
+        self
+    }
+
+    /// The keys of the items that inserting `key` creates, from the outermost container to the
+    /// item itself
+    ///
+    /// Every key but the last one is a container that ends with the path separator. The last key
+    /// is the key of the item in the file, with the escape characters removed.
+    ///
+    /// ```
+    /// use gvdb::write::HashTableBuilder;
+    ///
+    /// let table_builder = HashTableBuilder::with_path_separator(Some("::"));
+    /// assert_eq!(table_builder.split_key("a::b::c"), ["a::", "a::b::", "a::b::c"]);
+    /// assert_eq!(table_builder.split_key("a::b::"), ["a::", "a::b::"]);
+    /// ```
+    pub fn split_key(&self, key: &str) -> Vec<String> {
+        let separator = self.path_separator.as_deref().filter(|sep| !sep.is_empty());
+        let mut keys = Vec::new();
+        let mut current = String::new();
+        let mut rest = key;
+
+        while let Some(c) = rest.chars().next() {
+            if let Some(escaped) = self
+                .escape
+                .filter(|escape| *escape == c)
+                .and_then(|_| rest[c.len_utf8()..].chars().next())
+            {
+                current.push(escaped);
+                rest = &rest[c.len_utf8() + escaped.len_utf8()..];
+            } else if let Some(after) = separator.and_then(|sep| rest.strip_prefix(sep)) {
+                current += separator.unwrap();
+                rest = after;
+                if !rest.is_empty() {
+                    keys.push(current.clone());
+                }
+            } else {
+                current.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
         }
+
+        keys.push(current);
+        keys
+    }
+
+    /// Check that GLib can read the item keys `keys` of `key`
+    fn validate_key(key: &str, keys: &[String]) -> Result<()> {
+        let invalid = |reason| {
+            Err(Error::InvalidKey {
+                key: key.to_string(),
+                reason,
+            })
+        };
+
+        if let Some(position) = key.find('\0') {
+            return invalid(InvalidKeyReason::Nul { position });
+        }
+
+        // Every segment is stored as the key of its own item
+        let mut parent_len = 0;
+        for item_key in keys {
+            let length = item_key.len() - parent_len;
+            if length > u16::MAX as usize {
+                return invalid(InvalidKeyReason::SegmentTooLong { length });
+            }
+
+            parent_len = item_key.len();
+        }
+
+        Ok(())
     }
 
-    fn insert_item_value(
+    pub(crate) fn insert_item_value(
         &mut self,
         key: &(impl ToString + ?Sized),
         item: HashValue<'a>,
     ) -> Result<()> {
-        let key = key.to_string();
-
-        if let Some(sep) = &self.path_separator {
-            let mut this_key = "".to_string();
-            let mut last_key: Option<String> = None;
-
-            for segment in key.split(sep) {
-                this_key += segment;
-                if this_key != key {
-                    this_key += sep;
-                }
+        let keys = self.split_key(&key.to_string());
+        if self.validate_keys {
+            Self::validate_key(&key.to_string(), &keys)?;
+        }
 
-                if let Some(last_key) = last_key {
-                    if let Some(last_item) = self.items.get_mut(&last_key) {
-                        if let HashValue::Container(ref mut container) = last_item {
-                            if !container.contains(&this_key) {
-                                container.push(this_key.clone());
-                            }
-                        } else {
-                            return Err(Error::Consistency(format!(
-                                "Parent item with key '{}' is not of type container",
-                                this_key
-                            )));
-                        }
-                    } else {
-                        let parent_item = HashValue::Container(vec![this_key.clone()]);
-                        self.items.insert(last_key.to_string(), parent_item);
+        let mut new_parents = Vec::new();
+        for (parent, child) in keys.iter().zip(&keys[1..]) {
+            match self.items.get_mut(parent) {
+                Some(HashValue::Container(container)) => {
+                    if !container.contains(child) {
+                        container.push(child.clone());
                     }
                 }
-
-                if key == this_key {
-                    // The item we actually want to insert
-                    self.items.insert(key.to_string(), item);
-                    break;
+                Some(_) => {
+                    return Err(Error::Consistency(format!(
+                        "Parent item with key '{}' is not of type container",
+                        parent
+                    )));
+                }
+                None => {
+                    self.items
+                        .insert(parent.clone(), HashValue::Container(vec![child.clone()]));
+                    new_parents.push(parent);
                 }
-
-                last_key = Some(this_key.clone());
             }
-        } else {
-            self.items.insert(key, item);
+        }
+
+        // Parents are created after their child, from the innermost to the outermost one, like
+        // in glib-compile-resources
+        let key = keys.last().unwrap();
+        self.items.insert(key.clone(), item);
+        self.record_insertion(key);
+        for parent in new_parents.iter().rev() {
+            self.record_insertion(parent);
         }
 
         Ok(())
     }
 
+    /// Remember the position of `key` in the order of insertion, if it is new
+    fn record_insertion(&mut self, key: &str) {
+        if !self.insertion_order.contains_key(key) {
+            let position = self.insertion_order.len();
+            self.insertion_order.insert(key.to_string(), position);
+        }
+    }
+
     /// Insert Value `item` for `key`
     ///
     /// ```
@@ -131,7 +319,7 @@ impl<'a> HashTableBuilder<'a> {
     /// let value = 123u32;
     /// table_builder.insert("variant_123", value);
     /// ```
-    pub fn insert<T: ?Sized>(&mut self, key: &(impl ToString + ?Sized), value: T) -> Result<()>
+    pub fn insert<T>(&mut self, key: &(impl ToString + ?Sized), value: T) -> Result<()>
     where
         T: Into<zvariant::Value<'a>>,
     {
@@ -139,6 +327,78 @@ impl<'a> HashTableBuilder<'a> {
         self.insert_item_value(key, item)
     }
 
+    /// Insert `value` for `key`, serialized with [`serde::Serialize`]
+    ///
+    /// The value is encoded directly with the GVariant encoder of zvariant when the file is
+    /// written, without constructing a [`zvariant::Value`] first.
+    ///
+    /// ```
+    /// use serde::Serialize;
+    /// use zvariant::Type;
+    ///
+    /// #[derive(Serialize, Type)]
+    /// struct Config {
+    ///     name: String,
+    ///     size: (u32, u32),
+    /// }
+    ///
+    /// let config = Config {
+    ///     name: "window".to_string(),
+    ///     size: (800, 600),
+    /// };
+    ///
+    /// let mut table_builder = gvdb::write::HashTableBuilder::new();
+    /// table_builder.insert_serialized("config", &config).unwrap();
+    /// ```
+    pub fn insert_serialized<T>(
+        &mut self,
+        key: &(impl ToString + ?Sized),
+        value: &'a T,
+    ) -> Result<()>
+    where
+        T: serde::Serialize + zvariant::Type,
+    {
+        let item = HashValue::Serialized(SerializeFn::new(value));
+        self.insert_item_value(key, item)
+    }
+
+    /// Insert `value` for `key` as GVariant maybe type `mT`
+    ///
+    /// `None` is stored as `nothing`. Read the value with
+    /// [`HashTable::get_optional`](crate::read::HashTable::get_optional).
+    ///
+    /// ```
+    /// let mut table_builder = gvdb::write::HashTableBuilder::new();
+    /// table_builder.insert_optional("just", Some(42u32)).unwrap();
+    /// table_builder.insert_optional("nothing", None::<String>).unwrap();
+    /// ```
+    pub fn insert_optional<T>(
+        &mut self,
+        key: &(impl ToString + ?Sized),
+        value: Option<T>,
+    ) -> Result<()>
+    where
+        T: serde::Serialize + zvariant::Type + 'a,
+    {
+        let item = HashValue::Serialized(SerializeFn::new_owned(value));
+        self.insert_item_value(key, item)
+    }
+
+    /// Insert the GVariant unit value `()` for `key`
+    ///
+    /// The unit type can be used for keys whose presence is the only information, like a set.
+    /// Read the value with [`HashTable::get_unit`](crate::read::HashTable::get_unit).
+    ///
+    /// ```
+    /// let mut table_builder = gvdb::write::HashTableBuilder::new();
+    /// table_builder.insert_unit("enabled").unwrap();
+    /// ```
+    pub fn insert_unit(&mut self, key: &(impl ToString + ?Sized)) -> Result<()> {
+        // zvariant doesn't support the unit type, so the serialized variant is inserted directly.
+        // The unit value is a single zero byte, followed by the separator and the type.
+        self.insert_item_value(key, HashValue::Bytes(b"\0\0()"))
+    }
+
     /// Insert GVariant `item` for `key`
     ///
     /// ```
@@ -209,6 +469,108 @@ impl<'a> HashTableBuilder<'a> {
         self.insert_item_value(key, item)
     }
 
+    /// Insert a copy of the existing hash table `table` at `key`
+    ///
+    /// All items of `table` are copied recursively, including nested hash tables and containers.
+    /// The serialized data of the values is copied as it is instead of decoding it, unless the
+    /// file is written with a different byte order than the file of `table`. This allows to
+    /// combine existing files efficiently.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use std::path::PathBuf;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let file = File::from_file(&PathBuf::from("test-data/test2.gvdb")).unwrap();
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder
+    ///     .insert_table_from("test2", &file.hash_table().unwrap())
+    ///     .unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let merged = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let table = merged.hash_table().unwrap().get_hash_table("test2").unwrap();
+    /// assert_eq!(table.get::<String>("string").unwrap(), "test string");
+    /// ```
+    pub fn insert_table_from(
+        &mut self,
+        key: &(impl ToString + ?Sized),
+        table: &HashTable<'a, '_>,
+    ) -> Result<()> {
+        let table_builder = Self::from_hash_table_copy(table, self.path_separator.as_deref())?;
+        self.insert_table(key, table_builder)
+    }
+
+    /// Insert `value` at exactly `key`, without creating or linking parent containers
+    ///
+    /// The key is not split at the path separator. To make the item a child of a container, list
+    /// it in [`HashTableBuilder::insert_container`].
+    ///
+    /// ```
+    /// # use gvdb::write::HashTableBuilder;
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder
+    ///     .insert_verbatim("/org/example/key", zvariant::Value::new(42u32))
+    ///     .unwrap();
+    /// assert!(!table_builder.contains_key("/org/example/"));
+    /// ```
+    pub fn insert_verbatim(
+        &mut self,
+        key: &(impl ToString + ?Sized),
+        value: impl Into<HashValue<'a>>,
+    ) -> Result<()> {
+        let key = key.to_string();
+        if self.validate_keys {
+            Self::validate_key(&key, &self.split_key(&key))?;
+        }
+
+        self.record_insertion(&key);
+        self.items.insert(key, value.into());
+        Ok(())
+    }
+
+    /// Insert a container at exactly `key` with the keys of its `children`
+    ///
+    /// Like with [`HashTableBuilder::insert_verbatim`], no parent containers are created. The keys
+    /// of the children have to start with `key`, and the children have to be inserted before the
+    /// hash table is written.
+    ///
+    /// ```
+    /// # use gvdb::write::HashTableBuilder;
+    /// let mut table_builder = HashTableBuilder::with_path_separator(None);
+    /// table_builder
+    ///     .insert_container("/org/", vec!["/org/key".to_string()])
+    ///     .unwrap();
+    /// table_builder
+    ///     .insert_verbatim("/org/key", zvariant::Value::new(42u32))
+    ///     .unwrap();
+    /// ```
+    pub fn insert_container(
+        &mut self,
+        key: &(impl ToString + ?Sized),
+        children: Vec<String>,
+    ) -> Result<()> {
+        let key = key.to_string();
+        if self.validate_keys {
+            for child in &children {
+                Self::validate_key(child, &self.split_key(child))?;
+            }
+        }
+
+        if let Some(child) = children
+            .iter()
+            .find(|child| !child.starts_with(&key) || child.len() == key.len())
+        {
+            return Err(Error::Consistency(format!(
+                "Child item '{}' of container '{}' has to start with the key of the container",
+                child, key
+            )));
+        }
+
+        self.insert_verbatim(&key, HashValue::Container(children))
+    }
+
     /// The number of items contained in the hash table builder
     pub fn len(&self) -> usize {
         self.items.len()
@@ -219,13 +581,147 @@ impl<'a> HashTableBuilder<'a> {
         self.items.is_empty()
     }
 
-    pub(crate) fn build(mut self) -> Result<SimpleHashTable<'a>> {
-        let mut hash_table = SimpleHashTable::with_n_buckets(self.items.len());
+    /// Whether the hash table builder contains an item at `key`
+    ///
+    /// Parent containers that were created implicitly also count as items.
+    ///
+    /// ```
+    /// # let mut table_builder = gvdb::write::HashTableBuilder::new();
+    /// table_builder.insert_string("dir/string", "test").unwrap();
+    /// assert!(table_builder.contains_key("dir/string"));
+    /// assert!(table_builder.contains_key("dir/"));
+    /// ```
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.items.contains_key(key)
+    }
 
-        let mut keys: Vec<String> = self.items.keys().cloned().collect();
-        keys.sort();
+    /// Returns the item at `key`, if there is one
+    ///
+    /// ```
+    /// # let mut table_builder = gvdb::write::HashTableBuilder::new();
+    /// table_builder.insert("int", 42u32).unwrap();
+    /// let value = table_builder.get("int").unwrap().value().unwrap();
+    /// assert_eq!(value, &zvariant::Value::from(42u32));
+    /// ```
+    pub fn get(&self, key: &str) -> Option<&HashValue<'a>> {
+        self.items.get(key)
+    }
+
+    /// An iterator over all keys and items in arbitrary order
+    ///
+    /// ```
+    /// # let mut table_builder = gvdb::write::HashTableBuilder::new();
+    /// table_builder.insert("dir/int", 42u32).unwrap();
+    /// let mut keys: Vec<&str> = table_builder.iter().map(|(key, _)| key).collect();
+    /// keys.sort();
+    /// assert_eq!(keys, ["dir/", "dir/int"]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &HashValue<'a>)> {
+        self.items.iter().map(|(key, value)| (key.as_str(), value))
+    }
+
+    /// Remove the item at `key`
+    ///
+    /// Removing a container also removes all of its children. Parent containers that end up
+    /// without any children are removed as well.
+    ///
+    /// Returns whether an item was removed.
+    ///
+    /// ```
+    /// # let mut table_builder = gvdb::write::HashTableBuilder::new();
+    /// table_builder.insert_string("dir/string", "test").unwrap();
+    /// assert!(table_builder.remove("dir/string"));
+    /// assert!(table_builder.is_empty());
+    /// ```
+    pub fn remove(&mut self, key: &str) -> bool {
+        if !self.remove_subtree(key) {
+            return false;
+        }
 
-        for key in keys {
+        // Unlink the item from its parent container
+        let mut child = key.to_string();
+        while let Some((parent, children)) =
+            self.items.iter_mut().find_map(|(parent, item)| match item {
+                HashValue::Container(children) if children.contains(&child) => {
+                    Some((parent.clone(), children))
+                }
+                _ => None,
+            })
+        {
+            children.retain(|c| *c != child);
+            if !children.is_empty() {
+                break;
+            }
+
+            self.items.remove(&parent);
+            self.insertion_order.remove(&parent);
+            child = parent;
+        }
+
+        true
+    }
+
+    fn remove_subtree(&mut self, key: &str) -> bool {
+        self.insertion_order.remove(key);
+        match self.items.remove(key) {
+            Some(HashValue::Container(children)) => {
+                for child in children {
+                    self.remove_subtree(&child);
+                }
+                true
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// Returns the nested hash table builder at `key`, if there is one
+    ///
+    /// ```
+    /// # use gvdb::write::HashTableBuilder;
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert_table("table", HashTableBuilder::new()).unwrap();
+    /// table_builder.table_mut("table").unwrap().insert("int", 42u32).unwrap();
+    /// ```
+    pub fn table_mut(&mut self, key: &str) -> Option<&mut HashTableBuilder<'a>> {
+        match self.items.get_mut(key) {
+            Some(HashValue::TableBuilder(table_builder)) => Some(table_builder),
+            _ => None,
+        }
+    }
+
+    /// The number of hash buckets to use for the current number of items
+    fn n_buckets(&self) -> Result<usize> {
+        if let Some(n_buckets) = self.bucket_count {
+            if n_buckets == 0 && !self.items.is_empty() {
+                return Err(Error::Consistency(
+                    "A hash table with items needs at least one bucket".to_string(),
+                ));
+            }
+
+            return Ok(n_buckets);
+        }
+
+        if !self.load_factor.is_finite() || self.load_factor <= 0.0 {
+            return Err(Error::Consistency(format!(
+                "Invalid load factor {}, expected a positive number",
+                self.load_factor
+            )));
+        }
+
+        Ok((self.items.len() as f64 / self.load_factor as f64).ceil() as usize)
+    }
+
+    /// Build the hash table
+    ///
+    /// Items are prepended to their bucket, so the keys are inserted in ascending order by
+    /// default, which stores the items of each bucket in descending order. See
+    /// [`FileWriter::with_canonical_ordering`] and [`FileWriter::with_glib_compat_layout`] for
+    /// the other orders.
+    pub(crate) fn build(mut self, order: ItemOrder) -> Result<SimpleHashTable<'a>> {
+        let mut hash_table = SimpleHashTable::with_n_buckets(self.n_buckets()?);
+
+        for key in self.ordered_keys(order) {
             let value = self.items.remove(&key).unwrap();
             hash_table.insert(&key, value);
         }
@@ -247,32 +743,246 @@ impl<'a> HashTableBuilder<'a> {
     }
 }
 
-impl<'a> Default for HashTableBuilder<'a> {
-    fn default() -> Self {
-        Self::new()
+impl<'a> HashTableBuilder<'a> {
+    /// The keys in the order in which they are inserted into the hash buckets
+    fn ordered_keys(&self, order: ItemOrder) -> Vec<String> {
+        let mut keys: Vec<String> = self.items.keys().cloned().collect();
+        keys.sort();
+        match order {
+            ItemOrder::Sorted => {}
+            ItemOrder::Canonical => {
+                keys.sort_by_cached_key(|key| std::cmp::Reverse((djb_hash(key), key.clone())));
+            }
+            ItemOrder::Glib => {
+                keys.sort_by_key(|key| self.insertion_order.get(key).copied());
+                keys = glib_order::iteration_order(keys.iter().map(String::as_str))
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect();
+            }
+        }
+
+        keys
     }
-}
 
-#[derive(Debug)]
-struct Chunk {
-    // The pointer that points to the data where the chunk will be in memory in the finished file
-    pointer: Pointer,
+    /// The layout of this hash table when it is written with `writer`
+    ///
+    /// The plan lists the bucket and the final index of every item, without serializing any
+    /// values. Use this to predict the layout of a file or to check that the layout doesn't
+    /// depend on the order of insertion. See [`HashTablePlan`].
+    pub fn plan(&self, writer: &FileWriter) -> Result<HashTablePlan> {
+        let n_buckets = self.n_buckets()?;
+        let mut buckets = vec![Vec::new(); n_buckets];
+        for key in self.ordered_keys(writer.item_order()) {
+            let hash = djb_hash(&key);
+            buckets[hash as usize % n_buckets].push((key, hash));
+        }
 
-    // We use a boxed slice because this guarantees that the size is not changed afterwards
-    data: Box<[u8]>,
-}
+        // Items are prepended to their bucket
+        let mut items: Vec<PlannedItem> = buckets
+            .into_iter()
+            .enumerate()
+            .flat_map(|(bucket, keys)| {
+                keys.into_iter().rev().map(move |(key, hash)| {
+                    let typ = self.items[&key].typ();
+                    PlannedItem::new(key, hash, bucket, typ)
+                })
+            })
+            .collect();
+
+        let indices: HashMap<&str, usize> = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| (item.key(), index))
+            .collect();
+        let mut parents = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            if let Some(HashValue::Container(children)) = self.items.get(item.key()) {
+                for child in children {
+                    match indices.get(child.as_str()) {
+                        Some(child_index) => parents.push((*child_index, index)),
+                        None => {
+                            return Err(Error::Consistency(format!(
+                                "Child item '{}' not found for parent: '{}'",
+                                child,
+                                item.key()
+                            )))
+                        }
+                    }
+                }
+            }
+        }
 
-impl Chunk {
-    pub fn new(data: Box<[u8]>, pointer: Pointer) -> Self {
-        Self { pointer, data }
-    }
+        for (child, parent) in parents {
+            items[child].set_parent(parent);
+        }
 
-    pub fn data_mut(&mut self) -> &mut [u8] {
-        &mut self.data
+        Ok(HashTablePlan::new(n_buckets, items))
     }
+}
 
-    pub fn into_data(self) -> Box<[u8]> {
-        self.data
+impl HashTableBuilder<'static> {
+    /// Create a hash table builder containing all items of the existing hash table `table`
+    ///
+    /// Nested hash tables are converted recursively. The container items of `table` are
+    /// preserved as they are, independent of the path separator. Items that are inserted later
+    /// are split at `/`, use [`HashTableBuilder::from_hash_table_with_path_separator`] for tables
+    /// that were written with a different path separator.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use gvdb::read::File;
+    /// # use gvdb::write::{FileWriter, HashTableBuilder};
+    /// # let mut table_builder = HashTableBuilder::new();
+    /// # table_builder.insert_string("string", "test").unwrap();
+    /// # let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let mut table_builder = HashTableBuilder::from_hash_table(&file.hash_table().unwrap()).unwrap();
+    /// table_builder.insert("int", 42u32).unwrap();
+    /// ```
+    pub fn from_hash_table(table: &HashTable) -> crate::read::Result<Self> {
+        Self::from_hash_table_with_path_separator(table, Some("/"))
+    }
+
+    /// Create a hash table builder containing all items of the existing hash table `table`, which
+    /// was written with the path separator `sep`
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use gvdb::read::File;
+    /// # use gvdb::write::{FileWriter, HashTableBuilder};
+    /// let mut table_builder = HashTableBuilder::with_path_separator(Some(":"));
+    /// table_builder.insert("dir:a", 1u32).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// let mut table_builder =
+    ///     HashTableBuilder::from_hash_table_with_path_separator(&table, Some(":")).unwrap();
+    /// table_builder.insert("dir:b", 2u32).unwrap();
+    /// assert_eq!(table_builder.len(), 3);
+    /// ```
+    pub fn from_hash_table_with_path_separator(
+        table: &HashTable,
+        sep: Option<&str>,
+    ) -> crate::read::Result<Self> {
+        Self::from_hash_table_with(table, sep, &|table, key| {
+            Ok(HashValue::Value(
+                table.get_value(key)?.try_to_owned()?.into(),
+            ))
+        })
+    }
+}
+
+impl<'a> HashTableBuilder<'a> {
+    /// Create a hash table builder from `table` that references the serialized values of `table`
+    /// instead of decoding them
+    ///
+    /// The values are copied as they are, so the result must be written with the same
+    /// endianness as the file of `table`.
+    pub(crate) fn from_hash_table_raw(table: &HashTable<'a, '_>) -> crate::read::Result<Self> {
+        Self::from_hash_table_with(table, Some("/"), &|table, key| {
+            Ok(HashValue::Bytes(table.get_raw(key)?.1))
+        })
+    }
+
+    /// Create a hash table builder with the path separator `sep` from `table` that copies the
+    /// serialized values of `table`, converting them only if the result is written with a
+    /// different endianness
+    pub(crate) fn from_hash_table_copy(
+        table: &HashTable<'a, '_>,
+        sep: Option<&str>,
+    ) -> crate::read::Result<Self> {
+        let source: zvariant::Endian = Endian::from_byteswapped(table.file.byteswapped).into();
+        Self::from_hash_table_with(table, sep, &|table, key| {
+            Ok(HashValue::Serialized(SerializeFn::new_raw(
+                table.get_raw(key)?.1,
+                source,
+            )))
+        })
+    }
+
+    /// The items in the order of their insertion
+    pub(crate) fn into_items(mut self) -> Vec<(String, HashValue<'a>)> {
+        let mut items: Vec<_> = self.items.drain().collect();
+        items.sort_by_key(|(key, _)| self.insertion_order.get(key).copied());
+        items
+    }
+
+    pub(crate) fn item_mut(&mut self, key: &str) -> Option<&mut HashValue<'a>> {
+        self.items.get_mut(key)
+    }
+
+    fn from_hash_table_with<'t, 'file>(
+        table: &HashTable<'t, 'file>,
+        sep: Option<&str>,
+        value: &dyn Fn(&HashTable<'t, 'file>, &str) -> crate::read::Result<HashValue<'a>>,
+    ) -> crate::read::Result<Self> {
+        let mut builder = Self::with_path_separator(sep);
+        let items = table.keys_and_items()?;
+
+        for (key, item) in &items {
+            let value = match item.typ()? {
+                HashItemType::Value => value(table, key)?,
+                HashItemType::HashTable => HashValue::TableBuilder(Self::from_hash_table_with(
+                    &table.get_hash_table(key)?,
+                    sep,
+                    value,
+                )?),
+                HashItemType::Container => HashValue::Container(Vec::new()),
+            };
+
+            builder.record_insertion(key);
+            builder.items.insert(key.clone(), value);
+        }
+
+        for (key, item) in &items {
+            if item.parent() == 0xffffffff {
+                continue;
+            }
+
+            // The parent offsets have already been validated when building the keys
+            let parent_key = &items[item.parent() as usize].0;
+            if let Some(HashValue::Container(children)) = builder.items.get_mut(parent_key) {
+                children.push(key.clone());
+            } else {
+                return Err(crate::read::Error::Data(format!(
+                    "Parent item with key '{}' is not of type container",
+                    parent_key
+                )));
+            }
+        }
+
+        Ok(builder)
+    }
+}
+
+impl<'a> Default for HashTableBuilder<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Chunk {
+    // The pointer that points to the data where the chunk will be in memory in the finished file
+    pointer: Pointer,
+
+    // We use a boxed slice because this guarantees that the size is not changed afterwards
+    data: Box<[u8]>,
+}
+
+impl Chunk {
+    pub fn new(data: Box<[u8]>, pointer: Pointer) -> Self {
+        Self { pointer, data }
+    }
+
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+
+    pub fn into_data(self) -> Box<[u8]> {
+        self.data
     }
 
     pub fn pointer(&self) -> Pointer {
@@ -282,6 +992,9 @@ impl Chunk {
 
 /// Create GVDB files
 ///
+/// The output is deterministic: the same items and options always result in the same bytes,
+/// independent of the order in which the items were inserted into the [`HashTableBuilder`].
+///
 /// # Example
 /// ```
 /// use glib::prelude::*;
@@ -300,6 +1013,19 @@ pub struct FileWriter {
     offset: usize,
     chunks: VecDeque<Chunk>,
     byteswap: bool,
+    n_bloom_words: u32,
+    checksum: bool,
+    deduplicate: bool,
+    value_alignment: usize,
+    canonical_ordering: bool,
+    glib_compat: bool,
+    packed: bool,
+
+    // Indices of the chunks that may be shared, by the hash of their data
+    shared_chunks: HashMap<u64, Vec<usize>>,
+
+    // Start offsets of the unused space between chunks, by the size of the space
+    gaps: BTreeMap<usize, Vec<usize>>,
 }
 
 impl FileWriter {
@@ -308,86 +1034,397 @@ impl FileWriter {
     /// let file_writer = gvdb::write::FileWriter::new();
     /// ```
     pub fn new() -> Self {
-        #[cfg(target_endian = "little")]
-        let byteswap = false;
-        #[cfg(target_endian = "big")]
-        let byteswap = true;
-        Self::with_byteswap(byteswap)
+        Self::endian(Endian::Little)
     }
 
     /// Create a new instance configured for writing big endian data
     /// (not recommended for most use cases)
     /// ```
-    /// let file_writer = gvdb::write::FileWriter::new();
+    /// let file_writer = gvdb::write::FileWriter::for_big_endian();
     /// ```
     pub fn for_big_endian() -> Self {
-        #[cfg(target_endian = "little")]
-        let byteswap = true;
-        #[cfg(target_endian = "big")]
-        let byteswap = false;
-        Self::with_byteswap(byteswap)
+        Self::endian(Endian::Big)
+    }
+
+    /// Create a new instance configured for writing data in the byte order `endian`
+    /// ```
+    /// use gvdb::Endian;
+    /// use gvdb::write::FileWriter;
+    ///
+    /// let file_writer = FileWriter::endian(Endian::native());
+    /// ```
+    pub fn endian(endian: Endian) -> Self {
+        Self::with_byteswap(endian.is_byteswapped())
     }
 
     /// Specify manually whether you want to swap the endianness of the file. The default is to
     /// always create a little-endian file
-    fn with_byteswap(byteswap: bool) -> Self {
-        let mut this = Self {
-            offset: 0,
+    pub(crate) fn with_byteswap(byteswap: bool) -> Self {
+        let mut this = Self::at_offset(0, byteswap);
+
+        // The header chunk always fits into the file
+        let data = vec![0; size_of::<Header>()].into_boxed_slice();
+        this.chunks
+            .push_back(Chunk::new(data, Pointer::new(0, size_of::<Header>())));
+        this.offset = size_of::<Header>();
+        this
+    }
+
+    /// Create a writer that places its chunks at `offset` of a file that is written elsewhere.
+    /// No header chunk will be allocated, the chunks have to be written with
+    /// [`FileWriter::write_chunks`].
+    pub(crate) fn at_offset(offset: usize, byteswap: bool) -> Self {
+        Self {
+            offset,
             chunks: Default::default(),
             byteswap,
-        };
+            n_bloom_words: 0,
+            checksum: false,
+            deduplicate: false,
+            value_alignment: VALUE_ALIGNMENT,
+            canonical_ordering: false,
+            glib_compat: false,
+            packed: false,
+            shared_chunks: Default::default(),
+            gaps: Default::default(),
+        }
+    }
 
-        this.allocate_empty_chunk(size_of::<Header>(), 1);
-        this
+    /// Generate a bloom filter with `n_bloom_words` 32 bit words for every hash table
+    ///
+    /// The bloom filter allows readers to skip the bucket lookup for most keys that are not
+    /// contained in a hash table. The default is to not generate a bloom filter.
+    ///
+    /// ```
+    /// let file_writer = gvdb::write::FileWriter::new().with_bloom_words(8);
+    /// ```
+    pub fn with_bloom_words(mut self, n_bloom_words: u32) -> Self {
+        self.n_bloom_words = n_bloom_words;
+        self
+    }
+
+    /// Store a CRC32 checksum of the file data in the file header
+    ///
+    /// The checksum is stored in a header field that is ignored by GLib, so the files stay
+    /// compatible with other GVDB readers. Use [`File::verify_checksum`] to detect truncated or
+    /// damaged files. The default is to not store a checksum.
+    ///
+    /// The whole file is assembled in memory before it is written, as the header is written first.
+    ///
+    /// ```
+    /// let file_writer = gvdb::write::FileWriter::new().with_checksum(true);
+    /// ```
+    pub fn with_checksum(mut self, checksum: bool) -> Self {
+        self.checksum = checksum;
+        self
+    }
+
+    /// Share the chunks of identical keys and values
+    ///
+    /// Every key segment and value is written to its own chunk by default. With deduplication,
+    /// items with identical key segments or identical value data point to the same chunk, which
+    /// shrinks files with many repeated strings or payloads. The files can be read by all GVDB
+    /// readers, but are not byte-identical to the ones written by GLib. The default is to not
+    /// deduplicate.
+    ///
+    /// ```
+    /// let file_writer = gvdb::write::FileWriter::new().with_deduplication(true);
+    /// ```
+    pub fn with_deduplication(mut self, deduplicate: bool) -> Self {
+        self.deduplicate = deduplicate;
+        self
+    }
+
+    /// Align values that are at least `alignment` bytes large to a multiple of `alignment`
+    ///
+    /// This is useful for memory-mapped files with large values. With e.g. an alignment of 4096,
+    /// large values start at a page boundary and reading them doesn't touch the pages of their
+    /// neighbours. Smaller values keep the default alignment of 8 bytes, so they are not padded to
+    /// the full alignment. Readers are unaffected, as the data of every value is referenced with
+    /// its exact offsets.
+    ///
+    /// `alignment` must be a power of two, otherwise writing fails with [`Error::Consistency`].
+    ///
+    /// ```
+    /// let file_writer = gvdb::write::FileWriter::new().with_value_alignment(4096);
+    /// ```
+    pub fn with_value_alignment(mut self, alignment: usize) -> Self {
+        self.value_alignment = alignment;
+        self
+    }
+
+    /// Sort the items in each hash bucket by their hash value and key
+    ///
+    /// By default, the items of a bucket are stored in descending order of their keys. With
+    /// canonical ordering they are sorted by `(hash, key)` in ascending order instead, which is
+    /// independent of the bucket count and easy to reproduce with other writers. The chunks of
+    /// keys and values are written in the order of the items, so the whole file layout follows.
+    /// The default is to not use canonical ordering.
+    ///
+    /// ```
+    /// let file_writer = gvdb::write::FileWriter::new().with_canonical_ordering(true);
+    /// ```
+    pub fn with_canonical_ordering(mut self, canonical_ordering: bool) -> Self {
+        self.canonical_ordering = canonical_ordering;
+        self
+    }
+
+    /// Lay out the file exactly like the GVDB writer of GLib
+    ///
+    /// The chunks are always written in the same order and with the same alignment as by GLib's
+    /// `gvdb_table_write_contents`. The order of the items inside each hash bucket, the order of
+    /// the children of containers and the offsets of empty chunks can still differ, as they
+    /// depend on the internals of GLib. With this option they match as well, so identical input
+    /// produces identical bytes. This is useful e.g. for binary delta updates of files that were
+    /// written by `glib-compile-resources` before.
+    ///
+    /// GLib adds the items of a hash table to a `GHashTable`, whose order of iteration depends on
+    /// the order of insertion. The items of a [`HashTableBuilder`] are treated as if they were
+    /// inserted in the same order, where parent containers that are created for a key are
+    /// inserted after the key. Removed items don't leave a trace.
+    ///
+    /// The layout only matches if the options that GLib doesn't support, like bloom words,
+    /// deduplication, a value alignment or a bucket count, are left at their default. This option
+    /// takes precedence over [`with_canonical_ordering`](Self::with_canonical_ordering). The
+    /// default is to not use the GLib compatible layout.
+    ///
+    /// ```
+    /// let file_writer = gvdb::write::FileWriter::new().with_glib_compat_layout(true);
+    /// ```
+    pub fn with_glib_compat_layout(mut self, glib_compat: bool) -> Self {
+        self.glib_compat = glib_compat;
+        self
+    }
+
+    /// Place small chunks in the padding between aligned chunks
+    ///
+    /// Values are aligned to 8 bytes, so a file with many tiny values, like integers or short
+    /// strings, consists of a lot of padding. With a packed layout, keys, container children and
+    /// other chunks are written into this padding when they fit, instead of being appended to the
+    /// end of the file. Readers are unaffected, as every chunk is referenced with its exact
+    /// offsets. Values themselves are not packed, as GLib only reads values that start at a
+    /// multiple of 8 bytes. Identical values can share a chunk with
+    /// [`with_deduplication`](Self::with_deduplication) instead. Use [`File::stats`] and [`FileStats::padding_bytes`](crate::read::FileStats::padding_bytes) to
+    /// quantify the savings. The default is to not pack the file.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let table = || {
+    ///     let mut table_builder = HashTableBuilder::new();
+    ///     for i in 0..100u32 {
+    ///         table_builder.insert(&format!("key{}", i), i).unwrap();
+    ///     }
+    ///     table_builder
+    /// };
+    ///
+    /// let data = FileWriter::new().write_to_vec_with_table(table()).unwrap();
+    /// let packed = FileWriter::new()
+    ///     .with_packed_layout(true)
+    ///     .write_to_vec_with_table(table())
+    ///     .unwrap();
+    ///
+    /// let stats = File::from_bytes(Cow::Borrowed(&data)).unwrap().stats().unwrap();
+    /// let packed_stats = File::from_bytes(Cow::Borrowed(&packed)).unwrap().stats().unwrap();
+    /// assert!(packed.len() < data.len());
+    /// assert!(packed_stats.padding_bytes() < stats.padding_bytes());
+    /// ```
+    pub fn with_packed_layout(mut self, packed: bool) -> Self {
+        self.packed = packed;
+        self
+    }
+
+    /// The order of the items in the hash buckets
+    fn item_order(&self) -> ItemOrder {
+        if self.glib_compat {
+            ItemOrder::Glib
+        } else if self.canonical_ordering {
+            ItemOrder::Canonical
+        } else {
+            ItemOrder::Sorted
+        }
+    }
+
+    /// The alignment of a value chunk with `size` bytes
+    fn value_alignment(&self, size: usize) -> Result<usize> {
+        if !self.value_alignment.is_power_of_two() {
+            return Err(Error::Consistency(format!(
+                "Invalid value alignment {}, expected a power of two",
+                self.value_alignment
+            )));
+        }
+
+        if size >= self.value_alignment {
+            Ok(self.value_alignment.max(VALUE_ALIGNMENT))
+        } else {
+            Ok(VALUE_ALIGNMENT)
+        }
+    }
+
+    /// Allocate a value chunk
+    fn add_value_data(&mut self, data: Box<[u8]>) -> Result<(usize, &mut Chunk)> {
+        let alignment = self.value_alignment(data.len())?;
+        self.allocate_shared_chunk(data, alignment)
     }
 
     /// Allocate a chunk
+    ///
+    /// Returns [`Error::TooLarge`] if the chunk would end beyond the 4 GiB that can be addressed
+    /// by the 32 bit offsets of the file format.
     fn allocate_chunk_with_data(
         &mut self,
         data: Box<[u8]>,
         alignment: usize,
-    ) -> (usize, &mut Chunk) {
+    ) -> Result<(usize, &mut Chunk)> {
+        // GLib doesn't allocate any space for empty chunks
+        if self.glib_compat && data.is_empty() {
+            self.chunks.push_back(Chunk::new(data, Pointer::NULL));
+            let index = self.chunks.len() - 1;
+            return Ok((index, &mut self.chunks[index]));
+        }
+
+        if self.packed {
+            if let Some(offset_start) = self.take_gap(data.len(), alignment) {
+                trace_event!(
+                    trace,
+                    offset = offset_start,
+                    size = data.len(),
+                    alignment,
+                    "Allocated chunk in padding"
+                );
+                let pointer = Pointer::new(offset_start, offset_start + data.len());
+                self.chunks.push_back(Chunk::new(data, pointer));
+                let index = self.chunks.len() - 1;
+                return Ok((index, &mut self.chunks[index]));
+            }
+        }
+
         // Align the data
-        self.offset = align_offset(self.offset, alignment);
+        let offset_start = align_offset(self.offset, alignment);
 
         // Calculate the pointer
-        let offset_start = self.offset;
-        let offset_end = offset_start + data.len();
+        let offset_end = offset_start
+            .checked_add(data.len())
+            .filter(|end| *end <= u32::MAX as usize)
+            .ok_or(Error::TooLarge {
+                offset: offset_start,
+                size: data.len(),
+            })?;
         let pointer = Pointer::new(offset_start, offset_end);
 
+        if self.packed {
+            self.add_gap(self.offset, offset_start);
+        }
+
+        trace_event!(
+            trace,
+            offset = offset_start,
+            size = data.len(),
+            padding = offset_start - self.offset,
+            "Allocated chunk"
+        );
+
         // Update the offset to the end of the chunk
         self.offset = offset_end;
 
         let chunk = Chunk::new(data, pointer);
         self.chunks.push_back(chunk);
         let index = self.chunks.len() - 1;
-        (index, &mut self.chunks[index])
+        Ok((index, &mut self.chunks[index]))
+    }
+
+    /// Remember the unused space from `start` to `end` for a packed layout
+    fn add_gap(&mut self, start: usize, end: usize) {
+        if start < end {
+            self.gaps.entry(end - start).or_default().push(start);
+        }
+    }
+
+    /// Find unused space for a chunk with `size` bytes and `alignment` between the existing chunks
+    ///
+    /// Returns the start offset of the chunk. The remaining space before and after the chunk stays
+    /// available. Only the most recent gap of every size is checked, as most gaps are the padding
+    /// before an aligned chunk and differ only in their size.
+    fn take_gap(&mut self, size: usize, alignment: usize) -> Option<usize> {
+        if size == 0 {
+            return None;
+        }
+
+        let (gap_size, gap_start, start) =
+            self.gaps.range(size..).find_map(|(gap_size, starts)| {
+                let gap_start = *starts.last()?;
+                let start = align_offset(gap_start, alignment);
+                (start + size <= gap_start + gap_size).then_some((*gap_size, gap_start, start))
+            })?;
+
+        let starts = self.gaps.get_mut(&gap_size)?;
+        starts.pop();
+        if starts.is_empty() {
+            self.gaps.remove(&gap_size);
+        }
+
+        self.add_gap(gap_start, start);
+        self.add_gap(start + size, gap_start + gap_size);
+        Some(start)
+    }
+
+    /// Allocate a chunk for data that is never modified after it was written
+    ///
+    /// With deduplication enabled, an existing chunk with identical data and a suitable alignment
+    /// is returned instead of allocating a new one.
+    fn allocate_shared_chunk(
+        &mut self,
+        data: Box<[u8]>,
+        alignment: usize,
+    ) -> Result<(usize, &mut Chunk)> {
+        if !self.deduplicate {
+            return self.allocate_chunk_with_data(data, alignment);
+        }
+
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let existing = self.shared_chunks.get(&hash).and_then(|indices| {
+            indices.iter().copied().find(|index| {
+                let chunk = &self.chunks[*index];
+                chunk.data == data && chunk.pointer().start() as usize % alignment == 0
+            })
+        });
+
+        if let Some(index) = existing {
+            return Ok((index, &mut self.chunks[index]));
+        }
+
+        let index = self.allocate_chunk_with_data(data, alignment)?.0;
+        self.shared_chunks.entry(hash).or_default().push(index);
+        Ok((index, &mut self.chunks[index]))
     }
 
-    fn allocate_empty_chunk(&mut self, size: usize, alignment: usize) -> (usize, &mut Chunk) {
+    fn allocate_empty_chunk(
+        &mut self,
+        size: usize,
+        alignment: usize,
+    ) -> Result<(usize, &mut Chunk)> {
         let data = vec![0; size].into_boxed_slice();
         self.allocate_chunk_with_data(data, alignment)
     }
 
-    fn add_value(&mut self, value: &zvariant::Value) -> Result<(usize, &mut Chunk)> {
-        #[cfg(target_endian = "little")]
-        let le = true;
-        #[cfg(target_endian = "big")]
-        let le = false;
-
-        let data: Box<[u8]> = if le && !self.byteswap || !le && self.byteswap {
-            let context = zvariant::serialized::Context::new_gvariant(zvariant::LE, 0);
-            Box::from(&*zvariant::to_bytes(context, value)?)
-        } else {
-            let context = zvariant::serialized::Context::new_gvariant(zvariant::BE, 0);
-            Box::from(&*zvariant::to_bytes(context, value)?)
-        };
+    /// The byte order of the values in the file
+    pub(crate) fn zvariant_endianess(&self) -> zvariant::Endian {
+        Endian::from_byteswapped(self.byteswap).into()
+    }
 
-        Ok(self.allocate_chunk_with_data(data, 8))
+    pub(crate) fn add_value(&mut self, value: &zvariant::Value) -> Result<(usize, &mut Chunk)> {
+        let context = zvariant::serialized::Context::new_gvariant(self.zvariant_endianess(), 0);
+        let data: Box<[u8]> = Box::from(&*zvariant::to_bytes(context, value)?);
+        self.add_value_data(data)
     }
 
     #[cfg(feature = "glib")]
-    fn add_gvariant(&mut self, variant: &glib::Variant) -> (usize, &mut Chunk) {
+    pub(crate) fn add_gvariant(&mut self, variant: &glib::Variant) -> Result<(usize, &mut Chunk)> {
         let value = if self.byteswap {
             glib::Variant::from_variant(&variant.byteswap())
         } else {
@@ -396,12 +1433,36 @@ impl FileWriter {
 
         let normal = value.normal_form();
         let data = normal.data();
-        self.allocate_chunk_with_data(data.to_vec().into_boxed_slice(), 8)
+        self.add_value_data(data.to_vec().into_boxed_slice())
     }
 
-    fn add_string(&mut self, string: &str) -> (usize, &mut Chunk) {
-        let data = string.to_string().into_boxed_str().into_boxed_bytes();
-        self.allocate_chunk_with_data(data, 1)
+    fn add_key(&mut self, key: &str) -> Result<(usize, &mut Chunk)> {
+        // The size of keys is stored as 16 bit integer
+        if key.len() > u16::MAX as usize {
+            return Err(Error::KeyTooLong {
+                key: key.to_string(),
+            });
+        }
+
+        let data = key.to_string().into_boxed_str().into_boxed_bytes();
+        self.allocate_shared_chunk(data, 1)
+    }
+
+    /// Calculate the bloom filter words for all items of `table`
+    fn bloom_words(&self, table: &SimpleHashTable) -> Vec<u32> {
+        let mut bloom_words = vec![0u32; self.n_bloom_words as usize];
+        if self.n_bloom_words == 0 {
+            return bloom_words;
+        }
+
+        for (_bucket, item) in table.iter() {
+            let hash_value = item.hash();
+            let word = (hash_value / 32) % self.n_bloom_words;
+            bloom_words[word as usize] |=
+                1 << (hash_value & 31) | 1 << ((hash_value >> BLOOM_SHIFT) & 31);
+        }
+
+        bloom_words
     }
 
     fn add_simple_hash_table(&mut self, table: SimpleHashTable) -> Result<(usize, &mut Chunk)> {
@@ -409,7 +1470,15 @@ impl FileWriter {
             item.set_assigned_index(index as u32);
         }
 
-        let header = HashHeader::new(5, 0, table.n_buckets() as u32);
+        if self.n_bloom_words >= MAX_BLOOM_WORDS {
+            return Err(Error::Consistency(format!(
+                "Number of bloom words {} exceeds the maximum of {}",
+                self.n_bloom_words,
+                MAX_BLOOM_WORDS - 1
+            )));
+        }
+
+        let header = HashHeader::new(BLOOM_SHIFT, self.n_bloom_words, table.n_buckets() as u32);
         let items_len = table.n_items() * size_of::<HashItem>();
         let size =
             size_of::<HashHeader>() + header.bloom_words_len() + header.buckets_len() + items_len;
@@ -417,10 +1486,17 @@ impl FileWriter {
         let hash_buckets_offset = size_of::<HashHeader>() + header.bloom_words_len();
         let hash_items_offset = hash_buckets_offset + header.buckets_len();
 
-        let (hash_table_chunk_index, hash_table_chunk) = self.allocate_empty_chunk(size, 4);
-        let header = transmute_one_to_bytes(&header);
+        let bloom_words = self.bloom_words(&table);
+        let (hash_table_chunk_index, hash_table_chunk) = self.allocate_empty_chunk(size, 4)?;
+        let header = header.as_bytes();
         hash_table_chunk.data_mut()[0..header.len()].copy_from_slice(header);
 
+        for (index, word) in bloom_words.into_iter().enumerate() {
+            let start = size_of::<HashHeader>() + index * size_of::<u32>();
+            hash_table_chunk.data_mut()[start..start + size_of::<u32>()]
+                .copy_from_slice(&word.to_le_bytes());
+        }
+
         let mut n_item = 0;
         for bucket in 0..table.n_buckets() {
             let hash_bucket_start = hash_buckets_offset + bucket * size_of::<u32>();
@@ -449,17 +1525,30 @@ impl FileWriter {
                     )));
                 }
 
-                let key_ptr = self.add_string(key).1.pointer();
+                let key_ptr = self.add_key(key)?.1.pointer();
                 let typ = current_item.value_ref().typ();
 
                 let value_ptr = match current_item.value().take() {
+                    HashValue::Written(_, pointer) => pointer,
                     HashValue::Value(value) => self.add_value(&value)?.1.pointer(),
+                    HashValue::Serialized(serialize) => {
+                        let data = serialize
+                            .serialize(self.zvariant_endianess())?
+                            .into_boxed_slice();
+                        self.add_value_data(data)?.1.pointer()
+                    }
+                    HashValue::Bytes(data) => self.add_value_data(Box::from(data))?.1.pointer(),
                     #[cfg(feature = "glib")]
-                    HashValue::GVariant(variant) => self.add_gvariant(&variant).1.pointer(),
+                    HashValue::GVariant(variant) => self.add_gvariant(&variant)?.1.pointer(),
                     HashValue::TableBuilder(tb) => self.add_table_builder(tb)?.1.pointer(),
-                    HashValue::Container(children) => {
+                    HashValue::Container(mut children) => {
+                        // GLib sorts the children of a container by their key
+                        if self.glib_compat {
+                            children.sort();
+                        }
+
                         let size = children.len() * size_of::<u32>();
-                        let chunk = self.allocate_empty_chunk(size, 4).1;
+                        let chunk = self.allocate_empty_chunk(size, 4)?.1;
 
                         let mut offset = 0;
                         for child in children {
@@ -490,27 +1579,35 @@ impl FileWriter {
                 let hash_item_end = hash_item_start + size_of::<HashItem>();
 
                 self.chunks[hash_table_chunk_index].data[hash_item_start..hash_item_end]
-                    .copy_from_slice(transmute_one_to_bytes(&hash_item));
+                    .copy_from_slice(hash_item.as_bytes());
 
                 n_item += 1;
             }
         }
 
+        if n_item != table.n_items() {
+            return Err(Error::Consistency(format!(
+                "Expected {} items in the hash buckets, found {}",
+                table.n_items(),
+                n_item
+            )));
+        }
+
         Ok((
             hash_table_chunk_index,
             &mut self.chunks[hash_table_chunk_index],
         ))
     }
 
-    fn add_table_builder(
+    pub(crate) fn add_table_builder(
         &mut self,
         table_builder: HashTableBuilder,
     ) -> Result<(usize, &mut Chunk)> {
-        self.add_simple_hash_table(table_builder.build()?)
+        self.add_simple_hash_table(table_builder.build(self.item_order())?)
     }
 
     fn file_size(&self) -> usize {
-        self.chunks[self.chunks.len() - 1].pointer().end() as usize
+        self.offset
     }
 
     fn serialize(mut self, root_chunk_index: usize, writer: &mut dyn Write) -> Result<usize> {
@@ -522,23 +1619,47 @@ impl FileWriter {
             })?
             .pointer();
         let header = Header::new(self.byteswap, 0, root_ptr);
-        self.chunks[0].data_mut()[0..size_of::<Header>()]
-            .copy_from_slice(transmute_one_to_bytes(&header));
+        self.chunks[0].data_mut()[0..size_of::<Header>()].copy_from_slice(header.as_bytes());
+
+        if !self.checksum {
+            return self.write_chunks(0, writer);
+        }
+
+        let mut data = Vec::with_capacity(self.file_size());
+        self.write_chunks(0, &mut data)?;
+        let header = header.with_checksum(crc32fast::hash(&data[size_of::<Header>()..]));
+        data[0..size_of::<Header>()].copy_from_slice(header.as_bytes());
+
+        writer.write_all(&data)?;
+        Ok(data.len())
+    }
+
+    /// Write all chunks to `writer`, which is currently located at file offset `position`.
+    /// The space between the chunks is filled with zero bytes.
+    ///
+    /// Returns the file offset after the last chunk.
+    pub(crate) fn write_chunks(self, mut position: usize, writer: &mut dyn Write) -> Result<usize> {
+        let mut chunks = self.chunks;
+        if self.packed {
+            // Chunks in gaps are located before chunks that were allocated earlier
+            chunks
+                .make_contiguous()
+                .sort_by_key(|chunk| chunk.pointer().start());
+        }
 
-        let mut size = 0;
-        for chunk in self.chunks.into_iter() {
+        for chunk in chunks.into_iter() {
             // Align
-            if size < chunk.pointer().start() as usize {
-                let padding = chunk.pointer().start() as usize - size;
-                size += padding;
+            if position < chunk.pointer().start() as usize {
+                let padding = chunk.pointer().start() as usize - position;
+                position += padding;
                 writer.write_all(&vec![0; padding])?;
             }
 
-            size += chunk.pointer().size();
+            position += chunk.pointer().size();
             writer.write_all(&chunk.into_data())?;
         }
 
-        Ok(size)
+        Ok(position)
     }
 
     fn serialize_to_vec(self, root_chunk_index: usize) -> Result<Vec<u8>> {
@@ -562,6 +1683,111 @@ impl FileWriter {
         let index = self.add_table_builder(table_builder)?.0;
         self.serialize_to_vec(index)
     }
+
+    /// Write a GVDB file with one nested hash table per entry of `tables` into the provided
+    /// [`std::io::Write`]
+    ///
+    /// The names of the tables are used verbatim as the keys of the root hash table. Read the
+    /// tables with [`File::table`] and list them with [`File::table_names`].
+    pub fn write_with_tables(
+        mut self,
+        tables: BTreeMap<String, HashTableBuilder>,
+        writer: &mut dyn Write,
+    ) -> Result<usize> {
+        let index = self.add_table_builder(Self::root_for_tables(tables)?)?.0;
+        self.serialize(index, writer)
+    }
+
+    /// Create a [`Vec<u8>`] with the GVDB file data, containing one nested hash table per entry of
+    /// `tables`
+    ///
+    /// ```
+    /// use gvdb::read::File;
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    /// use std::borrow::Cow;
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut settings = HashTableBuilder::new();
+    /// settings.insert("volume", 11u32).unwrap();
+    /// let mut cache = HashTableBuilder::new();
+    /// cache.insert_string("last-file", "/tmp/test").unwrap();
+    ///
+    /// let tables = BTreeMap::from([
+    ///     ("settings".to_string(), settings),
+    ///     ("cache".to_string(), cache),
+    /// ]);
+    /// let data = FileWriter::new().write_to_vec_with_tables(tables).unwrap();
+    ///
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// assert_eq!(file.table_names().unwrap(), vec!["cache", "settings"]);
+    /// assert_eq!(file.table("settings").unwrap().get::<u32>("volume").unwrap(), 11);
+    /// ```
+    pub fn write_to_vec_with_tables(
+        mut self,
+        tables: BTreeMap<String, HashTableBuilder>,
+    ) -> Result<Vec<u8>> {
+        let index = self.add_table_builder(Self::root_for_tables(tables)?)?.0;
+        self.serialize_to_vec(index)
+    }
+
+    fn root_for_tables<'a>(
+        tables: BTreeMap<String, HashTableBuilder<'a>>,
+    ) -> Result<HashTableBuilder<'a>> {
+        let mut root = HashTableBuilder::with_path_separator(None);
+        for (name, table) in tables {
+            root.insert_table(&name, table)?;
+        }
+
+        Ok(root)
+    }
+
+    /// Create a [`Vec<u8>`] with the data of `original` after applying `changes` to its root
+    /// hash table
+    ///
+    /// The serialized data of all values that are not changed is copied from `original` as it is,
+    /// without decoding and encoding it again. Only the hash tables and changed values are
+    /// created from scratch. The endianness of `original` is preserved.
+    ///
+    /// ```
+    /// use gvdb::read::File;
+    /// use gvdb::write::{ChangeSet, FileWriter};
+    /// use std::borrow::Cow;
+    /// use std::path::PathBuf;
+    ///
+    /// let file = File::from_file(&PathBuf::from("test-data/test2.gvdb")).unwrap();
+    /// let mut changes = ChangeSet::new();
+    /// changes.insert("int", 42u32);
+    /// changes.remove("table");
+    ///
+    /// let data = FileWriter::rewrite_with_changes(&file, changes).unwrap();
+    /// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+    /// assert_eq!(file.hash_table().unwrap().get::<u32>("int").unwrap(), 42);
+    /// ```
+    pub fn rewrite_with_changes(original: &File, changes: ChangeSet) -> Result<Vec<u8>> {
+        let mut table_builder = HashTableBuilder::from_hash_table_raw(&original.hash_table()?)?;
+        changes.apply(&mut table_builder)?;
+        Self::with_byteswap(original.byteswapped).write_to_vec_with_table(table_builder)
+    }
+
+    /// Asynchronously write the GVDB file into the provided [`tokio::io::AsyncWrite`]
+    ///
+    /// The file data is serialized in memory and then written to `writer`.
+    #[cfg(feature = "async")]
+    pub async fn write_with_table_async<W>(
+        self,
+        table_builder: HashTableBuilder<'_>,
+        writer: &mut W,
+    ) -> Result<usize>
+    where
+        W: tokio::io::AsyncWrite + Unpin + ?Sized,
+    {
+        use tokio::io::AsyncWriteExt;
+
+        let data = self.write_to_vec_with_table(table_builder)?;
+        writer.write_all(&data).await?;
+        writer.flush().await?;
+        Ok(data.len())
+    }
 }
 
 impl Default for FileWriter {
@@ -580,7 +1806,7 @@ mod test {
 
     use crate::test::{
         assert_bytes_eq, assert_is_file_1, assert_is_file_2, byte_compare_file_1,
-        byte_compare_file_2,
+        byte_compare_file_2, byte_compare_file_3, TEST_FILE_1, TEST_FILE_2, TEST_FILE_3,
     };
     #[allow(unused_imports)]
     use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
@@ -609,7 +1835,7 @@ mod test {
         builder2.insert_bytes("bytes", &[1, 2, 3, 4]).unwrap();
         builder.insert_table("table", builder2).unwrap();
 
-        let table = builder.build().unwrap();
+        let table = builder.build(ItemOrder::Sorted).unwrap();
 
         assert_eq!(
             table.get("string").unwrap().value_ref().value().unwrap(),
@@ -629,7 +1855,7 @@ mod test {
             panic!("Invalid value");
         };
 
-        let table2 = tb.build().unwrap();
+        let table2 = tb.build(ItemOrder::Sorted).unwrap();
         let data: &[u8] = &[1, 2, 3, 4];
         assert_eq!(
             table2.get("bytes").unwrap().value_ref().value().unwrap(),
@@ -711,14 +1937,108 @@ mod test {
             }
 
             let data = file_builder.write_to_vec_with_table(table_builder).unwrap();
-            if last_data.is_some() {
-                assert_bytes_eq(&last_data.unwrap(), &data, "Reproducible builds");
+            if let Some(last_data) = last_data {
+                assert_bytes_eq(&last_data, &data, "Reproducible builds");
             }
 
             last_data = Some(data);
         }
     }
 
+    #[test]
+    fn canonical_ordering() {
+        let build = |keys: &mut dyn Iterator<Item = u32>| {
+            let mut table_builder = HashTableBuilder::new().with_bucket_count(8);
+            for num in keys {
+                table_builder.insert(&format!("key{}", num), num).unwrap();
+            }
+
+            FileWriter::new()
+                .with_canonical_ordering(true)
+                .write_to_vec_with_table(table_builder)
+                .unwrap()
+        };
+
+        let data = build(&mut (0..200));
+        assert_bytes_eq(&data, &build(&mut (0..200).rev()), "Insertion order");
+
+        // Items are sorted by bucket, hash and key
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+        let items: Vec<(u32, u32, String)> = table
+            .keys()
+            .unwrap()
+            .into_iter()
+            .enumerate()
+            .map(|(index, key)| {
+                let hash = table.get_hash_item_for_index(index).unwrap().hash_value();
+                (hash % 8, hash, key)
+            })
+            .collect();
+        let mut sorted = items.clone();
+        sorted.sort();
+        assert_eq!(items, sorted);
+
+        // Files written by GLib only have one item per bucket
+        let mut table_builder = HashTableBuilder::new();
+        table_builder
+            .insert_value(
+                "root_key",
+                zvariant::Value::new((1234u32, 98765u32, "TEST_STRING_VALUE")),
+            )
+            .unwrap();
+        let data = FileWriter::new()
+            .with_canonical_ordering(true)
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let root = File::from_bytes(Cow::Owned(data)).unwrap();
+        byte_compare_file_1(&root);
+
+        let root = File::from_file(&TEST_FILE_3).unwrap();
+        let table_builder = HashTableBuilder::from_hash_table(&root.hash_table().unwrap()).unwrap();
+        let data = FileWriter::new()
+            .with_canonical_ordering(true)
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        byte_compare_file_3(&File::from_bytes(Cow::Owned(data)).unwrap());
+    }
+
+    #[test]
+    fn insert_serialized() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize, zvariant::Type)]
+        struct Config {
+            name: String,
+            size: (u32, u16),
+            values: Vec<i64>,
+        }
+
+        let config = Config {
+            name: "window".to_string(),
+            size: (800, 600),
+            values: vec![-1, 2, 3],
+        };
+
+        for writer in [FileWriter::new(), FileWriter::for_big_endian()] {
+            let mut table_builder = HashTableBuilder::new();
+            table_builder.insert_serialized("config", &config).unwrap();
+            table_builder.insert_serialized("nested/int", &5u8).unwrap();
+            let data = writer.write_to_vec_with_table(table_builder).unwrap();
+
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            let table = file.hash_table().unwrap();
+            assert_eq!(table.get::<Config>("config").unwrap(), config);
+            assert_eq!(table.get::<u8>("nested/int").unwrap(), 5);
+            assert_eq!(
+                table
+                    .get_value("config")
+                    .unwrap()
+                    .value_signature()
+                    .as_str(),
+                "(s(uq)ax)"
+            );
+        }
+    }
+
     #[test]
     fn big_endian() {
         let mut file_builder = FileWriter::for_big_endian();
@@ -742,6 +2062,488 @@ mod test {
         assert_is_file_1(&root);
     }
 
+    #[test]
+    fn too_large() {
+        let mut writer = FileWriter::at_offset(u32::MAX as usize - 16, false);
+        writer.add_value(&zvariant::Value::new(1u32)).unwrap();
+        assert_matches!(
+            writer.add_value(&zvariant::Value::new(vec![0u8; 16])),
+            Err(Error::TooLarge { .. })
+        );
+
+        let mut table_builder = HashTableBuilder::new().with_key_validation(false);
+        table_builder
+            .insert_string(&"a".repeat(u16::MAX as usize + 1), "long key")
+            .unwrap();
+        assert_matches!(
+            FileWriter::new().write_to_vec_with_table(table_builder),
+            Err(Error::KeyTooLong { .. })
+        );
+
+        let mut table_builder = HashTableBuilder::new();
+        table_builder
+            .insert_string(&"a".repeat(u16::MAX as usize), "long key")
+            .unwrap();
+        assert!(FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .is_ok());
+    }
+
+    #[test]
+    fn escaped_keys() {
+        let mut table_builder = HashTableBuilder::with_path_separator(Some("::"))
+            .with_escape_char('\\')
+            .with_bucket_count(4);
+        assert_eq!(table_builder.split_key(""), [""]);
+        assert_eq!(table_builder.split_key("a\\::b::c"), ["a::b::", "a::b::c"]);
+        assert_eq!(table_builder.split_key("a:\\:b"), ["a::b"]);
+        assert_eq!(table_builder.split_key("a\\\\::b\\"), ["a\\::", "a\\::b\\"]);
+
+        table_builder.insert("dir::a\\::b", 1u32).unwrap();
+        table_builder.insert("dir::c", 2u32).unwrap();
+        table_builder.insert("d\\::e::f", 3u32).unwrap();
+        assert!(table_builder.contains_key("dir::a::b"));
+        assert!(!table_builder.contains_key("dir::a::"));
+
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+        let mut keys = table.keys().unwrap();
+        keys.sort();
+        assert_eq!(keys, ["d::e::", "d::e::f", "dir::", "dir::a::b", "dir::c"]);
+        assert_eq!(table.get::<u32>("dir::a::b").unwrap(), 1);
+        assert_eq!(table.get::<u32>("d::e::f").unwrap(), 3);
+    }
+
+    #[test]
+    fn key_validation() {
+        let mut table_builder = HashTableBuilder::new();
+        let err = table_builder.insert("dir/a\0b", 1u32).unwrap_err();
+        assert_matches!(
+            err,
+            Error::InvalidKey {
+                reason: InvalidKeyReason::Nul { position: 5 },
+                ..
+            }
+        );
+
+        // The limit applies to the segments between path separators
+        let segment = "a".repeat(u16::MAX as usize);
+        assert_matches!(
+            table_builder.insert(&format!("{}/b", segment), 1u32),
+            Err(Error::InvalidKey {
+                reason: InvalidKeyReason::SegmentTooLong { length: 65536 },
+                ..
+            })
+        );
+        table_builder
+            .insert(&format!("b/{}", segment), 1u32)
+            .unwrap();
+        table_builder
+            .insert(&format!("{}/{}", &segment[1..], segment), 1u32)
+            .unwrap();
+        assert_eq!(table_builder.len(), 4);
+
+        let mut table_builder = HashTableBuilder::with_path_separator(None);
+        assert_matches!(
+            table_builder.insert(&format!("{}/b", segment), 1u32),
+            Err(Error::InvalidKey { .. })
+        );
+        assert!(table_builder.is_empty());
+
+        // Exotic keys can be written deliberately
+        let mut table_builder = HashTableBuilder::new().with_key_validation(false);
+        table_builder.insert("dir/a\0b", 1u32).unwrap();
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        assert_eq!(
+            file.hash_table().unwrap().get::<u32>("dir/a\0b").unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn key_validation_verbatim() {
+        let long = "a".repeat(u16::MAX as usize + 1);
+        let mut table_builder = HashTableBuilder::new();
+        assert_matches!(
+            table_builder.insert_verbatim("a\0b", zvariant::Value::new(1u32)),
+            Err(Error::InvalidKey {
+                reason: InvalidKeyReason::Nul { position: 1 },
+                ..
+            })
+        );
+        assert_matches!(
+            table_builder.insert_verbatim(&long, zvariant::Value::new(1u32)),
+            Err(Error::InvalidKey {
+                reason: InvalidKeyReason::SegmentTooLong { length: 65536 },
+                ..
+            })
+        );
+        assert_matches!(
+            table_builder.insert_container("dir\0/", vec!["dir\0/a".to_string()]),
+            Err(Error::InvalidKey { .. })
+        );
+        assert_matches!(
+            table_builder.insert_container("dir/", vec!["dir/a\0".to_string()]),
+            Err(Error::InvalidKey {
+                reason: InvalidKeyReason::Nul { position: 5 },
+                ..
+            })
+        );
+        assert_matches!(
+            table_builder.insert_container("dir/", vec![format!("dir/{}", long)]),
+            Err(Error::InvalidKey {
+                reason: InvalidKeyReason::SegmentTooLong { length: 65536 },
+                ..
+            })
+        );
+        assert!(table_builder.is_empty());
+
+        // Without validation, keys that are too long fail when the file is written
+        let mut table_builder = HashTableBuilder::new().with_key_validation(false);
+        table_builder
+            .insert_verbatim(&long, zvariant::Value::new(1u32))
+            .unwrap();
+        assert_matches!(
+            FileWriter::new().write_to_vec_with_table(table_builder),
+            Err(Error::KeyTooLong { .. })
+        );
+
+        let mut table_builder = HashTableBuilder::new().with_key_validation(false);
+        table_builder
+            .insert_container("dir/", vec![format!("dir/{}", long)])
+            .unwrap();
+        table_builder
+            .insert_verbatim(&format!("dir/{}", long), zvariant::Value::new(1u32))
+            .unwrap();
+        assert_matches!(
+            FileWriter::new().write_to_vec_with_table(table_builder),
+            Err(Error::KeyTooLong { .. })
+        );
+    }
+
+    #[test]
+    fn checksum() {
+        fn new_simple_table() -> HashTableBuilder<'static> {
+            let mut table_builder = HashTableBuilder::new();
+            table_builder
+                .insert_string("string", "test string")
+                .unwrap();
+            table_builder
+        }
+
+        for writer in [FileWriter::new(), FileWriter::for_big_endian()] {
+            let mut data = Vec::new();
+            writer
+                .with_checksum(true)
+                .write_with_table(new_simple_table(), &mut data)
+                .unwrap();
+
+            let file = File::from_bytes(Cow::Borrowed(&data)).unwrap();
+            assert!(file.verify_checksum().unwrap());
+            assert_eq!(
+                file.hash_table().unwrap().get::<String>("string").unwrap(),
+                "test string"
+            );
+
+            // Damaged data
+            let mut damaged = data.clone();
+            let last = damaged.len() - 1;
+            damaged[last] ^= 0xff;
+            let file = File::from_bytes(Cow::Owned(damaged)).unwrap();
+            assert_matches!(
+                file.verify_checksum(),
+                Err(crate::read::Error::ChecksumMismatch { .. })
+            );
+
+            // Truncated data
+            let file = File::from_bytes(Cow::Borrowed(&data[..data.len() - 8])).unwrap();
+            assert_matches!(
+                file.verify_checksum(),
+                Err(crate::read::Error::ChecksumMismatch { .. })
+            );
+        }
+
+        // No checksum
+        let data = FileWriter::new()
+            .write_to_vec_with_table(new_simple_table())
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        assert!(!file.verify_checksum().unwrap());
+        assert!(!File::from_file(&TEST_FILE_2)
+            .unwrap()
+            .verify_checksum()
+            .unwrap());
+    }
+
+    #[test]
+    fn deduplication() {
+        fn new_table() -> HashTableBuilder<'static> {
+            let mut table_builder = HashTableBuilder::with_path_separator(Some("/"));
+            for dir in ["a", "b", "c"] {
+                for name in ["icon.svg", "style.css"] {
+                    table_builder
+                        .insert_string(&format!("/{}/icons/{}", dir, name), "same value")
+                        .unwrap();
+                }
+            }
+            table_builder.insert_bytes("bytes", b"same value").unwrap();
+            table_builder
+        }
+
+        let data = FileWriter::new()
+            .write_to_vec_with_table(new_table())
+            .unwrap();
+        let deduplicated = FileWriter::new()
+            .with_deduplication(true)
+            .write_to_vec_with_table(new_table())
+            .unwrap();
+        assert!(deduplicated.len() < data.len());
+
+        let file = File::from_bytes(Cow::Owned(deduplicated)).unwrap();
+        let table = file.hash_table().unwrap();
+        assert_eq!(
+            table.keys().unwrap(),
+            File::from_bytes(Cow::Owned(data))
+                .unwrap()
+                .hash_table()
+                .unwrap()
+                .keys()
+                .unwrap()
+        );
+        assert_eq!(
+            table.get::<String>("/c/icons/style.css").unwrap(),
+            "same value"
+        );
+        assert_eq!(table.get_byte_slice("bytes").unwrap(), b"same value");
+        assert!(file.validate().is_empty());
+    }
+
+    #[test]
+    fn packed_layout() {
+        fn new_table() -> HashTableBuilder<'static> {
+            let mut table_builder = HashTableBuilder::with_path_separator(Some("/"));
+            for i in 0..200u32 {
+                table_builder
+                    .insert(&format!("/dir{}/key{}", i % 7, i), i)
+                    .unwrap();
+                table_builder
+                    .insert_string(&format!("/strings/{}", i), &"x".repeat(i as usize % 13))
+                    .unwrap();
+            }
+            table_builder.insert_bytes("large", &[1; 5000]).unwrap();
+            table_builder
+        }
+
+        let options: [fn() -> FileWriter; 5] = [
+            FileWriter::new,
+            FileWriter::for_big_endian,
+            || FileWriter::new().with_deduplication(true),
+            || FileWriter::new().with_value_alignment(4096),
+            || FileWriter::new().with_checksum(true).with_bloom_words(4),
+        ];
+
+        for writer in options {
+            let data = writer().write_to_vec_with_table(new_table()).unwrap();
+            let packed = writer()
+                .with_packed_layout(true)
+                .write_to_vec_with_table(new_table())
+                .unwrap();
+            assert!(packed.len() < data.len());
+
+            let file = File::from_bytes(Cow::Owned(packed)).unwrap();
+            assert!(file.validate().is_empty());
+            let table = file.hash_table().unwrap();
+            for i in 0..200u32 {
+                assert_eq!(
+                    table
+                        .get::<u32>(&format!("/dir{}/key{}", i % 7, i))
+                        .unwrap(),
+                    i
+                );
+                assert_eq!(
+                    table.get_str(&format!("/strings/{}", i)).unwrap(),
+                    "x".repeat(i as usize % 13)
+                );
+            }
+            assert_eq!(table.get_byte_slice("large").unwrap(), &[1; 5000]);
+            let info = table.get_item_info("/dir3/").unwrap();
+            assert_eq!(
+                (info.value_end() - info.value_start()) as usize,
+                (0..200).filter(|i| i % 7 == 3).count() * 4
+            );
+        }
+    }
+
+    #[test]
+    fn value_alignment() {
+        let large = vec![1u8; 5000];
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert_string("small", "small value").unwrap();
+        table_builder.insert_bytes("large", &large).unwrap();
+        table_builder.insert_bytes("large2", &large[1..]).unwrap();
+
+        let data = FileWriter::new()
+            .with_value_alignment(4096)
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+        for key in ["large", "large2"] {
+            assert_eq!(table.get_item_info(key).unwrap().value_start() % 4096, 0);
+        }
+        assert_ne!(
+            table.get_item_info("small").unwrap().value_start() % 4096,
+            0
+        );
+        assert_eq!(table.get_byte_slice("large").unwrap(), &large[..]);
+        assert_eq!(table.get_byte_slice("large2").unwrap(), &large[1..]);
+        assert!(file.validate().is_empty());
+
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert_string("small", "small value").unwrap();
+        assert_matches!(
+            FileWriter::new()
+                .with_value_alignment(100)
+                .write_to_vec_with_table(table_builder),
+            Err(Error::Consistency(_))
+        );
+    }
+
+    #[test]
+    fn glib_compat_layout() {
+        let build = |glib_compat: bool| {
+            let mut table_builder = HashTableBuilder::new();
+            table_builder.insert_string("dir/b", "b").unwrap();
+            table_builder.insert_string("dir/a", "a").unwrap();
+            table_builder
+                .insert_container("empty/", Vec::new())
+                .unwrap();
+
+            let data = FileWriter::new()
+                .with_glib_compat_layout(glib_compat)
+                .write_to_vec_with_table(table_builder)
+                .unwrap();
+            File::from_bytes(Cow::Owned(data)).unwrap()
+        };
+
+        let file = build(true);
+        let table = file.hash_table().unwrap();
+        let info = table.get_item_info("empty/").unwrap();
+        assert_eq!((info.value_start(), info.value_end()), (0, 0));
+
+        // The children of containers are sorted by key
+        let keys = table.keys().unwrap();
+        let item = table.get_hash_item("dir/").unwrap();
+        let children: Vec<&str> = file
+            .dereference(item.value_ptr(), 4)
+            .unwrap()
+            .chunks(4)
+            .map(|index| keys[u32::from_le_bytes(index.try_into().unwrap()) as usize].as_str())
+            .collect();
+        assert_eq!(children, ["dir/a", "dir/b"]);
+        assert!(file.validate().is_empty());
+
+        let file = build(false);
+        let info = file.hash_table().unwrap().get_item_info("empty/").unwrap();
+        assert_ne!(info.value_start(), 0);
+
+        // Files written by GLib are reproduced
+        for reference in [&*TEST_FILE_1, &*TEST_FILE_2] {
+            let file = File::from_file(reference).unwrap();
+            let table_builder =
+                HashTableBuilder::from_hash_table(&file.hash_table().unwrap()).unwrap();
+            let data = FileWriter::endian(file.endianness())
+                .with_glib_compat_layout(true)
+                .write_to_vec_with_table(table_builder)
+                .unwrap();
+            assert_bytes_eq(
+                &std::fs::read(reference).unwrap(),
+                &data,
+                "GLib compatible layout",
+            );
+        }
+    }
+
+    #[test]
+    fn endianness() {
+        for endian in [Endian::Little, Endian::Big, Endian::native()] {
+            let mut table_builder = HashTableBuilder::new();
+            table_builder
+                .insert_string("string", "test string")
+                .unwrap();
+            let data = FileWriter::endian(endian)
+                .write_to_vec_with_table(table_builder)
+                .unwrap();
+
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            assert_eq!(file.endianness(), endian);
+            assert_eq!(file.is_byteswapped(), endian != Endian::native());
+            assert_eq!(file.version(), 0);
+            assert_eq!(
+                file.hash_table().unwrap().get::<String>("string").unwrap(),
+                "test string"
+            );
+        }
+
+        let file = File::from_file(&TEST_FILE_1).unwrap();
+        assert_eq!(file.endianness(), Endian::Little);
+    }
+
+    #[test]
+    fn insert_table_from() {
+        let gresource = File::from_file(&TEST_FILE_3).unwrap();
+        let mut source = HashTableBuilder::new();
+        source.insert("int", 42u32).unwrap();
+        source.insert_string("dir/string", "test string").unwrap();
+        let big_endian = FileWriter::for_big_endian()
+            .write_to_vec_with_table(source)
+            .unwrap();
+        let big_endian = File::from_bytes(Cow::Owned(big_endian)).unwrap();
+
+        for endian in [Endian::Little, Endian::Big] {
+            let mut table_builder = HashTableBuilder::new();
+            table_builder
+                .insert_table_from("gresource", &gresource.hash_table().unwrap())
+                .unwrap();
+            table_builder
+                .insert_table_from("big_endian", &big_endian.hash_table().unwrap())
+                .unwrap();
+            let data = FileWriter::endian(endian)
+                .write_to_vec_with_table(table_builder)
+                .unwrap();
+
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            let root = file.hash_table().unwrap();
+            let table = root.get_hash_table("gresource").unwrap();
+            assert_eq!(
+                table.keys_sorted().unwrap(),
+                gresource.hash_table().unwrap().keys_sorted().unwrap()
+            );
+            let key = "/gvdb/rs/test/json/test.json";
+            assert_eq!(
+                table.get_value(key).unwrap(),
+                gresource.hash_table().unwrap().get_value(key).unwrap()
+            );
+            // Values are copied as they are if the byte order matches
+            assert_eq!(
+                table.get_raw(key).unwrap().1
+                    == gresource.hash_table().unwrap().get_raw(key).unwrap().1,
+                endian == Endian::Little
+            );
+
+            let table = root.get_hash_table("big_endian").unwrap();
+            assert_eq!(table.get_u32("int").unwrap(), 42);
+            assert_eq!(table.get_str("dir/string").unwrap(), "test string");
+            assert_eq!(table.get_raw("dir/").unwrap().0, HashItemType::Container);
+        }
+    }
+
     #[test]
     fn container() {
         let mut file_builder = FileWriter::new();
@@ -764,6 +2566,148 @@ mod test {
         println!("{:?}", root);
     }
 
+    #[test]
+    fn inspect_and_remove() {
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert("a/b/int", 42u32).unwrap();
+        table_builder.insert_string("a/string", "str").unwrap();
+        table_builder
+            .insert_table("table", HashTableBuilder::new())
+            .unwrap();
+
+        assert!(table_builder.contains_key("a/b/int"));
+        assert!(table_builder.contains_key("a/b/"));
+        assert!(!table_builder.contains_key("a/b"));
+        assert_eq!(
+            table_builder.get("a/b/int").unwrap().value(),
+            Some(&zvariant::Value::from(42u32))
+        );
+        assert_eq!(
+            table_builder.get("a/").unwrap().container(),
+            Some(&vec!["a/b/".to_string(), "a/string".to_string()])
+        );
+        assert_eq!(
+            table_builder.get("table").unwrap().typ(),
+            HashItemType::HashTable
+        );
+        assert!(table_builder.get("missing").is_none());
+
+        let mut keys: Vec<&str> = table_builder.iter().map(|(key, _)| key).collect();
+        keys.sort();
+        assert_eq!(keys, ["a/", "a/b/", "a/b/int", "a/string", "table"]);
+
+        // Removing the last child prunes the empty parent containers
+        assert!(table_builder.remove("a/b/int"));
+        assert!(!table_builder.contains_key("a/b/"));
+        assert_eq!(
+            table_builder.get("a/").unwrap().container(),
+            Some(&vec!["a/string".to_string()])
+        );
+        assert!(!table_builder.remove("a/b/int"));
+
+        assert!(table_builder.remove("a/string"));
+        assert!(!table_builder.contains_key("a/"));
+        assert_eq!(table_builder.len(), 1);
+
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        assert_eq!(file.hash_table().unwrap().keys().unwrap(), vec!["table"]);
+    }
+
+    #[test]
+    fn verbatim() {
+        let mut table_builder = HashTableBuilder::new();
+        table_builder
+            .insert_verbatim("a/int", zvariant::Value::new(1u32))
+            .unwrap();
+        table_builder
+            .insert_container("dir/", vec!["dir/x".to_string(), "dir/y/z".to_string()])
+            .unwrap();
+        table_builder
+            .insert_verbatim("dir/x", zvariant::Value::new(2u32))
+            .unwrap();
+        table_builder
+            .insert_verbatim("dir/y/z", zvariant::Value::new(3u32))
+            .unwrap();
+        assert!(!table_builder.contains_key("a/"));
+        assert!(!table_builder.contains_key("dir/y/"));
+
+        assert_matches!(
+            table_builder.insert_container("c/", vec!["other".to_string()]),
+            Err(Error::Consistency(_))
+        );
+        assert_matches!(
+            table_builder.insert_container("c/", vec!["c/".to_string()]),
+            Err(Error::Consistency(_))
+        );
+
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+        let mut keys = table.keys().unwrap();
+        keys.sort();
+        assert_eq!(keys, vec!["a/int", "dir/", "dir/x", "dir/y/z"]);
+        assert_eq!(table.get::<u32>("dir/y/z").unwrap(), 3);
+        assert_eq!(
+            table.get_item_info("dir/x").unwrap().parent(),
+            table.get_item_info("dir/y/z").unwrap().parent()
+        );
+        assert_eq!(table.get_item_info("a/int").unwrap().parent(), None);
+    }
+
+    #[test]
+    fn tables() {
+        let mut first = HashTableBuilder::new();
+        first.insert("dir/int", 1u32).unwrap();
+        let mut second = HashTableBuilder::new();
+        second.insert_string("string", "test").unwrap();
+        let tables = BTreeMap::from([
+            ("first/".to_string(), first),
+            ("second".to_string(), second),
+            ("empty".to_string(), HashTableBuilder::new()),
+        ]);
+
+        let mut data = Vec::new();
+        let size = FileWriter::new()
+            .write_with_tables(tables, &mut data)
+            .unwrap();
+        assert_eq!(size, data.len());
+
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        assert_eq!(
+            file.table_names().unwrap(),
+            vec!["empty", "first/", "second"]
+        );
+        assert_eq!(
+            file.table("first/").unwrap().get::<u32>("dir/int").unwrap(),
+            1
+        );
+        assert_eq!(
+            file.table("second").unwrap().get_str("string").unwrap(),
+            "test"
+        );
+        assert!(file.table("empty").unwrap().keys().unwrap().is_empty());
+        assert_matches!(
+            file.table("missing"),
+            Err(crate::read::Error::KeyNotFound(_))
+        );
+
+        // The root of test file 3 contains containers and values
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        assert_matches!(
+            file.table_names(),
+            Err(crate::read::Error::WrongItemType { .. })
+        );
+        assert_matches!(
+            file.table("/gvdb/rs/test/"),
+            Err(crate::read::Error::WrongItemType { .. })
+        );
+    }
+
     #[test]
     fn missing_root() {
         let file = FileWriter::new();
@@ -776,7 +2720,7 @@ mod test {
         let item = HashValue::Container(vec!["missing".to_string()]);
         table.insert_item_value("test", item).unwrap();
 
-        assert_matches!(table.build(), Err(Error::Consistency(_)));
+        assert_matches!(table.build(ItemOrder::Sorted), Err(Error::Consistency(_)));
     }
 
     #[test]
@@ -804,7 +2748,7 @@ mod test {
     fn remove_child2() {
         let mut table_builder = HashTableBuilder::new();
         table_builder.insert_string("test/test", "test").unwrap();
-        let mut table = table_builder.build().unwrap();
+        let mut table = table_builder.build(ItemOrder::Sorted).unwrap();
         table.remove("test/test");
 
         let mut file = FileWriter::new();
@@ -812,6 +2756,197 @@ mod test {
         assert_matches!(err, Error::Consistency(_))
     }
 
+    #[test]
+    fn bloom_words() {
+        let mut table_builder = HashTableBuilder::new();
+        for i in 0..50 {
+            table_builder.insert(&format!("dir/key{}", i), i).unwrap();
+        }
+
+        let file_writer = FileWriter::new().with_bloom_words(4);
+        let bytes = file_writer.write_to_vec_with_table(table_builder).unwrap();
+        let root = File::from_bytes(Cow::Owned(bytes)).unwrap();
+        let table = root.hash_table().unwrap();
+        assert_eq!(table.header.n_bloom_words(), 4);
+        assert_eq!(table.header.bloom_shift(), 5);
+
+        for i in 0..50 {
+            assert_eq!(table.get::<i32>(&format!("dir/key{}", i)).unwrap(), i);
+        }
+        assert_matches!(
+            table.get_hash_item("dir/key50"),
+            Err(crate::read::Error::KeyNotFound(_))
+        );
+    }
+
+    #[test]
+    fn plan() {
+        let new_table_builder = || {
+            let mut table_builder = HashTableBuilder::new();
+            for key in ["b/c/d", "a", "b/e", "f/g", "zz"] {
+                table_builder.insert_string(key, key).unwrap();
+            }
+            table_builder
+                .insert_table("table", HashTableBuilder::new())
+                .unwrap();
+            table_builder
+        };
+
+        for writer in [
+            FileWriter::new(),
+            FileWriter::new().with_canonical_ordering(true),
+            FileWriter::new().with_glib_compat_layout(true),
+        ] {
+            let plan = new_table_builder().plan(&writer).unwrap();
+            assert_eq!(plan.len(), 9);
+
+            let data = writer.write_to_vec_with_table(new_table_builder()).unwrap();
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            let table = file.hash_table().unwrap();
+            assert_eq!(plan.len(), table.len());
+
+            let planned_keys = plan
+                .items()
+                .iter()
+                .map(|item| item.key().to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(planned_keys, table.keys().unwrap());
+
+            for (index, item) in plan.items().iter().enumerate() {
+                let hash_item = table.get_hash_item_for_index(index).unwrap();
+                assert_eq!(item.hash(), hash_item.hash_value());
+                assert_eq!(item.typ(), hash_item.typ().unwrap());
+                assert_eq!(Some(item.bucket()), table.bucket_for_key(item.key()));
+                assert!(plan.bucket_items(item.bucket()).contains(&index));
+                assert_eq!(
+                    item.parent(),
+                    Some(hash_item.parent() as usize).filter(|p| *p != u32::MAX as usize)
+                );
+                assert_eq!(plan.index_of(item.key()), Some(index));
+            }
+        }
+
+        let plan = HashTableBuilder::new().plan(&FileWriter::new()).unwrap();
+        assert!(plan.is_empty());
+        assert_eq!(plan.n_buckets(), 0);
+
+        // Containers with missing children are reported
+        let mut table_builder = HashTableBuilder::new();
+        table_builder
+            .insert_item_value(
+                "dir/",
+                HashValue::Container(vec!["dir/missing".to_string()]),
+            )
+            .unwrap();
+        assert_matches!(
+            table_builder.plan(&FileWriter::new()),
+            Err(Error::Consistency(_))
+        );
+    }
+
+    #[test]
+    fn bucket_count() {
+        for (table_builder, n_buckets) in [
+            (HashTableBuilder::new(), 10),
+            (HashTableBuilder::new().with_bucket_count(1), 1),
+            (HashTableBuilder::new().with_bucket_count(32), 32),
+            (HashTableBuilder::new().with_load_factor(4.0), 3),
+            (HashTableBuilder::new().with_load_factor(0.5), 20),
+        ] {
+            let mut table_builder = table_builder;
+            for i in 0..10 {
+                table_builder.insert(&format!("key{}", i), i).unwrap();
+            }
+
+            let bytes = FileWriter::new()
+                .write_to_vec_with_table(table_builder)
+                .unwrap();
+            let root = File::from_bytes(Cow::Owned(bytes)).unwrap();
+            let table = root.hash_table().unwrap();
+            assert_eq!(table.header.n_buckets(), n_buckets);
+
+            for i in 0..10 {
+                assert_eq!(table.get::<i32>(&format!("key{}", i)).unwrap(), i);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_bucket_count() {
+        // An empty table doesn't need any buckets
+        let table_builder = HashTableBuilder::new().with_bucket_count(0);
+        FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+
+        let mut table_builder = HashTableBuilder::new().with_bucket_count(0);
+        table_builder.insert_string("test", "test").unwrap();
+        let err = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap_err();
+        assert_matches!(err, Error::Consistency(_));
+
+        for load_factor in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            let mut table_builder = HashTableBuilder::new().with_load_factor(load_factor);
+            table_builder.insert_string("test", "test").unwrap();
+            let err = FileWriter::new()
+                .write_to_vec_with_table(table_builder)
+                .unwrap_err();
+            assert_matches!(err, Error::Consistency(_));
+        }
+    }
+
+    #[test]
+    fn too_many_bloom_words() {
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert_string("test", "test").unwrap();
+        let file_writer = FileWriter::new().with_bloom_words(1 << 27);
+        let err = file_writer
+            .write_to_vec_with_table(table_builder)
+            .unwrap_err();
+        assert_matches!(err, Error::Consistency(_));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn write_async() {
+        let mut table_builder = HashTableBuilder::new();
+        table_builder
+            .insert_string("string", "test string")
+            .unwrap();
+        let mut table_builder_2 = HashTableBuilder::new();
+        table_builder_2.insert("int", 42u32).unwrap();
+        table_builder
+            .insert_table("table", table_builder_2)
+            .unwrap();
+
+        let mut data = Vec::new();
+        let len = FileWriter::new()
+            .write_with_table_async(table_builder, &mut data)
+            .await
+            .unwrap();
+        assert_eq!(len, data.len());
+
+        let root = File::from_bytes(Cow::Owned(data)).unwrap();
+        assert_is_file_2(&root);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn write_async_io_error() {
+        let mut table = HashTableBuilder::new();
+        table.insert("test", "test").unwrap();
+
+        // This buffer is intentionally too small to result in I/O error
+        let mut buffer = [0u8; 10];
+        let mut cursor = Cursor::new(&mut buffer[..]);
+        let err = FileWriter::new()
+            .write_with_table_async(table, &mut cursor)
+            .await
+            .unwrap_err();
+        assert_matches!(err, Error::Io(_, _));
+    }
+
     #[test]
     fn io_error() {
         let file = FileWriter::default();
@@ -830,6 +2965,7 @@ mod test {
 
 #[cfg(all(feature = "glib", test))]
 mod test_glib {
+    use crate::write::file::ItemOrder;
     use crate::write::hash::SimpleHashTable;
     use crate::write::item::HashValue;
     use crate::write::{FileWriter, HashTableBuilder};
@@ -851,7 +2987,7 @@ mod test_glib {
     fn hash_table_builder() {
         let mut table = HashTableBuilder::new();
         table.insert_gvariant("test", "test".to_variant()).unwrap();
-        let simple_ht = table.build().unwrap();
+        let simple_ht = table.build(ItemOrder::Sorted).unwrap();
         assert_eq!(
             simple_ht
                 .get("test")
@@ -0,0 +1,203 @@
+//! Emulation of the iteration order of GLib's `GHashTable`
+//!
+//! GLib's GVDB writer and `glib-compile-resources` store their items in a `GHashTable` with string
+//! keys and write them in the order in which the hash table is iterated. This order depends on the
+//! hash values, the order of insertion and the resizes of the table. To produce the same layout,
+//! the placement of the keys is replayed here.
+
+/// The minimum size of a `GHashTable` is `1 << MIN_SHIFT`
+const MIN_SHIFT: u32 = 3;
+
+/// The prime numbers that are used for the initial bucket of a hash value, by shift
+const PRIME_MOD: [u32; 32] = [
+    1, 2, 3, 7, 13, 31, 61, 127, 251, 509, 1021, 2039, 4093, 8191, 16381, 32749, 65521, 131071,
+    262139, 524287, 1048573, 2097143, 4194301, 8388593, 16777213, 33554393, 67108859, 134217689,
+    268435399, 536870909, 1073741789, 2147483647,
+];
+
+/// `g_str_hash`, which hashes the bytes as signed chars
+fn str_hash(key: &str) -> u32 {
+    let hash = key.bytes().fold(5381u32, |hash, byte| {
+        hash.wrapping_mul(33).wrapping_add(byte as i8 as u32)
+    });
+
+    // Hash values 0 and 1 mark unused and deleted nodes
+    hash.max(2)
+}
+
+#[derive(Copy, Clone)]
+struct Node {
+    hash: u32,
+    index: usize,
+}
+
+struct GHashTable<'k> {
+    keys: Vec<&'k str>,
+    nodes: Vec<Option<Node>>,
+    modulo: u32,
+    mask: usize,
+    n_occupied: usize,
+}
+
+impl<'k> GHashTable<'k> {
+    fn new() -> Self {
+        let mut this = Self {
+            keys: Vec::new(),
+            nodes: Vec::new(),
+            modulo: 0,
+            mask: 0,
+            n_occupied: 0,
+        };
+
+        this.set_shift(MIN_SHIFT);
+        this.nodes.resize(1 << MIN_SHIFT, None);
+        this
+    }
+
+    fn set_shift(&mut self, shift: u32) {
+        self.modulo = PRIME_MOD[shift as usize];
+        self.mask = (1 << shift) - 1;
+    }
+
+    fn size(&self) -> usize {
+        self.mask + 1
+    }
+
+    fn hash_to_index(&self, hash: u32) -> usize {
+        (hash.wrapping_mul(11) % self.modulo) as usize
+    }
+
+    fn insert(&mut self, key: &'k str) {
+        let hash = str_hash(key);
+        let mut index = self.hash_to_index(hash);
+        let mut step = 0;
+
+        while let Some(node) = self.nodes[index] {
+            if node.hash == hash && self.keys[node.index] == key {
+                // Replacing a value keeps the position of the key
+                return;
+            }
+
+            step += 1;
+            index = (index + step) & self.mask;
+        }
+
+        self.nodes[index] = Some(Node {
+            hash,
+            index: self.keys.len(),
+        });
+        self.keys.push(key);
+        self.n_occupied += 1;
+        self.maybe_resize();
+    }
+
+    fn maybe_resize(&mut self) {
+        let size = self.size();
+        let n_nodes = self.keys.len();
+
+        if (size > n_nodes * 4 && size > 1 << MIN_SHIFT)
+            || size <= self.n_occupied + self.n_occupied / 16
+        {
+            self.resize();
+        }
+    }
+
+    /// Rehash all nodes in place, like `g_hash_table_resize`
+    fn resize(&mut self) {
+        let old_size = self.size();
+
+        // The size is truncated to an integer, as in C
+        let mut n = (self.keys.len() as f64 * 1.333) as u32;
+        let mut shift = 0;
+        while n != 0 {
+            n >>= 1;
+            shift += 1;
+        }
+        self.set_shift(shift.max(MIN_SHIFT));
+
+        let new_size = self.size();
+        if new_size > old_size {
+            self.nodes.resize(new_size, None);
+        }
+
+        let mut relocated = vec![false; new_size.max(old_size)];
+        for i in 0..old_size {
+            if relocated[i] {
+                continue;
+            }
+
+            let Some(mut node) = self.nodes[i].take() else {
+                continue;
+            };
+
+            // Move the node to its new position, evicting the node that is stored there
+            loop {
+                let mut index = self.hash_to_index(node.hash);
+                let mut step = 0;
+                while relocated[index] {
+                    step += 1;
+                    index = (index + step) & self.mask;
+                }
+
+                relocated[index] = true;
+                match self.nodes[index].replace(node) {
+                    Some(evicted) => node = evicted,
+                    None => break,
+                }
+            }
+        }
+
+        self.nodes.truncate(new_size);
+        self.n_occupied = self.keys.len();
+    }
+}
+
+/// The order in which a `GHashTable` iterates over `keys` after they were inserted in the given
+/// order
+pub(crate) fn iteration_order<'k>(keys: impl IntoIterator<Item = &'k str>) -> Vec<&'k str> {
+    let mut table = GHashTable::new();
+    for key in keys {
+        table.insert(key);
+    }
+
+    table
+        .nodes
+        .iter()
+        .flatten()
+        .map(|node| table.keys[node.index])
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    #[test]
+    fn order() {
+        assert_eq!(str_hash(""), 5381);
+        assert_eq!(
+            str_hash("ä"),
+            5381u32
+                .wrapping_mul(33)
+                .wrapping_sub(61)
+                .wrapping_mul(33)
+                .wrapping_sub(92)
+        );
+
+        assert!(iteration_order([]).is_empty());
+
+        // Duplicates keep their first position
+        let order = iteration_order(["a", "b", "a"]);
+        assert_eq!(order.len(), 2);
+
+        let keys: Vec<String> = (0..100).map(|i| format!("key{}", i)).collect();
+        let order = iteration_order(keys.iter().map(String::as_str));
+        assert_eq!(order.len(), 100);
+        let mut sorted = order.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 100);
+    }
+}
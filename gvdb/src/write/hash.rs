@@ -36,7 +36,7 @@ impl<'a> SimpleHashTable<'a> {
         let bucket = self.hash_bucket(hash_value);
 
         let item = Rc::new(HashItemBuilder::new(key, hash_value, item));
-        let replaced_item = std::mem::replace(&mut self.buckets[bucket], Some(item.clone()));
+        let replaced_item = self.buckets[bucket].replace(item.clone());
         if let Some(replaced_item) = replaced_item {
             if replaced_item.key() == key {
                 // Replace
@@ -294,10 +294,9 @@ mod test {
 
         let mut values: HashSet<i32> = (0..20).collect();
         for bucket in 0..table.n_buckets() {
-            let mut iter = table.iter_bucket(bucket);
-            while let Some(next) = iter.next() {
+            for next in table.iter_bucket(bucket) {
                 let num: i32 = next.value().borrow().value().unwrap().try_into().unwrap();
-                assert_eq!(values.remove(&num), true);
+                assert!(values.remove(&num));
             }
         }
     }
@@ -1,22 +1,91 @@
-use crate::read::HashItemType;
+use crate::read::{HashItemType, Pointer};
 use crate::write::file::HashTableBuilder;
 use std::cell::{Cell, Ref, RefCell};
+use std::fmt::{Debug, Formatter};
 use std::rc::Rc;
 
+/// Serializes a value to GVariant data of type `v` in the requested byte order
+pub struct SerializeFn<'a>(Box<dyn Fn(zvariant::Endian) -> zvariant::Result<Vec<u8>> + 'a>);
+
+impl<'a> SerializeFn<'a> {
+    pub fn new<T>(value: &'a T) -> Self
+    where
+        T: serde::Serialize + zvariant::Type,
+    {
+        Self(Box::new(move |endian| {
+            let context = zvariant::serialized::Context::new_gvariant(endian, 0);
+            Ok(zvariant::to_bytes(context, &zvariant::SerializeValue(value))?.to_vec())
+        }))
+    }
+
+    pub fn new_owned<T>(value: T) -> Self
+    where
+        T: serde::Serialize + zvariant::Type + 'a,
+    {
+        Self(Box::new(move |endian| {
+            let context = zvariant::serialized::Context::new_gvariant(endian, 0);
+            Ok(zvariant::to_bytes(context, &zvariant::SerializeValue(&value))?.to_vec())
+        }))
+    }
+
+    /// Serialized GVariant `data` of type `v` in byte order `source`
+    ///
+    /// The data is copied as it is when the byte order matches, and decoded and encoded again
+    /// otherwise.
+    pub fn new_raw(data: &'a [u8], source: zvariant::Endian) -> Self {
+        Self(Box::new(move |endian| {
+            if endian == source {
+                return Ok(data.to_vec());
+            }
+
+            let source = zvariant::serialized::Context::new_gvariant(source, 0);
+            let data = zvariant::serialized::Data::new(data, source);
+            let (value, _) = data.deserialize::<zvariant::Value>()?;
+            let context = zvariant::serialized::Context::new_gvariant(endian, 0);
+            Ok(zvariant::to_bytes(context, &value)?.to_vec())
+        }))
+    }
+
+    pub fn serialize(&self, endian: zvariant::Endian) -> zvariant::Result<Vec<u8>> {
+        (self.0)(endian)
+    }
+}
+
+impl Debug for SerializeFn<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SerializeFn").finish_non_exhaustive()
+    }
+}
+
 /// Holds the value of a GVDB hash table
+///
+/// Returned by [`HashTableBuilder::get`] and [`HashTableBuilder::iter`].
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum HashValue<'a> {
-    // A zvariant::Value
+    /// A zvariant::Value
     Value(zvariant::Value<'a>),
 
-    // A glib::Variant
+    /// A glib::Variant
     #[cfg(feature = "glib")]
     GVariant(glib::Variant),
 
+    /// A nested hash table
     TableBuilder(HashTableBuilder<'a>),
 
-    // A child container with no additional value
+    /// A child container with no additional value, holding the keys of its children
     Container(Vec<String>),
+
+    // Data of the specified type that has already been written to the file at the pointer location
+    #[doc(hidden)]
+    Written(HashItemType, Pointer),
+
+    /// Serialized GVariant data of type `v` that is copied to the file as is
+    Bytes(&'a [u8]),
+
+    // A value that is serialized with serde when writing the file
+    #[doc(hidden)]
+    Serialized(SerializeFn<'a>),
 }
 
 impl<'a> Default for HashValue<'a> {
@@ -25,8 +94,8 @@ impl<'a> Default for HashValue<'a> {
     }
 }
 
-#[allow(dead_code)]
 impl<'a> HashValue<'a> {
+    /// The type of the hash item this value is written as
     pub fn typ(&self) -> HashItemType {
         match self {
             HashValue::Value(_) => HashItemType::Value,
@@ -34,16 +103,20 @@ impl<'a> HashValue<'a> {
             HashValue::GVariant(_) => HashItemType::Value,
             HashValue::TableBuilder(_) => HashItemType::HashTable,
             HashValue::Container(_) => HashItemType::Container,
+            HashValue::Written(typ, _) => *typ,
+            HashValue::Bytes(_) | HashValue::Serialized(_) => HashItemType::Value,
         }
     }
 
-    pub fn value(&self) -> Option<&zvariant::Value> {
+    /// The [`zvariant::Value`], if this is a [`HashValue::Value`]
+    pub fn value(&self) -> Option<&zvariant::Value<'_>> {
         match self {
             HashValue::Value(value) => Some(value),
             _ => None,
         }
     }
 
+    /// The [`glib::Variant`], if this is a [`HashValue::GVariant`]
     #[cfg(feature = "glib")]
     pub fn gvariant(&self) -> Option<&glib::Variant> {
         match self {
@@ -52,14 +125,15 @@ impl<'a> HashValue<'a> {
         }
     }
 
-    #[allow(dead_code)]
-    pub fn table_builder(&self) -> Option<&HashTableBuilder> {
+    /// The nested hash table builder, if this is a [`HashValue::TableBuilder`]
+    pub fn table_builder(&self) -> Option<&HashTableBuilder<'_>> {
         match self {
             HashValue::TableBuilder(tb) => Some(tb),
             _ => None,
         }
     }
 
+    /// The keys of the children, if this is a [`HashValue::Container`]
     pub fn container(&self) -> Option<&Vec<String>> {
         match self {
             HashValue::Container(children) => Some(children),
@@ -138,7 +212,7 @@ impl<'a> HashItemBuilder<'a> {
         &self.value
     }
 
-    pub fn value_ref(&self) -> Ref<HashValue<'a>> {
+    pub fn value_ref(&self) -> Ref<'_, HashValue<'a>> {
         self.value.borrow()
     }
 
@@ -146,7 +220,7 @@ impl<'a> HashItemBuilder<'a> {
         &self.parent
     }
 
-    pub fn parent_ref(&self) -> Ref<Option<Rc<HashItemBuilder<'a>>>> {
+    pub fn parent_ref(&self) -> Ref<'_, Option<Rc<HashItemBuilder<'a>>>> {
         self.parent.borrow()
     }
 
@@ -0,0 +1,270 @@
+use crate::read::json::{CONTAINER_TYPE, TABLE_TYPE};
+use crate::util::{member_types, split_type};
+use crate::write::error::{Error, Result};
+use crate::write::item::HashValue;
+use crate::write::HashTableBuilder;
+use serde_json::Value as JsonValue;
+use zvariant::{Array, Dict, Maybe, ObjectPath, Signature, StructureBuilder, Value};
+
+impl HashTableBuilder<'static> {
+    /// Create a hash table builder from JSON in the format created by
+    /// [`File::to_json`](crate::read::File::to_json)
+    ///
+    /// All items are inserted exactly as they are listed in the JSON object. Container items
+    /// (type `L`) are only created if they are listed explicitly, so the returned builder has no
+    /// path separator.
+    ///
+    /// ```
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let json = serde_json::json!({
+    ///     "string": { "type": "s", "value": "test string" },
+    ///     "table": { "type": "H", "value": { "int": { "type": "u", "value": 42 } } },
+    /// });
+    ///
+    /// let table_builder = HashTableBuilder::from_json(&json).unwrap();
+    /// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    /// ```
+    pub fn from_json(json: &JsonValue) -> Result<Self> {
+        let map = json.as_object().ok_or_else(|| {
+            Error::Json(format!(
+                "Expected JSON object for hash table, found '{}'",
+                json
+            ))
+        })?;
+
+        let mut builder = Self::with_path_separator(None);
+        for (key, entry) in map {
+            let typ = entry
+                .get("type")
+                .and_then(JsonValue::as_str)
+                .ok_or_else(|| Error::Json(format!("Item '{}' has no type", key)))?;
+            let value = entry
+                .get("value")
+                .ok_or_else(|| Error::Json(format!("Item '{}' has no value", key)))?;
+
+            let item = match typ {
+                TABLE_TYPE => HashValue::TableBuilder(Self::from_json(value)?),
+                CONTAINER_TYPE => HashValue::Container(
+                    value
+                        .as_array()
+                        .and_then(|children| {
+                            children
+                                .iter()
+                                .map(|child| child.as_str().map(ToString::to_string))
+                                .collect()
+                        })
+                        .ok_or_else(|| expected(typ, value))?,
+                ),
+                _ => HashValue::Value(value_from_json(typ, value)?),
+            };
+
+            builder.insert_item_value(key, item)?;
+        }
+
+        Ok(builder)
+    }
+}
+
+fn expected(typ: &str, json: &JsonValue) -> Error {
+    Error::Json(format!(
+        "Expected JSON value for type '{}', found '{}'",
+        typ, json
+    ))
+}
+
+fn int_from_json<T: TryFrom<i64> + TryFrom<u64>>(typ: &str, json: &JsonValue) -> Result<T> {
+    let value = if let Some(value) = json.as_u64() {
+        T::try_from(value).ok()
+    } else {
+        json.as_i64().and_then(|value| T::try_from(value).ok())
+    };
+
+    value.ok_or_else(|| expected(typ, json))
+}
+
+/// Convert a JSON value to a value of the GVariant type `typ`
+fn value_from_json(typ: &str, json: &JsonValue) -> Result<Value<'static>> {
+    if split_type(typ) != Some((typ, "")) {
+        return Err(Error::Json(format!("Invalid type string '{}'", typ)));
+    }
+
+    Ok(match typ.as_bytes()[0] {
+        b'y' => Value::U8(int_from_json(typ, json)?),
+        b'b' => Value::Bool(json.as_bool().ok_or_else(|| expected(typ, json))?),
+        b'n' => Value::I16(int_from_json(typ, json)?),
+        b'q' => Value::U16(int_from_json(typ, json)?),
+        b'i' => Value::I32(int_from_json(typ, json)?),
+        b'u' => Value::U32(int_from_json(typ, json)?),
+        b'x' => Value::I64(int_from_json(typ, json)?),
+        b't' => Value::U64(int_from_json(typ, json)?),
+        b'd' => Value::F64(json.as_f64().ok_or_else(|| expected(typ, json))?),
+        b's' | b'o' | b'g' => {
+            let string = json
+                .as_str()
+                .ok_or_else(|| expected(typ, json))?
+                .to_string();
+            match typ {
+                "s" => Value::from(string),
+                "o" => Value::ObjectPath(ObjectPath::try_from(string)?),
+                _ => Value::Signature(Signature::try_from(string)?),
+            }
+        }
+        b'v' => {
+            let inner_type = json
+                .get("type")
+                .and_then(JsonValue::as_str)
+                .ok_or_else(|| expected(typ, json))?;
+            let inner = json.get("value").ok_or_else(|| expected(typ, json))?;
+            Value::Value(Box::new(value_from_json(inner_type, inner)?))
+        }
+        b'm' => {
+            let inner_type = &typ[1..];
+            if json.is_null() {
+                Value::Maybe(Maybe::nothing(Signature::try_from(inner_type)?.to_owned()))
+            } else {
+                Value::Maybe(Maybe::just(value_from_json(inner_type, json)?))
+            }
+        }
+        b'a' if typ.as_bytes().get(1) == Some(&b'{') => {
+            // The type string has already been validated
+            let types = member_types(&typ[1..]).unwrap_or_default();
+            let [key_type, value_type] = types[..] else {
+                return Err(Error::Json(format!("Invalid dictionary type '{}'", typ)));
+            };
+            let mut dict = Dict::new(
+                Signature::try_from(key_type)?.to_owned(),
+                Signature::try_from(value_type)?.to_owned(),
+            );
+
+            match json {
+                JsonValue::Object(map) => {
+                    for (key, value) in map {
+                        dict.append(
+                            value_from_json(key_type, &JsonValue::String(key.clone()))?,
+                            value_from_json(value_type, value)?,
+                        )?;
+                    }
+                }
+                JsonValue::Array(entries) => {
+                    for entry in entries {
+                        match entry.as_array().map(Vec::as_slice) {
+                            Some([key, value]) => dict.append(
+                                value_from_json(key_type, key)?,
+                                value_from_json(value_type, value)?,
+                            )?,
+                            _ => return Err(expected(typ, json)),
+                        }
+                    }
+                }
+                _ => return Err(expected(typ, json)),
+            }
+
+            Value::Dict(dict)
+        }
+        b'a' => {
+            let element_type = &typ[1..];
+            let mut array = Array::new(Signature::try_from(element_type)?.to_owned());
+            for element in json.as_array().ok_or_else(|| expected(typ, json))? {
+                array.append(value_from_json(element_type, element)?)?;
+            }
+
+            Value::Array(array)
+        }
+        b'(' => {
+            let types = member_types(typ).unwrap_or_default();
+            let fields = json.as_array().ok_or_else(|| expected(typ, json))?;
+            if types.is_empty() || types.len() != fields.len() {
+                return Err(expected(typ, json));
+            }
+
+            let mut builder = StructureBuilder::new();
+            for (typ, field) in types.into_iter().zip(fields) {
+                builder = builder.append_field(value_from_json(typ, field)?);
+            }
+
+            Value::Structure(builder.build())
+        }
+        _ => return Err(Error::Json(format!("Unsupported type string '{}'", typ))),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::value_from_json;
+    use crate::read::File;
+    use crate::test::{TEST_FILE_2, TEST_FILE_3};
+    use crate::write::{Error, FileWriter, HashTableBuilder};
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use serde_json::json;
+    use std::borrow::Cow;
+
+    fn round_trip(json: &serde_json::Value, byteswap: bool) -> serde_json::Value {
+        let builder = HashTableBuilder::from_json(json).unwrap();
+        let writer = if byteswap {
+            FileWriter::for_big_endian()
+        } else {
+            FileWriter::new()
+        };
+
+        let data = writer.write_to_vec_with_table(builder).unwrap();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        file.to_json().unwrap()
+    }
+
+    #[test]
+    fn values() {
+        let json = json!({
+            "string": { "type": "s", "value": "test string" },
+            "numbers": { "type": "(ynqiuxtd)", "value": [1, -2, 3, -4, 5, -6, 7, 0.5] },
+            "path": { "type": "o", "value": "/org/gtk/test" },
+            "maybe": { "type": "ams", "value": ["a", null] },
+            "dict": { "type": "a{sv}", "value": { "key": { "type": "b", "value": true } } },
+            "int_dict": { "type": "a{ias}", "value": [[1, ["a", "b"]]] },
+            "table": { "type": "H", "value": { "int": { "type": "u", "value": 42 } } },
+        });
+
+        assert_eq!(round_trip(&json, false), json);
+    }
+
+    #[test]
+    fn test_files() {
+        for path in [&*TEST_FILE_2, &*TEST_FILE_3] {
+            let json = File::from_file(path).unwrap().to_json().unwrap();
+            assert_eq!(round_trip(&json, false), json);
+            assert_eq!(round_trip(&json, true), json);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert_matches!(value_from_json("u", &json!(-1)), Err(Error::Json(_)));
+        assert_matches!(value_from_json("y", &json!(256)), Err(Error::Json(_)));
+        assert_matches!(value_from_json("s", &json!(1)), Err(Error::Json(_)));
+        assert_matches!(value_from_json("(u", &json!([1])), Err(Error::Json(_)));
+        assert_matches!(value_from_json("uu", &json!(1)), Err(Error::Json(_)));
+        assert_matches!(value_from_json("(uu)", &json!([1])), Err(Error::Json(_)));
+        assert_matches!(value_from_json("h", &json!(1)), Err(Error::Json(_)));
+        assert_matches!(
+            value_from_json("o", &json!("test")),
+            Err(Error::ZVariant(_))
+        );
+
+        assert_matches!(HashTableBuilder::from_json(&json!([])), Err(Error::Json(_)));
+        assert_matches!(
+            HashTableBuilder::from_json(&json!({ "key": { "value": 1 } })),
+            Err(Error::Json(_))
+        );
+        assert_matches!(
+            HashTableBuilder::from_json(&json!({ "key/": { "type": "L", "value": [1] } })),
+            Err(Error::Json(_))
+        );
+        assert_matches!(
+            HashTableBuilder::from_json(&json!({ "k": { "type": "é", "value": 1 } })),
+            Err(Error::Json(_))
+        );
+        assert_matches!(value_from_json("(é)", &json!([1])), Err(Error::Json(_)));
+    }
+}
@@ -0,0 +1,214 @@
+use crate::read::File;
+use crate::write::error::{Error, Result};
+use crate::write::{HashTableBuilder, HashValue};
+
+/// How [`merge`] resolves items that exist in more than one file
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MergePolicy {
+    /// Fail with [`Error::MergeConflict`]
+    #[default]
+    Error,
+
+    /// Keep the item of the first file that contains the key
+    FirstWins,
+
+    /// Keep the item of the last file that contains the key
+    LastWins,
+}
+
+/// Combine the root hash tables of `files` into a single hash table
+///
+/// The result contains the union of all keys. Nested hash tables that exist in multiple files
+/// are merged recursively, and containers list the children of all files. Other items that exist
+/// in more than one file are resolved according to `policy`. An item that is a container in one
+/// file and not a container in another file is always a conflict, as its children would be lost.
+///
+/// The serialized data of the values is copied from the files as it is, unless the result is
+/// written with a different byte order, see [`HashTableBuilder::insert_table_from`].
+///
+/// ```
+/// # use std::borrow::Cow;
+/// use gvdb::read::File;
+/// use gvdb::write::{merge, FileWriter, HashTableBuilder, MergePolicy};
+///
+/// let mut files = Vec::new();
+/// for value in [1u32, 2] {
+///     let mut table_builder = HashTableBuilder::new();
+///     table_builder.insert("value", value).unwrap();
+///     table_builder.insert(&format!("module{}/enabled", value), true).unwrap();
+///     let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+///     files.push(File::from_bytes(Cow::Owned(data)).unwrap());
+/// }
+///
+/// assert!(merge(&files, MergePolicy::Error).is_err());
+///
+/// let table_builder = merge(&files, MergePolicy::LastWins).unwrap();
+/// let data = FileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+/// let file = File::from_bytes(Cow::Owned(data)).unwrap();
+/// let table = file.hash_table().unwrap();
+/// assert_eq!(table.get_u32("value").unwrap(), 2);
+/// assert!(table.get_bool("module1/enabled").unwrap());
+/// ```
+pub fn merge<'a>(files: &'a [File<'_>], policy: MergePolicy) -> Result<HashTableBuilder<'a>> {
+    let mut merged = HashTableBuilder::new();
+    for file in files {
+        let table_builder = HashTableBuilder::from_hash_table_copy(&file.hash_table()?, Some("/"))?;
+        merge_into(&mut merged, table_builder, policy, &mut Vec::new())?;
+    }
+
+    Ok(merged)
+}
+
+/// Merge the items of `source` into `target`
+///
+/// `path` holds the keys of the nested hash tables that contain `target`.
+fn merge_into<'a>(
+    target: &mut HashTableBuilder<'a>,
+    source: HashTableBuilder<'a>,
+    policy: MergePolicy,
+    path: &mut Vec<String>,
+) -> Result<()> {
+    for (key, value) in source.into_items() {
+        let Some(existing) = target.item_mut(&key) else {
+            target.insert_verbatim(&key, value)?;
+            continue;
+        };
+
+        match (existing, value) {
+            (HashValue::Container(children), HashValue::Container(new_children)) => {
+                for child in new_children {
+                    if !children.contains(&child) {
+                        children.push(child);
+                    }
+                }
+            }
+            (HashValue::TableBuilder(table), HashValue::TableBuilder(new_table)) => {
+                path.push(key);
+                merge_into(table, new_table, policy, path)?;
+                path.pop();
+            }
+            (existing, value) => {
+                let container = matches!(existing, HashValue::Container(_))
+                    || matches!(value, HashValue::Container(_));
+                match policy {
+                    MergePolicy::FirstWins if !container => {}
+                    MergePolicy::LastWins if !container => *existing = value,
+                    _ => {
+                        let mut path = path.clone();
+                        path.push(key);
+                        return Err(Error::MergeConflict { path });
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::HashItemType;
+    use crate::test::*;
+    use crate::write::FileWriter;
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use std::borrow::Cow;
+
+    fn new_file(table_builder: HashTableBuilder) -> File<'static> {
+        let data = FileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        File::from_bytes(Cow::Owned(data)).unwrap()
+    }
+
+    #[test]
+    fn merge_files() {
+        let mut nested = HashTableBuilder::new();
+        nested.insert("other", 1u32).unwrap();
+        let mut table_builder = HashTableBuilder::new();
+        table_builder.insert_string("string", "first").unwrap();
+        table_builder.insert_table("table", nested).unwrap();
+        table_builder.insert("dir/a", 1u32).unwrap();
+
+        let files = [
+            File::from_file(&TEST_FILE_2).unwrap(),
+            new_file(table_builder),
+            File::from_file(&TEST_FILE_3).unwrap(),
+        ];
+
+        for (policy, string) in [
+            (MergePolicy::FirstWins, "test string"),
+            (MergePolicy::LastWins, "first"),
+        ] {
+            let file = new_file(merge(&files, policy).unwrap());
+            let table = file.hash_table().unwrap();
+            assert_eq!(table.get_str("string").unwrap(), string);
+            assert_eq!(table.get_u32("dir/a").unwrap(), 1);
+            assert!(table.get_raw("/gvdb/rs/test/test.css").is_ok());
+
+            let nested = table.get_hash_table("table").unwrap();
+            assert_eq!(nested.keys_sorted().unwrap(), ["int", "other"]);
+            assert_eq!(nested.get_u32("int").unwrap(), 42);
+        }
+
+        assert_matches!(
+            merge(&files, MergePolicy::Error),
+            Err(Error::MergeConflict { path }) if path == ["string"]
+        );
+        assert!(merge(&[], MergePolicy::Error).unwrap().is_empty());
+    }
+
+    #[test]
+    fn merge_conflicts() {
+        let mut first = HashTableBuilder::new();
+        first.insert("dir/a", 1u32).unwrap();
+        let mut nested = HashTableBuilder::new();
+        nested.insert("int", 1u32).unwrap();
+        first.insert_table("table", nested).unwrap();
+
+        let mut second = HashTableBuilder::new();
+        second.insert("dir/b", 2u32).unwrap();
+        let mut nested = HashTableBuilder::new();
+        nested.insert("int", 2u32).unwrap();
+        second.insert_table("table", nested).unwrap();
+
+        let files = [new_file(first), new_file(second)];
+        let err = merge(&files, MergePolicy::Error).unwrap_err();
+        assert_matches!(&err, Error::MergeConflict { path } if path == &["table", "int"]);
+        assert!(format!("{}", err).contains("table"));
+
+        // Containers list the children of all files
+        let file = new_file(merge(&files, MergePolicy::LastWins).unwrap());
+        let table = file.hash_table().unwrap();
+        assert_eq!(table.get_raw("dir/").unwrap().1.len(), 8);
+        assert_eq!(table.get_u32("dir/a").unwrap(), 1);
+        assert_eq!(table.get_u32("dir/b").unwrap(), 2);
+        assert_eq!(
+            table
+                .get_hash_table("table")
+                .unwrap()
+                .get_u32("int")
+                .unwrap(),
+            2
+        );
+
+        // A container can't be replaced by a value
+        let mut value = HashTableBuilder::new();
+        value
+            .insert_verbatim("dir/", zvariant::Value::new(3u32))
+            .unwrap();
+        let files = [new_file(value), files.into_iter().next().unwrap()];
+        assert_eq!(
+            files[0].hash_table().unwrap().get_raw("dir/").unwrap().0,
+            HashItemType::Value
+        );
+        assert_matches!(
+            merge(&files, MergePolicy::LastWins),
+            Err(Error::MergeConflict { path }) if path == ["dir/"]
+        );
+    }
+}
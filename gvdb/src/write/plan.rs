@@ -0,0 +1,119 @@
+use crate::read::HashItemType;
+
+/// The layout of a hash table, before it is written
+///
+/// Created by [`HashTableBuilder::plan`](crate::write::HashTableBuilder::plan). The plan lists
+/// the items in the order in which they are stored in the hash table, so the position of an item
+/// in [`HashTablePlan::items`] is its index in the written file. Nested hash tables have their
+/// own plan.
+///
+/// ```
+/// use gvdb::write::{FileWriter, HashTableBuilder};
+///
+/// let mut table_builder = HashTableBuilder::new();
+/// table_builder.insert_string("dir/file", "content").unwrap();
+/// let plan = table_builder.plan(&FileWriter::new()).unwrap();
+///
+/// assert_eq!(plan.len(), 2);
+/// let index = plan.index_of("dir/file").unwrap();
+/// let parent = plan.items()[index].parent().unwrap();
+/// assert_eq!(plan.items()[parent].key(), "dir/");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HashTablePlan {
+    n_buckets: usize,
+    items: Vec<PlannedItem>,
+}
+
+impl HashTablePlan {
+    pub(crate) fn new(n_buckets: usize, items: Vec<PlannedItem>) -> Self {
+        Self { n_buckets, items }
+    }
+
+    /// The number of hash buckets
+    pub fn n_buckets(&self) -> usize {
+        self.n_buckets
+    }
+
+    /// The items in the order of their index in the written hash table
+    pub fn items(&self) -> &[PlannedItem] {
+        &self.items
+    }
+
+    /// The number of items
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether the hash table has no items
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The index of the item with the full key `key`
+    pub fn index_of(&self, key: &str) -> Option<usize> {
+        self.items.iter().position(|item| item.key == key)
+    }
+
+    /// The indices of the items in `bucket`
+    ///
+    /// The items of a bucket are stored next to each other, so this is a range.
+    pub fn bucket_items(&self, bucket: usize) -> std::ops::Range<usize> {
+        let start = self.items.partition_point(|item| item.bucket < bucket);
+        let end = self.items.partition_point(|item| item.bucket <= bucket);
+        start..end
+    }
+}
+
+/// An item of a [`HashTablePlan`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlannedItem {
+    key: String,
+    hash: u32,
+    bucket: usize,
+    typ: HashItemType,
+    parent: Option<usize>,
+}
+
+impl PlannedItem {
+    pub(crate) fn new(key: String, hash: u32, bucket: usize, typ: HashItemType) -> Self {
+        Self {
+            key,
+            hash,
+            bucket,
+            typ,
+            parent: None,
+        }
+    }
+
+    pub(crate) fn set_parent(&mut self, parent: usize) {
+        self.parent = Some(parent);
+    }
+
+    /// The full key of the item
+    ///
+    /// Items with a parent only store the part of the key after the key of the parent.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The [`djb_hash`](crate::djb_hash) of the full key
+    pub fn hash(&self) -> u32 {
+        self.hash
+    }
+
+    /// The hash bucket of the item
+    pub fn bucket(&self) -> usize {
+        self.bucket
+    }
+
+    /// The type of the item
+    pub fn typ(&self) -> HashItemType {
+        self.typ
+    }
+
+    /// The index of the container item that lists this item as child, if there is one
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+}
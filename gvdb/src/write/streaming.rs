@@ -0,0 +1,297 @@
+use crate::read::{HashItemType, Header, Pointer};
+use crate::write::error::Result;
+use crate::write::file::FileWriter;
+use crate::write::item::HashValue;
+use crate::write::HashTableBuilder;
+use std::io::{Seek, SeekFrom, Write};
+use std::mem::size_of;
+use zerocopy::IntoBytes;
+
+/// Create GVDB files without keeping the serialized data in memory
+///
+/// In contrast to [`FileWriter`], every value is written to the output as soon as it is inserted.
+/// Only the hash table metadata is kept in memory until [`StreamingFileWriter::finish`] writes the
+/// root hash table and fixes up the file header.
+///
+/// The file is incomplete until [`StreamingFileWriter::finish`] has been called.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use gvdb::write::{HashTableBuilder, StreamingFileWriter};
+///
+/// let mut writer = StreamingFileWriter::new(Cursor::new(Vec::new())).unwrap();
+/// writer.insert_string("string", "test string").unwrap();
+/// writer.insert("int", 42u32).unwrap();
+///
+/// let mut table_builder = HashTableBuilder::new();
+/// table_builder.insert_bytes("bytes", &[1, 2, 3]).unwrap();
+/// writer.insert_table("table", table_builder).unwrap();
+///
+/// let file_data = writer.finish().unwrap().into_inner();
+/// ```
+pub struct StreamingFileWriter<W: Write + Seek> {
+    writer: W,
+
+    // The stream position of the file header
+    start: u64,
+
+    // The current offset relative to the start of the file
+    offset: usize,
+    byteswap: bool,
+
+    // The root hash table, containing only already written values
+    table: HashTableBuilder<'static>,
+}
+
+impl<W: Write + Seek> StreamingFileWriter<W> {
+    /// Create a new instance configured for writing little endian data (preferred endianness)
+    ///
+    /// The file will start at the current stream position of `writer`.
+    pub fn new(writer: W) -> Result<Self> {
+        #[cfg(target_endian = "little")]
+        let byteswap = false;
+        #[cfg(target_endian = "big")]
+        let byteswap = true;
+        Self::with_byteswap(writer, byteswap)
+    }
+
+    /// Create a new instance configured for writing big endian data
+    /// (not recommended for most use cases)
+    ///
+    /// The file will start at the current stream position of `writer`.
+    pub fn for_big_endian(writer: W) -> Result<Self> {
+        #[cfg(target_endian = "little")]
+        let byteswap = true;
+        #[cfg(target_endian = "big")]
+        let byteswap = false;
+        Self::with_byteswap(writer, byteswap)
+    }
+
+    fn with_byteswap(mut writer: W, byteswap: bool) -> Result<Self> {
+        let start = writer.stream_position()?;
+
+        // The header will be written when the root pointer is known
+        writer.write_all(&[0; size_of::<Header>()])?;
+
+        Ok(Self {
+            writer,
+            start,
+            offset: size_of::<Header>(),
+            byteswap,
+            table: HashTableBuilder::new(),
+        })
+    }
+
+    /// Write the chunks allocated by `f` to the output and return the pointer returned by `f`
+    fn write_with<F>(&mut self, f: F) -> Result<Pointer>
+    where
+        F: FnOnce(&mut FileWriter) -> Result<Pointer>,
+    {
+        let mut chunk_writer = FileWriter::at_offset(self.offset, self.byteswap);
+        let pointer = f(&mut chunk_writer)?;
+        self.offset = chunk_writer.write_chunks(self.offset, &mut self.writer)?;
+        Ok(pointer)
+    }
+
+    /// Write Value `value` and insert it for `key`
+    pub fn insert_value(
+        &mut self,
+        key: &(impl ToString + ?Sized),
+        value: &zvariant::Value,
+    ) -> Result<()> {
+        let pointer = self.write_with(|writer| Ok(writer.add_value(value)?.1.pointer()))?;
+        self.table
+            .insert_item_value(key, HashValue::Written(HashItemType::Value, pointer))
+    }
+
+    /// Write `value` and insert it for `key` where `value` needs to be `Into<zvariant::Value>`
+    pub fn insert<'a, T>(&mut self, key: &(impl ToString + ?Sized), value: T) -> Result<()>
+    where
+        T: Into<zvariant::Value<'a>>,
+    {
+        self.insert_value(key, &value.into())
+    }
+
+    /// Write GVariant `variant` and insert it for `key`
+    #[cfg(feature = "glib")]
+    pub fn insert_gvariant(
+        &mut self,
+        key: &(impl ToString + ?Sized),
+        variant: &glib::Variant,
+    ) -> Result<()> {
+        let pointer = self.write_with(|writer| Ok(writer.add_gvariant(variant)?.1.pointer()))?;
+        self.table
+            .insert_item_value(key, HashValue::Written(HashItemType::Value, pointer))
+    }
+
+    /// Convenience method to write a string type GVariant for `string` and insert it at `key`
+    pub fn insert_string(
+        &mut self,
+        key: &(impl ToString + ?Sized),
+        string: &(impl ToString + ?Sized),
+    ) -> Result<()> {
+        self.insert_value(key, &zvariant::Value::new(string.to_string()))
+    }
+
+    /// Convenience method to write a byte type GVariant for `bytes` and insert it at `key`
+    pub fn insert_bytes(&mut self, key: &(impl ToString + ?Sized), bytes: &[u8]) -> Result<()> {
+        self.insert_value(key, &zvariant::Value::new(bytes))
+    }
+
+    /// Write an entire hash table and insert it at `key`
+    pub fn insert_table(
+        &mut self,
+        key: &(impl ToString + ?Sized),
+        table_builder: HashTableBuilder,
+    ) -> Result<()> {
+        let pointer =
+            self.write_with(|writer| Ok(writer.add_table_builder(table_builder)?.1.pointer()))?;
+        self.table
+            .insert_item_value(key, HashValue::Written(HashItemType::HashTable, pointer))
+    }
+
+    /// Write the root hash table and the file header
+    ///
+    /// Returns the underlying writer, positioned at the end of the file.
+    pub fn finish(mut self) -> Result<W> {
+        let table = std::mem::take(&mut self.table);
+        let root_ptr =
+            self.write_with(|writer| Ok(writer.add_table_builder(table)?.1.pointer()))?;
+
+        let header = Header::new(self.byteswap, 0, root_ptr);
+        self.writer.seek(SeekFrom::Start(self.start))?;
+        self.writer.write_all(header.as_bytes())?;
+        self.writer
+            .seek(SeekFrom::Start(self.start + self.offset as u64))?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write + Seek> std::fmt::Debug for StreamingFileWriter<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamingFileWriter")
+            .field("start", &self.start)
+            .field("offset", &self.offset)
+            .field("byteswap", &self.byteswap)
+            .field("table", &self.table)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::File;
+    use crate::test::{assert_is_file_1, assert_is_file_2};
+    use crate::write::Error;
+    use matches::assert_matches;
+    use std::borrow::Cow;
+    use std::io::Cursor;
+
+    #[test]
+    fn file_1() {
+        let mut writer = StreamingFileWriter::new(Cursor::new(Vec::new())).unwrap();
+        let variant = zvariant::Value::new((1234u32, 98765u32, "TEST_STRING_VALUE"));
+        writer.insert_value("root_key", &variant).unwrap();
+        println!("{:?}", writer);
+
+        let data = writer.finish().unwrap().into_inner();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        assert_is_file_1(&file);
+    }
+
+    #[test]
+    fn file_2() {
+        for big_endian in [true, false] {
+            let cursor = Cursor::new(Vec::new());
+            let mut writer = if big_endian {
+                StreamingFileWriter::for_big_endian(cursor).unwrap()
+            } else {
+                StreamingFileWriter::new(cursor).unwrap()
+            };
+
+            writer.insert_string("string", "test string").unwrap();
+            let mut table_builder = HashTableBuilder::new();
+            table_builder.insert("int", 42u32).unwrap();
+            writer.insert_table("table", table_builder).unwrap();
+
+            let data = writer.finish().unwrap().into_inner();
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            assert_eq!(
+                file.byteswapped,
+                cfg!(target_endian = "little") == big_endian
+            );
+            assert_is_file_2(&file);
+        }
+    }
+
+    #[test]
+    fn nested_keys() {
+        let mut writer = StreamingFileWriter::new(Cursor::new(Vec::new())).unwrap();
+        writer.insert_bytes("/dir/bytes", &[1, 2, 3]).unwrap();
+        writer.insert("/dir/sub/int", 5u8).unwrap();
+
+        let data = writer.finish().unwrap().into_inner();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+        let mut keys = table.keys().unwrap();
+        keys.sort();
+        assert_eq!(
+            keys,
+            ["/", "/dir/", "/dir/bytes", "/dir/sub/", "/dir/sub/int"]
+        );
+        assert_eq!(table.get::<Vec<u8>>("/dir/bytes").unwrap(), vec![1, 2, 3]);
+        assert_eq!(table.get::<u8>("/dir/sub/int").unwrap(), 5);
+    }
+
+    #[test]
+    fn stream_position() {
+        // The file doesn't have to start at the beginning of the stream
+        let mut cursor = Cursor::new(Vec::new());
+        cursor.write_all(b"prefix").unwrap();
+
+        let mut writer = StreamingFileWriter::new(cursor).unwrap();
+        writer.insert_string("string", "test string").unwrap();
+        let data = writer.finish().unwrap().into_inner();
+
+        assert_eq!(&data[0..6], b"prefix");
+        let file = File::from_bytes(Cow::Owned(data[6..].to_vec())).unwrap();
+        let string: String = file.hash_table().unwrap().get("string").unwrap();
+        assert_eq!(string, "test string");
+    }
+
+    #[test]
+    fn io_error() {
+        // This buffer is intentionally too small to result in I/O error
+        let buffer = [0u8; 30];
+        let mut writer = StreamingFileWriter::new(Cursor::new(buffer)).unwrap();
+        let err = writer.insert_string("test", "test").unwrap_err();
+        assert_matches!(err, Error::Io(_, _));
+    }
+}
+
+#[cfg(all(feature = "glib", test))]
+mod test_glib {
+    use crate::read::File;
+    use crate::write::StreamingFileWriter;
+    use glib::prelude::*;
+    use std::borrow::Cow;
+    use std::io::Cursor;
+
+    #[test]
+    fn insert_gvariant() {
+        let mut writer = StreamingFileWriter::new(Cursor::new(Vec::new())).unwrap();
+        writer
+            .insert_gvariant("test", &"test".to_variant())
+            .unwrap();
+
+        let data = writer.finish().unwrap().into_inner();
+        let file = File::from_bytes(Cow::Owned(data)).unwrap();
+        let value: String = file.hash_table().unwrap().get("test").unwrap();
+        assert_eq!(value, "test");
+    }
+}
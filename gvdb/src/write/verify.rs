@@ -0,0 +1,415 @@
+use crate::read::{File, HashItemType, HashTable};
+use crate::write::error::{Error, Result};
+use crate::write::{FileWriter, HashTableBuilder, HashValue};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::mem::size_of;
+
+/// A difference between a [`HashTableBuilder`] and the file that was written from it, found by
+/// [`FileWriter::write_and_verify`]
+///
+/// The path of every mismatch holds the keys of the enclosing nested hash tables, followed by the
+/// key of the item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VerifyMismatch {
+    /// The item is missing from the file
+    Missing {
+        /// The path of the item
+        path: Vec<String>,
+    },
+
+    /// The file contains an item that is not in the builder
+    Unexpected {
+        /// The path of the item
+        path: Vec<String>,
+    },
+
+    /// The item is listed in the file, but looking up its key fails
+    Lookup {
+        /// The path of the item
+        path: Vec<String>,
+        /// The error of the lookup
+        error: String,
+    },
+
+    /// The item has a different type in the file
+    Type {
+        /// The path of the item
+        path: Vec<String>,
+        /// The type in the builder
+        expected: HashItemType,
+        /// The type in the file
+        found: HashItemType,
+    },
+
+    /// The serialized data of the value differs
+    Value {
+        /// The path of the item
+        path: Vec<String>,
+    },
+
+    /// The container has different children in the file
+    Children {
+        /// The path of the container
+        path: Vec<String>,
+        /// The full keys of the children in the builder, sorted
+        expected: Vec<String>,
+        /// The full keys of the children in the file, sorted
+        found: Vec<String>,
+    },
+}
+
+impl VerifyMismatch {
+    /// The keys of the enclosing nested hash tables, followed by the key of the item
+    pub fn path(&self) -> &[String] {
+        match self {
+            VerifyMismatch::Missing { path }
+            | VerifyMismatch::Unexpected { path }
+            | VerifyMismatch::Lookup { path, .. }
+            | VerifyMismatch::Type { path, .. }
+            | VerifyMismatch::Value { path }
+            | VerifyMismatch::Children { path, .. } => path,
+        }
+    }
+}
+
+impl Display for VerifyMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifyMismatch::Missing { path } => write!(f, "Item {:?} is missing", path),
+            VerifyMismatch::Unexpected { path } => write!(f, "Item {:?} is unexpected", path),
+            VerifyMismatch::Lookup { path, error } => {
+                write!(f, "Lookup of item {:?} failed: {}", path, error)
+            }
+            VerifyMismatch::Type {
+                path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Item {:?} has type {}, expected {}",
+                path, found, expected
+            ),
+            VerifyMismatch::Value { path } => write!(f, "Value of item {:?} differs", path),
+            VerifyMismatch::Children {
+                path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Container {:?} has children {:?}, expected {:?}",
+                path, found, expected
+            ),
+        }
+    }
+}
+
+/// The expected content of an item, taken from the builder before it is written
+enum Expected {
+    /// Serialized value data in the byte order of the file
+    Value(Vec<u8>),
+
+    /// The items of a nested hash table
+    Table(BTreeMap<String, Expected>),
+
+    /// The sorted full keys of the children of a container
+    Container(BTreeSet<String>),
+
+    /// An item that was written before and can only be checked for its type
+    Unchecked(HashItemType),
+}
+
+impl Expected {
+    fn typ(&self) -> HashItemType {
+        match self {
+            Expected::Value(_) => HashItemType::Value,
+            Expected::Table(_) => HashItemType::HashTable,
+            Expected::Container(_) => HashItemType::Container,
+            Expected::Unchecked(typ) => *typ,
+        }
+    }
+}
+
+impl FileWriter {
+    /// Serialize the items of `table_builder` the same way as the writer does
+    fn expected_items(
+        &self,
+        table_builder: &HashTableBuilder,
+    ) -> Result<BTreeMap<String, Expected>> {
+        let endian = self.zvariant_endianess();
+        let mut items = BTreeMap::new();
+        for (key, value) in table_builder.iter() {
+            let expected = match value {
+                HashValue::Value(value) => {
+                    let context = zvariant::serialized::Context::new_gvariant(endian, 0);
+                    Expected::Value(zvariant::to_bytes(context, value)?.to_vec())
+                }
+                #[cfg(feature = "glib")]
+                HashValue::GVariant(variant) => {
+                    let variant = if endian == zvariant::NATIVE_ENDIAN {
+                        glib::Variant::from_variant(variant)
+                    } else {
+                        glib::Variant::from_variant(&variant.byteswap())
+                    };
+                    Expected::Value(variant.normal_form().data().to_vec())
+                }
+                HashValue::TableBuilder(table_builder) => {
+                    Expected::Table(self.expected_items(table_builder)?)
+                }
+                HashValue::Container(children) => {
+                    Expected::Container(children.iter().cloned().collect())
+                }
+                HashValue::Written(typ, _) => Expected::Unchecked(*typ),
+                HashValue::Bytes(data) => Expected::Value(data.to_vec()),
+                HashValue::Serialized(serialize) => Expected::Value(serialize.serialize(endian)?),
+            };
+
+            items.insert(key.to_string(), expected);
+        }
+
+        Ok(items)
+    }
+
+    /// Write the GVDB file to a [`Vec<u8>`] and check it by reading it back
+    ///
+    /// The written data is opened with [`File`] and every item of `table_builder` is looked up
+    /// and compared with the builder: the item types, the serialized data of all values in the
+    /// byte order of the writer, the children of containers and the items of nested hash tables.
+    /// This catches writer bugs before a corrupt file is stored or shipped, at the cost of
+    /// serializing every value twice.
+    ///
+    /// Returns [`Error::Verification`] with all differences if the file doesn't match.
+    ///
+    /// ```
+    /// use gvdb::write::{FileWriter, HashTableBuilder};
+    ///
+    /// let mut table_builder = HashTableBuilder::new();
+    /// table_builder.insert_string("string", "test string").unwrap();
+    /// table_builder.insert("dir/int", 42u32).unwrap();
+    ///
+    /// let data = FileWriter::for_big_endian()
+    ///     .write_and_verify(table_builder)
+    ///     .unwrap();
+    /// ```
+    pub fn write_and_verify(self, table_builder: HashTableBuilder) -> Result<Vec<u8>> {
+        let expected = self.expected_items(&table_builder)?;
+        let data = self.write_to_vec_with_table(table_builder)?;
+
+        let file = File::from_bytes(Cow::Borrowed(&data))?;
+        let mut mismatches = Vec::new();
+        verify_table(
+            &expected,
+            &file.hash_table()?,
+            &mut Vec::new(),
+            &mut mismatches,
+        )?;
+
+        if mismatches.is_empty() {
+            Ok(data)
+        } else {
+            Err(Error::Verification(mismatches))
+        }
+    }
+}
+
+/// The path of the item `key` in the nested hash table at `path`
+fn item_path(path: &[String], key: &str) -> Vec<String> {
+    let mut path = path.to_vec();
+    path.push(key.to_string());
+    path
+}
+
+/// Compare the items of `table` with `expected` and collect all differences
+///
+/// `path` holds the keys of the nested hash tables that contain `table`.
+fn verify_table(
+    expected: &BTreeMap<String, Expected>,
+    table: &HashTable,
+    path: &mut Vec<String>,
+    mismatches: &mut Vec<VerifyMismatch>,
+) -> crate::read::Result<()> {
+    let keys = table.keys()?;
+
+    for key in &keys {
+        if !expected.contains_key(key) {
+            mismatches.push(VerifyMismatch::Unexpected {
+                path: item_path(path, key),
+            });
+        }
+    }
+
+    for (key, expected) in expected {
+        if !keys.contains(key) {
+            mismatches.push(VerifyMismatch::Missing {
+                path: item_path(path, key),
+            });
+            continue;
+        }
+
+        let (typ, data) = match table.get_raw(key) {
+            Ok(raw) => raw,
+            Err(err) => {
+                mismatches.push(VerifyMismatch::Lookup {
+                    path: item_path(path, key),
+                    error: err.to_string(),
+                });
+                continue;
+            }
+        };
+
+        if typ != expected.typ() {
+            mismatches.push(VerifyMismatch::Type {
+                path: item_path(path, key),
+                expected: expected.typ(),
+                found: typ,
+            });
+            continue;
+        }
+
+        match expected {
+            Expected::Value(value) => {
+                if data != value.as_slice() {
+                    mismatches.push(VerifyMismatch::Value {
+                        path: item_path(path, key),
+                    });
+                }
+            }
+            Expected::Table(items) => {
+                path.push(key.clone());
+                verify_table(items, &table.get_hash_table(key)?, path, mismatches)?;
+                path.pop();
+            }
+            Expected::Container(children) => {
+                let found: BTreeSet<String> = data
+                    .chunks_exact(size_of::<u32>())
+                    .map(|chunk| {
+                        let index = u32::from_le_bytes(chunk.try_into().unwrap()) as usize;
+                        keys.get(index)
+                            .cloned()
+                            .unwrap_or_else(|| index.to_string())
+                    })
+                    .collect();
+
+                if &found != children {
+                    mismatches.push(VerifyMismatch::Children {
+                        path: item_path(path, key),
+                        expected: children.iter().cloned().collect(),
+                        found: found.into_iter().collect(),
+                    });
+                }
+            }
+            Expected::Unchecked(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::Endian;
+    use matches::assert_matches;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+
+    fn table_builder() -> HashTableBuilder<'static> {
+        let mut nested = HashTableBuilder::new();
+        nested.insert("int", 42u32).unwrap();
+        nested.insert_bytes("bytes", b"\0\0\0\0\0u").unwrap();
+
+        let mut table_builder = HashTableBuilder::new();
+        table_builder
+            .insert_string("string", "test string")
+            .unwrap();
+        table_builder.insert("dir/a", 1u64).unwrap();
+        table_builder.insert("dir/b", (1u16, "b")).unwrap();
+        table_builder.insert_table("table", nested).unwrap();
+        table_builder
+    }
+
+    #[test]
+    fn write_and_verify() {
+        for endian in [Endian::Little, Endian::Big] {
+            let data = FileWriter::endian(endian)
+                .write_and_verify(table_builder())
+                .unwrap();
+            let expected = FileWriter::endian(endian)
+                .write_to_vec_with_table(table_builder())
+                .unwrap();
+            assert_eq!(data, expected);
+
+            let file = File::from_bytes(Cow::Owned(data)).unwrap();
+            assert_eq!(file.hash_table().unwrap().get::<u64>("dir/a").unwrap(), 1);
+        }
+
+        let file = File::from_file(&TEST_FILE_3).unwrap();
+        let table_builder = HashTableBuilder::from_hash_table(&file.hash_table().unwrap()).unwrap();
+        FileWriter::new()
+            .with_glib_compat_layout(true)
+            .write_and_verify(table_builder)
+            .unwrap();
+    }
+
+    #[test]
+    fn verify_mismatches() {
+        let file = File::from_bytes(Cow::Owned(
+            FileWriter::new()
+                .write_to_vec_with_table(table_builder())
+                .unwrap(),
+        ))
+        .unwrap();
+
+        let mut table_builder = table_builder();
+        table_builder.insert("missing", 0u8).unwrap();
+        table_builder.remove("string");
+        table_builder.insert_string("dir/a", "a").unwrap();
+        table_builder
+            .table_mut("table")
+            .unwrap()
+            .insert_table("int", HashTableBuilder::new())
+            .unwrap();
+
+        let expected = FileWriter::new().expected_items(&table_builder).unwrap();
+        let mut mismatches = Vec::new();
+        verify_table(
+            &expected,
+            &file.hash_table().unwrap(),
+            &mut Vec::new(),
+            &mut mismatches,
+        )
+        .unwrap();
+
+        assert_eq!(
+            mismatches,
+            [
+                VerifyMismatch::Unexpected {
+                    path: vec!["string".to_string()]
+                },
+                VerifyMismatch::Value {
+                    path: vec!["dir/a".to_string()]
+                },
+                VerifyMismatch::Missing {
+                    path: vec!["missing".to_string()]
+                },
+                VerifyMismatch::Type {
+                    path: vec!["table".to_string(), "int".to_string()],
+                    expected: HashItemType::HashTable,
+                    found: HashItemType::Value,
+                },
+            ]
+        );
+        assert_eq!(mismatches[3].path(), ["table", "int"]);
+        assert_eq!(
+            mismatches[3].to_string(),
+            "Item [\"table\", \"int\"] has type Value, expected HashTable"
+        );
+
+        let err = Error::Verification(mismatches);
+        assert_matches!(&err, Error::Verification(mismatches) if mismatches.len() == 4);
+        assert!(err.to_string().contains("4 mismatches"));
+    }
+}